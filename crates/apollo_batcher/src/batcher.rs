@@ -7,9 +7,11 @@ use apollo_batcher_types::batcher_types::{
     DecisionReachedInput,
     DecisionReachedResponse,
     GetHeightResponse,
+    GetPreconfirmedBlockResponse,
     GetProposalContent,
     GetProposalContentInput,
     GetProposalContentResponse,
+    PreconfirmedBlock,
     ProposalCommitment,
     ProposalId,
     ProposalStatus,
@@ -53,6 +55,7 @@ use crate::block_builder::{
     BlockBuilderTrait,
     BlockExecutionArtifacts,
     BlockMetadata,
+    SharedPreconfirmedBlock,
 };
 use crate::config::BatcherConfig;
 use crate::metrics::{
@@ -112,6 +115,11 @@ pub struct Batcher {
     // Each stream is kept until SendProposalContent::Finish/Abort is received, or a new height is
     // started.
     validate_tx_streams: HashMap<ProposalId, InputStreamSender>,
+
+    // The last block proposed in the current height, updated by its block builder as transactions
+    // are executed. Cleared once it can no longer be part of the chain: when a block is validated,
+    // when a different proposal is decided on, or when a new height is started.
+    preconfirmed_block: Option<(ProposalId, SharedPreconfirmedBlock)>,
 }
 
 impl Batcher {
@@ -138,6 +146,7 @@ impl Batcher {
             executed_proposals: Arc::new(Mutex::new(HashMap::new())),
             propose_tx_streams: HashMap::new(),
             validate_tx_streams: HashMap::new(),
+            preconfirmed_block: None,
         }
     }
 
@@ -214,6 +223,11 @@ impl Batcher {
 
         // A channel to receive the transactions included in the proposed block.
         let (output_tx_sender, output_tx_receiver) = tokio::sync::mpsc::unbounded_channel();
+        let preconfirmed_block = Arc::new(std::sync::Mutex::new(PreconfirmedBlock {
+            block_info: propose_block_input.block_info.clone(),
+            transactions: Vec::new(),
+            state_diff: ThinStateDiff::default(),
+        }));
 
         let (block_builder, abort_signal_sender) = self
             .block_builder_factory
@@ -228,6 +242,7 @@ impl Batcher {
                 },
                 Box::new(tx_provider),
                 Some(output_tx_sender),
+                Some(preconfirmed_block.clone()),
                 tokio::runtime::Handle::current(),
             )
             .map_err(|err| {
@@ -244,6 +259,7 @@ impl Batcher {
         .await?;
 
         self.propose_tx_streams.insert(propose_block_input.proposal_id, output_tx_receiver);
+        self.preconfirmed_block = Some((propose_block_input.proposal_id, preconfirmed_block));
         LAST_PROPOSED_BLOCK.set_lossy(block_number.0);
         Ok(())
     }
@@ -295,6 +311,7 @@ impl Batcher {
                 },
                 Box::new(tx_provider),
                 None,
+                None,
                 tokio::runtime::Handle::current(),
             )
             .map_err(|err| {
@@ -311,6 +328,7 @@ impl Batcher {
         .await?;
 
         self.validate_tx_streams.insert(validate_block_input.proposal_id, input_tx_sender);
+        self.preconfirmed_block = None;
         Ok(())
     }
 
@@ -339,6 +357,7 @@ impl Batcher {
         self.executed_proposals.lock().await.clear();
         self.propose_tx_streams.clear();
         self.validate_tx_streams.clear();
+        self.preconfirmed_block = None;
         self.active_height = None;
     }
 
@@ -419,6 +438,20 @@ impl Batcher {
         Ok(GetHeightResponse { height })
     }
 
+    #[instrument(skip(self), err)]
+    pub async fn get_preconfirmed_block(&self) -> BatcherResult<GetPreconfirmedBlockResponse> {
+        let Some((proposal_id, preconfirmed_block)) = &self.preconfirmed_block else {
+            return Ok(GetPreconfirmedBlockResponse { preconfirmed_block: None });
+        };
+        // A proposal that failed will not be part of the chain.
+        if let Some(Err(_)) = self.executed_proposals.lock().await.get(proposal_id) {
+            return Ok(GetPreconfirmedBlockResponse { preconfirmed_block: None });
+        }
+        let preconfirmed_block =
+            preconfirmed_block.lock().expect("Preconfirmed block lock failed.").clone();
+        Ok(GetPreconfirmedBlockResponse { preconfirmed_block: Some(preconfirmed_block) })
+    }
+
     #[instrument(skip(self), err)]
     pub async fn get_proposal_content(
         &mut self,
@@ -505,6 +538,9 @@ impl Batcher {
         let height = self.active_height.ok_or(BatcherError::NoActiveHeight)?;
 
         let proposal_id = input.proposal_id;
+        if self.preconfirmed_block.as_ref().is_some_and(|(id, _)| *id != proposal_id) {
+            self.preconfirmed_block = None;
+        }
         let proposal_result = self.executed_proposals.lock().await.remove(&proposal_id);
         let block_execution_artifacts = proposal_result
             .ok_or(BatcherError::ExecutedProposalNotFound { proposal_id })?
@@ -762,17 +798,17 @@ pub fn create_batcher(
     l1_provider_client: SharedL1ProviderClient,
     class_manager_client: SharedClassManagerClient,
 ) -> Batcher {
+    apollo_storage::migrations::migrate_storage(config.storage.clone())
+        .expect("Failed to migrate batcher's storage");
     let (storage_reader, storage_writer) = apollo_storage::open_storage(config.storage.clone())
         .expect("Failed to open batcher's storage");
 
-    let block_builder_factory = Box::new(BlockBuilderFactory {
-        block_builder_config: config.block_builder_config.clone(),
-        storage_reader: storage_reader.clone(),
-        contract_class_manager: ContractClassManager::start(
-            config.contract_class_manager_config.clone(),
-        ),
-        class_manager_client: class_manager_client.clone(),
-    });
+    let block_builder_factory = Box::new(BlockBuilderFactory::new(
+        config.block_builder_config.clone(),
+        storage_reader.clone(),
+        ContractClassManager::start(config.contract_class_manager_config.clone()),
+        class_manager_client.clone(),
+    ));
     let storage_reader = Arc::new(storage_reader);
     let storage_writer = Box::new(storage_writer);
     let transaction_converter =
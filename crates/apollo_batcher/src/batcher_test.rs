@@ -181,7 +181,7 @@ fn mock_create_builder_for_validate_block(
     build_block_result: BlockBuilderResult<BlockExecutionArtifacts>,
 ) {
     block_builder_factory.expect_create_block_builder().times(1).return_once(
-        |_, _, tx_provider, _, _| {
+        |_, _, tx_provider, _, _, _| {
             let block_builder = FakeValidateBlockBuilder {
                 tx_provider,
                 build_block_result: Some(build_block_result),
@@ -197,9 +197,10 @@ fn mock_create_builder_for_propose_block(
     build_block_result: BlockBuilderResult<BlockExecutionArtifacts>,
 ) {
     block_builder_factory.expect_create_block_builder().times(1).return_once(
-        move |_, _, _, output_content_sender, _| {
+        move |_, _, _, output_content_sender, preconfirmed_block, _| {
             let block_builder = FakeProposeBlockBuilder {
                 output_content_sender: output_content_sender.unwrap(),
+                preconfirmed_block: preconfirmed_block.unwrap(),
                 output_txs,
                 build_block_result: Some(build_block_result),
             };
@@ -659,6 +660,50 @@ async fn get_height() {
     assert_eq!(result, GetHeightResponse { height: INITIAL_HEIGHT });
 }
 
+#[rstest]
+#[tokio::test]
+async fn get_preconfirmed_block() {
+    let proposed_txs = test_txs(0..2);
+    let mut block_builder_factory = MockBlockBuilderFactoryTrait::new();
+    mock_create_builder_for_propose_block(
+        &mut block_builder_factory,
+        proposed_txs.clone(),
+        Ok(BlockExecutionArtifacts::create_for_testing()),
+    );
+    let mut l1_provider_client = MockL1ProviderClient::new();
+    l1_provider_client.expect_start_block().times(1).returning(|_, _| Ok(()));
+    let mut batcher = create_batcher(MockDependencies {
+        block_builder_factory,
+        l1_provider_client,
+        ..Default::default()
+    })
+    .await;
+
+    // No block is proposed yet.
+    assert_eq!(batcher.get_preconfirmed_block().await.unwrap().preconfirmed_block, None);
+
+    batcher.start_height(StartHeightInput { height: INITIAL_HEIGHT }).await.unwrap();
+    batcher.propose_block(propose_block_input(PROPOSAL_ID)).await.unwrap();
+    batcher.await_active_proposal().await;
+
+    let preconfirmed_block =
+        batcher.get_preconfirmed_block().await.unwrap().preconfirmed_block.unwrap();
+    assert_eq!(preconfirmed_block.block_info, propose_block_input(PROPOSAL_ID).block_info);
+    let preconfirmed_txs: Vec<_> =
+        preconfirmed_block.transactions.into_iter().map(|tx| tx.transaction).collect();
+    assert_eq!(preconfirmed_txs, proposed_txs);
+
+    // A decision on another proposal means the proposed block will not be part of the chain.
+    let other_proposal_id = ProposalId(PROPOSAL_ID.0 + 1);
+    let decision_reached_result =
+        batcher.decision_reached(DecisionReachedInput { proposal_id: other_proposal_id }).await;
+    assert_eq!(
+        decision_reached_result,
+        Err(BatcherError::ExecutedProposalNotFound { proposal_id: other_proposal_id })
+    );
+    assert_eq!(batcher.get_preconfirmed_block().await.unwrap().preconfirmed_block, None);
+}
+
 #[rstest]
 #[tokio::test]
 async fn propose_block_without_retrospective_block_hash() {
@@ -1093,11 +1138,9 @@ fn validate_batcher_config_failure() {
     };
 
     let error = config.validate().unwrap_err();
-    assert!(
-        error
-            .to_string()
-            .contains("input_stream_content_buffer_size must be at least tx_chunk_size")
-    );
+    assert!(error
+        .to_string()
+        .contains("input_stream_content_buffer_size must be at least tx_chunk_size"));
 }
 
 #[rstest]
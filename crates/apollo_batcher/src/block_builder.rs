@@ -1,7 +1,11 @@
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
-use apollo_batcher_types::batcher_types::ProposalCommitment;
+use apollo_batcher_types::batcher_types::{
+    PreconfirmedBlock,
+    PreconfirmedTransaction,
+    ProposalCommitment,
+};
 use apollo_class_manager_types::transaction_converter::{
     TransactionConverter,
     TransactionConverterError,
@@ -18,14 +22,16 @@ use async_trait::async_trait;
 use blockifier::blockifier::config::TransactionExecutorConfig;
 use blockifier::blockifier::transaction_executor::{
     BlockExecutionSummary,
+    TransactionExecutionOutput,
     TransactionExecutor,
     TransactionExecutorError as BlockifierTransactionExecutorError,
     TransactionExecutorResult,
 };
 use blockifier::blockifier_versioned_constants::{VersionedConstants, VersionedConstantsOverrides};
 use blockifier::bouncer::{BouncerConfig, BouncerWeights, CasmHashComputationData};
+use blockifier::concurrency::conflict_prediction::{apply_order, AccessSetPredictor};
 use blockifier::context::{BlockContext, ChainInfo};
-use blockifier::state::cached_state::CommitmentStateDiff;
+use blockifier::state::cached_state::{CommitmentStateDiff, StateMaps};
 use blockifier::state::contract_class_manager::ContractClassManager;
 use blockifier::state::errors::StateError;
 use blockifier::state::state_reader_and_contract_manager::StateReaderAndContractManager;
@@ -41,13 +47,24 @@ use starknet_api::consensus_transaction::InternalConsensusTransaction;
 use starknet_api::core::{ContractAddress, Nonce};
 use starknet_api::execution_resources::GasAmount;
 use starknet_api::state::ThinStateDiff;
-use starknet_api::transaction::TransactionHash;
+use starknet_api::transaction::{
+    Event,
+    MessageToL1,
+    RevertedTransactionExecutionStatus,
+    TransactionExecutionStatus,
+    TransactionHash,
+};
 use thiserror::Error;
 use tokio::sync::Mutex;
 use tracing::{debug, error, info, trace};
 
 use crate::block_builder::FailOnErrorCause::L1HandlerTransactionValidationFailed;
-use crate::metrics::FULL_BLOCKS;
+use crate::metrics::{
+    record_capacity_utilization,
+    record_concurrency_stats,
+    record_transaction_too_large,
+    FULL_BLOCKS,
+};
 use crate::transaction_executor::TransactionExecutorTrait;
 use crate::transaction_provider::{NextTxs, TransactionProvider, TransactionProviderError};
 
@@ -73,6 +90,14 @@ pub enum BlockBuilderError {
 
 pub type BlockBuilderResult<T> = Result<T, BlockBuilderError>;
 
+// The number of most conflicting storage keys of a block to log in concurrency mode.
+const N_CONFLICTING_STORAGE_KEYS_TO_LOG: usize = 5;
+// The number of senders whose latest access sets are kept for conflict-aware reordering.
+const ACCESS_SET_PREDICTOR_MAX_N_SENDERS: usize = 100_000;
+
+pub type SharedAccessSetPredictor = Arc<std::sync::Mutex<AccessSetPredictor>>;
+pub type SharedPreconfirmedBlock = Arc<std::sync::Mutex<PreconfirmedBlock>>;
+
 #[derive(Debug, Error)]
 pub enum FailOnErrorCause {
     #[error("Block is full")]
@@ -114,15 +139,7 @@ impl BlockExecutionArtifacts {
 
     pub fn thin_state_diff(&self) -> ThinStateDiff {
         // TODO(Ayelet): Remove the clones.
-        let commitment_state_diff = self.commitment_state_diff.clone();
-        ThinStateDiff {
-            deployed_contracts: commitment_state_diff.address_to_class_hash,
-            storage_diffs: commitment_state_diff.storage_updates,
-            declared_classes: commitment_state_diff.class_hash_to_compiled_class_hash,
-            nonces: commitment_state_diff.address_to_nonce,
-            // TODO(AlonH): Remove this when the structure of storage diffs changes.
-            deprecated_declared_classes: Vec::new(),
-        }
+        thin_state_diff(self.commitment_state_diff.clone())
     }
 
     pub fn commitment(&self) -> ProposalCommitment {
@@ -132,9 +149,21 @@ impl BlockExecutionArtifacts {
     }
 }
 
+fn thin_state_diff(commitment_state_diff: CommitmentStateDiff) -> ThinStateDiff {
+    ThinStateDiff {
+        deployed_contracts: commitment_state_diff.address_to_class_hash,
+        storage_diffs: commitment_state_diff.storage_updates,
+        declared_classes: commitment_state_diff.class_hash_to_compiled_class_hash,
+        nonces: commitment_state_diff.address_to_nonce,
+        // TODO(AlonH): Remove this when the structure of storage diffs changes.
+        deprecated_declared_classes: Vec::new(),
+    }
+}
+
 /// The BlockBuilderTrait is responsible for building a new block from transactions provided by the
 /// tx_provider. The block building will stop at time deadline.
-/// The transactions that were added to the block will be streamed to the output_content_sender.
+/// The transactions that were added to the block will be streamed to the output_content_sender,
+/// and added to the preconfirmed block, if given.
 #[cfg_attr(test, automock)]
 #[async_trait]
 pub trait BlockBuilderTrait: Send {
@@ -153,6 +182,14 @@ pub struct BlockBuilder {
     output_content_sender: Option<tokio::sync::mpsc::UnboundedSender<InternalConsensusTransaction>>,
     abort_signal_receiver: tokio::sync::oneshot::Receiver<()>,
     transaction_converter: TransactionConverter,
+    // Used to reorder each chunk of transactions to reduce conflicts in concurrent execution;
+    // `None` if conflict-aware reordering is disabled or when validating a block.
+    access_set_predictor: Option<SharedAccessSetPredictor>,
+    // Exposes the transactions executed so far to the readers of the pending state; `None` when
+    // validating a block.
+    preconfirmed_block: Option<SharedPreconfirmedBlock>,
+    // The accumulated state diff of the transactions in the preconfirmed block.
+    preconfirmed_state_maps: StateMaps,
 
     // Parameters to configure the block builder behavior.
     tx_chunk_size: usize,
@@ -170,17 +207,25 @@ impl BlockBuilder {
         >,
         abort_signal_receiver: tokio::sync::oneshot::Receiver<()>,
         transaction_converter: TransactionConverter,
+        access_set_predictor: Option<SharedAccessSetPredictor>,
+        preconfirmed_block: Option<SharedPreconfirmedBlock>,
         tx_chunk_size: usize,
         tx_polling_interval_millis: u64,
         execution_params: BlockBuilderExecutionParams,
     ) -> Self {
         let executor = Arc::new(Mutex::new(executor));
+        // A validator must execute the transactions in the proposed order to reproduce the
+        // proposer's state diff, so only a proposer reorders them.
+        let access_set_predictor = access_set_predictor.filter(|_| output_content_sender.is_some());
         Self {
             executor,
             tx_provider,
             output_content_sender,
             abort_signal_receiver,
             transaction_converter,
+            access_set_predictor,
+            preconfirmed_block,
+            preconfirmed_state_maps: StateMaps::default(),
             tx_chunk_size,
             tx_polling_interval_millis,
             execution_params,
@@ -221,7 +266,7 @@ impl BlockBuilderTrait for BlockBuilder {
                 }
                 Ok(result) => result,
             };
-            let next_tx_chunk = match next_txs {
+            let mut next_tx_chunk = match next_txs {
                 NextTxs::Txs(txs) => txs,
                 NextTxs::End => break,
             };
@@ -237,7 +282,22 @@ impl BlockBuilderTrait for BlockBuilder {
             let tx_convert_futures = next_tx_chunk.iter().map(|tx| async {
                 convert_to_executable_blockifier_tx(&self.transaction_converter, tx.clone()).await
             });
-            let executor_input_chunk = futures::future::try_join_all(tx_convert_futures).await?;
+            let mut executor_input_chunk =
+                futures::future::try_join_all(tx_convert_futures).await?;
+            let sender_addresses: Vec<Option<ContractAddress>> =
+                executor_input_chunk.iter().map(account_sender_address).collect();
+            let sender_addresses = match &self.access_set_predictor {
+                Some(access_set_predictor) => {
+                    let order = access_set_predictor
+                        .lock()
+                        .expect("Access set predictor lock failed.")
+                        .conflict_aware_order(&sender_addresses);
+                    next_tx_chunk = apply_order(next_tx_chunk, &order);
+                    executor_input_chunk = apply_order(executor_input_chunk, &order);
+                    apply_order(sender_addresses, &order)
+                }
+                None => sender_addresses,
+            };
 
             // Execute the transactions on a separate thread pool to avoid blocking the executor
             // while waiting on `block_on` calls.
@@ -251,6 +311,28 @@ impl BlockBuilderTrait for BlockBuilder {
             .await
             .expect("Failed to spawn blocking executor task.");
             trace!("Transaction execution results: {:?}", results);
+            if let Some(preconfirmed_block) = &self.preconfirmed_block {
+                add_to_preconfirmed_block(
+                    preconfirmed_block,
+                    &mut self.preconfirmed_state_maps,
+                    &next_tx_chunk,
+                    &results,
+                );
+            }
+            let results: Vec<_> = results
+                .into_iter()
+                .map(|result| result.map(|(tx_execution_info, _state_diff)| tx_execution_info))
+                .collect();
+            if let Some(access_set_predictor) = &self.access_set_predictor {
+                let mut access_set_predictor =
+                    access_set_predictor.lock().expect("Access set predictor lock failed.");
+                for (sender_address, result) in sender_addresses.iter().zip(results.iter()) {
+                    if let (Some(sender_address), Ok(tx_execution_info)) = (sender_address, result)
+                    {
+                        access_set_predictor.record(*sender_address, tx_execution_info);
+                    }
+                }
+            }
             block_is_full = collect_execution_results_and_stream_txs(
                 next_tx_chunk,
                 results,
@@ -265,8 +347,27 @@ impl BlockBuilderTrait for BlockBuilder {
             state_diff,
             compressed_state_diff,
             bouncer_weights,
+            capacity_utilization,
             casm_hash_computation_data,
+            concurrency_stats,
         } = self.executor.lock().await.close_block()?;
+        debug!(
+            "Block capacity utilization: {:?}. Dimensions that closed the block: {:?}.",
+            capacity_utilization.utilization, capacity_utilization.closing_dimensions,
+        );
+        record_capacity_utilization(&capacity_utilization);
+        if let Some(concurrency_stats) = concurrency_stats {
+            debug!(
+                "Concurrency stats: {} chunks, {} aborts, {} aborts in commit, {} re-executions. \
+                 Most conflicting storage keys: {:?}.",
+                concurrency_stats.n_chunks,
+                concurrency_stats.n_aborts,
+                concurrency_stats.n_aborts_in_commit,
+                concurrency_stats.n_reexecutions(),
+                concurrency_stats.most_conflicting_storage_keys(N_CONFLICTING_STORAGE_KEYS_TO_LOG),
+            );
+            record_concurrency_stats(&concurrency_stats);
+        }
         Ok(BlockExecutionArtifacts {
             execution_data,
             commitment_state_diff: state_diff,
@@ -278,6 +379,90 @@ impl BlockBuilderTrait for BlockBuilder {
     }
 }
 
+/// Adds the successfully executed transactions of a chunk to the preconfirmed block, and updates
+/// its state diff accordingly.
+fn add_to_preconfirmed_block(
+    preconfirmed_block: &SharedPreconfirmedBlock,
+    preconfirmed_state_maps: &mut StateMaps,
+    tx_chunk: &[InternalConsensusTransaction],
+    results: &[TransactionExecutorResult<TransactionExecutionOutput>],
+) {
+    let mut preconfirmed_transactions = Vec::new();
+    for (tx, result) in tx_chunk.iter().zip(results.iter()) {
+        if let Ok((tx_execution_info, state_diff)) = result {
+            preconfirmed_transactions.push(preconfirmed_transaction(tx.clone(), tx_execution_info));
+            preconfirmed_state_maps.extend(state_diff);
+        }
+    }
+    if preconfirmed_transactions.is_empty() {
+        return;
+    }
+    let state_diff = thin_state_diff(preconfirmed_state_maps.clone().into());
+    let mut preconfirmed_block =
+        preconfirmed_block.lock().expect("Preconfirmed block lock failed.");
+    preconfirmed_block.transactions.extend(preconfirmed_transactions);
+    preconfirmed_block.state_diff = state_diff;
+}
+
+fn preconfirmed_transaction(
+    transaction: InternalConsensusTransaction,
+    tx_execution_info: &TransactionExecutionInfo,
+) -> PreconfirmedTransaction {
+    let execution_status = match &tx_execution_info.revert_error {
+        Some(revert_error) => {
+            TransactionExecutionStatus::Reverted(RevertedTransactionExecutionStatus {
+                revert_reason: revert_error.to_string(),
+            })
+        }
+        None => TransactionExecutionStatus::Succeeded,
+    };
+    let mut events = Vec::new();
+    let mut messages_sent = Vec::new();
+    // The order of the events and the messages is kept per top-level call (validate, execute and
+    // fee transfer).
+    for call_info in tx_execution_info.non_optional_call_infos() {
+        let mut ordered_events = Vec::new();
+        let mut ordered_messages = Vec::new();
+        for inner_call_info in call_info.iter() {
+            let from_address = inner_call_info.call.storage_address;
+            ordered_events.extend(inner_call_info.execution.events.iter().map(|ordered_event| {
+                (ordered_event.order, Event { from_address, content: ordered_event.event.clone() })
+            }));
+            ordered_messages.extend(inner_call_info.execution.l2_to_l1_messages.iter().map(
+                |ordered_message| {
+                    (
+                        ordered_message.order,
+                        MessageToL1 {
+                            from_address,
+                            to_address: ordered_message.message.to_address,
+                            payload: ordered_message.message.payload.clone(),
+                        },
+                    )
+                },
+            ));
+        }
+        ordered_events.sort_by_key(|(order, _)| *order);
+        ordered_messages.sort_by_key(|(order, _)| *order);
+        events.extend(ordered_events.into_iter().map(|(_, event)| event));
+        messages_sent.extend(ordered_messages.into_iter().map(|(_, message)| message));
+    }
+    PreconfirmedTransaction {
+        transaction,
+        execution_status,
+        actual_fee: tx_execution_info.receipt.fee,
+        events,
+        messages_sent,
+    }
+}
+
+/// Returns the sender of an account transaction, or `None` for an L1 handler transaction.
+fn account_sender_address(tx: &BlockifierTransaction) -> Option<ContractAddress> {
+    match tx {
+        BlockifierTransaction::Account(account_tx) => Some(account_tx.sender_address()),
+        BlockifierTransaction::L1Handler(_) => None,
+    }
+}
+
 async fn convert_to_executable_blockifier_tx(
     transaction_converter: &TransactionConverter,
     tx: InternalConsensusTransaction,
@@ -343,6 +528,7 @@ async fn collect_execution_results_and_stream_txs(
                     tx_hash,
                     err.log_compatible_to_string()
                 );
+                record_transaction_too_large(&err);
                 if fail_on_err {
                     return Err(BlockBuilderError::FailOnError(
                         FailOnErrorCause::TransactionFailed(err),
@@ -376,6 +562,7 @@ pub trait BlockBuilderFactoryTrait: Send + Sync {
         output_content_sender: Option<
             tokio::sync::mpsc::UnboundedSender<InternalConsensusTransaction>,
         >,
+        preconfirmed_block: Option<SharedPreconfirmedBlock>,
         runtime: tokio::runtime::Handle,
     ) -> BlockBuilderResult<(Box<dyn BlockBuilderTrait>, AbortSignalSender)>;
 }
@@ -387,6 +574,7 @@ pub struct BlockBuilderConfig {
     pub bouncer_config: BouncerConfig,
     pub tx_chunk_size: usize,
     pub tx_polling_interval_millis: u64,
+    pub conflict_aware_reordering: bool,
     pub versioned_constants_overrides: VersionedConstantsOverrides,
 }
 
@@ -399,6 +587,7 @@ impl Default for BlockBuilderConfig {
             bouncer_config: BouncerConfig::default(),
             tx_chunk_size: 100,
             tx_polling_interval_millis: 100,
+            conflict_aware_reordering: false,
             versioned_constants_overrides: VersionedConstantsOverrides::default(),
         }
     }
@@ -422,6 +611,14 @@ impl SerializeConfig for BlockBuilderConfig {
              request returned no transactions.",
             ParamPrivacyInput::Public,
         )]));
+        dump.append(&mut BTreeMap::from([ser_param(
+            "conflict_aware_reordering",
+            &self.conflict_aware_reordering,
+            "Reorders each chunk of transactions to spread out transactions predicted (by the \
+             storage accessed by previous transactions of their senders) to conflict, reducing \
+             aborts in concurrent execution. Per-account nonce order is preserved.",
+            ParamPrivacyInput::Public,
+        )]));
         dump.append(&mut prepend_sub_config_name(
             self.versioned_constants_overrides.dump(),
             "versioned_constants_overrides",
@@ -435,9 +632,28 @@ pub struct BlockBuilderFactory {
     pub storage_reader: StorageReader,
     pub contract_class_manager: ContractClassManager,
     pub class_manager_client: SharedClassManagerClient,
+    // Shared by all the block builders, to predict conflicts based on the history of all blocks.
+    pub access_set_predictor: SharedAccessSetPredictor,
 }
 
 impl BlockBuilderFactory {
+    pub fn new(
+        block_builder_config: BlockBuilderConfig,
+        storage_reader: StorageReader,
+        contract_class_manager: ContractClassManager,
+        class_manager_client: SharedClassManagerClient,
+    ) -> Self {
+        Self {
+            block_builder_config,
+            storage_reader,
+            contract_class_manager,
+            class_manager_client,
+            access_set_predictor: Arc::new(std::sync::Mutex::new(AccessSetPredictor::new(
+                ACCESS_SET_PREDICTOR_MAX_N_SENDERS,
+            ))),
+        }
+    }
+
     // TODO(noamsp): Investigate and remove this clippy warning.
     #[allow(clippy::result_large_err)]
     fn preprocess_and_create_transaction_executor(
@@ -485,6 +701,7 @@ impl BlockBuilderFactoryTrait for BlockBuilderFactory {
         output_content_sender: Option<
             tokio::sync::mpsc::UnboundedSender<InternalConsensusTransaction>,
         >,
+        preconfirmed_block: Option<SharedPreconfirmedBlock>,
         runtime: tokio::runtime::Handle,
     ) -> BlockBuilderResult<(Box<dyn BlockBuilderTrait>, AbortSignalSender)> {
         let executor = self.preprocess_and_create_transaction_executor(block_metadata, runtime)?;
@@ -499,6 +716,10 @@ impl BlockBuilderFactoryTrait for BlockBuilderFactory {
             output_content_sender,
             abort_signal_receiver,
             transaction_converter,
+            self.block_builder_config
+                .conflict_aware_reordering
+                .then(|| self.access_set_predictor.clone()),
+            preconfirmed_block,
             self.block_builder_config.tx_chunk_size,
             self.block_builder_config.tx_polling_interval_millis,
             execution_params,
@@ -1,6 +1,7 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 
+use apollo_batcher_types::batcher_types::PreconfirmedBlock;
 use apollo_class_manager_types::transaction_converter::TransactionConverter;
 use apollo_class_manager_types::MockClassManagerClient;
 use apollo_l1_provider_types::InvalidValidationStatus;
@@ -12,12 +13,23 @@ use apollo_l1_provider_types::InvalidValidationStatus::{
 use assert_matches::assert_matches;
 use blockifier::blockifier::transaction_executor::{
     BlockExecutionSummary,
+    TransactionExecutionOutput,
     TransactionExecutorError,
 };
-use blockifier::bouncer::{BouncerWeights, CasmHashComputationData};
+use blockifier::bouncer::{
+    BlockCapacityUtilization,
+    BouncerDimension,
+    BouncerWeights,
+    CasmHashComputationData,
+};
+use blockifier::concurrency::conflict_prediction::AccessSetPredictor;
+use blockifier::execution::call_info::{CallInfo, StorageAccessTracker};
+use blockifier::execution::entry_point::CallEntryPoint;
 use blockifier::fee::fee_checks::FeeCheckError;
 use blockifier::fee::receipt::TransactionReceipt;
+use blockifier::state::cached_state::StateMaps;
 use blockifier::state::errors::StateError;
+use blockifier::transaction::errors::TransactionExecutionError;
 use blockifier::transaction::objects::{RevertError, TransactionExecutionInfo};
 use blockifier::transaction::transaction_execution::Transaction as BlockifierTransaction;
 use indexmap::{IndexMap, IndexSet};
@@ -26,12 +38,14 @@ use mockall::predicate::eq;
 use mockall::Sequence;
 use pretty_assertions::assert_eq;
 use rstest::rstest;
+use starknet_api::block::BlockInfo;
 use starknet_api::consensus_transaction::InternalConsensusTransaction;
 use starknet_api::execution_resources::{GasAmount, GasVector};
+use starknet_api::test_utils::invoke::{internal_invoke_tx, InvokeTxArgs};
 use starknet_api::test_utils::CHAIN_ID_FOR_TESTS;
 use starknet_api::transaction::fields::Fee;
-use starknet_api::transaction::TransactionHash;
-use starknet_api::tx_hash;
+use starknet_api::transaction::{TransactionExecutionStatus, TransactionHash};
+use starknet_api::{contract_address, nonce, storage_key, tx_hash};
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
 
 use crate::block_builder::{
@@ -44,7 +58,7 @@ use crate::block_builder::{
     BlockTransactionExecutionData,
     FailOnErrorCause,
 };
-use crate::metrics::FULL_BLOCKS;
+use crate::metrics::{FULL_BLOCKS, LABEL_NAME_BOUNCER_DIMENSION, TRANSACTIONS_TOO_LARGE};
 use crate::test_utils::{test_l1_handler_txs, test_txs};
 use crate::transaction_executor::MockTransactionExecutorTrait;
 use crate::transaction_provider::TransactionProviderError::L1HandlerTransactionValidationFailed;
@@ -64,8 +78,8 @@ struct TestExpectations {
     expected_full_blocks_metric: u64,
 }
 
-fn output_channel()
--> (UnboundedSender<InternalConsensusTransaction>, UnboundedReceiver<InternalConsensusTransaction>)
+fn output_channel(
+) -> (UnboundedSender<InternalConsensusTransaction>, UnboundedReceiver<InternalConsensusTransaction>)
 {
     tokio::sync::mpsc::unbounded_channel()
 }
@@ -106,6 +120,10 @@ fn execution_info() -> TransactionExecutionInfo {
     }
 }
 
+fn execution_output() -> TransactionExecutionOutput {
+    (execution_info(), StateMaps::default())
+}
+
 fn one_chunk_test_expectations() -> TestExpectations {
     let input_txs = test_txs(0..3);
     let block_size = input_txs.len();
@@ -134,7 +152,7 @@ fn one_chunk_mock_executor(
         .expect_add_txs_to_block()
         .times(1)
         .withf(move |blockifier_input| compare_tx_hashes(&input_txs_cloned, blockifier_input))
-        .return_once(move |_| (0..block_size).map(|_| Ok(execution_info())).collect());
+        .return_once(move |_| (0..block_size).map(|_| Ok(execution_output())).collect());
 
     let expected_block_artifacts =
         set_close_block_expectations(&mut mock_transaction_executor, block_size);
@@ -155,7 +173,9 @@ fn two_chunks_test_expectations() -> TestExpectations {
             .times(1)
             .in_sequence(seq)
             .withf(move |blockifier_input| compare_tx_hashes(&tx_chunk, blockifier_input))
-            .return_once(move |_| (0..TX_CHUNK_SIZE).map(move |_| Ok(execution_info())).collect());
+            .return_once(move |_| {
+                (0..TX_CHUNK_SIZE).map(move |_| Ok(execution_output())).collect()
+            });
     };
 
     let mut seq = Sequence::new();
@@ -198,7 +218,7 @@ fn mock_transaction_executor_block_full(
 ) -> MockTransactionExecutorTrait {
     let input_txs_cloned = input_txs.to_vec();
     let mut mock_transaction_executor = MockTransactionExecutorTrait::new();
-    let execution_results = vec![Ok(execution_info())];
+    let execution_results = vec![Ok(execution_output())];
     // When the block is full, the executor will return less results than the number of input txs.
     assert!(input_txs.len() > execution_results.len());
     mock_transaction_executor
@@ -237,7 +257,7 @@ fn mock_transaction_executor_with_delay(
         .withf(move |blockifier_input| compare_tx_hashes(&input_txs_cloned, blockifier_input))
         .return_once(move |_| {
             std::thread::sleep(std::time::Duration::from_secs(BLOCK_GENERATION_DEADLINE_SECS));
-            (0..TX_CHUNK_SIZE).map(move |_| Ok(execution_info())).collect()
+            (0..TX_CHUNK_SIZE).map(move |_| Ok(execution_output())).collect()
         });
     mock_transaction_executor
 }
@@ -271,7 +291,7 @@ fn stream_done_test_expectations() -> TestExpectations {
         .expect_add_txs_to_block()
         .times(1)
         .withf(move |blockifier_input| compare_tx_hashes(&input_txs_cloned, blockifier_input))
-        .return_once(move |_| (0..block_size).map(|_| Ok(execution_info())).collect());
+        .return_once(move |_| (0..block_size).map(|_| Ok(execution_output())).collect());
 
     let expected_block_artifacts =
         set_close_block_expectations(&mut mock_transaction_executor, block_size);
@@ -306,7 +326,7 @@ fn transaction_failed_test_expectations() -> TestExpectations {
                 if (failed_tx_hashes_ref).contains(&BlockifierTransaction::tx_hash(tx)) {
                     Err(TransactionExecutorError::StateError(StateError::OutOfRangeContractAddress))
                 } else {
-                    Ok(execution_info())
+                    Ok(execution_output())
                 }
             })
             .collect()
@@ -330,7 +350,9 @@ fn transaction_failed_test_expectations() -> TestExpectations {
             state_diff: expected_block_artifacts_copy.commitment_state_diff,
             compressed_state_diff: None,
             bouncer_weights: expected_block_artifacts_copy.bouncer_weights,
+            capacity_utilization: BlockCapacityUtilization::default(),
             casm_hash_computation_data: expected_block_artifacts_copy.casm_hash_computation_data,
+            concurrency_stats: None,
         })
     });
 
@@ -365,7 +387,9 @@ fn set_close_block_expectations(
             state_diff: output_block_artifacts.commitment_state_diff,
             compressed_state_diff: None,
             bouncer_weights: output_block_artifacts.bouncer_weights,
+            capacity_utilization: BlockCapacityUtilization::default(),
             casm_hash_computation_data: output_block_artifacts.casm_hash_computation_data,
+            concurrency_stats: None,
         })
     });
     output_block_artifacts_copy
@@ -484,6 +508,8 @@ async fn run_build_block(
         output_sender,
         abort_receiver,
         transaction_converter,
+        None,
+        None,
         TX_CHUNK_SIZE,
         TX_POLLING_INTERVAL,
         BlockBuilderExecutionParams { deadline, fail_on_err },
@@ -628,7 +654,7 @@ async fn test_build_block_abort() {
     let mut mock_transaction_executor = MockTransactionExecutorTrait::new();
     mock_transaction_executor
         .expect_add_txs_to_block()
-        .return_once(|_| (0..3).map(|_| Ok(execution_info())).collect());
+        .return_once(|_| (0..3).map(|_| Ok(execution_output())).collect());
     mock_transaction_executor.expect_close_block().times(0);
 
     let (output_tx_sender, mut output_tx_receiver) = output_channel();
@@ -747,7 +773,7 @@ async fn failed_l1_handler_transaction_consumed() {
     mock_transaction_executor.expect_add_txs_to_block().times(1).return_once(move |_| {
         vec![
             Err(TransactionExecutorError::StateError(StateError::OutOfRangeContractAddress)),
-            Ok(execution_info()),
+            Ok(execution_output()),
         ]
     });
 
@@ -756,7 +782,9 @@ async fn failed_l1_handler_transaction_consumed() {
             state_diff: Default::default(),
             compressed_state_diff: None,
             bouncer_weights: BouncerWeights::empty(),
+            capacity_utilization: BlockCapacityUtilization::default(),
             casm_hash_computation_data: CasmHashComputationData::default(),
+            concurrency_stats: None,
         })
     });
 
@@ -778,3 +806,201 @@ async fn failed_l1_handler_transaction_consumed() {
         l1_handler_tx.iter().map(|tx| tx.tx_hash()).collect::<IndexSet<_>>()
     );
 }
+
+#[tokio::test]
+async fn transaction_too_large_is_counted_per_exceeded_dimension() {
+    let recorder = PrometheusBuilder::new().build_recorder();
+    let _recorder_guard = metrics::set_default_local_recorder(&recorder);
+    TRANSACTIONS_TOO_LARGE.register();
+    let input_txs = test_txs(0..1);
+    let mock_tx_provider = mock_tx_provider_stream_done(input_txs);
+
+    let mut mock_transaction_executor = MockTransactionExecutorTrait::new();
+    mock_transaction_executor.expect_add_txs_to_block().times(1).return_once(|_| {
+        let max_capacity = BouncerWeights { n_events: 1, n_txs: 1, ..BouncerWeights::max() };
+        let tx_size = BouncerWeights { n_events: 2, n_txs: 1, ..BouncerWeights::empty() };
+        vec![Err(TransactionExecutorError::TransactionExecutionError(
+            TransactionExecutionError::TransactionTooLarge {
+                max_capacity: Box::new(max_capacity),
+                tx_size: Box::new(tx_size),
+            },
+        ))]
+    });
+    set_close_block_expectations(&mut mock_transaction_executor, 0);
+
+    let (_abort_sender, abort_receiver) = tokio::sync::oneshot::channel();
+    run_build_block(
+        mock_transaction_executor,
+        mock_tx_provider,
+        None,
+        false,
+        abort_receiver,
+        BLOCK_GENERATION_DEADLINE_SECS,
+    )
+    .await
+    .unwrap();
+
+    let metrics = recorder.handle().render();
+    let too_large = |dimension: BouncerDimension| {
+        TRANSACTIONS_TOO_LARGE
+            .parse_numeric_metric::<u64>(
+                &metrics,
+                &[(LABEL_NAME_BOUNCER_DIMENSION, dimension.into())],
+            )
+            .unwrap()
+    };
+    assert_eq!(too_large(BouncerDimension::NEvents), 1);
+    assert_eq!(too_large(BouncerDimension::NTxs), 0);
+}
+
+#[tokio::test]
+async fn test_preconfirmed_block() {
+    let input_txs = test_txs(0..3);
+    let failed_tx_hash = tx_hash!(1);
+    let contract_address = contract_address!("0x100");
+    let mock_tx_provider = mock_tx_provider_stream_done(input_txs);
+
+    let mut mock_transaction_executor = MockTransactionExecutorTrait::new();
+    mock_transaction_executor.expect_add_txs_to_block().times(1).return_once(
+        move |txs: &[BlockifierTransaction]| {
+            txs.iter()
+                .enumerate()
+                .map(|(i, tx)| {
+                    if BlockifierTransaction::tx_hash(tx) == failed_tx_hash {
+                        return Err(TransactionExecutorError::StateError(
+                            StateError::OutOfRangeContractAddress,
+                        ));
+                    }
+                    let nonce = nonce!(u64::try_from(i).unwrap() + 1);
+                    let state_diff = StateMaps {
+                        nonces: HashMap::from([(contract_address, nonce)]),
+                        ..Default::default()
+                    };
+                    Ok((execution_info(), state_diff))
+                })
+                .collect()
+        },
+    );
+    mock_transaction_executor.expect_close_block().times(1).return_once(|| {
+        Ok(BlockExecutionSummary {
+            state_diff: Default::default(),
+            compressed_state_diff: None,
+            bouncer_weights: BouncerWeights::empty(),
+            capacity_utilization: BlockCapacityUtilization::default(),
+            casm_hash_computation_data: CasmHashComputationData::default(),
+            concurrency_stats: None,
+        })
+    });
+
+    let preconfirmed_block = Arc::new(std::sync::Mutex::new(PreconfirmedBlock {
+        block_info: BlockInfo::default(),
+        transactions: Vec::new(),
+        state_diff: Default::default(),
+    }));
+    let (_abort_sender, abort_receiver) = tokio::sync::oneshot::channel();
+    let deadline = tokio::time::Instant::now()
+        + tokio::time::Duration::from_secs(BLOCK_GENERATION_DEADLINE_SECS);
+    let transaction_converter = TransactionConverter::new(
+        Arc::new(MockClassManagerClient::new()),
+        CHAIN_ID_FOR_TESTS.clone(),
+    );
+    let mut block_builder = BlockBuilder::new(
+        mock_transaction_executor,
+        Box::new(mock_tx_provider),
+        None,
+        abort_receiver,
+        transaction_converter,
+        None,
+        Some(preconfirmed_block.clone()),
+        TX_CHUNK_SIZE,
+        TX_POLLING_INTERVAL,
+        BlockBuilderExecutionParams { deadline, fail_on_err: false },
+    );
+    block_builder.build_block().await.unwrap();
+
+    // The failed transaction is not preconfirmed, and the state diff of the later transaction
+    // overrides the earlier one.
+    let preconfirmed_block = preconfirmed_block.lock().unwrap();
+    let tx_hashes: Vec<_> =
+        preconfirmed_block.transactions.iter().map(|tx| tx.transaction.tx_hash()).collect();
+    assert_eq!(tx_hashes, vec![tx_hash!(0), tx_hash!(2)]);
+    assert_matches!(
+        preconfirmed_block.transactions[0].execution_status,
+        TransactionExecutionStatus::Reverted(_)
+    );
+    assert_eq!(
+        preconfirmed_block.state_diff.nonces,
+        IndexMap::from([(contract_address, nonce!(3_u64))])
+    );
+}
+
+#[rstest]
+#[case::proposer_reorders(true, vec![0, 2, 1])]
+#[case::validator_keeps_arrival_order(false, vec![0, 1, 2])]
+#[tokio::test]
+async fn conflict_aware_reordering_only_when_proposing(
+    #[case] is_proposer: bool,
+    #[case] expected_execution_order: Vec<usize>,
+) {
+    // The first two senders are predicted to access the same storage entry, so a proposer
+    // separates their transactions.
+    let sender_addresses =
+        [contract_address!("0x1"), contract_address!("0x2"), contract_address!("0x3")];
+    let accessing_amm_pool = TransactionExecutionInfo {
+        execute_call_info: Some(CallInfo {
+            call: CallEntryPoint {
+                storage_address: contract_address!("0x100"),
+                ..Default::default()
+            },
+            storage_access_tracker: StorageAccessTracker {
+                accessed_storage_keys: HashSet::from([storage_key!("0x7")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let mut access_set_predictor = AccessSetPredictor::new(sender_addresses.len());
+    access_set_predictor.record(sender_addresses[0], &accessing_amm_pool);
+    access_set_predictor.record(sender_addresses[1], &accessing_amm_pool);
+    access_set_predictor.record(sender_addresses[2], &TransactionExecutionInfo::default());
+
+    let input_txs: Vec<_> = sender_addresses
+        .into_iter()
+        .enumerate()
+        .map(|(i, sender_address)| {
+            InternalConsensusTransaction::RpcTransaction(internal_invoke_tx(InvokeTxArgs {
+                tx_hash: tx_hash!(i),
+                sender_address,
+                ..Default::default()
+            }))
+        })
+        .collect();
+    let expected_executed_txs: Vec<_> =
+        expected_execution_order.iter().map(|&i| input_txs[i].clone()).collect();
+    let (mock_transaction_executor, _) =
+        one_chunk_mock_executor(&expected_executed_txs, input_txs.len());
+    let mock_tx_provider = mock_tx_provider_stream_done(input_txs);
+    let (output_tx_sender, _output_tx_receiver) = output_channel();
+
+    let (_abort_sender, abort_receiver) = tokio::sync::oneshot::channel();
+    let deadline = tokio::time::Instant::now()
+        + tokio::time::Duration::from_secs(BLOCK_GENERATION_DEADLINE_SECS);
+    let transaction_converter = TransactionConverter::new(
+        Arc::new(MockClassManagerClient::new()),
+        CHAIN_ID_FOR_TESTS.clone(),
+    );
+    let mut block_builder = BlockBuilder::new(
+        mock_transaction_executor,
+        Box::new(mock_tx_provider),
+        is_proposer.then_some(output_tx_sender),
+        abort_receiver,
+        transaction_converter,
+        Some(Arc::new(std::sync::Mutex::new(access_set_predictor))),
+        None,
+        TX_CHUNK_SIZE,
+        TX_POLLING_INTERVAL,
+        BlockBuilderExecutionParams { deadline, fail_on_err: false },
+    );
+    block_builder.build_block().await.unwrap();
+}
@@ -39,6 +39,9 @@ impl ComponentRequestHandler<BatcherRequest, BatcherResponse> for Batcher {
             BatcherRequest::RevertBlock(input) => {
                 BatcherResponse::RevertBlock(self.revert_block(input).await)
             }
+            BatcherRequest::GetPreconfirmedBlock => BatcherResponse::GetPreconfirmedBlock(
+                self.get_preconfirmed_block().await.map(Box::new),
+            ),
         }
     }
 }
@@ -1,6 +1,17 @@
-use apollo_metrics::define_metrics;
-use apollo_metrics::metrics::{MetricCounter, MetricGauge};
+use apollo_metrics::metrics::{
+    LabeledMetricCounter,
+    LabeledMetricHistogram,
+    MetricCounter,
+    MetricGauge,
+    MetricHistogram,
+};
+use apollo_metrics::{define_metrics, generate_permutation_labels};
+use blockifier::blockifier::transaction_executor::TransactionExecutorError;
+use blockifier::bouncer::{BlockCapacityUtilization, BouncerDimension};
+use blockifier::concurrency::stats::ConcurrencyStats;
+use blockifier::transaction::errors::TransactionExecutionError;
 use starknet_api::block::BlockNumber;
+use strum::VariantNames;
 
 define_metrics!(
     Batcher => {
@@ -21,9 +32,28 @@ define_metrics!(
         MetricCounter { SYNCED_TRANSACTIONS, "batcher_synced_transactions", "Counter of synced transactions", init = 0 },
 
         MetricCounter { FULL_BLOCKS, "batcher_full_blocks", "Counter of blocks closed on full capacity", init = 0 },
+        // Block capacity
+        LabeledMetricHistogram { BLOCK_CAPACITY_UTILIZATION, "batcher_block_capacity_utilization", "The ratio between the weight and the max capacity of a closed block, per bouncer dimension", labels = BOUNCER_DIMENSION_LABELS },
+        LabeledMetricCounter { BLOCK_CLOSING_DIMENSIONS, "batcher_block_closing_dimensions", "Counter of full blocks, per bouncer dimension whose capacity was reached", init = 0, labels = BOUNCER_DIMENSION_LABELS },
+        LabeledMetricCounter { TRANSACTIONS_TOO_LARGE, "batcher_transactions_too_large", "Counter of transactions rejected for exceeding the block max capacity, per bouncer dimension exceeded", init = 0, labels = BOUNCER_DIMENSION_LABELS },
+        // Concurrent execution
+        MetricCounter { CONCURRENCY_ABORTS, "batcher_concurrency_aborts", "Counter of transaction executions aborted by validation during concurrent execution", init = 0 },
+        MetricCounter { CONCURRENCY_ABORTS_IN_COMMIT, "batcher_concurrency_aborts_in_commit", "Counter of transactions that failed re-validation on commit during concurrent execution", init = 0 },
+        MetricCounter { CONCURRENCY_REEXECUTIONS, "batcher_concurrency_reexecutions", "Counter of re-executions of committed transactions during concurrent execution", init = 0 },
+        MetricHistogram { CONCURRENCY_MAX_EXECUTIONS_PER_TX, "batcher_concurrency_max_executions_per_tx", "The maximal number of executions of a single transaction in a block" },
+        MetricHistogram { CONCURRENCY_EXECUTE_PHASE_TIME, "batcher_concurrency_execute_phase_time", "The cumulative time (secs) spent by all workers executing transactions of a block" },
+        MetricHistogram { CONCURRENCY_VALIDATE_PHASE_TIME, "batcher_concurrency_validate_phase_time", "The cumulative time (secs) spent by all workers validating transactions of a block" },
+        MetricHistogram { CONCURRENCY_COMMIT_PHASE_TIME, "batcher_concurrency_commit_phase_time", "The cumulative time (secs) spent committing transactions of a block, including re-executions" },
     },
 );
 
+pub const LABEL_NAME_BOUNCER_DIMENSION: &str = "bouncer_dimension";
+
+generate_permutation_labels! {
+    BOUNCER_DIMENSION_LABELS,
+    (LABEL_NAME_BOUNCER_DIMENSION, BouncerDimension),
+}
+
 pub fn register_metrics(storage_height: BlockNumber) {
     STORAGE_HEIGHT.register();
     STORAGE_HEIGHT.set_lossy(storage_height.0);
@@ -42,6 +72,55 @@ pub fn register_metrics(storage_height: BlockNumber) {
     SYNCED_TRANSACTIONS.register();
 
     FULL_BLOCKS.register();
+
+    BLOCK_CAPACITY_UTILIZATION.register();
+    BLOCK_CLOSING_DIMENSIONS.register();
+    TRANSACTIONS_TOO_LARGE.register();
+
+    CONCURRENCY_ABORTS.register();
+    CONCURRENCY_ABORTS_IN_COMMIT.register();
+    CONCURRENCY_REEXECUTIONS.register();
+    CONCURRENCY_MAX_EXECUTIONS_PER_TX.register();
+    CONCURRENCY_EXECUTE_PHASE_TIME.register();
+    CONCURRENCY_VALIDATE_PHASE_TIME.register();
+    CONCURRENCY_COMMIT_PHASE_TIME.register();
+}
+
+pub(crate) fn record_concurrency_stats(concurrency_stats: &ConcurrencyStats) {
+    CONCURRENCY_ABORTS.increment(usize_to_u64(concurrency_stats.n_aborts));
+    CONCURRENCY_ABORTS_IN_COMMIT.increment(usize_to_u64(concurrency_stats.n_aborts_in_commit));
+    CONCURRENCY_REEXECUTIONS.increment(usize_to_u64(concurrency_stats.n_reexecutions()));
+    CONCURRENCY_MAX_EXECUTIONS_PER_TX
+        .record(u32::try_from(concurrency_stats.max_executions_per_tx()).unwrap_or(u32::MAX));
+    CONCURRENCY_EXECUTE_PHASE_TIME.record(concurrency_stats.execute_duration.as_secs_f64());
+    CONCURRENCY_VALIDATE_PHASE_TIME.record(concurrency_stats.validate_duration.as_secs_f64());
+    CONCURRENCY_COMMIT_PHASE_TIME.record(concurrency_stats.commit_duration.as_secs_f64());
+}
+
+pub(crate) fn record_capacity_utilization(capacity_utilization: &BlockCapacityUtilization) {
+    for (&dimension, &utilization) in &capacity_utilization.utilization {
+        BLOCK_CAPACITY_UTILIZATION
+            .record(utilization, &[(LABEL_NAME_BOUNCER_DIMENSION, dimension.into())]);
+    }
+    for &dimension in &capacity_utilization.closing_dimensions {
+        BLOCK_CLOSING_DIMENSIONS.increment(1, &[(LABEL_NAME_BOUNCER_DIMENSION, dimension.into())]);
+    }
+}
+
+pub(crate) fn record_transaction_too_large(error: &TransactionExecutorError) {
+    if let TransactionExecutorError::TransactionExecutionError(
+        TransactionExecutionError::TransactionTooLarge { max_capacity, tx_size },
+    ) = error
+    {
+        for dimension in max_capacity.exceeded_dimensions(tx_size) {
+            TRANSACTIONS_TOO_LARGE
+                .increment(1, &[(LABEL_NAME_BOUNCER_DIMENSION, dimension.into())]);
+        }
+    }
+}
+
+fn usize_to_u64(value: usize) -> u64 {
+    value.try_into().expect("usize should fit in u64.")
 }
 
 /// A handle to update the proposal metrics when the proposal is created and dropped.
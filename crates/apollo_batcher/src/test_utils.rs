@@ -1,5 +1,6 @@
 use std::ops::Range;
 
+use apollo_batcher_types::batcher_types::PreconfirmedTransaction;
 use async_trait::async_trait;
 use blockifier::bouncer::{BouncerWeights, CasmHashComputationData};
 use blockifier::fee::receipt::TransactionReceipt;
@@ -11,7 +12,7 @@ use starknet_api::execution_resources::GasAmount;
 use starknet_api::test_utils::invoke::{internal_invoke_tx, InvokeTxArgs};
 use starknet_api::test_utils::l1_handler::{executable_l1_handler_tx, L1HandlerTxArgs};
 use starknet_api::transaction::fields::Fee;
-use starknet_api::transaction::TransactionHash;
+use starknet_api::transaction::{TransactionExecutionStatus, TransactionHash};
 use starknet_api::{class_hash, contract_address, nonce, tx_hash};
 use tokio::sync::mpsc::UnboundedSender;
 
@@ -20,6 +21,7 @@ use crate::block_builder::{
     BlockBuilderTrait,
     BlockExecutionArtifacts,
     BlockTransactionExecutionData,
+    SharedPreconfirmedBlock,
 };
 use crate::transaction_provider::{NextTxs, TransactionProvider};
 
@@ -51,9 +53,10 @@ impl BlockBuilderTrait for FakeValidateBlockBuilder {
 }
 
 // A fake block builder for propose flow, that sends the given transactions to the output content
-// sender.
+// sender and adds them to the preconfirmed block.
 pub(crate) struct FakeProposeBlockBuilder {
     pub output_content_sender: UnboundedSender<InternalConsensusTransaction>,
+    pub preconfirmed_block: SharedPreconfirmedBlock,
     pub output_txs: Vec<InternalConsensusTransaction>,
     pub build_block_result: Option<BlockBuilderResult<BlockExecutionArtifacts>>,
 }
@@ -63,6 +66,13 @@ impl BlockBuilderTrait for FakeProposeBlockBuilder {
     async fn build_block(&mut self) -> BlockBuilderResult<BlockExecutionArtifacts> {
         for tx in &self.output_txs {
             self.output_content_sender.send(tx.clone()).unwrap();
+            self.preconfirmed_block.lock().unwrap().transactions.push(PreconfirmedTransaction {
+                transaction: tx.clone(),
+                execution_status: TransactionExecutionStatus::Succeeded,
+                actual_fee: Fee::default(),
+                events: Vec::new(),
+                messages_sent: Vec::new(),
+            });
         }
 
         // build_block should be called only once, so we can safely take the result.
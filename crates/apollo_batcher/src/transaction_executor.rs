@@ -1,10 +1,10 @@
 use blockifier::blockifier::transaction_executor::{
     BlockExecutionSummary,
+    TransactionExecutionOutput,
     TransactionExecutor,
     TransactionExecutorResult,
 };
 use blockifier::state::state_api::StateReader;
-use blockifier::transaction::objects::TransactionExecutionInfo;
 use blockifier::transaction::transaction_execution::Transaction as BlockifierTransaction;
 #[cfg(test)]
 use mockall::automock;
@@ -14,20 +14,18 @@ pub trait TransactionExecutorTrait: Send {
     fn add_txs_to_block(
         &mut self,
         txs: &[BlockifierTransaction],
-    ) -> Vec<TransactionExecutorResult<TransactionExecutionInfo>>;
+    ) -> Vec<TransactionExecutorResult<TransactionExecutionOutput>>;
     fn close_block(&mut self) -> TransactionExecutorResult<BlockExecutionSummary>;
 }
 
 impl<S: StateReader + Send + Sync> TransactionExecutorTrait for TransactionExecutor<S> {
-    /// Adds the transactions to the generated block and returns the execution results.
+    /// Adds the transactions to the generated block and returns the execution results, along
+    /// with the state diff of each transaction.
     fn add_txs_to_block(
         &mut self,
         txs: &[BlockifierTransaction],
-    ) -> Vec<TransactionExecutorResult<TransactionExecutionInfo>> {
+    ) -> Vec<TransactionExecutorResult<TransactionExecutionOutput>> {
         self.execute_txs(txs)
-            .into_iter()
-            .map(|res| res.map(|(tx_execution_info, _state_diff)| tx_execution_info))
-            .collect()
     }
     /// Finalizes the block creation and returns the commitment state diff, visited
     /// segments mapping and bouncer.
@@ -10,6 +10,8 @@ use starknet_api::consensus_transaction::InternalConsensusTransaction;
 use starknet_api::core::StateDiffCommitment;
 use starknet_api::execution_resources::GasAmount;
 use starknet_api::state::ThinStateDiff;
+use starknet_api::transaction::fields::Fee;
+use starknet_api::transaction::{Event, MessageToL1, TransactionExecutionStatus};
 
 use crate::errors::BatcherError;
 
@@ -54,6 +56,31 @@ pub struct GetHeightResponse {
     pub height: BlockNumber,
 }
 
+/// The block that the batcher is currently proposing. Its transactions were executed but are not
+/// yet part of a decided block.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PreconfirmedBlock {
+    pub block_info: BlockInfo,
+    // Ordered by the execution order in the block.
+    pub transactions: Vec<PreconfirmedTransaction>,
+    // The accumulated state diff of all the transactions above.
+    pub state_diff: ThinStateDiff,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PreconfirmedTransaction {
+    pub transaction: InternalConsensusTransaction,
+    pub execution_status: TransactionExecutionStatus,
+    pub actual_fee: Fee,
+    pub events: Vec<Event>,
+    pub messages_sent: Vec<MessageToL1>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GetPreconfirmedBlockResponse {
+    pub preconfirmed_block: Option<PreconfirmedBlock>,
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct GetProposalContentResponse {
     pub content: GetProposalContent,
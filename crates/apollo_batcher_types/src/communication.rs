@@ -17,6 +17,7 @@ use crate::batcher_types::{
     DecisionReachedInput,
     DecisionReachedResponse,
     GetHeightResponse,
+    GetPreconfirmedBlockResponse,
     GetProposalContentInput,
     GetProposalContentResponse,
     ProposeBlockInput,
@@ -77,6 +78,9 @@ pub trait BatcherClient: Send + Sync {
     ) -> BatcherClientResult<DecisionReachedResponse>;
     /// Reverts the block with the given block number, only if it is the last in the storage.
     async fn revert_block(&self, input: RevertBlockInput) -> BatcherClientResult<()>;
+    /// Gets the transactions executed so far in the block that is being proposed, and their state
+    /// diff. Returns `None` if no block is being proposed in the active height.
+    async fn get_preconfirmed_block(&self) -> BatcherClientResult<GetPreconfirmedBlockResponse>;
 }
 
 #[derive(Serialize, Deserialize, Clone, AsRefStr)]
@@ -90,6 +94,7 @@ pub enum BatcherRequest {
     DecisionReached(DecisionReachedInput),
     AddSyncBlock(SyncBlock),
     RevertBlock(RevertBlockInput),
+    GetPreconfirmedBlock,
 }
 impl_debug_for_infra_requests_and_responses!(BatcherRequest);
 
@@ -104,6 +109,7 @@ pub enum BatcherResponse {
     DecisionReached(BatcherResult<Box<DecisionReachedResponse>>),
     AddSyncBlock(BatcherResult<()>),
     RevertBlock(BatcherResult<()>),
+    GetPreconfirmedBlock(BatcherResult<Box<GetPreconfirmedBlockResponse>>),
 }
 impl_debug_for_infra_requests_and_responses!(BatcherResponse);
 
@@ -227,4 +233,15 @@ where
             Direct
         )
     }
+
+    async fn get_preconfirmed_block(&self) -> BatcherClientResult<GetPreconfirmedBlockResponse> {
+        let request = BatcherRequest::GetPreconfirmedBlock;
+        handle_all_response_variants!(
+            BatcherResponse,
+            GetPreconfirmedBlock,
+            BatcherClientError,
+            BatcherError,
+            Boxed
+        )
+    }
 }
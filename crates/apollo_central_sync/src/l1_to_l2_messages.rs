@@ -0,0 +1,217 @@
+//! Indexes the messages sent from the base layer to L2 by the hash of the base layer transaction
+//! that sent them.
+//!
+//! The indexer follows the base layer tip, and backfills the blocks below the tip at the time
+//! indexing was first enabled, newest first, down to a configured block. It only fetches the
+//! messages; they are written by the sync client that owns the storage writer, whether it syncs
+//! from central or from peers.
+
+#[cfg(test)]
+#[path = "l1_to_l2_messages_test.rs"]
+mod l1_to_l2_messages_test;
+
+use std::cmp::{max, min};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+use std::time::Duration;
+
+use apollo_config::converters::deserialize_seconds_to_duration;
+use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use apollo_storage::base_layer::{BaseLayerStorageReader, L1ToL2Messages};
+use apollo_storage::StorageReader;
+use async_stream::stream;
+use futures::stream::BoxStream;
+use futures_util::{Stream, StreamExt};
+use papyrus_base_layer::L1BlockNumber;
+use serde::{Deserialize, Serialize};
+use starknet_api::core::ChainId;
+use starknet_api::transaction::TransactionHasher;
+use tracing::{debug, warn};
+
+use crate::sources::base_layer::{BaseLayerSourceError, BaseLayerSourceTrait};
+use crate::StateSyncError;
+
+// Max amount of base layer blocks to query for messages to L2 in a single request.
+const MAX_L1_BLOCKS_PER_MESSAGES_REQUEST: u64 = 1000;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub struct L1ToL2MessagesIndexerConfig {
+    pub finality: u64,
+    #[serde(deserialize_with = "deserialize_seconds_to_duration")]
+    pub polling_interval: Duration,
+    pub first_l1_block_number: L1BlockNumber,
+}
+
+impl SerializeConfig for L1ToL2MessagesIndexerConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "finality",
+                &self.finality,
+                "Number of confirmations a base layer block needs before its messages to L2 are \
+                 indexed, so that base layer reorgs don't leave stale entries in the index.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "polling_interval",
+                &self.polling_interval.as_secs(),
+                "Time in seconds to poll the base layer for new blocks once indexing caught up \
+                 with it, and to wait before retrying after an error.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "first_l1_block_number",
+                &self.first_l1_block_number,
+                "The first base layer block to index messages to L2 from. The blocks between it \
+                 and the base layer tip at the time indexing was first enabled are backfilled, \
+                 newest first.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+impl Default for L1ToL2MessagesIndexerConfig {
+    fn default() -> Self {
+        Self { finality: 10, polling_interval: Duration::from_secs(10), first_l1_block_number: 0 }
+    }
+}
+
+pub struct L1ToL2MessagesIndexer<TBaseLayerSource> {
+    config: L1ToL2MessagesIndexerConfig,
+    reader: StorageReader,
+    base_layer_source: Arc<TBaseLayerSource>,
+    chain_id: ChainId,
+}
+
+// Derive would require the base layer source to be Clone.
+impl<TBaseLayerSource> Clone for L1ToL2MessagesIndexer<TBaseLayerSource> {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config,
+            reader: self.reader.clone(),
+            base_layer_source: self.base_layer_source.clone(),
+            chain_id: self.chain_id.clone(),
+        }
+    }
+}
+
+impl<TBaseLayerSource: BaseLayerSourceTrait + Send + Sync + 'static>
+    L1ToL2MessagesIndexer<TBaseLayerSource>
+{
+    pub fn new(
+        config: L1ToL2MessagesIndexerConfig,
+        reader: StorageReader,
+        base_layer_source: TBaseLayerSource,
+        chain_id: ChainId,
+    ) -> Self {
+        Self { config, reader, base_layer_source: Arc::new(base_layer_source), chain_id }
+    }
+
+    /// Returns a never ending stream of the messages to index, both from new base layer blocks and
+    /// from backfilled ones. The messages must be indexed before the stream is polled again.
+    /// Errors are logged and retried, so that they don't interrupt the sync client consuming the
+    /// stream.
+    pub fn stream(&self) -> BoxStream<'static, L1ToL2Messages> {
+        futures::stream::select(self.clone().new_messages(), self.clone().backfilled_messages())
+            .boxed()
+    }
+
+    fn new_messages(self) -> impl Stream<Item = L1ToL2Messages> {
+        stream! {
+            loop {
+                match self.next_new_messages().await {
+                    Ok(Some(l1_to_l2_messages)) => yield l1_to_l2_messages,
+                    Ok(None) => tokio::time::sleep(self.config.polling_interval).await,
+                    Err(err) => {
+                        warn!("Failed to get the messages to L2 of new base layer blocks: {err}.");
+                        tokio::time::sleep(self.config.polling_interval).await;
+                    }
+                }
+            }
+        }
+    }
+
+    fn backfilled_messages(self) -> impl Stream<Item = L1ToL2Messages> {
+        stream! {
+            loop {
+                match self.next_backfilled_messages().await {
+                    Ok(Some(l1_to_l2_messages)) => yield l1_to_l2_messages,
+                    Ok(None) => tokio::time::sleep(self.config.polling_interval).await,
+                    Err(err) => {
+                        warn!("Failed to get the messages to L2 of backfilled base layer blocks: \
+                               {err}.");
+                        tokio::time::sleep(self.config.polling_interval).await;
+                    }
+                }
+            }
+        }
+    }
+
+    // Returns the messages of the finalized base layer blocks above the indexed ones. If none were
+    // indexed, starts from the current base layer tip.
+    async fn next_new_messages(&self) -> Result<Option<L1ToL2Messages>, StateSyncError> {
+        let Some(latest_l1_block_number) =
+            self.base_layer_source.latest_l1_block_number(self.config.finality).await?
+        else {
+            debug!("The base layer has no finalized blocks, waiting for it to advance.");
+            return Ok(None);
+        };
+        let from = self
+            .reader
+            .begin_ro_txn()?
+            .get_l1_to_l2_messages_marker()?
+            .unwrap_or(latest_l1_block_number);
+        if from > latest_l1_block_number {
+            return Ok(None);
+        }
+        let up_to = min(latest_l1_block_number, from + MAX_L1_BLOCKS_PER_MESSAGES_REQUEST - 1);
+        Ok(Some(self.messages_to_l2(from..=up_to).await?))
+    }
+
+    // Returns the messages of the base layer blocks right below the indexed ones, until the first
+    // block to index is reached.
+    async fn next_backfilled_messages(&self) -> Result<Option<L1ToL2Messages>, StateSyncError> {
+        let Some(lowest_l1_block_number) =
+            self.reader.begin_ro_txn()?.get_l1_to_l2_messages_backfill_marker()?
+        else {
+            // Backfilling starts below the first new blocks that are indexed.
+            return Ok(None);
+        };
+        if lowest_l1_block_number <= self.config.first_l1_block_number {
+            return Ok(None);
+        }
+        let from = max(
+            self.config.first_l1_block_number,
+            lowest_l1_block_number.saturating_sub(MAX_L1_BLOCKS_PER_MESSAGES_REQUEST),
+        );
+        Ok(Some(self.messages_to_l2(from..=lowest_l1_block_number - 1).await?))
+    }
+
+    async fn messages_to_l2(
+        &self,
+        l1_blocks: RangeInclusive<L1BlockNumber>,
+    ) -> Result<L1ToL2Messages, BaseLayerSourceError> {
+        let mut messages = Vec::new();
+        for (l1_transaction_hash, tx) in
+            self.base_layer_source.messages_to_l2(l1_blocks.clone()).await?
+        {
+            match tx.calculate_transaction_hash(&self.chain_id, &tx.version) {
+                Ok(tx_hash) => messages.push((l1_transaction_hash, tx_hash)),
+                Err(err) => warn!(
+                    "Failed to calculate the hash of a message to L2 sent in base layer \
+                     transaction {l1_transaction_hash:?}: {err}."
+                ),
+            }
+        }
+        debug!(
+            "Indexing {} messages to L2 from base layer blocks {} to {}.",
+            messages.len(),
+            l1_blocks.start(),
+            l1_blocks.end()
+        );
+        Ok(L1ToL2Messages { l1_blocks, messages })
+    }
+}
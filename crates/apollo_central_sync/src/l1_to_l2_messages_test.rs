@@ -0,0 +1,100 @@
+use apollo_storage::base_layer::{BaseLayerStorageWriter, L1ToL2Messages};
+use apollo_storage::test_utils::get_test_storage;
+use apollo_storage::StorageWriter;
+use mockall::predicate::eq;
+use pretty_assertions::assert_eq;
+use starknet_api::core::{ChainId, L1TransactionHash};
+use starknet_api::transaction::{L1HandlerTransaction, TransactionHasher};
+
+use crate::l1_to_l2_messages::{L1ToL2MessagesIndexer, L1ToL2MessagesIndexerConfig};
+use crate::sources::base_layer::MockBaseLayerSourceTrait;
+
+const FINALITY: u64 = 5;
+const LATEST_L1_BLOCK_NUMBER: u64 = 2500;
+
+fn index(writer: &mut StorageWriter, l1_to_l2_messages: &L1ToL2Messages) {
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .index_l1_to_l2_messages(l1_to_l2_messages)
+        .unwrap()
+        .commit()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn new_messages_start_from_the_base_layer_tip() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+
+    let l1_transaction_hash = L1TransactionHash([1; 32]);
+    let tx = L1HandlerTransaction::default();
+    let tx_hash = tx.calculate_transaction_hash(&ChainId::Mainnet, &tx.version).unwrap();
+    let mut mock = MockBaseLayerSourceTrait::new();
+    mock.expect_latest_l1_block_number()
+        .with(eq(FINALITY))
+        .returning(|_| Ok(Some(LATEST_L1_BLOCK_NUMBER)));
+    mock.expect_messages_to_l2()
+        .with(eq(LATEST_L1_BLOCK_NUMBER..=LATEST_L1_BLOCK_NUMBER))
+        .times(1)
+        .returning(move |_| Ok(vec![(l1_transaction_hash, tx.clone())]));
+    let config = L1ToL2MessagesIndexerConfig { finality: FINALITY, ..Default::default() };
+    let indexer = L1ToL2MessagesIndexer::new(config, reader, mock, ChainId::Mainnet);
+
+    // Without indexed blocks, indexing starts from the base layer tip.
+    let l1_to_l2_messages = indexer.next_new_messages().await.unwrap().unwrap();
+    assert_eq!(
+        l1_to_l2_messages,
+        L1ToL2Messages {
+            l1_blocks: LATEST_L1_BLOCK_NUMBER..=LATEST_L1_BLOCK_NUMBER,
+            messages: vec![(l1_transaction_hash, tx_hash)],
+        }
+    );
+    index(&mut writer, &l1_to_l2_messages);
+
+    // Once caught up with the base layer, there's nothing new to index.
+    assert_eq!(indexer.next_new_messages().await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn new_messages_are_fetched_in_bounded_batches() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    index(&mut writer, &L1ToL2Messages { l1_blocks: 0..=999, messages: vec![] });
+
+    let mut mock = MockBaseLayerSourceTrait::new();
+    mock.expect_latest_l1_block_number().returning(|_| Ok(Some(LATEST_L1_BLOCK_NUMBER)));
+    mock.expect_messages_to_l2().with(eq(1000..=1999)).times(1).returning(|_| Ok(vec![]));
+    let indexer = L1ToL2MessagesIndexer::new(Default::default(), reader, mock, ChainId::Mainnet);
+
+    let l1_to_l2_messages = indexer.next_new_messages().await.unwrap().unwrap();
+    assert_eq!(l1_to_l2_messages, L1ToL2Messages { l1_blocks: 1000..=1999, messages: vec![] });
+}
+
+#[tokio::test]
+async fn backfill_goes_down_to_the_first_l1_block() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+
+    let mut mock = MockBaseLayerSourceTrait::new();
+    mock.expect_messages_to_l2().with(eq(1500..=2499)).times(1).returning(|_| Ok(vec![]));
+    mock.expect_messages_to_l2().with(eq(1200..=1499)).times(1).returning(|_| Ok(vec![]));
+    let config = L1ToL2MessagesIndexerConfig { first_l1_block_number: 1200, ..Default::default() };
+    let indexer = L1ToL2MessagesIndexer::new(config, reader, mock, ChainId::Mainnet);
+
+    // Backfilling starts only once the first new blocks are indexed.
+    assert_eq!(indexer.next_backfilled_messages().await.unwrap(), None);
+    index(
+        &mut writer,
+        &L1ToL2Messages {
+            l1_blocks: LATEST_L1_BLOCK_NUMBER..=LATEST_L1_BLOCK_NUMBER,
+            messages: vec![],
+        },
+    );
+
+    for expected_l1_blocks in [1500..=2499, 1200..=1499] {
+        let l1_to_l2_messages = indexer.next_backfilled_messages().await.unwrap().unwrap();
+        assert_eq!(l1_to_l2_messages.l1_blocks, expected_l1_blocks);
+        index(&mut writer, &l1_to_l2_messages);
+    }
+
+    // The first block to index was reached.
+    assert_eq!(indexer.next_backfilled_messages().await.unwrap(), None);
+}
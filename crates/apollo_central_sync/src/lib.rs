@@ -2,6 +2,7 @@
 // within this crate
 #![cfg_attr(coverage_nightly, feature(coverage_attribute))]
 
+pub mod l1_to_l2_messages;
 mod pending_sync;
 pub mod sources;
 #[cfg(test)]
@@ -29,7 +30,7 @@ use apollo_state_sync_metrics::metrics::{
     STATE_SYNC_PROCESSED_TRANSACTIONS,
     STATE_SYNC_STATE_MARKER,
 };
-use apollo_storage::base_layer::{BaseLayerStorageReader, BaseLayerStorageWriter};
+use apollo_storage::base_layer::{BaseLayerStorageReader, BaseLayerStorageWriter, L1ToL2Messages};
 use apollo_storage::body::BodyStorageWriter;
 use apollo_storage::class::{ClassStorageReader, ClassStorageWriter};
 use apollo_storage::class_manager::{ClassManagerStorageReader, ClassManagerStorageWriter};
@@ -64,6 +65,7 @@ use tokio::sync::{Mutex, RwLock};
 use tokio::task::{spawn_blocking, JoinError};
 use tracing::{debug, error, info, instrument, trace, warn};
 
+use crate::l1_to_l2_messages::L1ToL2MessagesIndexer;
 use crate::pending_sync::sync_pending_data;
 use crate::sources::base_layer::{BaseLayerSourceTrait, EthereumBaseLayerSource};
 use crate::sources::central::{CentralError, CentralSource, CentralSourceTrait};
@@ -190,6 +192,7 @@ pub struct GenericStateSync<
     writer: Arc<Mutex<StorageWriter>>,
     sequencer_pub_key: Option<SequencerPublicKey>,
     class_manager_client: Option<SharedClassManagerClient>,
+    l1_to_l2_messages_indexer: Option<L1ToL2MessagesIndexer<TBaseLayerSource>>,
 }
 
 pub type StateSyncResult = Result<(), StateSyncError>;
@@ -265,12 +268,15 @@ pub enum SyncEvent {
         block_number: BlockNumber,
         block_hash: BlockHash,
     },
+    NewL1ToL2Messages {
+        l1_to_l2_messages: L1ToL2Messages,
+    },
 }
 
 impl<
     TCentralSource: CentralSourceTrait + Sync + Send + 'static,
     TPendingSource: PendingSourceTrait + Sync + Send + 'static,
-    TBaseLayerSource: BaseLayerSourceTrait + Sync + Send,
+    TBaseLayerSource: BaseLayerSourceTrait + Sync + Send + 'static,
 > GenericStateSync<TCentralSource, TPendingSource, TBaseLayerSource>
 {
     pub async fn run(mut self) -> StateSyncResult {
@@ -389,6 +395,14 @@ impl<
             .fuse(),
             None => stream::pending().boxed().fuse(),
         };
+        let l1_to_l2_messages_stream = match &self.l1_to_l2_messages_indexer {
+            Some(l1_to_l2_messages_indexer) => l1_to_l2_messages_indexer
+                .stream()
+                .map(|l1_to_l2_messages| Ok(SyncEvent::NewL1ToL2Messages { l1_to_l2_messages }))
+                .boxed()
+                .fuse(),
+            None => stream::pending().boxed().fuse(),
+        };
         // TODO(dvir): try use interval instead of stream.
         // TODO(DvirYo): fix the bug and remove this check.
         let check_sync_progress =
@@ -398,6 +412,7 @@ impl<
             state_diff_stream,
             compiled_class_stream,
             base_layer_block_stream,
+            l1_to_l2_messages_stream,
             check_sync_progress
         );
 
@@ -408,6 +423,7 @@ impl<
               res = state_diff_stream.next() => res,
               res = compiled_class_stream.next() => res,
               res = base_layer_block_stream.next() => res,
+              res = l1_to_l2_messages_stream.next() => res,
               res = check_sync_progress.next() => res,
               complete => break,
             }
@@ -455,6 +471,9 @@ impl<
             SyncEvent::NewBaseLayerBlock { block_number, block_hash } => {
                 self.store_base_layer_block(block_number, block_hash).await
             }
+            SyncEvent::NewL1ToL2Messages { l1_to_l2_messages } => {
+                self.store_l1_to_l2_messages(l1_to_l2_messages).await
+            }
             SyncEvent::NoProgress => Err(StateSyncError::NoProgress),
         }
     }
@@ -705,6 +724,18 @@ impl<
         .await
     }
 
+    #[instrument(skip(self, l1_to_l2_messages), level = "debug", err)]
+    async fn store_l1_to_l2_messages(
+        &mut self,
+        l1_to_l2_messages: L1ToL2Messages,
+    ) -> StateSyncResult {
+        self.perform_storage_writes(move |writer| {
+            writer.begin_rw_txn()?.index_l1_to_l2_messages(&l1_to_l2_messages)?.commit()?;
+            Ok(())
+        })
+        .await
+    }
+
     // Compares the block's parent hash to the stored block.
     fn verify_parent_block_hash(
         &self,
@@ -948,6 +979,7 @@ impl StateSync {
         reader: StorageReader,
         writer: StorageWriter,
         class_manager_client: Option<SharedClassManagerClient>,
+        l1_to_l2_messages_indexer: Option<L1ToL2MessagesIndexer<EthereumBaseLayerSource>>,
     ) -> Self {
         let base_layer_source = base_layer_source.map(Arc::new);
         Self {
@@ -962,6 +994,7 @@ impl StateSync {
             writer: Arc::new(Mutex::new(writer)),
             sequencer_pub_key: None,
             class_manager_client,
+            l1_to_l2_messages_indexer,
         }
     }
 }
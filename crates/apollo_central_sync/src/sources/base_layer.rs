@@ -1,9 +1,14 @@
+use std::ops::RangeInclusive;
+
 use async_trait::async_trait;
 #[cfg(test)]
 use mockall::automock;
+use papyrus_base_layer::constants::LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER;
 use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerContract;
-use papyrus_base_layer::BaseLayerContract;
+use papyrus_base_layer::{BaseLayerContract, L1BlockNumber, L1Event};
 use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::core::L1TransactionHash;
+use starknet_api::transaction::L1HandlerTransaction;
 
 pub type EthereumBaseLayerSource = EthereumBaseLayerContract;
 
@@ -25,6 +30,19 @@ pub trait BaseLayerSourceTrait {
     async fn latest_proved_block(
         &self,
     ) -> Result<Option<(BlockNumber, BlockHash)>, BaseLayerSourceError>;
+
+    /// Returns the number of the latest base layer block with at least `finality` confirmations.
+    async fn latest_l1_block_number(
+        &self,
+        finality: u64,
+    ) -> Result<Option<L1BlockNumber>, BaseLayerSourceError>;
+
+    /// Returns the messages sent to L2 in the given base layer blocks, in the order they were
+    /// sent, together with the hash of the base layer transaction that sent them.
+    async fn messages_to_l2(
+        &self,
+        l1_blocks: RangeInclusive<L1BlockNumber>,
+    ) -> Result<Vec<(L1TransactionHash, L1HandlerTransaction)>, BaseLayerSourceError>;
 }
 
 #[async_trait]
@@ -42,4 +60,34 @@ impl<
             .map(|block| block.map(|block| (block.number, block.hash)))
             .map_err(|e| BaseLayerSourceError::BaseLayerContractError(Box::new(e)))
     }
+
+    async fn latest_l1_block_number(
+        &self,
+        finality: u64,
+    ) -> Result<Option<L1BlockNumber>, BaseLayerSourceError> {
+        BaseLayerContract::latest_l1_block_number(self, finality)
+            .await
+            .map_err(|e| BaseLayerSourceError::BaseLayerContractError(Box::new(e)))
+    }
+
+    async fn messages_to_l2(
+        &self,
+        l1_blocks: RangeInclusive<L1BlockNumber>,
+    ) -> Result<Vec<(L1TransactionHash, L1HandlerTransaction)>, BaseLayerSourceError> {
+        let events = self
+            .events(l1_blocks, &[LOG_MESSAGE_TO_L2_EVENT_IDENTIFIER])
+            .await
+            .map_err(|e| BaseLayerSourceError::BaseLayerContractError(Box::new(e)))?;
+        Ok(events
+            .into_iter()
+            .filter_map(|event| match event {
+                // Logs always carry the hash of their transaction, except for pending logs which
+                // we don't query.
+                L1Event::LogMessageToL2 { tx, l1_tx_hash: Some(l1_tx_hash), .. } => {
+                    Some((L1TransactionHash(l1_tx_hash.0), tx))
+                }
+                _ => None,
+            })
+            .collect())
+    }
 }
@@ -116,7 +116,7 @@ async fn run_sync(
     reader: StorageReader,
     writer: StorageWriter,
     central: impl CentralSourceTrait + Send + Sync + 'static,
-    base_layer: impl BaseLayerSourceTrait + Send + Sync,
+    base_layer: impl BaseLayerSourceTrait + Send + Sync + 'static,
     config: SyncConfig,
     class_manager_client: Option<Arc<dyn ClassManagerClient>>,
 ) -> StateSyncResult {
@@ -140,6 +140,7 @@ async fn run_sync(
         // up until that block we call add_class_and_executable_unsafe and from that block we call
         // add_class.
         class_manager_client,
+        l1_to_l2_messages_indexer: None,
     };
 
     state_sync.run().await?;
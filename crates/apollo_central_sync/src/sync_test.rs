@@ -195,6 +195,7 @@ async fn store_base_layer_block_test() {
         writer: Arc::new(Mutex::new(writer)),
         sequencer_pub_key: None,
         class_manager_client: None,
+        l1_to_l2_messages_indexer: None,
     };
 
     // Trying to store a block without a header in the storage.
@@ -549,8 +550,8 @@ async fn pending_sync_doesnt_stop_when_data_has_block_hash_field_with_the_same_h
 }
 
 #[tokio::test]
-async fn pending_sync_updates_when_data_has_block_hash_field_with_the_same_hash_and_more_transactions()
- {
+async fn pending_sync_updates_when_data_has_block_hash_field_with_the_same_hash_and_more_transactions(
+) {
     const FIRST_BLOCK_HASH: BlockHash = BlockHash(StarkHash::ONE);
     let genesis_hash = BlockHash(felt!(GENESIS_HASH));
     // Storage with one block header.
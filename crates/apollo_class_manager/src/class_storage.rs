@@ -282,6 +282,7 @@ pub struct ClassHashStorage {
 impl ClassHashStorage {
     pub fn new(config: ClassHashStorageConfig) -> ClassHashStorageResult<Self> {
         let storage_config = StorageConfig::from(config);
+        apollo_storage::migrations::migrate_storage(storage_config.clone())?;
         let (reader, writer) = apollo_storage::open_storage(storage_config)?;
 
         Ok(Self { reader, writer: Arc::new(Mutex::new(writer)) })
@@ -350,21 +351,8 @@ impl FsClassStorage {
         self.get_deprecated_executable_path(class_id).exists()
     }
 
-    /// Returns the directory that will hold classes related to the given class ID.
-    /// For a class ID: 0xa1b2c3d4... (rest of hash), the structure is:
-    /// a1/
-    /// └── b2/
-    ///     └── a1b2c3d4.../
-    fn get_class_dir(&self, class_id: ClassId) -> PathBuf {
-        let class_id = hex::encode(class_id.to_bytes_be());
-        let (first_msb_byte, second_msb_byte, _rest_of_bytes) =
-            (&class_id[..2], &class_id[2..4], &class_id[4..]);
-
-        PathBuf::from(first_msb_byte).join(second_msb_byte).join(class_id)
-    }
-
     fn get_persistent_dir(&self, class_id: ClassId) -> PathBuf {
-        self.persistent_root.join(self.get_class_dir(class_id))
+        self.persistent_root.join(get_class_dir(class_id))
     }
 
     fn get_persistent_dir_with_create(&self, class_id: ClassId) -> FsClassStorageResult<PathBuf> {
@@ -431,7 +419,7 @@ impl FsClassStorage {
     ) -> FsClassStorageResult<()> {
         // Write classes to a temporary directory.
         let tmp_dir = create_tmp_dir()?;
-        let tmp_dir = tmp_dir.path().join(self.get_class_dir(class_id));
+        let tmp_dir = tmp_dir.path().join(get_class_dir(class_id));
         class.write_to_file(concat_sierra_filename(&tmp_dir))?;
         executable_class.write_to_file(concat_executable_filename(&tmp_dir))?;
 
@@ -450,7 +438,7 @@ impl FsClassStorage {
     ) -> FsClassStorageResult<()> {
         // Write class to a temporary directory.
         let tmp_dir = create_tmp_dir()?;
-        let tmp_dir = tmp_dir.path().join(self.get_class_dir(class_id));
+        let tmp_dir = tmp_dir.path().join(get_class_dir(class_id));
         class.write_to_file(concat_deprecated_executable_filename(&tmp_dir))?;
 
         // Atomically rename directory to persistent one.
@@ -551,6 +539,62 @@ impl ClassStorage for FsClassStorage {
     }
 }
 
+/// The classes, out of given ones, that a class storage holds.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct StoredClasses {
+    pub classes: Vec<ClassId>,
+    pub deprecated_classes: Vec<ClassId>,
+}
+
+/// Returns which of the given classes and deprecated classes the class storage of the given config
+/// holds, e.g., to report the classes of reverted blocks. Doesn't modify the storage: the class
+/// hash storage is opened read-only, so it is neither migrated nor pruned.
+pub fn find_stored_classes(
+    config: &FsClassStorageConfig,
+    classes: &[ClassId],
+    deprecated_classes: &[ClassId],
+) -> FsClassStorageResult<StoredClasses> {
+    let classes =
+        find_classes_with_executable_class_hash(config.class_hash_storage_config.clone(), classes)?;
+    let deprecated_classes = deprecated_classes
+        .iter()
+        .filter(|class_id| {
+            let persistent_dir = config.persistent_root.join(get_class_dir(**class_id));
+            concat_deprecated_executable_filename(&persistent_dir).exists()
+        })
+        .copied()
+        .collect();
+    Ok(StoredClasses { classes, deprecated_classes })
+}
+
+fn find_classes_with_executable_class_hash(
+    config: ClassHashStorageConfig,
+    classes: &[ClassId],
+) -> ClassHashStorageResult<Vec<ClassId>> {
+    let reader = apollo_storage::read_only::open_storage_read_only(StorageConfig::from(config))?;
+    let txn = reader.begin_ro_txn()?;
+    let mut stored_classes = Vec::new();
+    for class_id in classes {
+        if txn.get_executable_class_hash(class_id)?.is_some() {
+            stored_classes.push(*class_id);
+        }
+    }
+    Ok(stored_classes)
+}
+
+/// Returns the directory that will hold classes related to the given class ID.
+/// For a class ID: 0xa1b2c3d4... (rest of hash), the structure is:
+/// a1/
+/// └── b2/
+///     └── a1b2c3d4.../
+fn get_class_dir(class_id: ClassId) -> PathBuf {
+    let class_id = hex::encode(class_id.to_bytes_be());
+    let (first_msb_byte, second_msb_byte, _rest_of_bytes) =
+        (&class_id[..2], &class_id[2..4], &class_id[4..]);
+
+    PathBuf::from(first_msb_byte).join(second_msb_byte).join(class_id)
+}
+
 impl PartialEq for FsClassStorageError {
     fn eq(&self, other: &Self) -> bool {
         // Only compare enum variants; no need to compare the error values.
@@ -5,13 +5,16 @@ use starknet_api::state::SierraContractClass;
 
 use crate::class_storage::{
     create_tmp_dir,
+    find_stored_classes,
     ClassHashStorage,
     ClassHashStorageConfig,
     ClassStorage,
     FsClassStorage,
     FsClassStorageError,
+    StoredClasses,
 };
 use crate::config::ClassHashDbConfig;
+use crate::test_utils::FsClassStorageBuilderForTesting;
 
 // TODO(Elin): consider creating an empty Casm instead of vec (doesn't implement default).
 
@@ -138,3 +141,37 @@ fn fs_storage_partial_write_no_atomic_marker() {
     assert_eq!(storage.get_sierra(class_id), Ok(None));
     assert_eq!(storage.get_executable(class_id), Ok(None));
 }
+
+#[test]
+fn find_stored_classes_of_storage() {
+    let (mut storage, config, _handles) = FsClassStorageBuilderForTesting::default().build();
+    let class_id = ClassHash(felt!("0x1234"));
+    let deprecated_class_id = ClassHash(felt!("0x5678"));
+    let missing_class_id = ClassHash(felt!("0x9abc"));
+    storage
+        .set_class(
+            class_id,
+            RawClass::try_from(SierraContractClass::default()).unwrap(),
+            CompiledClassHash(felt!("0x1")),
+            RawExecutableClass::new_unchecked(vec![4, 5, 6].into()),
+        )
+        .unwrap();
+    storage
+        .set_deprecated_class(
+            deprecated_class_id,
+            RawExecutableClass::new_unchecked(vec![7, 8, 9].into()),
+        )
+        .unwrap();
+    // The class hash storage is opened read-only after the writing storage is closed.
+    drop(storage);
+
+    assert_eq!(
+        find_stored_classes(
+            &config,
+            &[class_id, missing_class_id],
+            &[deprecated_class_id, missing_class_id]
+        )
+        .unwrap(),
+        StoredClasses { classes: vec![class_id], deprecated_classes: vec![deprecated_class_id] }
+    );
+}
@@ -24,6 +24,7 @@ use starknet_api::core::{
 };
 use starknet_api::data_availability::DataAvailabilityMode;
 use starknet_api::executable_transaction::L1HandlerTransaction;
+use starknet_api::execution_resources::GasAmount;
 use starknet_api::rpc_transaction::{
     InternalRpcDeclareTransactionV3,
     InternalRpcDeployAccountTransaction,
@@ -57,13 +58,47 @@ use crate::fee_market::FeeMarketInfo;
 #[path = "central_objects_test.rs"]
 mod central_objects_test;
 
-pub(crate) type CentralBouncerWeights = BouncerWeights;
 pub(crate) type CentralFeeMarketInfo = FeeMarketInfo;
 pub(crate) type CentralCompressedStateDiff = CentralStateDiff;
 pub(crate) type CentralSierraContractClassEntry = (ClassHash, CentralSierraContractClass);
 pub(crate) type CentralCasmContractClassEntry = (CompiledClassHash, CentralCasmContractClass);
 pub(crate) type CentralCasmHashComputationData = CasmHashComputationData;
 
+// The bouncer weights in the format known to the Python pipeline. New bouncer dimensions are mapped
+// here explicitly, so that they don't change the written blob unnoticed.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub(crate) struct CentralBouncerWeights {
+    l1_gas: usize,
+    message_segment_length: usize,
+    n_events: usize,
+    state_diff_size: usize,
+    sierra_gas: GasAmount,
+    n_txs: usize,
+}
+
+impl From<BouncerWeights> for CentralBouncerWeights {
+    fn from(bouncer_weights: BouncerWeights) -> CentralBouncerWeights {
+        // The proving cost is not supported by the Python pipeline.
+        let BouncerWeights {
+            l1_gas,
+            message_segment_length,
+            n_events,
+            state_diff_size,
+            sierra_gas,
+            n_txs,
+            proving_cost: _,
+        } = bouncer_weights;
+        CentralBouncerWeights {
+            l1_gas,
+            message_segment_length,
+            n_events,
+            state_diff_size,
+            sierra_gas,
+            n_txs,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize)]
 struct CentralResourcePrice {
     price_in_wei: NonzeroGasPrice,
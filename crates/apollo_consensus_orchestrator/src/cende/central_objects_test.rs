@@ -4,6 +4,7 @@ use std::vec;
 
 use apollo_class_manager_types::MockClassManagerClient;
 use apollo_infra_utils::test_utils::assert_json_eq;
+use blockifier::bouncer::BouncerWeights;
 use blockifier::execution::call_info::{
     CallExecution,
     CallInfo,
@@ -346,17 +347,22 @@ fn central_l1_handler_tx() -> CentralTransactionWritten {
     }
 }
 
-fn central_bouncer_weights() -> CentralBouncerWeights {
-    CentralBouncerWeights {
+fn bouncer_weights() -> BouncerWeights {
+    BouncerWeights {
         l1_gas: 8,
         message_segment_length: 9,
         n_events: 2,
         state_diff_size: 45,
         sierra_gas: GasAmount(10),
         n_txs: 2,
+        proving_cost: 12,
     }
 }
 
+fn central_bouncer_weights() -> CentralBouncerWeights {
+    bouncer_weights().into()
+}
+
 fn central_fee_market_info() -> CentralFeeMarketInfo {
     CentralFeeMarketInfo { l2_gas_consumed: GasAmount(150000), next_l2_gas_price: GasPrice(100000) }
 }
@@ -620,7 +626,7 @@ fn central_blob() -> AerospikeBlob {
         state_diff: thin_state_diff(),
         compressed_state_diff: Some(commitment_state_diff()),
         transactions: input_txs,
-        bouncer_weights: central_bouncer_weights(),
+        bouncer_weights: bouncer_weights(),
         fee_market_info: central_fee_market_info(),
         execution_infos: vec![transaction_execution_info()],
         casm_hash_computation_data: central_casm_hash_computation_data(),
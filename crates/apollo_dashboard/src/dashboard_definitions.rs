@@ -7,9 +7,12 @@ use crate::panels::batcher::{
     PANEL_BATCHER_REMOTE_MSGS_PROCESSED,
     PANEL_BATCHER_REMOTE_MSGS_RECEIVED,
     PANEL_BATCHER_REMOTE_VALID_MSGS_RECEIVED,
+    PANEL_BLOCK_CAPACITY_UTILIZATION,
+    PANEL_BLOCK_CLOSING_DIMENSIONS,
     PANEL_PROPOSAL_FAILED,
     PANEL_PROPOSAL_STARTED,
     PANEL_PROPOSAL_SUCCEEDED,
+    PANEL_TRANSACTIONS_TOO_LARGE,
 };
 use crate::panels::class_manager::{
     PANEL_CLASS_MANAGER_LOCAL_MSGS_PROCESSED,
@@ -240,6 +243,9 @@ const BATCHER_ROW: Row = Row::new(
         PANEL_PROPOSAL_SUCCEEDED,
         PANEL_PROPOSAL_FAILED,
         PANEL_BATCHED_TRANSACTIONS,
+        PANEL_BLOCK_CAPACITY_UTILIZATION,
+        PANEL_BLOCK_CLOSING_DIMENSIONS,
+        PANEL_TRANSACTIONS_TOO_LARGE,
     ],
 );
 
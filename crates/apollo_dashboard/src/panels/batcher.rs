@@ -1,8 +1,12 @@
 use apollo_batcher::metrics::{
     BATCHED_TRANSACTIONS,
+    BLOCK_CAPACITY_UTILIZATION,
+    BLOCK_CLOSING_DIMENSIONS,
+    LABEL_NAME_BOUNCER_DIMENSION,
     PROPOSAL_FAILED,
     PROPOSAL_STARTED,
     PROPOSAL_SUCCEEDED,
+    TRANSACTIONS_TOO_LARGE,
 };
 use apollo_infra::metrics::{
     BATCHER_LOCAL_MSGS_PROCESSED,
@@ -12,6 +16,7 @@ use apollo_infra::metrics::{
     BATCHER_REMOTE_MSGS_RECEIVED,
     BATCHER_REMOTE_VALID_MSGS_RECEIVED,
 };
+use const_format::formatcp;
 
 use crate::dashboard::{Panel, PanelType};
 
@@ -23,6 +28,36 @@ pub(crate) const PANEL_PROPOSAL_FAILED: Panel =
     Panel::from_counter(PROPOSAL_FAILED, PanelType::Stat);
 pub(crate) const PANEL_BATCHED_TRANSACTIONS: Panel =
     Panel::from_counter(BATCHED_TRANSACTIONS, PanelType::Stat);
+pub(crate) const PANEL_BLOCK_CAPACITY_UTILIZATION: Panel = Panel::new(
+    BLOCK_CAPACITY_UTILIZATION.get_name(),
+    BLOCK_CAPACITY_UTILIZATION.get_description(),
+    formatcp!(
+        "avg by ({}) (avg_over_time({}[10m]))",
+        LABEL_NAME_BOUNCER_DIMENSION,
+        BLOCK_CAPACITY_UTILIZATION.get_name_with_filter()
+    ),
+    PanelType::Graph,
+);
+pub(crate) const PANEL_BLOCK_CLOSING_DIMENSIONS: Panel = Panel::new(
+    BLOCK_CLOSING_DIMENSIONS.get_name(),
+    BLOCK_CLOSING_DIMENSIONS.get_description(),
+    formatcp!(
+        "sum by ({}) ({})",
+        LABEL_NAME_BOUNCER_DIMENSION,
+        BLOCK_CLOSING_DIMENSIONS.get_name_with_filter()
+    ),
+    PanelType::Stat,
+);
+pub(crate) const PANEL_TRANSACTIONS_TOO_LARGE: Panel = Panel::new(
+    TRANSACTIONS_TOO_LARGE.get_name(),
+    TRANSACTIONS_TOO_LARGE.get_description(),
+    formatcp!(
+        "sum by ({}) ({})",
+        LABEL_NAME_BOUNCER_DIMENSION,
+        TRANSACTIONS_TOO_LARGE.get_name_with_filter()
+    ),
+    PanelType::Stat,
+);
 
 pub(crate) const PANEL_BATCHER_LOCAL_MSGS_RECEIVED: Panel =
     Panel::from_counter(BATCHER_LOCAL_MSGS_RECEIVED, PanelType::Stat);
@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Deserialize;
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector};
+use starknet_api::rpc_transaction::{RpcDeclareTransaction, RpcInvokeTransaction, RpcTransaction};
+use starknet_types_core::felt::Felt;
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+use crate::config::{AdmissionPolicyConfig, InvokeTarget};
+use crate::errors::AdmissionPolicyError;
+use crate::metrics::{GATEWAY_ADMISSION_POLICY_REJECTIONS, LABEL_NAME_ADMISSION_POLICY_RULE};
+
+#[cfg(test)]
+#[path = "admission_policy_test.rs"]
+mod admission_policy_test;
+
+#[derive(Debug, Error)]
+pub enum AdmissionPolicyFileError {
+    #[error("Failed to read the admission policy file {path:?}: {source}")]
+    Io { path: PathBuf, source: std::io::Error },
+    #[error("Failed to parse the admission policy file {path:?}: {source}")]
+    Parse { path: PathBuf, source: serde_json::Error },
+}
+
+/// The admission rules, as given in the policy file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct AdmissionPolicyRules {
+    pub allowed_sender_addresses: HashSet<ContractAddress>,
+    pub denied_declare_class_hashes: HashSet<ClassHash>,
+    pub forbidden_invoke_targets: Vec<InvokeTarget>,
+}
+
+impl AdmissionPolicyRules {
+    fn merge(&self, other: &Self) -> Self {
+        let mut merged = self.clone();
+        merged.allowed_sender_addresses.extend(&other.allowed_sender_addresses);
+        merged.denied_declare_class_hashes.extend(&other.denied_declare_class_hashes);
+        merged.forbidden_invoke_targets.extend(&other.forbidden_invoke_targets);
+        merged
+    }
+
+    fn is_empty(&self) -> bool {
+        self.allowed_sender_addresses.is_empty()
+            && self.denied_declare_class_hashes.is_empty()
+            && self.forbidden_invoke_targets.is_empty()
+    }
+
+    fn check(&self, tx: &RpcTransaction) -> Result<(), AdmissionPolicyError> {
+        if !self.allowed_sender_addresses.is_empty() {
+            let sender_address = tx.calculate_sender_address()?;
+            if !self.allowed_sender_addresses.contains(&sender_address) {
+                return Err(AdmissionPolicyError::SenderNotAllowed { sender_address });
+            }
+        }
+
+        match tx {
+            RpcTransaction::Declare(RpcDeclareTransaction::V3(tx))
+                if !self.denied_declare_class_hashes.is_empty() =>
+            {
+                let class_hash = tx.contract_class.calculate_class_hash();
+                if self.denied_declare_class_hashes.contains(&class_hash) {
+                    return Err(AdmissionPolicyError::DeniedDeclareClassHash { class_hash });
+                }
+            }
+            RpcTransaction::Invoke(RpcInvokeTransaction::V3(tx))
+                if !self.forbidden_invoke_targets.is_empty() =>
+            {
+                let calls = parse_multicall(&tx.calldata.0)
+                    .ok_or(AdmissionPolicyError::UnrecognizedInvokeCalldata)?;
+                for (contract_address, selector) in calls {
+                    if self
+                        .forbidden_invoke_targets
+                        .iter()
+                        .any(|t| t.matches(contract_address, selector))
+                    {
+                        return Err(AdmissionPolicyError::ForbiddenInvokeTarget {
+                            contract_address,
+                            selector,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Extracts the called contracts and selectors from the calldata of an account's `__execute__`,
+/// laid out as `[n_calls, (to, selector, calldata_len, calldata...)...]`. Returns `None` if the
+/// calldata doesn't follow this layout.
+///
+/// Only the top-level calls of the multicall are extracted, so forbidding a target is best-effort:
+/// an account or an allowed contract may still call a forbidden target during execution.
+fn parse_multicall(calldata: &[Felt]) -> Option<Vec<(ContractAddress, EntryPointSelector)>> {
+    let (n_calls, mut remaining) = calldata.split_first()?;
+    let n_calls = usize::try_from(*n_calls).ok()?;
+    // Each call takes at least 3 felts, which bounds the allocation below.
+    if n_calls > remaining.len() / 3 {
+        return None;
+    }
+
+    let mut calls = Vec::with_capacity(n_calls);
+    for _ in 0..n_calls {
+        let [to, selector, calldata_len, rest @ ..] = remaining else {
+            return None;
+        };
+        let calldata_len = usize::try_from(*calldata_len).ok()?;
+        if calldata_len > rest.len() {
+            return None;
+        }
+        calls.push((ContractAddress::try_from(*to).ok()?, EntryPointSelector(*selector)));
+        remaining = &rest[calldata_len..];
+    }
+
+    remaining.is_empty().then_some(calls)
+}
+
+struct PolicyFileState {
+    last_checked: Instant,
+    modified: Option<SystemTime>,
+    // The config rules merged with the rules of the last successfully loaded policy file.
+    rules: Arc<AdmissionPolicyRules>,
+}
+
+/// Decides whether a transaction may enter the gateway, according to the configured rules and the
+/// policy file.
+pub struct AdmissionPolicy {
+    config_rules: AdmissionPolicyRules,
+    policy_file_path: Option<PathBuf>,
+    reload_interval: Duration,
+    state: RwLock<PolicyFileState>,
+}
+
+impl AdmissionPolicy {
+    /// Fails if the policy file is configured but can't be loaded, so that a node doesn't start
+    /// with a partial policy.
+    pub fn new(config: &AdmissionPolicyConfig) -> Result<Self, AdmissionPolicyFileError> {
+        let config_rules = AdmissionPolicyRules {
+            allowed_sender_addresses: config.allowed_sender_addresses.iter().copied().collect(),
+            denied_declare_class_hashes: config
+                .denied_declare_class_hashes
+                .iter()
+                .copied()
+                .collect(),
+            forbidden_invoke_targets: config.forbidden_invoke_targets.clone(),
+        };
+        let (modified, rules) = match &config.policy_file_path {
+            Some(path) => {
+                let modified = modification_time(path)?;
+                (Some(modified), config_rules.merge(&load_policy_file(path)?))
+            }
+            None => (None, config_rules.clone()),
+        };
+
+        Ok(Self {
+            config_rules,
+            policy_file_path: config.policy_file_path.clone(),
+            reload_interval: config.policy_file_reload_interval,
+            state: RwLock::new(PolicyFileState {
+                last_checked: Instant::now(),
+                modified,
+                rules: Arc::new(rules),
+            }),
+        })
+    }
+
+    /// Checks the transaction against the rules, counting and logging rejections.
+    pub fn check(&self, tx: &RpcTransaction) -> Result<(), AdmissionPolicyError> {
+        let rules = self.current_rules();
+        if rules.is_empty() {
+            return Ok(());
+        }
+
+        rules.check(tx).inspect_err(|e| {
+            info!("Transaction rejected by the admission policy: {e}");
+            GATEWAY_ADMISSION_POLICY_REJECTIONS
+                .increment(1, &[(LABEL_NAME_ADMISSION_POLICY_RULE, e.into())]);
+        })
+    }
+
+    /// Returns the rules, reloading the policy file first if it was modified since the last load.
+    /// The file is checked at most once per reload interval. If the modified file can't be loaded,
+    /// the previous rules stay in effect until it is modified again.
+    fn current_rules(&self) -> Arc<AdmissionPolicyRules> {
+        let state = self.state.read().expect("Admission policy lock is poisoned.");
+        let Some(path) = &self.policy_file_path else {
+            return state.rules.clone();
+        };
+        if state.last_checked.elapsed() < self.reload_interval {
+            return state.rules.clone();
+        }
+        drop(state);
+
+        let mut state = self.state.write().expect("Admission policy lock is poisoned.");
+        // Another thread may have checked the file while the lock was released.
+        if state.last_checked.elapsed() < self.reload_interval {
+            return state.rules.clone();
+        }
+        state.last_checked = Instant::now();
+        let modified = match modification_time(path) {
+            Ok(modified) => modified,
+            Err(e) => {
+                warn!("{e}. Keeping the previous admission policy.");
+                return state.rules.clone();
+            }
+        };
+        if state.modified != Some(modified) {
+            state.modified = Some(modified);
+            match load_policy_file(path) {
+                Ok(file_rules) => {
+                    info!("Reloaded the admission policy file {path:?}.");
+                    state.rules = Arc::new(self.config_rules.merge(&file_rules));
+                }
+                Err(e) => error!("{e}. Keeping the previous admission policy."),
+            }
+        }
+        state.rules.clone()
+    }
+}
+
+fn modification_time(path: &Path) -> Result<SystemTime, AdmissionPolicyFileError> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|source| AdmissionPolicyFileError::Io { path: path.to_path_buf(), source })
+}
+
+fn load_policy_file(path: &Path) -> Result<AdmissionPolicyRules, AdmissionPolicyFileError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|source| AdmissionPolicyFileError::Io { path: path.to_path_buf(), source })?;
+    serde_json::from_str(&contents)
+        .map_err(|source| AdmissionPolicyFileError::Parse { path: path.to_path_buf(), source })
+}
@@ -0,0 +1,260 @@
+use std::fs::File;
+use std::time::{Duration, SystemTime};
+
+use assert_matches::assert_matches;
+use metrics_exporter_prometheus::PrometheusBuilder;
+use rstest::rstest;
+use starknet_api::core::{ContractAddress, EntryPointSelector, PatriciaKey};
+use starknet_api::rpc_transaction::RpcTransaction;
+use starknet_api::state::SierraContractClass;
+use starknet_api::test_utils::declare::rpc_declare_tx;
+use starknet_api::test_utils::invoke::rpc_invoke_tx;
+use starknet_api::transaction::fields::Calldata;
+use starknet_api::{calldata, contract_address, declare_tx_args, felt, invoke_tx_args};
+use tempfile::NamedTempFile;
+
+use crate::admission_policy::AdmissionPolicy;
+use crate::config::{AdmissionPolicyConfig, InvokeTarget};
+use crate::errors::AdmissionPolicyError;
+use crate::metrics::{
+    register_metrics,
+    GATEWAY_ADMISSION_POLICY_REJECTIONS,
+    LABEL_NAME_ADMISSION_POLICY_RULE,
+};
+
+const SENDER: ContractAddress = ContractAddress(PatriciaKey::ONE);
+
+fn invoke(sender_address: ContractAddress, calldata: Calldata) -> RpcTransaction {
+    rpc_invoke_tx(invoke_tx_args!(sender_address, calldata))
+}
+
+fn transfer_calldata() -> Calldata {
+    // A single call to 0x100, selector 0x200, with calldata [0x7, 0x8].
+    calldata![felt!(1_u8), felt!("0x100"), felt!("0x200"), felt!(2_u8), felt!(7_u8), felt!(8_u8)]
+}
+
+fn write_policy_file(file: &NamedTempFile, contents: &str, modified: SystemTime) {
+    std::fs::write(file.path(), contents).unwrap();
+    File::options().write(true).open(file.path()).unwrap().set_modified(modified).unwrap();
+}
+
+#[test]
+fn empty_policy_admits_all_transactions() {
+    let policy = AdmissionPolicy::new(&AdmissionPolicyConfig::default()).unwrap();
+
+    assert_matches!(policy.check(&invoke(SENDER, calldata![felt!(5_u8)])), Ok(()));
+}
+
+#[rstest]
+#[case::allowed(SENDER, true)]
+#[case::not_allowed(contract_address!("0x2"), false)]
+fn sender_allowlist(#[case] sender_address: ContractAddress, #[case] admitted: bool) {
+    let config =
+        AdmissionPolicyConfig { allowed_sender_addresses: vec![SENDER], ..Default::default() };
+    let policy = AdmissionPolicy::new(&config).unwrap();
+
+    let result = policy.check(&invoke(sender_address, transfer_calldata()));
+
+    if admitted {
+        assert_matches!(result, Ok(()));
+    } else {
+        assert_eq!(result, Err(AdmissionPolicyError::SenderNotAllowed { sender_address }));
+    }
+}
+
+#[test]
+fn denied_declare_class_hash() {
+    let contract_class = SierraContractClass::default();
+    let class_hash = contract_class.calculate_class_hash();
+    let config = AdmissionPolicyConfig {
+        denied_declare_class_hashes: vec![class_hash],
+        ..Default::default()
+    };
+    let policy = AdmissionPolicy::new(&config).unwrap();
+
+    let tx = rpc_declare_tx(declare_tx_args!(), contract_class);
+
+    assert_eq!(policy.check(&tx), Err(AdmissionPolicyError::DeniedDeclareClassHash { class_hash }));
+}
+
+#[rstest]
+#[case::any_selector_of_contract(
+    InvokeTarget { contract_address: contract_address!("0x100"), selector: None },
+    true
+)]
+#[case::matching_selector(
+    InvokeTarget {
+        contract_address: contract_address!("0x100"),
+        selector: Some(EntryPointSelector(felt!("0x200"))),
+    },
+    true
+)]
+#[case::other_selector(
+    InvokeTarget {
+        contract_address: contract_address!("0x100"),
+        selector: Some(EntryPointSelector(felt!("0x300"))),
+    },
+    false
+)]
+#[case::other_contract(
+    InvokeTarget { contract_address: contract_address!("0x101"), selector: None },
+    false
+)]
+fn forbidden_invoke_target(#[case] target: InvokeTarget, #[case] forbidden: bool) {
+    let config =
+        AdmissionPolicyConfig { forbidden_invoke_targets: vec![target], ..Default::default() };
+    let policy = AdmissionPolicy::new(&config).unwrap();
+
+    let result = policy.check(&invoke(SENDER, transfer_calldata()));
+
+    if forbidden {
+        assert_eq!(
+            result,
+            Err(AdmissionPolicyError::ForbiddenInvokeTarget {
+                contract_address: contract_address!("0x100"),
+                selector: EntryPointSelector(felt!("0x200")),
+            })
+        );
+    } else {
+        assert_matches!(result, Ok(()));
+    }
+}
+
+#[rstest]
+#[case::empty(calldata![])]
+#[case::missing_call(calldata![felt!(2_u8), felt!("0x100"), felt!("0x200"), felt!(0_u8)])]
+#[case::calldata_too_short(calldata![felt!(1_u8), felt!("0x100"), felt!("0x200"), felt!(3_u8)])]
+#[case::trailing_data(calldata![felt!(1_u8), felt!("0x100"), felt!("0x200"), felt!(0_u8), felt!(1_u8)])]
+fn unrecognized_invoke_calldata_is_rejected(#[case] calldata: Calldata) {
+    let config = AdmissionPolicyConfig {
+        forbidden_invoke_targets: vec![InvokeTarget {
+            contract_address: contract_address!("0x999"),
+            selector: None,
+        }],
+        ..Default::default()
+    };
+    let policy = AdmissionPolicy::new(&config).unwrap();
+
+    assert_eq!(
+        policy.check(&invoke(SENDER, calldata)),
+        Err(AdmissionPolicyError::UnrecognizedInvokeCalldata)
+    );
+}
+
+#[test]
+fn policy_file_is_merged_and_reloaded() {
+    let policy_file = NamedTempFile::new().unwrap();
+    let first_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+    write_policy_file(&policy_file, r#"{"allowed_sender_addresses": ["0x2"]}"#, first_modified);
+    let config = AdmissionPolicyConfig {
+        allowed_sender_addresses: vec![SENDER],
+        policy_file_path: Some(policy_file.path().to_path_buf()),
+        policy_file_reload_interval: Duration::ZERO,
+        ..Default::default()
+    };
+    let policy = AdmissionPolicy::new(&config).unwrap();
+
+    assert_matches!(policy.check(&invoke(SENDER, transfer_calldata())), Ok(()));
+    assert_matches!(policy.check(&invoke(contract_address!("0x2"), transfer_calldata())), Ok(()));
+
+    write_policy_file(
+        &policy_file,
+        r#"{"forbidden_invoke_targets": [{"contract_address": "0x100"}]}"#,
+        first_modified + Duration::from_secs(1),
+    );
+    assert_matches!(
+        policy.check(&invoke(contract_address!("0x2"), transfer_calldata())),
+        Err(AdmissionPolicyError::SenderNotAllowed { .. })
+    );
+    assert_matches!(
+        policy.check(&invoke(SENDER, transfer_calldata())),
+        Err(AdmissionPolicyError::ForbiddenInvokeTarget { .. })
+    );
+
+    // An invalid file keeps the previous rules in effect.
+    write_policy_file(&policy_file, "not json", first_modified + Duration::from_secs(2));
+    assert_matches!(
+        policy.check(&invoke(SENDER, transfer_calldata())),
+        Err(AdmissionPolicyError::ForbiddenInvokeTarget { .. })
+    );
+}
+
+#[test]
+fn policy_file_is_not_reloaded_within_the_interval() {
+    let policy_file = NamedTempFile::new().unwrap();
+    let first_modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1000);
+    write_policy_file(&policy_file, "{}", first_modified);
+    let config = AdmissionPolicyConfig {
+        policy_file_path: Some(policy_file.path().to_path_buf()),
+        policy_file_reload_interval: Duration::from_secs(3600),
+        ..Default::default()
+    };
+    let policy = AdmissionPolicy::new(&config).unwrap();
+
+    write_policy_file(
+        &policy_file,
+        r#"{"allowed_sender_addresses": ["0x2"]}"#,
+        first_modified + Duration::from_secs(1),
+    );
+    assert_matches!(policy.check(&invoke(SENDER, transfer_calldata())), Ok(()));
+}
+
+#[test]
+fn invalid_policy_file_fails_creation() {
+    let policy_file = NamedTempFile::new().unwrap();
+    write_policy_file(&policy_file, r#"{"unknown_rule": []}"#, SystemTime::now());
+    let config = AdmissionPolicyConfig {
+        policy_file_path: Some(policy_file.path().to_path_buf()),
+        ..Default::default()
+    };
+
+    assert!(AdmissionPolicy::new(&config).is_err());
+}
+
+#[test]
+fn config_lists_are_comma_separated() {
+    let config: AdmissionPolicyConfig = serde_json::from_value(serde_json::json!({
+        "allowed_sender_addresses": "0x1, 0x2",
+        "denied_declare_class_hashes": "",
+        "forbidden_invoke_targets": "0x100,0x101:0x200",
+        "policy_file_path": null,
+        "policy_file_reload_interval": 5,
+    }))
+    .unwrap();
+
+    assert_eq!(config.allowed_sender_addresses, vec![SENDER, contract_address!("0x2")]);
+    assert!(config.denied_declare_class_hashes.is_empty());
+    assert_eq!(config.policy_file_reload_interval, Duration::from_secs(5));
+    assert_eq!(
+        config.forbidden_invoke_targets,
+        vec![
+            InvokeTarget { contract_address: contract_address!("0x100"), selector: None },
+            InvokeTarget {
+                contract_address: contract_address!("0x101"),
+                selector: Some(EntryPointSelector(felt!("0x200"))),
+            },
+        ]
+    );
+    assert_eq!(serde_json::from_value(serde_json::to_value(&config).unwrap()).ok(), Some(config));
+}
+
+#[test]
+fn rejections_are_counted_per_rule() {
+    let recorder = PrometheusBuilder::new().build_recorder();
+    let _recorder_guard = metrics::set_default_local_recorder(&recorder);
+    register_metrics();
+    let config =
+        AdmissionPolicyConfig { allowed_sender_addresses: vec![SENDER], ..Default::default() };
+    let policy = AdmissionPolicy::new(&config).unwrap();
+
+    policy.check(&invoke(contract_address!("0x2"), transfer_calldata())).unwrap_err();
+
+    let metrics = recorder.handle().render();
+    let rejections = |rule| {
+        GATEWAY_ADMISSION_POLICY_REJECTIONS
+            .parse_numeric_metric::<u64>(&metrics, &[(LABEL_NAME_ADMISSION_POLICY_RULE, rule)])
+            .unwrap()
+    };
+    assert_eq!(rejections("sender_not_allowed"), 1);
+    assert_eq!(rejections("denied_declare_class_hash"), 0);
+}
@@ -1,11 +1,20 @@
 use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
-use apollo_config::dumping::{prepend_sub_config_name, ser_param, SerializeConfig};
+use apollo_config::converters::deserialize_seconds_to_duration;
+use apollo_config::dumping::{
+    prepend_sub_config_name,
+    ser_optional_param,
+    ser_param,
+    SerializeConfig,
+};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use blockifier::blockifier_versioned_constants::VersionedConstantsOverrides;
 use blockifier::context::ChainInfo;
-use serde::{Deserialize, Serialize};
-use starknet_api::core::Nonce;
+use serde::de::Error as DeserializationError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, Nonce};
 use starknet_types_core::felt::Felt;
 use validator::Validate;
 
@@ -19,6 +28,7 @@ pub struct GatewayConfig {
     pub stateful_tx_validator_config: StatefulTransactionValidatorConfig,
     pub chain_info: ChainInfo,
     pub block_declare: bool,
+    pub admission_policy_config: AdmissionPolicyConfig,
 }
 
 impl SerializeConfig for GatewayConfig {
@@ -38,6 +48,10 @@ impl SerializeConfig for GatewayConfig {
             "stateful_tx_validator_config",
         ));
         dump.extend(prepend_sub_config_name(self.chain_info.dump(), "chain_info"));
+        dump.extend(prepend_sub_config_name(
+            self.admission_policy_config.dump(),
+            "admission_policy_config",
+        ));
         dump
     }
 }
@@ -203,3 +217,189 @@ impl SerializeConfig for StatefulTransactionValidatorConfig {
         dump
     }
 }
+
+/// Rules restricting which transactions the gateway admits, for permissioned deployments. The
+/// static lists are merged with the lists of the policy file, which is reloaded when it is
+/// modified. The file is checked for modifications at most once per reload interval.
+#[derive(Clone, Debug, Serialize, Deserialize, Validate, PartialEq)]
+pub struct AdmissionPolicyConfig {
+    // If empty, transactions from all senders are admitted.
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated"
+    )]
+    pub allowed_sender_addresses: Vec<ContractAddress>,
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated"
+    )]
+    pub denied_declare_class_hashes: Vec<ClassHash>,
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated"
+    )]
+    pub forbidden_invoke_targets: Vec<InvokeTarget>,
+    pub policy_file_path: Option<PathBuf>,
+    #[serde(
+        serialize_with = "serialize_duration_as_seconds",
+        deserialize_with = "deserialize_seconds_to_duration"
+    )]
+    pub policy_file_reload_interval: Duration,
+}
+
+impl Default for AdmissionPolicyConfig {
+    fn default() -> Self {
+        Self {
+            allowed_sender_addresses: Vec::new(),
+            denied_declare_class_hashes: Vec::new(),
+            forbidden_invoke_targets: Vec::new(),
+            policy_file_path: None,
+            policy_file_reload_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl SerializeConfig for AdmissionPolicyConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        let mut dump = BTreeMap::from_iter([
+            ser_param(
+                "allowed_sender_addresses",
+                &join_comma_separated(&self.allowed_sender_addresses),
+                "Comma-separated sender addresses allowed to submit transactions. If empty (in \
+                 both the config and the policy file), all senders are allowed.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "denied_declare_class_hashes",
+                &join_comma_separated(&self.denied_declare_class_hashes),
+                "Comma-separated class hashes that may not be declared.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "forbidden_invoke_targets",
+                &join_comma_separated(&self.forbidden_invoke_targets),
+                "Comma-separated contracts that invoke transactions may not call, each given as \
+                 'address' or 'address:selector' to forbid a single entry point. Best-effort: \
+                 only the calls of the account's multicall are matched, not the calls these \
+                 contracts make in turn.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "policy_file_reload_interval",
+                &self.policy_file_reload_interval.as_secs(),
+                "Minimum time in seconds between checks of the policy file for modifications. \
+                 Until the next check, transactions are checked against the last loaded rules.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
+        dump.extend(ser_optional_param(
+            &self.policy_file_path,
+            PathBuf::new(),
+            "policy_file_path",
+            "Path to a JSON file with additional admission rules, reloaded when modified.",
+            ParamPrivacyInput::Public,
+        ));
+        dump
+    }
+}
+
+/// An item of a comma-separated list param.
+trait ListParamItem: Sized {
+    fn parse(raw: &str) -> Result<Self, String>;
+    fn format(&self) -> String;
+}
+
+fn parse_felt(raw: &str) -> Result<Felt, String> {
+    Felt::from_hex(raw).map_err(|e| format!("Invalid hex value {raw}: {e}."))
+}
+
+impl ListParamItem for ContractAddress {
+    fn parse(raw: &str) -> Result<Self, String> {
+        ContractAddress::try_from(parse_felt(raw)?).map_err(|e| e.to_string())
+    }
+
+    fn format(&self) -> String {
+        self.0.key().to_hex_string()
+    }
+}
+
+impl ListParamItem for ClassHash {
+    fn parse(raw: &str) -> Result<Self, String> {
+        Ok(ClassHash(parse_felt(raw)?))
+    }
+
+    fn format(&self) -> String {
+        self.0.to_hex_string()
+    }
+}
+
+/// A contract that invoke transactions may not call. If a selector is given, only that entry point
+/// is forbidden.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+pub struct InvokeTarget {
+    pub contract_address: ContractAddress,
+    #[serde(default)]
+    pub selector: Option<EntryPointSelector>,
+}
+
+impl InvokeTarget {
+    pub fn matches(&self, contract_address: ContractAddress, selector: EntryPointSelector) -> bool {
+        self.contract_address == contract_address
+            && self.selector.is_none_or(|forbidden_selector| forbidden_selector == selector)
+    }
+}
+
+impl ListParamItem for InvokeTarget {
+    /// Parses either `address` or `address:selector`.
+    fn parse(raw: &str) -> Result<Self, String> {
+        let (address, selector) = match raw.split_once(':') {
+            Some((address, selector)) => (address, Some(selector)),
+            None => (raw, None),
+        };
+        Ok(Self {
+            contract_address: ContractAddress::parse(address)?,
+            selector: selector.map(parse_felt).transpose()?.map(EntryPointSelector),
+        })
+    }
+
+    fn format(&self) -> String {
+        match self.selector {
+            Some(selector) => {
+                format!("{}:{}", self.contract_address.format(), selector.0.to_hex_string())
+            }
+            None => self.contract_address.format(),
+        }
+    }
+}
+
+fn serialize_duration_as_seconds<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_secs())
+}
+
+fn join_comma_separated<T: ListParamItem>(items: &[T]) -> String {
+    items.iter().map(ListParamItem::format).collect::<Vec<_>>().join(",")
+}
+
+fn serialize_comma_separated<T: ListParamItem, S: Serializer>(
+    items: &[T],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&join_comma_separated(items))
+}
+
+fn deserialize_comma_separated<'de, T, D>(de: D) -> Result<Vec<T>, D::Error>
+where
+    T: ListParamItem,
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    raw_str
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| T::parse(item).map_err(D::Error::custom))
+        .collect()
+}
@@ -10,8 +10,11 @@ use axum::http::StatusCode;
 use blockifier::state::errors::StateError;
 use serde_json::{Error as SerdeError, Value};
 use starknet_api::block::GasPrice;
+use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector};
 use starknet_api::transaction::fields::AllResourceBounds;
 use starknet_api::StarknetApiError;
+use strum::EnumVariantNames;
+use strum_macros::IntoStaticStr;
 use thiserror::Error;
 use tracing::{debug, error, warn};
 
@@ -168,6 +171,35 @@ impl From<StatelessTransactionValidatorError> for StarknetError {
     }
 }
 
+/// A transaction rejected by the admission policy. The variant names label the rejection metric.
+#[derive(Debug, Error, IntoStaticStr, EnumVariantNames)]
+#[cfg_attr(test, derive(PartialEq))]
+#[strum(serialize_all = "snake_case")]
+pub enum AdmissionPolicyError {
+    #[error("Declaring class {class_hash} is not allowed.")]
+    DeniedDeclareClassHash { class_hash: ClassHash },
+    #[error("Calling entry point {selector} of contract {contract_address} is not allowed.")]
+    ForbiddenInvokeTarget { contract_address: ContractAddress, selector: EntryPointSelector },
+    #[error("Sender {sender_address} is not allowed to submit transactions.")]
+    SenderNotAllowed { sender_address: ContractAddress },
+    #[error(transparent)]
+    StarknetApiError(#[from] StarknetApiError),
+    #[error("The invoke calldata is not a multicall and can't be checked for forbidden calls.")]
+    UnrecognizedInvokeCalldata,
+}
+
+impl From<AdmissionPolicyError> for StarknetError {
+    fn from(e: AdmissionPolicyError) -> Self {
+        // Error does not exist in deprecated GW.
+        StarknetError {
+            code: StarknetErrorCode::UnknownErrorCode(
+                "StarknetErrorCode.TRANSACTION_NOT_ADMITTED".to_string(),
+            ),
+            message: e.to_string(),
+        }
+    }
+}
+
 /// Converts a mempool client result to a gateway result. Some errors variants are unreachable in
 /// Gateway context, and some are not considered errors from the gateway's perspective.
 pub fn mempool_client_result_to_gw_spec_result(
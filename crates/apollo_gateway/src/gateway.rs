@@ -33,6 +33,7 @@ use starknet_api::rpc_transaction::{
 };
 use tracing::{debug, error, instrument, warn, Span};
 
+use crate::admission_policy::AdmissionPolicy;
 use crate::config::GatewayConfig;
 use crate::errors::{mempool_client_result_to_deprecated_gw_result, GatewayResult};
 use crate::metrics::{register_metrics, GatewayMetricHandle, GATEWAY_ADD_TX_LATENCY};
@@ -48,6 +49,7 @@ pub mod gateway_test;
 pub struct Gateway {
     pub config: GatewayConfig,
     pub stateless_tx_validator: Arc<StatelessTransactionValidator>,
+    pub admission_policy: Arc<AdmissionPolicy>,
     pub stateful_tx_validator: Arc<StatefulTransactionValidator>,
     pub state_reader_factory: Arc<dyn StateReaderFactory>,
     pub mempool_client: SharedMempoolClient,
@@ -67,6 +69,10 @@ impl Gateway {
             stateless_tx_validator: Arc::new(StatelessTransactionValidator {
                 config: config.stateless_tx_validator_config.clone(),
             }),
+            admission_policy: Arc::new(
+                AdmissionPolicy::new(&config.admission_policy_config)
+                    .expect("Failed to load the admission policy."),
+            ),
             stateful_tx_validator: Arc::new(StatefulTransactionValidator {
                 config: config.stateful_tx_validator_config.clone(),
             }),
@@ -129,6 +135,7 @@ impl Gateway {
 /// from running.
 struct ProcessTxBlockingTask {
     stateless_tx_validator: Arc<StatelessTransactionValidator>,
+    admission_policy: Arc<AdmissionPolicy>,
     stateful_tx_validator: Arc<StatefulTransactionValidator>,
     state_reader_factory: Arc<dyn StateReaderFactory>,
     mempool_client: SharedMempoolClient,
@@ -142,6 +149,7 @@ impl ProcessTxBlockingTask {
     pub fn new(gateway: &Gateway, tx: RpcTransaction, runtime: tokio::runtime::Handle) -> Self {
         Self {
             stateless_tx_validator: gateway.stateless_tx_validator.clone(),
+            admission_policy: gateway.admission_policy.clone(),
             stateful_tx_validator: gateway.stateful_tx_validator.clone(),
             state_reader_factory: gateway.state_reader_factory.clone(),
             mempool_client: gateway.mempool_client.clone(),
@@ -160,6 +168,10 @@ impl ProcessTxBlockingTask {
         // Perform stateless validations.
         self.stateless_tx_validator.validate(&self.tx)?;
 
+        // Reject transactions not admitted by the deployment's policy before doing any stateful
+        // work, in particular before declared classes are added to the class manager.
+        self.admission_policy.check(&self.tx)?;
+
         let internal_tx = self
             .runtime
             .block_on(self.transaction_converter.convert_rpc_tx_to_internal_rpc_tx(self.tx))
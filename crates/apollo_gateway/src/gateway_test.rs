@@ -53,6 +53,7 @@ use starknet_types_core::felt::Felt;
 use strum::VariantNames;
 
 use crate::config::{
+    AdmissionPolicyConfig,
     GatewayConfig,
     StatefulTransactionValidatorConfig,
     StatelessTransactionValidatorConfig,
@@ -79,6 +80,7 @@ fn config() -> GatewayConfig {
         stateful_tx_validator_config: StatefulTransactionValidatorConfig::default(),
         chain_info: ChainInfo::create_for_testing(),
         block_declare: false,
+        admission_policy_config: AdmissionPolicyConfig::default(),
     }
 }
 
@@ -1,3 +1,4 @@
+pub mod admission_policy;
 pub mod communication;
 mod compiler_version;
 pub mod config;
@@ -5,8 +5,11 @@ use starknet_api::rpc_transaction::{RpcTransaction, RpcTransactionLabelValue};
 use strum::{EnumVariantNames, VariantNames};
 use strum_macros::IntoStaticStr;
 
+use crate::errors::AdmissionPolicyError;
+
 pub const LABEL_NAME_TX_TYPE: &str = "tx_type";
 pub const LABEL_NAME_SOURCE: &str = "source";
+pub const LABEL_NAME_ADMISSION_POLICY_RULE: &str = "rule";
 
 generate_permutation_labels! {
     TRANSACTION_TYPE_AND_SOURCE_LABELS,
@@ -14,11 +17,17 @@ generate_permutation_labels! {
     (LABEL_NAME_SOURCE, SourceLabelValue),
 }
 
+generate_permutation_labels! {
+    ADMISSION_POLICY_RULE_LABELS,
+    (LABEL_NAME_ADMISSION_POLICY_RULE, AdmissionPolicyError),
+}
+
 define_metrics!(
     Gateway => {
         LabeledMetricCounter { GATEWAY_TRANSACTIONS_RECEIVED, "gateway_transactions_received", "Counter of transactions received", init = 0 , labels = TRANSACTION_TYPE_AND_SOURCE_LABELS},
         LabeledMetricCounter { GATEWAY_TRANSACTIONS_FAILED, "gateway_transactions_failed", "Counter of failed transactions", init = 0 , labels = TRANSACTION_TYPE_AND_SOURCE_LABELS},
         LabeledMetricCounter { GATEWAY_TRANSACTIONS_SENT_TO_MEMPOOL, "gateway_transactions_sent_to_mempool", "Counter of transactions sent to the mempool", init = 0 , labels = TRANSACTION_TYPE_AND_SOURCE_LABELS},
+        LabeledMetricCounter { GATEWAY_ADMISSION_POLICY_REJECTIONS, "gateway_admission_policy_rejections", "Counter of transactions rejected by the admission policy", init = 0 , labels = ADMISSION_POLICY_RULE_LABELS},
         MetricHistogram { GATEWAY_ADD_TX_LATENCY, "gateway_add_tx_latency", "Latency of gateway add_tx function in secs" },
         MetricHistogram { GATEWAY_VALIDATE_TX_LATENCY, "gateway_validate_tx_latency", "Latency of gateway validate function in secs" },
     },
@@ -88,6 +97,7 @@ pub(crate) fn register_metrics() {
     GATEWAY_TRANSACTIONS_RECEIVED.register();
     GATEWAY_TRANSACTIONS_FAILED.register();
     GATEWAY_TRANSACTIONS_SENT_TO_MEMPOOL.register();
+    GATEWAY_ADMISSION_POLICY_REJECTIONS.register();
     GATEWAY_ADD_TX_LATENCY.register();
     GATEWAY_VALIDATE_TX_LATENCY.register();
 }
@@ -16,6 +16,7 @@ use apollo_consensus_manager::config::ConsensusManagerConfig;
 use apollo_consensus_orchestrator::cende::{CendeConfig, RECORDER_WRITE_BLOB_PATH};
 use apollo_consensus_orchestrator::config::ContextConfig;
 use apollo_gateway::config::{
+    AdmissionPolicyConfig,
     GatewayConfig,
     StatefulTransactionValidatorConfig,
     StatelessTransactionValidatorConfig,
@@ -533,6 +534,7 @@ pub fn create_gateway_config(
         stateful_tx_validator_config,
         chain_info,
         block_declare: false,
+        admission_policy_config: AdmissionPolicyConfig::default(),
     }
 }
 
@@ -15,6 +15,7 @@ use async_trait::async_trait;
 use starknet_api::block::GasPrice;
 use starknet_api::core::ContractAddress;
 use starknet_api::rpc_transaction::InternalRpcTransaction;
+use starknet_api::transaction::TransactionHash;
 use tracing::warn;
 
 use crate::config::MempoolConfig;
@@ -107,6 +108,13 @@ impl MempoolCommunicationWrapper {
         Ok(())
     }
 
+    fn get_txs_by_hash(
+        &self,
+        tx_hashes: Vec<TransactionHash>,
+    ) -> MempoolResult<Vec<InternalRpcTransaction>> {
+        Ok(self.mempool.get_txs_by_hash(&tx_hashes))
+    }
+
     fn mempool_snapshot(&self) -> MempoolResult<MempoolSnapshot> {
         self.mempool.mempool_snapshot()
     }
@@ -133,6 +141,9 @@ impl ComponentRequestHandler<MempoolRequest, MempoolResponse> for MempoolCommuni
             MempoolRequest::UpdateGasPrice(gas_price) => {
                 MempoolResponse::UpdateGasPrice(self.update_gas_price(gas_price))
             }
+            MempoolRequest::GetTransactionsByHash(tx_hashes) => {
+                MempoolResponse::GetTransactionsByHash(self.get_txs_by_hash(tx_hashes))
+            }
             MempoolRequest::GetMempoolSnapshot() => {
                 MempoolResponse::GetMempoolSnapshot(self.mempool_snapshot())
             }
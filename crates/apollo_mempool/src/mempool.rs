@@ -626,6 +626,15 @@ impl Mempool {
         valid_txs
     }
 
+    /// Returns the transactions with the given hashes that are in the pool, skipping the others.
+    pub fn get_txs_by_hash(&self, tx_hashes: &[TransactionHash]) -> Vec<InternalRpcTransaction> {
+        tx_hashes
+            .iter()
+            .filter_map(|&tx_hash| self.tx_pool.get_by_tx_hash(tx_hash).ok())
+            .cloned()
+            .collect()
+    }
+
     pub fn mempool_snapshot(&self) -> MempoolResult<MempoolSnapshot> {
         Ok(MempoolSnapshot {
             transactions: self.tx_pool.chronological_txs_hashes(),
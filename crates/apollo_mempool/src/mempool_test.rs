@@ -1373,6 +1373,19 @@ fn test_get_mempool_snapshot() {
     assert_eq!(mempool_snapshot.transactions, expected_chronological_hashes);
 }
 
+#[rstest]
+fn test_get_txs_by_hash(mut mempool: Mempool) {
+    let input_tx = add_tx_input!(tx_hash: 1, tx_nonce: 0, account_nonce: 0);
+    add_tx(&mut mempool, &input_tx);
+
+    // Test.
+    let txs = mempool.get_txs_by_hash(&[tx_hash!(2), tx_hash!(1)]);
+
+    // Only transactions in the pool are returned, and they aren't removed from it.
+    assert_eq!(txs, vec![input_tx.tx.clone()]);
+    assert_eq!(mempool.get_txs_by_hash(&[tx_hash!(1)]), vec![input_tx.tx]);
+}
+
 #[rstest]
 #[tokio::test]
 async fn add_tx_tolerates_p2p_propagation_error(mempool: Mempool) {
@@ -12,6 +12,7 @@ use serde::{Deserialize, Serialize};
 use starknet_api::block::GasPrice;
 use starknet_api::core::ContractAddress;
 use starknet_api::rpc_transaction::InternalRpcTransaction;
+use starknet_api::transaction::TransactionHash;
 use strum_macros::AsRefStr;
 use thiserror::Error;
 
@@ -48,6 +49,12 @@ pub trait MempoolClient: Send + Sync {
     ) -> MempoolClientResult<bool>;
     async fn update_gas_price(&self, gas_price: GasPrice) -> MempoolClientResult<()>;
     async fn get_mempool_snapshot(&self) -> MempoolClientResult<MempoolSnapshot>;
+    /// Returns the transactions with the given hashes that are still in the mempool, without
+    /// removing them.
+    async fn get_txs_by_hash(
+        &self,
+        tx_hashes: Vec<TransactionHash>,
+    ) -> MempoolClientResult<Vec<InternalRpcTransaction>>;
 }
 
 #[derive(Clone, Serialize, Deserialize, AsRefStr)]
@@ -59,6 +66,7 @@ pub enum MempoolRequest {
     // TODO(yair): Rename to `StartBlock` and add cleanup of staged txs.
     UpdateGasPrice(GasPrice),
     GetMempoolSnapshot(),
+    GetTransactionsByHash(Vec<TransactionHash>),
 }
 impl_debug_for_infra_requests_and_responses!(MempoolRequest);
 
@@ -70,6 +78,7 @@ pub enum MempoolResponse {
     AccountTxInPoolOrRecentBlock(MempoolResult<bool>),
     UpdateGasPrice(MempoolResult<()>),
     GetMempoolSnapshot(MempoolResult<MempoolSnapshot>),
+    GetTransactionsByHash(MempoolResult<Vec<InternalRpcTransaction>>),
 }
 impl_debug_for_infra_requests_and_responses!(MempoolResponse);
 
@@ -154,4 +163,18 @@ where
             Direct
         )
     }
+
+    async fn get_txs_by_hash(
+        &self,
+        tx_hashes: Vec<TransactionHash>,
+    ) -> MempoolClientResult<Vec<InternalRpcTransaction>> {
+        let request = MempoolRequest::GetTransactionsByHash(tx_hashes);
+        handle_all_response_variants!(
+            MempoolResponse,
+            GetTransactionsByHash,
+            MempoolClientError,
+            MempoolError,
+            Direct
+        )
+    }
 }
@@ -0,0 +1,118 @@
+use std::collections::BTreeSet;
+use std::env::args;
+
+use apollo_class_manager::class_storage::find_stored_classes;
+use apollo_node::config::component_execution_config::ReactiveComponentExecutionMode;
+use apollo_node::config::node_config::SequencerNodeConfig;
+use apollo_reverts::{revert_storage, RevertSummary};
+use apollo_storage::{open_storage, StorageConfig};
+use starknet_api::block::BlockNumber;
+
+const USAGE: &str = "Usage: sequencer_revert <dry-run|revert> <revert up to and including block \
+                     number> [node config arguments]";
+
+/// Reverts the storages of all the node's components that run locally (the batcher and the state
+/// sync, including the class manager and base layer markers of the latter) to the same height,
+/// each in a single transaction, and exits. In dry-run mode only reports what would be removed,
+/// e.g.:
+/// cargo run --bin sequencer_revert -- dry-run 1000 --config_file config.json
+///
+/// Opening the storages doesn't migrate or prune them; a storage with pending migrations fails to
+/// open, so it should be reverted with the binary of the version that wrote it.
+///
+/// The class manager's storage is keyed by class hash rather than by height, and the gateway adds
+/// classes to it before they are declared in a block, so it isn't reverted. Instead, the classes of
+/// the reverted blocks that it holds are reported, since they may be kept for transactions that
+/// are resubmitted.
+fn main() {
+    let mut args = args();
+    let executable = args.next().expect("Missing the executable name");
+    let (Some(mode), Some(revert_up_to_and_including)) = (args.next(), args.next()) else {
+        panic!("{USAGE}");
+    };
+    let dry_run = match mode.as_str() {
+        "dry-run" => true,
+        "revert" => false,
+        _ => panic!("{USAGE}"),
+    };
+    let revert_up_to_and_including =
+        BlockNumber(revert_up_to_and_including.parse().unwrap_or_else(|_| panic!("{USAGE}")));
+    let config =
+        SequencerNodeConfig::load_and_process(std::iter::once(executable).chain(args).collect())
+            .expect("Failed to load the node config");
+
+    let components = [
+        ("Batcher", &config.components.batcher.execution_mode, &config.batcher_config.storage),
+        (
+            "State Sync",
+            &config.components.state_sync.execution_mode,
+            &config.state_sync_config.storage_config,
+        ),
+    ];
+    let mut summaries = Vec::new();
+    for (component_name, execution_mode, storage_config) in components {
+        if runs_locally(component_name, execution_mode) {
+            summaries.push(revert_component_storage(
+                component_name,
+                storage_config,
+                revert_up_to_and_including,
+                dry_run,
+            ));
+        }
+    }
+    if runs_locally("Class Manager", &config.components.class_manager.execution_mode) {
+        report_class_manager_storage(&config, &summaries);
+    }
+}
+
+fn runs_locally(component_name: &str, execution_mode: &ReactiveComponentExecutionMode) -> bool {
+    match execution_mode {
+        ReactiveComponentExecutionMode::LocalExecutionWithRemoteDisabled
+        | ReactiveComponentExecutionMode::LocalExecutionWithRemoteEnabled => true,
+        ReactiveComponentExecutionMode::Disabled | ReactiveComponentExecutionMode::Remote => {
+            println!("Skipping {component_name}, which doesn't run in this node.");
+            false
+        }
+    }
+}
+
+fn revert_component_storage(
+    component_name: &str,
+    storage_config: &StorageConfig,
+    revert_up_to_and_including: BlockNumber,
+    dry_run: bool,
+) -> RevertSummary {
+    let (_reader, mut writer) = open_storage(storage_config.clone())
+        .unwrap_or_else(|err| panic!("Failed to open {component_name}'s storage: {err}"));
+    let summary = revert_storage(&mut writer, revert_up_to_and_including, dry_run)
+        .unwrap_or_else(|err| panic!("Failed to revert {component_name}'s storage: {err}"));
+    let action = if dry_run { "Would revert" } else { "Reverted" };
+    println!(
+        "{action} {component_name}'s storage:\n{}",
+        serde_json::to_string_pretty(&summary).expect("Failed to serialize the revert summary")
+    );
+    summary
+}
+
+// Reports the classes declared in the reverted blocks that the class manager's storage holds.
+fn report_class_manager_storage(config: &SequencerNodeConfig, summaries: &[RevertSummary]) {
+    // The batcher and the state sync may both have reverted the same classes.
+    let classes: BTreeSet<_> =
+        summaries.iter().flat_map(|summary| summary.reverted_classes.iter().copied()).collect();
+    let deprecated_classes: BTreeSet<_> = summaries
+        .iter()
+        .flat_map(|summary| summary.reverted_deprecated_classes.iter().copied())
+        .collect();
+    let stored_classes = find_stored_classes(
+        &config.class_manager_config.class_storage_config,
+        &Vec::from_iter(classes),
+        &Vec::from_iter(deprecated_classes),
+    )
+    .unwrap_or_else(|err| panic!("Failed to read Class Manager's storage: {err}"));
+    println!(
+        "Class Manager's storage isn't reverted; it holds these classes of the reverted \
+         blocks:\n{}",
+        serde_json::to_string_pretty(&stored_classes)
+            .expect("Failed to serialize the stored classes")
+    );
+}
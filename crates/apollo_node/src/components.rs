@@ -209,7 +209,10 @@ pub async fn create_node_components(
                 .expect("Class Manager Client should be available");
             let (state_sync, state_sync_runner) = create_state_sync_and_runner(
                 config.state_sync_config.clone(),
+                config.base_layer_config.clone(),
                 class_manager_client,
+                clients.get_batcher_shared_client(),
+                clients.get_mempool_shared_client(),
             );
             (Some(state_sync), Some(state_sync_runner))
         }
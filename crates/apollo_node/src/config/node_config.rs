@@ -47,6 +47,21 @@ pub const POINTER_TARGET_VALUE: &str = "PointerTarget";
 // Configuration parameters that share the same value across multiple components.
 pub static CONFIG_POINTERS: LazyLock<ConfigPointers> = LazyLock::new(|| {
     let mut pointers = vec![
+        (
+            ser_pointer_target_param(
+                "additional_fee_tokens",
+                &String::new(),
+                "Fee tokens overriding the ETH and STRK fee tokens, as comma-separated \
+                 'version:address:rate' items: transactions of the version and above are charged \
+                 in the token at the address, rate (an integer or a fraction 'n/d') units of the \
+                 token per unit of the fee.",
+            ),
+            set_pointing_param_paths(&[
+                "batcher_config.block_builder_config.chain_info.additional_fee_tokens",
+                "gateway_config.chain_info.additional_fee_tokens",
+                "state_sync_config.rpc_config.execution_config.additional_fee_tokens",
+            ]),
+        ),
         (
             ser_pointer_target_param(
                 "chain_id",
@@ -34,6 +34,7 @@ use apollo_protobuf::sync::{
     TransactionQuery,
 };
 use apollo_state_sync_types::state_sync_types::SyncBlock;
+use apollo_storage::base_layer::{BaseLayerStorageWriter, L1ToL2Messages};
 use apollo_storage::{StorageError, StorageReader, StorageWriter};
 use block_data_stream_builder::{BlockDataResult, BlockDataStreamBuilder};
 use class::ClassStreamBuilder;
@@ -228,6 +229,7 @@ pub struct P2pSyncClient {
     storage_writer: StorageWriter,
     p2p_sync_channels: P2pSyncClientChannels,
     internal_blocks_receiver: BoxStream<'static, SyncBlock>,
+    l1_to_l2_messages_stream: BoxStream<'static, L1ToL2Messages>,
     class_manager_client: SharedClassManagerClient,
 }
 
@@ -238,6 +240,7 @@ impl P2pSyncClient {
         storage_writer: StorageWriter,
         p2p_sync_channels: P2pSyncClientChannels,
         internal_blocks_receiver: BoxStream<'static, SyncBlock>,
+        l1_to_l2_messages_stream: BoxStream<'static, L1ToL2Messages>,
         class_manager_client: SharedClassManagerClient,
     ) -> Self {
         Self {
@@ -246,6 +249,7 @@ impl P2pSyncClient {
             storage_writer,
             p2p_sync_channels,
             internal_blocks_receiver,
+            l1_to_l2_messages_stream,
             class_manager_client,
         }
     }
@@ -264,6 +268,7 @@ impl P2pSyncClient {
             mut storage_writer,
             p2p_sync_channels,
             mut internal_blocks_receiver,
+            mut l1_to_l2_messages_stream,
             mut class_manager_client,
         } = self;
         let mut data_stream =
@@ -279,6 +284,14 @@ impl P2pSyncClient {
                     let data = data.expect("Sync data stream should never end")?;
                     data.write_to_storage(&mut storage_writer, &mut class_manager_client).await?;
                 }
+                maybe_l1_to_l2_messages = l1_to_l2_messages_stream.next() => {
+                    let l1_to_l2_messages = maybe_l1_to_l2_messages
+                        .expect("L1 to L2 messages stream should never end");
+                    storage_writer
+                        .begin_rw_txn()?
+                        .index_l1_to_l2_messages(&l1_to_l2_messages)?
+                        .commit()?;
+                }
             }
         }
     }
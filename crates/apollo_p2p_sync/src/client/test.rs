@@ -3,25 +3,28 @@ use std::vec;
 
 use apollo_protobuf::sync::DataOrFin;
 use apollo_state_sync_types::state_sync_types::SyncBlock;
+use apollo_storage::base_layer::{BaseLayerStorageReader, L1ToL2Messages};
 use apollo_storage::body::BodyStorageReader;
 use apollo_storage::header::HeaderStorageReader;
 use apollo_storage::state::StateStorageReader;
 use apollo_test_utils::{get_rng, GetTestInstance};
-use futures::FutureExt;
+use futures::{FutureExt, StreamExt};
 use indexmap::IndexMap;
 use rand::Rng;
 use rand_chacha::ChaCha8Rng;
 use starknet_api::block::{BlockHeaderWithoutHash, BlockNumber};
-use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::core::{ClassHash, ContractAddress, L1TransactionHash};
 use starknet_api::state::ThinStateDiff;
 use starknet_api::transaction::TransactionHash;
 
 use crate::client::test_utils::{
     random_header,
     run_test,
+    setup,
     wait_for_marker,
     Action,
     DataType,
+    TestArgs,
     SLEEP_DURATION_TO_LET_SYNC_ADVANCE,
     TIMEOUT_FOR_TEST,
 };
@@ -258,6 +261,49 @@ async fn receive_blocks_first_externally_and_then_internally() {
     .await;
 }
 
+#[tokio::test]
+async fn index_l1_to_l2_messages() {
+    let TestArgs {
+        mut p2p_sync,
+        storage_reader,
+        mock_header_response_manager: _mock_header_response_manager,
+        mock_state_diff_response_manager: _mock_state_diff_response_manager,
+        mock_transaction_response_manager: _mock_transaction_response_manager,
+        mock_class_response_manager: _mock_class_response_manager,
+    } = setup();
+    let l1_transaction_hash = L1TransactionHash([1; 32]);
+    let l1_handler_transaction_hash = TransactionHash::default();
+    let l1_to_l2_messages = L1ToL2Messages {
+        l1_blocks: 10..=19,
+        messages: vec![(l1_transaction_hash, l1_handler_transaction_hash)],
+    };
+    p2p_sync.l1_to_l2_messages_stream =
+        futures::stream::iter([l1_to_l2_messages]).chain(futures::stream::pending()).boxed();
+
+    let wait_for_messages = async {
+        while storage_reader
+            .begin_ro_txn()
+            .unwrap()
+            .get_l1_to_l2_messages_marker()
+            .unwrap()
+            .is_none()
+        {
+            tokio::time::sleep(SLEEP_DURATION_TO_LET_SYNC_ADVANCE).await;
+        }
+    };
+    tokio::select! {
+        _ = p2p_sync.run() => panic!("P2P sync client stopped unexpectedly"),
+        _ = tokio::time::timeout(TIMEOUT_FOR_TEST, wait_for_messages) => {}
+    }
+
+    let txn = storage_reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_l1_to_l2_messages_marker().unwrap(), Some(20));
+    assert_eq!(
+        txn.get_l1_handler_transaction_hashes(&l1_transaction_hash).unwrap(),
+        vec![l1_handler_transaction_hash]
+    );
+}
+
 fn create_random_sync_block(
     block_number: BlockNumber,
     account_transaction_hashes_len: u8,
@@ -121,6 +121,7 @@ pub fn setup() -> TestArgs {
         storage_writer,
         p2p_sync_channels,
         futures::stream::pending().boxed(),
+        futures::stream::pending().boxed(),
         class_manager_client,
     );
     TestArgs {
@@ -218,6 +219,7 @@ pub async fn run_test(
         storage_writer,
         p2p_sync_channels,
         internal_block_receiver.boxed(),
+        futures::stream::pending().boxed(),
         class_manager_client,
     );
 
@@ -22,7 +22,12 @@ use syn::{
 /// prepend the version id to the trait name and to every method name (note method name refers to
 /// the name the API has for the function not the actual function name). We need this in order to be
 /// able to merge multiple versions of jsonrpc APIs into one server and not have a clash in method
-/// resolution.
+/// resolution. The subscribe and unsubscribe names of subscriptions are versioned the same way.
+///
+/// Given the `unversioned_aliases` flag after the version id, every method (and the subscribe and
+/// unsubscribe methods of every subscription) is also served under its name without the version
+/// id, e.g. `#[versioned_rpc("V0_6_0", unversioned_aliases)]` serves `starknet_V0_6_0_blockNumber`
+/// also as `starknet_blockNumber`.
 ///
 /// # Example:
 ///
@@ -45,7 +50,8 @@ use syn::{
 /// ```
 #[proc_macro_attribute]
 pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
-    let version = parse_macro_input!(attr as syn::LitStr);
+    let VersionedRpcMacroInput { version, unversioned_aliases } =
+        parse_macro_input!(attr as VersionedRpcMacroInput);
     let item_trait = parse_macro_input!(input as ItemTrait);
 
     let trait_name = &item_trait.ident;
@@ -69,15 +75,77 @@ pub fn versioned_rpc(attr: TokenStream, input: TokenStream) -> TokenStream {
                                     if meta.path.is_ident("name") {
                                         let value = meta.value()?;
                                         let method_name: LitStr = value.parse()?;
-                                        let new_meta_str = format!(
-                                            "method(name = \"{}_{}\")",
+                                        let mut new_meta_str = format!(
+                                            "method(name = \"{}_{}\"",
                                             version.value(),
                                             method_name.value()
                                         );
+                                        if unversioned_aliases {
+                                            new_meta_str.push_str(&format!(
+                                                ", aliases = [\"starknet_{}\"]",
+                                                method_name.value()
+                                            ));
+                                        }
+                                        new_meta_str.push(')');
                                         new_attr.meta = syn::parse_str::<Meta>(&new_meta_str)?;
                                     }
                                     Ok(())
                                 });
+                            } else if attr.path().is_ident("subscription") {
+                                // The subscription attribute has a `name = "a" => "b"` argument
+                                // which isn't a valid meta, so its arguments are versioned token by
+                                // token. The name of the notification is left as is.
+                                if let Ok(args) = attr.parse_args_with(|input: ParseStream<'_>| {
+                                    let mut args = quote! {};
+                                    let mut aliases = quote! {};
+                                    while !input.is_empty() {
+                                        if input.peek(Ident)
+                                            && input.peek2(Token![=])
+                                            && input.peek3(LitStr)
+                                        {
+                                            let key: Ident = input.parse()?;
+                                            let eq: Token![=] = input.parse()?;
+                                            let mut value: LitStr = input.parse()?;
+                                            if key == "name" || key == "unsubscribe" {
+                                                let unversioned_name = value.value();
+                                                value = LitStr::new(
+                                                    &format!(
+                                                        "{}_{}",
+                                                        version.value(),
+                                                        unversioned_name
+                                                    ),
+                                                    value.span(),
+                                                );
+                                                if unversioned_aliases {
+                                                    let alias = LitStr::new(
+                                                        &format!("starknet_{unversioned_name}"),
+                                                        value.span(),
+                                                    );
+                                                    let aliases_key = if key == "name" {
+                                                        quote! { aliases }
+                                                    } else {
+                                                        quote! { unsubscribe_aliases }
+                                                    };
+                                                    aliases.extend(
+                                                        quote! { , #aliases_key = [#alias] },
+                                                    );
+                                                }
+                                            }
+                                            args.extend(quote! { #key #eq #value });
+                                        } else {
+                                            let token = input.step(|cursor| {
+                                                cursor
+                                                    .token_tree()
+                                                    .ok_or_else(|| cursor.error("Unexpected end"))
+                                            })?;
+                                            args.extend(quote! { #token });
+                                        }
+                                    }
+                                    args.extend(aliases);
+                                    Ok(args)
+                                }) {
+                                    new_attr.meta = syn::parse_quote!(subscription(#args));
+                                }
                             }
                             new_attr
                         })
@@ -271,6 +339,27 @@ fn create_modified_function(
     modified_function.to_token_stream().into()
 }
 
+struct VersionedRpcMacroInput {
+    version: LitStr,
+    unversioned_aliases: bool,
+}
+
+impl Parse for VersionedRpcMacroInput {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let version: LitStr = input.parse()?;
+        let mut unversioned_aliases = false;
+        if input.parse::<Option<Token![,]>>()?.is_some() {
+            let flag: Ident = input.parse()?;
+            if flag != "unversioned_aliases" {
+                return Err(syn::Error::new(flag.span(), "Expected `unversioned_aliases`"));
+            }
+            unversioned_aliases = true;
+        }
+
+        Ok(VersionedRpcMacroInput { version, unversioned_aliases })
+    }
+}
+
 struct HandleAllResponseVariantsMacroInput {
     response_enum: Ident,
     request_response_enum_var: Ident,
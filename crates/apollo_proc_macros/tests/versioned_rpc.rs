@@ -0,0 +1,74 @@
+use apollo_proc_macros::versioned_rpc;
+use jsonrpsee::core::{async_trait, RpcResult, SubscriptionResult};
+use jsonrpsee::proc_macros::rpc;
+use jsonrpsee::PendingSubscriptionSink;
+
+#[versioned_rpc("V0_1")]
+pub trait Versioned {
+    #[method(name = "blockNumber")]
+    fn block_number(&self) -> RpcResult<u64>;
+}
+
+#[versioned_rpc("V0_2", unversioned_aliases)]
+pub trait Aliased {
+    #[method(name = "blockNumber")]
+    fn block_number(&self) -> RpcResult<u64>;
+
+    #[subscription(
+        name = "subscribeNewHeads" => "subscriptionNewHeads",
+        unsubscribe = "unsubscribeNewHeads",
+        item = u64
+    )]
+    async fn subscribe_new_heads(&self) -> SubscriptionResult;
+}
+
+struct Server;
+
+impl VersionedV0_1Server for Server {
+    fn block_number(&self) -> RpcResult<u64> {
+        Ok(1)
+    }
+}
+
+#[async_trait]
+impl AliasedV0_2Server for Server {
+    fn block_number(&self) -> RpcResult<u64> {
+        Ok(2)
+    }
+
+    async fn subscribe_new_heads(&self, pending: PendingSubscriptionSink) -> SubscriptionResult {
+        pending
+            .reject(jsonrpsee::types::ErrorObjectOwned::from(
+                jsonrpsee::types::error::ErrorCode::InternalError,
+            ))
+            .await;
+        Ok(())
+    }
+}
+
+#[test]
+fn versioned_rpc_method_names() {
+    let mut method_names = VersionedV0_1Server::into_rpc(Server).method_names().collect::<Vec<_>>();
+    method_names.sort();
+    assert_eq!(method_names, vec!["starknet_V0_1_blockNumber"]);
+}
+
+#[tokio::test]
+async fn versioned_rpc_unversioned_aliases() {
+    let module = AliasedV0_2Server::into_rpc(Server);
+    let mut method_names = module.method_names().collect::<Vec<_>>();
+    method_names.sort();
+    assert_eq!(
+        method_names,
+        vec![
+            "starknet_V0_2_blockNumber",
+            "starknet_V0_2_subscribeNewHeads",
+            "starknet_V0_2_unsubscribeNewHeads",
+            "starknet_blockNumber",
+            "starknet_subscribeNewHeads",
+            "starknet_unsubscribeNewHeads",
+        ]
+    );
+    let block_number: u64 = module.call("starknet_blockNumber", [(); 0]).await.unwrap();
+    assert_eq!(block_number, 2);
+}
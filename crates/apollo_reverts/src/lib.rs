@@ -1,18 +1,27 @@
+#[cfg(test)]
+#[path = "revert_test.rs"]
+mod revert_test;
+
 use std::collections::BTreeMap;
 use std::future::Future;
 
 use apollo_config::dumping::{ser_param, SerializeConfig};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
-use apollo_storage::base_layer::BaseLayerStorageWriter;
-use apollo_storage::body::BodyStorageWriter;
-use apollo_storage::class_manager::ClassManagerStorageWriter;
-use apollo_storage::header::HeaderStorageWriter;
-use apollo_storage::state::StateStorageWriter;
-use apollo_storage::StorageWriter;
+use apollo_storage::base_layer::{BaseLayerStorageReader, BaseLayerStorageWriter};
+use apollo_storage::body::{BodyStorageReader, BodyStorageWriter};
+use apollo_storage::class::ClassStorageReader;
+use apollo_storage::class_manager::{ClassManagerStorageReader, ClassManagerStorageWriter};
+use apollo_storage::compiled_class::CasmStorageReader;
+use apollo_storage::db::RW;
+use apollo_storage::header::{HeaderStorageReader, HeaderStorageWriter};
+use apollo_storage::pruning::PruningStorageReader;
+use apollo_storage::state::{StateStorageReader, StateStorageWriter};
+use apollo_storage::{StorageError, StorageResult, StorageTxn, StorageWriter};
 use futures::future::pending;
 use futures::never::Never;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::BlockNumber;
+use starknet_api::core::ClassHash;
 use tracing::info;
 use validator::Validate;
 
@@ -106,22 +115,143 @@ where
 /// the block.
 // This function will panic if the storage reader fails to revert.
 pub fn revert_block(storage_writer: &mut StorageWriter, target_block_marker: BlockNumber) {
-    storage_writer
-        .begin_rw_txn()
-        .unwrap()
-        .revert_header(target_block_marker)
-        .unwrap()
-        .0
-        .revert_body(target_block_marker)
-        .unwrap()
-        .0
-        .revert_state_diff(target_block_marker)
-        .unwrap()
-        .0
-        .try_revert_class_manager_marker(target_block_marker)
-        .unwrap()
-        .try_revert_base_layer_marker(target_block_marker)
-        .unwrap()
-        .commit()
-        .unwrap();
+    let (txn, _reverted_block) =
+        revert_block_in_txn(storage_writer.begin_rw_txn().unwrap(), target_block_marker).unwrap();
+    txn.commit().unwrap();
+}
+
+/// The markers of a storage, see [`RevertSummary`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct StorageMarkers {
+    pub header: BlockNumber,
+    pub body: BlockNumber,
+    pub state: BlockNumber,
+    pub class: BlockNumber,
+    pub compiled_class: BlockNumber,
+    pub base_layer: BlockNumber,
+    pub class_manager: BlockNumber,
+}
+
+impl StorageMarkers {
+    fn read(txn: &StorageTxn<'_, RW>) -> StorageResult<Self> {
+        Ok(Self {
+            header: txn.get_header_marker()?,
+            body: txn.get_body_marker()?,
+            state: txn.get_state_marker()?,
+            class: txn.get_class_marker()?,
+            compiled_class: txn.get_compiled_class_marker()?,
+            base_layer: txn.get_base_layer_block_marker()?,
+            class_manager: txn.get_class_manager_block_marker()?,
+        })
+    }
+
+    // The first block number for which the storage has no data at all.
+    fn height_marker(&self) -> BlockNumber {
+        self.header.max(self.body).max(self.state)
+    }
+}
+
+/// The data removed from a storage by [`revert_storage`], or that would be removed by it in a dry
+/// run.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct RevertSummary {
+    pub markers_before: StorageMarkers,
+    pub markers_after: StorageMarkers,
+    /// The blocks that had a header, a body or a state diff, in descending order.
+    pub reverted_blocks: Vec<BlockNumber>,
+    pub n_reverted_transactions: usize,
+    pub reverted_classes: Vec<ClassHash>,
+    pub reverted_deprecated_classes: Vec<ClassHash>,
+    pub reverted_compiled_classes: Vec<ClassHash>,
+}
+
+/// An error of [`revert_storage`].
+#[derive(thiserror::Error, Debug)]
+pub enum RevertError {
+    #[error(
+        "Cannot revert up to block {revert_up_to_and_including}, which is outside the retained \
+         history of the storage; the first retained block is {pruning_marker}."
+    )]
+    TargetPruned { revert_up_to_and_including: BlockNumber, pruning_marker: BlockNumber },
+    #[error(transparent)]
+    StorageError(#[from] StorageError),
+}
+
+/// Reverts all the blocks from `revert_up_to_and_including` and above in a single transaction, so
+/// either all of them are reverted or none is. In a dry run, the transaction is aborted instead of
+/// committed, so the returned summary describes exactly what a real revert would remove.
+/// Fails if the storage was pruned beyond `revert_up_to_and_including`, since the history needed
+/// for reverting the pruned blocks was deleted.
+pub fn revert_storage(
+    storage_writer: &mut StorageWriter,
+    revert_up_to_and_including: BlockNumber,
+    dry_run: bool,
+) -> Result<RevertSummary, RevertError> {
+    let mut txn = storage_writer.begin_rw_txn()?;
+    let pruning_marker = txn.get_pruning_marker()?;
+    if revert_up_to_and_including < pruning_marker {
+        return Err(RevertError::TargetPruned { revert_up_to_and_including, pruning_marker });
+    }
+    let markers_before = StorageMarkers::read(&txn)?;
+    let mut summary = RevertSummary { markers_before, ..Default::default() };
+
+    let mut storage_height_marker = markers_before.height_marker();
+    while storage_height_marker > revert_up_to_and_including {
+        storage_height_marker = storage_height_marker.prev().expect(
+            "A block number that's greater than another block number should return Some on prev",
+        );
+        let reverted_block;
+        (txn, reverted_block) = revert_block_in_txn(txn, storage_height_marker)?;
+        if reverted_block.had_data {
+            summary.reverted_blocks.push(storage_height_marker);
+        }
+        summary.n_reverted_transactions += reverted_block.n_transactions;
+        summary.reverted_classes.extend(reverted_block.classes);
+        summary.reverted_deprecated_classes.extend(reverted_block.deprecated_classes);
+        summary.reverted_compiled_classes.extend(reverted_block.compiled_classes);
+    }
+    summary.markers_after = StorageMarkers::read(&txn)?;
+
+    if dry_run {
+        // Dropping the transaction aborts it.
+        drop(txn);
+    } else {
+        txn.commit()?;
+    }
+    Ok(summary)
+}
+
+// The data of a block removed by `revert_block_in_txn`.
+struct RevertedBlock {
+    had_data: bool,
+    n_transactions: usize,
+    classes: Vec<ClassHash>,
+    deprecated_classes: Vec<ClassHash>,
+    compiled_classes: Vec<ClassHash>,
+}
+
+fn revert_block_in_txn(
+    txn: StorageTxn<'_, RW>,
+    target_block_marker: BlockNumber,
+) -> StorageResult<(StorageTxn<'_, RW>, RevertedBlock)> {
+    let (txn, header, _signature) = txn.revert_header(target_block_marker)?;
+    let (txn, body) = txn.revert_body(target_block_marker)?;
+    let (txn, state_diff) = txn.revert_state_diff(target_block_marker)?;
+    let txn = txn
+        .try_revert_class_manager_marker(target_block_marker)?
+        .try_revert_base_layer_marker(target_block_marker)?;
+
+    let mut reverted_block = RevertedBlock {
+        had_data: header.is_some() || body.is_some() || state_diff.is_some(),
+        n_transactions: body.map(|(transactions, _, _)| transactions.len()).unwrap_or_default(),
+        classes: Vec::new(),
+        deprecated_classes: Vec::new(),
+        compiled_classes: Vec::new(),
+    };
+    if let Some((_, _, classes, _, deprecated_classes, compiled_classes)) = state_diff {
+        reverted_block.classes = classes.into_keys().collect();
+        reverted_block.deprecated_classes = deprecated_classes.into_keys().collect();
+        reverted_block.compiled_classes = compiled_classes.into_keys().collect();
+    }
+    Ok((txn, reverted_block))
 }
@@ -0,0 +1,107 @@
+use apollo_storage::base_layer::{BaseLayerStorageReader, BaseLayerStorageWriter};
+use apollo_storage::body::BodyStorageWriter;
+use apollo_storage::header::{HeaderStorageReader, HeaderStorageWriter};
+use apollo_storage::pruning::prune_storage;
+use apollo_storage::state::StateStorageWriter;
+use apollo_storage::test_utils::{get_test_storage, get_test_storage_by_scope};
+use apollo_storage::{StorageScope, StorageWriter};
+use apollo_test_utils::get_test_block;
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::felt;
+use starknet_api::state::ThinStateDiff;
+
+use crate::{revert_storage, RevertError, StorageMarkers};
+
+const N_BLOCKS: u64 = 3;
+const N_TRANSACTIONS_PER_BLOCK: usize = 2;
+
+fn write_blocks(writer: &mut StorageWriter) {
+    let mut txn = writer.begin_rw_txn().unwrap();
+    for block_number in (0..N_BLOCKS).map(BlockNumber) {
+        let mut block = get_test_block(N_TRANSACTIONS_PER_BLOCK, None, None, None);
+        // The blocks and the transactions are indexed by their hashes, so they must differ.
+        block.header.block_hash = BlockHash(felt!(block_number.0 + 1));
+        block.header.block_header_without_hash.block_number = block_number;
+        for transaction_hash in &mut block.body.transaction_hashes {
+            transaction_hash.0 += felt!(block_number.0 * N_TRANSACTIONS_PER_BLOCK as u64);
+        }
+        txn = txn
+            .append_header(block_number, &block.header)
+            .unwrap()
+            .append_body(block_number, block.body)
+            .unwrap()
+            .append_state_diff(block_number, ThinStateDiff::default())
+            .unwrap();
+    }
+    txn.update_base_layer_block_marker(&BlockNumber(N_BLOCKS)).unwrap().commit().unwrap();
+}
+
+#[test]
+fn dry_run_and_revert() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    write_blocks(&mut writer);
+    let markers_before = StorageMarkers {
+        header: BlockNumber(N_BLOCKS),
+        body: BlockNumber(N_BLOCKS),
+        state: BlockNumber(N_BLOCKS),
+        // The blocks declare no classes, so the compiled class marker follows the state marker.
+        compiled_class: BlockNumber(N_BLOCKS),
+        base_layer: BlockNumber(N_BLOCKS),
+        ..Default::default()
+    };
+    let markers_after = StorageMarkers {
+        header: BlockNumber(1),
+        body: BlockNumber(1),
+        state: BlockNumber(1),
+        compiled_class: BlockNumber(1),
+        base_layer: BlockNumber(1),
+        ..Default::default()
+    };
+
+    let dry_run_summary = revert_storage(&mut writer, BlockNumber(1), true).unwrap();
+    assert_eq!(dry_run_summary.markers_before, markers_before);
+    assert_eq!(dry_run_summary.markers_after, markers_after);
+    assert_eq!(dry_run_summary.reverted_blocks, vec![BlockNumber(2), BlockNumber(1)]);
+    assert_eq!(dry_run_summary.n_reverted_transactions, 2 * N_TRANSACTIONS_PER_BLOCK);
+    // A dry run doesn't change the storage.
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_header_marker().unwrap(), BlockNumber(N_BLOCKS));
+    assert_eq!(txn.get_base_layer_block_marker().unwrap(), BlockNumber(N_BLOCKS));
+    drop(txn);
+
+    let summary = revert_storage(&mut writer, BlockNumber(1), false).unwrap();
+    assert_eq!(summary, dry_run_summary);
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_header_marker().unwrap(), BlockNumber(1));
+    assert_eq!(txn.get_base_layer_block_marker().unwrap(), BlockNumber(1));
+    drop(txn);
+
+    // Reverting again has nothing to remove.
+    let summary = revert_storage(&mut writer, BlockNumber(1), false).unwrap();
+    assert_eq!(summary.markers_before, markers_after);
+    assert_eq!(summary.reverted_blocks, vec![]);
+}
+
+#[test]
+fn revert_below_pruning_marker_fails() {
+    let ((reader, mut writer), _temp_dir) =
+        get_test_storage_by_scope(StorageScope::Pruned { retained_blocks: 1 });
+    write_blocks(&mut writer);
+    let pruning_marker = prune_storage(&mut writer, 1).unwrap();
+    assert_eq!(pruning_marker, BlockNumber(N_BLOCKS - 1));
+
+    for dry_run in [true, false] {
+        assert_matches!(
+            revert_storage(&mut writer, BlockNumber(N_BLOCKS - 2), dry_run),
+            Err(RevertError::TargetPruned { pruning_marker: BlockNumber(marker), .. })
+                if marker == N_BLOCKS - 1
+        );
+    }
+    assert_eq!(reader.begin_ro_txn().unwrap().get_header_marker().unwrap(), BlockNumber(N_BLOCKS));
+
+    // The retained blocks can be reverted.
+    revert_storage(&mut writer, pruning_marker, false).unwrap();
+    assert_eq!(reader.begin_ro_txn().unwrap().get_header_marker().unwrap(), pruning_marker);
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use apollo_class_manager_types::SharedClassManagerClient;
+use apollo_mempool_types::communication::SharedMempoolClient;
 use apollo_rpc_execution::ExecutionConfig;
 use apollo_starknet_client::reader::PendingData;
 use apollo_starknet_client::writer::StarknetWriter;
@@ -13,8 +14,11 @@ use starknet_api::core::{ChainId, ContractAddress, EntryPointSelector};
 use starknet_api::transaction::fields::Calldata;
 use tokio::sync::RwLock;
 
+use crate::debug::api_impl::DebugJsonRpcServerImpl;
+use crate::subscriptions::Subscriptions;
 use crate::v0_8::api::api_impl::JsonRpcServerImpl as JsonRpcServerV0_8Impl;
-use crate::version_config;
+use crate::v0_9::api::api_impl::JsonRpcServerImpl as JsonRpcServerV0_9Impl;
+use crate::version_config::{self, VersionId};
 
 #[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Tag {
@@ -58,26 +62,33 @@ pub fn get_methods_from_supported_apis(
     storage_reader: StorageReader,
     max_events_chunk_size: usize,
     max_events_keys: usize,
+    max_events_block_range: u64,
     starting_block: BlockHashAndNumber,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     starknet_writer: Arc<dyn StarknetWriter>,
     class_manager_client: Option<SharedClassManagerClient>,
+    mempool_client: Option<SharedMempoolClient>,
+    enable_debug_api: bool,
 ) -> Methods {
     let mut methods: Methods = Methods::new();
+    // The subscriptions are shared by all the versions.
+    let subscriptions = Subscriptions::new(storage_reader.clone(), mempool_client);
     let server_gen = JsonRpcServerImplGenerator {
         chain_id: chain_id.clone(),
         execution_config,
         storage_reader,
         max_events_chunk_size,
         max_events_keys,
+        max_events_block_range,
         starting_block,
         shared_highest_block,
         pending_data,
         pending_classes,
         starknet_writer,
         class_manager_client,
+        subscriptions,
     };
     version_config::VERSION_CONFIG
         .iter()
@@ -85,11 +96,15 @@ pub fn get_methods_from_supported_apis(
             let (version, version_state) = version_config;
             match version_state {
                 version_config::VersionState::Deprecated => None,
-                version_config::VersionState::Supported => {
+                version_config::VersionState::Supported
+                | version_config::VersionState::Experimental => {
                     let methods = match *version {
                         version_config::VERSION_0_8 => {
                             server_gen.clone().generator::<JsonRpcServerV0_8Impl>()
                         }
+                        version_config::VERSION_0_9 => {
+                            server_gen.clone().generator::<JsonRpcServerV0_9Impl>()
+                        }
                         // TODO(yair): remove this once the version is an enum instead of a string.
                         _ => unreachable!("Unrecognized RPC spec version: {}", version),
                     };
@@ -101,6 +116,9 @@ pub fn get_methods_from_supported_apis(
             let _res = methods.merge(new_methods);
             methods
         });
+    if enable_debug_api {
+        let _res = methods.merge(server_gen.rpc_module::<DebugJsonRpcServerImpl>());
+    }
     methods
 }
 
@@ -112,12 +130,14 @@ pub trait JsonRpcServerTrait: Sized {
         storage_reader: StorageReader,
         max_events_chunk_size: usize,
         max_events_keys: usize,
+        max_events_block_range: u64,
         starting_block: BlockHashAndNumber,
         shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
         pending_data: Arc<RwLock<PendingData>>,
         pending_classes: Arc<RwLock<PendingClasses>>,
         starknet_writer: Arc<dyn StarknetWriter>,
         class_manager_client: Option<SharedClassManagerClient>,
+        subscriptions: Subscriptions,
     ) -> Self;
 
     fn into_rpc_module(self) -> RpcModule<Self>;
@@ -130,6 +150,7 @@ struct JsonRpcServerImplGenerator {
     storage_reader: StorageReader,
     max_events_chunk_size: usize,
     max_events_keys: usize,
+    max_events_block_range: u64,
     starting_block: BlockHashAndNumber,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pending_data: Arc<RwLock<PendingData>>,
@@ -137,6 +158,7 @@ struct JsonRpcServerImplGenerator {
     // TODO(shahak): Change this struct to be with a generic type of StarknetWriter.
     starknet_writer: Arc<dyn StarknetWriter>,
     class_manager_client: Option<SharedClassManagerClient>,
+    subscriptions: Subscriptions,
 }
 
 type JsonRpcServerImplParams = (
@@ -145,12 +167,14 @@ type JsonRpcServerImplParams = (
     StorageReader,
     usize,
     usize,
+    u64,
     BlockHashAndNumber,
     Arc<RwLock<Option<BlockHashAndNumber>>>,
     Arc<RwLock<PendingData>>,
     Arc<RwLock<PendingClasses>>,
     Arc<dyn StarknetWriter>,
     Option<SharedClassManagerClient>,
+    Subscriptions,
 );
 
 impl JsonRpcServerImplGenerator {
@@ -161,18 +185,20 @@ impl JsonRpcServerImplGenerator {
             self.storage_reader,
             self.max_events_chunk_size,
             self.max_events_keys,
+            self.max_events_block_range,
             self.starting_block,
             self.shared_highest_block,
             self.pending_data,
             self.pending_classes,
             self.starknet_writer,
             self.class_manager_client,
+            self.subscriptions,
         )
     }
 
-    fn generator<T>(self) -> Methods
+    fn rpc_module<T>(self) -> RpcModule<T>
     where
-        T: JsonRpcServerTrait,
+        T: JsonRpcServerTrait + Send + Sync + 'static,
     {
         let (
             chain_id,
@@ -180,28 +206,67 @@ impl JsonRpcServerImplGenerator {
             storage_reader,
             max_events_chunk_size,
             max_events_keys,
+            max_events_block_range,
             starting_block,
             shared_highest_block,
             pending_data,
             pending_classes,
             starknet_writer,
             class_manager_client,
+            subscriptions,
         ) = self.get_params();
-        Into::<Methods>::into(
-            T::new(
-                chain_id,
-                fee_contract_address,
-                storage_reader,
-                max_events_chunk_size,
-                max_events_keys,
-                starting_block,
-                shared_highest_block,
-                pending_data,
-                pending_classes,
-                starknet_writer,
-                class_manager_client,
-            )
-            .into_rpc_module(),
+        T::new(
+            chain_id,
+            fee_contract_address,
+            storage_reader,
+            max_events_chunk_size,
+            max_events_keys,
+            max_events_block_range,
+            starting_block,
+            shared_highest_block,
+            pending_data,
+            pending_classes,
+            starknet_writer,
+            class_manager_client,
+            subscriptions,
         )
+        .into_rpc_module()
+    }
+
+    fn generator<T>(self) -> Methods
+    where
+        T: JsonRpcServerTrait + Send + Sync + 'static,
+    {
+        self.rpc_module::<T>().into()
+    }
+}
+
+/// Serves the methods of the inherited version that the given module doesn't override under the
+/// names of the given version, so that a new version only implements the methods that changed.
+pub(crate) fn inherit_methods<T: Send + Sync + 'static>(
+    rpc_module: &mut RpcModule<T>,
+    inherited_methods: Methods,
+    inherited_version: &VersionId,
+    version: &VersionId,
+) {
+    let inherited_prefix = format!("starknet_{}_", inherited_version.name);
+    for inherited_method_name in inherited_methods.method_names() {
+        let Some(stripped_method_name) = inherited_method_name.strip_prefix(&inherited_prefix)
+        else {
+            continue;
+        };
+        let method_name = format!("starknet_{}_{stripped_method_name}", version.name);
+        if rpc_module.method(&method_name).is_some() {
+            continue;
+        }
+        let callback = inherited_methods
+            .method(inherited_method_name)
+            .expect("Method names should have a callback")
+            .clone();
+        // The methods are registered once when the server starts, and must outlive it.
+        let method_name: &'static str = Box::leak(method_name.into());
+        rpc_module
+            .verify_and_insert(method_name, callback)
+            .expect("Inherited method names should be unique");
     }
 }
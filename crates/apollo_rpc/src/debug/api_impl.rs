@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use apollo_class_manager_types::SharedClassManagerClient;
+use apollo_rpc_execution::objects::{
+    CallTraceOutput,
+    FeeEstimation,
+    ResourceBoundsEstimation,
+    StateOverrides,
+    TransactionCallTrace,
+};
+use apollo_rpc_execution::ExecutionConfig;
+use apollo_starknet_client::reader::PendingData;
+use apollo_starknet_client::writer::StarknetWriter;
+use apollo_storage::StorageReader;
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::RpcModule;
+use papyrus_common::pending_classes::PendingClasses;
+use starknet_api::block::BlockHashAndNumber;
+use starknet_api::core::ChainId;
+use starknet_api::transaction::TransactionHash;
+use starknet_types_core::felt::Felt;
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+use super::DebugJsonRpcServer;
+use crate::api::{BlockId, CallRequest, JsonRpcServerTrait};
+use crate::subscriptions::Subscriptions;
+use crate::v0_8::api::api_impl::JsonRpcServerImpl as JsonRpcServerV0_8Impl;
+use crate::v0_8::api::{SimulatedTransaction, SimulationFlag};
+use crate::v0_8::broadcasted_transaction::BroadcastedTransaction;
+
+/// Rpc server of the debug namespace. The execution is done by the V0_8 server.
+#[derive(Clone)]
+pub struct DebugJsonRpcServerImpl {
+    pub inherited: JsonRpcServerV0_8Impl,
+}
+
+#[async_trait]
+impl DebugJsonRpcServer for DebugJsonRpcServerImpl {
+    #[instrument(skip(self), level = "debug", err)]
+    async fn trace_transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionCallTrace> {
+        self.inherited.trace_transaction_calls(transaction_hash).await
+    }
+
+    #[instrument(skip(self), level = "debug", err)]
+    async fn trace_call(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<CallTraceOutput> {
+        self.inherited.trace_call(request, block_id, state_overrides.unwrap_or_default()).await
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn call(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<Vec<Felt>> {
+        self.inherited
+            .call_with_state_overrides(request, block_id, state_overrides.unwrap_or_default())
+            .await
+    }
+
+    #[instrument(skip(self, request), level = "debug", err, ret)]
+    async fn estimate_fee(
+        &self,
+        request: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        block_id: BlockId,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<Vec<FeeEstimation>> {
+        self.inherited
+            .estimate_fee_with_state_overrides(
+                request,
+                simulation_flags,
+                block_id,
+                state_overrides.unwrap_or_default(),
+            )
+            .await
+    }
+
+    #[instrument(skip(self, request), level = "debug", err, ret)]
+    async fn estimate_resource_bounds(
+        &self,
+        request: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        block_id: BlockId,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<Vec<ResourceBoundsEstimation>> {
+        self.inherited
+            .estimate_resource_bounds(
+                request,
+                simulation_flags,
+                block_id,
+                state_overrides.unwrap_or_default(),
+            )
+            .await
+    }
+
+    #[instrument(skip(self, transactions), level = "debug", err, ret)]
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<Vec<SimulatedTransaction>> {
+        self.inherited
+            .simulate_transactions_with_state_overrides(
+                block_id,
+                transactions,
+                simulation_flags,
+                state_overrides.unwrap_or_default(),
+            )
+            .await
+    }
+}
+
+impl JsonRpcServerTrait for DebugJsonRpcServerImpl {
+    fn new(
+        chain_id: ChainId,
+        execution_config: ExecutionConfig,
+        storage_reader: StorageReader,
+        max_events_chunk_size: usize,
+        max_events_keys: usize,
+        max_events_block_range: u64,
+        starting_block: BlockHashAndNumber,
+        shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
+        pending_data: Arc<RwLock<PendingData>>,
+        pending_classes: Arc<RwLock<PendingClasses>>,
+        writer_client: Arc<dyn StarknetWriter>,
+        class_manager_client: Option<SharedClassManagerClient>,
+        subscriptions: Subscriptions,
+    ) -> Self {
+        Self {
+            inherited: JsonRpcServerV0_8Impl::new(
+                chain_id,
+                execution_config,
+                storage_reader,
+                max_events_chunk_size,
+                max_events_keys,
+                max_events_block_range,
+                starting_block,
+                shared_highest_block,
+                pending_data,
+                pending_classes,
+                writer_client,
+                class_manager_client,
+                subscriptions,
+            ),
+        }
+    }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        self.into_rpc()
+    }
+}
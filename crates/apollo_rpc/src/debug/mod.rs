@@ -0,0 +1,88 @@
+use apollo_rpc_execution::objects::{
+    CallTraceOutput,
+    FeeEstimation,
+    ResourceBoundsEstimation,
+    StateOverrides,
+    TransactionCallTrace,
+};
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use starknet_api::transaction::TransactionHash;
+use starknet_types_core::felt::Felt;
+
+use crate::api::{BlockId, CallRequest};
+use crate::v0_8::api::{SimulatedTransaction, SimulationFlag};
+use crate::v0_8::broadcasted_transaction::BroadcastedTransaction;
+
+pub mod api_impl;
+#[cfg(test)]
+mod test;
+
+/// The debug namespace, for inspecting the execution of calls and transactions. Its methods aren't
+/// versioned and use the objects of V0_8.
+///
+/// The state overrides are applied on top of the state of the given block. They set the nonce, the
+/// class hash, storage entries and the fee token balances of contracts, and overriding the class
+/// hash of an address without a contract deploys one.
+#[rpc(server, namespace = "debug")]
+#[async_trait]
+pub trait DebugJsonRpc {
+    /// Re-executes a transaction that is already included in a block, and returns the call trees
+    /// of its validation, execution and fee transfer with the storage each call accessed.
+    #[method(name = "traceTransaction")]
+    async fn trace_transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionCallTrace>;
+
+    /// Executes the entry point of the contract at the given address with the given calldata, and
+    /// returns its call tree with the storage each call accessed. Failed calls are traced as well.
+    #[method(name = "traceCall")]
+    async fn trace_call(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<CallTraceOutput>;
+
+    /// Same as starknet_call, on top of the given state overrides.
+    #[method(name = "call")]
+    async fn call(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<Vec<Felt>>;
+
+    /// Same as starknet_estimateFee, on top of the given state overrides.
+    #[method(name = "estimateFee")]
+    async fn estimate_fee(
+        &self,
+        request: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        block_id: BlockId,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<Vec<FeeEstimation>>;
+
+    /// Suggests the resource bounds of V3 transactions. The L2 gas bound of each transaction is
+    /// the minimal one found under which it succeeds, and the reverting transactions are reported
+    /// with their revert reason.
+    #[method(name = "estimateResourceBounds")]
+    async fn estimate_resource_bounds(
+        &self,
+        request: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        block_id: BlockId,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<Vec<ResourceBoundsEstimation>>;
+
+    /// Same as starknet_simulateTransactions, on top of the given state overrides.
+    #[method(name = "simulateTransactions")]
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        state_overrides: Option<StateOverrides>,
+    ) -> RpcResult<Vec<SimulatedTransaction>>;
+}
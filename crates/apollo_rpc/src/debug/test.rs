@@ -0,0 +1,88 @@
+use apollo_rpc_execution::execution_utils::selector_from_name;
+use apollo_rpc_execution::objects::{CallTraceOutput, ContractStateOverride, StateOverrides};
+use assert_matches::assert_matches;
+use indexmap::indexmap;
+use jsonrpsee::core::Error;
+use pretty_assertions::assert_eq;
+use starknet_api::block::BlockNumber;
+use starknet_api::state::StorageKey;
+use starknet_api::{calldata, class_hash, contract_address, felt};
+use starknet_types_core::felt::Felt;
+
+use super::api_impl::DebugJsonRpcServerImpl;
+use crate::api::{BlockHashOrNumber, BlockId, CallRequest};
+use crate::test_utils::get_test_rpc_server_and_storage_writer;
+use crate::v0_8::error::CONTRACT_NOT_FOUND;
+use crate::v0_8::execution_test::{prepare_storage_for_execution, CONTRACT_ADDRESS};
+
+#[tokio::test]
+async fn trace_call() {
+    let (module, storage_writer) =
+        get_test_rpc_server_and_storage_writer::<DebugJsonRpcServerImpl>();
+    prepare_storage_for_execution(storage_writer);
+
+    let key = felt!(1234_u16);
+    let value = felt!(18_u8);
+    let output = module
+        .call::<_, CallTraceOutput>(
+            "debug_traceCall",
+            (
+                CallRequest {
+                    contract_address: *CONTRACT_ADDRESS,
+                    entry_point_selector: selector_from_name("test_storage_read_write"),
+                    calldata: calldata![key, value],
+                },
+                BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(0))),
+                Option::<StateOverrides>::None,
+            ),
+        )
+        .await
+        .unwrap();
+
+    let storage_key = StorageKey::try_from(key).unwrap();
+    assert_eq!(output.call.result.0, vec![value]);
+    assert_eq!(output.call.accessed_storage_keys, vec![storage_key]);
+    assert_eq!(
+        output.storage_diffs,
+        indexmap!(*CONTRACT_ADDRESS => indexmap!(storage_key => value))
+    );
+}
+
+#[tokio::test]
+async fn call_with_state_overrides() {
+    let (module, storage_writer) =
+        get_test_rpc_server_and_storage_writer::<DebugJsonRpcServerImpl>();
+    prepare_storage_for_execution(storage_writer);
+
+    let key = felt!(1234_u16);
+    let value = felt!(18_u8);
+    let request = CallRequest {
+        contract_address: contract_address!("0x1234"),
+        entry_point_selector: selector_from_name("test_storage_read_write"),
+        calldata: calldata![key, value],
+    };
+    let block_id = BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(0)));
+
+    // Without overrides, there's no contract at the address.
+    let err = module
+        .call::<_, Vec<Felt>>(
+            "debug_call",
+            (request.clone(), block_id, Option::<StateOverrides>::None),
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == CONTRACT_NOT_FOUND.into());
+
+    // Overriding the class hash of the address deploys the class there.
+    let state_overrides: StateOverrides = indexmap!(
+        request.contract_address => ContractStateOverride {
+            class_hash: Some(class_hash!("0x2")),
+            ..Default::default()
+        }
+    );
+    let result = module
+        .call::<_, Vec<Felt>>("debug_call", (request, block_id, Some(state_overrides)))
+        .await
+        .unwrap();
+    assert_eq!(result, vec![value]);
+}
@@ -3,15 +3,20 @@
 #![cfg_attr(coverage_nightly, feature(coverage_attribute))]
 
 mod api;
+mod debug;
 mod middleware;
 mod pending;
+mod rate_limit;
 mod rpc_metrics;
 #[cfg(test)]
 mod rpc_test;
+mod streaming;
+mod subscriptions;
 mod syncing_state;
 #[cfg(test)]
 mod test_utils;
 mod v0_8;
+mod v0_9;
 mod version_config;
 
 use std::collections::BTreeMap;
@@ -22,6 +27,7 @@ use apollo_class_manager_types::SharedClassManagerClient;
 use apollo_config::dumping::{prepend_sub_config_name, ser_param, SerializeConfig};
 use apollo_config::validators::validate_ascii;
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use apollo_mempool_types::communication::SharedMempoolClient;
 use apollo_rpc_execution::ExecutionConfig;
 use apollo_starknet_client::reader::PendingData;
 use apollo_starknet_client::writer::StarknetGatewayClient;
@@ -32,7 +38,7 @@ use apollo_storage::db::TransactionKind;
 use apollo_storage::state::StateStorageReader;
 use apollo_storage::{StorageReader, StorageScope, StorageTxn};
 use jsonrpsee::core::RpcResult;
-use jsonrpsee::server::{ServerBuilder, ServerHandle};
+use jsonrpsee::server::{BatchRequestConfig, RandomStringIdProvider, ServerBuilder, ServerHandle};
 use jsonrpsee::types::error::ErrorCode::InternalError;
 use jsonrpsee::types::error::INTERNAL_ERROR_MSG;
 use jsonrpsee::types::ErrorObjectOwned;
@@ -51,6 +57,8 @@ use validator::Validate;
 
 use crate::api::get_methods_from_supported_apis;
 use crate::middleware::proxy_rpc_request;
+pub use crate::rate_limit::RateLimitConfig;
+use crate::rate_limit::{RateLimitLayer, RateLimitLogger};
 use crate::syncing_state::get_last_synced_block;
 pub use crate::v0_8::transaction::{
     InvokeTransaction as InvokeTransactionRPC0_8,
@@ -67,6 +75,9 @@ const GENESIS_HASH: &str = "0x0";
 /// Maximum size of a supported transaction body - 10MB.
 pub const SERVER_MAX_BODY_SIZE: u32 = 10 * 1024 * 1024;
 
+/// The number of characters in the ids of subscriptions.
+const SUBSCRIPTION_ID_LENGTH: usize = 16;
+
 #[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Validate)]
 pub struct RpcConfig {
     #[validate(custom = "validate_ascii")]
@@ -75,11 +86,20 @@ pub struct RpcConfig {
     pub port: u16,
     pub max_events_chunk_size: usize,
     pub max_events_keys: usize,
+    #[validate(range(min = 1))]
+    pub max_events_block_range: u64,
+    pub max_subscriptions_per_connection: u32,
+    pub max_batch_request_length: u32,
+    pub max_batch_cost: u64,
+    pub max_response_body_size: u32,
     // TODO(lev,shahak): remove once we remove papyrus.
     pub collect_metrics: bool,
+    pub enable_debug_api: bool,
     pub starknet_url: String,
     pub apollo_gateway_retry_config: RetryConfig,
     pub execution_config: ExecutionConfig,
+    #[validate]
+    pub rate_limit_config: RateLimitConfig,
 }
 
 impl Default for RpcConfig {
@@ -90,7 +110,13 @@ impl Default for RpcConfig {
             port: 8090,
             max_events_chunk_size: 1000,
             max_events_keys: 100,
+            max_events_block_range: 10000,
+            max_subscriptions_per_connection: 100,
+            max_batch_request_length: 100,
+            max_batch_cost: 500,
+            max_response_body_size: SERVER_MAX_BODY_SIZE,
             collect_metrics: false,
+            enable_debug_api: false,
             starknet_url: String::from("https://alpha-mainnet.starknet.io/"),
             apollo_gateway_retry_config: RetryConfig {
                 retry_base_millis: 50,
@@ -98,6 +124,7 @@ impl Default for RpcConfig {
                 max_retries: 5,
             },
             execution_config: ExecutionConfig::default(),
+            rate_limit_config: RateLimitConfig::default(),
         }
     }
 }
@@ -134,12 +161,59 @@ impl SerializeConfig for RpcConfig {
                 "Maximum number of keys supported by the node in get_events requests.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "max_events_block_range",
+                &self.max_events_block_range,
+                "Maximum number of blocks scanned by a single get_events request. If the range of \
+                 the request is larger, the response has a continuation token that points to the \
+                 next block.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_subscriptions_per_connection",
+                &self.max_subscriptions_per_connection,
+                "Maximum number of active subscriptions of a WebSocket connection.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_batch_request_length",
+                &self.max_batch_request_length,
+                "Maximum number of requests in a batch. Larger batches are rejected without \
+                 executing any of their requests.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_batch_cost",
+                &self.max_batch_cost,
+                "Maximum total weight of the requests in a batch, with the method weights of the \
+                 rate limit config, even if rate limiting is disabled. Costlier batches are \
+                 rejected without executing any of their requests. Single requests and batches \
+                 over a WebSocket aren't limited.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_response_body_size",
+                &self.max_response_body_size,
+                "Maximum size in bytes of a response, or of the whole response to a batch. The \
+                 serialization of a response stops once it exceeds this size, and an error is \
+                 returned instead. The transactions of the blocks of getBlockWithTxs and \
+                 getBlockWithReceipts and the events of getEvents are read from the storage \
+                 while they're serialized, so their reading stops there as well.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "collect_metrics",
                 &self.collect_metrics,
                 "If true, collect metrics for the rpc.",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "enable_debug_api",
+                &self.enable_debug_api,
+                "If true, serve the debug namespace, which traces calls and transactions and \
+                 executes them with state overrides.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "starknet_url",
                 &self.starknet_url,
@@ -150,6 +224,10 @@ impl SerializeConfig for RpcConfig {
 
         self_params_dump
             .append(&mut prepend_sub_config_name(self.execution_config.dump(), "execution_config"));
+        self_params_dump.append(&mut prepend_sub_config_name(
+            self.rate_limit_config.dump(),
+            "rate_limit_config",
+        ));
         let mut retry_config_dump = prepend_sub_config_name(
             self.apollo_gateway_retry_config.dump(),
             "apollo_gateway_retry_config",
@@ -181,7 +259,7 @@ fn verify_storage_scope(storage_reader: &StorageReader) -> RpcResult<()> {
         StorageScope::StateOnly => {
             Err(internal_server_error_with_msg("Unsupported method in state-only scope."))
         }
-        StorageScope::FullArchive => Ok(()),
+        StorageScope::FullArchive | StorageScope::Pruned { .. } => Ok(()),
     }
 }
 
@@ -209,7 +287,8 @@ fn get_block_status<Mode: TransactionKind>(
 #[derive(Clone, Debug, PartialEq)]
 struct ContinuationTokenAsStruct(EventIndex);
 
-#[instrument(skip(storage_reader, class_manager_client), level = "debug", err)]
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip(storage_reader, class_manager_client, mempool_client), level = "debug", err)]
 pub async fn run_server(
     config: &RpcConfig,
     shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
@@ -218,15 +297,17 @@ pub async fn run_server(
     storage_reader: StorageReader,
     node_version: &'static str,
     class_manager_client: Option<SharedClassManagerClient>,
+    mempool_client: Option<SharedMempoolClient>,
 ) -> anyhow::Result<(SocketAddr, ServerHandle)> {
     let starting_block = get_last_synced_block(storage_reader.clone())?;
     debug!("Starting JSON-RPC.");
     let methods = get_methods_from_supported_apis(
         &config.chain_id,
-        config.execution_config,
+        config.execution_config.clone(),
         storage_reader,
         config.max_events_chunk_size,
         config.max_events_keys,
+        config.max_events_block_range,
         starting_block,
         shared_highest_block,
         pending_data,
@@ -237,22 +318,34 @@ pub async fn run_server(
             config.apollo_gateway_retry_config,
         )?),
         class_manager_client,
+        mempool_client,
+        config.enable_debug_api,
     );
+    let rate_limit_layer = RateLimitLayer::new(&config.rate_limit_config, config.max_batch_cost);
+    let methods = rate_limit_layer.limit_websocket_calls(methods);
     let addr;
     let handle;
     let server_builder = ServerBuilder::default()
         .max_request_body_size(SERVER_MAX_BODY_SIZE)
-        .set_middleware(tower::ServiceBuilder::new().filter_async(proxy_rpc_request));
+        .max_response_body_size(config.max_response_body_size)
+        .set_batch_request_config(BatchRequestConfig::Limit(config.max_batch_request_length))
+        .max_subscriptions_per_connection(config.max_subscriptions_per_connection)
+        .set_id_provider(RandomStringIdProvider::new(SUBSCRIPTION_ID_LENGTH))
+        .set_middleware(
+            tower::ServiceBuilder::new().filter_async(proxy_rpc_request).layer(rate_limit_layer),
+        );
 
     let server_address = SocketAddr::new(config.ip, config.port);
 
     if config.collect_metrics {
-        let server =
-            server_builder.set_logger(MetricLogger::new(&methods)).build(&server_address).await?;
+        let server = server_builder
+            .set_logger((MetricLogger::new(&methods), RateLimitLogger))
+            .build(&server_address)
+            .await?;
         addr = server.local_addr()?;
         handle = server.start(methods);
     } else {
-        let server = server_builder.build(&server_address).await?;
+        let server = server_builder.set_logger(RateLimitLogger).build(&server_address).await?;
         addr = server.local_addr()?;
         handle = server.start(methods);
     }
@@ -1,16 +1,28 @@
+#[cfg(test)]
+#[path = "middleware_test.rs"]
+mod middleware_test;
+
+use hyper::header::UPGRADE;
 use hyper::{Body, Request};
 use jsonrpsee::core::http_helpers::read_body;
 use regex::Regex;
 use tower::BoxError;
 use tracing::debug;
 
-use crate::version_config::{VERSION_0_8, VERSION_PATTERN};
+use crate::version_config::{VersionState, DEFAULT_VERSION, VERSION_CONFIG, VERSION_PATTERN};
 use crate::SERVER_MAX_BODY_SIZE;
 
-/// [`Tower`] middleware intended to proxy method requests to the last supported version of the API,
-/// which is V0_8. The middleware reads the JsonRPC request body and request path then prefixes the
-/// method name with the appropriate version identifier. It returns a new [`hyper::Request`] object
-/// with the new method name.
+const DEBUG_METHOD_PREFIX: &str = "debug_";
+
+/// [`Tower`] middleware intended to proxy method requests to the version of the API given in the
+/// request path (e.g. /rpc/v0_8), or to [`DEFAULT_VERSION`] if the path has no version. The
+/// middleware reads the JsonRPC request body and request path then prefixes the method name with
+/// the appropriate version identifier. It returns a new [`hyper::Request`] object with the new
+/// method name.
+///
+/// Requests over WebSocket are sent after the upgrade, so they can't be routed. They call the
+/// unversioned names of the methods, which are served by [`DEFAULT_VERSION`], so upgrades on the
+/// path of another version are rejected.
 ///
 /// # Arguments
 /// * req - [`hyper::Request`] object passed by the server.
@@ -24,7 +36,19 @@ pub(crate) async fn proxy_rpc_request(req: Request<Body>) -> Result<Request<Body
         return Err(BoxError::from("Unsupported path for request"));
     }
 
-    let prefix = VERSION_0_8.name;
+    if is_websocket_upgrade_request(&req) {
+        if !is_websocket_path_supported(req.uri().path()) {
+            return Err(BoxError::from(format!(
+                "WebSocket is served only for version {}",
+                DEFAULT_VERSION.name
+            )));
+        }
+        return Ok(req);
+    }
+
+    let Some(prefix) = get_requested_version(req.uri().path()) else {
+        return Err(BoxError::from("Unsupported version for request"));
+    };
     let (parts, body) = req.into_parts();
     let (body_bytes, is_single) =
         read_body(&parts.headers, body, SERVER_MAX_BODY_SIZE).await.map_err(BoxError::from)?;
@@ -50,6 +74,10 @@ fn add_version_to_method_name_in_body(
     let Ok(vec_body) = vec_body
         .iter_mut()
         .map(|body| {
+            // The methods of the debug namespace aren't versioned.
+            if body.method.starts_with(DEBUG_METHOD_PREFIX) {
+                return Ok(body);
+            }
             let Some(stripped_method) = strip_starknet_from_method(body.method.as_ref()) else {
                 return Err(BoxError::from("Method name has unexpected format"));
             };
@@ -74,6 +102,38 @@ fn strip_starknet_from_method(method: &str) -> Option<&str> {
     split_method_name.get(1).copied()
 }
 
+fn is_websocket_upgrade_request(req: &Request<Body>) -> bool {
+    req.headers()
+        .get(UPGRADE)
+        .is_some_and(|upgrade| upgrade.as_bytes().eq_ignore_ascii_case(b"websocket"))
+}
+
+// A WebSocket is served on a path without a version or with the version whose methods are served
+// under their unversioned names.
+fn is_websocket_path_supported(path: &str) -> bool {
+    !path.starts_with("/rpc/") || get_requested_version(path) == Some(DEFAULT_VERSION.name)
+}
+
+// Returns the name of the served version that the path requests. A path without a version requests
+// the default version.
+fn get_requested_version(path: &str) -> Option<&'static str> {
+    let Some(requested_version) = path.strip_prefix("/rpc/") else {
+        return Some(DEFAULT_VERSION.name);
+    };
+    let mut served_versions = VERSION_CONFIG
+        .iter()
+        .filter(|(_, version_state)| {
+            matches!(version_state, VersionState::Supported | VersionState::Experimental)
+        })
+        .map(|(version, _)| version);
+    let requested_version = requested_version.to_uppercase();
+    served_versions
+        .find(|version| {
+            requested_version == version.name || requested_version == version.to_string()
+        })
+        .map(|version| version.name)
+}
+
 fn is_supported_path(path: &str) -> bool {
     let re = Regex::new((r"^\/rpc(\/".to_string() + VERSION_PATTERN + ")?$").as_str())
         .expect("should be a valid regex");
@@ -0,0 +1,42 @@
+use pretty_assertions::assert_eq;
+
+use super::{
+    add_version_to_method_name_in_body,
+    get_requested_version,
+    is_websocket_path_supported,
+};
+use crate::version_config::{VERSION_0_8, VERSION_0_9};
+
+#[test]
+fn requested_version_by_path() {
+    assert_eq!(get_requested_version("/rpc"), Some(VERSION_0_8.name));
+    assert_eq!(get_requested_version("/rpc/v0_8"), Some(VERSION_0_8.name));
+    assert_eq!(get_requested_version("/rpc/V0_8_0"), Some(VERSION_0_8.name));
+    assert_eq!(get_requested_version("/rpc/v0_9"), Some(VERSION_0_9.name));
+    assert_eq!(get_requested_version("/rpc/v0_7"), None);
+}
+
+#[test]
+fn websocket_path_by_version() {
+    assert!(is_websocket_path_supported("/rpc"));
+    assert!(is_websocket_path_supported("/rpc/v0_8"));
+    assert!(is_websocket_path_supported("/rpc/V0_8_0"));
+    assert!(!is_websocket_path_supported("/rpc/v0_9"));
+    assert!(!is_websocket_path_supported("/rpc/v0_7"));
+}
+
+#[test]
+fn debug_methods_are_not_versioned() {
+    let body = r#"[
+        {"jsonrpc": "2.0", "id": 1, "method": "debug_traceCall"},
+        {"jsonrpc": "2.0", "id": 2, "method": "starknet_call"}
+    ]"#;
+    let requests = serde_json::from_str::<Vec<jsonrpsee::types::Request<'_>>>(body).unwrap();
+
+    let new_body = add_version_to_method_name_in_body(requests, VERSION_0_9.name, false).unwrap();
+
+    let new_requests =
+        serde_json::from_slice::<Vec<jsonrpsee::types::Request<'_>>>(&new_body).unwrap();
+    let methods = new_requests.iter().map(|request| request.method.as_ref()).collect::<Vec<_>>();
+    assert_eq!(methods, vec!["debug_traceCall", "starknet_V0_9_call"]);
+}
@@ -0,0 +1,751 @@
+#[cfg(test)]
+#[path = "rate_limit_test.rs"]
+mod rate_limit_test;
+
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use apollo_config::dumping::{ser_param, SerializeConfig};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
+use hyper::header::{HeaderValue, CONTENT_TYPE, UPGRADE};
+use hyper::{Body, HeaderMap, Request, Response, StatusCode};
+use jsonrpsee::core::http_helpers::read_body;
+use jsonrpsee::server::logger::{HttpRequest, Logger, MethodKind, TransportProtocol};
+use jsonrpsee::types::error::{TOO_BIG_BATCH_REQUEST_CODE, TOO_BIG_BATCH_REQUEST_MSG};
+use jsonrpsee::types::{ErrorObjectOwned, Params};
+use jsonrpsee::{ConnectionId, MethodCallback, MethodResponse, Methods};
+use metrics::counter;
+use serde::de::Error as DeserializationError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tower::{BoxError, Layer, Service};
+use tracing::debug;
+use validator::Validate;
+
+use crate::SERVER_MAX_BODY_SIZE;
+
+// The error code that Ethereum nodes return when a limit is exceeded (EIP-1474).
+const LIMIT_EXCEEDED_CODE: i32 = -32005;
+const RATE_LIMIT_EXCEEDED_MSG: &str = "Rate limit exceeded";
+const CONCURRENCY_LIMIT_EXCEEDED_MSG: &str = "Too many concurrent execution requests";
+
+// Name and labels of the metric of the rejected requests.
+const RATE_LIMITED_REQUESTS: &str = "rpc_rate_limited_requests";
+const REASON_LABEL: &str = "reason";
+const RATE_LIMIT_REASON: &str = "rate_limit";
+const CONCURRENCY_LIMIT_REASON: &str = "concurrency_limit";
+const BATCH_COST_REASON: &str = "batch_cost";
+
+// Headers that proxies in front of the node use to pass the IP of the client.
+const FORWARDED_FOR_HEADER: &str = "x-forwarded-for";
+const REAL_IP_HEADER: &str = "x-real-ip";
+
+// Above this number of tracked clients, the clients whose budget is full are forgotten.
+const MAX_TRACKED_CLIENTS: usize = 10000;
+
+/// Limits on the requests of each client of the JSON RPC server. A client is identified by the API
+/// key in its requests, if it's one of the configured keys, or else by its IP. The IP of the client
+/// is the one of the peer of the connection, unless the peer is a trusted proxy, which passes the
+/// IP of the client in a header. The calls over a WebSocket are limited per connection.
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Validate)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    #[validate(range(min = 1))]
+    pub points_per_second: u64,
+    #[validate(range(min = 1))]
+    pub burst_points: u64,
+    pub read_method_weight: u64,
+    pub block_method_weight: u64,
+    pub events_method_weight: u64,
+    pub execution_method_weight: u64,
+    pub trace_method_weight: u64,
+    #[validate(range(min = 1))]
+    pub max_concurrent_execution_requests: usize,
+    pub api_key_header: String,
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated"
+    )]
+    pub api_keys: Vec<String>,
+    #[serde(
+        serialize_with = "serialize_comma_separated",
+        deserialize_with = "deserialize_comma_separated"
+    )]
+    pub trusted_proxies: Vec<IpAddr>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        RateLimitConfig {
+            enabled: false,
+            points_per_second: 100,
+            burst_points: 200,
+            read_method_weight: 1,
+            block_method_weight: 10,
+            events_method_weight: 10,
+            execution_method_weight: 20,
+            trace_method_weight: 50,
+            max_concurrent_execution_requests: 32,
+            api_key_header: String::from("x-api-key"),
+            api_keys: Vec::new(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+impl SerializeConfig for RateLimitConfig {
+    fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
+        BTreeMap::from_iter([
+            ser_param(
+                "enabled",
+                &self.enabled,
+                "If true, limit the rate of the requests of each client and the number of \
+                 concurrent execution requests.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "points_per_second",
+                &self.points_per_second,
+                "The number of points a client gains every second. Each method call costs the \
+                 weight of the method.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "burst_points",
+                &self.burst_points,
+                "The maximal number of points a client can accumulate.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "read_method_weight",
+                &self.read_method_weight,
+                "The cost of a call to a method that reads from the storage.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "block_method_weight",
+                &self.block_method_weight,
+                "The cost of a call to a method that returns a whole block (getBlockWithTxs, \
+                 getBlockWithReceipts and getStateUpdate).",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "events_method_weight",
+                &self.events_method_weight,
+                "The cost of a call to getEvents.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "execution_method_weight",
+                &self.execution_method_weight,
+                "The cost of a call to a method that executes transactions (call, estimateFee, \
+                 estimateMessageFee and simulateTransactions).",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "trace_method_weight",
+                &self.trace_method_weight,
+                "The cost of a call to a method that traces or repeatedly executes transactions \
+                 (traceTransaction, traceBlockTransactions, traceCall and estimateResourceBounds).",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "max_concurrent_execution_requests",
+                &self.max_concurrent_execution_requests,
+                "Maximum number of execution and trace calls the server runs at the same time, \
+                 over all the clients.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "api_key_header",
+                &self.api_key_header,
+                "The header with the API key that identifies the client. Requests without one of \
+                 the configured API keys are identified by the IP of the client.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "api_keys",
+                &join_comma_separated(&self.api_keys),
+                "Comma-separated API keys, each identifying a client with its own budget. Other \
+                 keys are ignored.",
+                ParamPrivacyInput::Private,
+            ),
+            ser_param(
+                "trusted_proxies",
+                &join_comma_separated(&self.trusted_proxies),
+                "Comma-separated IPs of the proxies in front of the node. The IP of the client of \
+                 the requests they pass is taken from the X-Forwarded-For or X-Real-IP header. \
+                 The headers of requests from other peers are ignored.",
+                ParamPrivacyInput::Public,
+            ),
+        ])
+    }
+}
+
+impl RateLimitConfig {
+    fn weight(&self, method_class: MethodClass) -> u64 {
+        match method_class {
+            MethodClass::Read => self.read_method_weight,
+            MethodClass::Block => self.block_method_weight,
+            MethodClass::Events => self.events_method_weight,
+            MethodClass::Execution => self.execution_method_weight,
+            MethodClass::Trace => self.trace_method_weight,
+        }
+    }
+
+    // The total weight of calls to methods of the given classes.
+    fn cost(&self, method_classes: &[MethodClass]) -> u64 {
+        method_classes.iter().map(|class| self.weight(*class)).fold(0, u64::saturating_add)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MethodClass {
+    Read,
+    Block,
+    Events,
+    Execution,
+    Trace,
+}
+
+impl MethodClass {
+    fn of(method: &str) -> Self {
+        // The method name may be prefixed with its namespace and version (e.g. starknet_V0_8_call).
+        match method.rsplit('_').next().unwrap_or(method) {
+            "traceTransaction"
+            | "traceBlockTransactions"
+            | "traceCall"
+            | "estimateResourceBounds" => MethodClass::Trace,
+            "call" | "estimateFee" | "estimateMessageFee" | "simulateTransactions" => {
+                MethodClass::Execution
+            }
+            "getEvents" => MethodClass::Events,
+            "getBlockWithTxs" | "getBlockWithReceipts" | "getStateUpdate" => MethodClass::Block,
+            _ => MethodClass::Read,
+        }
+    }
+
+    fn is_execution(&self) -> bool {
+        matches!(self, MethodClass::Execution | MethodClass::Trace)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Rejection {
+    RateLimit { retry_after: Duration },
+    ConcurrencyLimit,
+    BatchCost { cost: u64, max_batch_cost: u64 },
+}
+
+impl Rejection {
+    fn reason(&self) -> &'static str {
+        match self {
+            Rejection::RateLimit { .. } => RATE_LIMIT_REASON,
+            Rejection::ConcurrencyLimit => CONCURRENCY_LIMIT_REASON,
+            Rejection::BatchCost { .. } => BATCH_COST_REASON,
+        }
+    }
+
+    fn error_object(&self) -> ErrorObjectOwned {
+        match self {
+            Rejection::RateLimit { retry_after } => ErrorObjectOwned::owned(
+                LIMIT_EXCEEDED_CODE,
+                RATE_LIMIT_EXCEEDED_MSG,
+                Some(json!({ "retry_after_ms": retry_after.as_millis() })),
+            ),
+            Rejection::ConcurrencyLimit => ErrorObjectOwned::owned(
+                LIMIT_EXCEEDED_CODE,
+                CONCURRENCY_LIMIT_EXCEEDED_MSG,
+                None::<()>,
+            ),
+            Rejection::BatchCost { cost, max_batch_cost } => ErrorObjectOwned::owned(
+                TOO_BIG_BATCH_REQUEST_CODE,
+                TOO_BIG_BATCH_REQUEST_MSG,
+                Some(json!({ "cost": cost, "max_batch_cost": max_batch_cost })),
+            ),
+        }
+    }
+}
+
+// Logs the rejection of a request or a call and counts it in the metrics.
+fn report_rejection(client: &str, rejection: &Rejection) {
+    debug!(%client, ?rejection, "Rejecting request.");
+    counter!(RATE_LIMITED_REQUESTS, REASON_LABEL => rejection.reason()).increment(1);
+}
+
+// The configured points are far below 2^53, so they're converted exactly.
+#[allow(clippy::as_conversions)]
+fn points_as_f64(points: u64) -> f64 {
+    points as f64
+}
+
+// The budget of a single client.
+#[derive(Debug)]
+struct TokenBucket {
+    points: f64,
+    last_refill: Instant,
+}
+
+struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    execution_permits: Arc<Semaphore>,
+}
+
+impl RateLimiter {
+    fn new(config: RateLimitConfig) -> Self {
+        let execution_permits = Arc::new(Semaphore::new(config.max_concurrent_execution_requests));
+        Self { config, buckets: Mutex::new(HashMap::new()), execution_permits }
+    }
+
+    // Admits the calls of a request of the client if the client has enough points for all of them
+    // and there are enough free execution slots. Returns the execution slots taken by the request,
+    // which should be held until the request is handled.
+    fn admit(
+        &self,
+        client: &str,
+        method_classes: &[MethodClass],
+        now: Instant,
+    ) -> Result<Option<OwnedSemaphorePermit>, Rejection> {
+        let execution_calls = method_classes.iter().filter(|class| class.is_execution()).count();
+        let permit = match execution_calls {
+            0 => None,
+            _ => {
+                let slots = execution_calls.min(self.config.max_concurrent_execution_requests);
+                let slots = u32::try_from(slots).expect("Number of slots should fit in u32.");
+                Some(
+                    self.execution_permits
+                        .clone()
+                        .try_acquire_many_owned(slots)
+                        .map_err(|_| Rejection::ConcurrencyLimit)?,
+                )
+            }
+        };
+        // A request that costs more than the burst is admitted when the budget of the client is
+        // full, so that every method remains callable.
+        let burst = points_as_f64(self.config.burst_points);
+        let cost = points_as_f64(self.config.cost(method_classes).min(self.config.burst_points));
+
+        let mut buckets = self.buckets.lock().expect("Rate limiter lock should not be poisoned.");
+        if buckets.len() >= MAX_TRACKED_CLIENTS {
+            // A client with a full budget is the same as a new client.
+            buckets.retain(|_, bucket| self.refill(bucket, now) < burst);
+        }
+        let bucket = buckets
+            .entry(client.to_string())
+            .or_insert(TokenBucket { points: burst, last_refill: now });
+        let points = self.refill(bucket, now);
+        if points < cost {
+            let retry_after = Duration::from_secs_f64(
+                (cost - points) / points_as_f64(self.config.points_per_second),
+            );
+            return Err(Rejection::RateLimit { retry_after });
+        }
+        bucket.points -= cost;
+        Ok(permit)
+    }
+
+    // Adds the points the client gained since the last refill and returns its points.
+    fn refill(&self, bucket: &mut TokenBucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.last_refill).as_secs_f64();
+        bucket.points = (bucket.points + elapsed * points_as_f64(self.config.points_per_second))
+            .min(points_as_f64(self.config.burst_points));
+        bucket.last_refill = bucket.last_refill.max(now);
+        bucket.points
+    }
+
+    // Identifies the client of a request with the given headers from the given peer.
+    fn client_id(&self, headers: &HeaderMap, peer_address: SocketAddr) -> String {
+        let header = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+        };
+        if let Some(api_key) = header(&self.config.api_key_header) {
+            if let Some(index) = self.config.api_keys.iter().position(|key| key == api_key) {
+                // The key is identified by its index, so that it isn't logged.
+                return format!("key:{index}");
+            }
+        }
+        let is_trusted_proxy = |ip: &IpAddr| self.config.trusted_proxies.contains(ip);
+        let peer_ip = peer_address.ip();
+        if !is_trusted_proxy(&peer_ip) {
+            return format!("ip:{peer_ip}");
+        }
+        // Each proxy appends the address it got the request from to X-Forwarded-For, so the client
+        // is the last address that isn't of a trusted proxy.
+        let forwarded_ip = header(FORWARDED_FOR_HEADER)
+            .and_then(|forwarded_for| {
+                forwarded_for
+                    .rsplit(',')
+                    .map_while(|ip| ip.trim().parse::<IpAddr>().ok())
+                    .find(|ip| !is_trusted_proxy(ip))
+            })
+            .or_else(|| header(REAL_IP_HEADER).and_then(|ip| ip.parse().ok()));
+        format!("ip:{}", forwarded_ip.unwrap_or(peer_ip))
+    }
+
+    // Admits a call to a method of the given class if it's made over a WebSocket and the
+    // connection has enough points for it. Calls over HTTP are admitted by the layer.
+    fn admit_websocket_call(
+        &self,
+        connection_id: ConnectionId,
+        method_class: MethodClass,
+    ) -> Result<Option<OwnedSemaphorePermit>, Rejection> {
+        if !matches!(CALL_TRANSPORT.take(), Some(TransportProtocol::WebSocket)) {
+            return Ok(None);
+        }
+        let client = format!("connection:{connection_id}");
+        self.admit(&client, &[method_class], Instant::now()).inspect_err(|rejection| {
+            report_rejection(&client, rejection);
+        })
+    }
+}
+
+thread_local! {
+    // The transport of the call that the server is about to make on this thread. The server makes
+    // the call right after it passes it to its logger, without yielding in between.
+    static CALL_TRANSPORT: Cell<Option<TransportProtocol>> = const { Cell::new(None) };
+}
+
+// The slot in the extensions of a request for the address of its peer, which the server passes
+// only to its logger.
+#[derive(Clone, Default)]
+struct PeerAddress(Arc<OnceLock<SocketAddr>>);
+
+/// Logger that passes to the rate limiter what the server passes only to its logger: the address
+/// of the peer of each request and the transport of each call. Must be set on every server with a
+/// [`RateLimitLayer`].
+#[derive(Clone)]
+pub(crate) struct RateLimitLogger;
+
+impl Logger for RateLimitLogger {
+    type Instant = ();
+
+    fn on_connect(&self, remote_addr: SocketAddr, request: &HttpRequest, _t: TransportProtocol) {
+        if let Some(peer_address) = request.extensions().get::<PeerAddress>() {
+            // The slot is new for every request.
+            let _ = peer_address.0.set(remote_addr);
+        }
+    }
+
+    fn on_request(&self, _transport: TransportProtocol) -> Self::Instant {}
+
+    fn on_call(
+        &self,
+        _method_name: &str,
+        _params: Params<'_>,
+        _kind: MethodKind,
+        transport: TransportProtocol,
+    ) {
+        CALL_TRANSPORT.set(Some(transport));
+    }
+
+    fn on_result(
+        &self,
+        _method_name: &str,
+        _success_or_error: jsonrpsee::helpers::MethodResponseResult,
+        _started_at: Self::Instant,
+        _transport: TransportProtocol,
+    ) {
+    }
+
+    fn on_response(&self, _result: &str, _started_at: Self::Instant, _t: TransportProtocol) {}
+
+    fn on_disconnect(&self, _remote_addr: SocketAddr, _transport: TransportProtocol) {}
+}
+
+// The fields of a JSON RPC call that are needed in order to charge it and to reject it.
+#[derive(Deserialize)]
+struct RpcCall {
+    #[serde(default)]
+    id: Value,
+    method: String,
+}
+
+/// [`Tower`] layer that limits the requests of each client of the server and the number of
+/// execution requests that run concurrently. Rejected calls are answered with a JsonRPC error
+/// without reaching the server. Opening a WebSocket costs a single read, and the calls over it are
+/// charged by the methods that [`RateLimitLayer::limit_websocket_calls`] returns.
+///
+/// Regardless of whether rate limiting is enabled, an HTTP batch whose calls weigh more than the
+/// maximal batch cost is rejected before any of its calls is handled. Batches over a WebSocket
+/// aren't checked.
+///
+/// Must be the innermost layer of a server whose logger is a [`RateLimitLogger`], as the address
+/// of the peer of a request is known only once the request is given to the server.
+///
+/// [`Tower`]: https://crates.io/crates/tower
+#[derive(Clone)]
+pub(crate) struct RateLimitLayer {
+    limiter: Option<Arc<RateLimiter>>,
+    batch_limit: Arc<BatchLimit>,
+}
+
+impl RateLimitLayer {
+    pub(crate) fn new(config: &RateLimitConfig, max_batch_cost: u64) -> Self {
+        Self {
+            limiter: config.enabled.then(|| Arc::new(RateLimiter::new(config.clone()))),
+            batch_limit: Arc::new(BatchLimit { config: config.clone(), max_batch_cost }),
+        }
+    }
+
+    /// Returns the given methods, with the calls over a WebSocket charged to the connection they're
+    /// made on, as they don't pass through the layer. Unsubscribing is free.
+    pub(crate) fn limit_websocket_calls(&self, methods: Methods) -> Methods {
+        let Some(limiter) = &self.limiter else {
+            return methods;
+        };
+        let mut limited_methods = Methods::new();
+        for method_name in methods.method_names() {
+            let callback = methods.method(method_name).expect("Listed method should exist").clone();
+            limited_methods
+                .verify_and_insert(
+                    method_name,
+                    limit_websocket_calls_of(limiter.clone(), method_name, callback),
+                )
+                .expect("Method names should be unique");
+        }
+        limited_methods
+    }
+}
+
+// Wraps the callback of the given method so that its calls over a WebSocket are charged.
+fn limit_websocket_calls_of(
+    limiter: Arc<RateLimiter>,
+    method_name: &str,
+    callback: MethodCallback,
+) -> MethodCallback {
+    let method_class = MethodClass::of(method_name);
+    match callback {
+        // Synchronous methods don't get the connection, so they're called as asynchronous ones.
+        MethodCallback::Sync(method) => {
+            MethodCallback::Async(Arc::new(move |id, params, connection_id, max_response_size| {
+                let response = match limiter.admit_websocket_call(connection_id, method_class) {
+                    Ok(_execution_permit) => method(id, params, max_response_size),
+                    Err(rejection) => MethodResponse::error(id, rejection.error_object()),
+                };
+                Box::pin(async move { response })
+            }))
+        }
+        MethodCallback::Async(method) => {
+            MethodCallback::Async(Arc::new(move |id, params, connection_id, max_response_size| {
+                match limiter.admit_websocket_call(connection_id, method_class) {
+                    Ok(execution_permit) => {
+                        let response = method(id, params, connection_id, max_response_size);
+                        Box::pin(async move {
+                            let response = response.await;
+                            drop(execution_permit);
+                            response
+                        })
+                    }
+                    Err(rejection) => {
+                        let response = MethodResponse::error(id, rejection.error_object());
+                        Box::pin(async move { response })
+                    }
+                }
+            }))
+        }
+        MethodCallback::Subscription(method) => {
+            MethodCallback::Subscription(Arc::new(move |id, params, sink, state| {
+                match limiter.admit_websocket_call(state.conn_id, method_class) {
+                    Ok(_) => method(id, params, sink, state),
+                    // The callback of a subscription sends its response by itself.
+                    Err(rejection) => {
+                        let response = MethodResponse::error(id, rejection.error_object());
+                        Box::pin(async move {
+                            let _ = sink.send(response.result.clone()).await;
+                            Ok(response)
+                        })
+                    }
+                }
+            }))
+        }
+        unsubscription @ MethodCallback::Unsubscription(_) => unsubscription,
+    }
+}
+
+// The bound on the total weight of the calls in a batch.
+struct BatchLimit {
+    config: RateLimitConfig,
+    max_batch_cost: u64,
+}
+
+impl BatchLimit {
+    fn check(&self, calls: &[RpcCall]) -> Result<(), Rejection> {
+        let method_classes =
+            calls.iter().map(|call| MethodClass::of(&call.method)).collect::<Vec<_>>();
+        let cost = self.config.cost(&method_classes);
+        if cost > self.max_batch_cost {
+            return Err(Rejection::BatchCost { cost, max_batch_cost: self.max_batch_cost });
+        }
+        Ok(())
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            limiter: self.limiter.clone(),
+            batch_limit: self.batch_limit.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct RateLimitService<S> {
+    inner: S,
+    limiter: Option<Arc<RateLimiter>>,
+    batch_limit: Arc<BatchLimit>,
+}
+
+impl<S> Service<Request<Body>> for RateLimitService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, req: Request<Body>) -> Self::Future {
+        let limiter = self.limiter.clone();
+        let batch_limit = self.batch_limit.clone();
+        // The inner service that was polled ready is the one that should handle the request.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(async move {
+            let (mut req, calls, is_single) = read_calls(req).await?;
+            // A batch is rejected before the server is given it, so none of its calls runs.
+            let batch_check =
+                calls.as_deref().filter(|_| !is_single).map(|calls| batch_limit.check(calls));
+            if let Some(Err(rejection)) = batch_check {
+                debug!(?rejection, "Rejecting batch request.");
+                counter!(RATE_LIMITED_REQUESTS, REASON_LABEL => rejection.reason()).increment(1);
+                return Ok(rejection_response(&rejection, calls, is_single));
+            }
+            let Some(limiter) = limiter else {
+                return inner.call(req).await.map_err(Into::into);
+            };
+            let peer_address = PeerAddress::default();
+            req.extensions_mut().insert(peer_address.clone());
+            let headers = req.headers().clone();
+            // The server passes the address of the peer to its logger when it's given the request,
+            // but handles the request only once the response is polled, so the request can still
+            // be rejected.
+            let response = inner.call(req);
+            let Some(peer_address) = peer_address.0.get().copied() else {
+                // The server refused the request by itself.
+                return response.await.map_err(Into::into);
+            };
+            let client = limiter.client_id(&headers, peer_address);
+            let method_classes = match &calls {
+                Some(calls) => calls.iter().map(|call| MethodClass::of(&call.method)).collect(),
+                // Requests that aren't JsonRPC calls are charged as a single read.
+                None => vec![MethodClass::Read],
+            };
+            match limiter.admit(&client, &method_classes, Instant::now()) {
+                Ok(execution_permit) => {
+                    let response = response.await.map_err(Into::into);
+                    drop(execution_permit);
+                    response
+                }
+                // The WebSocket of a rejected upgrade request isn't opened, as the response to the
+                // upgrade is dropped.
+                Err(rejection) => {
+                    report_rejection(&client, &rejection);
+                    Ok(rejection_response(&rejection, calls, is_single))
+                }
+            }
+        })
+    }
+}
+
+// Reads the calls in the body of the request and returns the request with the same body, the
+// calls, if the body holds JsonRPC calls, and whether it holds a single call.
+async fn read_calls(
+    req: Request<Body>,
+) -> Result<(Request<Body>, Option<Vec<RpcCall>>, bool), BoxError> {
+    if req.headers().get(UPGRADE).is_some() {
+        return Ok((req, None, true));
+    }
+    let (parts, body) = req.into_parts();
+    let (body_bytes, is_single) =
+        read_body(&parts.headers, body, SERVER_MAX_BODY_SIZE).await.map_err(BoxError::from)?;
+    let calls = match is_single {
+        true => serde_json::from_slice::<RpcCall>(&body_bytes).ok().map(|call| vec![call]),
+        false => serde_json::from_slice::<Vec<RpcCall>>(&body_bytes).ok(),
+    };
+    Ok((Request::from_parts(parts, body_bytes.into()), calls, is_single))
+}
+
+fn rejection_response(
+    rejection: &Rejection,
+    calls: Option<Vec<RpcCall>>,
+    is_single: bool,
+) -> Response<Body> {
+    let Some(calls) = calls else {
+        // WebSocket upgrades and malformed requests can't be answered per call.
+        let mut response = Response::new(Body::from(
+            serde_json::to_vec(&rejection.error_object()).expect("Error should be serializable."),
+        ));
+        *response.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+        response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        return response;
+    };
+    let mut errors = calls
+        .into_iter()
+        .map(|call| json!({ "jsonrpc": "2.0", "error": rejection.error_object(), "id": call.id }));
+    let body = match is_single {
+        true => errors.next().unwrap_or(Value::Null),
+        false => Value::Array(errors.collect()),
+    };
+    let mut response = Response::new(Body::from(body.to_string()));
+    response.headers_mut().insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response
+}
+
+fn join_comma_separated<T: Display>(items: &[T]) -> String {
+    items.iter().map(ToString::to_string).collect::<Vec<_>>().join(",")
+}
+
+fn serialize_comma_separated<T: Display, S: Serializer>(
+    items: &[T],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&join_comma_separated(items))
+}
+
+fn deserialize_comma_separated<'de, T, D>(de: D) -> Result<Vec<T>, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    let raw_str: String = Deserialize::deserialize(de)?;
+    raw_str
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| item.parse().map_err(D::Error::custom))
+        .collect()
+}
@@ -0,0 +1,245 @@
+use std::convert::Infallible;
+use std::fmt::Debug;
+use std::future::{ready, Ready};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use hyper::{Body, Request, Response};
+use jsonrpsee::server::logger::{Logger, MethodKind, TransportProtocol};
+use jsonrpsee::types::error::TOO_BIG_BATCH_REQUEST_CODE;
+use jsonrpsee::types::{ErrorObjectOwned, Id, Params};
+use jsonrpsee::{MethodCallback, MethodResponse, RpcModule};
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use tower::{service_fn, Layer, Service, ServiceExt};
+
+use super::{
+    MethodClass,
+    RateLimitConfig,
+    RateLimitLayer,
+    RateLimitLogger,
+    RateLimiter,
+    Rejection,
+    LIMIT_EXCEEDED_CODE,
+    RATE_LIMIT_EXCEEDED_MSG,
+};
+
+const CLIENT_IP: IpAddr = IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1));
+const PROXY_IP: IpAddr = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+const PORT: u16 = 1234;
+const MAX_BATCH_COST: u64 = 15;
+
+fn test_config() -> RateLimitConfig {
+    RateLimitConfig {
+        enabled: true,
+        points_per_second: 10,
+        burst_points: 20,
+        read_method_weight: 1,
+        block_method_weight: 3,
+        events_method_weight: 5,
+        execution_method_weight: 10,
+        trace_method_weight: 20,
+        max_concurrent_execution_requests: 2,
+        ..Default::default()
+    }
+}
+
+fn request(body: Value, headers: &[(&str, &str)]) -> Request<Body> {
+    let mut builder = Request::post("/rpc");
+    for (name, value) in headers {
+        builder = builder.header(*name, *value);
+    }
+    builder.body(Body::from(body.to_string())).unwrap()
+}
+
+// Echoes the body of the request. Like the JSON RPC server, passes the address of the peer to the
+// logger before the response is polled.
+fn echo(req: Request<Body>) -> Ready<Result<Response<Body>, Infallible>> {
+    RateLimitLogger.on_connect(SocketAddr::new(CLIENT_IP, PORT), &req, TransportProtocol::Http);
+    ready(Ok(Response::new(req.into_body())))
+}
+
+// Sends the request to the service and returns the body of the response.
+async fn send<S>(service: &mut S, req: Request<Body>) -> Value
+where
+    S: Service<Request<Body>, Response = Response<Body>>,
+    S::Error: Debug,
+{
+    let response = service.ready().await.unwrap().call(req).await.unwrap();
+    let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+    serde_json::from_slice(&body).unwrap()
+}
+
+#[test]
+fn method_classes() {
+    assert_eq!(MethodClass::of("starknet_blockNumber"), MethodClass::Read);
+    assert_eq!(MethodClass::of("starknet_V0_8_getEvents"), MethodClass::Events);
+    assert_eq!(MethodClass::of("starknet_getBlockWithReceipts"), MethodClass::Block);
+    assert_eq!(MethodClass::of("starknet_V0_9_getStateUpdate"), MethodClass::Block);
+    assert_eq!(MethodClass::of("starknet_getBlockWithTxHashes"), MethodClass::Read);
+    assert_eq!(MethodClass::of("starknet_call"), MethodClass::Execution);
+    assert_eq!(MethodClass::of("starknet_V0_9_estimateFee"), MethodClass::Execution);
+    assert_eq!(MethodClass::of("starknet_traceBlockTransactions"), MethodClass::Trace);
+    assert_eq!(MethodClass::of("debug_traceCall"), MethodClass::Trace);
+    assert_eq!(MethodClass::of("debug_estimateFee"), MethodClass::Execution);
+    assert_eq!(MethodClass::of("debug_estimateResourceBounds"), MethodClass::Trace);
+}
+
+#[test]
+fn client_id_by_api_key_then_ip() {
+    let limiter = RateLimiter::new(RateLimitConfig {
+        api_keys: vec!["key0".to_string(), "key1".to_string()],
+        trusted_proxies: vec![PROXY_IP],
+        ..test_config()
+    });
+    let id = |headers: &[(&str, &str)], peer_ip: IpAddr| {
+        limiter.client_id(request(json!({}), headers).headers(), SocketAddr::new(peer_ip, PORT))
+    };
+
+    // Only the configured API keys identify clients.
+    assert_eq!(id(&[("x-api-key", "key1"), ("x-forwarded-for", "2.2.2.2")], PROXY_IP), "key:1");
+    assert_eq!(id(&[("x-api-key", "key2")], CLIENT_IP), "ip:1.1.1.1");
+    // The IP of the client is forwarded only by trusted proxies.
+    assert_eq!(
+        id(&[("x-forwarded-for", "2.2.2.2"), ("x-real-ip", "3.3.3.3")], CLIENT_IP),
+        "ip:1.1.1.1"
+    );
+    assert_eq!(id(&[("x-forwarded-for", "3.3.3.3, 2.2.2.2, 10.0.0.1")], PROXY_IP), "ip:2.2.2.2");
+    assert_eq!(id(&[("x-real-ip", "3.3.3.3")], PROXY_IP), "ip:3.3.3.3");
+    assert_eq!(id(&[], PROXY_IP), "ip:10.0.0.1");
+}
+
+#[test]
+fn points_are_charged_by_weight_and_refilled() {
+    let limiter = RateLimiter::new(test_config());
+    let now = Instant::now();
+
+    // The budget starts full and the costs of the calls add up.
+    limiter.admit("client", &[MethodClass::Events; 3], now).unwrap();
+    assert_eq!(
+        limiter.admit("client", &[MethodClass::Execution], now).unwrap_err(),
+        Rejection::RateLimit { retry_after: Duration::from_millis(500) }
+    );
+    // Other clients have their own budget.
+    limiter.admit("other_client", &[MethodClass::Execution], now).unwrap();
+
+    // After half a second the client gained the missing 5 points.
+    limiter.admit("client", &[MethodClass::Execution], now + Duration::from_millis(500)).unwrap();
+
+    // A call that costs more than the burst is admitted when the budget is full.
+    let later = now + Duration::from_secs(10);
+    limiter.admit("client", &[MethodClass::Trace, MethodClass::Trace], later).unwrap();
+    limiter.admit("client", &[MethodClass::Read], later).unwrap_err();
+}
+
+#[test]
+fn execution_concurrency_is_capped() {
+    let config = RateLimitConfig { burst_points: 1000, ..test_config() };
+    let limiter = RateLimiter::new(config);
+    let now = Instant::now();
+
+    let first_permit = limiter.admit("client", &[MethodClass::Execution], now).unwrap();
+    let _second_permit = limiter.admit("other_client", &[MethodClass::Trace], now).unwrap();
+    assert_eq!(
+        limiter.admit("client", &[MethodClass::Execution], now).unwrap_err(),
+        Rejection::ConcurrencyLimit
+    );
+    // Reads don't take execution slots.
+    assert!(limiter.admit("client", &[MethodClass::Read], now).unwrap().is_none());
+
+    drop(first_permit);
+    limiter.admit("client", &[MethodClass::Execution], now).unwrap();
+}
+
+#[tokio::test]
+async fn rejected_calls_get_json_rpc_errors() {
+    let layer =
+        RateLimitLayer::new(&RateLimitConfig { burst_points: 15, ..test_config() }, MAX_BATCH_COST);
+    let mut service = layer.layer(service_fn(echo));
+    let call = |id: u64, method: &str| json!({"jsonrpc": "2.0", "id": id, "method": method});
+
+    // Admitted requests reach the server untouched.
+    let body = json!([call(1, "starknet_call"), call(2, "starknet_blockNumber")]);
+    assert_eq!(send(&mut service, request(body.clone(), &[])).await, body);
+
+    // The forwarded IP of an untrusted peer doesn't give it a new budget.
+    let body = json!([call(3, "starknet_call"), call(4, "starknet_blockNumber")]);
+    let response = send(&mut service, request(body, &[("x-forwarded-for", "2.2.2.2")])).await;
+    for (response, id) in response.as_array().unwrap().iter().zip([3, 4]) {
+        assert_eq!(response["id"], id);
+        assert_eq!(response["error"]["code"], LIMIT_EXCEEDED_CODE);
+        assert_eq!(response["error"]["message"], RATE_LIMIT_EXCEEDED_MSG);
+        assert!(response["error"]["data"]["retry_after_ms"].is_number());
+    }
+}
+
+#[tokio::test]
+async fn disabled_layer_passes_everything() {
+    let layer =
+        RateLimitLayer::new(&RateLimitConfig { enabled: false, ..test_config() }, MAX_BATCH_COST);
+    let mut service = layer.layer(service_fn(echo));
+    let body = json!({"jsonrpc": "2.0", "id": 1, "method": "starknet_traceBlockTransactions"});
+    for _ in 0..10 {
+        assert_eq!(send(&mut service, request(body.clone(), &[])).await, body);
+    }
+}
+
+#[tokio::test]
+async fn costly_batches_are_rejected_even_when_disabled() {
+    let layer =
+        RateLimitLayer::new(&RateLimitConfig { enabled: false, ..test_config() }, MAX_BATCH_COST);
+    let mut service = layer.layer(service_fn(echo));
+    let call = |id: u64, method: &str| json!({"jsonrpc": "2.0", "id": id, "method": method});
+
+    let body = json!([call(1, "starknet_getEvents"), call(2, "starknet_getEvents")]);
+    assert_eq!(send(&mut service, request(body.clone(), &[])).await, body);
+
+    // The batch doesn't reach the server.
+    let body = json!([call(3, "starknet_getEvents"), call(4, "starknet_traceCall")]);
+    let response = send(&mut service, request(body, &[])).await;
+    for (response, id) in response.as_array().unwrap().iter().zip([3, 4]) {
+        assert_eq!(response["id"], id);
+        assert_eq!(response["error"]["code"], TOO_BIG_BATCH_REQUEST_CODE);
+        assert_eq!(response["error"]["data"], json!({"cost": 25, "max_batch_cost": 15}));
+    }
+
+    // Single calls aren't limited.
+    let body = call(5, "starknet_traceCall");
+    assert_eq!(send(&mut service, request(body.clone(), &[])).await, body);
+}
+
+#[tokio::test]
+async fn websocket_calls_are_charged_to_their_connection() {
+    let mut module = RpcModule::new(());
+    module
+        .register_method("starknet_call", |_, _| Ok::<_, ErrorObjectOwned>(true))
+        .expect("Method name should be unique");
+    let layer =
+        RateLimitLayer::new(&RateLimitConfig { burst_points: 15, ..test_config() }, MAX_BATCH_COST);
+    let methods = layer.limit_websocket_calls(module.into());
+    let Some(MethodCallback::Async(method)) = methods.method("starknet_call") else {
+        panic!("The calls of a limited method should get the connection");
+    };
+    // Like the JSON RPC server, passes the call to the logger right before making it.
+    let call = |transport: TransportProtocol, connection_id: usize| {
+        RateLimitLogger.on_call(
+            "starknet_call",
+            Params::new(None),
+            MethodKind::MethodCall,
+            transport,
+        );
+        method(Id::Number(1), Params::new(None), connection_id, usize::MAX)
+    };
+    let is_rejected = |response: MethodResponse| {
+        response.success_or_error.as_error_code() == Some(LIMIT_EXCEEDED_CODE)
+    };
+
+    assert!(!is_rejected(call(TransportProtocol::WebSocket, 1).await));
+    assert!(is_rejected(call(TransportProtocol::WebSocket, 1).await));
+    // Each connection has its own budget.
+    assert!(!is_rejected(call(TransportProtocol::WebSocket, 2).await));
+    // Calls over HTTP are charged by the layer.
+    for _ in 0..3 {
+        assert!(!is_rejected(call(TransportProtocol::Http, 1).await));
+    }
+}
@@ -19,6 +19,8 @@ const REQUEST_LATENCY: &str = "rpc_request_latency_seconds";
 const METHOD_LABEL: &str = "method";
 const VERSION_LABEL: &str = "version";
 const ILLEGAL_METHOD: &str = "illegal_method";
+const UNVERSIONED: &str = "unversioned";
+const DEBUG: &str = "debug";
 
 // Register the metrics and returns a set of the method names.
 fn init_metrics(methods: &Methods) -> HashSet<String> {
@@ -108,7 +110,14 @@ impl Logger for MetricLogger {
 
 // Given method_name returns (method, version).
 // Example: method_name: starknet_V0_6_0_blockNumber; output: (blockNumber, V0_6_0).
+// The unversioned aliases of the methods served over WebSocket have the unversioned label.
+// Example: method_name: starknet_blockNumber; output: (blockNumber, unversioned).
+// The methods of the debug namespace have the debug label.
+// Example: method_name: debug_traceCall; output: (traceCall, debug).
 fn get_method_and_version(method_name: &str) -> (String, String) {
+    if let Some(method) = method_name.strip_prefix("debug_") {
+        return (method.to_string(), DEBUG.to_string());
+    }
     // The structure of method_name is in the following format: "starknet_V0_6_0_blockNumber".
     // Only method in this format will arrive to this point in the code.
     let last_underscore_index = method_name
@@ -117,6 +126,6 @@ fn get_method_and_version(method_name: &str) -> (String, String) {
 
     (
         method_name[last_underscore_index + 1..].to_string(),
-        method_name[9..last_underscore_index].to_string(),
+        method_name.get(9..last_underscore_index).unwrap_or(UNVERSIONED).to_string(),
     )
 }
@@ -38,6 +38,14 @@ fn get_method_and_version_test() {
     let (method, version) = get_method_and_version(method_name);
     assert_eq!(method, "blockNumber");
     assert_eq!(version, "V0_8_0");
+
+    let (method, version) = get_method_and_version("starknet_blockNumber");
+    assert_eq!(method, "blockNumber");
+    assert_eq!(version, "unversioned");
+
+    let (method, version) = get_method_and_version("debug_traceCall");
+    assert_eq!(method, "traceCall");
+    assert_eq!(version, "debug");
 }
 
 // Ignored because server_metrics test is running in parallel and we are unable to install multiple
@@ -169,6 +177,7 @@ async fn server_metrics() {
         storage_reader,
         "NODE VERSION",
         None,
+        None,
     )
     .await
     .unwrap();
@@ -1,12 +1,19 @@
+use std::net::SocketAddr;
 use std::panic;
 
 use apollo_storage::base_layer::BaseLayerStorageWriter;
+use apollo_storage::body::BodyStorageWriter;
 use apollo_storage::header::HeaderStorageWriter;
+use apollo_storage::state::StateStorageWriter;
 use apollo_storage::test_utils::get_test_storage;
+use apollo_storage::StorageReader;
+use apollo_test_utils::{get_test_block, send_request};
 use assert_matches::assert_matches;
 use jsonrpsee::core::client::ClientT;
 use jsonrpsee::core::{Error, RpcResult};
 use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::server::ServerHandle;
+use jsonrpsee::types::error::{OVERSIZED_RESPONSE_CODE, TOO_BIG_BATCH_REQUEST_CODE};
 use jsonrpsee::types::ErrorObjectOwned;
 use pretty_assertions::assert_eq;
 use starknet_api::block::{
@@ -16,6 +23,7 @@ use starknet_api::block::{
     BlockNumber,
     BlockStatus,
 };
+use starknet_api::state::ThinStateDiff;
 
 use crate::test_utils::{
     get_test_highest_block,
@@ -23,7 +31,25 @@ use crate::test_utils::{
     get_test_pending_data,
     get_test_rpc_config,
 };
-use crate::{get_block_status, run_server};
+use crate::{get_block_status, run_server, RpcConfig};
+
+async fn run_test_server(
+    config: RpcConfig,
+    storage_reader: StorageReader,
+) -> (SocketAddr, ServerHandle) {
+    run_server(
+        &config,
+        get_test_highest_block(),
+        get_test_pending_data(),
+        get_test_pending_classes(),
+        storage_reader,
+        "NODE VERSION",
+        None,
+        None,
+    )
+    .await
+    .unwrap()
+}
 
 #[tokio::test]
 async fn run_server_no_blocks() {
@@ -40,6 +66,7 @@ async fn run_server_no_blocks() {
         storage_reader,
         "NODE VERSION",
         None,
+        None,
     )
     .await
     .unwrap();
@@ -102,3 +129,108 @@ fn serialization_precision() {
     let deserialized = serde_json::to_string(&serialized).unwrap();
     assert_eq!(input, deserialized);
 }
+
+// A batch of the given number of calls to starknet_chainId.
+fn chain_id_batch(length: usize) -> String {
+    let requests = (0..length)
+        .map(|id| format!(r#"{{"jsonrpc":"2.0","id":{id},"method":"starknet_chainId"}}"#))
+        .collect::<Vec<_>>();
+    format!("[{}]", requests.join(","))
+}
+
+async fn send_batch(addr: SocketAddr, body: String) -> serde_json::Value {
+    let response = reqwest::Client::new()
+        .post(format!("http://{addr:?}/rpc/v0_8"))
+        .header("Content-Type", "application/json")
+        .body(body)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    serde_json::from_str::<serde_json::Value>(&response).unwrap()
+}
+
+#[tokio::test]
+async fn batch_longer_than_limit_is_rejected() {
+    let config = RpcConfig { max_batch_request_length: 2, ..get_test_rpc_config() };
+    let ((storage_reader, _), _temp_dir) = get_test_storage();
+    let (addr, _handle) = run_test_server(config, storage_reader).await;
+
+    let response = send_batch(addr, chain_id_batch(2)).await;
+    assert_eq!(response.as_array().unwrap().len(), 2);
+
+    let response = send_batch(addr, chain_id_batch(3)).await;
+    assert_eq!(response["error"]["code"], TOO_BIG_BATCH_REQUEST_CODE);
+}
+
+#[tokio::test]
+async fn batch_costlier_than_limit_is_rejected() {
+    // The calls to starknet_chainId are reads, which weigh 1 by default.
+    let config = RpcConfig { max_batch_cost: 2, ..get_test_rpc_config() };
+    let ((storage_reader, _), _temp_dir) = get_test_storage();
+    let (addr, _handle) = run_test_server(config, storage_reader).await;
+
+    let response = send_batch(addr, chain_id_batch(2)).await;
+    for response in response.as_array().unwrap() {
+        assert!(response["result"].is_string());
+    }
+
+    let response = send_batch(addr, chain_id_batch(3)).await;
+    let responses = response.as_array().unwrap();
+    assert_eq!(responses.len(), 3);
+    for (id, response) in responses.iter().enumerate() {
+        assert_eq!(response["id"], id);
+        assert_eq!(response["error"]["code"], TOO_BIG_BATCH_REQUEST_CODE);
+        assert_eq!(response["error"]["data"]["cost"], 3);
+    }
+}
+
+#[tokio::test]
+async fn response_larger_than_limit_is_replaced_by_error() {
+    let config = RpcConfig { max_response_body_size: 10, ..get_test_rpc_config() };
+    let ((storage_reader, _), _temp_dir) = get_test_storage();
+    let (addr, _handle) = run_test_server(config, storage_reader).await;
+
+    let response = send_request(addr, "starknet_chainId", "", "V0_8").await;
+    assert_eq!(response["error"]["code"], OVERSIZED_RESPONSE_CODE);
+}
+
+#[tokio::test]
+async fn streamed_response_larger_than_limit_is_replaced_by_error() {
+    let ((storage_reader, mut storage_writer), _temp_dir) = get_test_storage();
+    let block = get_test_block(50, Some(2), None, None);
+    let block_number = block.header.block_header_without_hash.block_number;
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block_number, &block.header)
+        .unwrap()
+        .append_body(block_number, block.body)
+        .unwrap()
+        .append_state_diff(block_number, ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let requests = [
+        ("starknet_getBlockWithReceipts", r#"{"block_number": 0}"#, "transactions", 50),
+        ("starknet_getEvents", r#"{"chunk_size": 10}"#, "events", 10),
+    ];
+    for (method, params, streamed_field, expected_len) in requests {
+        let (addr, _handle) = run_test_server(get_test_rpc_config(), storage_reader.clone()).await;
+        let response = send_request(addr, method, params, "V0_8").await;
+        assert_eq!(
+            response["result"][streamed_field].as_array().unwrap().len(),
+            expected_len,
+            "{method}"
+        );
+
+        let max_response_body_size = u32::try_from(response.to_string().len() / 2).unwrap();
+        let config = RpcConfig { max_response_body_size, ..get_test_rpc_config() };
+        let (addr, _handle) = run_test_server(config, storage_reader.clone()).await;
+        let response = send_request(addr, method, params, "V0_8").await;
+        assert_eq!(response["error"]["code"], OVERSIZED_RESPONSE_CODE, "{method}");
+    }
+}
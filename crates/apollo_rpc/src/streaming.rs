@@ -0,0 +1,109 @@
+//! Sequences of a response that are read from the storage while the response is serialized.
+//!
+//! The items of such a sequence aren't held in memory together, and their reading stops once the
+//! serialized response exceeds the maximum response size. The items are read in a transaction of
+//! their own, so a failure to read an item (e.g. of a block that was reverted after the handler
+//! returned) fails the serialization, and the response is an internal error.
+
+use std::fmt::{self, Debug, Formatter};
+use std::sync::Arc;
+
+use apollo_storage::db::RO;
+use apollo_storage::{StorageReader, StorageTxn};
+use jsonrpsee::core::RpcResult;
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Error, Serialize, SerializeSeq, Serializer};
+
+type ReadItem<T> = dyn Fn(&StorageTxn<'_, RO>, usize) -> RpcResult<T> + Send + Sync;
+
+/// A sequence whose first items are read from the storage while it's serialized, followed by
+/// items that are held in memory (e.g. of the pending block).
+#[derive(Clone)]
+pub struct StreamedSeq<T> {
+    stored_items: Option<StoredItems<T>>,
+    items: Vec<T>,
+}
+
+struct StoredItems<T> {
+    storage_reader: StorageReader,
+    len: usize,
+    // Reads the item at the given position in the sequence.
+    read_item: Arc<ReadItem<T>>,
+}
+
+impl<T> Clone for StoredItems<T> {
+    fn clone(&self) -> Self {
+        Self {
+            storage_reader: self.storage_reader.clone(),
+            len: self.len,
+            read_item: self.read_item.clone(),
+        }
+    }
+}
+
+impl<T> StreamedSeq<T> {
+    pub fn from_items(items: Vec<T>) -> Self {
+        Self { stored_items: None, items }
+    }
+
+    /// A sequence of `len` items, where the item at position `i` is `read_item(txn, i)`.
+    pub fn from_storage(
+        storage_reader: StorageReader,
+        len: usize,
+        read_item: impl Fn(&StorageTxn<'_, RO>, usize) -> RpcResult<T> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            stored_items: Some(StoredItems { storage_reader, len, read_item: Arc::new(read_item) }),
+            items: vec![],
+        }
+    }
+
+    /// Appends items that are serialized after the items read from the storage.
+    pub fn extend(&mut self, items: impl IntoIterator<Item = T>) {
+        self.items.extend(items);
+    }
+
+    fn n_stored_items(&self) -> usize {
+        self.stored_items.as_ref().map_or(0, |stored_items| stored_items.len)
+    }
+}
+
+impl<T: Serialize> Serialize for StreamedSeq<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.n_stored_items() + self.items.len()))?;
+        if let Some(stored_items) = &self.stored_items {
+            let txn = stored_items.storage_reader.begin_ro_txn().map_err(S::Error::custom)?;
+            for index in 0..stored_items.len {
+                let item = (stored_items.read_item)(&txn, index)
+                    .map_err(|err| S::Error::custom(err.message()))?;
+                seq.serialize_element(&item)?;
+            }
+        }
+        for item in &self.items {
+            seq.serialize_element(item)?;
+        }
+        seq.end()
+    }
+}
+
+// The client of the API gets the items in memory.
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for StreamedSeq<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer).map(Self::from_items)
+    }
+}
+
+impl<T: Debug> Debug for StreamedSeq<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamedSeq")
+            .field("n_stored_items", &self.n_stored_items())
+            .field("items", &self.items)
+            .finish()
+    }
+}
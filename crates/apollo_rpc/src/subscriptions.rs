@@ -0,0 +1,278 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use apollo_mempool_types::communication::SharedMempoolClient;
+use apollo_storage::base_layer::BaseLayerStorageReader;
+use apollo_storage::body::BodyStorageReader;
+use apollo_storage::db::TransactionKind;
+use apollo_storage::header::HeaderStorageReader;
+use apollo_storage::state::StateStorageReader;
+use apollo_storage::{StorageReader, StorageResult, StorageTxn};
+use jsonrpsee::types::SubscriptionId;
+use jsonrpsee::ConnectionId;
+use serde_json::Value;
+use starknet_api::block::{BlockHashAndNumber, BlockNumber};
+use starknet_api::rpc_transaction::InternalRpcTransaction;
+use starknet_api::transaction::TransactionHash;
+use tokio::sync::{broadcast, Notify};
+use tracing::{debug, error};
+
+/// The interval in which the storage and the mempool are polled for chain updates.
+pub(crate) const CHAIN_UPDATES_POLLING_INTERVAL: Duration = Duration::from_millis(100);
+/// The number of chain updates a subscriber can fall behind before it misses updates.
+const CHAIN_UPDATES_CHANNEL_CAPACITY: usize = 1024;
+/// The number of the latest notified blocks whose revert is detected.
+pub(crate) const MAX_REORG_DEPTH: usize = 1024;
+
+/// An update to the chain, as seen by the storage and the mempool.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum ChainUpdate {
+    /// A block whose body and state diff are stored.
+    NewBlock(BlockHashAndNumber),
+    /// The blocks in the range were reverted from the storage. Blocks from the first block onwards
+    /// will be notified again once they are stored.
+    Reorg { first: BlockHashAndNumber, last: BlockHashAndNumber },
+    /// The blocks below the given block number were accepted on L1.
+    AcceptedOnL1(BlockNumber),
+    /// Transactions that were added to the mempool.
+    NewPendingTransactions(Arc<Vec<InternalRpcTransaction>>),
+}
+
+/// The key of an active subscription. Subscription ids are unique only within their connection.
+type SubscriptionKey = (ConnectionId, String);
+
+/// The chain updates feed and the active subscriptions of the RPC server, shared by all of its
+/// versions. The storage and the mempool are polled for chain updates only while there are
+/// subscribers to them. Without a mempool client, no pending transactions are notified.
+#[derive(Clone)]
+pub struct Subscriptions {
+    storage_reader: StorageReader,
+    mempool_client: Option<SharedMempoolClient>,
+    sender: broadcast::Sender<ChainUpdate>,
+    is_polling: Arc<Mutex<bool>>,
+    active_subscriptions: Arc<Mutex<HashMap<SubscriptionKey, Arc<Notify>>>>,
+}
+
+impl Subscriptions {
+    pub fn new(storage_reader: StorageReader, mempool_client: Option<SharedMempoolClient>) -> Self {
+        let (sender, _) = broadcast::channel(CHAIN_UPDATES_CHANNEL_CAPACITY);
+        Self {
+            storage_reader,
+            mempool_client,
+            sender,
+            is_polling: Arc::new(Mutex::new(false)),
+            active_subscriptions: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns a receiver of the chain updates from now on, and starts polling for them if no one
+    /// else is subscribed.
+    pub(crate) fn subscribe_to_chain_updates(&self) -> broadcast::Receiver<ChainUpdate> {
+        let mut is_polling = self.is_polling.lock().expect("Lock should not be poisoned");
+        let receiver = self.sender.subscribe();
+        if !*is_polling {
+            *is_polling = true;
+            tokio::spawn(self.clone().poll_chain_updates());
+        }
+        receiver
+    }
+
+    /// Whether pending transactions are notified.
+    pub(crate) fn has_mempool(&self) -> bool {
+        self.mempool_client.is_some()
+    }
+
+    /// Registers the subscription of the given connection so that it can be cancelled by
+    /// `unsubscribe`. The subscription is unregistered when the returned value is dropped.
+    pub(crate) fn register(
+        &self,
+        connection_id: ConnectionId,
+        subscription_id: SubscriptionId<'_>,
+    ) -> ActiveSubscription {
+        let key = (connection_id, subscription_id_key(&Value::from(subscription_id)));
+        let cancelled = Arc::new(Notify::new());
+        self.active_subscriptions
+            .lock()
+            .expect("Lock should not be poisoned")
+            .insert(key.clone(), cancelled.clone());
+        ActiveSubscription {
+            key,
+            cancelled,
+            active_subscriptions: self.active_subscriptions.clone(),
+        }
+    }
+
+    /// Cancels the subscription of the given connection with the given id. Returns false if the
+    /// connection has no such subscription.
+    pub(crate) fn unsubscribe(&self, connection_id: ConnectionId, subscription_id: &Value) -> bool {
+        let Some(cancelled) = self
+            .active_subscriptions
+            .lock()
+            .expect("Lock should not be poisoned")
+            .remove(&(connection_id, subscription_id_key(subscription_id)))
+        else {
+            return false;
+        };
+        cancelled.notify_one();
+        true
+    }
+
+    async fn poll_chain_updates(self) {
+        debug!("Polling for chain updates.");
+        let mut state = None;
+        loop {
+            {
+                let mut is_polling = self.is_polling.lock().expect("Lock should not be poisoned");
+                if self.sender.receiver_count() == 0 {
+                    *is_polling = false;
+                    debug!("No more subscribers to chain updates, stopping to poll.");
+                    return;
+                }
+            }
+            match self.poll_storage(&mut state) {
+                Ok(state) => self.poll_mempool(state).await,
+                Err(err) => error!("Failed to poll the storage for chain updates: {err}."),
+            }
+            tokio::time::sleep(CHAIN_UPDATES_POLLING_INTERVAL).await;
+        }
+    }
+
+    // The storage transaction isn't held across awaits, so the storage is polled separately from
+    // the mempool.
+    fn poll_storage<'a>(
+        &self,
+        state: &'a mut Option<PollingState>,
+    ) -> StorageResult<&'a mut PollingState> {
+        let txn = self.storage_reader.begin_ro_txn()?;
+        let block_marker = get_notified_block_marker(&txn)?;
+        let base_layer_marker = txn.get_base_layer_block_marker()?;
+        let state = state.get_or_insert_with(|| PollingState {
+            // The latest block is notified again, in case a subscriber missed it while polling
+            // started.
+            next_block: block_marker.prev().unwrap_or_default(),
+            notified_blocks: VecDeque::new(),
+            base_layer_marker,
+            mempool_transactions: None,
+        });
+
+        // Detect the notified blocks that were reverted, from the latest one backwards.
+        let mut reverted_blocks = vec![];
+        while let Some(block) = state.notified_blocks.back() {
+            let stored_hash = match block.number < block_marker {
+                true => txn.get_block_header(block.number)?.map(|header| header.block_hash),
+                false => None,
+            };
+            if stored_hash == Some(block.hash) {
+                break;
+            }
+            reverted_blocks.extend(state.notified_blocks.pop_back());
+        }
+        if let (Some(last), Some(first)) = (reverted_blocks.first(), reverted_blocks.last()) {
+            debug!("Blocks {} to {} were reverted.", first.number, last.number);
+            state.next_block = first.number;
+            self.send(ChainUpdate::Reorg { first: *first, last: *last });
+        }
+
+        for block_number in state.next_block.iter_up_to(block_marker) {
+            let Some(header) = txn.get_block_header(block_number)? else {
+                break;
+            };
+            let block = BlockHashAndNumber { hash: header.block_hash, number: block_number };
+            if state.notified_blocks.len() == MAX_REORG_DEPTH {
+                state.notified_blocks.pop_front();
+            }
+            state.notified_blocks.push_back(block);
+            state.next_block = block_number.unchecked_next();
+            self.send(ChainUpdate::NewBlock(block));
+        }
+
+        if base_layer_marker != state.base_layer_marker {
+            state.base_layer_marker = base_layer_marker;
+            self.send(ChainUpdate::AcceptedOnL1(base_layer_marker));
+        }
+        Ok(state)
+    }
+
+    async fn poll_mempool(&self, state: &mut PollingState) {
+        let Some(mempool_client) = &self.mempool_client else {
+            return;
+        };
+        let mempool_snapshot = match mempool_client.get_mempool_snapshot().await {
+            Ok(mempool_snapshot) => mempool_snapshot,
+            Err(err) => {
+                error!("Failed to poll the mempool for chain updates: {err}.");
+                return;
+            }
+        };
+        // The transactions that were in the mempool when polling started aren't notified.
+        let Some(known_transactions) = state
+            .mempool_transactions
+            .replace(mempool_snapshot.transactions.iter().copied().collect::<HashSet<_>>())
+        else {
+            return;
+        };
+        let new_transaction_hashes = mempool_snapshot
+            .transactions
+            .into_iter()
+            .filter(|transaction_hash| !known_transactions.contains(transaction_hash))
+            .collect::<Vec<_>>();
+        if new_transaction_hashes.is_empty() {
+            return;
+        }
+        // Transactions that left the mempool since the snapshot are skipped.
+        match mempool_client.get_txs_by_hash(new_transaction_hashes).await {
+            Ok(new_transactions) if !new_transactions.is_empty() => {
+                self.send(ChainUpdate::NewPendingTransactions(Arc::new(new_transactions)));
+            }
+            Ok(_) => {}
+            Err(err) => error!("Failed to get the new transactions from the mempool: {err}."),
+        }
+    }
+
+    fn send(&self, update: ChainUpdate) {
+        // Fails only if there are no subscribers, in which case the polling stops.
+        let _ = self.sender.send(update);
+    }
+}
+
+/// A subscription that can be cancelled by `Subscriptions::unsubscribe`.
+pub(crate) struct ActiveSubscription {
+    key: SubscriptionKey,
+    cancelled: Arc<Notify>,
+    active_subscriptions: Arc<Mutex<HashMap<SubscriptionKey, Arc<Notify>>>>,
+}
+
+impl ActiveSubscription {
+    /// Completes once the subscription is cancelled.
+    pub(crate) async fn cancelled(&self) {
+        self.cancelled.notified().await
+    }
+}
+
+impl Drop for ActiveSubscription {
+    fn drop(&mut self) {
+        self.active_subscriptions.lock().expect("Lock should not be poisoned").remove(&self.key);
+    }
+}
+
+struct PollingState {
+    next_block: BlockNumber,
+    notified_blocks: VecDeque<BlockHashAndNumber>,
+    base_layer_marker: BlockNumber,
+    // The transactions in the last mempool snapshot, if the mempool was polled.
+    mempool_transactions: Option<HashSet<TransactionHash>>,
+}
+
+/// Returns the first block whose body or state diff isn't stored yet. The blocks below it are
+/// notified as new blocks.
+pub(crate) fn get_notified_block_marker<Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+) -> StorageResult<BlockNumber> {
+    Ok(txn.get_state_marker()?.min(txn.get_body_marker()?))
+}
+
+// Subscription ids are either numbers or strings, so they are keyed by their JSON representation.
+fn subscription_id_key(subscription_id: &Value) -> String {
+    subscription_id.to_string()
+}
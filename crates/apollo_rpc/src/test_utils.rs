@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::sync::Arc;
 
+use apollo_mempool_types::communication::SharedMempoolClient;
 use apollo_rpc_execution::ExecutionConfig;
 use apollo_starknet_client::reader::PendingData;
 use apollo_starknet_client::writer::MockStarknetWriter;
@@ -23,6 +24,7 @@ use strum_macros::EnumIter;
 use tokio::sync::RwLock;
 
 use crate::api::JsonRpcServerTrait;
+use crate::subscriptions::Subscriptions;
 use crate::version_config::{VersionId, VERSION_PATTERN};
 use crate::RpcConfig;
 
@@ -32,12 +34,15 @@ pub fn get_test_rpc_config() -> RpcConfig {
         execution_config: ExecutionConfig {
             eth_fee_contract_address: contract_address!("0x1001"),
             strk_fee_contract_address: contract_address!("0x1001"),
+            additional_fee_tokens: Default::default(),
             default_initial_gas_cost: 10000000000,
+            max_price_per_unit_headroom_percent: 50,
         },
         ip: "127.0.0.1".parse().unwrap(),
         port: 0,
         max_events_chunk_size: 10,
         max_events_keys: 10,
+        max_events_block_range: 3,
         collect_metrics: false,
         ..Default::default()
     }
@@ -55,17 +60,48 @@ pub(crate) fn get_test_pending_classes() -> Arc<RwLock<PendingClasses>> {
     Arc::new(RwLock::new(PendingClasses::default()))
 }
 
-pub(crate) fn get_test_rpc_server_and_storage_writer<T: JsonRpcServerTrait>()
--> (RpcModule<T>, StorageWriter) {
+pub(crate) fn get_test_rpc_server_and_storage_writer<T: JsonRpcServerTrait>(
+) -> (RpcModule<T>, StorageWriter) {
     get_test_rpc_server_and_storage_writer_from_params(None, None, None, None, None)
 }
 
+pub(crate) fn get_test_rpc_server_and_storage_writer_with_mempool<T: JsonRpcServerTrait>(
+    mempool_client: SharedMempoolClient,
+) -> (RpcModule<T>, StorageWriter) {
+    get_test_rpc_server_and_storage_writer_from_params_and_mempool(
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(mempool_client),
+    )
+}
+
 pub(crate) fn get_test_rpc_server_and_storage_writer_from_params<T: JsonRpcServerTrait>(
     mock_client: Option<MockStarknetWriter>,
     shared_highest_block: Option<Arc<RwLock<Option<BlockHashAndNumber>>>>,
     pending_data: Option<Arc<RwLock<PendingData>>>,
     pending_classes: Option<Arc<RwLock<PendingClasses>>>,
     storage_scope: Option<StorageScope>,
+) -> (RpcModule<T>, StorageWriter) {
+    get_test_rpc_server_and_storage_writer_from_params_and_mempool(
+        mock_client,
+        shared_highest_block,
+        pending_data,
+        pending_classes,
+        storage_scope,
+        None,
+    )
+}
+
+fn get_test_rpc_server_and_storage_writer_from_params_and_mempool<T: JsonRpcServerTrait>(
+    mock_client: Option<MockStarknetWriter>,
+    shared_highest_block: Option<Arc<RwLock<Option<BlockHashAndNumber>>>>,
+    pending_data: Option<Arc<RwLock<PendingData>>>,
+    pending_classes: Option<Arc<RwLock<PendingClasses>>>,
+    storage_scope: Option<StorageScope>,
+    mempool_client: Option<SharedMempoolClient>,
 ) -> (RpcModule<T>, StorageWriter) {
     let mock_client = mock_client.unwrap_or_default();
     let shared_highest_block = shared_highest_block.unwrap_or(get_test_highest_block());
@@ -80,15 +116,17 @@ pub(crate) fn get_test_rpc_server_and_storage_writer_from_params<T: JsonRpcServe
         T::new(
             config.chain_id,
             config.execution_config,
-            storage_reader,
+            storage_reader.clone(),
             config.max_events_chunk_size,
             config.max_events_keys,
+            config.max_events_block_range,
             BlockHashAndNumber::default(),
             shared_highest_block,
             pending_data,
             pending_classes,
             mock_client_arc,
             None,
+            Subscriptions::new(storage_reader.clone(), mempool_client),
         )
         .into_rpc_module(),
         storage_writer,
@@ -525,7 +563,7 @@ fn params_vec_to_named_params(
 }
 
 // Read the spec file and return the json object specifying the given method.
-fn get_method_spec_object(
+pub(crate) fn get_method_spec_object(
     method: &str,
     version_id: &VersionId,
     spec_file: SpecFile,
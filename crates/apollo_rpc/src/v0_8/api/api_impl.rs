@@ -1,14 +1,29 @@
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 
 use apollo_class_manager_types::SharedClassManagerClient;
-use apollo_rpc_execution::objects::{FeeEstimation, PendingData as ExecutionPendingData};
+use apollo_rpc_execution::objects::{
+    CallTraceOutput,
+    FeeEstimation,
+    PendingData as ExecutionPendingData,
+    ResourceBoundsEstimation,
+    StateOverrides,
+    TransactionCallTrace,
+    TransactionSimulationOutput,
+    TransactionTrace as ExecutionTransactionTrace,
+};
 use apollo_rpc_execution::{
     estimate_fee as exec_estimate_fee,
+    estimate_resource_bounds as exec_estimate_resource_bounds,
     execute_call,
     execution_utils,
     simulate_transactions as exec_simulate_transactions,
+    trace_call as exec_trace_call,
+    trace_transactions as exec_trace_transactions,
     ExecutableTransactionInput,
     ExecutionConfig,
+    ExecutionError,
+    ExecutionResult,
 };
 use apollo_starknet_client::reader::objects::pending_data::{
     DeprecatedPendingBlock,
@@ -22,6 +37,7 @@ use apollo_starknet_client::reader::objects::transaction::{
 use apollo_starknet_client::reader::PendingData;
 use apollo_starknet_client::writer::{StarknetWriter, WriterClientError};
 use apollo_starknet_client::ClientError;
+use apollo_storage::body::event_keys_filter::EventKeysFilterReader;
 use apollo_storage::body::events::{EventIndex, EventsReader};
 use apollo_storage::body::{BodyStorageReader, TransactionIndex};
 use apollo_storage::compiled_class::CasmStorageReader;
@@ -29,9 +45,9 @@ use apollo_storage::db::{TransactionKind, RO};
 use apollo_storage::state::StateStorageReader;
 use apollo_storage::{StorageError, StorageReader, StorageTxn};
 use async_trait::async_trait;
-use jsonrpsee::core::RpcResult;
+use jsonrpsee::core::{RpcResult, SubscriptionResult};
 use jsonrpsee::types::ErrorObjectOwned;
-use jsonrpsee::RpcModule;
+use jsonrpsee::{PendingSubscriptionSink, RpcModule};
 use papyrus_common::pending_classes::{PendingClasses, PendingClassesTrait};
 use starknet_api::block::{
     BlockHash,
@@ -45,20 +61,27 @@ use starknet_api::core::{
     ChainId,
     ClassHash,
     ContractAddress,
+    EntryPointSelector,
     GlobalRoot,
     Nonce,
     BLOCK_HASH_TABLE_ADDRESS,
 };
+use starknet_api::execution_resources::{
+    ExecutionResources as StarknetApiExecutionResources,
+    GasVector,
+};
 use starknet_api::execution_utils::format_panic_data;
 use starknet_api::hash::StarkHash;
 use starknet_api::state::{StateNumber, StorageKey, ThinStateDiff as StarknetApiThinStateDiff};
-use starknet_api::transaction::fields::Fee;
+use starknet_api::transaction::fields::{Calldata, Fee};
 use starknet_api::transaction::{
     EventContent,
     EventIndexInTransactionOutput,
+    EventKey,
     Transaction as StarknetApiTransaction,
     TransactionHash,
     TransactionOffsetInBlock,
+    TransactionOutput as StarknetApiTransactionOutput,
     TransactionVersion,
 };
 use starknet_types_core::felt::Felt;
@@ -90,14 +113,26 @@ use super::super::error::{
     INVALID_TRANSACTION_INDEX,
     NO_BLOCKS,
     PAGE_SIZE_TOO_BIG,
+    TOO_MANY_ADDRESSES_IN_FILTER,
+    TOO_MANY_BLOCKS_BACK,
     TOO_MANY_KEYS_IN_FILTER,
     TRANSACTION_HASH_NOT_FOUND,
+    UNSUPPORTED_TX_VERSION,
 };
 use super::super::execution::TransactionTrace;
 use super::super::state::{AcceptedStateUpdate, PendingStateUpdate, StateUpdate};
+use super::super::subscription::{
+    unsubscribe_callback,
+    ChainSubscription,
+    PendingTransaction,
+    TransactionStatusNotification,
+    MAX_BLOCKS_BACK,
+    MAX_SENDER_ADDRESSES_IN_FILTER,
+};
 use super::super::transaction::{
     get_block_tx_hashes_by_number,
-    get_block_txs_by_number,
+    get_block_txs_count_by_number,
+    get_tx_with_hash_by_index,
     Event,
     GeneralTransactionReceipt,
     L1HandlerMsgHash,
@@ -107,6 +142,7 @@ use super::super::transaction::{
     PendingTransactionOutput,
     PendingTransactionReceipt,
     Transaction,
+    TransactionFinalityStatus,
     TransactionOutput,
     TransactionReceipt,
     TransactionStatus,
@@ -144,6 +180,8 @@ use super::{
 };
 use crate::api::{BlockHashOrNumber, JsonRpcServerTrait, Tag};
 use crate::pending::client_pending_data_to_execution_pending_data;
+use crate::streaming::StreamedSeq;
+use crate::subscriptions::{ChainUpdate, Subscriptions};
 use crate::syncing_state::{get_last_synced_block, SyncStatus, SyncingState};
 use crate::v0_8::state::ThinStateDiff;
 use crate::version_config::VERSION_0_8 as VERSION;
@@ -160,18 +198,56 @@ use crate::{
 const DONT_IGNORE_L1_DA_MODE: bool = false;
 
 /// Rpc server.
+#[derive(Clone)]
 pub struct JsonRpcServerImpl {
     pub chain_id: ChainId,
     pub execution_config: ExecutionConfig,
     pub storage_reader: StorageReader,
     pub max_events_chunk_size: usize,
     pub max_events_keys: usize,
+    pub max_events_block_range: u64,
     pub starting_block: BlockHashAndNumber,
     pub shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
     pub pending_data: Arc<RwLock<PendingData>>,
     pub pending_classes: Arc<RwLock<PendingClasses>>,
     pub writer_client: Arc<dyn StarknetWriter>,
     pub class_manager_client: Option<SharedClassManagerClient>,
+    pub subscriptions: Subscriptions,
+}
+
+// The signature of the functions of the execution crate that run a call.
+type CallRunner<T> = fn(
+    StorageReader,
+    Option<ExecutionPendingData>,
+    StateOverrides,
+    &ChainId,
+    StateNumber,
+    BlockNumber,
+    &ContractAddress,
+    EntryPointSelector,
+    Calldata,
+    &ExecutionConfig,
+    bool,
+    Option<(SharedClassManagerClient, Handle)>,
+) -> ExecutionResult<T>;
+
+// A simulated transaction, with the state diff it induced in the format of the API.
+pub(crate) struct SimulationOutput {
+    pub transaction_trace: ExecutionTransactionTrace,
+    pub state_diff: ThinStateDiff,
+    pub fee_estimation: FeeEstimation,
+    pub gas_consumed: GasVector,
+}
+
+// The inputs for re-executing a transaction.
+struct TransactionReplay {
+    maybe_pending_data: Option<ExecutionPendingData>,
+    // The transactions of the block up to and including the re-executed transaction.
+    executable_transactions: Vec<ExecutableTransactionInput>,
+    transaction_hashes: Vec<TransactionHash>,
+    block_number: BlockNumber,
+    state_number: StateNumber,
+    block_not_reverted_validator: BlockNotRevertedValidator,
 }
 
 async fn create_class_manager_client(
@@ -228,12 +304,15 @@ impl JsonRpcServer for JsonRpcServerImpl {
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    async fn get_block_w_full_transactions(&self, block_id: BlockId) -> RpcResult<Block> {
+    async fn get_block_w_full_transactions(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Block<StreamedSeq<TransactionWithHash>>> {
         self.get_block(
             block_id,
             |mut pending_data| {
                 let client_transactions = pending_data.block.transactions_mutable().drain(..);
-                Ok(Transactions::Full(
+                Ok(StreamedSeq::from_items(
                     client_transactions
                         .map(|client_transaction| {
                             let transaction_hash = client_transaction.transaction_hash();
@@ -250,17 +329,19 @@ impl JsonRpcServer for JsonRpcServerImpl {
                 ))
             },
             |txn, block_number| {
-                let transactions = get_block_txs_by_number(txn, block_number)?;
-                let transaction_hashes = get_block_tx_hashes_by_number(txn, block_number)?;
-                Ok(Transactions::Full(
-                    transactions
-                        .into_iter()
-                        .zip(transaction_hashes)
-                        .map(|(transaction, transaction_hash)| TransactionWithHash {
-                            transaction,
-                            transaction_hash,
-                        })
-                        .collect(),
+                Ok(StreamedSeq::from_storage(
+                    self.storage_reader.clone(),
+                    get_block_txs_count_by_number(txn, block_number)?,
+                    move |txn, transaction_offset| {
+                        let (transaction, transaction_hash) = get_tx_with_hash_by_index(
+                            txn,
+                            TransactionIndex(
+                                block_number,
+                                TransactionOffsetInBlock(transaction_offset),
+                            ),
+                        )?;
+                        Ok(TransactionWithHash { transaction, transaction_hash })
+                    },
                 ))
             },
         )
@@ -271,72 +352,8 @@ impl JsonRpcServer for JsonRpcServerImpl {
     async fn get_block_w_full_transactions_and_receipts(
         &self,
         block_id: BlockId,
-    ) -> RpcResult<Block> {
-        self.get_block(
-            block_id,
-            |mut pending_data| {
-                let (client_transactions, client_receipts) =
-                    pending_data.block.transactions_and_receipts_mutable();
-                let client_transactions_and_receipts =
-                    client_transactions.drain(..).zip(client_receipts.drain(..));
-                Ok(Transactions::FullWithReceipts(
-                    client_transactions_and_receipts
-                        .map(|(client_transaction, client_transaction_receipt)| {
-                            let receipt = client_receipt_to_rpc_pending_receipt(
-                                &client_transaction,
-                                client_transaction_receipt,
-                            )?
-                            .into();
-
-                            let starknet_api_transaction: StarknetApiTransaction =
-                                client_transaction.try_into().map_err(internal_server_error)?;
-                            Ok(TransactionWithReceipt {
-                                transaction: starknet_api_transaction
-                                    .try_into()
-                                    .map_err(internal_server_error)?,
-                                receipt,
-                            })
-                        })
-                        .collect::<Result<Vec<_>, ErrorObjectOwned>>()?,
-                ))
-            },
-            |txn, block_number| {
-                let transactions = get_block_txs_by_number(txn, block_number)?;
-                let transaction_hashes = get_block_tx_hashes_by_number(txn, block_number)?;
-                Ok(Transactions::FullWithReceipts(
-                    transactions
-                        .into_iter()
-                        .zip(transaction_hashes)
-                        .enumerate()
-                        .map(|(transaction_offset, (transaction, transaction_hash))| {
-                            let transaction_index = TransactionIndex(
-                                block_number,
-                                TransactionOffsetInBlock(transaction_offset),
-                            );
-                            let msg_hash = match &transaction {
-                                Transaction::L1Handler(l1_handler_tx) => {
-                                    Some(l1_handler_tx.calc_msg_hash())
-                                }
-                                _ => None,
-                            };
-                            let transaction_version = transaction.version();
-                            Ok(TransactionWithReceipt {
-                                transaction,
-                                receipt: get_non_pending_receipt(
-                                    txn,
-                                    transaction_index,
-                                    transaction_hash,
-                                    transaction_version,
-                                    msg_hash,
-                                )?
-                                .into(),
-                            })
-                        })
-                        .collect::<Result<Vec<_>, ErrorObjectOwned>>()?,
-                ))
-            },
-        )
-        .await
+    ) -> RpcResult<Block<StreamedSeq<TransactionWithReceipt>>> {
+        self.get_block_with_receipts(block_id).await
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
@@ -541,59 +558,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
         &self,
         transaction_hash: TransactionHash,
     ) -> RpcResult<GeneralTransactionReceipt> {
-        verify_storage_scope(&self.storage_reader)?;
-
-        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
-
-        if let Some(transaction_index) =
-            txn.get_transaction_idx_by_hash(&transaction_hash).map_err(internal_server_error)?
-        {
-            let tx = txn
-                .get_transaction(transaction_index)
-                .map_err(internal_server_error)?
-                .unwrap_or_else(|| panic!("Should have tx {}", transaction_hash));
-
-            // TODO(Shahak): Add version function to transaction in SN_API.
-            let tx_version = match &tx {
-                StarknetApiTransaction::Declare(tx) => tx.version(),
-                StarknetApiTransaction::Deploy(tx) => tx.version,
-                StarknetApiTransaction::DeployAccount(tx) => tx.version(),
-                StarknetApiTransaction::Invoke(tx) => tx.version(),
-                StarknetApiTransaction::L1Handler(tx) => tx.version,
-            };
-
-            let msg_hash = match tx {
-                StarknetApiTransaction::L1Handler(l1_handler_tx) => {
-                    Some(l1_handler_tx.calc_msg_hash())
-                }
-                _ => None,
-            };
-
-            get_non_pending_receipt(&txn, transaction_index, transaction_hash, tx_version, msg_hash)
-        } else {
-            // The transaction is not in any non-pending block. Search for it in the pending block
-            // and if it's not found, return error.
-
-            // TODO(shahak): Consider cloning the transactions and the receipts in order to free
-            // the lock sooner (Check which is better).
-            let pending_data = read_pending_data(&self.pending_data, &txn).await?;
-
-            let client_transaction_receipt = pending_data
-                .block
-                .transaction_receipts()
-                .iter()
-                .find(|receipt| receipt.transaction_hash == transaction_hash)
-                .ok_or_else(|| ErrorObjectOwned::from(TRANSACTION_HASH_NOT_FOUND))?
-                .clone();
-            let client_transaction = &pending_data
-                .block
-                .transactions()
-                .iter()
-                .find(|tx| tx.transaction_hash() == transaction_hash)
-                .ok_or_else(|| ErrorObjectOwned::from(TRANSACTION_HASH_NOT_FOUND))?
-                .clone();
-            client_receipt_to_rpc_pending_receipt(client_transaction, client_transaction_receipt)
-        }
+        self.get_general_transaction_receipt(transaction_hash).await
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
@@ -713,7 +678,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
-    async fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk> {
+    async fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk<StreamedSeq<Event>>> {
         verify_storage_scope(&self.storage_reader)?;
 
         // Check the chunk size.
@@ -735,7 +700,10 @@ impl JsonRpcServer for JsonRpcServerImpl {
                 );
             }
             // There are no blocks.
-            return Ok(EventsChunk { events: vec![], continuation_token: None });
+            return Ok(EventsChunk {
+                events: StreamedSeq::from_items(vec![]),
+                continuation_token: None,
+            });
         };
         let from_block_number = match filter.from_block {
             None => BlockNumber(0),
@@ -748,19 +716,31 @@ impl JsonRpcServer for JsonRpcServerImpl {
         };
 
         if from_block_number > to_block_number {
-            return Ok(EventsChunk { events: vec![], continuation_token: None });
+            return Ok(EventsChunk {
+                events: StreamedSeq::from_items(vec![]),
+                continuation_token: None,
+            });
         }
 
         // Get the event index. If there's a continuation token we take the event index from there.
         // Otherwise, we take the first index in the from_block_number.
         let start_event_index = match &filter.continuation_token {
             Some(token) => token.parse()?.0,
-            None => EventIndex(
-                TransactionIndex(from_block_number, TransactionOffsetInBlock(0)),
-                EventIndexInTransactionOutput(0),
-            ),
+            None => first_event_index_in_block(from_block_number),
         };
 
+        // Limit the number of blocks scanned in this call. The rest of the range is left for the
+        // next call, through a continuation token that points to the first block that wasn't
+        // scanned.
+        let first_block_in_call = start_event_index.0.0;
+        let last_block_in_call = BlockNumber(
+            first_block_in_call.0.saturating_add(self.max_events_block_range.saturating_sub(1)),
+        );
+        let is_block_range_limited = last_block_in_call < to_block_number;
+        if is_block_range_limited {
+            to_block_number = last_block_in_call;
+        }
+
         let include_pending_block = to_block_number > latest_block_number;
         if include_pending_block {
             to_block_number = to_block_number.prev().expect(
@@ -772,50 +752,66 @@ impl JsonRpcServer for JsonRpcServerImpl {
         // Once we collected enough events, we continue to check if there are any more events
         // corresponding to the requested filter. If there are, we return a continuation token
         // pointing to the next relevant event. Otherwise, we return a continuation token None.
-        let mut filtered_events = vec![];
-        if start_event_index.0.0 <= latest_block_number {
+        // When filtering by keys, the blocks whose event keys filter rules out a match are skipped
+        // without reading their events.
+        // Only the indices of the stored events are collected, and the events are read again while
+        // the response is serialized.
+        let mut stored_event_indices = vec![];
+        let mut pending_events = vec![];
+        let filter_by_keys = filter.keys.iter().any(|keys| !keys.is_empty());
+        let mut block_start_event_index = start_event_index;
+        'blocks: while block_start_event_index.0.0 <= latest_block_number
+            && block_start_event_index.0.0 <= to_block_number
+        {
+            let current_block_number = block_start_event_index.0.0;
+            if filter_by_keys
+                && !txn
+                    .block_may_match_event_keys(current_block_number, &filter.keys)
+                    .map_err(internal_server_error)?
+            {
+                block_start_event_index =
+                    first_event_index_in_block(current_block_number.unchecked_next());
+                continue;
+            }
             for ((from_address, event_index), content) in txn
-                .iter_events(filter.address, start_event_index, to_block_number)
+                .iter_events(filter.address, block_start_event_index, to_block_number)
                 .map_err(internal_server_error)?
             {
                 let block_number = (event_index.0).0;
                 if block_number > to_block_number {
-                    break;
+                    break 'blocks;
                 }
                 if let Some(filter_address) = filter.address {
                     if from_address != filter_address {
                         // The iterator of this loop outputs only events that have the filter's
                         // address, unless there are no more such events and then it outputs other
                         // events, and we can stop the iteration.
-                        break;
+                        break 'blocks;
                     }
                 }
+                if filter_by_keys && block_number != current_block_number {
+                    // Check the event keys filter of the next block before reading its events.
+                    block_start_event_index = first_event_index_in_block(block_number);
+                    continue 'blocks;
+                }
                 // TODO(Shahak): Consider changing empty sets in the filer keys to None.
-                if do_event_keys_match_filter(&content, &filter) {
-                    if filtered_events.len() == filter.chunk_size {
+                if do_event_keys_match_filter(&content, &filter.keys) {
+                    if stored_event_indices.len() == filter.chunk_size {
                         return Ok(EventsChunk {
-                            events: filtered_events,
+                            events: stored_and_pending_events(
+                                self.storage_reader.clone(),
+                                stored_event_indices,
+                                pending_events,
+                            ),
                             continuation_token: Some(ContinuationToken::new(
                                 ContinuationTokenAsStruct(event_index),
                             )?),
                         });
                     }
-                    let header: BlockHeader = get_block_header_by_number(&txn, block_number)
-                        .map_err(internal_server_error)?
-                        .into();
-                    let transaction_hash = txn
-                        .get_transaction_hash_by_idx(&event_index.0)
-                        .map_err(internal_server_error)?
-                        .ok_or_else(|| internal_server_error("Unknown internal error."))?;
-                    let emitted_event = Event {
-                        block_hash: Some(header.block_hash),
-                        block_number: Some(block_number),
-                        transaction_hash,
-                        event: starknet_api::transaction::Event { from_address, content },
-                    };
-                    filtered_events.push(emitted_event);
+                    stored_event_indices.push(event_index);
                 }
             }
+            break;
         }
 
         if include_pending_block {
@@ -836,9 +832,13 @@ impl JsonRpcServer for JsonRpcServerImpl {
                     if transaction_offset == transaction_start && event_offset < event_start {
                         continue;
                     }
-                    if filtered_events.len() == filter.chunk_size {
+                    if stored_event_indices.len() + pending_events.len() == filter.chunk_size {
                         return Ok(EventsChunk {
-                            events: filtered_events,
+                            events: stored_and_pending_events(
+                                self.storage_reader.clone(),
+                                stored_event_indices,
+                                pending_events,
+                            ),
                             continuation_token: Some(ContinuationToken::new(
                                 ContinuationTokenAsStruct(EventIndex(
                                     TransactionIndex(
@@ -850,7 +850,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
                             )?),
                         });
                     }
-                    if !do_event_keys_match_filter(&event.content, &filter) {
+                    if !do_event_keys_match_filter(&event.content, &filter.keys) {
                         continue;
                     }
                     if let Some(filter_address) = filter.address {
@@ -858,7 +858,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
                             continue;
                         }
                     }
-                    filtered_events.push(Event {
+                    pending_events.push(Event {
                         block_hash: None,
                         block_number: None,
                         transaction_hash: receipt.transaction_hash,
@@ -868,7 +868,20 @@ impl JsonRpcServer for JsonRpcServerImpl {
             }
         }
 
-        Ok(EventsChunk { events: filtered_events, continuation_token: None })
+        let continuation_token = match is_block_range_limited {
+            true => Some(ContinuationToken::new(ContinuationTokenAsStruct(
+                first_event_index_in_block(to_block_number.unchecked_next()),
+            ))?),
+            false => None,
+        };
+        Ok(EventsChunk {
+            events: stored_and_pending_events(
+                self.storage_reader.clone(),
+                stored_event_indices,
+                pending_events,
+            ),
+            continuation_token,
+        })
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
@@ -893,55 +906,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
 
     #[instrument(skip(self), level = "debug", err, ret)]
     async fn call(&self, request: CallRequest, block_id: BlockId) -> RpcResult<Vec<Felt>> {
-        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
-        let maybe_pending_data = if let BlockId::Tag(Tag::Pending) = block_id {
-            Some(client_pending_data_to_execution_pending_data(
-                read_pending_data(&self.pending_data, &txn).await?,
-                self.pending_classes.read().await.clone(),
-            ))
-        } else {
-            None
-        };
-        let block_number = get_accepted_block_number(&txn, block_id)?;
-        let block_not_reverted_validator = BlockNotRevertedValidator::new(block_number, &txn)?;
-        drop(txn);
-        let state_number = StateNumber::unchecked_right_after_block(block_number);
-        let execution_config = self.execution_config;
-
-        let chain_id = self.chain_id.clone();
-        let reader = self.storage_reader.clone();
-        let contract_address_copy = request.contract_address;
-        let class_manager_client =
-            create_class_manager_client(self.class_manager_client.clone()).await;
-
-        let res = tokio::task::spawn_blocking(move || {
-            execute_call(
-                reader,
-                maybe_pending_data,
-                &chain_id,
-                state_number,
-                block_number,
-                &contract_address_copy,
-                request.entry_point_selector,
-                request.calldata,
-                &execution_config,
-                DONT_IGNORE_L1_DA_MODE,
-                class_manager_client,
-            )
-        })
-        .await
-        .map_err(internal_server_error)?
-        .map_err(execution_error_to_error_object_owned)?;
-
-        if res.failed {
-            let contract_err = ContractError { revert_error: format_panic_data(&res.retdata.0) };
-            let rpc_err: JsonRpcError<ContractError> = contract_err.into();
-            return Err(rpc_err.into());
-        }
-
-        block_not_reverted_validator.validate(&self.storage_reader)?;
-
-        Ok(res.retdata.0)
+        self.call_with_state_overrides(request, block_id, StateOverrides::default()).await
     }
 
     #[instrument(skip(self), level = "debug", err, ret)]
@@ -1004,11 +969,65 @@ impl JsonRpcServer for JsonRpcServerImpl {
         simulation_flags: Vec<SimulationFlag>,
         block_id: BlockId,
     ) -> RpcResult<Vec<FeeEstimation>> {
-        trace!("Estimating fee of transactions: {:#?}", transactions);
-        let validate = !simulation_flags.contains(&SimulationFlag::SkipValidate);
+        self.estimate_fee_with_state_overrides(
+            transactions,
+            simulation_flags,
+            block_id,
+            StateOverrides::default(),
+        )
+        .await
+    }
 
-        let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+    #[instrument(skip(self, transactions), level = "debug", err, ret)]
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+    ) -> RpcResult<Vec<SimulatedTransaction>> {
+        self.simulate_transactions_with_state_overrides(
+            block_id,
+            transactions,
+            simulation_flags,
+            StateOverrides::default(),
+        )
+        .await
+    }
+
+    #[instrument(skip(self), level = "debug", err)]
+    async fn trace_transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionTrace> {
+        let simulation_output = self.simulate_transaction_replay(transaction_hash).await?;
+        Ok((simulation_output.transaction_trace, simulation_output.state_diff).into())
+    }
+
+    #[instrument(skip(self), level = "debug", err)]
+    async fn trace_block_transactions(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<TransactionTraceWithHash>> {
+        Ok(self
+            .simulate_block_transactions(block_id)
+            .await?
+            .into_iter()
+            .map(|(transaction_hash, simulation_output)| TransactionTraceWithHash {
+                transaction_hash,
+                trace_root: (simulation_output.transaction_trace, simulation_output.state_diff)
+                    .into(),
+            })
+            .collect())
+    }
 
+    #[instrument(skip(self, message), level = "debug", err)]
+    async fn estimate_message_fee(
+        &self,
+        message: MessageFromL1,
+        block_id: BlockId,
+    ) -> RpcResult<FeeEstimation> {
+        trace!("Estimating fee of message: {:#?}", message);
+        let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
         let maybe_pending_data = if let BlockId::Tag(Tag::Pending) = block_id {
             Some(client_pending_data_to_execution_pending_data(
                 read_pending_data(&self.pending_data, &storage_txn).await?,
@@ -1017,16 +1036,19 @@ impl JsonRpcServer for JsonRpcServerImpl {
         } else {
             None
         };
-
+        // Convert the message to an L1 handler transaction, and estimate the fee of the
+        // transaction.
+        // The fee input is used to bound the amount of fee used. Because we want to estimate the
+        // fee, we pass u128::MAX so the execution won't fail.
         let executable_txns =
-            transactions.into_iter().map(|tx| tx.try_into()).collect::<Result<_, _>>()?;
+            vec![ExecutableTransactionInput::L1Handler(message.into(), Fee(u128::MAX), false)];
 
         let block_number = get_accepted_block_number(&storage_txn, block_id)?;
         let block_not_reverted_validator =
             BlockNotRevertedValidator::new(block_number, &storage_txn)?;
         drop(storage_txn);
         let state_number = StateNumber::unchecked_right_after_block(block_number);
-        let execution_config = self.execution_config;
+        let execution_config = self.execution_config.clone();
 
         let chain_id = self.chain_id.clone();
         let reader = self.storage_reader.clone();
@@ -1039,10 +1061,11 @@ impl JsonRpcServer for JsonRpcServerImpl {
                 &chain_id,
                 reader,
                 maybe_pending_data,
+                StateOverrides::default(),
                 state_number,
                 block_number,
                 &execution_config,
-                validate,
+                false,
                 DONT_IGNORE_L1_DA_MODE,
                 class_manager_client,
             )
@@ -1053,26 +1076,522 @@ impl JsonRpcServer for JsonRpcServerImpl {
         block_not_reverted_validator.validate(&self.storage_reader)?;
 
         match estimate_fee_result {
-            Ok(Ok(fees)) => Ok(fees),
-            Ok(Err(reverted_tx)) => {
-                Err(ErrorObjectOwned::from(JsonRpcError::<TransactionExecutionError>::from(
-                    TransactionExecutionError {
-                        transaction_index: reverted_tx.index,
-                        execution_error: reverted_tx.revert_reason,
-                    },
-                )))
+            Ok(Ok(mut fee_as_vec)) => {
+                if fee_as_vec.len() != 1 {
+                    return Err(internal_server_error(format!(
+                        "Expected a single fee, got {}",
+                        fee_as_vec.len()
+                    )));
+                }
+                let Some(fee_estimation) = fee_as_vec.pop() else {
+                    return Err(internal_server_error(
+                        "Expected a single fee, got an empty vector",
+                    ));
+                };
+                Ok(fee_estimation)
             }
+            // Error in the execution of the contract.
+            Ok(Err(reverted_tx)) => Err(JsonRpcError::<ContractError>::from(ContractError {
+                revert_error: reverted_tx.revert_reason,
+            })
+            .into()),
+            // Internal error during the execution.
             Err(err) => Err(internal_server_error(err)),
         }
     }
 
-    #[instrument(skip(self, transactions), level = "debug", err, ret)]
-    async fn simulate_transactions(
+    #[instrument(skip(self), level = "debug", err)]
+    fn get_compiled_class(
         &self,
         block_id: BlockId,
-        transactions: Vec<BroadcastedTransaction>,
-        simulation_flags: Vec<SimulationFlag>,
+        class_hash: ClassHash,
+    ) -> RpcResult<(CompiledContractClass, SierraVersion)> {
+        let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        let state_reader = storage_txn.get_state_reader().map_err(internal_server_error)?;
+        let block_number = get_accepted_block_number(&storage_txn, block_id)?;
+
+        // Check if this class exists in the Cairo1 classes table.
+        if let Some(class_definition_block_number) = state_reader
+            .get_class_definition_block_number(&class_hash)
+            .map_err(internal_server_error)?
+        {
+            if class_definition_block_number > block_number {
+                return Err(ErrorObjectOwned::from(CLASS_HASH_NOT_FOUND));
+            }
+            let (option_casm, option_sierra) = storage_txn
+                .get_casm_and_sierra(&class_hash)
+                .map_err(internal_server_error_with_msg)?;
+
+            // Check if both options are `Some`.
+            let (casm, sierra) = option_casm
+                .zip(option_sierra)
+                .ok_or_else(|| ErrorObjectOwned::from(CLASS_HASH_NOT_FOUND))?;
+            let sierra_version = SierraVersion::extract_from_program(&sierra.sierra_program)
+                .map_err(internal_server_error_with_msg)?;
+            return Ok((CompiledContractClass::V1(casm), sierra_version));
+        }
+
+        // Check if this class exists in the Cairo0 classes table.
+        let state_number = StateNumber::right_after_block(block_number)
+            .ok_or_else(|| internal_server_error("Could not compute state number"))?;
+        let deprecated_compiled_contract_class = state_reader
+            .get_deprecated_class_definition_at(state_number, &class_hash)
+            .map_err(internal_server_error)?
+            .ok_or_else(|| ErrorObjectOwned::from(CLASS_HASH_NOT_FOUND))?;
+        Ok((
+            CompiledContractClass::V0(deprecated_compiled_contract_class),
+            SierraVersion::DEPRECATED,
+        ))
+    }
+
+    async fn subscribe_new_heads(
+        &self,
+        pending: PendingSubscriptionSink,
+        block_id: Option<BlockId>,
+    ) -> SubscriptionResult {
+        let first_block = match self.get_subscription_first_block(block_id) {
+            Ok(first_block) => first_block,
+            Err(err) => {
+                pending.reject(err).await;
+                return Ok(());
+            }
+        };
+        let subscription = ChainSubscription::accept(pending, &self.subscriptions).await?;
+        subscription
+            .stream_block_items(&self.storage_reader, first_block, |txn, block_number| {
+                Ok(vec![BlockHeader::from(get_block_header_by_number(txn, block_number)?)])
+            })
+            .await
+    }
+
+    async fn subscribe_events(
+        &self,
+        pending: PendingSubscriptionSink,
+        from_address: Option<ContractAddress>,
+        keys: Option<Vec<HashSet<EventKey>>>,
+        block_id: Option<BlockId>,
+    ) -> SubscriptionResult {
+        let keys = keys.unwrap_or_default();
+        let first_block = match keys.len() > self.max_events_keys {
+            true => Err(ErrorObjectOwned::from(TOO_MANY_KEYS_IN_FILTER)),
+            false => self.get_subscription_first_block(block_id),
+        };
+        let first_block = match first_block {
+            Ok(first_block) => first_block,
+            Err(err) => {
+                pending.reject(err).await;
+                return Ok(());
+            }
+        };
+        let subscription = ChainSubscription::accept(pending, &self.subscriptions).await?;
+        subscription
+            .stream_block_items(&self.storage_reader, first_block, |txn, block_number| {
+                get_block_events(txn, block_number, from_address, &keys)
+            })
+            .await
+    }
+
+    async fn subscribe_transaction_status(
+        &self,
+        pending: PendingSubscriptionSink,
+        transaction_hash: TransactionHash,
+    ) -> SubscriptionResult {
+        if let Err(err) = verify_storage_scope(&self.storage_reader) {
+            pending.reject(err).await;
+            return Ok(());
+        }
+        let mut subscription = ChainSubscription::accept(pending, &self.subscriptions).await?;
+        // The status is sent whenever it changes, until the transaction is accepted on L1.
+        let mut sent_status = None;
+        let mut transaction_block_number = None;
+        loop {
+            if self.is_transaction_known(transaction_hash).await? {
+                let receipt = self.get_transaction_receipt(transaction_hash).await?;
+                if let GeneralTransactionReceipt::TransactionReceipt(receipt) = &receipt {
+                    transaction_block_number = Some(receipt.block_number);
+                }
+                let status = receipt.transaction_status();
+                if sent_status.as_ref() != Some(&status) {
+                    subscription
+                        .send(&TransactionStatusNotification {
+                            transaction_hash,
+                            status: status.clone(),
+                        })
+                        .await?;
+                    if status.finality_status == TransactionFinalityStatus::AcceptedOnL1 {
+                        return Ok(());
+                    }
+                    sent_status = Some(status);
+                }
+            }
+            let Some(update) = subscription.next_update().await? else {
+                return Ok(());
+            };
+            if let ChainUpdate::Reorg { first, last } = update {
+                if transaction_block_number.is_some_and(|block_number| first.number <= block_number)
+                {
+                    subscription.send_reorg(first, last).await?;
+                    sent_status = None;
+                    transaction_block_number = None;
+                }
+            }
+        }
+    }
+
+    async fn subscribe_pending_transactions(
+        &self,
+        pending: PendingSubscriptionSink,
+        transaction_details: Option<bool>,
+        sender_address: Option<Vec<ContractAddress>>,
+    ) -> SubscriptionResult {
+        if sender_address
+            .as_ref()
+            .is_some_and(|sender_address| sender_address.len() > MAX_SENDER_ADDRESSES_IN_FILTER)
+        {
+            pending.reject(ErrorObjectOwned::from(TOO_MANY_ADDRESSES_IN_FILTER)).await;
+            return Ok(());
+        }
+        if !self.subscriptions.has_mempool() {
+            pending
+                .reject(internal_server_error_with_msg(
+                    "Pending transactions are unavailable without a mempool.",
+                ))
+                .await;
+            return Ok(());
+        }
+        let mut subscription = ChainSubscription::accept(pending, &self.subscriptions).await?;
+        while let Some(update) = subscription.next_update().await? {
+            let ChainUpdate::NewPendingTransactions(transactions) = update else {
+                continue;
+            };
+            for transaction in transactions.iter() {
+                if let Some(sender_address) = &sender_address {
+                    if !sender_address.contains(&transaction.contract_address()) {
+                        continue;
+                    }
+                }
+                let transaction_hash = transaction.tx_hash;
+                let pending_transaction = match transaction_details.unwrap_or_default() {
+                    true => {
+                        let starknet_api_transaction =
+                            StarknetApiTransaction::from(transaction.tx.clone());
+                        PendingTransaction::Full(TransactionWithHash {
+                            transaction: starknet_api_transaction.try_into()?,
+                            transaction_hash,
+                        })
+                    }
+                    false => PendingTransaction::Hash(transaction_hash),
+                };
+                subscription.send(&pending_transaction).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn read_pending_data<Mode: TransactionKind>(
+    pending_data: &Arc<RwLock<PendingData>>,
+    txn: &StorageTxn<'_, Mode>,
+) -> RpcResult<PendingData> {
+    let latest_header = match get_latest_block_number(txn)? {
+        Some(latest_block_number) => get_block_header_by_number(txn, latest_block_number)?,
+        None => starknet_api::block::BlockHeader {
+            block_header_without_hash: BlockHeaderWithoutHash {
+                parent_hash: BlockHash(StarkHash::from_hex_unchecked(GENESIS_HASH)),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    };
+    let pending_data = &pending_data.read().await;
+    if pending_data.block.parent_block_hash() == latest_header.block_hash {
+        Ok((*pending_data).clone())
+    } else {
+        Ok(PendingData {
+            block: PendingBlockOrDeprecated::Deprecated(DeprecatedPendingBlock {
+                parent_block_hash: latest_header.block_hash,
+                eth_l1_gas_price: latest_header.block_header_without_hash.l1_gas_price.price_in_wei,
+                strk_l1_gas_price: latest_header
+                    .block_header_without_hash
+                    .l1_gas_price
+                    .price_in_fri,
+                timestamp: latest_header.block_header_without_hash.timestamp,
+                sequencer_address: latest_header.block_header_without_hash.sequencer,
+                starknet_version: latest_header
+                    .block_header_without_hash
+                    .starknet_version
+                    .to_string(),
+                ..Default::default()
+            }),
+            state_update: ClientPendingStateUpdate {
+                old_root: latest_header.block_header_without_hash.state_root,
+                state_diff: Default::default(),
+            },
+        })
+    }
+}
+
+impl JsonRpcServerImpl {
+    // Runs a call on the state of the given block with the given state overrides, using the given
+    // function of the execution crate.
+    async fn run_call<T: Send + 'static>(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        state_overrides: StateOverrides,
+        run: CallRunner<T>,
+    ) -> RpcResult<T> {
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        let maybe_pending_data = if let BlockId::Tag(Tag::Pending) = block_id {
+            Some(client_pending_data_to_execution_pending_data(
+                read_pending_data(&self.pending_data, &txn).await?,
+                self.pending_classes.read().await.clone(),
+            ))
+        } else {
+            None
+        };
+        let block_number = get_accepted_block_number(&txn, block_id)?;
+        let block_not_reverted_validator = BlockNotRevertedValidator::new(block_number, &txn)?;
+        drop(txn);
+        let state_number = StateNumber::unchecked_right_after_block(block_number);
+        let execution_config = self.execution_config.clone();
+
+        let chain_id = self.chain_id.clone();
+        let reader = self.storage_reader.clone();
+        let contract_address_copy = request.contract_address;
+        let class_manager_client =
+            create_class_manager_client(self.class_manager_client.clone()).await;
+
+        let res = tokio::task::spawn_blocking(move || {
+            run(
+                reader,
+                maybe_pending_data,
+                state_overrides,
+                &chain_id,
+                state_number,
+                block_number,
+                &contract_address_copy,
+                request.entry_point_selector,
+                request.calldata,
+                &execution_config,
+                DONT_IGNORE_L1_DA_MODE,
+                class_manager_client,
+            )
+        })
+        .await
+        .map_err(internal_server_error)?
+        .map_err(execution_error_to_error_object_owned)?;
+
+        block_not_reverted_validator.validate(&self.storage_reader)?;
+
+        Ok(res)
+    }
+
+    // Calls the contract on the state of the given block with the given state overrides.
+    pub(crate) async fn call_with_state_overrides(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        state_overrides: StateOverrides,
+    ) -> RpcResult<Vec<Felt>> {
+        let res = self.run_call(request, block_id, state_overrides, execute_call).await?;
+
+        if res.failed {
+            let contract_err = ContractError { revert_error: format_panic_data(&res.retdata.0) };
+            let rpc_err: JsonRpcError<ContractError> = contract_err.into();
+            return Err(rpc_err.into());
+        }
+
+        Ok(res.retdata.0)
+    }
+
+    // Returns the call tree of the call on the state of the given block with the given state
+    // overrides. Failed calls are traced as well.
+    pub(crate) async fn trace_call(
+        &self,
+        request: CallRequest,
+        block_id: BlockId,
+        state_overrides: StateOverrides,
+    ) -> RpcResult<CallTraceOutput> {
+        self.run_call(request, block_id, state_overrides, exec_trace_call).await
+    }
+
+    // Estimates the fee of the transactions on the state of the given block with the given state
+    // overrides.
+    pub(crate) async fn estimate_fee_with_state_overrides(
+        &self,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        block_id: BlockId,
+        state_overrides: StateOverrides,
+    ) -> RpcResult<Vec<FeeEstimation>> {
+        trace!("Estimating fee of transactions: {:#?}", transactions);
+        let validate = !simulation_flags.contains(&SimulationFlag::SkipValidate);
+
+        let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+
+        let maybe_pending_data = if let BlockId::Tag(Tag::Pending) = block_id {
+            Some(client_pending_data_to_execution_pending_data(
+                read_pending_data(&self.pending_data, &storage_txn).await?,
+                self.pending_classes.read().await.clone(),
+            ))
+        } else {
+            None
+        };
+
+        let executable_txns =
+            transactions.into_iter().map(|tx| tx.try_into()).collect::<Result<_, _>>()?;
+
+        let block_number = get_accepted_block_number(&storage_txn, block_id)?;
+        let block_not_reverted_validator =
+            BlockNotRevertedValidator::new(block_number, &storage_txn)?;
+        drop(storage_txn);
+        let state_number = StateNumber::unchecked_right_after_block(block_number);
+        let execution_config = self.execution_config.clone();
+
+        let chain_id = self.chain_id.clone();
+        let reader = self.storage_reader.clone();
+        let class_manager_client =
+            create_class_manager_client(self.class_manager_client.clone()).await;
+
+        let estimate_fee_result = tokio::task::spawn_blocking(move || {
+            exec_estimate_fee(
+                executable_txns,
+                &chain_id,
+                reader,
+                maybe_pending_data,
+                state_overrides,
+                state_number,
+                block_number,
+                &execution_config,
+                validate,
+                DONT_IGNORE_L1_DA_MODE,
+                class_manager_client,
+            )
+        })
+        .await
+        .map_err(internal_server_error)?;
+
+        block_not_reverted_validator.validate(&self.storage_reader)?;
+
+        match estimate_fee_result {
+            Ok(Ok(fees)) => Ok(fees),
+            Ok(Err(reverted_tx)) => {
+                Err(ErrorObjectOwned::from(JsonRpcError::<TransactionExecutionError>::from(
+                    TransactionExecutionError {
+                        transaction_index: reverted_tx.index,
+                        execution_error: reverted_tx.revert_reason,
+                    },
+                )))
+            }
+            Err(err) => Err(internal_server_error(err)),
+        }
+    }
+
+    // Suggests the resource bounds of the transactions on the state of the given block with the
+    // given state overrides.
+    pub(crate) async fn estimate_resource_bounds(
+        &self,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        block_id: BlockId,
+        state_overrides: StateOverrides,
+    ) -> RpcResult<Vec<ResourceBoundsEstimation>> {
+        trace!("Estimating resource bounds of transactions: {:#?}", transactions);
+        let validate = !simulation_flags.contains(&SimulationFlag::SkipValidate);
+
+        let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+
+        let maybe_pending_data = if let BlockId::Tag(Tag::Pending) = block_id {
+            Some(client_pending_data_to_execution_pending_data(
+                read_pending_data(&self.pending_data, &storage_txn).await?,
+                self.pending_classes.read().await.clone(),
+            ))
+        } else {
+            None
+        };
+
+        let executable_txns =
+            transactions.into_iter().map(|tx| tx.try_into()).collect::<Result<_, _>>()?;
+
+        let block_number = get_accepted_block_number(&storage_txn, block_id)?;
+        let block_not_reverted_validator =
+            BlockNotRevertedValidator::new(block_number, &storage_txn)?;
+        drop(storage_txn);
+        let state_number = StateNumber::unchecked_right_after_block(block_number);
+        let execution_config = self.execution_config.clone();
+
+        let chain_id = self.chain_id.clone();
+        let reader = self.storage_reader.clone();
+        let class_manager_client =
+            create_class_manager_client(self.class_manager_client.clone()).await;
+
+        let estimation_result = tokio::task::spawn_blocking(move || {
+            exec_estimate_resource_bounds(
+                executable_txns,
+                &chain_id,
+                reader,
+                maybe_pending_data,
+                state_overrides,
+                state_number,
+                block_number,
+                &execution_config,
+                validate,
+                DONT_IGNORE_L1_DA_MODE,
+                class_manager_client,
+            )
+        })
+        .await
+        .map_err(internal_server_error)?;
+
+        block_not_reverted_validator.validate(&self.storage_reader)?;
+
+        match estimation_result {
+            Ok(estimations) => Ok(estimations),
+            Err(ExecutionError::TransactionExecutionError {
+                transaction_index,
+                execution_error,
+            }) => Err(ErrorObjectOwned::from(JsonRpcError::<TransactionExecutionError>::from(
+                TransactionExecutionError { transaction_index, execution_error },
+            ))),
+            Err(ExecutionError::ResourceBoundsNotSupported { .. }) => {
+                Err(UNSUPPORTED_TX_VERSION.into())
+            }
+            Err(err) => Err(execution_error_to_error_object_owned(err)),
+        }
+    }
+
+    // Simulates the transactions on the state of the given block with the given state overrides.
+    pub(crate) async fn simulate_transactions_with_state_overrides(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        state_overrides: StateOverrides,
     ) -> RpcResult<Vec<SimulatedTransaction>> {
+        Ok(self
+            .simulate(block_id, transactions, simulation_flags, state_overrides)
+            .await?
+            .into_iter()
+            .map(|simulation_output| SimulatedTransaction {
+                transaction_trace: (
+                    simulation_output.transaction_trace,
+                    simulation_output.state_diff,
+                )
+                    .into(),
+                fee_estimation: simulation_output.fee_estimation,
+            })
+            .collect())
+    }
+
+    // Simulates the given transactions on top of the given block.
+    pub(crate) async fn simulate(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+        state_overrides: StateOverrides,
+    ) -> RpcResult<Vec<SimulationOutput>> {
         trace!("Simulating transactions: {:#?}", transactions);
         let executable_txns =
             transactions.into_iter().map(|tx| tx.try_into()).collect::<Result<_, _>>()?;
@@ -1093,7 +1612,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
             BlockNotRevertedValidator::new(block_number, &storage_txn)?;
         drop(storage_txn);
         let state_number = StateNumber::unchecked_right_after_block(block_number);
-        let execution_config = self.execution_config;
+        let execution_config = self.execution_config.clone();
 
         let chain_id = self.chain_id.clone();
         let reader = self.storage_reader.clone();
@@ -1110,6 +1629,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
                 &chain_id,
                 reader,
                 maybe_pending_data,
+                state_overrides,
                 state_number,
                 block_number,
                 &execution_config,
@@ -1127,132 +1647,26 @@ impl JsonRpcServer for JsonRpcServerImpl {
 
         let mut res = vec![];
         for simulation_output in simulation_results {
-            let state_diff = self
-                .convert_thin_state_diff(
-                    simulation_output.induced_state_diff,
-                    block_id,
-                    block_number,
-                )
-                .await?;
-            res.push(SimulatedTransaction {
-                transaction_trace: (simulation_output.transaction_trace, state_diff).into(),
-                fee_estimation: simulation_output.fee_estimation,
-            });
+            res.push(self.to_simulation_output(simulation_output, block_id, block_number).await?);
         }
         Ok(res)
     }
 
-    #[instrument(skip(self), level = "debug", err)]
-    async fn trace_transaction(
+    // Re-executes the given transaction on the state it was executed on.
+    pub(crate) async fn simulate_transaction_replay(
         &self,
         transaction_hash: TransactionHash,
-    ) -> RpcResult<TransactionTrace> {
-        let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
-
-        let pending_block = read_pending_data(&self.pending_data, &storage_txn).await?.block;
-        // Search for the transaction inside the pending block.
-        let (
+    ) -> RpcResult<SimulationOutput> {
+        let TransactionReplay {
             maybe_pending_data,
             executable_transactions,
             transaction_hashes,
             block_number,
             state_number,
-        ) = if let Some((pending_transaction_offset, _)) = pending_block
-            .transaction_receipts()
-            .iter()
-            .enumerate()
-            .find(|(_, receipt)| receipt.transaction_hash == transaction_hash)
-        {
-            // If there are no blocks in the network and there is a pending block, as an edge
-            // case we treat this as if the pending block is empty.
-            let block_number =
-                get_latest_block_number(&storage_txn)?.ok_or(INVALID_TRANSACTION_HASH)?;
-            let state_number = StateNumber::unchecked_right_after_block(block_number);
-            let executable_transactions = pending_block
-                .transactions()
-                .iter()
-                .take(pending_transaction_offset + 1)
-                .map(|client_transaction| {
-                    let starknet_api_transaction: StarknetApiTransaction =
-                        client_transaction.clone().try_into().map_err(internal_server_error)?;
-                    stored_txn_to_executable_txn(
-                        starknet_api_transaction,
-                        &storage_txn,
-                        state_number,
-                    )
-                })
-                .collect::<Result<_, _>>()?;
-            let transaction_hashes = pending_block
-                .transaction_receipts()
-                .iter()
-                .map(|receipt| receipt.transaction_hash)
-                .collect();
-            let maybe_pending_data = Some(ExecutionPendingData {
-                timestamp: pending_block.timestamp(),
-                l1_gas_price: pending_block.l1_gas_price(),
-                l1_data_gas_price: pending_block.l1_data_gas_price(),
-                l2_gas_price: pending_block.l2_gas_price(),
-                l1_da_mode: pending_block.l1_da_mode(),
-                sequencer: pending_block.sequencer_address(),
-                // The pending state diff should be empty since we look at the state in the
-                // start of the pending block.
-                // Not using ..Default::default() to avoid missing fields in the future.
-                storage_diffs: Default::default(),
-                deployed_contracts: Default::default(),
-                declared_classes: Default::default(),
-                old_declared_contracts: Default::default(),
-                nonces: Default::default(),
-                replaced_classes: Default::default(),
-                classes: Default::default(),
-            });
-            (
-                maybe_pending_data,
-                executable_transactions,
-                transaction_hashes,
-                block_number,
-                state_number,
-            )
-        } else {
-            // Transaction is not inside the pending block. Search for it in the storage.
-            let TransactionIndex(block_number, tx_offset) = storage_txn
-                .get_transaction_idx_by_hash(&transaction_hash)
-                .map_err(internal_server_error)?
-                .ok_or(TRANSACTION_HASH_NOT_FOUND)?;
-
-            let block_transactions = storage_txn
-                .get_block_transactions(block_number)
-                .map_err(internal_server_error)?
-                .ok_or_else(|| {
-                    internal_server_error(StorageError::DBInconsistency {
-                        msg: format!("Missing block {block_number} transactions"),
-                    })
-                })?;
-
-            let transaction_hashes = storage_txn
-                .get_block_transaction_hashes(block_number)
-                .map_err(internal_server_error)?
-                .ok_or_else(|| {
-                    internal_server_error(StorageError::DBInconsistency {
-                        msg: format!("Missing block {block_number} transactions"),
-                    })
-                })?;
-
-            let state_number = StateNumber::right_before_block(block_number);
-            let executable_transactions = block_transactions
-                .into_iter()
-                .take(tx_offset.0 + 1)
-                .map(|tx| stored_txn_to_executable_txn(tx, &storage_txn, state_number))
-                .collect::<Result<_, _>>()?;
-
-            (None, executable_transactions, transaction_hashes, block_number, state_number)
-        };
-
-        let block_not_reverted_validator =
-            BlockNotRevertedValidator::new(block_number, &storage_txn)?;
-
-        drop(storage_txn);
+            block_not_reverted_validator,
+        } = self.transaction_replay(transaction_hash).await?;
 
-        let execution_config = self.execution_config;
+        let execution_config = self.execution_config.clone();
 
         let chain_id = self.chain_id.clone();
         let reader = self.storage_reader.clone();
@@ -1267,6 +1681,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
                 &chain_id,
                 reader,
                 maybe_pending_data,
+                StateOverrides::default(),
                 state_number,
                 block_number,
                 &execution_config,
@@ -1290,17 +1705,14 @@ impl JsonRpcServer for JsonRpcServerImpl {
         } else {
             BlockId::HashOrNumber(BlockHashOrNumber::Number(block_number))
         };
-        let state_diff = self
-            .convert_thin_state_diff(simulation_result.induced_state_diff, block_id, block_number)
-            .await?;
-        Ok((simulation_result.transaction_trace, state_diff).into())
+        self.to_simulation_output(simulation_result, block_id, block_number).await
     }
 
-    #[instrument(skip(self), level = "debug", err)]
-    async fn trace_block_transactions(
+    // Re-executes the transactions of the given block, along with their hashes.
+    pub(crate) async fn simulate_block_transactions(
         &self,
         block_id: BlockId,
-    ) -> RpcResult<Vec<TransactionTraceWithHash>> {
+    ) -> RpcResult<Vec<(TransactionHash, SimulationOutput)>> {
         let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
 
         let maybe_client_pending_data = if let BlockId::Tag(Tag::Pending) = block_id {
@@ -1380,7 +1792,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
 
         drop(storage_txn);
 
-        let execution_config = self.execution_config;
+        let execution_config = self.execution_config.clone();
 
         let chain_id = self.chain_id.clone();
         let reader = self.storage_reader.clone();
@@ -1395,6 +1807,7 @@ impl JsonRpcServer for JsonRpcServerImpl {
                 &chain_id,
                 reader,
                 maybe_pending_data,
+                StateOverrides::default(),
                 state_number,
                 block_number,
                 &execution_config,
@@ -1414,195 +1827,341 @@ impl JsonRpcServer for JsonRpcServerImpl {
         for (simulation_output, transaction_hash) in
             simulation_results.into_iter().zip(transaction_hashes)
         {
-            let state_diff = self
-                .convert_thin_state_diff(
-                    simulation_output.induced_state_diff,
-                    block_id,
-                    block_number,
-                )
-                .await?;
-            res.push(TransactionTraceWithHash {
+            res.push((
                 transaction_hash,
-                trace_root: (simulation_output.transaction_trace, state_diff).into(),
-            });
+                self.to_simulation_output(simulation_output, block_id, block_number).await?,
+            ));
         }
         Ok(res)
     }
 
-    #[instrument(skip(self, message), level = "debug", err)]
-    async fn estimate_message_fee(
+    async fn to_simulation_output(
         &self,
-        message: MessageFromL1,
+        simulation_output: TransactionSimulationOutput,
         block_id: BlockId,
-    ) -> RpcResult<FeeEstimation> {
-        trace!("Estimating fee of message: {:#?}", message);
+        block_number: BlockNumber,
+    ) -> RpcResult<SimulationOutput> {
+        let TransactionSimulationOutput {
+            transaction_trace,
+            induced_state_diff,
+            fee_estimation,
+            gas_consumed,
+        } = simulation_output;
+        let state_diff =
+            self.convert_thin_state_diff(induced_state_diff, block_id, block_number).await?;
+        Ok(SimulationOutput { transaction_trace, state_diff, fee_estimation, gas_consumed })
+    }
+
+    // Returns what is needed to re-execute the given transaction: the transactions of its block up
+    // to and including it, and the state they run on.
+    async fn transaction_replay(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionReplay> {
         let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
-        let maybe_pending_data = if let BlockId::Tag(Tag::Pending) = block_id {
-            Some(client_pending_data_to_execution_pending_data(
-                read_pending_data(&self.pending_data, &storage_txn).await?,
-                self.pending_classes.read().await.clone(),
-            ))
+
+        let pending_block = read_pending_data(&self.pending_data, &storage_txn).await?.block;
+        // Search for the transaction inside the pending block.
+        let (
+            maybe_pending_data,
+            executable_transactions,
+            transaction_hashes,
+            block_number,
+            state_number,
+        ) = if let Some((pending_transaction_offset, _)) = pending_block
+            .transaction_receipts()
+            .iter()
+            .enumerate()
+            .find(|(_, receipt)| receipt.transaction_hash == transaction_hash)
+        {
+            // If there are no blocks in the network and there is a pending block, as an edge
+            // case we treat this as if the pending block is empty.
+            let block_number =
+                get_latest_block_number(&storage_txn)?.ok_or(INVALID_TRANSACTION_HASH)?;
+            let state_number = StateNumber::unchecked_right_after_block(block_number);
+            let executable_transactions = pending_block
+                .transactions()
+                .iter()
+                .take(pending_transaction_offset + 1)
+                .map(|client_transaction| {
+                    let starknet_api_transaction: StarknetApiTransaction =
+                        client_transaction.clone().try_into().map_err(internal_server_error)?;
+                    stored_txn_to_executable_txn(
+                        starknet_api_transaction,
+                        &storage_txn,
+                        state_number,
+                    )
+                })
+                .collect::<Result<_, _>>()?;
+            let transaction_hashes = pending_block
+                .transaction_receipts()
+                .iter()
+                .map(|receipt| receipt.transaction_hash)
+                .collect();
+            let maybe_pending_data = Some(ExecutionPendingData {
+                timestamp: pending_block.timestamp(),
+                l1_gas_price: pending_block.l1_gas_price(),
+                l1_data_gas_price: pending_block.l1_data_gas_price(),
+                l2_gas_price: pending_block.l2_gas_price(),
+                l1_da_mode: pending_block.l1_da_mode(),
+                sequencer: pending_block.sequencer_address(),
+                // The pending state diff should be empty since we look at the state in the
+                // start of the pending block.
+                // Not using ..Default::default() to avoid missing fields in the future.
+                storage_diffs: Default::default(),
+                deployed_contracts: Default::default(),
+                declared_classes: Default::default(),
+                old_declared_contracts: Default::default(),
+                nonces: Default::default(),
+                replaced_classes: Default::default(),
+                classes: Default::default(),
+            });
+            (
+                maybe_pending_data,
+                executable_transactions,
+                transaction_hashes,
+                block_number,
+                state_number,
+            )
         } else {
-            None
+            // Transaction is not inside the pending block. Search for it in the storage.
+            let TransactionIndex(block_number, tx_offset) = storage_txn
+                .get_transaction_idx_by_hash(&transaction_hash)
+                .map_err(internal_server_error)?
+                .ok_or(TRANSACTION_HASH_NOT_FOUND)?;
+
+            let block_transactions = storage_txn
+                .get_block_transactions(block_number)
+                .map_err(internal_server_error)?
+                .ok_or_else(|| {
+                    internal_server_error(StorageError::DBInconsistency {
+                        msg: format!("Missing block {block_number} transactions"),
+                    })
+                })?;
+
+            let transaction_hashes = storage_txn
+                .get_block_transaction_hashes(block_number)
+                .map_err(internal_server_error)?
+                .ok_or_else(|| {
+                    internal_server_error(StorageError::DBInconsistency {
+                        msg: format!("Missing block {block_number} transactions"),
+                    })
+                })?;
+
+            let state_number = StateNumber::right_before_block(block_number);
+            let executable_transactions = block_transactions
+                .into_iter()
+                .take(tx_offset.0 + 1)
+                .map(|tx| stored_txn_to_executable_txn(tx, &storage_txn, state_number))
+                .collect::<Result<_, _>>()?;
+
+            (None, executable_transactions, transaction_hashes, block_number, state_number)
         };
-        // Convert the message to an L1 handler transaction, and estimate the fee of the
-        // transaction.
-        // The fee input is used to bound the amount of fee used. Because we want to estimate the
-        // fee, we pass u128::MAX so the execution won't fail.
-        let executable_txns =
-            vec![ExecutableTransactionInput::L1Handler(message.into(), Fee(u128::MAX), false)];
 
-        let block_number = get_accepted_block_number(&storage_txn, block_id)?;
         let block_not_reverted_validator =
             BlockNotRevertedValidator::new(block_number, &storage_txn)?;
-        drop(storage_txn);
-        let state_number = StateNumber::unchecked_right_after_block(block_number);
-        let execution_config = self.execution_config;
+
+        Ok(TransactionReplay {
+            maybe_pending_data,
+            executable_transactions,
+            transaction_hashes,
+            block_number,
+            state_number,
+            block_not_reverted_validator,
+        })
+    }
+
+    // Returns the call trees of the given transaction.
+    pub(crate) async fn trace_transaction_calls(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionCallTrace> {
+        let TransactionReplay {
+            maybe_pending_data,
+            executable_transactions,
+            transaction_hashes,
+            block_number,
+            state_number,
+            block_not_reverted_validator,
+        } = self.transaction_replay(transaction_hash).await?;
+
+        let execution_config = self.execution_config.clone();
 
         let chain_id = self.chain_id.clone();
         let reader = self.storage_reader.clone();
         let class_manager_client =
             create_class_manager_client(self.class_manager_client.clone()).await;
 
-        let estimate_fee_result = tokio::task::spawn_blocking(move || {
-            exec_estimate_fee(
-                executable_txns,
+        let mut traces = tokio::task::spawn_blocking(move || {
+            exec_trace_transactions(
+                executable_transactions,
+                Some(transaction_hashes),
                 &chain_id,
                 reader,
                 maybe_pending_data,
+                StateOverrides::default(),
                 state_number,
                 block_number,
                 &execution_config,
-                false,
+                true,
+                true,
                 DONT_IGNORE_L1_DA_MODE,
                 class_manager_client,
             )
         })
         .await
-        .map_err(internal_server_error)?;
+        .map_err(internal_server_error)?
+        .map_err(execution_error_to_error_object_owned)?;
 
         block_not_reverted_validator.validate(&self.storage_reader)?;
 
-        match estimate_fee_result {
-            Ok(Ok(mut fee_as_vec)) => {
-                if fee_as_vec.len() != 1 {
-                    return Err(internal_server_error(format!(
-                        "Expected a single fee, got {}",
-                        fee_as_vec.len()
-                    )));
-                }
-                let Some(fee_estimation) = fee_as_vec.pop() else {
-                    return Err(internal_server_error(
-                        "Expected a single fee, got an empty vector",
-                    ));
-                };
-                Ok(fee_estimation)
-            }
-            // Error in the execution of the contract.
-            Ok(Err(reverted_tx)) => Err(JsonRpcError::<ContractError>::from(ContractError {
-                revert_error: reverted_tx.revert_reason,
-            })
-            .into()),
-            // Internal error during the execution.
-            Err(err) => Err(internal_server_error(err)),
-        }
+        Ok(traces.pop().expect("Should have transaction execution result"))
     }
 
-    #[instrument(skip(self), level = "debug", err)]
-    fn get_compiled_class(
+    // Gets the receipt of the given transaction, whose execution resources are in the given
+    // format.
+    pub(crate) async fn get_general_transaction_receipt<Resources>(
         &self,
-        block_id: BlockId,
-        class_hash: ClassHash,
-    ) -> RpcResult<(CompiledContractClass, SierraVersion)> {
-        let storage_txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
-        let state_reader = storage_txn.get_state_reader().map_err(internal_server_error)?;
-        let block_number = get_accepted_block_number(&storage_txn, block_id)?;
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<GeneralTransactionReceipt<Resources>>
+    where
+        Resources: From<StarknetApiExecutionResources>,
+    {
+        verify_storage_scope(&self.storage_reader)?;
 
-        // Check if this class exists in the Cairo1 classes table.
-        if let Some(class_definition_block_number) = state_reader
-            .get_class_definition_block_number(&class_hash)
-            .map_err(internal_server_error)?
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+
+        if let Some(transaction_index) =
+            txn.get_transaction_idx_by_hash(&transaction_hash).map_err(internal_server_error)?
         {
-            if class_definition_block_number > block_number {
-                return Err(ErrorObjectOwned::from(CLASS_HASH_NOT_FOUND));
-            }
-            let (option_casm, option_sierra) = storage_txn
-                .get_casm_and_sierra(&class_hash)
-                .map_err(internal_server_error_with_msg)?;
+            let tx = txn
+                .get_transaction(transaction_index)
+                .map_err(internal_server_error)?
+                .unwrap_or_else(|| panic!("Should have tx {}", transaction_hash));
 
-            // Check if both options are `Some`.
-            let (casm, sierra) = option_casm
-                .zip(option_sierra)
-                .ok_or_else(|| ErrorObjectOwned::from(CLASS_HASH_NOT_FOUND))?;
-            let sierra_version = SierraVersion::extract_from_program(&sierra.sierra_program)
-                .map_err(internal_server_error_with_msg)?;
-            return Ok((CompiledContractClass::V1(casm), sierra_version));
-        }
+            // TODO(Shahak): Add version function to transaction in SN_API.
+            let tx_version = match &tx {
+                StarknetApiTransaction::Declare(tx) => tx.version(),
+                StarknetApiTransaction::Deploy(tx) => tx.version,
+                StarknetApiTransaction::DeployAccount(tx) => tx.version(),
+                StarknetApiTransaction::Invoke(tx) => tx.version(),
+                StarknetApiTransaction::L1Handler(tx) => tx.version,
+            };
 
-        // Check if this class exists in the Cairo0 classes table.
-        let state_number = StateNumber::right_after_block(block_number)
-            .ok_or_else(|| internal_server_error("Could not compute state number"))?;
-        let deprecated_compiled_contract_class = state_reader
-            .get_deprecated_class_definition_at(state_number, &class_hash)
-            .map_err(internal_server_error)?
-            .ok_or_else(|| ErrorObjectOwned::from(CLASS_HASH_NOT_FOUND))?;
-        Ok((
-            CompiledContractClass::V0(deprecated_compiled_contract_class),
-            SierraVersion::DEPRECATED,
-        ))
+            let msg_hash = match tx {
+                StarknetApiTransaction::L1Handler(l1_handler_tx) => {
+                    Some(l1_handler_tx.calc_msg_hash())
+                }
+                _ => None,
+            };
+
+            get_non_pending_receipt(&txn, transaction_index, transaction_hash, tx_version, msg_hash)
+        } else {
+            // The transaction is not in any non-pending block. Search for it in the pending block
+            // and if it's not found, return error.
+
+            // TODO(shahak): Consider cloning the transactions and the receipts in order to free
+            // the lock sooner (Check which is better).
+            let pending_data = read_pending_data(&self.pending_data, &txn).await?;
+
+            let client_transaction_receipt = pending_data
+                .block
+                .transaction_receipts()
+                .iter()
+                .find(|receipt| receipt.transaction_hash == transaction_hash)
+                .ok_or_else(|| ErrorObjectOwned::from(TRANSACTION_HASH_NOT_FOUND))?
+                .clone();
+            let client_transaction = &pending_data
+                .block
+                .transactions()
+                .iter()
+                .find(|tx| tx.transaction_hash() == transaction_hash)
+                .ok_or_else(|| ErrorObjectOwned::from(TRANSACTION_HASH_NOT_FOUND))?
+                .clone();
+            client_receipt_to_rpc_pending_receipt(client_transaction, client_transaction_receipt)
+        }
     }
-}
 
-async fn read_pending_data<Mode: TransactionKind>(
-    pending_data: &Arc<RwLock<PendingData>>,
-    txn: &StorageTxn<'_, Mode>,
-) -> RpcResult<PendingData> {
-    let latest_header = match get_latest_block_number(txn)? {
-        Some(latest_block_number) => get_block_header_by_number(txn, latest_block_number)?,
-        None => starknet_api::block::BlockHeader {
-            block_header_without_hash: BlockHeaderWithoutHash {
-                parent_hash: BlockHash(StarkHash::from_hex_unchecked(GENESIS_HASH)),
-                ..Default::default()
+    // Gets the block with the given ID along with the receipts of its transactions, whose execution
+    // resources are in the given format.
+    pub(crate) async fn get_block_with_receipts<Resources>(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Block<StreamedSeq<TransactionWithReceipt<Resources>>>>
+    where
+        Resources: From<StarknetApiExecutionResources> + Send + Sync + 'static,
+    {
+        self.get_block(
+            block_id,
+            |mut pending_data| {
+                let (client_transactions, client_receipts) =
+                    pending_data.block.transactions_and_receipts_mutable();
+                let client_transactions_and_receipts =
+                    client_transactions.drain(..).zip(client_receipts.drain(..));
+                Ok(StreamedSeq::from_items(
+                    client_transactions_and_receipts
+                        .map(|(client_transaction, client_transaction_receipt)| {
+                            let receipt = client_receipt_to_rpc_pending_receipt(
+                                &client_transaction,
+                                client_transaction_receipt,
+                            )?
+                            .into();
+
+                            let starknet_api_transaction: StarknetApiTransaction =
+                                client_transaction.try_into().map_err(internal_server_error)?;
+                            Ok(TransactionWithReceipt {
+                                transaction: starknet_api_transaction
+                                    .try_into()
+                                    .map_err(internal_server_error)?,
+                                receipt,
+                            })
+                        })
+                        .collect::<Result<Vec<_>, ErrorObjectOwned>>()?,
+                ))
             },
-            ..Default::default()
-        },
-    };
-    let pending_data = &pending_data.read().await;
-    if pending_data.block.parent_block_hash() == latest_header.block_hash {
-        Ok((*pending_data).clone())
-    } else {
-        Ok(PendingData {
-            block: PendingBlockOrDeprecated::Deprecated(DeprecatedPendingBlock {
-                parent_block_hash: latest_header.block_hash,
-                eth_l1_gas_price: latest_header.block_header_without_hash.l1_gas_price.price_in_wei,
-                strk_l1_gas_price: latest_header
-                    .block_header_without_hash
-                    .l1_gas_price
-                    .price_in_fri,
-                timestamp: latest_header.block_header_without_hash.timestamp,
-                sequencer_address: latest_header.block_header_without_hash.sequencer,
-                starknet_version: latest_header
-                    .block_header_without_hash
-                    .starknet_version
-                    .to_string(),
-                ..Default::default()
-            }),
-            state_update: ClientPendingStateUpdate {
-                old_root: latest_header.block_header_without_hash.state_root,
-                state_diff: Default::default(),
+            |txn, block_number| {
+                Ok(StreamedSeq::from_storage(
+                    self.storage_reader.clone(),
+                    get_block_txs_count_by_number(txn, block_number)?,
+                    move |txn, transaction_offset| {
+                        let transaction_index = TransactionIndex(
+                            block_number,
+                            TransactionOffsetInBlock(transaction_offset),
+                        );
+                        let (transaction, transaction_hash): (Transaction, _) =
+                            get_tx_with_hash_by_index(txn, transaction_index)?;
+                        let msg_hash = match &transaction {
+                            Transaction::L1Handler(l1_handler_tx) => {
+                                Some(l1_handler_tx.calc_msg_hash())
+                            }
+                            _ => None,
+                        };
+                        let transaction_version = transaction.version();
+                        Ok(TransactionWithReceipt {
+                            transaction,
+                            receipt: get_non_pending_receipt(
+                                txn,
+                                transaction_index,
+                                transaction_hash,
+                                transaction_version,
+                                msg_hash,
+                            )?
+                            .into(),
+                        })
+                    },
+                ))
             },
-        })
+        )
+        .await
     }
-}
 
-impl JsonRpcServerImpl {
     // Get the block with the given ID and the given custom logic for getting the transactions.
-    async fn get_block(
+    async fn get_block<Txs>(
         &self,
         block_id: BlockId,
-        get_pending_transactions: impl FnOnce(PendingData) -> RpcResult<Transactions>,
-        get_transactions: impl FnOnce(&StorageTxn<'_, RO>, BlockNumber) -> RpcResult<Transactions>,
-    ) -> RpcResult<Block> {
+        get_pending_transactions: impl FnOnce(PendingData) -> RpcResult<Txs>,
+        get_transactions: impl FnOnce(&StorageTxn<'_, RO>, BlockNumber) -> RpcResult<Txs>,
+    ) -> RpcResult<Block<Txs>> {
         verify_storage_scope(&self.storage_reader)?;
         let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
         if let BlockId::Tag(Tag::Pending) = block_id {
@@ -1709,15 +2268,54 @@ impl JsonRpcServerImpl {
         });
         Ok(ThinStateDiff::from(thin_state_diff, replaced_classes))
     }
+
+    // Returns the block from which a subscription to blocks starts, which is the latest block by
+    // default.
+    fn get_subscription_first_block(&self, block_id: Option<BlockId>) -> RpcResult<BlockNumber> {
+        verify_storage_scope(&self.storage_reader)?;
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        let Some(latest_block_number) = get_latest_block_number(&txn)? else {
+            return Ok(BlockNumber(0));
+        };
+        let first_block = match block_id {
+            Some(block_id) => get_accepted_block_number(&txn, block_id)?,
+            None => latest_block_number,
+        };
+        if latest_block_number.0 - first_block.0 > MAX_BLOCKS_BACK {
+            return Err(ErrorObjectOwned::from(TOO_MANY_BLOCKS_BACK));
+        }
+        Ok(first_block)
+    }
+
+    // Whether the transaction is in a stored block or in the pending block.
+    async fn is_transaction_known(&self, transaction_hash: TransactionHash) -> RpcResult<bool> {
+        let txn = self.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        if txn
+            .get_transaction_idx_by_hash(&transaction_hash)
+            .map_err(internal_server_error)?
+            .is_some()
+        {
+            return Ok(true);
+        }
+        Ok(read_pending_data(&self.pending_data, &txn)
+            .await?
+            .block
+            .transactions()
+            .iter()
+            .any(|transaction| transaction.transaction_hash() == transaction_hash))
+    }
 }
 
-fn get_non_pending_receipt<Mode: TransactionKind>(
+fn get_non_pending_receipt<Mode: TransactionKind, Resources>(
     txn: &StorageTxn<'_, Mode>,
     transaction_index: TransactionIndex,
     transaction_hash: TransactionHash,
     tx_version: TransactionVersion,
     msg_hash: Option<L1L2MsgHash>,
-) -> RpcResult<GeneralTransactionReceipt> {
+) -> RpcResult<GeneralTransactionReceipt<Resources>>
+where
+    Resources: From<StarknetApiExecutionResources>,
+{
     let block_number = transaction_index.0;
     let status = get_block_status(txn, block_number)?;
 
@@ -1747,10 +2345,13 @@ fn get_non_pending_receipt<Mode: TransactionKind>(
     }))
 }
 
-fn client_receipt_to_rpc_pending_receipt(
+fn client_receipt_to_rpc_pending_receipt<Resources>(
     client_transaction: &ClientTransaction,
     client_transaction_receipt: ClientTransactionReceipt,
-) -> RpcResult<GeneralTransactionReceipt> {
+) -> RpcResult<GeneralTransactionReceipt<Resources>>
+where
+    Resources: From<StarknetApiExecutionResources>,
+{
     let transaction_hash = client_transaction.transaction_hash();
     let starknet_api_output =
         client_transaction_receipt.into_starknet_api_transaction_output(client_transaction);
@@ -1758,7 +2359,7 @@ fn client_receipt_to_rpc_pending_receipt(
         ClientTransaction::L1Handler(tx) => Some(tx.calc_msg_hash()),
         _ => None,
     };
-    let output = PendingTransactionOutput::try_from(TransactionOutput::from((
+    let output = PendingTransactionOutput::try_from(TransactionOutput::<Resources>::from((
         starknet_api_output,
         client_transaction.transaction_version(),
         msg_hash,
@@ -1771,8 +2372,102 @@ fn client_receipt_to_rpc_pending_receipt(
     }))
 }
 
-fn do_event_keys_match_filter(event_content: &EventContent, filter: &EventFilter) -> bool {
-    filter.keys.iter().enumerate().all(|(i, keys)| {
+fn first_event_index_in_block(block_number: BlockNumber) -> EventIndex {
+    EventIndex(
+        TransactionIndex(block_number, TransactionOffsetInBlock(0)),
+        EventIndexInTransactionOutput(0),
+    )
+}
+
+// Returns the events of a getEvents chunk, where the stored events are read from the storage by
+// their indices while they're serialized, and are followed by the pending events.
+fn stored_and_pending_events(
+    storage_reader: StorageReader,
+    stored_event_indices: Vec<EventIndex>,
+    pending_events: Vec<Event>,
+) -> StreamedSeq<Event> {
+    // Consecutive events are mostly of the same transaction, so its output is read once for them.
+    let last_transaction_output: Mutex<Option<(TransactionIndex, StarknetApiTransactionOutput)>> =
+        Mutex::new(None);
+    let mut events =
+        StreamedSeq::from_storage(storage_reader, stored_event_indices.len(), move |txn, i| {
+            let EventIndex(transaction_index, EventIndexInTransactionOutput(event_offset)) =
+                stored_event_indices[i];
+            let mut last_transaction_output =
+                last_transaction_output.lock().map_err(internal_server_error)?;
+            if last_transaction_output.as_ref().map(|(index, _)| *index) != Some(transaction_index)
+            {
+                let transaction_output = txn
+                    .get_transaction_output(transaction_index)
+                    .map_err(internal_server_error)?
+                    .ok_or_else(|| ErrorObjectOwned::from(BLOCK_NOT_FOUND))?;
+                *last_transaction_output = Some((transaction_index, transaction_output));
+            }
+            let (_, transaction_output) =
+                last_transaction_output.as_ref().expect("The transaction output was just set.");
+            let event = transaction_output
+                .events()
+                .get(event_offset)
+                .cloned()
+                .ok_or_else(|| internal_server_error("Unknown internal error."))?;
+            let block_number = transaction_index.0;
+            let transaction_hash = txn
+                .get_transaction_hash_by_idx(&transaction_index)
+                .map_err(internal_server_error)?
+                .ok_or_else(|| internal_server_error("Unknown internal error."))?;
+            Ok(Event {
+                block_hash: Some(get_block_header_by_number(txn, block_number)?.block_hash),
+                block_number: Some(block_number),
+                transaction_hash,
+                event,
+            })
+        });
+    events.extend(pending_events);
+    events
+}
+
+// Returns the events of the stored block that match the filter.
+fn get_block_events(
+    txn: &StorageTxn<'_, RO>,
+    block_number: BlockNumber,
+    address: Option<ContractAddress>,
+    keys: &[HashSet<EventKey>],
+) -> RpcResult<Vec<Event>> {
+    let block_hash = get_block_header_by_number(txn, block_number)?.block_hash;
+    let mut events = vec![];
+    for ((from_address, event_index), content) in txn
+        .iter_events(address, first_event_index_in_block(block_number), block_number)
+        .map_err(internal_server_error)?
+    {
+        // The iterator outputs only events that have the address, unless there are no more such
+        // events, and it may output events of the following blocks.
+        if event_index.0.0 != block_number
+            || address.is_some_and(|address| address != from_address)
+        {
+            break;
+        }
+        if !do_event_keys_match_filter(&content, keys) {
+            continue;
+        }
+        let transaction_hash = txn
+            .get_transaction_hash_by_idx(&event_index.0)
+            .map_err(internal_server_error)?
+            .ok_or_else(|| internal_server_error("Unknown internal error."))?;
+        events.push(Event {
+            block_hash: Some(block_hash),
+            block_number: Some(block_number),
+            transaction_hash,
+            event: starknet_api::transaction::Event { from_address, content },
+        });
+    }
+    Ok(events)
+}
+
+fn do_event_keys_match_filter(
+    event_content: &EventContent,
+    filter_keys: &[HashSet<EventKey>],
+) -> bool {
+    filter_keys.iter().enumerate().all(|(i, keys)| {
         event_content.keys.len() > i && (keys.is_empty() || keys.contains(&event_content.keys[i]))
     })
 }
@@ -1784,12 +2479,14 @@ impl JsonRpcServerTrait for JsonRpcServerImpl {
         storage_reader: StorageReader,
         max_events_chunk_size: usize,
         max_events_keys: usize,
+        max_events_block_range: u64,
         starting_block: BlockHashAndNumber,
         shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
         pending_data: Arc<RwLock<PendingData>>,
         pending_classes: Arc<RwLock<PendingClasses>>,
         writer_client: Arc<dyn StarknetWriter>,
         class_manager_client: Option<SharedClassManagerClient>,
+        subscriptions: Subscriptions,
     ) -> Self {
         Self {
             chain_id,
@@ -1797,16 +2494,26 @@ impl JsonRpcServerTrait for JsonRpcServerImpl {
             storage_reader,
             max_events_chunk_size,
             max_events_keys,
+            max_events_block_range,
             starting_block,
             shared_highest_block,
             pending_data,
             pending_classes,
             writer_client,
             class_manager_client,
+            subscriptions,
         }
     }
 
     fn into_rpc_module(self) -> RpcModule<Self> {
-        self.into_rpc()
+        let unsubscribe = unsubscribe_callback(self.subscriptions.clone());
+        let mut rpc_module = self.into_rpc();
+        rpc_module
+            .verify_and_insert("starknet_V0_8_unsubscribe", unsubscribe.clone())
+            .expect("The unsubscribe method name should be unique");
+        rpc_module
+            .verify_and_insert("starknet_unsubscribe", unsubscribe)
+            .expect("The unsubscribe method name should be unique");
+        rpc_module
     }
 }
@@ -11,7 +11,7 @@ use apollo_storage::state::StateStorageReader;
 use apollo_storage::StorageTxn;
 use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
 use flate2::bufread::GzDecoder;
-use jsonrpsee::core::RpcResult;
+use jsonrpsee::core::{RpcResult, SubscriptionResult};
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::ErrorObjectOwned;
 use papyrus_common::deprecated_class_abi::calculate_deprecated_class_abi_length;
@@ -30,7 +30,7 @@ use starknet_api::transaction::{EventKey, TransactionHash, TransactionOffsetInBl
 use starknet_types_core::felt::Felt;
 use tracing::debug;
 
-use super::block::Block;
+use super::block::{Block, BlockHeader};
 use super::broadcasted_transaction::{
     BroadcastedDeclareTransaction,
     BroadcastedDeclareV1Transaction,
@@ -46,6 +46,7 @@ use super::error::{
 };
 use super::execution::TransactionTrace;
 use super::state::{ContractClass, StateUpdate};
+use super::subscription::{PendingTransaction, TransactionStatusNotification};
 use super::transaction::{
     DeployAccountTransaction,
     DeployAccountTransactionV1,
@@ -59,19 +60,22 @@ use super::transaction::{
     MessageFromL1,
     TransactionStatus,
     TransactionWithHash,
+    TransactionWithReceipt,
     TypedDeployAccountTransaction,
     TypedInvokeTransaction,
 };
 use super::write_api_result::{AddDeclareOkResult, AddDeployAccountOkResult, AddInvokeOkResult};
 use crate::api::{BlockId, CallRequest};
+use crate::streaming::StreamedSeq;
 use crate::syncing_state::SyncingState;
 use crate::{internal_server_error, ContinuationTokenAsStruct};
 
 pub mod api_impl;
 #[cfg(test)]
-mod test;
+pub(crate) mod test;
 
-#[versioned_rpc("V0_8")]
+// The methods are also served under their unversioned names, which requests over WebSocket use.
+#[versioned_rpc("V0_8", unversioned_aliases)]
 #[async_trait]
 pub trait JsonRpc {
     /// Returns the version of the Starknet JSON-RPC specification being used
@@ -92,14 +96,17 @@ pub trait JsonRpc {
 
     /// Gets block information with full transactions given a block identifier.
     #[method(name = "getBlockWithTxs")]
-    async fn get_block_w_full_transactions(&self, block_id: BlockId) -> RpcResult<Block>;
+    async fn get_block_w_full_transactions(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Block<StreamedSeq<TransactionWithHash>>>;
 
     /// Gets block information with full transactions given a block identifier.
     #[method(name = "getBlockWithReceipts")]
     async fn get_block_w_full_transactions_and_receipts(
         &self,
         block_id: BlockId,
-    ) -> RpcResult<Block>;
+    ) -> RpcResult<Block<StreamedSeq<TransactionWithReceipt>>>;
 
     /// Gets the value of the storage at the given address, key, and block.
     #[method(name = "getStorageAt")]
@@ -186,7 +193,7 @@ pub trait JsonRpc {
 
     /// Returns all events matching the given filter.
     #[method(name = "getEvents")]
-    async fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk>;
+    async fn get_events(&self, filter: EventFilter) -> RpcResult<EventsChunk<StreamedSeq<Event>>>;
 
     /// Returns the synching status of the node, or false if the node is not synching.
     #[method(name = "syncing")]
@@ -265,6 +272,52 @@ pub trait JsonRpc {
         block_id: BlockId,
         class_hash: ClassHash,
     ) -> RpcResult<(CompiledContractClass, SierraVersion)>;
+
+    /// Subscribes to the headers of new blocks, starting from the given block.
+    #[subscription(
+        name = "subscribeNewHeads" => "subscriptionNewHeads",
+        unsubscribe = "unsubscribeNewHeads",
+        item = BlockHeader
+    )]
+    async fn subscribe_new_heads(&self, block_id: Option<BlockId>) -> SubscriptionResult;
+
+    /// Subscribes to the events of new blocks that match the filter, starting from the given
+    /// block.
+    #[subscription(
+        name = "subscribeEvents" => "subscriptionEvents",
+        unsubscribe = "unsubscribeEvents",
+        item = Event
+    )]
+    async fn subscribe_events(
+        &self,
+        from_address: Option<ContractAddress>,
+        keys: Option<Vec<HashSet<EventKey>>>,
+        block_id: Option<BlockId>,
+    ) -> SubscriptionResult;
+
+    /// Subscribes to the status of a transaction, until it's accepted on L1.
+    #[subscription(
+        name = "subscribeTransactionStatus" => "subscriptionTransactionStatus",
+        unsubscribe = "unsubscribeTransactionStatus",
+        item = TransactionStatusNotification
+    )]
+    async fn subscribe_transaction_status(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> SubscriptionResult;
+
+    /// Subscribes to the transactions added to the mempool, optionally only those of the given
+    /// senders.
+    #[subscription(
+        name = "subscribePendingTransactions" => "subscriptionPendingTransactions",
+        unsubscribe = "unsubscribePendingTransactions",
+        item = PendingTransaction
+    )]
+    async fn subscribe_pending_transactions(
+        &self,
+        transaction_details: Option<bool>,
+        sender_address: Option<Vec<ContractAddress>>,
+    ) -> SubscriptionResult;
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -275,9 +328,11 @@ pub enum GatewayContractClass {
     Sierra(ContractClass),
 }
 
+// getEvents returns chunks whose events are a StreamedSeq, read from the storage while they're
+// serialized.
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
-pub struct EventsChunk {
-    pub events: Vec<Event>,
+pub struct EventsChunk<Events = Vec<Event>> {
+    pub events: Events,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub continuation_token: Option<ContinuationToken>,
 }
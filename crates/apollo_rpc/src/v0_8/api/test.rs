@@ -3,7 +3,15 @@ use std::fmt::Debug;
 use std::iter;
 use std::net::SocketAddr;
 use std::ops::Index;
-
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use apollo_mempool_types::communication::MockMempoolClient;
+use apollo_mempool_types::mempool_types::{
+    MempoolSnapshot,
+    MempoolStateSnapshot,
+    TransactionQueueSnapshot,
+};
 use apollo_starknet_client::reader::objects::pending_data::{
     DeprecatedPendingBlock,
     PendingBlockOrDeprecated,
@@ -45,7 +53,7 @@ use apollo_storage::compiled_class::CasmStorageWriter;
 use apollo_storage::header::HeaderStorageWriter;
 use apollo_storage::state::StateStorageWriter;
 use apollo_storage::test_utils::get_test_storage;
-use apollo_storage::StorageScope;
+use apollo_storage::{StorageScope, StorageWriter};
 use apollo_test_utils::{
     auto_impl_get_test_instance,
     get_number_of_variants,
@@ -61,8 +69,10 @@ use async_trait::async_trait;
 use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
 use indexmap::{indexmap, IndexMap};
 use itertools::Itertools;
+use jsonrpsee::core::server::Subscription;
 use jsonrpsee::core::Error;
-use jsonrpsee::Methods;
+use jsonrpsee::types::{Id, Params};
+use jsonrpsee::{rpc_params, MethodCallback, Methods};
 use jsonschema::JSONSchema;
 use lazy_static::lazy_static;
 use mockall::predicate::eq;
@@ -71,9 +81,11 @@ use pretty_assertions::assert_eq;
 use rand::{random, RngCore};
 use rand_chacha::ChaCha8Rng;
 use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{
     Block as StarknetApiBlock,
+    BlockBody,
     BlockHash,
     BlockHashAndNumber,
     BlockHeader,
@@ -81,6 +93,7 @@ use starknet_api::block::{
     BlockNumber,
     BlockStatus,
     BlockTimestamp,
+    GasPrice,
     GasPricePerToken,
     StarknetVersion,
 };
@@ -102,6 +115,7 @@ use starknet_api::deprecated_contract_class::{
     FunctionStateMutability,
 };
 use starknet_api::state::{SierraContractClass as StarknetApiContractClass, StateDiff};
+use starknet_api::test_utils::invoke::internal_invoke_tx;
 use starknet_api::transaction::{
     Event as StarknetApiEvent,
     EventContent,
@@ -113,11 +127,16 @@ use starknet_api::transaction::{
     TransactionOffsetInBlock,
     TransactionOutput as StarknetApiTransactionOutput,
 };
-use starknet_api::{class_hash, contract_address, felt, storage_key, tx_hash};
+use starknet_api::{class_hash, contract_address, felt, invoke_tx_args, storage_key, tx_hash};
 use starknet_types_core::felt::Felt;
 
 use super::super::api::EventsChunk;
-use super::super::block::{Block, GeneralBlockHeader, PendingBlockHeader};
+use super::super::block::{
+    Block,
+    BlockHeader as RpcBlockHeader,
+    GeneralBlockHeader,
+    PendingBlockHeader,
+};
 use super::super::broadcasted_transaction::BroadcastedDeclareTransaction;
 use super::super::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use super::super::error::{
@@ -129,9 +148,12 @@ use super::super::error::{
     CONTRACT_NOT_FOUND,
     DUPLICATE_TX,
     INVALID_CONTINUATION_TOKEN,
+    INVALID_SUBSCRIPTION_ID,
     INVALID_TRANSACTION_INDEX,
     NO_BLOCKS,
     PAGE_SIZE_TOO_BIG,
+    TOO_MANY_ADDRESSES_IN_FILTER,
+    TOO_MANY_BLOCKS_BACK,
     TOO_MANY_KEYS_IN_FILTER,
     TRANSACTION_HASH_NOT_FOUND,
 };
@@ -148,6 +170,13 @@ use super::super::state::{
     StorageEntry,
     ThinStateDiff,
 };
+use super::super::subscription::{
+    PendingTransaction,
+    ReorgData,
+    TransactionStatusNotification,
+    MAX_BLOCKS_BACK,
+    MAX_SENDER_ADDRESSES_IN_FILTER,
+};
 use super::super::transaction::{
     DeployAccountTransaction,
     Event,
@@ -177,6 +206,7 @@ use super::super::write_api_result::{
 use super::api_impl::JsonRpcServerImpl;
 use super::{ContinuationToken, EventFilter, GatewayContractClass};
 use crate::api::{BlockHashOrNumber, BlockId, Tag};
+use crate::subscriptions::CHAIN_UPDATES_POLLING_INTERVAL;
 use crate::syncing_state::SyncStatus;
 use crate::test_utils::{
     call_and_validate_schema_for_result,
@@ -191,6 +221,7 @@ use crate::test_utils::{
     get_test_rpc_config,
     get_test_rpc_server_and_storage_writer,
     get_test_rpc_server_and_storage_writer_from_params,
+    get_test_rpc_server_and_storage_writer_with_mempool,
     method_name_to_spec_method_name,
     raw_call,
     validate_schema,
@@ -208,6 +239,8 @@ use crate::{
 
 const NODE_VERSION: &str = "NODE VERSION";
 
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(5);
+
 #[tokio::test]
 async fn spec_version() {
     let (module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
@@ -1273,7 +1306,7 @@ async fn get_transaction_status() {
         starknet_api::transaction::TransactionOutput::L1Handler(_) => Some(L1L2MsgHash::default()),
         _ => None,
     };
-    let output = TransactionOutput::from((tx, transaction_version, msg_hash));
+    let output: TransactionOutput = TransactionOutput::from((tx, transaction_version, msg_hash));
     let expected_status = TransactionStatus {
         finality_status: TransactionFinalityStatus::AcceptedOnL2,
         execution_status: output.execution_status().clone(),
@@ -1392,7 +1425,7 @@ async fn get_transaction_receipt() {
         starknet_api::transaction::Transaction::L1Handler(tx) => Some(tx.calc_msg_hash()),
         _ => None,
     };
-    let output = TransactionOutput::from((
+    let output: TransactionOutput = TransactionOutput::from((
         block.body.transaction_outputs.index(0).clone(),
         transaction_version,
         msg_hash,
@@ -2202,7 +2235,7 @@ async fn get_storage_at() {
     assert_matches!(err, Error::Call(err) if err == BLOCK_NOT_FOUND.into());
 }
 
-fn generate_client_transaction_client_receipt_rpc_transaction_and_rpc_receipt(
+pub(crate) fn generate_client_transaction_client_receipt_rpc_transaction_and_rpc_receipt(
     rng: &mut ChaCha8Rng,
 ) -> (ClientTransaction, ClientTransactionReceipt, Transaction, PendingTransactionReceipt) {
     let pending_transaction_hash = tx_hash!(rng.next_u64());
@@ -2971,7 +3004,7 @@ async fn test_get_events(
     }
 
     for (expected_event_indices, expected_continuation_token) in expected_result_by_index {
-        let expected_result = EventsChunk {
+        let expected_result: EventsChunk = EventsChunk {
             events: expected_event_indices
                 .iter()
                 .map(|event_index| event_index_to_event.get(event_index).unwrap())
@@ -3330,6 +3363,39 @@ async fn get_events_to_block() {
     .await;
 }
 
+#[tokio::test]
+async fn get_events_block_range_limit() {
+    let max_events_block_range = get_test_rpc_config().max_events_block_range;
+    let n_blocks = max_events_block_range + 1;
+    let blocks_metadata =
+        (0..n_blocks).map(|_| BlockMetadata(vec![vec![DEFAULT_EVENT_METADATA]])).collect();
+    let pending_block_metadata = None;
+    let is_pending_up_to_date = true;
+    let first_event_index = |block_number| {
+        EventIndex(
+            TransactionIndex(BlockNumber(block_number), TransactionOffsetInBlock(0)),
+            EventIndexInTransactionOutput(0),
+        )
+    };
+    // The first call scans only the allowed number of blocks, and its continuation token points to
+    // the first block that wasn't scanned.
+    let expected_result_by_index = vec![
+        (
+            (0..max_events_block_range).map(first_event_index).collect(),
+            Some(ContinuationTokenAsStruct(first_event_index(max_events_block_range))),
+        ),
+        (vec![first_event_index(max_events_block_range)], None),
+    ];
+    test_get_events(
+        blocks_metadata,
+        pending_block_metadata,
+        is_pending_up_to_date,
+        EventFilter { chunk_size: 10, ..Default::default() },
+        expected_result_by_index,
+    )
+    .await;
+}
+
 // TODO(nevo): add a test that returns the block not found error for getEvents
 #[tokio::test]
 async fn get_events_no_blocks() {
@@ -3564,6 +3630,7 @@ async fn serialize_returns_valid_json() {
         storage_reader,
         NODE_VERSION,
         None,
+        None,
     )
     .await
     .unwrap();
@@ -4177,6 +4244,339 @@ fn spec_api_methods_coverage() {
     );
 }
 
+// Waits for the next notification of the subscription and returns its result.
+async fn next_notification<T: DeserializeOwned>(subscription: &mut Subscription) -> T {
+    tokio::time::timeout(NOTIFICATION_TIMEOUT, subscription.next::<T>())
+        .await
+        .expect("Timed out waiting for a notification")
+        .expect("The subscription was closed")
+        .unwrap()
+        .0
+}
+
+// Appends a block with the given number and hash, whose body and state diff are stored.
+fn append_subscription_test_block(
+    storage_writer: &mut StorageWriter,
+    block_number: BlockNumber,
+    block_hash: BlockHash,
+    body: BlockBody,
+) {
+    let header = BlockHeader {
+        block_hash,
+        block_header_without_hash: BlockHeaderWithoutHash { block_number, ..Default::default() },
+        ..Default::default()
+    };
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block_number, &header)
+        .unwrap()
+        .append_body(block_number, body)
+        .unwrap()
+        .append_state_diff(block_number, starknet_api::state::ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn subscribe_new_heads() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    for i in 0..3 {
+        append_subscription_test_block(
+            &mut storage_writer,
+            BlockNumber(i),
+            BlockHash(felt!(i)),
+            BlockBody::default(),
+        );
+    }
+
+    let mut subscription = module
+        .subscribe_unbounded(
+            "starknet_V0_8_subscribeNewHeads",
+            [BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(1)))],
+        )
+        .await
+        .unwrap();
+    for i in 1..3 {
+        let header: RpcBlockHeader = next_notification(&mut subscription).await;
+        assert_eq!(header.block_number, BlockNumber(i));
+        assert_eq!(header.block_hash, BlockHash(felt!(i)));
+    }
+    // Let the chain updates be polled before the chain changes.
+    tokio::time::sleep(CHAIN_UPDATES_POLLING_INTERVAL * 2).await;
+
+    append_subscription_test_block(
+        &mut storage_writer,
+        BlockNumber(3),
+        BlockHash(felt!(3_u8)),
+        BlockBody::default(),
+    );
+    let header: RpcBlockHeader = next_notification(&mut subscription).await;
+    assert_eq!(header.block_number, BlockNumber(3));
+    tokio::time::sleep(CHAIN_UPDATES_POLLING_INTERVAL * 2).await;
+
+    // Replace the last two blocks with a new block at once.
+    let mut txn = storage_writer.begin_rw_txn().unwrap();
+    for i in [3, 2] {
+        (txn, _, _) = txn.revert_header(BlockNumber(i)).unwrap();
+        (txn, _) = txn.revert_body(BlockNumber(i)).unwrap();
+        (txn, _) = txn.revert_state_diff(BlockNumber(i)).unwrap();
+    }
+    let header = BlockHeader {
+        block_hash: BlockHash(felt!(22_u8)),
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number: BlockNumber(2),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    txn.append_header(BlockNumber(2), &header)
+        .unwrap()
+        .append_body(BlockNumber(2), BlockBody::default())
+        .unwrap()
+        .append_state_diff(BlockNumber(2), starknet_api::state::ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    let reorg: ReorgData = next_notification(&mut subscription).await;
+    assert_eq!(
+        reorg,
+        ReorgData {
+            starting_block_hash: BlockHash(felt!(2_u8)),
+            starting_block_number: BlockNumber(2),
+            ending_block_hash: BlockHash(felt!(3_u8)),
+            ending_block_number: BlockNumber(3),
+        }
+    );
+    let header: RpcBlockHeader = next_notification(&mut subscription).await;
+    assert_eq!(header.block_number, BlockNumber(2));
+    assert_eq!(header.block_hash, BlockHash(felt!(22_u8)));
+}
+
+#[tokio::test]
+async fn subscribe_new_heads_too_many_blocks_back() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    for i in 0..=(MAX_BLOCKS_BACK + 1) {
+        append_subscription_test_block(
+            &mut storage_writer,
+            BlockNumber(i),
+            BlockHash(felt!(i)),
+            BlockBody::default(),
+        );
+    }
+    let err = module
+        .subscribe_unbounded(
+            "starknet_V0_8_subscribeNewHeads",
+            [BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(0)))],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == TOO_MANY_BLOCKS_BACK.into());
+}
+
+#[tokio::test]
+async fn subscribe_events() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let from_addresses = vec![contract_address!("0x22"), contract_address!("0x23")];
+    let body = get_test_body(2, Some(2), Some(from_addresses.clone()), None);
+    let n_events_from_first_address = body
+        .transaction_outputs
+        .iter()
+        .flat_map(|output| output.events())
+        .filter(|event| event.from_address == from_addresses[0])
+        .count();
+    append_subscription_test_block(&mut storage_writer, BlockNumber(0), BlockHash::default(), body);
+
+    let mut subscription = module
+        .subscribe_unbounded("starknet_V0_8_subscribeEvents", [from_addresses[0]])
+        .await
+        .unwrap();
+    for _ in 0..n_events_from_first_address {
+        let event: Event = next_notification(&mut subscription).await;
+        assert_eq!(event.event.from_address, from_addresses[0]);
+        assert_eq!(event.block_number, Some(BlockNumber(0)));
+    }
+
+    let too_many_keys = vec![HashSet::<EventKey>::new(); get_test_rpc_config().max_events_keys + 1];
+    let err = module
+        .subscribe_unbounded(
+            "starknet_V0_8_subscribeEvents",
+            rpc_params![None::<ContractAddress>, too_many_keys],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == TOO_MANY_KEYS_IN_FILTER.into());
+}
+
+#[tokio::test]
+async fn subscribe_transaction_status() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let body = get_test_body(1, None, None, None);
+    let transaction_hash = body.transaction_hashes[0];
+    let mut subscription = module
+        .subscribe_unbounded("starknet_V0_8_subscribeTransactionStatus", [transaction_hash])
+        .await
+        .unwrap();
+
+    // The status is sent once the transaction is known.
+    append_subscription_test_block(&mut storage_writer, BlockNumber(0), BlockHash::default(), body);
+    let notification: TransactionStatusNotification = next_notification(&mut subscription).await;
+    assert_eq!(notification.transaction_hash, transaction_hash);
+    assert_eq!(notification.status.finality_status, TransactionFinalityStatus::AcceptedOnL2);
+
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .update_base_layer_block_marker(&BlockNumber(1))
+        .unwrap()
+        .commit()
+        .unwrap();
+    let notification: TransactionStatusNotification = next_notification(&mut subscription).await;
+    assert_eq!(notification.status.finality_status, TransactionFinalityStatus::AcceptedOnL1);
+}
+
+// Returns a mempool snapshot with the given transactions.
+fn mempool_snapshot(transactions: Vec<TransactionHash>) -> MempoolSnapshot {
+    MempoolSnapshot {
+        transactions,
+        delayed_declares: vec![],
+        transaction_queue: TransactionQueueSnapshot {
+            gas_price_threshold: GasPrice::default(),
+            priority_queue: vec![],
+            pending_queue: vec![],
+        },
+        mempool_state: MempoolStateSnapshot { committed: HashMap::new(), staged: HashMap::new() },
+    }
+}
+
+#[tokio::test]
+async fn subscribe_pending_transactions() {
+    let sender_address = contract_address!("0x1");
+    let transaction = internal_invoke_tx(invoke_tx_args!(sender_address, tx_hash: tx_hash!(1)));
+    let mempool_transactions = Arc::new(Mutex::new(vec![]));
+    let mut mempool_client = MockMempoolClient::new();
+    let snapshot_transactions = mempool_transactions.clone();
+    mempool_client
+        .expect_get_mempool_snapshot()
+        .returning(move || Ok(mempool_snapshot(snapshot_transactions.lock().unwrap().clone())));
+    let mempool_transaction = transaction.clone();
+    mempool_client
+        .expect_get_txs_by_hash()
+        .with(eq(vec![transaction.tx_hash]))
+        .returning(move |_| Ok(vec![mempool_transaction.clone()]));
+    let (module, _) = get_test_rpc_server_and_storage_writer_with_mempool::<JsonRpcServerImpl>(
+        Arc::new(mempool_client),
+    );
+
+    let mut hashes_subscription = module
+        .subscribe_unbounded("starknet_V0_8_subscribePendingTransactions", rpc_params![])
+        .await
+        .unwrap();
+    let mut details_subscription = module
+        .subscribe_unbounded(
+            "starknet_V0_8_subscribePendingTransactions",
+            rpc_params![true, vec![sender_address]],
+        )
+        .await
+        .unwrap();
+    let mut other_sender_subscription = module
+        .subscribe_unbounded(
+            "starknet_V0_8_subscribePendingTransactions",
+            rpc_params![false, vec![contract_address!("0x2")]],
+        )
+        .await
+        .unwrap();
+    // Let the mempool be polled before the transaction is added to it.
+    tokio::time::sleep(CHAIN_UPDATES_POLLING_INTERVAL * 2).await;
+
+    mempool_transactions.lock().unwrap().push(transaction.tx_hash);
+    let pending_transaction: PendingTransaction = next_notification(&mut hashes_subscription).await;
+    assert_eq!(pending_transaction, PendingTransaction::Hash(transaction.tx_hash));
+    let pending_transaction: PendingTransaction =
+        next_notification(&mut details_subscription).await;
+    assert_eq!(
+        pending_transaction,
+        PendingTransaction::Full(TransactionWithHash {
+            transaction: StarknetApiTransaction::from(transaction.tx.clone()).try_into().unwrap(),
+            transaction_hash: transaction.tx_hash,
+        })
+    );
+    // The transaction isn't notified again, nor to subscribers of other senders.
+    tokio::time::sleep(CHAIN_UPDATES_POLLING_INTERVAL * 2).await;
+    assert!(tokio::time::timeout(
+        CHAIN_UPDATES_POLLING_INTERVAL,
+        hashes_subscription.next::<serde_json::Value>()
+    )
+    .await
+    .is_err());
+    assert!(tokio::time::timeout(
+        CHAIN_UPDATES_POLLING_INTERVAL,
+        other_sender_subscription.next::<serde_json::Value>()
+    )
+    .await
+    .is_err());
+
+    let too_many_addresses = vec![ContractAddress::default(); MAX_SENDER_ADDRESSES_IN_FILTER + 1];
+    let err = module
+        .subscribe_unbounded(
+            "starknet_V0_8_subscribePendingTransactions",
+            rpc_params![false, too_many_addresses],
+        )
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == TOO_MANY_ADDRESSES_IN_FILTER.into());
+}
+
+#[tokio::test]
+async fn subscribe_pending_transactions_without_mempool() {
+    let (module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let err = module
+        .subscribe_unbounded("starknet_V0_8_subscribePendingTransactions", rpc_params![])
+        .await
+        .unwrap_err();
+    assert_matches!(err, Error::Call(_));
+}
+
+#[tokio::test]
+async fn unsubscribe() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    append_subscription_test_block(
+        &mut storage_writer,
+        BlockNumber(0),
+        BlockHash::default(),
+        BlockBody::default(),
+    );
+    let mut subscription =
+        module.subscribe_unbounded("starknet_V0_8_subscribeNewHeads", rpc_params![]).await.unwrap();
+    // The subscription can be cancelled once it sends notifications.
+    let _: RpcBlockHeader = next_notification(&mut subscription).await;
+    let subscription_id = serde_json::Value::from(subscription.subscription_id().clone());
+
+    // A subscription can't be cancelled by another connection. The subscriptions of the test
+    // module are of the connection with id 0.
+    let Some(MethodCallback::Unsubscription(unsubscribe_callback)) =
+        module.method("starknet_V0_8_unsubscribe")
+    else {
+        panic!("starknet_V0_8_unsubscribe should be an unsubscription method");
+    };
+    let params = serde_json::to_string(&[&subscription_id]).unwrap();
+    let response = unsubscribe_callback(Id::Number(1), Params::new(Some(&params)), 1, usize::MAX);
+    assert_eq!(response.success_or_error.as_error_code(), Some(INVALID_SUBSCRIPTION_ID.code));
+
+    let unsubscribed =
+        module.call::<_, bool>("starknet_V0_8_unsubscribe", [&subscription_id]).await.unwrap();
+    assert!(unsubscribed);
+    let err =
+        module.call::<_, bool>("starknet_V0_8_unsubscribe", [&subscription_id]).await.unwrap_err();
+    assert_matches!(err, Error::Call(err) if err == INVALID_SUBSCRIPTION_ID.into());
+}
+
 auto_impl_get_test_instance! {
     pub struct PendingBlockHeader {
         pub parent_hash: BlockHash,
@@ -74,13 +74,15 @@ impl From<starknet_api::block::BlockHeader> for BlockHeader {
     }
 }
 
+// getBlockWithTxs and getBlockWithReceipts return blocks whose transactions are a StreamedSeq, read
+// from the storage while they're serialized.
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct Block {
+pub struct Block<Txs = Transactions> {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub status: Option<BlockStatus>,
     #[serde(flatten)]
     pub header: GeneralBlockHeader,
-    pub transactions: Transactions,
+    pub transactions: Txs,
 }
 
 pub fn get_block_header_by_number<Mode: TransactionKind>(
@@ -167,6 +169,10 @@ impl BlockNotRevertedValidator {
             .map_err(internal_server_error)?
             .ok_or(error.clone())?
             .block_hash;
-        if new_block_hash == self.old_block_hash { Ok(()) } else { Err(error) }
+        if new_block_hash == self.old_block_hash {
+            Ok(())
+        } else {
+            Err(error)
+        }
     }
 }
@@ -125,6 +125,18 @@ pub fn unexpected_error(data: String) -> JsonRpcError<String> {
     JsonRpcError { code: 63, message: "An unexpected error occurred", data: Some(data) }
 }
 
+pub const INVALID_SUBSCRIPTION_ID: JsonRpcError<String> =
+    JsonRpcError { code: 66, message: "Invalid subscription id", data: None };
+
+pub const TOO_MANY_ADDRESSES_IN_FILTER: JsonRpcError<String> = JsonRpcError {
+    code: 67,
+    message: "Too many addresses in filter sender_address filter",
+    data: None,
+};
+
+pub const TOO_MANY_BLOCKS_BACK: JsonRpcError<String> =
+    JsonRpcError { code: 68, message: "Cannot go back more than 1024 blocks", data: None };
+
 impl<T: Serialize> From<JsonRpcError<T>> for ErrorObjectOwned {
     fn from(err: JsonRpcError<T>) -> Self {
         ErrorObjectOwned::owned(err.code, err.message, err.data)
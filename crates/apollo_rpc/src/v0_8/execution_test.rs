@@ -1644,7 +1644,7 @@ async fn write_block_0_as_pending(
     }
 }
 
-fn prepare_storage_for_execution(mut storage_writer: StorageWriter) -> StorageWriter {
+pub(crate) fn prepare_storage_for_execution(mut storage_writer: StorageWriter) -> StorageWriter {
     let class1 = serde_json::from_value::<SN_API_DeprecatedContractClass>(read_json_file(
         "deprecated_class.json",
     ))
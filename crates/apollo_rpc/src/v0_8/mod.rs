@@ -5,8 +5,9 @@ pub mod deprecated_contract_class;
 pub mod error;
 pub mod execution;
 #[cfg(test)]
-mod execution_test;
+pub(crate) mod execution_test;
 pub mod state;
+pub mod subscription;
 pub mod transaction;
 pub mod write_api_error;
 pub mod write_api_result;
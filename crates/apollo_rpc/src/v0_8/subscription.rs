@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use apollo_storage::db::RO;
+use apollo_storage::{StorageReader, StorageTxn};
+use jsonrpsee::core::{RpcResult, SubscriptionResult};
+use jsonrpsee::types::{ErrorObjectOwned, ResponsePayload};
+use jsonrpsee::{
+    MethodCallback,
+    MethodResponse,
+    PendingSubscriptionSink,
+    SubscriptionMessage,
+    SubscriptionSink,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use starknet_api::block::{BlockHash, BlockHashAndNumber, BlockNumber};
+use starknet_api::transaction::TransactionHash;
+use tokio::sync::broadcast;
+
+use super::error::INVALID_SUBSCRIPTION_ID;
+use super::transaction::{TransactionStatus, TransactionWithHash};
+use crate::internal_server_error;
+use crate::subscriptions::{
+    get_notified_block_marker,
+    ActiveSubscription,
+    ChainUpdate,
+    Subscriptions,
+};
+
+/// The maximal number of blocks before the latest block that a subscription can start from.
+pub const MAX_BLOCKS_BACK: u64 = 1024;
+
+/// The maximal number of addresses in the sender address filter of pending transactions.
+pub const MAX_SENDER_ADDRESSES_IN_FILTER: usize = 1024;
+
+const REORG_NOTIFICATION_METHOD: &str = "starknet_subscriptionReorg";
+
+/// The range of blocks that were reverted, sent to the subscriptions that were notified of them.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ReorgData {
+    pub starting_block_hash: BlockHash,
+    pub starting_block_number: BlockNumber,
+    pub ending_block_hash: BlockHash,
+    pub ending_block_number: BlockNumber,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TransactionStatusNotification {
+    pub transaction_hash: TransactionHash,
+    pub status: TransactionStatus,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PendingTransaction {
+    Hash(TransactionHash),
+    Full(TransactionWithHash),
+}
+
+/// An accepted subscription to the chain updates. It ends once the client closes it or it's
+/// cancelled by `starknet_unsubscribe`.
+pub(crate) struct ChainSubscription {
+    sink: SubscriptionSink,
+    // Unregisters the subscription when dropped.
+    active_subscription: ActiveSubscription,
+    chain_updates: broadcast::Receiver<ChainUpdate>,
+}
+
+impl ChainSubscription {
+    pub(crate) async fn accept(
+        pending: PendingSubscriptionSink,
+        subscriptions: &Subscriptions,
+    ) -> Result<Self, jsonrpsee::core::StringError> {
+        // Subscribing to the chain updates before accepting, so that no update after the caller
+        // reads the storage is missed.
+        let chain_updates = subscriptions.subscribe_to_chain_updates();
+        let sink = pending.accept().await?;
+        let active_subscription =
+            subscriptions.register(sink.connection_id(), sink.subscription_id());
+        Ok(Self { sink, active_subscription, chain_updates })
+    }
+
+    pub(crate) async fn send(&self, item: &(impl Serialize + Sync)) -> SubscriptionResult {
+        self.sink.send(SubscriptionMessage::from_json(item)?).await?;
+        Ok(())
+    }
+
+    pub(crate) async fn send_reorg(
+        &self,
+        first: BlockHashAndNumber,
+        last: BlockHashAndNumber,
+    ) -> SubscriptionResult {
+        let reorg_data = ReorgData {
+            starting_block_hash: first.hash,
+            starting_block_number: first.number,
+            ending_block_hash: last.hash,
+            ending_block_number: last.number,
+        };
+        self.sink
+            .send(SubscriptionMessage::new(
+                REORG_NOTIFICATION_METHOD,
+                self.sink.subscription_id(),
+                &reorg_data,
+            )?)
+            .await?;
+        Ok(())
+    }
+
+    /// Waits for the next chain update. Returns None once the subscription ended. Fails if the
+    /// subscription fell too far behind the chain updates.
+    pub(crate) async fn next_update(
+        &mut self,
+    ) -> Result<Option<ChainUpdate>, broadcast::error::RecvError> {
+        tokio::select! {
+            _ = self.sink.closed() => Ok(None),
+            _ = self.active_subscription.cancelled() => Ok(None),
+            update = self.chain_updates.recv() => update.map(Some),
+        }
+    }
+
+    /// Sends the items of the blocks from the first block onwards, as they are stored, along with
+    /// notifications of the reorgs of the sent blocks, until the subscription ends.
+    pub(crate) async fn stream_block_items<T: Serialize + Send + Sync>(
+        mut self,
+        storage_reader: &StorageReader,
+        first_block: BlockNumber,
+        get_block_items: impl Fn(&StorageTxn<'_, RO>, BlockNumber) -> RpcResult<Vec<T>> + Send + Sync,
+    ) -> SubscriptionResult {
+        let mut next_block = first_block;
+        let block_marker = get_notified_block_marker(
+            &storage_reader.begin_ro_txn().map_err(internal_server_error)?,
+        )
+        .map_err(internal_server_error)?;
+        self.send_block_items(storage_reader, &mut next_block, block_marker, &get_block_items)
+            .await?;
+        while let Some(update) = self.next_update().await? {
+            match update {
+                ChainUpdate::NewBlock(block) => {
+                    self.send_block_items(
+                        storage_reader,
+                        &mut next_block,
+                        block.number.unchecked_next(),
+                        &get_block_items,
+                    )
+                    .await?
+                }
+                ChainUpdate::Reorg { first, last } if first.number < next_block => {
+                    self.send_reorg(first, last).await?;
+                    next_block = first.number;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    async fn send_block_items<T: Serialize + Send + Sync>(
+        &self,
+        storage_reader: &StorageReader,
+        next_block: &mut BlockNumber,
+        end_block: BlockNumber,
+        get_block_items: &(impl Fn(&StorageTxn<'_, RO>, BlockNumber) -> RpcResult<Vec<T>> + Sync),
+    ) -> SubscriptionResult {
+        while *next_block < end_block {
+            // The transaction isn't held while sending.
+            let items = {
+                let txn = storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+                get_block_items(&txn, *next_block)?
+            };
+            for item in items {
+                self.send(&item).await?;
+            }
+            *next_block = next_block.unchecked_next();
+        }
+        Ok(())
+    }
+}
+
+/// Returns the callback of `starknet_unsubscribe`, which cancels a subscription of the connection
+/// it's called on. It isn't a method of the API trait, as those don't get the connection id.
+pub(crate) fn unsubscribe_callback(subscriptions: Subscriptions) -> MethodCallback {
+    MethodCallback::Unsubscription(Arc::new(move |id, params, connection_id, max_response_size| {
+        let subscription_id = match params.one::<Value>() {
+            Ok(subscription_id) => subscription_id,
+            Err(err) => return MethodResponse::error(id, err),
+        };
+        match subscriptions.unsubscribe(connection_id, &subscription_id) {
+            true => MethodResponse::response(id, ResponsePayload::result(true), max_response_size),
+            false => MethodResponse::error(id, ErrorObjectOwned::from(INVALID_SUBSCRIPTION_ID)),
+        }
+    }))
+}
@@ -9,7 +9,7 @@ use std::sync::Arc;
 
 use apollo_rpc_execution::objects::PriceUnit;
 use apollo_starknet_client::writer::objects::transaction as client_transaction;
-use apollo_storage::body::BodyStorageReader;
+use apollo_storage::body::{BodyStorageReader, TransactionIndex};
 use apollo_storage::db::TransactionKind;
 use apollo_storage::StorageTxn;
 use ethers::core::abi::{encode_packed, Token};
@@ -562,8 +562,8 @@ pub struct TransactionWithHash {
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct TransactionWithReceipt {
-    pub receipt: TransactionReceiptInBlock,
+pub struct TransactionWithReceipt<Resources = ExecutionResources> {
+    pub receipt: TransactionReceiptInBlock<Resources>,
     pub transaction: Transaction,
 }
 
@@ -698,12 +698,12 @@ impl From<BlockStatus> for TransactionFinalityStatus {
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
 #[serde(untagged)]
-pub enum GeneralTransactionReceipt {
-    TransactionReceipt(TransactionReceipt),
-    PendingTransactionReceipt(PendingTransactionReceipt),
+pub enum GeneralTransactionReceipt<Resources = ExecutionResources> {
+    TransactionReceipt(TransactionReceipt<Resources>),
+    PendingTransactionReceipt(PendingTransactionReceipt<Resources>),
 }
 
-impl GeneralTransactionReceipt {
+impl<Resources> GeneralTransactionReceipt<Resources> {
     pub fn transaction_status(&self) -> TransactionStatus {
         match self {
             GeneralTransactionReceipt::TransactionReceipt(receipt) => TransactionStatus {
@@ -719,48 +719,50 @@ impl GeneralTransactionReceipt {
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct TransactionReceipt {
+pub struct TransactionReceipt<Resources = ExecutionResources> {
     pub finality_status: TransactionFinalityStatus,
     pub transaction_hash: TransactionHash,
     pub block_hash: BlockHash,
     pub block_number: BlockNumber,
     #[serde(flatten)]
-    pub output: TransactionOutput,
+    pub output: TransactionOutput<Resources>,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct PendingTransactionReceipt {
+pub struct PendingTransactionReceipt<Resources = ExecutionResources> {
     pub finality_status: PendingTransactionFinalityStatus,
     pub transaction_hash: TransactionHash,
     #[serde(flatten)]
-    pub output: PendingTransactionOutput,
+    pub output: PendingTransactionOutput<Resources>,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct TransactionReceiptInBlock {
+pub struct TransactionReceiptInBlock<Resources = ExecutionResources> {
     pub finality_status: TransactionFinalityStatus,
     pub transaction_hash: TransactionHash,
     #[serde(flatten)]
-    pub output: TransactionOutput,
+    pub output: TransactionOutput<Resources>,
 }
 
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
 #[serde(tag = "type")]
-pub enum TransactionOutput {
+pub enum TransactionOutput<Resources = ExecutionResources> {
     #[serde(rename = "DECLARE")]
-    Declare(DeclareTransactionOutput),
+    Declare(DeclareTransactionOutput<Resources>),
     #[serde(rename = "DEPLOY")]
-    Deploy(DeployTransactionOutput),
+    Deploy(DeployTransactionOutput<Resources>),
     #[serde(rename = "DEPLOY_ACCOUNT")]
-    DeployAccount(DeployAccountTransactionOutput),
+    DeployAccount(DeployAccountTransactionOutput<Resources>),
     #[serde(rename = "INVOKE")]
-    Invoke(InvokeTransactionOutput),
+    Invoke(InvokeTransactionOutput<Resources>),
     #[serde(rename = "L1_HANDLER")]
-    L1Handler(L1HandlerTransactionOutput),
+    L1Handler(L1HandlerTransactionOutput<Resources>),
 }
 
-impl From<GeneralTransactionReceipt> for TransactionReceiptInBlock {
-    fn from(value: GeneralTransactionReceipt) -> Self {
+impl<Resources> From<GeneralTransactionReceipt<Resources>>
+    for TransactionReceiptInBlock<Resources>
+{
+    fn from(value: GeneralTransactionReceipt<Resources>) -> Self {
         match value {
             GeneralTransactionReceipt::TransactionReceipt(receipt) => Self {
                 finality_status: receipt.finality_status,
@@ -801,63 +803,63 @@ pub struct FeePayment {
 /// A declare transaction output.
 // Note: execution_resources is not included in the output because it is not used in this version.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct DeclareTransactionOutput {
+pub struct DeclareTransactionOutput<Resources = ExecutionResources> {
     pub actual_fee: FeePayment,
     pub messages_sent: Vec<MessageToL1>,
     pub events: Vec<starknet_api::transaction::Event>,
     #[serde(flatten)]
     pub execution_status: TransactionExecutionStatus,
-    pub execution_resources: ExecutionResources,
+    pub execution_resources: Resources,
 }
 
 /// A deploy-account transaction output.
 // Note: execution_resources is not included in the output because it is not used in this version.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct DeployAccountTransactionOutput {
+pub struct DeployAccountTransactionOutput<Resources = ExecutionResources> {
     pub actual_fee: FeePayment,
     pub messages_sent: Vec<MessageToL1>,
     pub events: Vec<starknet_api::transaction::Event>,
     pub contract_address: ContractAddress,
     #[serde(flatten)]
     pub execution_status: TransactionExecutionStatus,
-    pub execution_resources: ExecutionResources,
+    pub execution_resources: Resources,
 }
 
 /// A deploy transaction output.
 // Note: execution_resources is not included in the output because it is not used in this version.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct DeployTransactionOutput {
+pub struct DeployTransactionOutput<Resources = ExecutionResources> {
     pub actual_fee: FeePayment,
     pub messages_sent: Vec<MessageToL1>,
     pub events: Vec<starknet_api::transaction::Event>,
     pub contract_address: ContractAddress,
     #[serde(flatten)]
     pub execution_status: TransactionExecutionStatus,
-    pub execution_resources: ExecutionResources,
+    pub execution_resources: Resources,
 }
 
 /// An invoke transaction output.
 // Note: execution_resources is not included in the output because it is not used in this version.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct InvokeTransactionOutput {
+pub struct InvokeTransactionOutput<Resources = ExecutionResources> {
     pub actual_fee: FeePayment,
     pub messages_sent: Vec<MessageToL1>,
     pub events: Vec<starknet_api::transaction::Event>,
     #[serde(flatten)]
     pub execution_status: TransactionExecutionStatus,
-    pub execution_resources: ExecutionResources,
+    pub execution_resources: Resources,
 }
 
 /// An L1 handler transaction output.
 // Note: execution_resources is not included in the output because it is not used in this version.
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
-pub struct L1HandlerTransactionOutput {
+pub struct L1HandlerTransactionOutput<Resources = ExecutionResources> {
     pub actual_fee: FeePayment,
     pub messages_sent: Vec<MessageToL1>,
     pub events: Vec<starknet_api::transaction::Event>,
     #[serde(flatten)]
     pub execution_status: TransactionExecutionStatus,
-    pub execution_resources: ExecutionResources,
+    pub execution_resources: Resources,
     pub message_hash: L1L2MsgHash,
 }
 
@@ -1015,18 +1017,18 @@ impl From<starknet_api::execution_resources::ExecutionResources> for Computation
 // of a bug that makes deny_unknown_fields not work well with flatten:
 // https://github.com/serde-rs/serde/issues/1358
 #[serde(deny_unknown_fields)]
-pub enum PendingTransactionOutput {
+pub enum PendingTransactionOutput<Resources = ExecutionResources> {
     #[serde(rename = "DECLARE")]
-    Declare(DeclareTransactionOutput),
+    Declare(DeclareTransactionOutput<Resources>),
     #[serde(rename = "DEPLOY_ACCOUNT")]
-    DeployAccount(DeployAccountTransactionOutput),
+    DeployAccount(DeployAccountTransactionOutput<Resources>),
     #[serde(rename = "INVOKE")]
-    Invoke(InvokeTransactionOutput),
+    Invoke(InvokeTransactionOutput<Resources>),
     #[serde(rename = "L1_HANDLER")]
-    L1Handler(L1HandlerTransactionOutput),
+    L1Handler(L1HandlerTransactionOutput<Resources>),
 }
 
-impl PendingTransactionOutput {
+impl<Resources> PendingTransactionOutput<Resources> {
     pub fn execution_status(&self) -> &TransactionExecutionStatus {
         match self {
             PendingTransactionOutput::Declare(tx_output) => &tx_output.execution_status,
@@ -1037,7 +1039,7 @@ impl PendingTransactionOutput {
     }
 }
 
-impl TransactionOutput {
+impl<Resources> TransactionOutput<Resources> {
     pub fn execution_status(&self) -> &TransactionExecutionStatus {
         match self {
             TransactionOutput::Declare(tx_output) => &tx_output.execution_status,
@@ -1049,8 +1051,11 @@ impl TransactionOutput {
     }
 }
 
-impl From<(starknet_api::transaction::TransactionOutput, TransactionVersion, Option<L1L2MsgHash>)>
-    for TransactionOutput
+impl<Resources>
+    From<(starknet_api::transaction::TransactionOutput, TransactionVersion, Option<L1L2MsgHash>)>
+    for TransactionOutput<Resources>
+where
+    Resources: From<starknet_api::execution_resources::ExecutionResources>,
 {
     #[cfg_attr(coverage_nightly, coverage_attribute)]
     fn from(
@@ -1126,10 +1131,10 @@ impl From<(starknet_api::transaction::TransactionOutput, TransactionVersion, Opt
     }
 }
 
-impl TryFrom<TransactionOutput> for PendingTransactionOutput {
+impl<Resources> TryFrom<TransactionOutput<Resources>> for PendingTransactionOutput<Resources> {
     type Error = ErrorObjectOwned;
 
-    fn try_from(tx_output: TransactionOutput) -> Result<Self, Self::Error> {
+    fn try_from(tx_output: TransactionOutput<Resources>) -> Result<Self, Self::Error> {
         match tx_output {
             TransactionOutput::Declare(declare_tx_output) => {
                 Ok(PendingTransactionOutput::Declare(declare_tx_output))
@@ -1167,31 +1172,44 @@ pub struct Event {
     pub event: starknet_api::transaction::Event,
 }
 
-pub fn get_block_txs_by_number<
-    Mode: TransactionKind,
-    Transaction: TryFrom<starknet_api::transaction::Transaction, Error = ErrorObjectOwned>,
->(
+pub fn get_block_tx_hashes_by_number<Mode: TransactionKind>(
     txn: &StorageTxn<'_, Mode>,
     block_number: BlockNumber,
-) -> Result<Vec<Transaction>, ErrorObjectOwned> {
-    let transactions = txn
-        .get_block_transactions(block_number)
+) -> Result<Vec<TransactionHash>, ErrorObjectOwned> {
+    let transaction_hashes = txn
+        .get_block_transaction_hashes(block_number)
         .map_err(internal_server_error)?
         .ok_or_else(|| ErrorObjectOwned::from(BLOCK_NOT_FOUND))?;
 
-    transactions.into_iter().map(Transaction::try_from).collect()
+    Ok(transaction_hashes)
 }
 
-pub fn get_block_tx_hashes_by_number<Mode: TransactionKind>(
+pub fn get_block_txs_count_by_number<Mode: TransactionKind>(
     txn: &StorageTxn<'_, Mode>,
     block_number: BlockNumber,
-) -> Result<Vec<TransactionHash>, ErrorObjectOwned> {
-    let transaction_hashes = txn
-        .get_block_transaction_hashes(block_number)
+) -> Result<usize, ErrorObjectOwned> {
+    txn.get_block_transactions_count(block_number)
+        .map_err(internal_server_error)?
+        .ok_or_else(|| ErrorObjectOwned::from(BLOCK_NOT_FOUND))
+}
+
+pub fn get_tx_with_hash_by_index<
+    Mode: TransactionKind,
+    Transaction: TryFrom<starknet_api::transaction::Transaction, Error = ErrorObjectOwned>,
+>(
+    txn: &StorageTxn<'_, Mode>,
+    transaction_index: TransactionIndex,
+) -> Result<(Transaction, TransactionHash), ErrorObjectOwned> {
+    let transaction = txn
+        .get_transaction(transaction_index)
+        .map_err(internal_server_error)?
+        .ok_or_else(|| ErrorObjectOwned::from(BLOCK_NOT_FOUND))?;
+    let transaction_hash = txn
+        .get_transaction_hash_by_idx(&transaction_index)
         .map_err(internal_server_error)?
         .ok_or_else(|| ErrorObjectOwned::from(BLOCK_NOT_FOUND))?;
 
-    Ok(transaction_hashes)
+    Ok((Transaction::try_from(transaction)?, transaction_hash))
 }
 
 /// The hash of a L1 -> L2 message.
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use apollo_class_manager_types::SharedClassManagerClient;
+use apollo_rpc_execution::objects::StateOverrides;
+use apollo_rpc_execution::ExecutionConfig;
+use apollo_starknet_client::reader::PendingData;
+use apollo_starknet_client::writer::StarknetWriter;
+use apollo_storage::base_layer::BaseLayerStorageReader;
+use apollo_storage::StorageReader;
+use async_trait::async_trait;
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::RpcModule;
+use papyrus_common::pending_classes::PendingClasses;
+use starknet_api::block::{BlockHashAndNumber, BlockNumber};
+use starknet_api::core::{ChainId, ClassHash, ContractAddress, L1TransactionHash};
+use starknet_api::transaction::TransactionHash;
+use tokio::sync::RwLock;
+use tracing::instrument;
+
+use super::super::error::STORAGE_PROOF_NOT_SUPPORTED;
+use super::super::execution::TransactionTrace;
+use super::super::state::{ContractStorageKeys, StorageProof};
+use super::super::transaction::{
+    ExecutionResources,
+    GeneralTransactionReceipt,
+    MessageStatus,
+    PreConfirmedTransactionFinalityStatus,
+    PreConfirmedTransactionReceipt,
+    TransactionStatus,
+    TransactionWithReceipt,
+};
+use super::{JsonRpcV0_9Server as JsonRpcServer, SimulatedTransaction, TransactionTraceWithHash};
+use crate::api::{inherit_methods, BlockId, JsonRpcServerTrait};
+use crate::streaming::StreamedSeq;
+use crate::subscriptions::Subscriptions;
+use crate::v0_8::api::api_impl::JsonRpcServerImpl as JsonRpcServerV0_8Impl;
+use crate::v0_8::api::SimulationFlag;
+use crate::v0_8::block::{get_accepted_block_number, Block};
+use crate::v0_8::broadcasted_transaction::BroadcastedTransaction;
+use crate::v0_8::error::TRANSACTION_HASH_NOT_FOUND;
+use crate::v0_8::transaction::GeneralTransactionReceipt as V0_8GeneralTransactionReceipt;
+use crate::version_config::{VERSION_0_8, VERSION_0_9 as VERSION};
+use crate::{get_latest_block_number, internal_server_error};
+
+/// Rpc server. The methods that didn't change since V0_8 are served by the V0_8 server.
+#[derive(Clone)]
+pub struct JsonRpcServerImpl {
+    pub inherited: JsonRpcServerV0_8Impl,
+}
+
+#[async_trait]
+impl JsonRpcServer for JsonRpcServerImpl {
+    #[instrument(skip(self), level = "debug", err, ret)]
+    fn spec_version(&self) -> RpcResult<String> {
+        Ok(format!("{VERSION}"))
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn get_transaction_status(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionStatus> {
+        Ok(self.get_transaction_receipt(transaction_hash).await?.transaction_status())
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn get_block_w_full_transactions_and_receipts(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Block<StreamedSeq<TransactionWithReceipt>>> {
+        self.inherited.get_block_with_receipts(block_id).await
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn get_storage_proof(
+        &self,
+        block_id: BlockId,
+        _class_hashes: Option<Vec<ClassHash>>,
+        _contract_addresses: Option<Vec<ContractAddress>>,
+        _contracts_storage_keys: Option<Vec<ContractStorageKeys>>,
+    ) -> RpcResult<StorageProof> {
+        let txn = self.inherited.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+        get_accepted_block_number(&txn, block_id)?;
+        // The storage keeps the state diffs but not the state tries, so there are no proofs to
+        // return for any block.
+        Err(STORAGE_PROOF_NOT_SUPPORTED.into())
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<GeneralTransactionReceipt> {
+        match self
+            .inherited
+            .get_general_transaction_receipt::<ExecutionResources>(transaction_hash)
+            .await?
+        {
+            V0_8GeneralTransactionReceipt::TransactionReceipt(receipt) => {
+                Ok(GeneralTransactionReceipt::TransactionReceipt(receipt))
+            }
+            V0_8GeneralTransactionReceipt::PendingTransactionReceipt(receipt) => {
+                // The pending data is returned only if it's on top of the latest block.
+                let txn =
+                    self.inherited.storage_reader.begin_ro_txn().map_err(internal_server_error)?;
+                let block_number = get_latest_block_number(&txn)?
+                    .map_or(BlockNumber(0), |latest_block_number| {
+                        latest_block_number.unchecked_next()
+                    });
+                Ok(GeneralTransactionReceipt::PreConfirmedTransactionReceipt(
+                    PreConfirmedTransactionReceipt {
+                        finality_status: PreConfirmedTransactionFinalityStatus::PreConfirmed,
+                        transaction_hash: receipt.transaction_hash,
+                        block_number,
+                        output: receipt.output,
+                    },
+                ))
+            }
+        }
+    }
+
+    #[instrument(skip(self), level = "debug", err, ret)]
+    async fn get_messages_status(
+        &self,
+        transaction_hash: L1TransactionHash,
+    ) -> RpcResult<Vec<MessageStatus>> {
+        let l1_handler_transaction_hashes = self
+            .inherited
+            .storage_reader
+            .begin_ro_txn()
+            .map_err(internal_server_error)?
+            .get_l1_handler_transaction_hashes(&transaction_hash)
+            .map_err(internal_server_error)?;
+        if l1_handler_transaction_hashes.is_empty() {
+            return Err(TRANSACTION_HASH_NOT_FOUND.into());
+        }
+
+        let mut statuses = Vec::with_capacity(l1_handler_transaction_hashes.len());
+        for l1_handler_transaction_hash in l1_handler_transaction_hashes {
+            // A message whose L1 handler transaction wasn't found on L2 wasn't executed yet.
+            let status = match self.get_transaction_status(l1_handler_transaction_hash).await {
+                Ok(status) => MessageStatus::new(l1_handler_transaction_hash, status),
+                Err(err) if err == ErrorObjectOwned::from(TRANSACTION_HASH_NOT_FOUND) => {
+                    MessageStatus::received(l1_handler_transaction_hash)
+                }
+                Err(err) => return Err(err),
+            };
+            statuses.push(status);
+        }
+        Ok(statuses)
+    }
+
+    #[instrument(skip(self, transactions), level = "debug", err, ret)]
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+    ) -> RpcResult<Vec<SimulatedTransaction>> {
+        Ok(self
+            .inherited
+            .simulate(block_id, transactions, simulation_flags, StateOverrides::default())
+            .await?
+            .into_iter()
+            .map(|simulation_output| SimulatedTransaction {
+                transaction_trace: (
+                    simulation_output.transaction_trace,
+                    simulation_output.state_diff,
+                    simulation_output.gas_consumed,
+                )
+                    .into(),
+                fee_estimation: simulation_output.fee_estimation,
+            })
+            .collect())
+    }
+
+    #[instrument(skip(self), level = "debug", err)]
+    async fn trace_transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionTrace> {
+        let simulation_output =
+            self.inherited.simulate_transaction_replay(transaction_hash).await?;
+        Ok((
+            simulation_output.transaction_trace,
+            simulation_output.state_diff,
+            simulation_output.gas_consumed,
+        )
+            .into())
+    }
+
+    #[instrument(skip(self), level = "debug", err)]
+    async fn trace_block_transactions(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<TransactionTraceWithHash>> {
+        Ok(self
+            .inherited
+            .simulate_block_transactions(block_id)
+            .await?
+            .into_iter()
+            .map(|(transaction_hash, simulation_output)| TransactionTraceWithHash {
+                transaction_hash,
+                trace_root: (
+                    simulation_output.transaction_trace,
+                    simulation_output.state_diff,
+                    simulation_output.gas_consumed,
+                )
+                    .into(),
+            })
+            .collect())
+    }
+}
+
+impl JsonRpcServerTrait for JsonRpcServerImpl {
+    fn new(
+        chain_id: ChainId,
+        execution_config: ExecutionConfig,
+        storage_reader: StorageReader,
+        max_events_chunk_size: usize,
+        max_events_keys: usize,
+        max_events_block_range: u64,
+        starting_block: BlockHashAndNumber,
+        shared_highest_block: Arc<RwLock<Option<BlockHashAndNumber>>>,
+        pending_data: Arc<RwLock<PendingData>>,
+        pending_classes: Arc<RwLock<PendingClasses>>,
+        writer_client: Arc<dyn StarknetWriter>,
+        class_manager_client: Option<SharedClassManagerClient>,
+        subscriptions: Subscriptions,
+    ) -> Self {
+        Self {
+            inherited: JsonRpcServerV0_8Impl::new(
+                chain_id,
+                execution_config,
+                storage_reader,
+                max_events_chunk_size,
+                max_events_keys,
+                max_events_block_range,
+                starting_block,
+                shared_highest_block,
+                pending_data,
+                pending_classes,
+                writer_client,
+                class_manager_client,
+                subscriptions,
+            ),
+        }
+    }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        let inherited_module = self.inherited.clone().into_rpc_module();
+        let mut rpc_module = self.into_rpc();
+        inherit_methods(&mut rpc_module, inherited_module.into(), &VERSION_0_8, &VERSION);
+        rpc_module
+    }
+}
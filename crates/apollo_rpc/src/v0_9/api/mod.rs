@@ -0,0 +1,111 @@
+use apollo_proc_macros::versioned_rpc;
+use apollo_rpc_execution::objects::FeeEstimation;
+use jsonrpsee::core::RpcResult;
+use jsonrpsee::proc_macros::rpc;
+use serde::{Deserialize, Serialize};
+use starknet_api::core::{ClassHash, ContractAddress, L1TransactionHash};
+use starknet_api::transaction::TransactionHash;
+
+use super::execution::TransactionTrace;
+use super::state::{ContractStorageKeys, StorageProof};
+use super::transaction::{
+    GeneralTransactionReceipt,
+    MessageStatus,
+    TransactionStatus,
+    TransactionWithReceipt,
+};
+use crate::api::BlockId;
+use crate::streaming::StreamedSeq;
+use crate::v0_8::api::SimulationFlag;
+use crate::v0_8::block::Block;
+use crate::v0_8::broadcasted_transaction::BroadcastedTransaction;
+
+pub mod api_impl;
+#[cfg(test)]
+mod test;
+
+// V0_9 is experimental: it's served only on its own path and may still change. Only the methods
+// that changed since V0_8, including the ones whose receipts and traces have the resources in gas,
+// are declared here. The other methods of V0_8 are served under the V0_9 names as they are.
+#[versioned_rpc("V0_9")]
+#[async_trait]
+pub trait JsonRpc {
+    /// Returns the version of the Starknet JSON-RPC specification being used
+    #[method(name = "specVersion")]
+    fn spec_version(&self) -> RpcResult<String>;
+
+    /// Gets the transaction status, along with the failure reason of reverted transactions.
+    #[method(name = "getTransactionStatus")]
+    async fn get_transaction_status(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionStatus>;
+
+    /// Gets block information with full transactions and receipts given the block id.
+    #[method(name = "getBlockWithReceipts")]
+    async fn get_block_w_full_transactions_and_receipts(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Block<StreamedSeq<TransactionWithReceipt>>>;
+
+    /// Gets the Merkle paths of the given classes, contracts and storage keys in the state tries
+    /// of the given block.
+    #[method(name = "getStorageProof")]
+    async fn get_storage_proof(
+        &self,
+        block_id: BlockId,
+        class_hashes: Option<Vec<ClassHash>>,
+        contract_addresses: Option<Vec<ContractAddress>>,
+        contracts_storage_keys: Option<Vec<ContractStorageKeys>>,
+    ) -> RpcResult<StorageProof>;
+
+    /// Gets the transaction receipt by the transaction hash.
+    #[method(name = "getTransactionReceipt")]
+    async fn get_transaction_receipt(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<GeneralTransactionReceipt>;
+
+    /// Gets the statuses of the L1 handler transactions of the messages sent to L2 by the given L1
+    /// transaction, in the order the messages were sent.
+    #[method(name = "getMessagesStatus")]
+    async fn get_messages_status(
+        &self,
+        transaction_hash: L1TransactionHash,
+    ) -> RpcResult<Vec<MessageStatus>>;
+
+    /// Simulates execution of a series of transactions.
+    #[method(name = "simulateTransactions")]
+    async fn simulate_transactions(
+        &self,
+        block_id: BlockId,
+        transactions: Vec<BroadcastedTransaction>,
+        simulation_flags: Vec<SimulationFlag>,
+    ) -> RpcResult<Vec<SimulatedTransaction>>;
+
+    /// Calculates the transaction trace of a transaction that is already included in a block.
+    #[method(name = "traceTransaction")]
+    async fn trace_transaction(
+        &self,
+        transaction_hash: TransactionHash,
+    ) -> RpcResult<TransactionTrace>;
+
+    /// Calculates the transaction trace of all of the transactions in a block.
+    #[method(name = "traceBlockTransactions")]
+    async fn trace_block_transactions(
+        &self,
+        block_id: BlockId,
+    ) -> RpcResult<Vec<TransactionTraceWithHash>>;
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SimulatedTransaction {
+    pub transaction_trace: TransactionTrace,
+    pub fee_estimation: FeeEstimation,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Eq, PartialEq)]
+pub struct TransactionTraceWithHash {
+    pub transaction_hash: TransactionHash,
+    pub trace_root: TransactionTrace,
+}
@@ -0,0 +1,578 @@
+use std::ops::Index;
+
+use apollo_rpc_execution::execution_utils::selector_from_name;
+use apollo_starknet_client::reader::objects::transaction::{
+    IntermediateInvokeTransaction as ClientInvokeTransaction,
+    Transaction as ClientTransaction,
+};
+use apollo_storage::base_layer::{BaseLayerStorageWriter, L1ToL2Messages};
+use apollo_storage::body::BodyStorageWriter;
+use apollo_storage::class::ClassStorageWriter;
+use apollo_storage::header::HeaderStorageWriter;
+use apollo_storage::state::StateStorageWriter;
+use apollo_storage::StorageWriter;
+use apollo_test_utils::{get_rng, get_test_block};
+use indexmap::indexmap;
+use itertools::Itertools;
+use jsonrpsee::Methods;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use starknet_api::block::{
+    Block,
+    BlockBody,
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+};
+use starknet_api::core::L1TransactionHash;
+use starknet_api::state::ThinStateDiff;
+use starknet_api::transaction::{
+    InvokeTransactionOutput,
+    RevertedTransactionExecutionStatus,
+    TransactionExecutionStatus,
+    TransactionOutput as StarknetApiTransactionOutput,
+    TransactionVersion,
+};
+use starknet_api::{calldata, class_hash, contract_address, felt, nonce, storage_key, tx_hash};
+use strum::IntoEnumIterator;
+
+use super::super::error::STORAGE_PROOF_NOT_SUPPORTED;
+use super::super::execution::{FunctionInvocationResult, TransactionTrace};
+use super::super::state::{ContractStorageKeys, StorageProof};
+use super::super::transaction::{
+    ExecutionResources,
+    ExecutionStatus,
+    GeneralTransactionReceipt,
+    MessageStatus,
+    PendingTransactionOutput,
+    PreConfirmedTransactionFinalityStatus,
+    PreConfirmedTransactionReceipt,
+    TransactionFinalityStatus,
+    TransactionStatus,
+};
+use super::api_impl::JsonRpcServerImpl;
+use super::{SimulatedTransaction, TransactionTraceWithHash};
+use crate::api::{BlockHashOrNumber, BlockId};
+use crate::test_utils::{
+    call_and_validate_schema_for_result,
+    call_api_then_assert_and_validate_schema_for_err,
+    call_api_then_assert_and_validate_schema_for_result,
+    get_method_names_from_spec,
+    get_method_spec_object,
+    get_test_pending_data,
+    get_test_rpc_server_and_storage_writer,
+    get_test_rpc_server_and_storage_writer_from_params,
+    method_name_to_spec_method_name,
+    SerializeJsonValue,
+    SpecFile,
+};
+use crate::v0_8::api::api_impl::JsonRpcServerImpl as JsonRpcServerV0_8Impl;
+use crate::v0_8::api::test::generate_client_transaction_client_receipt_rpc_transaction_and_rpc_receipt;
+use crate::v0_8::api::SimulationFlag;
+use crate::v0_8::broadcasted_transaction::BroadcastedTransaction;
+use crate::v0_8::error::{BLOCK_NOT_FOUND, TRANSACTION_HASH_NOT_FOUND};
+use crate::v0_8::execution_test::{
+    prepare_storage_for_execution,
+    ACCOUNT_ADDRESS,
+    BLOCK_TIMESTAMP,
+    DEPRECATED_CONTRACT_ADDRESS,
+    GAS_PRICE,
+    MAX_FEE,
+    SEQUENCER_ADDRESS,
+};
+use crate::v0_8::transaction::{
+    InvokeTransaction,
+    InvokeTransactionV1,
+    L1HandlerMsgHash,
+    TransactionOutput as V0_8TransactionOutput,
+    TransactionVersion1,
+};
+use crate::version_config::{VERSION_0_8, VERSION_0_9 as VERSION};
+
+// Appends the block along with an empty state diff, so that it's the latest block.
+fn append_test_block(storage_writer: &mut StorageWriter, block: Block) {
+    let block_number = block.header.block_header_without_hash.block_number;
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(block_number, &block.header)
+        .unwrap()
+        .append_body(block_number, block.body)
+        .unwrap()
+        .append_state_diff(block_number, ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+}
+
+#[tokio::test]
+async fn spec_version() {
+    let (module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+
+    call_api_then_assert_and_validate_schema_for_result(
+        &module,
+        "starknet_V0_9_specVersion",
+        vec![],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &format!("{VERSION}"),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn inherited_methods() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let (v0_8_module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerV0_8Impl>();
+    let block = get_test_block(1, None, None, None);
+    append_test_block(&mut storage_writer, block);
+
+    // Every method of V0_8 is served under the V0_9 name.
+    let v0_8_prefix = format!("starknet_{}_", VERSION_0_8.name);
+    for v0_8_method_name in
+        v0_8_module.method_names().filter(|method_name| method_name.starts_with(&v0_8_prefix))
+    {
+        let method_name = v0_8_method_name.replace(VERSION_0_8.name, VERSION.name);
+        assert!(module.method(&method_name).is_some(), "{method_name} is not served");
+    }
+    call_api_then_assert_and_validate_schema_for_result(
+        &module,
+        "starknet_V0_9_blockNumber",
+        vec![],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &BlockNumber(0),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn get_transaction_status() {
+    let method_name = "starknet_V0_9_getTransactionStatus";
+    let pending_data = get_test_pending_data();
+    let (module, mut storage_writer) = get_test_rpc_server_and_storage_writer_from_params::<
+        JsonRpcServerImpl,
+    >(None, None, Some(pending_data.clone()), None, None);
+    let mut block = get_test_block(2, None, None, None);
+    // Revert the second transaction.
+    let revert_reason = "Reverted".to_string();
+    let reverted_execution_status =
+        TransactionExecutionStatus::Reverted(RevertedTransactionExecutionStatus {
+            revert_reason: revert_reason.clone(),
+        });
+    match &mut block.body.transaction_outputs[1] {
+        StarknetApiTransactionOutput::Declare(output) => {
+            output.execution_status = reverted_execution_status
+        }
+        StarknetApiTransactionOutput::Deploy(output) => {
+            output.execution_status = reverted_execution_status
+        }
+        StarknetApiTransactionOutput::DeployAccount(output) => {
+            output.execution_status = reverted_execution_status
+        }
+        StarknetApiTransactionOutput::Invoke(output) => {
+            output.execution_status = reverted_execution_status
+        }
+        StarknetApiTransactionOutput::L1Handler(output) => {
+            output.execution_status = reverted_execution_status
+        }
+    }
+    append_test_block(&mut storage_writer, block.clone());
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .update_base_layer_block_marker(&BlockNumber(1))
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let status = call_and_validate_schema_for_result::<_, TransactionStatus>(
+        &module,
+        method_name,
+        vec![Box::new(*block.body.transaction_hashes.index(1))],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+    )
+    .await;
+    assert_eq!(
+        status,
+        TransactionStatus {
+            finality_status: TransactionFinalityStatus::AcceptedOnL1,
+            execution_status: ExecutionStatus::Reverted,
+            failure_reason: Some(revert_reason),
+        }
+    );
+
+    // Add a pending transaction and ask for its status.
+    let mut rng = get_rng();
+    let (client_transaction, client_transaction_receipt, _, _) =
+        generate_client_transaction_client_receipt_rpc_transaction_and_rpc_receipt(&mut rng);
+    {
+        let pending_block = &mut pending_data.write().await.block;
+        *pending_block.parent_block_hash_mutable() = block.header.block_hash;
+        pending_block.transactions_mutable().push(client_transaction);
+        pending_block.transaction_receipts_mutable().push(client_transaction_receipt.clone());
+    }
+    let status = call_and_validate_schema_for_result::<_, TransactionStatus>(
+        &module,
+        method_name,
+        vec![Box::new(client_transaction_receipt.transaction_hash)],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+    )
+    .await;
+    assert_eq!(status.finality_status, TransactionFinalityStatus::PreConfirmed);
+}
+
+#[tokio::test]
+async fn get_transaction_receipt() {
+    let method_name = "starknet_V0_9_getTransactionReceipt";
+    let pending_data = get_test_pending_data();
+    let (module, mut storage_writer) = get_test_rpc_server_and_storage_writer_from_params::<
+        JsonRpcServerImpl,
+    >(None, None, Some(pending_data.clone()), None, None);
+    let block = get_test_block(1, None, None, None);
+    append_test_block(&mut storage_writer, block.clone());
+
+    let mut rng = get_rng();
+    let (client_transaction, client_transaction_receipt, _, pending_receipt) =
+        generate_client_transaction_client_receipt_rpc_transaction_and_rpc_receipt(&mut rng);
+    // The output of V0_9 has the resources in gas.
+    let msg_hash = match &client_transaction {
+        ClientTransaction::L1Handler(tx) => Some(tx.calc_msg_hash()),
+        _ => None,
+    };
+    let output = PendingTransactionOutput::try_from(V0_8TransactionOutput::from((
+        client_transaction_receipt
+            .clone()
+            .into_starknet_api_transaction_output(&client_transaction),
+        client_transaction.transaction_version(),
+        msg_hash,
+    )))
+    .unwrap();
+    {
+        let pending_block = &mut pending_data.write().await.block;
+        *pending_block.parent_block_hash_mutable() = block.header.block_hash;
+        pending_block.transactions_mutable().push(client_transaction);
+        pending_block.transaction_receipts_mutable().push(client_transaction_receipt);
+    }
+    let expected_receipt =
+        GeneralTransactionReceipt::PreConfirmedTransactionReceipt(PreConfirmedTransactionReceipt {
+            finality_status: PreConfirmedTransactionFinalityStatus::PreConfirmed,
+            transaction_hash: pending_receipt.transaction_hash,
+            block_number: BlockNumber(1),
+            output,
+        });
+    call_api_then_assert_and_validate_schema_for_result(
+        &module,
+        method_name,
+        vec![Box::new(pending_receipt.transaction_hash)],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &expected_receipt,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn get_block_with_receipts() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let block = get_test_block(2, None, None, None);
+    append_test_block(&mut storage_writer, block.clone());
+
+    let block_with_receipts = call_and_validate_schema_for_result::<_, Value>(
+        &module,
+        "starknet_V0_9_getBlockWithReceipts",
+        vec![Box::new(BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(0))))],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+    )
+    .await;
+    let transactions = block_with_receipts["transactions"].as_array().unwrap();
+    assert_eq!(transactions.len(), block.body.transaction_outputs.len());
+    // The resources of the receipts are the gas the transactions consumed.
+    for (transaction, output) in transactions.iter().zip(block.body.transaction_outputs) {
+        assert_eq!(
+            transaction["receipt"]["execution_resources"],
+            serde_json::to_value(ExecutionResources::from(output.execution_resources().clone()))
+                .unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn get_storage_proof() {
+    let method_name = "starknet_V0_9_getStorageProof";
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let block = get_test_block(1, None, None, None);
+    append_test_block(&mut storage_writer, block);
+
+    let contract_address = contract_address!("0x2");
+    let params = |block_number| -> Vec<Box<dyn SerializeJsonValue>> {
+        vec![
+            Box::new(BlockId::HashOrNumber(BlockHashOrNumber::Number(block_number))),
+            Box::new(vec![class_hash!("0x1")]),
+            Box::new(vec![contract_address]),
+            Box::new(vec![ContractStorageKeys {
+                contract_address,
+                storage_keys: vec![storage_key!("0x3")],
+            }]),
+        ]
+    };
+
+    call_api_then_assert_and_validate_schema_for_err::<_, StorageProof>(
+        &module,
+        method_name,
+        params(BlockNumber(0)),
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &STORAGE_PROOF_NOT_SUPPORTED.into(),
+    )
+    .await;
+    call_api_then_assert_and_validate_schema_for_err::<_, StorageProof>(
+        &module,
+        method_name,
+        params(BlockNumber(1)),
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &BLOCK_NOT_FOUND.into(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn simulate_and_trace_transactions() {
+    let (module, storage_writer) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let mut storage_writer = prepare_storage_for_execution(storage_writer);
+
+    let invoke = InvokeTransactionV1 {
+        max_fee: *MAX_FEE,
+        version: TransactionVersion1::Version1,
+        sender_address: *ACCOUNT_ADDRESS,
+        calldata: calldata![
+            *DEPRECATED_CONTRACT_ADDRESS.0.key(),  // Contract address.
+            selector_from_name("return_result").0, // EP selector.
+            felt!(1_u8),                           // Calldata length.
+            felt!(2_u8)                            // Calldata: num.
+        ],
+        ..Default::default()
+    };
+    let mut simulated_transactions =
+        call_and_validate_schema_for_result::<_, Vec<SimulatedTransaction>>(
+            &module,
+            "starknet_V0_9_simulateTransactions",
+            vec![
+                Box::new(BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(2)))),
+                Box::new(vec![BroadcastedTransaction::Invoke(InvokeTransaction::Version1(
+                    invoke.clone(),
+                ))]),
+                Box::<Vec<SimulationFlag>>::default(),
+            ],
+            &VERSION,
+            SpecFile::TraceApi,
+        )
+        .await;
+    assert_eq!(simulated_transactions.len(), 1);
+    let TransactionTrace::Invoke(simulated_trace) =
+        simulated_transactions.pop().unwrap().transaction_trace
+    else {
+        panic!("Got a non-invoke transaction trace from an invoke transaction.");
+    };
+    assert!(!simulated_trace.validate_invocation.unwrap().is_reverted);
+    let FunctionInvocationResult::Ok(execute_invocation) = simulated_trace.execute_invocation
+    else {
+        panic!("Expected the invoke to succeed.");
+    };
+    assert!(!execute_invocation.is_reverted);
+    assert!(simulated_trace.execution_resources.l1_gas.0 > 0);
+
+    // Add the simulated transaction to a block and trace it.
+    let transaction_hash = tx_hash!(0x1234);
+    let client_transaction = ClientTransaction::Invoke(ClientInvokeTransaction {
+        max_fee: Some(invoke.max_fee),
+        sender_address: invoke.sender_address,
+        calldata: invoke.calldata,
+        nonce: Some(invoke.nonce),
+        version: TransactionVersion::ONE,
+        ..Default::default()
+    });
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(
+            BlockNumber(3),
+            &BlockHeader {
+                block_hash: BlockHash(felt!("0x3")),
+                block_header_without_hash: BlockHeaderWithoutHash {
+                    l1_gas_price: *GAS_PRICE,
+                    sequencer: *SEQUENCER_ADDRESS,
+                    timestamp: *BLOCK_TIMESTAMP,
+                    parent_hash: BlockHash(felt!("0x2")),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .append_body(
+            BlockNumber(3),
+            BlockBody {
+                transactions: vec![client_transaction.try_into().unwrap()],
+                transaction_outputs: vec![StarknetApiTransactionOutput::Invoke(
+                    InvokeTransactionOutput::default(),
+                )],
+                transaction_hashes: vec![transaction_hash],
+            },
+        )
+        .unwrap()
+        .append_state_diff(
+            BlockNumber(3),
+            ThinStateDiff {
+                nonces: indexmap!(*ACCOUNT_ADDRESS => nonce!(1_u128)),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+        .append_classes(BlockNumber(3), &[], &[])
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let trace = call_and_validate_schema_for_result::<_, TransactionTrace>(
+        &module,
+        "starknet_V0_9_traceTransaction",
+        vec![Box::new(transaction_hash)],
+        &VERSION,
+        SpecFile::TraceApi,
+    )
+    .await;
+    let TransactionTrace::Invoke(invoke_trace) = &trace else {
+        panic!("Got a non-invoke transaction trace from an invoke transaction.");
+    };
+    assert!(invoke_trace.execution_resources.l1_gas.0 > 0);
+
+    let traces = call_and_validate_schema_for_result::<_, Vec<TransactionTraceWithHash>>(
+        &module,
+        "starknet_V0_9_traceBlockTransactions",
+        vec![Box::new(BlockId::HashOrNumber(BlockHashOrNumber::Number(BlockNumber(3))))],
+        &VERSION,
+        SpecFile::TraceApi,
+    )
+    .await;
+    assert_eq!(traces, vec![TransactionTraceWithHash { transaction_hash, trace_root: trace }]);
+}
+
+#[tokio::test]
+async fn get_messages_status() {
+    let method_name = "starknet_V0_9_getMessagesStatus";
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let block = get_test_block(1, None, None, None);
+    append_test_block(&mut storage_writer, block.clone());
+
+    // The L1 transaction sent two messages, only the first was executed on L2.
+    let l1_transaction_hash = L1TransactionHash([1; 32]);
+    let executed_tx_hash = block.body.transaction_hashes[0];
+    let not_executed_tx_hash = tx_hash!(0x1234);
+    storage_writer
+        .begin_rw_txn()
+        .unwrap()
+        .index_l1_to_l2_messages(&L1ToL2Messages {
+            l1_blocks: 0..=0,
+            messages: vec![
+                (l1_transaction_hash, executed_tx_hash),
+                (l1_transaction_hash, not_executed_tx_hash),
+            ],
+        })
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let statuses = call_and_validate_schema_for_result::<_, Vec<MessageStatus>>(
+        &module,
+        method_name,
+        vec![Box::new(l1_transaction_hash)],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+    )
+    .await;
+    assert_eq!(statuses.len(), 2);
+    assert_eq!(statuses[0].transaction_hash, executed_tx_hash);
+    assert_eq!(statuses[0].finality_status, TransactionFinalityStatus::AcceptedOnL2);
+    assert!(statuses[0].execution_status.is_some());
+    assert_eq!(statuses[1], MessageStatus::received(not_executed_tx_hash));
+
+    // Ask for an L1 transaction that sent no messages.
+    call_api_then_assert_and_validate_schema_for_err::<_, Vec<MessageStatus>>(
+        &module,
+        method_name,
+        vec![Box::new(L1TransactionHash([2; 32]))],
+        &VERSION,
+        SpecFile::StarknetApiOpenrpc,
+        &TRANSACTION_HASH_NOT_FOUND.into(),
+    )
+    .await;
+}
+
+// Calls every method of the spec that has no parameters and validates its result against the spec.
+#[tokio::test]
+async fn spec_methods_without_params_conform_to_spec() {
+    let (module, mut storage_writer) =
+        get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let block = get_test_block(1, None, None, None);
+    append_test_block(&mut storage_writer, block);
+
+    for spec_file in SpecFile::iter() {
+        let spec_methods: Value = serde_json::from_str(
+            &std::fs::read_to_string(format!("./resources/{}/{spec_file}", VERSION.name)).unwrap(),
+        )
+        .unwrap();
+        for spec_method in spec_methods["methods"].as_array().unwrap() {
+            let spec_method_name = spec_method["name"].as_str().unwrap();
+            let method_name =
+                spec_method_name.replacen("starknet_", &format!("starknet_{}_", VERSION.name), 1);
+            let method_spec = get_method_spec_object(&method_name, &VERSION, spec_file);
+            if !method_spec["params"].as_array().unwrap().is_empty() {
+                continue;
+            }
+            call_and_validate_schema_for_result::<_, Value>(
+                &module,
+                &method_name,
+                vec![],
+                &VERSION,
+                spec_file,
+            )
+            .await;
+        }
+    }
+}
+
+#[test]
+fn spec_api_methods_coverage() {
+    let (module, _) = get_test_rpc_server_and_storage_writer::<JsonRpcServerImpl>();
+    let implemented_methods: Methods = module.into();
+    let implemented_method_names = implemented_methods
+        .method_names()
+        .map(method_name_to_spec_method_name)
+        .sorted()
+        .collect::<Vec<_>>();
+    let non_implemented_apis = ["starknet_pendingTransactions".to_string()];
+    let method_names_in_spec = get_method_names_from_spec(&VERSION)
+        .iter()
+        .map(|method| method.replace('\"', ""))
+        .filter(|method| !non_implemented_apis.contains(method))
+        .sorted()
+        .collect::<Vec<_>>();
+
+    // Methods in the spec are a subset of the implemented methods.
+    assert!(
+        method_names_in_spec.iter().all(|method| implemented_method_names.contains(method)),
+        "Implemented methods: {:#?}, methods in spec: {:#?}",
+        implemented_method_names,
+        method_names_in_spec
+    );
+}
@@ -0,0 +1,7 @@
+use crate::v0_8::error::JsonRpcError;
+
+pub const STORAGE_PROOF_NOT_SUPPORTED: JsonRpcError<String> = JsonRpcError {
+    code: 42,
+    message: "the node doesn't support storage proofs for blocks that are too far in the past",
+    data: None,
+};
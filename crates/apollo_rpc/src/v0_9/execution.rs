@@ -0,0 +1,217 @@
+use apollo_rpc_execution::objects::{
+    CallType,
+    FunctionCall,
+    FunctionInvocation as ExecutionFunctionInvocation,
+    FunctionInvocationResult as ExecutionFunctionInvocationResult,
+    OrderedEvent,
+    OrderedL2ToL1Message,
+    Retdata,
+    RevertReason,
+    TransactionTrace as ExecutionTransactionTrace,
+};
+use serde::{Deserialize, Serialize};
+use starknet_api::contract_class::EntryPointType;
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::execution_resources::{GasAmount, GasVector};
+
+use super::transaction::ExecutionResources;
+use crate::v0_8::state::ThinStateDiff;
+
+// The differences from the traces of V0_8 are the resources, which are given in gas only, and the
+// revert flag of the function invocations.
+/// The execution trace of a transaction.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[serde(tag = "type")]
+pub enum TransactionTrace {
+    #[serde(rename = "L1_HANDLER")]
+    L1Handler(L1HandlerTransactionTrace),
+    #[serde(rename = "INVOKE")]
+    Invoke(InvokeTransactionTrace),
+    #[serde(rename = "DECLARE")]
+    Declare(DeclareTransactionTrace),
+    #[serde(rename = "DEPLOY_ACCOUNT")]
+    DeployAccount(DeployAccountTransactionTrace),
+}
+
+/// The execution trace of an L1Handler transaction.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct L1HandlerTransactionTrace {
+    /// The trace of the funcion call.
+    pub function_invocation: FunctionInvocation,
+    /// The state diff induced by this transaction.
+    pub state_diff: ThinStateDiff,
+    /// The total execution resources of this transaction.
+    pub execution_resources: ExecutionResources,
+}
+
+/// The execution trace of an Invoke transaction.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct InvokeTransactionTrace {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The trace of the __validate__ call.
+    pub validate_invocation: Option<FunctionInvocation>,
+    /// The trace of the __execute__ call or the reason in case of reverted transaction.
+    pub execute_invocation: FunctionInvocationResult,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    /// The trace of the __fee_transfer__ call.
+    pub fee_transfer_invocation: Option<FunctionInvocation>,
+    /// The state diff induced by this transaction.
+    pub state_diff: ThinStateDiff,
+    /// The total execution resources of this transaction.
+    pub execution_resources: ExecutionResources,
+}
+
+/// The execution trace of a Declare transaction.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct DeclareTransactionTrace {
+    /// The trace of the __validate__ call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<FunctionInvocation>,
+    /// The trace of the __fee_transfer__ call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<FunctionInvocation>,
+    /// The state diff induced by this transaction.
+    pub state_diff: ThinStateDiff,
+    /// The total execution resources of this transaction.
+    pub execution_resources: ExecutionResources,
+}
+
+/// The execution trace of a DeployAccount transaction.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct DeployAccountTransactionTrace {
+    /// The trace of the __validate__ call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<FunctionInvocation>,
+    /// The trace of the __constructor__ call.
+    pub constructor_invocation: FunctionInvocation,
+    /// The trace of the __fee_transfer__ call.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<FunctionInvocation>,
+    /// The state diff induced by this transaction.
+    pub state_diff: ThinStateDiff,
+    /// The total execution resources of this transaction.
+    pub execution_resources: ExecutionResources,
+}
+
+/// Wether the function invocation succeeded or reverted.
+// Not using `Result` because it is not being serialized according to the spec.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+#[allow(missing_docs)]
+#[serde(untagged)]
+pub enum FunctionInvocationResult {
+    Ok(FunctionInvocation),
+    Err(RevertReason),
+}
+
+/// The execution trace of a function call.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct FunctionInvocation {
+    #[serde(flatten)]
+    /// The details of the function call.
+    pub function_call: FunctionCall,
+    /// The address of the invoking contract. 0 for the root invocation.
+    pub caller_address: ContractAddress,
+    /// The hash of the class being called.
+    pub class_hash: ClassHash,
+    /// The type of the entry point being called.
+    pub entry_point_type: EntryPointType,
+    /// library call or regular call.
+    pub call_type: CallType,
+    /// The value returned from the function invocation.
+    pub result: Retdata,
+    /// The calls made by this invocation.
+    pub calls: Vec<Self>,
+    /// The events emitted in this invocation.
+    pub events: Vec<OrderedEvent>,
+    /// The messages sent by this invocation to L1.
+    pub messages: Vec<OrderedL2ToL1Message>,
+    /// The gas consumed by this invocation, including the calls it made.
+    pub execution_resources: InnerCallExecutionResources,
+    /// Whether this invocation reverted.
+    pub is_reverted: bool,
+}
+
+/// The resources consumed by a function call. The data availability gas isn't attributed to the
+/// calls, since the state diff is squashed across the transaction.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct InnerCallExecutionResources {
+    pub l1_gas: GasAmount,
+    pub l2_gas: GasAmount,
+}
+
+impl From<(ExecutionTransactionTrace, ThinStateDiff, GasVector)> for TransactionTrace {
+    fn from(
+        (trace, mut state_diff, gas_consumed): (
+            ExecutionTransactionTrace,
+            ThinStateDiff,
+            GasVector,
+        ),
+    ) -> Self {
+        // TODO(Shahak): Investigate why blockifier sometimes returns unsorted state diff
+        state_diff.sort();
+        let execution_resources = ExecutionResources::from(gas_consumed);
+        match trace {
+            ExecutionTransactionTrace::L1Handler(trace) => {
+                Self::L1Handler(L1HandlerTransactionTrace {
+                    function_invocation: trace.function_invocation.into(),
+                    state_diff,
+                    execution_resources,
+                })
+            }
+            ExecutionTransactionTrace::Invoke(trace) => Self::Invoke(InvokeTransactionTrace {
+                validate_invocation: trace.validate_invocation.map(Into::into),
+                execute_invocation: trace.execute_invocation.into(),
+                fee_transfer_invocation: trace.fee_transfer_invocation.map(Into::into),
+                state_diff,
+                execution_resources,
+            }),
+            ExecutionTransactionTrace::Declare(trace) => Self::Declare(DeclareTransactionTrace {
+                validate_invocation: trace.validate_invocation.map(Into::into),
+                fee_transfer_invocation: trace.fee_transfer_invocation.map(Into::into),
+                state_diff,
+                execution_resources,
+            }),
+            ExecutionTransactionTrace::DeployAccount(trace) => {
+                Self::DeployAccount(DeployAccountTransactionTrace {
+                    validate_invocation: trace.validate_invocation.map(Into::into),
+                    constructor_invocation: trace.constructor_invocation.into(),
+                    fee_transfer_invocation: trace.fee_transfer_invocation.map(Into::into),
+                    state_diff,
+                    execution_resources,
+                })
+            }
+        }
+    }
+}
+
+impl From<ExecutionFunctionInvocationResult> for FunctionInvocationResult {
+    fn from(result: ExecutionFunctionInvocationResult) -> Self {
+        match result {
+            ExecutionFunctionInvocationResult::Ok(invocation) => Self::Ok(invocation.into()),
+            ExecutionFunctionInvocationResult::Err(revert_reason) => Self::Err(revert_reason),
+        }
+    }
+}
+
+impl From<ExecutionFunctionInvocation> for FunctionInvocation {
+    fn from(invocation: ExecutionFunctionInvocation) -> Self {
+        let gas_consumed = invocation.execution_resources.gas_consumed;
+        Self {
+            function_call: invocation.function_call,
+            caller_address: invocation.caller_address,
+            class_hash: invocation.class_hash,
+            entry_point_type: invocation.entry_point_type,
+            call_type: invocation.call_type,
+            result: invocation.result,
+            calls: invocation.calls.into_iter().map(Into::into).collect(),
+            events: invocation.events,
+            messages: invocation.messages,
+            execution_resources: InnerCallExecutionResources {
+                l1_gas: gas_consumed.l1_gas,
+                l2_gas: gas_consumed.l2_gas,
+            },
+            is_reverted: invocation.is_reverted,
+        }
+    }
+}
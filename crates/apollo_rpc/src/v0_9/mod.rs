@@ -0,0 +1,5 @@
+pub mod api;
+pub mod error;
+pub mod execution;
+pub mod state;
+pub mod transaction;
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockHash;
+use starknet_api::core::{ClassHash, ContractAddress, Nonce};
+use starknet_api::state::StorageKey;
+use starknet_types_core::felt::Felt;
+
+/// A contract and the keys of its storage whose proofs are requested.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ContractStorageKeys {
+    pub contract_address: ContractAddress,
+    pub storage_keys: Vec<StorageKey>,
+}
+
+/// The Merkle paths from the roots of the state tries to the requested leaves.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct StorageProof {
+    pub classes_proof: Vec<NodeHashToNode>,
+    pub contracts_proof: ContractsProof,
+    pub contracts_storage_proofs: Vec<Vec<NodeHashToNode>>,
+    pub global_roots: GlobalRoots,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct NodeHashToNode {
+    pub node_hash: Felt,
+    pub node: MerkleNode,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum MerkleNode {
+    Binary(BinaryNode),
+    Edge(EdgeNode),
+}
+
+/// An internal node whose children are both non-zero.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct BinaryNode {
+    pub left: Felt,
+    pub right: Felt,
+}
+
+/// A path from a node to its highest non-zero descendant.
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct EdgeNode {
+    pub path: Felt,
+    pub length: u8,
+    pub child: Felt,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ContractsProof {
+    pub nodes: Vec<NodeHashToNode>,
+    /// The data of the leaves of the requested contracts, in the order they were requested.
+    pub contract_leaves_data: Vec<ContractLeafData>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ContractLeafData {
+    pub nonce: Nonce,
+    pub class_hash: ClassHash,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub storage_root: Option<Felt>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct GlobalRoots {
+    pub contracts_tree_root: Felt,
+    pub classes_tree_root: Felt,
+    pub block_hash: BlockHash,
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+use starknet_api::block::BlockNumber;
+use starknet_api::execution_resources::{
+    ExecutionResources as StarknetApiExecutionResources,
+    GasAmount,
+    GasVector,
+};
+use starknet_api::transaction::{TransactionExecutionStatus, TransactionHash};
+
+use crate::v0_8::transaction::{
+    PendingTransactionOutput as V0_8PendingTransactionOutput,
+    TransactionFinalityStatus as AcceptedTransactionFinalityStatus,
+    TransactionReceipt as V0_8TransactionReceipt,
+    TransactionWithReceipt as V0_8TransactionWithReceipt,
+};
+
+pub type TransactionReceipt = V0_8TransactionReceipt<ExecutionResources>;
+pub type PendingTransactionOutput = V0_8PendingTransactionOutput<ExecutionResources>;
+pub type TransactionWithReceipt = V0_8TransactionWithReceipt<ExecutionResources>;
+
+/// The resources consumed by a transaction. Unlike V0_8, they're given in gas only.
+#[derive(Debug, Default, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct ExecutionResources {
+    pub l1_gas: GasAmount,
+    pub l1_data_gas: GasAmount,
+    pub l2_gas: GasAmount,
+}
+
+impl From<GasVector> for ExecutionResources {
+    fn from(gas_vector: GasVector) -> Self {
+        Self {
+            l1_gas: gas_vector.l1_gas,
+            l1_data_gas: gas_vector.l1_data_gas,
+            l2_gas: gas_vector.l2_gas,
+        }
+    }
+}
+
+impl From<StarknetApiExecutionResources> for ExecutionResources {
+    fn from(execution_resources: StarknetApiExecutionResources) -> Self {
+        execution_resources.gas_consumed.into()
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct TransactionStatus {
+    pub finality_status: TransactionFinalityStatus,
+    pub execution_status: ExecutionStatus,
+    /// The revert reason of a reverted transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub failure_reason: Option<String>,
+}
+
+impl TransactionStatus {
+    fn new(
+        finality_status: TransactionFinalityStatus,
+        execution_status: &TransactionExecutionStatus,
+    ) -> Self {
+        match execution_status {
+            TransactionExecutionStatus::Succeeded => Self {
+                finality_status,
+                execution_status: ExecutionStatus::Succeeded,
+                failure_reason: None,
+            },
+            TransactionExecutionStatus::Reverted(reverted_status) => Self {
+                finality_status,
+                execution_status: ExecutionStatus::Reverted,
+                failure_reason: Some(reverted_status.revert_reason.clone()),
+            },
+        }
+    }
+}
+
+/// Transaction Finality status on starknet.
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord, Default,
+)]
+pub enum TransactionFinalityStatus {
+    /// The transaction is known but wasn't included in a block yet.
+    #[serde(rename = "RECEIVED")]
+    Received,
+    /// The transaction is in the block that is currently built.
+    #[serde(rename = "PRE_CONFIRMED")]
+    PreConfirmed,
+    /// The transaction passed the validation and entered an actual created block.
+    #[serde(rename = "ACCEPTED_ON_L2")]
+    #[default]
+    AcceptedOnL2,
+    /// The transaction was accepted on-chain.
+    #[serde(rename = "ACCEPTED_ON_L1")]
+    AcceptedOnL1,
+}
+
+impl From<AcceptedTransactionFinalityStatus> for TransactionFinalityStatus {
+    fn from(status: AcceptedTransactionFinalityStatus) -> Self {
+        match status {
+            AcceptedTransactionFinalityStatus::AcceptedOnL2 => {
+                TransactionFinalityStatus::AcceptedOnL2
+            }
+            AcceptedTransactionFinalityStatus::AcceptedOnL1 => {
+                TransactionFinalityStatus::AcceptedOnL1
+            }
+        }
+    }
+}
+
+/// The status of the L1 handler transaction of a message sent from L1 to L2.
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct MessageStatus {
+    pub transaction_hash: TransactionHash,
+    pub finality_status: TransactionFinalityStatus,
+    /// The execution status, only present once the transaction was executed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub execution_status: Option<ExecutionStatus>,
+    /// The revert reason of a reverted transaction.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub failure_reason: Option<String>,
+}
+
+impl MessageStatus {
+    pub fn received(transaction_hash: TransactionHash) -> Self {
+        Self {
+            transaction_hash,
+            finality_status: TransactionFinalityStatus::Received,
+            execution_status: None,
+            failure_reason: None,
+        }
+    }
+
+    pub fn new(transaction_hash: TransactionHash, status: TransactionStatus) -> Self {
+        Self {
+            transaction_hash,
+            finality_status: status.finality_status,
+            execution_status: Some(status.execution_status),
+            failure_reason: status.failure_reason,
+        }
+    }
+}
+
+/// Transaction execution status on starknet, without the revert reason.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord)]
+pub enum ExecutionStatus {
+    #[serde(rename = "SUCCEEDED")]
+    Succeeded,
+    #[serde(rename = "REVERTED")]
+    Reverted,
+}
+
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
+#[serde(untagged)]
+pub enum GeneralTransactionReceipt {
+    TransactionReceipt(TransactionReceipt),
+    PreConfirmedTransactionReceipt(PreConfirmedTransactionReceipt),
+}
+
+impl GeneralTransactionReceipt {
+    pub fn transaction_status(&self) -> TransactionStatus {
+        match self {
+            GeneralTransactionReceipt::TransactionReceipt(receipt) => TransactionStatus::new(
+                receipt.finality_status.into(),
+                receipt.output.execution_status(),
+            ),
+            GeneralTransactionReceipt::PreConfirmedTransactionReceipt(receipt) => {
+                TransactionStatus::new(
+                    TransactionFinalityStatus::PreConfirmed,
+                    receipt.output.execution_status(),
+                )
+            }
+        }
+    }
+}
+
+/// The receipt of a transaction in the block that is currently built. Unlike the receipts of the
+/// pending block in V0_8, it has the number of the block the transaction will be in.
+#[derive(Debug, Clone, Eq, Hash, PartialEq, Deserialize, Serialize, PartialOrd, Ord)]
+pub struct PreConfirmedTransactionReceipt {
+    pub finality_status: PreConfirmedTransactionFinalityStatus,
+    pub transaction_hash: TransactionHash,
+    pub block_number: BlockNumber,
+    #[serde(flatten)]
+    pub output: PendingTransactionOutput,
+}
+
+/// Transaction Finality status on starknet for transactions in the block that is currently built.
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord, Default,
+)]
+pub enum PreConfirmedTransactionFinalityStatus {
+    #[serde(rename = "PRE_CONFIRMED")]
+    #[default]
+    PreConfirmed,
+}
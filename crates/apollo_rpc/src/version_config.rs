@@ -8,10 +8,13 @@ pub const VERSION_PATTERN: &str = "[Vv][0-9]+_[0-9]+(_[0-9]+)?";
 /// Labels the jsonRPC versions we have such that there can be multiple versions that are supported,
 /// and there can be multiple versions that are deprecated.
 /// Supported -> method exposed via the http path "/version_id" (e.g. http://host:port/V0_3_0)
+/// Experimental -> method exposed like a supported one, but the version may still change and is
+/// never the default version.
 /// Deprecated -> method not exposed.
 #[derive(Clone, Copy, Debug)]
 pub enum VersionState {
     Supported,
+    Experimental,
     #[allow(dead_code)]
     Deprecated,
 }
@@ -32,6 +35,12 @@ impl std::fmt::Display for VersionId {
     }
 }
 
-/// latest version must be set as supported
-pub const VERSION_CONFIG: &[(VersionId, VersionState)] = &[(VERSION_0_8, VersionState::Supported)];
+/// The default version must be set as supported
+pub const VERSION_CONFIG: &[(VersionId, VersionState)] =
+    &[(VERSION_0_8, VersionState::Supported), (VERSION_0_9, VersionState::Experimental)];
 pub const VERSION_0_8: VersionId = VersionId { name: "V0_8", patch: 0 };
+pub const VERSION_0_9: VersionId = VersionId { name: "V0_9", patch: 0 };
+/// The version served on the path without a version. Its methods are also served under their names
+/// without the version, which the requests over WebSocket call, so it must be the version whose API
+/// trait is given the `unversioned_aliases` flag of `versioned_rpc`.
+pub const DEFAULT_VERSION: VersionId = VERSION_0_8;
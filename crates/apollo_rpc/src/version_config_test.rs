@@ -2,12 +2,15 @@ use std::collections::HashMap;
 
 use pretty_assertions::assert_eq;
 
-use super::{VersionState, VERSION_CONFIG};
+use super::{VersionState, DEFAULT_VERSION, VERSION_CONFIG};
 
 #[tokio::test]
 async fn validate_version_configuration() {
-    let mut config_type_counter =
-        HashMap::from([(&VersionState::Supported, 0), (&VersionState::Deprecated, 0)]);
+    let mut config_type_counter = HashMap::from([
+        (&VersionState::Supported, 0),
+        (&VersionState::Experimental, 0),
+        (&VersionState::Deprecated, 0),
+    ]);
     let mut config_version_counter = HashMap::new();
     VERSION_CONFIG.iter().for_each(|config| {
         let (version_id, version_state) = config;
@@ -17,3 +20,10 @@ async fn validate_version_configuration() {
     // verify each version is listed once
     config_version_counter.iter().for_each(|version_counter| assert_eq!(*version_counter.1, 1))
 }
+
+#[test]
+fn default_version_is_supported() {
+    assert!(VERSION_CONFIG.iter().any(|(version, version_state)| {
+        *version == DEFAULT_VERSION && matches!(version_state, VersionState::Supported)
+    }));
+}
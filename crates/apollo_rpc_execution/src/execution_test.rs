@@ -9,24 +9,30 @@ use blockifier::execution::call_info::Retdata;
 use blockifier::execution::errors::ConstructorEntryPointExecutionError;
 use blockifier::execution::stack_trace::gen_tx_execution_error_trace;
 use blockifier::transaction::errors::TransactionExecutionError as BlockifierTransactionExecutionError;
+use blockifier::transaction::objects::TransactionExecutionInfo;
 use indexmap::indexmap;
 use pretty_assertions::assert_eq;
-use starknet_api::abi::abi_utils::get_storage_var_address;
-use starknet_api::block::{BlockNumber, StarknetVersion};
+use starknet_api::abi::abi_utils::{get_fee_token_var_address, get_storage_var_address};
+use starknet_api::block::{BlockNumber, GasPrice, StarknetVersion};
 use starknet_api::core::{ChainId, CompiledClassHash, EntryPointSelector};
-use starknet_api::state::{StateNumber, ThinStateDiff};
+use starknet_api::execution_resources::GasAmount;
+use starknet_api::state::{StateNumber, StorageKey, ThinStateDiff};
 use starknet_api::transaction::fields::{Calldata, Fee};
 use starknet_api::{calldata, class_hash, contract_address, felt, nonce};
 use starknet_types_core::felt::Felt;
 
 use crate::execution_utils::selector_from_name;
 use crate::objects::{
+    CallTraceOutput,
+    ContractStateOverride,
     DeclareTransactionTrace,
     DeployAccountTransactionTrace,
     FeeEstimation,
     FunctionInvocationResult,
     InvokeTransactionTrace,
     PriceUnit,
+    ResourceBoundsEstimation,
+    StateOverrides,
     TransactionSimulationOutput,
     TransactionTrace,
 };
@@ -48,9 +54,16 @@ use crate::test_utils::{
 use crate::testing_instances::get_test_execution_config;
 use crate::{
     estimate_fee,
+    estimate_resource_bounds,
     execute_call,
+    execute_transactions,
+    resolve_balance_overrides,
+    search_minimal_l2_gas_bound,
+    trace_call,
+    trace_transactions,
     ExecutableTransactionInput,
     ExecutionError,
+    ExecutionResult,
     FeeEstimationResult,
     RevertedTransaction,
 };
@@ -68,6 +81,7 @@ fn execute_call_cairo0() {
     let retdata = execute_call(
         storage_reader.clone(),
         None,
+        StateOverrides::default(),
         &chain_id,
         StateNumber::unchecked_right_after_block(BlockNumber(0)),
         BlockNumber(0),
@@ -86,6 +100,7 @@ fn execute_call_cairo0() {
     let retdata = execute_call(
         storage_reader.clone(),
         None,
+        StateOverrides::default(),
         &chain_id,
         StateNumber::unchecked_right_after_block(BlockNumber(0)),
         BlockNumber(0),
@@ -104,6 +119,7 @@ fn execute_call_cairo0() {
     let retdata = execute_call(
         storage_reader.clone(),
         None,
+        StateOverrides::default(),
         &chain_id,
         StateNumber::unchecked_right_after_block(BlockNumber(0)),
         BlockNumber(0),
@@ -122,6 +138,7 @@ fn execute_call_cairo0() {
     let retdata = execute_call(
         storage_reader,
         None,
+        StateOverrides::default(),
         &chain_id,
         StateNumber::unchecked_right_after_block(BlockNumber(0)),
         BlockNumber(0),
@@ -151,6 +168,7 @@ fn execute_call_cairo1() {
     let retdata = execute_call(
         storage_reader,
         None,
+        StateOverrides::default(),
         &CHAIN_ID,
         StateNumber::unchecked_right_after_block(BlockNumber(0)),
         BlockNumber(0),
@@ -167,6 +185,102 @@ fn execute_call_cairo1() {
     assert_eq!(retdata, Retdata(vec![value]));
 }
 
+#[test]
+fn trace_call_cairo1() {
+    let ((storage_reader, storage_writer), _temp_dir) = get_test_storage();
+    prepare_storage(storage_writer);
+
+    let key = felt!(1234_u16);
+    let value = felt!(18_u8);
+    let storage_key = StorageKey::try_from(key).unwrap();
+
+    let CallTraceOutput { call, storage_diffs } = trace_call(
+        storage_reader,
+        None,
+        StateOverrides::default(),
+        &CHAIN_ID,
+        StateNumber::unchecked_right_after_block(BlockNumber(0)),
+        BlockNumber(0),
+        &CONTRACT_ADDRESS,
+        selector_from_name("test_storage_read_write"),
+        calldata![key, value],
+        &get_test_execution_config(),
+        true,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(call.function_call.contract_address, *CONTRACT_ADDRESS);
+    assert_eq!(call.class_hash, class_hash!("0x2"));
+    assert_eq!(call.result.0, vec![value]);
+    assert!(!call.failed);
+    assert_eq!(call.storage_read_values, vec![value]);
+    assert_eq!(call.accessed_storage_keys, vec![storage_key]);
+    assert!(call.calls.is_empty());
+    assert_eq!(storage_diffs, indexmap!(*CONTRACT_ADDRESS => indexmap!(storage_key => value)));
+}
+
+#[test]
+fn execute_call_with_state_overrides() {
+    let ((storage_reader, storage_writer), _temp_dir) = get_test_storage();
+    prepare_storage(storage_writer);
+
+    let undeployed_address = contract_address!("0x3");
+    let key = felt!(1234_u16);
+    let value = felt!(18_u8);
+    let call = |state_overrides| {
+        execute_call(
+            storage_reader.clone(),
+            None,
+            state_overrides,
+            &CHAIN_ID,
+            StateNumber::unchecked_right_after_block(BlockNumber(0)),
+            BlockNumber(0),
+            &undeployed_address,
+            selector_from_name("test_storage_read_write"),
+            calldata![key, value],
+            &get_test_execution_config(),
+            true,
+            None,
+        )
+    };
+
+    assert_matches!(
+        call(StateOverrides::default()),
+        Err(ExecutionError::ContractNotFound { contract_address, .. })
+        if contract_address == undeployed_address
+    );
+
+    // Overriding the class hash deploys the contract at the address.
+    let state_overrides = indexmap!(
+        undeployed_address => ContractStateOverride {
+            class_hash: Some(class_hash!("0x2")),
+            ..Default::default()
+        },
+    );
+    assert_eq!(call(state_overrides).unwrap().retdata, Retdata(vec![value]));
+}
+
+#[test]
+fn balance_overrides_are_resolved_into_fee_token_storage() {
+    let execution_config = get_test_execution_config();
+    let address = contract_address!("0x3");
+    // The balance is 2^128 + 5.
+    let balance = Felt::from(u128::MAX) + felt!(6_u8);
+    let state_overrides = indexmap!(
+        address => ContractStateOverride { balance: Some(balance), ..Default::default() },
+    );
+
+    let resolved = resolve_balance_overrides(state_overrides, &execution_config);
+
+    let low_key = get_fee_token_var_address(address);
+    let high_key = low_key.next_storage_key().unwrap();
+    let expected_storage = indexmap!(low_key => felt!(5_u8), high_key => felt!(1_u8));
+    assert_eq!(resolved[&address], ContractStateOverride::default());
+    assert_eq!(resolved[&execution_config.strk_fee_contract_address].storage, expected_storage);
+    assert_eq!(resolved[&execution_config.eth_fee_contract_address].storage, expected_storage);
+}
+
 // TODO(yair): Compare to the expected fee instead of asserting that it is not zero (all
 // estimate_fee tests).
 #[test]
@@ -251,6 +365,7 @@ fn estimate_fees(txs: Vec<ExecutableTransactionInput>) -> FeeEstimationResult {
         &CHAIN_ID,
         storage_reader,
         None,
+        StateOverrides::default(),
         StateNumber::unchecked_right_after_block(BlockNumber(0)),
         BlockNumber(1),
         &get_test_execution_config(),
@@ -262,6 +377,241 @@ fn estimate_fees(txs: Vec<ExecutableTransactionInput>) -> FeeEstimationResult {
     .unwrap()
 }
 
+#[test]
+fn estimate_resource_bounds_invoke() {
+    let txs = TxsScenarioBuilder::default()
+        .invoke(*ACCOUNT_ADDRESS, *DEPRECATED_CONTRACT_ADDRESS)
+        .invoke(*ACCOUNT_ADDRESS, *DEPRECATED_CONTRACT_ADDRESS)
+        .collect();
+    let estimations = estimate_resource_bounds_of(txs, false).expect("Estimation should succeed.");
+
+    assert_eq!(estimations.len(), 2);
+    for estimation in estimations {
+        let ResourceBoundsEstimation { resource_bounds, fee_estimation, revert_reason } =
+            estimation;
+        assert_eq!(revert_reason, None);
+        assert_eq!(fee_estimation.unit, PriceUnit::Fri);
+        assert_ne!(resource_bounds.l2_gas.max_amount, GasAmount(0));
+        assert_eq!(Felt::from(resource_bounds.l1_gas.max_amount.0), fee_estimation.gas_consumed);
+        // The prices are suggested with the configured headroom of 50%.
+        let with_headroom = |price: GasPrice| GasPrice(price.0 * 3 / 2);
+        assert_eq!(
+            resource_bounds.l1_gas.max_price_per_unit,
+            with_headroom(fee_estimation.l1_gas_price)
+        );
+        assert_eq!(
+            resource_bounds.l2_gas.max_price_per_unit,
+            with_headroom(fee_estimation.l2_gas_price)
+        );
+        assert_eq!(
+            resource_bounds.l1_data_gas.max_price_per_unit,
+            with_headroom(fee_estimation.l1_data_gas_price)
+        );
+    }
+}
+
+#[test]
+fn estimate_resource_bounds_sierra_gas() {
+    const DEPTH: u64 = 1000;
+    let txs = TxsScenarioBuilder::default()
+        .invoke_recursion(DEPTH)
+        .invoke_recursion(10 * DEPTH)
+        .collect();
+    let estimations =
+        estimate_resource_bounds_of(txs.clone(), true).expect("Estimation should succeed.");
+    assert!(estimations.iter().all(|estimation| estimation.revert_reason.is_none()));
+    let l2_gas_bounds = estimations
+        .iter()
+        .map(|estimation| estimation.resource_bounds.l2_gas.max_amount)
+        .collect::<Vec<_>>();
+    // The bound follows the gas the transaction consumes instead of the highest possible bound.
+    assert!(l2_gas_bounds[0] < l2_gas_bounds[1]);
+
+    let tx = txs[0].clone();
+    let l2_gas_bound = l2_gas_bounds[0];
+    // The transaction succeeds under the suggested bound, which covers the L2 gas it consumes.
+    let execution_info = execute_under_l2_gas_bound(tx.clone(), l2_gas_bound, true).unwrap();
+    assert!(!execution_info.is_reverted());
+    assert!(execution_info.receipt.gas.l2_gas <= l2_gas_bound);
+    // The search converged, so the transaction fails under a bound lower by 1/64.
+    let lower_l2_gas_bound = GasAmount(l2_gas_bound.0 - l2_gas_bound.0 / 64);
+    match execute_under_l2_gas_bound(tx, lower_l2_gas_bound, true) {
+        Ok(execution_info) => assert!(
+            execution_info.is_reverted() || execution_info.receipt.gas.l2_gas > lower_l2_gas_bound
+        ),
+        Err(err) => assert_matches!(err, ExecutionError::TransactionExecutionError { .. }),
+    }
+}
+
+#[test]
+fn estimate_resource_bounds_out_of_gas() {
+    let tx = TxsScenarioBuilder::default().invoke_recursion(10000).collect().remove(0);
+    let l2_gas_bound = estimate_resource_bounds_of(vec![tx.clone()], true).unwrap()[0]
+        .resource_bounds
+        .l2_gas
+        .max_amount;
+
+    // Running out of gas in the execution reverts the transaction. Without the validation, the
+    // transaction consumes about half of the estimated gas, so the bound is below that.
+    let execution_info =
+        execute_under_l2_gas_bound(tx.clone(), GasAmount(l2_gas_bound.0 / 4), false).unwrap();
+    // The recursion runs out of gas in an inner call, whose failure the account unwraps.
+    assert!(execution_info.is_reverted());
+
+    // Running out of gas in the validation, which recurses as deep as the execution, fails the
+    // transaction.
+    assert_matches!(
+        execute_under_l2_gas_bound(tx, GasAmount(l2_gas_bound.0 / 4), true),
+        Err(ExecutionError::TransactionExecutionError { transaction_index: 0, .. })
+    );
+}
+
+#[test]
+fn search_minimal_l2_gas_bound_with_non_monotonic_gas_usage() {
+    // The transaction succeeds under the bounds in a range it doesn't succeed right above, as if
+    // a lower bound made it take a cheaper path.
+    let succeeds =
+        |l2_gas_bound: GasAmount| (1000..2000).contains(&l2_gas_bound.0) || l2_gas_bound.0 >= 5000;
+    let mut tried_l2_gas_bounds = vec![];
+    let l2_gas_bound =
+        search_minimal_l2_gas_bound(GasAmount(500), GasAmount(1_000_000), |l2_gas_bound| {
+            tried_l2_gas_bounds.push(l2_gas_bound);
+            Ok(succeeds(l2_gas_bound))
+        })
+        .unwrap();
+
+    // The suggested bound is one the transaction succeeded under, and a bound lower by at most
+    // 1/64 of it failed.
+    assert!(tried_l2_gas_bounds.contains(&l2_gas_bound));
+    assert!(succeeds(l2_gas_bound));
+    assert!(tried_l2_gas_bounds.iter().any(|tried_l2_gas_bound| {
+        !succeeds(*tried_l2_gas_bound)
+            && l2_gas_bound.0 - tried_l2_gas_bound.0 <= l2_gas_bound.0 / 64
+    }));
+}
+
+#[test]
+fn estimate_resource_bounds_reverted() {
+    let non_existing_contract = contract_address!("0x987");
+    let txs =
+        TxsScenarioBuilder::default().invoke(*ACCOUNT_ADDRESS, non_existing_contract).collect();
+    let estimations = estimate_resource_bounds_of(txs, false).expect("Estimation should succeed.");
+
+    assert_eq!(estimations.len(), 1);
+    assert!(estimations[0].revert_reason.is_some());
+}
+
+#[test]
+fn estimate_resource_bounds_of_deprecated_transaction() {
+    let txs = TxsScenarioBuilder::default()
+        .invoke(*ACCOUNT_ADDRESS, *DEPRECATED_CONTRACT_ADDRESS)
+        .invoke_deprecated(*ACCOUNT_ADDRESS, *DEPRECATED_CONTRACT_ADDRESS, None, false)
+        .collect();
+
+    assert_matches!(
+        estimate_resource_bounds_of(txs, false),
+        Err(ExecutionError::ResourceBoundsNotSupported { transaction_index: 1 })
+    );
+}
+
+#[allow(clippy::result_large_err)]
+fn estimate_resource_bounds_of(
+    txs: Vec<ExecutableTransactionInput>,
+    validate: bool,
+) -> ExecutionResult<Vec<ResourceBoundsEstimation>> {
+    let ((storage_reader, storage_writer), _temp_dir) = get_test_storage();
+    prepare_storage(storage_writer);
+
+    estimate_resource_bounds(
+        txs,
+        &CHAIN_ID,
+        storage_reader,
+        None,
+        StateOverrides::default(),
+        StateNumber::unchecked_right_after_block(BlockNumber(0)),
+        BlockNumber(1),
+        &get_test_execution_config(),
+        validate,
+        true,
+        None,
+    )
+}
+
+#[allow(clippy::result_large_err)]
+fn execute_under_l2_gas_bound(
+    tx: ExecutableTransactionInput,
+    l2_gas_bound: GasAmount,
+    validate: bool,
+) -> ExecutionResult<TransactionExecutionInfo> {
+    let ((storage_reader, storage_writer), _temp_dir) = get_test_storage();
+    prepare_storage(storage_writer);
+
+    let (mut execution_outputs, _block_context) = execute_transactions(
+        vec![tx.with_l2_gas_bound(l2_gas_bound)],
+        None,
+        &CHAIN_ID,
+        storage_reader,
+        None,
+        StateOverrides::default(),
+        StateNumber::unchecked_right_after_block(BlockNumber(0)),
+        BlockNumber(1),
+        &get_test_execution_config(),
+        false,
+        validate,
+        true,
+        None,
+    )?;
+    Ok(execution_outputs.remove(0).execution_info)
+}
+
+#[test]
+fn trace_invoke() {
+    let ((storage_reader, storage_writer), _temp_dir) = get_test_storage();
+    prepare_storage(storage_writer);
+
+    let txs = TxsScenarioBuilder::default()
+        .invoke_deprecated(*ACCOUNT_ADDRESS, *DEPRECATED_CONTRACT_ADDRESS, None, false)
+        .collect();
+    let traces = trace_transactions(
+        txs,
+        None,
+        &CHAIN_ID,
+        storage_reader,
+        None,
+        StateOverrides::default(),
+        StateNumber::unchecked_right_after_block(BlockNumber(0)),
+        BlockNumber(1),
+        &get_test_execution_config(),
+        true,
+        true,
+        true,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(traces.len(), 1);
+    let trace = &traces[0];
+    assert_eq!(
+        trace.validate_invocation.as_ref().unwrap().function_call.contract_address,
+        *ACCOUNT_ADDRESS
+    );
+    let execute_invocation = trace.execute_invocation.as_ref().unwrap();
+    assert_eq!(execute_invocation.function_call.contract_address, *ACCOUNT_ADDRESS);
+    assert_eq!(
+        execute_invocation.calls[0].function_call.contract_address,
+        *DEPRECATED_CONTRACT_ADDRESS
+    );
+    let fee_transfer_invocation = trace.fee_transfer_invocation.as_ref().unwrap();
+    assert_eq!(
+        fee_transfer_invocation.function_call.contract_address,
+        *TEST_ERC20_CONTRACT_ADDRESS
+    );
+    assert!(!fee_transfer_invocation.accessed_storage_keys.is_empty());
+    assert!(trace.revert_reason.is_none());
+    // The fee transfer wrote the balances.
+    assert!(trace.storage_diffs.contains_key(&*TEST_ERC20_CONTRACT_ADDRESS));
+}
+
 #[test]
 fn serialization_precision() {
     let input =
@@ -350,6 +700,43 @@ fn simulate_invoke() {
     }
 }
 
+#[test]
+fn simulate_invoke_sierra_gas() {
+    let ((storage_reader, storage_writer), _temp_dir) = get_test_storage();
+    prepare_storage(storage_writer);
+
+    let txs = TxsScenarioBuilder::default()
+        .invoke_recursion(10)
+        .collect()
+        .into_iter()
+        .map(|tx| tx.with_l2_gas_bound(GasAmount(10_000_000)))
+        .collect();
+    let mut result = execute_simulate_transactions(storage_reader, None, txs, None, false, true);
+    let TransactionSimulationOutput {
+        transaction_trace: TransactionTrace::Invoke(invoke_trace),
+        gas_consumed,
+        ..
+    } = result.remove(0)
+    else {
+        panic!("Wrong trace type, expected InvokeTransactionTrace.")
+    };
+    let validate_invocation = invoke_trace.validate_invocation.unwrap();
+    let FunctionInvocationResult::Ok(execute_invocation) = invoke_trace.execute_invocation else {
+        panic!("The invoke transaction shouldn't revert.")
+    };
+
+    // The invocations run on Sierra gas, which is part of the L2 gas of the transaction.
+    for invocation in [&validate_invocation, &execute_invocation] {
+        assert!(!invocation.is_reverted);
+        assert!(invocation.execution_resources.gas_consumed.l2_gas.0 > 0);
+    }
+    assert!(
+        gas_consumed.l2_gas
+            > validate_invocation.execution_resources.gas_consumed.l2_gas
+                + execute_invocation.execution_resources.gas_consumed.l2_gas
+    );
+}
+
 #[test]
 fn simulate_declare_deprecated() {
     let ((storage_reader, storage_writer), _temp_dir) = get_test_storage();
@@ -30,8 +30,14 @@ use apollo_storage::{StorageError, StorageReader};
 use blockifier::blockifier::block::{pre_process_block, validated_gas_prices};
 use blockifier::blockifier_versioned_constants::{VersionedConstants, VersionedConstantsError};
 use blockifier::bouncer::BouncerConfig;
-use blockifier::context::{BlockContext, ChainInfo, FeeTokenAddresses, TransactionContext};
-use blockifier::execution::call_info::CallExecution;
+use blockifier::context::{
+    AdditionalFeeTokens,
+    BlockContext,
+    ChainInfo,
+    FeeTokenAddresses,
+    TransactionContext,
+};
+use blockifier::execution::call_info::{CallExecution, CallInfo};
 use blockifier::execution::entry_point::{
     CallEntryPoint,
     CallType as BlockifierCallType,
@@ -51,12 +57,24 @@ use blockifier::transaction::transactions::ExecutableTransaction;
 use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
 use cairo_vm::types::builtin_name::BuiltinName;
 use execution_utils::{get_trace_constructor, induced_state_diff};
-use objects::{PriceUnit, TransactionSimulationOutput};
+use objects::{
+    CallTrace,
+    CallTraceOutput,
+    PriceUnit,
+    ResourceBoundsEstimation,
+    StateOverrides,
+    StorageDiffs,
+    TransactionCallTrace,
+    TransactionSimulationOutput,
+};
 use serde::{Deserialize, Serialize};
+use starknet_api::abi::abi_utils::get_fee_token_var_address;
 use starknet_api::block::{
     BlockHashAndNumber,
     BlockInfo,
     BlockNumber,
+    FeeType,
+    GasPrice,
     NonzeroGasPrice,
     StarknetVersion,
 };
@@ -66,7 +84,13 @@ use starknet_api::data_availability::L1DataAvailabilityMode;
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::execution_resources::GasAmount;
 use starknet_api::state::{StateNumber, ThinStateDiff};
-use starknet_api::transaction::fields::{Calldata, Fee};
+use starknet_api::transaction::fields::{
+    AllResourceBounds,
+    Calldata,
+    Fee,
+    ResourceBounds,
+    ValidResourceBounds,
+};
 use starknet_api::transaction::{
     DeclareTransaction,
     DeclareTransactionV0V1,
@@ -96,6 +120,9 @@ const STRK_FEE_CONTRACT_ADDRESS_STR: &str =
 const ETH_FEE_CONTRACT_ADDRESS_STR: &str =
     "0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7";
 const DEFAULT_INITIAL_GAS_COST: u64 = 10000000000;
+const DEFAULT_MAX_PRICE_PER_UNIT_HEADROOM_PERCENT: u64 = 50;
+// The search for the minimal L2 gas bound stops once the bound is known up to 1/64 of it.
+const L2_GAS_BOUND_SEARCH_PRECISION_DIVISOR: u64 = 64;
 
 /// Result type for execution functions.
 pub type ExecutionResult<T> = Result<T, ExecutionError>;
@@ -118,15 +145,21 @@ pub static ETH_FEE_CONTRACT_ADDRESS: LazyLock<ContractAddress> = LazyLock::new(|
     .expect("Error converting eth fee contract address from felt")
 });
 
-#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 /// Parameters that are needed for execution.
 pub struct ExecutionConfig {
     /// The strk address to receive fees
     pub strk_fee_contract_address: ContractAddress,
     /// The eth address to receive fees
     pub eth_fee_contract_address: ContractAddress,
+    /// Fee tokens overriding the strk and eth fee tokens for some transaction versions
+    #[serde(default)]
+    pub additional_fee_tokens: AdditionalFeeTokens,
     /// The initial gas cost for a transaction
     pub default_initial_gas_cost: u64,
+    /// The headroom in percents added to the gas prices of the block in the suggested resource
+    /// bounds
+    pub max_price_per_unit_headroom_percent: u64,
 }
 
 impl Default for ExecutionConfig {
@@ -134,7 +167,9 @@ impl Default for ExecutionConfig {
         ExecutionConfig {
             strk_fee_contract_address: *STRK_FEE_CONTRACT_ADDRESS,
             eth_fee_contract_address: *ETH_FEE_CONTRACT_ADDRESS,
+            additional_fee_tokens: AdditionalFeeTokens::default(),
             default_initial_gas_cost: DEFAULT_INITIAL_GAS_COST,
+            max_price_per_unit_headroom_percent: DEFAULT_MAX_PRICE_PER_UNIT_HEADROOM_PERCENT,
         }
     }
 }
@@ -154,12 +189,29 @@ impl SerializeConfig for ExecutionConfig {
                 "The eth fee token address to receive fees",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "additional_fee_tokens",
+                &self.additional_fee_tokens,
+                "Fee tokens overriding the strk and eth fee tokens, as comma-separated \
+                 'version:address:rate' items: transactions of the version and above are charged \
+                 in the token at the address, rate (an integer or a fraction 'n/d') units of the \
+                 token per unit of the fee.",
+                ParamPrivacyInput::Public,
+            ),
             ser_param(
                 "default_initial_gas_cost",
                 &self.default_initial_gas_cost,
                 "The initial gas cost for a transaction",
                 ParamPrivacyInput::Public,
             ),
+            ser_param(
+                "max_price_per_unit_headroom_percent",
+                &self.max_price_per_unit_headroom_percent,
+                "The headroom in percents added to the gas prices of the block in the resource \
+                 bounds suggested by the resource bounds estimation, as the prices may rise until \
+                 the transaction is included.",
+                ParamPrivacyInput::Public,
+            ),
         ])
     }
 }
@@ -193,6 +245,11 @@ pub enum ExecutionError {
     MissingClassHash,
     #[error("Missing compiled class with hash {class_hash} (The CASM table isn't synced)")]
     MissingCompiledClass { class_hash: ClassHash },
+    #[error(
+        "Transaction {transaction_index} has no resource bounds, only V3 transactions can be \
+         estimated with resource bounds."
+    )]
+    ResourceBoundsNotSupported { transaction_index: usize },
     #[error(transparent)]
     StateError(#[from] blockifier::state::errors::StateError),
     #[error(transparent)]
@@ -225,6 +282,7 @@ type BlockifierError = anyhow::Error;
 pub fn execute_call(
     storage_reader: StorageReader,
     maybe_pending_data: Option<PendingData>,
+    state_overrides: StateOverrides,
     chain_id: &ChainId,
     state_number: StateNumber,
     block_context_number: BlockNumber,
@@ -235,11 +293,84 @@ pub fn execute_call(
     override_kzg_da_to_false: bool,
     class_manager_handle: Option<(SharedClassManagerClient, Handle)>,
 ) -> ExecutionResult<CallExecution> {
+    let (call_info, _) = run_call(
+        storage_reader,
+        maybe_pending_data,
+        state_overrides,
+        chain_id,
+        state_number,
+        block_context_number,
+        contract_address,
+        entry_point_selector,
+        calldata,
+        execution_config,
+        override_kzg_da_to_false,
+        class_manager_handle,
+    )?;
+    Ok(call_info.execution)
+}
+
+/// Executes a StarkNet call and returns its call tree and the storage it wrote.
+#[allow(clippy::too_many_arguments)]
+// TODO(Dan, Yair): consider box large elements (because of BadDeclareTransaction) or use ID
+// instead.
+#[allow(clippy::result_large_err)]
+pub fn trace_call(
+    storage_reader: StorageReader,
+    maybe_pending_data: Option<PendingData>,
+    state_overrides: StateOverrides,
+    chain_id: &ChainId,
+    state_number: StateNumber,
+    block_context_number: BlockNumber,
+    contract_address: &ContractAddress,
+    entry_point_selector: EntryPointSelector,
+    calldata: Calldata,
+    execution_config: &ExecutionConfig,
+    override_kzg_da_to_false: bool,
+    class_manager_handle: Option<(SharedClassManagerClient, Handle)>,
+) -> ExecutionResult<CallTraceOutput> {
+    let (call_info, storage_diffs) = run_call(
+        storage_reader,
+        maybe_pending_data,
+        state_overrides,
+        chain_id,
+        state_number,
+        block_context_number,
+        contract_address,
+        entry_point_selector,
+        calldata,
+        execution_config,
+        override_kzg_da_to_false,
+        class_manager_handle,
+    )?;
+    Ok(CallTraceOutput { call: call_info.try_into()?, storage_diffs })
+}
+
+// Executes a call and returns its call info and the storage it wrote.
+#[allow(clippy::too_many_arguments)]
+// TODO(Dan, Yair): consider box large elements (because of BadDeclareTransaction) or use ID
+// instead.
+#[allow(clippy::result_large_err)]
+fn run_call(
+    storage_reader: StorageReader,
+    maybe_pending_data: Option<PendingData>,
+    state_overrides: StateOverrides,
+    chain_id: &ChainId,
+    state_number: StateNumber,
+    block_context_number: BlockNumber,
+    contract_address: &ContractAddress,
+    entry_point_selector: EntryPointSelector,
+    calldata: Calldata,
+    execution_config: &ExecutionConfig,
+    override_kzg_da_to_false: bool,
+    class_manager_handle: Option<(SharedClassManagerClient, Handle)>,
+) -> ExecutionResult<(CallInfo, StorageDiffs)> {
     verify_contract_exists(
         *contract_address,
         &storage_reader,
         state_number,
         maybe_pending_data.as_ref(),
+        &state_overrides,
     )?;
 
     // TODO(yair): check if this is the correct value.
@@ -262,6 +393,7 @@ pub fn execute_call(
         maybe_pending_data: maybe_pending_data.clone(),
         missing_compiled_class: Cell::new(None),
         class_manager_handle,
+        state_overrides: resolve_balance_overrides(state_overrides, execution_config),
     });
 
     let block_context = create_block_context(
@@ -283,17 +415,53 @@ pub fn execute_call(
         SierraGasRevertTracker::new(GasAmount(remaining_gas)),
     );
 
-    let res = call_entry_point
-        .execute(&mut cached_state, &mut context, &mut remaining_gas)
-        .map_err(|error| {
-            if let Some(class_hash) = cached_state.state.missing_compiled_class.get() {
-                ExecutionError::MissingCompiledClass { class_hash }
-            } else {
-                ExecutionError::ContractError(error.into())
-            }
-        })?;
+    // The call runs on a transactional state so that its writes are separated from the writes of
+    // the block context creation.
+    let mut transactional_state = CachedState::create_transactional(&mut cached_state);
+    let res = call_entry_point.execute(&mut transactional_state, &mut context, &mut remaining_gas);
+    let storage_diffs = induced_state_diff(&mut transactional_state, None)?.storage_diffs;
+    transactional_state.abort();
+    let call_info = res.map_err(|error| {
+        if let Some(class_hash) = cached_state.state.missing_compiled_class.get() {
+            ExecutionError::MissingCompiledClass { class_hash }
+        } else {
+            ExecutionError::ContractError(error.into())
+        }
+    })?;
+
+    Ok((call_info, storage_diffs))
+}
 
-    Ok(res.execution)
+// Moves the balance overrides into storage overrides of the balances in the fee token contracts.
+fn resolve_balance_overrides(
+    mut state_overrides: StateOverrides,
+    execution_config: &ExecutionConfig,
+) -> StateOverrides {
+    let balances = state_overrides
+        .iter_mut()
+        .filter_map(|(address, contract_override)| {
+            contract_override.balance.take().map(|balance| (*address, balance))
+        })
+        .collect::<Vec<_>>();
+    for (address, balance) in balances {
+        // The balance is a u256 stored in two consecutive storage cells, low bits first.
+        let low_key = get_fee_token_var_address(address);
+        let high_key =
+            low_key.next_storage_key().expect("Balance storage key should have a successor.");
+        let balance_bytes = balance.to_bytes_be();
+        let high = Felt::from_bytes_be_slice(&balance_bytes[..16]);
+        let low = Felt::from_bytes_be_slice(&balance_bytes[16..]);
+        let fee_contract_addresses =
+            [execution_config.strk_fee_contract_address, execution_config.eth_fee_contract_address]
+                .into_iter()
+                .chain(execution_config.additional_fee_tokens.addresses());
+        for fee_contract_address in fee_contract_addresses {
+            let storage = &mut state_overrides.entry(fee_contract_address).or_default().storage;
+            storage.insert(low_key, low);
+            storage.insert(high_key, high);
+        }
+    }
+    state_overrides
 }
 
 // TODO(Dan, Yair): consider box large elements (because of BadDeclareTransaction) or use ID
@@ -304,7 +472,14 @@ fn verify_contract_exists(
     storage_reader: &StorageReader,
     state_number: StateNumber,
     maybe_pending_data: Option<&PendingData>,
+    state_overrides: &StateOverrides,
 ) -> ExecutionResult<()> {
+    if state_overrides
+        .get(&contract_address)
+        .is_some_and(|contract_override| contract_override.class_hash.is_some())
+    {
+        return Ok(());
+    }
     execution_utils::get_class_hash_at(
         &storage_reader.begin_ro_txn()?,
         state_number,
@@ -397,6 +572,7 @@ fn create_block_context(
             strk_fee_token_address: execution_config.strk_fee_contract_address,
             eth_fee_token_address: execution_config.eth_fee_contract_address,
         },
+        additional_fee_tokens: execution_config.additional_fee_tokens.clone(),
     };
     let starknet_version = storage_reader
         .begin_ro_txn()?
@@ -574,6 +750,47 @@ impl ExecutableTransactionInput {
         }
     }
 
+    /// Returns whether the transaction has resource bounds, which only V3 transactions have.
+    fn has_resource_bounds(&self) -> bool {
+        matches!(
+            self,
+            ExecutableTransactionInput::Invoke(InvokeTransaction::V3(_), _)
+                | ExecutableTransactionInput::DeployAccount(DeployAccountTransaction::V3(_), _)
+                | ExecutableTransactionInput::DeclareV3(..)
+        )
+    }
+
+    /// Returns the transaction with the given L2 gas bound. The bounds of the other resources are
+    /// kept, and a transaction bounded only by L1 gas becomes bounded by all the resources.
+    fn with_l2_gas_bound(mut self, l2_gas_bound: GasAmount) -> Self {
+        let resource_bounds = match &mut self {
+            ExecutableTransactionInput::Invoke(InvokeTransaction::V3(tx), _) => {
+                &mut tx.resource_bounds
+            }
+            ExecutableTransactionInput::DeployAccount(DeployAccountTransaction::V3(tx), _) => {
+                &mut tx.resource_bounds
+            }
+            ExecutableTransactionInput::DeclareV3(tx, ..) => &mut tx.resource_bounds,
+            _ => return self,
+        };
+        let (l1_gas, l2_gas, l1_data_gas) = match *resource_bounds {
+            ValidResourceBounds::L1Gas(l1_gas) => {
+                (l1_gas, ResourceBounds::default(), ResourceBounds::default())
+            }
+            ValidResourceBounds::AllResources(AllResourceBounds {
+                l1_gas,
+                l2_gas,
+                l1_data_gas,
+            }) => (l1_gas, l2_gas, l1_data_gas),
+        };
+        *resource_bounds = ValidResourceBounds::AllResources(AllResourceBounds {
+            l1_gas,
+            l2_gas: ResourceBounds { max_amount: l2_gas_bound, ..l2_gas },
+            l1_data_gas,
+        });
+        self
+    }
+
     /// Returns the transaction version.
     pub fn transaction_version(&self) -> TransactionVersion {
         match self {
@@ -627,6 +844,7 @@ pub fn estimate_fee(
     chain_id: &ChainId,
     storage_reader: StorageReader,
     maybe_pending_data: Option<PendingData>,
+    state_overrides: StateOverrides,
     state_number: StateNumber,
     block_context_block_number: BlockNumber,
     execution_config: &ExecutionConfig,
@@ -640,6 +858,7 @@ pub fn estimate_fee(
         chain_id,
         storage_reader,
         maybe_pending_data,
+        state_overrides,
         state_number,
         block_context_block_number,
         execution_config,
@@ -664,6 +883,196 @@ pub fn estimate_fee(
     Ok(Ok(result))
 }
 
+/// Returns the suggested resource bounds for a series of V3 transactions.
+///
+/// Each transaction is executed under different L2 gas bounds to find the minimal bound under which
+/// it succeeds and the bound covers all the L2 gas it consumed. Since the gas usage of a
+/// transaction may depend on its bound, the search only ever suggests a bound under which the
+/// transaction was executed successfully, although a lower one may exist. A transaction that
+/// reverts under the highest possible bound is reported with its revert reason, and its L2 gas
+/// bound is the gas it consumed then. Each transaction runs on the state after the previous ones
+/// were executed under their suggested bounds. The gas prices are suggested with the configured
+/// headroom above the prices of the block.
+#[allow(clippy::too_many_arguments)]
+// TODO(Dan, Yair): consider box large elements (because of BadDeclareTransaction) or use ID
+// instead.
+#[allow(clippy::result_large_err)]
+pub fn estimate_resource_bounds(
+    txs: Vec<ExecutableTransactionInput>,
+    chain_id: &ChainId,
+    storage_reader: StorageReader,
+    maybe_pending_data: Option<PendingData>,
+    state_overrides: StateOverrides,
+    state_number: StateNumber,
+    block_context_block_number: BlockNumber,
+    execution_config: &ExecutionConfig,
+    validate: bool,
+    override_kzg_da_to_false: bool,
+    class_manager_handle: Option<(SharedClassManagerClient, Handle)>,
+) -> ExecutionResult<Vec<ResourceBoundsEstimation>> {
+    if let Some(transaction_index) = txs.iter().position(|tx| !tx.has_resource_bounds()) {
+        return Err(ExecutionError::ResourceBoundsNotSupported { transaction_index });
+    }
+    let mut cached_state = CachedState::new(ExecutionStateReader {
+        storage_reader: storage_reader.clone(),
+        state_number,
+        maybe_pending_data: maybe_pending_data.clone(),
+        missing_compiled_class: Cell::new(None),
+        class_manager_handle,
+        state_overrides: resolve_balance_overrides(state_overrides, execution_config),
+    });
+    let block_context = create_block_context(
+        &mut cached_state,
+        block_context_block_number,
+        chain_id.clone(),
+        &storage_reader,
+        maybe_pending_data.as_ref(),
+        execution_config,
+        override_kzg_da_to_false,
+    )?;
+    let max_l2_gas_bound = block_context.versioned_constants().initial_gas_no_user_l2_bound();
+
+    let (txs, tx_hashes) = calc_tx_hashes(txs, chain_id)?;
+    let mut res = vec![];
+    for (transaction_index, (tx, tx_hash)) in txs.into_iter().zip(tx_hashes.into_iter()).enumerate()
+    {
+        // The hash is of the original transaction, so that changing the bounds doesn't invalidate
+        // the signature.
+        let mut execute = |l2_gas_bound: GasAmount, commit: bool| {
+            execute_with_l2_gas_bound(
+                &mut cached_state,
+                &block_context,
+                tx.clone().with_l2_gas_bound(l2_gas_bound),
+                tx_hash,
+                transaction_index,
+                validate,
+                commit,
+            )
+        };
+        let succeeds = |execution_info: &TransactionExecutionInfo, l2_gas_bound: GasAmount| {
+            !execution_info.is_reverted() && execution_info.receipt.gas.l2_gas <= l2_gas_bound
+        };
+
+        let execution_info = execute(max_l2_gas_bound, false)?.execution_info;
+        let l2_gas_bound = if !succeeds(&execution_info, max_l2_gas_bound) {
+            execution_info.receipt.gas.l2_gas
+        } else {
+            search_minimal_l2_gas_bound(
+                execution_info.receipt.gas.l2_gas,
+                max_l2_gas_bound,
+                |l2_gas_bound| match execute(l2_gas_bound, false) {
+                    Ok(output) => Ok(succeeds(&output.execution_info, l2_gas_bound)),
+                    // Running out of gas in the validation fails the transaction instead of
+                    // reverting it.
+                    Err(ExecutionError::TransactionExecutionError { .. }) => Ok(false),
+                    Err(err) => Err(err),
+                },
+            )?
+        };
+
+        let tx_execution_output = execute(l2_gas_bound, true)?;
+        let receipt = &tx_execution_output.execution_info.receipt;
+        let gas_prices = &block_context.block_info().gas_prices;
+        let fee_type = FeeType::Strk;
+        // The prices may rise until the transaction is included, so they're suggested with a
+        // headroom above the prices of the block.
+        let headroom_percent = u128::from(execution_config.max_price_per_unit_headroom_percent);
+        let with_headroom =
+            |price: GasPrice| GasPrice(price.0.saturating_mul(100 + headroom_percent) / 100);
+        let resource_bounds = AllResourceBounds {
+            l1_gas: ResourceBounds {
+                max_amount: receipt.gas.l1_gas,
+                max_price_per_unit: with_headroom(gas_prices.l1_gas_price(&fee_type).get()),
+            },
+            l2_gas: ResourceBounds {
+                max_amount: l2_gas_bound.max(receipt.gas.l2_gas),
+                max_price_per_unit: with_headroom(gas_prices.l2_gas_price(&fee_type).get()),
+            },
+            l1_data_gas: ResourceBounds {
+                max_amount: receipt.gas.l1_data_gas,
+                max_price_per_unit: with_headroom(gas_prices.l1_data_gas_price(&fee_type).get()),
+            },
+        };
+        res.push(ResourceBoundsEstimation {
+            resource_bounds,
+            fee_estimation: tx_execution_output_to_fee_estimation(
+                &tx_execution_output,
+                &block_context,
+            )?,
+            revert_reason: tx_execution_output
+                .execution_info
+                .revert_error
+                .map(|revert_error| revert_error.to_string()),
+        });
+    }
+    Ok(res)
+}
+
+// Searches for the minimal L2 gas bound under which a transaction succeeds, given the L2 gas it
+// consumed and a bound under which it's known to succeed. A bound below the consumed L2 gas can't
+// cover it, so the consumed gas is tried first, and it doesn't suffice only when gas is withdrawn
+// ahead of its use.
+#[allow(clippy::result_large_err)]
+fn search_minimal_l2_gas_bound(
+    consumed_l2_gas: GasAmount,
+    succeeding_l2_gas_bound: GasAmount,
+    mut succeeds_under: impl FnMut(GasAmount) -> ExecutionResult<bool>,
+) -> ExecutionResult<GasAmount> {
+    let mut lower = consumed_l2_gas;
+    let mut upper = succeeding_l2_gas_bound;
+    let mut l2_gas_bound = lower;
+    loop {
+        // Only bounds under which the transaction succeeded are suggested, so a non-monotonic gas
+        // usage can't lead to a failing bound.
+        if succeeds_under(l2_gas_bound)? {
+            upper = l2_gas_bound;
+        } else {
+            lower = l2_gas_bound;
+        }
+        if upper.0 - lower.0 <= (upper.0 / L2_GAS_BOUND_SEARCH_PRECISION_DIVISOR).max(1) {
+            return Ok(upper);
+        }
+        l2_gas_bound = GasAmount(lower.0 + (upper.0 - lower.0) / 2);
+    }
+}
+
+// Executes a V3 transaction without charging its fee, and commits its changes to the state only if
+// asked to.
+// TODO(Dan, Yair): consider box large elements (because of BadDeclareTransaction) or use ID
+// instead.
+#[allow(clippy::result_large_err)]
+fn execute_with_l2_gas_bound(
+    cached_state: &mut CachedState<ExecutionStateReader>,
+    block_context: &BlockContext,
+    tx: ExecutableTransactionInput,
+    tx_hash: TransactionHash,
+    transaction_index: usize,
+    validate: bool,
+    commit: bool,
+) -> ExecutionResult<TransactionExecutionOutput> {
+    let mut transactional_state = CachedState::create_transactional(cached_state);
+    let blockifier_tx = to_blockifier_tx(tx, tx_hash, transaction_index, false, validate)?;
+    let tx_execution_info_result = blockifier_tx.execute(&mut transactional_state, block_context);
+    let state_diff = induced_state_diff(&mut transactional_state, None)?;
+    if commit {
+        transactional_state.commit();
+    } else {
+        transactional_state.abort();
+    }
+    let execution_info = tx_execution_info_result.map_err(|error| {
+        if let Some(class_hash) = cached_state.state.missing_compiled_class.get() {
+            ExecutionError::MissingCompiledClass { class_hash }
+        } else {
+            ExecutionError::from((transaction_index, error))
+        }
+    })?;
+    Ok(TransactionExecutionOutput {
+        execution_info,
+        induced_state_diff: state_diff,
+        price_unit: PriceUnit::Fri,
+    })
+}
+
 struct TransactionExecutionOutput {
     execution_info: TransactionExecutionInfo,
     induced_state_diff: ThinStateDiff,
@@ -682,6 +1091,7 @@ fn execute_transactions(
     chain_id: &ChainId,
     storage_reader: StorageReader,
     maybe_pending_data: Option<PendingData>,
+    state_overrides: StateOverrides,
     state_number: StateNumber,
     block_context_block_number: BlockNumber,
     execution_config: &ExecutionConfig,
@@ -697,6 +1107,7 @@ fn execute_transactions(
         maybe_pending_data: maybe_pending_data.clone(),
         missing_compiled_class: Cell::new(None),
         class_manager_handle,
+        state_overrides: resolve_balance_overrides(state_overrides, execution_config),
     });
 
     let block_context = create_block_context(
@@ -989,6 +1400,7 @@ pub fn simulate_transactions(
     chain_id: &ChainId,
     storage_reader: StorageReader,
     maybe_pending_data: Option<PendingData>,
+    state_overrides: StateOverrides,
     state_number: StateNumber,
     block_context_block_number: BlockNumber,
     execution_config: &ExecutionConfig,
@@ -1004,6 +1416,7 @@ pub fn simulate_transactions(
         chain_id,
         storage_reader,
         maybe_pending_data,
+        state_overrides,
         state_number,
         block_context_block_number,
         execution_config,
@@ -1018,14 +1431,79 @@ pub fn simulate_transactions(
         .map(|(tx_execution_output, trace_constructor)| {
             let fee_estimation =
                 tx_execution_output_to_fee_estimation(&tx_execution_output, &block_context)?;
+            let gas_consumed = tx_execution_output.execution_info.receipt.gas;
             match trace_constructor(tx_execution_output.execution_info) {
                 Ok(transaction_trace) => Ok(TransactionSimulationOutput {
                     transaction_trace,
                     induced_state_diff: tx_execution_output.induced_state_diff,
                     fee_estimation,
+                    gas_consumed,
                 }),
                 Err(e) => Err(e),
             }
         })
         .collect()
 }
+
+/// Executes a series of transactions and returns the call trees of each transaction and the storage
+/// it wrote.
+// TODO(Dan, Yair): consider box large elements (because of BadDeclareTransaction) or use ID
+// instead.
+#[allow(clippy::result_large_err)]
+#[allow(clippy::too_many_arguments)]
+pub fn trace_transactions(
+    txs: Vec<ExecutableTransactionInput>,
+    tx_hashes: Option<Vec<TransactionHash>>,
+    chain_id: &ChainId,
+    storage_reader: StorageReader,
+    maybe_pending_data: Option<PendingData>,
+    state_overrides: StateOverrides,
+    state_number: StateNumber,
+    block_context_block_number: BlockNumber,
+    execution_config: &ExecutionConfig,
+    charge_fee: bool,
+    validate: bool,
+    override_kzg_da_to_false: bool,
+    class_manager_handle: Option<(SharedClassManagerClient, Handle)>,
+) -> ExecutionResult<Vec<TransactionCallTrace>> {
+    let (execution_results, _) = execute_transactions(
+        txs,
+        tx_hashes,
+        chain_id,
+        storage_reader,
+        maybe_pending_data,
+        state_overrides,
+        state_number,
+        block_context_block_number,
+        execution_config,
+        charge_fee,
+        validate,
+        override_kzg_da_to_false,
+        class_manager_handle,
+    )?;
+    execution_results
+        .into_iter()
+        .map(
+            |TransactionExecutionOutput {
+                 execution_info, induced_state_diff: state_diff, ..
+             }| {
+                Ok(TransactionCallTrace {
+                    validate_invocation: execution_info
+                        .validate_call_info
+                        .map(CallTrace::try_from)
+                        .transpose()?,
+                    execute_invocation: execution_info
+                        .execute_call_info
+                        .map(CallTrace::try_from)
+                        .transpose()?,
+                    fee_transfer_invocation: execution_info
+                        .fee_transfer_call_info
+                        .map(CallTrace::try_from)
+                        .transpose()?,
+                    revert_reason: execution_info.revert_error.map(|error| error.to_string()),
+                    storage_diffs: state_diff.storage_diffs,
+                })
+            },
+        )
+        .collect()
+}
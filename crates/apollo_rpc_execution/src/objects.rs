@@ -36,11 +36,12 @@ use starknet_api::data_availability::L1DataAvailabilityMode;
 use starknet_api::execution_resources::{
     Builtin,
     ExecutionResources,
+    GasAmount,
     GasVector,
     GasVector as StarknetApiGasVector,
 };
-use starknet_api::state::ThinStateDiff;
-use starknet_api::transaction::fields::{Calldata, Fee};
+use starknet_api::state::{StorageKey, ThinStateDiff};
+use starknet_api::transaction::fields::{AllResourceBounds, Calldata, Fee};
 use starknet_api::transaction::{EventContent, MessageToL1};
 use starknet_types_core::felt::Felt;
 
@@ -57,6 +58,8 @@ pub struct TransactionSimulationOutput {
     pub induced_state_diff: ThinStateDiff,
     /// The details of the fees charged by the transaction.
     pub fee_estimation: FeeEstimation,
+    /// The gas consumed by the transaction.
+    pub gas_consumed: GasVector,
 }
 
 /// The execution trace of a transaction.
@@ -111,6 +114,20 @@ pub struct FeeEstimation {
     pub unit: PriceUnit,
 }
 
+/// The resource bounds suggested for a transaction, whose L2 gas bound is the minimal one found
+/// under which the transaction succeeds.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct ResourceBoundsEstimation {
+    /// The bounds of all the resources, priced with the configured headroom above the gas prices
+    /// of the block.
+    pub resource_bounds: AllResourceBounds,
+    /// The fee estimation of the transaction under the suggested bounds.
+    pub fee_estimation: FeeEstimation,
+    /// The revert reason, if the transaction reverts even under the highest L2 gas bound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+}
+
 /// The reason for a reverted transaction.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[allow(missing_docs)]
@@ -310,6 +327,8 @@ pub struct FunctionInvocation {
     pub messages: Vec<OrderedL2ToL1Message>,
     /// The VM execution resources used by this invocation.
     pub execution_resources: ExecutionResources,
+    /// Whether the invocation reverted.
+    pub is_reverted: bool,
 }
 
 impl TryFrom<(CallInfo, GasVector)> for FunctionInvocation {
@@ -352,7 +371,9 @@ impl TryFrom<(CallInfo, GasVector)> for FunctionInvocation {
             execution_resources: vm_resources_to_execution_resources(
                 call_info.resources,
                 gas_vector,
+                GasAmount(call_info.execution.gas_consumed),
             )?,
+            is_reverted: call_info.execution.failed,
         })
     }
 }
@@ -361,9 +382,11 @@ impl TryFrom<(CallInfo, GasVector)> for FunctionInvocation {
 // TODO(Dan, Yair): consider box large elements (because of BadDeclareTransaction) or use ID
 // instead.
 #[allow(clippy::result_large_err)]
+// The Sierra gas the call consumed is its L2 gas.
 fn vm_resources_to_execution_resources(
     vm_resources: VmExecutionResources,
     GasVector { l1_gas, l1_data_gas, l2_gas }: GasVector,
+    sierra_gas_consumed: GasAmount,
 ) -> ExecutionResult<ExecutionResources> {
     let mut builtin_instance_counter = HashMap::new();
     for (builtin_name, count) in vm_resources.builtin_instance_counter {
@@ -397,7 +420,7 @@ fn vm_resources_to_execution_resources(
         builtin_instance_counter,
         memory_holes: u64_from_usize(vm_resources.n_memory_holes),
         da_gas_consumed: StarknetApiGasVector { l1_gas, l2_gas, l1_data_gas },
-        gas_consumed: StarknetApiGasVector::default(),
+        gas_consumed: StarknetApiGasVector { l2_gas: sierra_gas_consumed, ..Default::default() },
     })
 }
 
@@ -483,6 +506,138 @@ pub struct FunctionCall {
     pub calldata: Calldata,
 }
 
+/// A call in the call tree of an execution, with the storage it accessed.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CallTrace {
+    #[serde(flatten)]
+    /// The details of the function call.
+    pub function_call: FunctionCall,
+    /// The address of the invoking contract. 0 for the root call.
+    pub caller_address: ContractAddress,
+    /// The hash of the class being called.
+    pub class_hash: ClassHash,
+    /// The type of the entry point being called.
+    pub entry_point_type: EntryPointType,
+    /// library call or regular call.
+    pub call_type: CallType,
+    /// The value returned from the call.
+    pub result: Retdata,
+    /// Whether the call failed.
+    pub failed: bool,
+    /// The Sierra gas consumed by the call, including its inner calls.
+    pub gas_consumed: u64,
+    /// The values returned by the storage reads of the call, in the order they were made.
+    pub storage_read_values: Vec<Felt>,
+    /// The storage keys the call read or wrote, sorted.
+    pub accessed_storage_keys: Vec<StorageKey>,
+    /// The events emitted in this call.
+    pub events: Vec<OrderedEvent>,
+    /// The messages sent by this call to L1.
+    pub messages: Vec<OrderedL2ToL1Message>,
+    /// The calls made by this call.
+    pub calls: Vec<Self>,
+}
+
+impl TryFrom<CallInfo> for CallTrace {
+    type Error = ExecutionError;
+    fn try_from(call_info: CallInfo) -> ExecutionResult<Self> {
+        Ok(Self {
+            function_call: FunctionCall {
+                contract_address: call_info.call.storage_address,
+                entry_point_selector: call_info.call.entry_point_selector,
+                calldata: call_info.call.calldata,
+            },
+            caller_address: call_info.call.caller_address,
+            class_hash: call_info.call.class_hash.ok_or(ExecutionError::MissingClassHash)?,
+            entry_point_type: call_info.call.entry_point_type,
+            call_type: call_info.call.call_type.into(),
+            result: call_info.execution.retdata.into(),
+            failed: call_info.execution.failed,
+            gas_consumed: call_info.execution.gas_consumed,
+            storage_read_values: call_info.storage_access_tracker.storage_read_values,
+            accessed_storage_keys: call_info
+                .storage_access_tracker
+                .accessed_storage_keys
+                .into_iter()
+                .sorted()
+                .collect(),
+            events: call_info
+                .execution
+                .events
+                .into_iter()
+                .sorted_by_key(|ordered_event| ordered_event.order)
+                .map(OrderedEvent::from)
+                .collect(),
+            messages: call_info
+                .execution
+                .l2_to_l1_messages
+                .into_iter()
+                .sorted_by_key(|ordered_message| ordered_message.order)
+                .map(|ordered_message| {
+                    OrderedL2ToL1Message::from(ordered_message, call_info.call.storage_address)
+                })
+                .collect(),
+            calls: call_info
+                .inner_calls
+                .into_iter()
+                .map(Self::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// The storage written by an execution, by contract address and storage key.
+pub type StorageDiffs = IndexMap<ContractAddress, IndexMap<StorageKey, Felt>>;
+
+/// The call tree of a call and the storage it wrote.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct CallTraceOutput {
+    /// The trace of the call.
+    pub call: CallTrace,
+    /// The storage written by the call.
+    pub storage_diffs: StorageDiffs,
+}
+
+/// The call trees of the phases of a transaction and the storage it wrote.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct TransactionCallTrace {
+    /// The trace of the validation call. [None] for L1 handlers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub validate_invocation: Option<CallTrace>,
+    /// The trace of the execution call, the constructor call for deploy account transactions.
+    /// [None] for declare transactions and for reverted transactions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execute_invocation: Option<CallTrace>,
+    /// The trace of the fee transfer call. [None] for L1 handlers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee_transfer_invocation: Option<CallTrace>,
+    /// The reason the transaction reverted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    /// The storage written by the transaction, including the fee transfer.
+    pub storage_diffs: StorageDiffs,
+}
+
+/// Overrides of the state of a contract, applied on top of the state the execution runs on.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ContractStateOverride {
+    /// The nonce of the contract.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<Nonce>,
+    /// The class hash of the contract. Setting it for an address without a contract deploys one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class_hash: Option<ClassHash>,
+    /// Storage entries of the contract. Entries that aren't given keep their value.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub storage: IndexMap<StorageKey, Felt>,
+    /// The balance of the contract in both the STRK and the ETH fee tokens.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<Felt>,
+}
+
+/// Overrides of the state by contract address, in the spirit of the state overrides of `eth_call`.
+pub type StateOverrides = IndexMap<ContractAddress, ContractStateOverride>;
+
 /// A state diff for the pending block.
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 pub struct PendingData {
@@ -28,7 +28,7 @@ use crate::execution_utils::{
     is_contract_class_declared,
     ExecutionUtilsError,
 };
-use crate::objects::PendingData;
+use crate::objects::{PendingData, StateOverrides};
 
 /// A view into the state at a specific state number.
 pub struct ExecutionStateReader {
@@ -39,6 +39,9 @@ pub struct ExecutionStateReader {
     // Blockifier's error, so we store the missing class's hash in case of error.
     pub missing_compiled_class: Cell<Option<ClassHash>>,
     pub class_manager_handle: Option<(SharedClassManagerClient, Handle)>,
+    // Overrides applied on top of the state. Balance overrides should already be resolved into
+    // storage overrides of the fee token contracts.
+    pub state_overrides: StateOverrides,
 }
 
 impl BlockifierStateReader for ExecutionStateReader {
@@ -47,6 +50,13 @@ impl BlockifierStateReader for ExecutionStateReader {
         contract_address: ContractAddress,
         key: StorageKey,
     ) -> StateResult<Felt> {
+        if let Some(value) = self
+            .state_overrides
+            .get(&contract_address)
+            .and_then(|contract_override| contract_override.storage.get(&key))
+        {
+            return Ok(*value);
+        }
         execution_utils::get_storage_at(
             &self.storage_reader.begin_ro_txn().map_err(storage_err_to_state_err)?,
             self.state_number,
@@ -59,6 +69,13 @@ impl BlockifierStateReader for ExecutionStateReader {
 
     // Returns the default value if the contract address is not found.
     fn get_nonce_at(&self, contract_address: ContractAddress) -> StateResult<Nonce> {
+        if let Some(nonce) = self
+            .state_overrides
+            .get(&contract_address)
+            .and_then(|contract_override| contract_override.nonce)
+        {
+            return Ok(nonce);
+        }
         Ok(execution_utils::get_nonce_at(
             &self.storage_reader.begin_ro_txn().map_err(storage_err_to_state_err)?,
             self.state_number,
@@ -71,6 +88,13 @@ impl BlockifierStateReader for ExecutionStateReader {
 
     // Returns the default value if the contract address is not found.
     fn get_class_hash_at(&self, contract_address: ContractAddress) -> StateResult<ClassHash> {
+        if let Some(class_hash) = self
+            .state_overrides
+            .get(&contract_address)
+            .and_then(|contract_override| contract_override.class_hash)
+        {
+            return Ok(class_hash);
+        }
         Ok(execution_utils::get_class_hash_at(
             &self.storage_reader.begin_ro_txn().map_err(storage_err_to_state_err)?,
             self.state_number,
@@ -40,7 +40,7 @@ use starknet_api::state::{SierraContractClass, StateNumber, ThinStateDiff};
 use starknet_api::{class_hash, contract_address, felt, storage_key};
 use starknet_types_core::felt::Felt;
 
-use crate::objects::PendingData;
+use crate::objects::{ContractStateOverride, PendingData, StateOverrides};
 use crate::state_reader::ExecutionStateReader;
 use crate::test_utils::{get_test_casm, get_test_deprecated_contract_class};
 
@@ -171,6 +171,7 @@ fn read_state() {
         maybe_pending_data: None,
         missing_compiled_class: Cell::new(None),
         class_manager_handle: None,
+        state_overrides: StateOverrides::default(),
     };
     let storage_after_block_0 = state_reader0.get_storage_at(address0, storage_key0).unwrap();
     assert_eq!(storage_after_block_0, Felt::default());
@@ -192,6 +193,7 @@ fn read_state() {
         maybe_pending_data: None,
         missing_compiled_class: Cell::new(None),
         class_manager_handle: None,
+        state_overrides: StateOverrides::default(),
     };
     let storage_after_block_1 = state_reader1.get_storage_at(address0, storage_key0).unwrap();
     assert_eq!(storage_after_block_1, storage_value0);
@@ -215,6 +217,7 @@ fn read_state() {
         maybe_pending_data: None,
         missing_compiled_class: Cell::new(None),
         class_manager_handle: None,
+        state_overrides: StateOverrides::default(),
     };
     let nonce_after_block_2 = state_reader2.get_nonce_at(address0).unwrap();
     assert_eq!(nonce_after_block_2, nonce0);
@@ -270,6 +273,21 @@ fn read_state() {
     }
     assert_eq!(state_reader2.get_class_hash_at(address0).unwrap(), class_hash3);
     assert_eq!(state_reader2.get_class_hash_at(address2).unwrap(), class_hash3);
+
+    // Test that the state overrides take precedence over the state.
+    state_reader2.state_overrides = indexmap!(
+        address0 => ContractStateOverride {
+            nonce: Some(nonce1),
+            class_hash: Some(class_hash2),
+            storage: indexmap!(storage_key0 => storage_value0),
+            ..Default::default()
+        },
+    );
+    assert_eq!(state_reader2.get_storage_at(address0, storage_key0).unwrap(), storage_value0);
+    assert_eq!(state_reader2.get_nonce_at(address0).unwrap(), nonce1);
+    assert_eq!(state_reader2.get_class_hash_at(address0).unwrap(), class_hash2);
+    assert_eq!(state_reader2.get_storage_at(address2, storage_key0).unwrap(), storage_value2);
+    assert_eq!(state_reader2.get_class_hash_at(address2).unwrap(), class_hash3);
 }
 
 // Make sure we have the arbitrary precision feature of serde_json.
@@ -296,6 +314,7 @@ fn set_execution_state_reader(
             maybe_pending_data: None,
             missing_compiled_class: Cell::new(None),
             class_manager_handle: Some((class_manager_client, run_time_handle)),
+            state_overrides: StateOverrides::default(),
         },
         storage_writer,
     )
@@ -6,6 +6,8 @@ use apollo_storage::compiled_class::CasmStorageWriter;
 use apollo_storage::header::HeaderStorageWriter;
 use apollo_storage::state::StateStorageWriter;
 use apollo_storage::{StorageReader, StorageWriter};
+use blockifier_test_utils::cairo_versions::{CairoVersion, RunnableCairo1};
+use blockifier_test_utils::contracts::FeatureContract;
 use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
 use indexmap::indexmap;
 use lazy_static::lazy_static;
@@ -30,10 +32,19 @@ use starknet_api::core::{
     Nonce,
     SequencerContractAddress,
 };
+use starknet_api::data_availability::DataAvailabilityMode;
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::state::{SierraContractClass, StateNumber, ThinStateDiff};
 use starknet_api::test_utils::read_json_file;
-use starknet_api::transaction::fields::Fee;
+use starknet_api::transaction::fields::{
+    AccountDeploymentData,
+    Calldata,
+    Fee,
+    PaymasterData,
+    Tip,
+    TransactionSignature,
+    ValidResourceBounds,
+};
 use starknet_api::transaction::{
     DeclareTransactionV0V1,
     DeclareTransactionV2,
@@ -41,13 +52,14 @@ use starknet_api::transaction::{
     DeployAccountTransactionV1,
     InvokeTransaction,
     InvokeTransactionV1,
+    InvokeTransactionV3,
     TransactionHash,
 };
 use starknet_api::{calldata, class_hash, contract_address, felt, nonce};
 use starknet_types_core::felt::Felt;
 
 use crate::execution_utils::selector_from_name;
-use crate::objects::{PendingData, TransactionSimulationOutput};
+use crate::objects::{PendingData, StateOverrides, TransactionSimulationOutput};
 use crate::testing_instances::get_test_execution_config;
 use crate::{simulate_transactions, ExecutableTransactionInput, OnlyQuery, SierraSize};
 
@@ -72,8 +84,19 @@ lazy_static! {
     pub static ref TEST_ERC20_CONTRACT_CLASS_HASH: ClassHash = class_hash!("0x1010");
     pub static ref TEST_ERC20_CONTRACT_ADDRESS: ContractAddress = contract_address!("0x1001");
     pub static ref ACCOUNT_INITIAL_BALANCE: Felt = felt!(2 * MAX_FEE.0);
+    pub static ref CAIRO1_ACCOUNT_ADDRESS: ContractAddress =
+        CAIRO1_ACCOUNT.get_instance_address(0);
+    pub static ref CAIRO1_CONTRACT_ADDRESS: ContractAddress =
+        CAIRO1_CONTRACT.get_instance_address(0);
 }
 
+// An account whose validation recurses as deep as the first calldata felt of the call it invokes.
+// Unlike the deprecated account, it runs on Sierra gas, and so do the contracts it calls.
+pub const CAIRO1_ACCOUNT: FeatureContract =
+    FeatureContract::AccountWithLongValidate(CairoVersion::Cairo1(RunnableCairo1::Casm));
+pub const CAIRO1_CONTRACT: FeatureContract =
+    FeatureContract::TestContract(CairoVersion::Cairo1(RunnableCairo1::Casm));
+
 // Sierra size must be > 0.
 const DUMMY_SIERRA_SIZE: SierraSize = 1;
 
@@ -95,6 +118,29 @@ pub fn get_test_erc20_fee_contract_class() -> DeprecatedContractClass {
 pub fn get_test_account_class() -> DeprecatedContractClass {
     get_test_instance("account_class.json")
 }
+fn get_feature_contract_casm(feature_contract: FeatureContract) -> CasmContractClass {
+    serde_json::from_str(&feature_contract.get_raw_class()).unwrap()
+}
+
+// A V3 invoke with default resource bounds, which are estimated or skipped in the tests.
+fn invoke_v3(
+    sender_address: ContractAddress,
+    nonce: Nonce,
+    calldata: Calldata,
+) -> InvokeTransactionV3 {
+    InvokeTransactionV3 {
+        resource_bounds: ValidResourceBounds::default(),
+        tip: Tip::default(),
+        signature: TransactionSignature::default(),
+        nonce,
+        sender_address,
+        calldata,
+        nonce_data_availability_mode: DataAvailabilityMode::L1,
+        fee_data_availability_mode: DataAvailabilityMode::L1,
+        paymaster_data: PaymasterData::default(),
+        account_deployment_data: AccountDeploymentData::default(),
+    }
+}
 
 pub fn prepare_storage(mut storage_writer: StorageWriter) {
     let class_hash0 = class_hash!("0x2");
@@ -106,6 +152,8 @@ pub fn prepare_storage(mut storage_writer: StorageWriter) {
         get_storage_var_address("ERC20_balances", &[*ACCOUNT_ADDRESS.0.key()]);
     let new_account_balance_key =
         get_storage_var_address("ERC20_balances", &[*NEW_ACCOUNT_ADDRESS.0.key()]);
+    let cairo1_account_balance_key =
+        get_storage_var_address("ERC20_balances", &[*CAIRO1_ACCOUNT_ADDRESS.0.key()]);
 
     storage_writer
         .begin_rw_txn()
@@ -133,19 +181,24 @@ pub fn prepare_storage(mut storage_writer: StorageWriter) {
                     *CONTRACT_ADDRESS => class_hash0,
                     *DEPRECATED_CONTRACT_ADDRESS => class_hash1,
                     *ACCOUNT_ADDRESS => *ACCOUNT_CLASS_HASH,
+                    *CAIRO1_ACCOUNT_ADDRESS => CAIRO1_ACCOUNT.get_class_hash(),
+                    *CAIRO1_CONTRACT_ADDRESS => CAIRO1_CONTRACT.get_class_hash(),
                 ),
                 storage_diffs: indexmap!(
                     *TEST_ERC20_CONTRACT_ADDRESS => indexmap!(
                         // Give the accounts some balance.
                         account_balance_key => *ACCOUNT_INITIAL_BALANCE,
                         new_account_balance_key => *ACCOUNT_INITIAL_BALANCE,
+                        cairo1_account_balance_key => *ACCOUNT_INITIAL_BALANCE,
                         // Give the first account mint permission (what is this?).
                         minter_var_address => *ACCOUNT_ADDRESS.0.key()
                     ),
                 ),
                 declared_classes: indexmap!(
                     // The class is not used in the execution, so it can be default.
-                    class_hash0 => CompiledClassHash::default()
+                    class_hash0 => CompiledClassHash::default(),
+                    CAIRO1_ACCOUNT.get_class_hash() => CAIRO1_ACCOUNT.get_compiled_class_hash(),
+                    CAIRO1_CONTRACT.get_class_hash() => CAIRO1_CONTRACT.get_compiled_class_hash(),
                 ),
                 deprecated_declared_classes: vec![
                     *TEST_ERC20_CONTRACT_CLASS_HASH,
@@ -157,13 +210,19 @@ pub fn prepare_storage(mut storage_writer: StorageWriter) {
                     *CONTRACT_ADDRESS => Nonce::default(),
                     *DEPRECATED_CONTRACT_ADDRESS => Nonce::default(),
                     *ACCOUNT_ADDRESS => Nonce::default(),
+                    *CAIRO1_ACCOUNT_ADDRESS => Nonce::default(),
+                    *CAIRO1_CONTRACT_ADDRESS => Nonce::default(),
                 ),
             },
         )
         .unwrap()
         .append_classes(
             BlockNumber(0),
-            &[(class_hash0, &SierraContractClass::default())],
+            &[
+                (class_hash0, &SierraContractClass::default()),
+                (CAIRO1_ACCOUNT.get_class_hash(), &CAIRO1_ACCOUNT.get_sierra()),
+                (CAIRO1_CONTRACT.get_class_hash(), &CAIRO1_CONTRACT.get_sierra()),
+            ],
             &[
                 (*TEST_ERC20_CONTRACT_CLASS_HASH, &get_test_erc20_fee_contract_class()),
                 (class_hash1, &get_test_deprecated_contract_class()),
@@ -173,6 +232,10 @@ pub fn prepare_storage(mut storage_writer: StorageWriter) {
         .unwrap()
         .append_casm(&class_hash0, &get_test_casm())
         .unwrap()
+        .append_casm(&CAIRO1_ACCOUNT.get_class_hash(), &get_feature_contract_casm(CAIRO1_ACCOUNT))
+        .unwrap()
+        .append_casm(&CAIRO1_CONTRACT.get_class_hash(), &get_feature_contract_casm(CAIRO1_CONTRACT))
+        .unwrap()
         .append_header(
             BlockNumber(1),
             &BlockHeader {
@@ -214,6 +277,7 @@ pub fn execute_simulate_transactions(
         &chain_id,
         storage_reader,
         maybe_pending_data,
+        StateOverrides::default(),
         StateNumber::unchecked_right_after_block(BlockNumber(0)),
         BlockNumber(1),
         &get_test_execution_config(),
@@ -283,6 +347,50 @@ impl TxsScenarioBuilder {
         self
     }
 
+    pub fn invoke(
+        mut self,
+        sender_address: ContractAddress,
+        contract_address: ContractAddress,
+    ) -> Self {
+        let calldata = calldata![
+            *contract_address.0.key(),             // Contract address.
+            selector_from_name("return_result").0, // EP selector.
+            felt!(1_u8),                           // Calldata length.
+            felt!(2_u8)                            // Calldata: num.
+        ];
+        let tx = ExecutableTransactionInput::Invoke(
+            InvokeTransaction::V3(invoke_v3(
+                sender_address,
+                self.next_nonce(sender_address),
+                calldata,
+            )),
+            false,
+        );
+        self.txs.push(tx);
+        self
+    }
+
+    // Invokes a recursion of the given depth from the Cairo 1 account, whose validation recurses
+    // as deep, so that the L2 gas the transaction consumes grows with the depth.
+    pub fn invoke_recursion(mut self, depth: u64) -> Self {
+        let calldata = calldata![
+            *CAIRO1_CONTRACT_ADDRESS.0.key(), // Contract address.
+            selector_from_name("recurse").0,  // EP selector.
+            felt!(1_u8),                      // Calldata length.
+            felt!(depth)                      // Calldata: depth.
+        ];
+        let tx = ExecutableTransactionInput::Invoke(
+            InvokeTransaction::V3(invoke_v3(
+                *CAIRO1_ACCOUNT_ADDRESS,
+                self.next_nonce(*CAIRO1_ACCOUNT_ADDRESS),
+                calldata,
+            )),
+            false,
+        );
+        self.txs.push(tx);
+        self
+    }
+
     pub fn declare_deprecated_class(mut self, sender_address: ContractAddress) -> Self {
         let tx = ExecutableTransactionInput::DeclareV1(
             DeclareTransactionV0V1 {
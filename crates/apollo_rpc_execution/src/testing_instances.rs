@@ -2,6 +2,7 @@
 //! Utilities for generating testing instances of the execution objects.
 
 use apollo_test_utils::{auto_impl_get_test_instance, get_number_of_variants, GetTestInstance};
+use blockifier::context::AdditionalFeeTokens;
 /// Returns the storage key of a storage variable.
 pub use starknet_api::abi::abi_utils::get_storage_var_address;
 use starknet_api::block::GasPrice;
@@ -37,7 +38,9 @@ pub fn get_test_execution_config() -> ExecutionConfig {
     ExecutionConfig {
         strk_fee_contract_address: contract_address!("0x1001"),
         eth_fee_contract_address: contract_address!("0x1001"),
+        additional_fee_tokens: AdditionalFeeTokens::default(),
         default_initial_gas_cost: 10_u64.pow(10),
+        max_price_per_unit_headroom_percent: 50,
     }
 }
 
@@ -119,6 +122,7 @@ impl GetTestInstance for FunctionInvocation {
             events: Vec::<OrderedEvent>::get_test_instance(rng),
             messages: Vec::<OrderedL2ToL1Message>::get_test_instance(rng),
             execution_resources: ExecutionResources::get_test_instance(rng),
+            is_reverted: bool::get_test_instance(rng),
         }
     }
 }
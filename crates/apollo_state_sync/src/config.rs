@@ -2,10 +2,16 @@ use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::result;
 
+use apollo_central_sync::l1_to_l2_messages::L1ToL2MessagesIndexerConfig;
 use apollo_central_sync::sources::central::CentralSourceConfig;
 use apollo_central_sync::SyncConfig;
-use apollo_config::dumping::{prepend_sub_config_name, ser_optional_sub_config, SerializeConfig};
-use apollo_config::{ParamPath, SerializedParam};
+use apollo_config::dumping::{
+    prepend_sub_config_name,
+    ser_optional_sub_config,
+    ser_param,
+    SerializeConfig,
+};
+use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use apollo_network::NetworkConfig;
 use apollo_p2p_sync::client::P2pSyncClientConfig;
 use apollo_reverts::RevertConfig;
@@ -33,6 +39,8 @@ pub struct StateSyncConfig {
     pub revert_config: RevertConfig,
     #[validate]
     pub rpc_config: RpcConfig,
+    pub preconfirmed_block_polling_interval_millis: u64,
+    pub l1_to_l2_messages_indexer_config: Option<L1ToL2MessagesIndexerConfig>,
 }
 
 impl SerializeConfig for StateSyncConfig {
@@ -43,6 +51,14 @@ impl SerializeConfig for StateSyncConfig {
         config.extend(ser_optional_sub_config(&self.network_config, "network_config"));
         config.extend(prepend_sub_config_name(self.revert_config.dump(), "revert_config"));
         config.extend(prepend_sub_config_name(self.rpc_config.dump(), "rpc_config"));
+        config.extend([ser_param(
+            "preconfirmed_block_polling_interval_millis",
+            &self.preconfirmed_block_polling_interval_millis,
+            "Time to wait (in milliseconds) between requests for the block proposed by the local \
+             batcher, which is served as the pending block of the JSON-RPC server when the \
+             central sync client is off.",
+            ParamPrivacyInput::Public,
+        )]);
         config.extend(ser_optional_sub_config(
             &self.p2p_sync_client_config,
             "p2p_sync_client_config",
@@ -51,6 +67,10 @@ impl SerializeConfig for StateSyncConfig {
             &self.central_sync_client_config,
             "central_sync_client_config",
         ));
+        config.extend(ser_optional_sub_config(
+            &self.l1_to_l2_messages_indexer_config,
+            "l1_to_l2_messages_indexer_config",
+        ));
         config
     }
 }
@@ -82,6 +102,8 @@ impl Default for StateSyncConfig {
             network_config: Some(NetworkConfig { port: STATE_SYNC_TCP_PORT, ..Default::default() }),
             revert_config: RevertConfig::default(),
             rpc_config: RpcConfig::default(),
+            preconfirmed_block_polling_interval_millis: 250,
+            l1_to_l2_messages_indexer_config: None,
         }
     }
 }
@@ -1,13 +1,16 @@
 pub mod config;
+mod preconfirmed_block;
 pub mod runner;
 #[cfg(test)]
 mod test;
 
 use std::cmp::min;
 
+use apollo_batcher_types::communication::SharedBatcherClient;
 use apollo_class_manager_types::SharedClassManagerClient;
 use apollo_infra::component_definitions::{ComponentRequestHandler, ComponentStarter};
 use apollo_infra::component_server::{LocalComponentServer, RemoteComponentServer};
+use apollo_mempool_types::communication::SharedMempoolClient;
 use apollo_state_sync_types::communication::{StateSyncRequest, StateSyncResponse};
 use apollo_state_sync_types::errors::StateSyncError;
 use apollo_state_sync_types::state_sync_types::{StateSyncResult, SyncBlock};
@@ -19,6 +22,7 @@ use apollo_storage::{StorageReader, StorageTxn};
 use async_trait::async_trait;
 use futures::channel::mpsc::{channel, Sender};
 use futures::SinkExt;
+use papyrus_base_layer::ethereum_base_layer_contract::EthereumBaseLayerConfig;
 use starknet_api::block::BlockNumber;
 use starknet_api::core::{ClassHash, ContractAddress, Nonce, BLOCK_HASH_TABLE_ADDRESS};
 use starknet_api::state::{StateNumber, StorageKey};
@@ -32,11 +36,20 @@ const BUFFER_SIZE: usize = 100000;
 
 pub fn create_state_sync_and_runner(
     config: StateSyncConfig,
+    base_layer_config: EthereumBaseLayerConfig,
     class_manager_client: SharedClassManagerClient,
+    batcher_client: Option<SharedBatcherClient>,
+    mempool_client: Option<SharedMempoolClient>,
 ) -> (StateSync, StateSyncRunner) {
     let (new_block_sender, new_block_receiver) = channel(BUFFER_SIZE);
-    let (state_sync_runner, storage_reader) =
-        StateSyncRunner::new(config, new_block_receiver, class_manager_client);
+    let (state_sync_runner, storage_reader) = StateSyncRunner::new(
+        config,
+        base_layer_config,
+        new_block_receiver,
+        class_manager_client,
+        batcher_client,
+        mempool_client,
+    );
     (StateSync { storage_reader, new_block_sender }, state_sync_runner)
 }
 
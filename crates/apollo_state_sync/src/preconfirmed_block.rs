@@ -0,0 +1,353 @@
+//! Serves the block that the local batcher is proposing as the pending block of the JSON-RPC
+//! server. Nodes that run their own sequencer have no feeder gateway to poll the pending block
+//! from.
+
+#[cfg(test)]
+#[path = "preconfirmed_block_test.rs"]
+mod preconfirmed_block_test;
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use apollo_batcher_types::batcher_types::{PreconfirmedBlock, PreconfirmedTransaction};
+use apollo_batcher_types::communication::SharedBatcherClient;
+use apollo_central_sync::GENESIS_HASH;
+use apollo_starknet_client::reader::objects::block::BlockStatus;
+use apollo_starknet_client::reader::objects::pending_data::{
+    PendingBlock,
+    PendingBlockOrDeprecated,
+    PendingStateUpdate,
+};
+use apollo_starknet_client::reader::objects::state::{
+    DeclaredClassHashEntry,
+    DeployedContract,
+    ReplacedClass,
+    StateDiff,
+    StorageEntry,
+};
+use apollo_starknet_client::reader::objects::transaction::{
+    IntermediateDeclareTransaction,
+    IntermediateDeployAccountTransaction,
+    IntermediateInvokeTransaction,
+    L1HandlerTransaction,
+    L2ToL1Message,
+    ReservedDataAvailabilityMode,
+    Transaction,
+    TransactionExecutionStatus as ClientTransactionExecutionStatus,
+    TransactionReceipt,
+};
+use apollo_starknet_client::reader::PendingData;
+use apollo_storage::header::HeaderStorageReader;
+use apollo_storage::state::StateStorageReader;
+use apollo_storage::{StorageReader, StorageResult};
+use futures::never::Never;
+use starknet_api::block::{BlockHash, GasPricePerToken, StarknetVersion};
+use starknet_api::consensus_transaction::InternalConsensusTransaction;
+use starknet_api::core::{ContractAddress, GlobalRoot, SequencerContractAddress};
+use starknet_api::data_availability::L1DataAvailabilityMode;
+use starknet_api::felt;
+use starknet_api::rpc_transaction::{
+    InternalRpcTransactionWithoutTxHash,
+    RpcDeployAccountTransaction,
+    RpcInvokeTransaction,
+};
+use starknet_api::state::StateNumber;
+use starknet_api::transaction::fields::ValidResourceBounds;
+use starknet_api::transaction::{
+    TransactionExecutionStatus,
+    TransactionOffsetInBlock,
+    TransactionVersion,
+};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+/// The pending data to serve when there is no block to expose as pending. It is ignored by the
+/// JSON-RPC server unless the storage is empty.
+pub(crate) fn empty_pending_data() -> PendingData {
+    PendingData {
+        // The pending data might change later to DeprecatedPendingBlock, depending on the
+        // response from the feeder gateway.
+        block: PendingBlockOrDeprecated::Current(PendingBlock {
+            parent_block_hash: BlockHash(felt!(GENESIS_HASH)),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Periodically replaces the pending data with the block that the batcher is proposing.
+pub(crate) async fn run_preconfirmed_block_poller(
+    batcher_client: SharedBatcherClient,
+    storage_reader: StorageReader,
+    pending_data: Arc<RwLock<PendingData>>,
+    polling_interval: Duration,
+) -> Never {
+    loop {
+        tokio::time::sleep(polling_interval).await;
+        let preconfirmed_block = match batcher_client.get_preconfirmed_block().await {
+            Ok(response) => response.preconfirmed_block,
+            Err(err) => {
+                debug!("Failed to get the preconfirmed block from the batcher: {err}");
+                continue;
+            }
+        };
+        let new_pending_data = match preconfirmed_block {
+            Some(preconfirmed_block) => {
+                match read_and_convert_preconfirmed_block(&storage_reader, preconfirmed_block) {
+                    Ok(maybe_pending_data) => maybe_pending_data.unwrap_or_else(empty_pending_data),
+                    Err(err) => {
+                        warn!("Failed to convert the preconfirmed block to pending data: {err}");
+                        continue;
+                    }
+                }
+            }
+            None => empty_pending_data(),
+        };
+        *pending_data.write().await = new_pending_data;
+    }
+}
+
+// Returns `None` if the state of the parent block is not synced yet, in which case the
+// preconfirmed block can't be served on top of the storage.
+fn read_and_convert_preconfirmed_block(
+    storage_reader: &StorageReader,
+    preconfirmed_block: PreconfirmedBlock,
+) -> StorageResult<Option<PendingData>> {
+    let txn = storage_reader.begin_ro_txn()?;
+    let Some(parent_block_number) = preconfirmed_block.block_info.block_number.prev() else {
+        return Ok(Some(preconfirmed_block_to_pending_data(
+            preconfirmed_block,
+            BlockHash(felt!(GENESIS_HASH)),
+            GlobalRoot::default(),
+            &HashSet::new(),
+        )));
+    };
+    if txn.get_state_marker()? <= parent_block_number {
+        return Ok(None);
+    }
+    let Some(parent_header) = txn.get_block_header(parent_block_number)? else {
+        return Ok(None);
+    };
+
+    // Contracts that already exist had their class replaced rather than being deployed.
+    let state_number = StateNumber::unchecked_right_after_block(parent_block_number);
+    let state_reader = txn.get_state_reader()?;
+    let mut replaced_contracts = HashSet::new();
+    for address in preconfirmed_block.state_diff.deployed_contracts.keys() {
+        if state_reader.get_class_hash_at(state_number, address)?.is_some() {
+            replaced_contracts.insert(*address);
+        }
+    }
+
+    Ok(Some(preconfirmed_block_to_pending_data(
+        preconfirmed_block,
+        parent_header.block_hash,
+        parent_header.block_header_without_hash.state_root,
+        &replaced_contracts,
+    )))
+}
+
+pub(crate) fn preconfirmed_block_to_pending_data(
+    preconfirmed_block: PreconfirmedBlock,
+    parent_block_hash: BlockHash,
+    old_root: GlobalRoot,
+    replaced_contracts: &HashSet<ContractAddress>,
+) -> PendingData {
+    let PreconfirmedBlock { block_info, transactions, state_diff } = preconfirmed_block;
+    let (transactions, transaction_receipts) = transactions
+        .into_iter()
+        .enumerate()
+        .map(|(index, preconfirmed_transaction)| {
+            to_client_transaction_and_receipt(
+                TransactionOffsetInBlock(index),
+                preconfirmed_transaction,
+            )
+        })
+        .unzip();
+    let gas_price_per_token =
+        |price_in_wei, price_in_fri| GasPricePerToken { price_in_fri, price_in_wei };
+    let eth_gas_prices = &block_info.gas_prices.eth_gas_prices;
+    let strk_gas_prices = &block_info.gas_prices.strk_gas_prices;
+    let block = PendingBlock {
+        parent_block_hash,
+        status: BlockStatus::Pending,
+        l1_gas_price: gas_price_per_token(
+            eth_gas_prices.l1_gas_price.get(),
+            strk_gas_prices.l1_gas_price.get(),
+        ),
+        l1_data_gas_price: gas_price_per_token(
+            eth_gas_prices.l1_data_gas_price.get(),
+            strk_gas_prices.l1_data_gas_price.get(),
+        ),
+        l2_gas_price: gas_price_per_token(
+            eth_gas_prices.l2_gas_price.get(),
+            strk_gas_prices.l2_gas_price.get(),
+        ),
+        transactions,
+        timestamp: block_info.block_timestamp,
+        sequencer_address: SequencerContractAddress(block_info.sequencer_address),
+        transaction_receipts,
+        starknet_version: StarknetVersion::LATEST.to_string(),
+        l1_da_mode: if block_info.use_kzg_da {
+            L1DataAvailabilityMode::Blob
+        } else {
+            L1DataAvailabilityMode::Calldata
+        },
+        ..Default::default()
+    };
+
+    let (replaced_classes, deployed_contracts) = state_diff
+        .deployed_contracts
+        .into_iter()
+        .partition::<Vec<_>, _>(|(address, _)| replaced_contracts.contains(address));
+    let state_diff = StateDiff {
+        storage_diffs: state_diff
+            .storage_diffs
+            .into_iter()
+            .map(|(address, storage_diff)| {
+                let entries =
+                    storage_diff.into_iter().map(|(key, value)| StorageEntry { key, value });
+                (address, entries.collect())
+            })
+            .collect(),
+        deployed_contracts: deployed_contracts
+            .into_iter()
+            .map(|(address, class_hash)| DeployedContract { address, class_hash })
+            .collect(),
+        declared_classes: state_diff
+            .declared_classes
+            .into_iter()
+            .map(|(class_hash, compiled_class_hash)| DeclaredClassHashEntry {
+                class_hash,
+                compiled_class_hash,
+            })
+            .collect(),
+        old_declared_contracts: state_diff.deprecated_declared_classes,
+        nonces: state_diff.nonces,
+        replaced_classes: replaced_classes
+            .into_iter()
+            .map(|(address, class_hash)| ReplacedClass { address, class_hash })
+            .collect(),
+    };
+
+    PendingData {
+        block: PendingBlockOrDeprecated::Current(block),
+        state_update: PendingStateUpdate { old_root, state_diff },
+    }
+}
+
+fn to_client_transaction_and_receipt(
+    transaction_index: TransactionOffsetInBlock,
+    preconfirmed_transaction: PreconfirmedTransaction,
+) -> (Transaction, TransactionReceipt) {
+    let PreconfirmedTransaction {
+        transaction,
+        execution_status,
+        actual_fee,
+        events,
+        messages_sent,
+    } = preconfirmed_transaction;
+    let transaction_hash = transaction.tx_hash();
+    let (execution_status, revert_error) = match execution_status {
+        TransactionExecutionStatus::Succeeded => {
+            (ClientTransactionExecutionStatus::Succeeded, None)
+        }
+        TransactionExecutionStatus::Reverted(status) => {
+            (ClientTransactionExecutionStatus::Reverted, Some(status.revert_reason))
+        }
+    };
+    let receipt = TransactionReceipt {
+        transaction_index,
+        transaction_hash,
+        l2_to_l1_messages: messages_sent
+            .into_iter()
+            .map(|message| L2ToL1Message {
+                from_address: message.from_address,
+                to_address: message.to_address,
+                payload: message.payload,
+            })
+            .collect(),
+        events,
+        actual_fee,
+        execution_status,
+        revert_error,
+        ..Default::default()
+    };
+    (to_client_transaction(transaction), receipt)
+}
+
+fn to_client_transaction(transaction: InternalConsensusTransaction) -> Transaction {
+    // The sequencer only accepts transactions with the L1 data availability mode.
+    let data_availability_mode = Some(ReservedDataAvailabilityMode::Reserved);
+    let transaction_hash = transaction.tx_hash();
+    match transaction {
+        InternalConsensusTransaction::RpcTransaction(rpc_transaction) => match rpc_transaction.tx {
+            InternalRpcTransactionWithoutTxHash::Declare(tx) => {
+                Transaction::Declare(IntermediateDeclareTransaction {
+                    resource_bounds: Some(ValidResourceBounds::AllResources(tx.resource_bounds)),
+                    tip: Some(tx.tip),
+                    signature: tx.signature,
+                    nonce: tx.nonce,
+                    class_hash: tx.class_hash,
+                    compiled_class_hash: Some(tx.compiled_class_hash),
+                    sender_address: tx.sender_address,
+                    nonce_data_availability_mode: data_availability_mode.clone(),
+                    fee_data_availability_mode: data_availability_mode,
+                    paymaster_data: Some(tx.paymaster_data),
+                    account_deployment_data: Some(tx.account_deployment_data),
+                    max_fee: None,
+                    version: TransactionVersion::THREE,
+                    transaction_hash,
+                })
+            }
+            InternalRpcTransactionWithoutTxHash::DeployAccount(deploy_account_tx) => {
+                let RpcDeployAccountTransaction::V3(tx) = deploy_account_tx.tx;
+                Transaction::DeployAccount(IntermediateDeployAccountTransaction {
+                    resource_bounds: Some(ValidResourceBounds::AllResources(tx.resource_bounds)),
+                    tip: Some(tx.tip),
+                    signature: tx.signature,
+                    nonce: tx.nonce,
+                    class_hash: tx.class_hash,
+                    contract_address_salt: tx.contract_address_salt,
+                    constructor_calldata: tx.constructor_calldata,
+                    nonce_data_availability_mode: data_availability_mode.clone(),
+                    fee_data_availability_mode: data_availability_mode,
+                    paymaster_data: Some(tx.paymaster_data),
+                    sender_address: deploy_account_tx.contract_address,
+                    max_fee: None,
+                    transaction_hash,
+                    version: TransactionVersion::THREE,
+                })
+            }
+            InternalRpcTransactionWithoutTxHash::Invoke(RpcInvokeTransaction::V3(tx)) => {
+                Transaction::Invoke(IntermediateInvokeTransaction {
+                    resource_bounds: Some(ValidResourceBounds::AllResources(tx.resource_bounds)),
+                    tip: Some(tx.tip),
+                    calldata: tx.calldata,
+                    sender_address: tx.sender_address,
+                    entry_point_selector: None,
+                    nonce: Some(tx.nonce),
+                    max_fee: None,
+                    signature: tx.signature,
+                    nonce_data_availability_mode: data_availability_mode.clone(),
+                    fee_data_availability_mode: data_availability_mode,
+                    paymaster_data: Some(tx.paymaster_data),
+                    account_deployment_data: Some(tx.account_deployment_data),
+                    transaction_hash,
+                    version: TransactionVersion::THREE,
+                })
+            }
+        },
+        InternalConsensusTransaction::L1Handler(l1_handler_tx) => {
+            Transaction::L1Handler(L1HandlerTransaction {
+                transaction_hash,
+                version: l1_handler_tx.tx.version,
+                nonce: l1_handler_tx.tx.nonce,
+                contract_address: l1_handler_tx.tx.contract_address,
+                entry_point_selector: l1_handler_tx.tx.entry_point_selector,
+                calldata: l1_handler_tx.tx.calldata,
+            })
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use std::collections::HashSet;
+
+use apollo_batcher_types::batcher_types::{PreconfirmedBlock, PreconfirmedTransaction};
+use apollo_starknet_client::reader::objects::pending_data::PendingBlockOrDeprecated;
+use apollo_starknet_client::reader::objects::state::{DeployedContract, ReplacedClass};
+use apollo_starknet_client::reader::objects::transaction::TransactionExecutionStatus as ClientTransactionExecutionStatus;
+use indexmap::IndexMap;
+use starknet_api::block::{BlockHash, BlockInfo, BlockNumber};
+use starknet_api::consensus_transaction::InternalConsensusTransaction;
+use starknet_api::core::GlobalRoot;
+use starknet_api::state::ThinStateDiff;
+use starknet_api::test_utils::invoke::{internal_invoke_tx, InvokeTxArgs};
+use starknet_api::transaction::fields::Fee;
+use starknet_api::transaction::{
+    RevertedTransactionExecutionStatus,
+    TransactionExecutionStatus,
+    TransactionHash,
+};
+use starknet_api::{class_hash, contract_address, felt, nonce, tx_hash};
+
+use crate::preconfirmed_block::preconfirmed_block_to_pending_data;
+
+fn preconfirmed_transaction(
+    tx_hash: TransactionHash,
+    execution_status: TransactionExecutionStatus,
+) -> PreconfirmedTransaction {
+    PreconfirmedTransaction {
+        transaction: InternalConsensusTransaction::RpcTransaction(internal_invoke_tx(
+            InvokeTxArgs { tx_hash, ..Default::default() },
+        )),
+        execution_status,
+        actual_fee: Fee(1),
+        events: vec![],
+        messages_sent: vec![],
+    }
+}
+
+#[test]
+fn preconfirmed_block_to_pending_data_conversion() {
+    let deployed_address = contract_address!("0x1");
+    let replaced_address = contract_address!("0x2");
+    let revert_reason = "reverted".to_string();
+    let preconfirmed_block = PreconfirmedBlock {
+        block_info: BlockInfo { block_number: BlockNumber(5), ..Default::default() },
+        transactions: vec![
+            preconfirmed_transaction(tx_hash!(1), TransactionExecutionStatus::Succeeded),
+            preconfirmed_transaction(
+                tx_hash!(2),
+                TransactionExecutionStatus::Reverted(RevertedTransactionExecutionStatus {
+                    revert_reason: revert_reason.clone(),
+                }),
+            ),
+        ],
+        state_diff: ThinStateDiff {
+            deployed_contracts: IndexMap::from([
+                (deployed_address, class_hash!("0x10")),
+                (replaced_address, class_hash!("0x20")),
+            ]),
+            nonces: IndexMap::from([(deployed_address, nonce!(1_u64))]),
+            ..Default::default()
+        },
+    };
+    let parent_block_hash = BlockHash(felt!("0x1234"));
+    let old_root = GlobalRoot(felt!("0x5678"));
+
+    let pending_data = preconfirmed_block_to_pending_data(
+        preconfirmed_block,
+        parent_block_hash,
+        old_root,
+        &HashSet::from([replaced_address]),
+    );
+
+    let PendingBlockOrDeprecated::Current(block) = pending_data.block else {
+        panic!("Expected the current pending block format.");
+    };
+    assert_eq!(block.parent_block_hash, parent_block_hash);
+    let tx_hashes: Vec<_> = block.transactions.iter().map(|tx| tx.transaction_hash()).collect();
+    assert_eq!(tx_hashes, vec![tx_hash!(1), tx_hash!(2)]);
+
+    let receipts = &block.transaction_receipts;
+    assert_eq!(receipts[0].transaction_hash, tx_hash!(1));
+    assert_eq!(receipts[0].execution_status, ClientTransactionExecutionStatus::Succeeded);
+    assert_eq!(receipts[0].revert_error, None);
+    assert_eq!(receipts[1].transaction_index.0, 1);
+    assert_eq!(receipts[1].execution_status, ClientTransactionExecutionStatus::Reverted);
+    assert_eq!(receipts[1].revert_error, Some(revert_reason));
+    assert_eq!(receipts[1].actual_fee, Fee(1));
+
+    let state_update = pending_data.state_update;
+    assert_eq!(state_update.old_root, old_root);
+    assert_eq!(
+        state_update.state_diff.deployed_contracts,
+        vec![DeployedContract { address: deployed_address, class_hash: class_hash!("0x10") }]
+    );
+    assert_eq!(
+        state_update.state_diff.replaced_classes,
+        vec![ReplacedClass { address: replaced_address, class_hash: class_hash!("0x20") }]
+    );
+    assert_eq!(state_update.state_diff.nonces, IndexMap::from([(deployed_address, nonce!(1_u64))]));
+}
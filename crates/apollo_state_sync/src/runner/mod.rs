@@ -2,17 +2,18 @@
 mod test;
 
 use std::sync::Arc;
+use std::time::Duration;
 
+use apollo_batcher_types::communication::SharedBatcherClient;
+use apollo_central_sync::l1_to_l2_messages::L1ToL2MessagesIndexer;
+use apollo_central_sync::sources::base_layer::EthereumBaseLayerSource;
 use apollo_central_sync::sources::central::{CentralError, CentralSource};
 use apollo_central_sync::sources::pending::PendingSource;
-use apollo_central_sync::{
-    StateSync as CentralStateSync,
-    StateSyncError as CentralStateSyncError,
-    GENESIS_HASH,
-};
+use apollo_central_sync::{StateSync as CentralStateSync, StateSyncError as CentralStateSyncError};
 use apollo_class_manager_types::SharedClassManagerClient;
 use apollo_infra::component_definitions::ComponentStarter;
 use apollo_infra::component_server::WrapperServer;
+use apollo_mempool_types::communication::SharedMempoolClient;
 use apollo_network::network_manager::metrics::{NetworkMetrics, SqmrNetworkMetrics};
 use apollo_network::network_manager::{NetworkError, NetworkManager};
 use apollo_p2p_sync::client::{
@@ -25,10 +26,6 @@ use apollo_p2p_sync::server::{P2pSyncServer, P2pSyncServerChannels};
 use apollo_p2p_sync::{Protocol, BUFFER_SIZE};
 use apollo_reverts::{revert_block, revert_blocks_and_eternal_pending};
 use apollo_rpc::{run_server, RpcConfig};
-use apollo_starknet_client::reader::objects::pending_data::{
-    PendingBlock,
-    PendingBlockOrDeprecated,
-};
 use apollo_starknet_client::reader::PendingData;
 use apollo_state_sync_metrics::metrics::{
     P2P_SYNC_NUM_ACTIVE_INBOUND_SESSIONS,
@@ -44,26 +41,33 @@ use apollo_state_sync_metrics::metrics::{
     STATE_SYNC_STATE_MARKER,
 };
 use apollo_state_sync_types::state_sync_types::SyncBlock;
+use apollo_storage::base_layer::L1ToL2Messages;
 use apollo_storage::body::BodyStorageReader;
 use apollo_storage::class_manager::ClassManagerStorageReader;
 use apollo_storage::compiled_class::CasmStorageReader;
 use apollo_storage::db::TransactionKind;
 use apollo_storage::header::HeaderStorageReader;
+use apollo_storage::migrations::migrate_storage;
 use apollo_storage::state::StateStorageReader;
 use apollo_storage::{open_storage, StorageConfig, StorageReader, StorageTxn, StorageWriter};
 use async_trait::async_trait;
 use futures::channel::mpsc::Receiver;
 use futures::future::{self, pending, BoxFuture};
 use futures::never::Never;
+use futures::stream::{self, BoxStream};
 use futures::{FutureExt, StreamExt};
+use papyrus_base_layer::ethereum_base_layer_contract::{
+    EthereumBaseLayerConfig,
+    EthereumBaseLayerContract,
+};
 use papyrus_common::pending_classes::PendingClasses;
-use starknet_api::block::{BlockHash, BlockHashAndNumber, BlockNumber};
-use starknet_api::felt;
+use starknet_api::block::{BlockHashAndNumber, BlockNumber};
 use tokio::sync::RwLock;
 use tracing::info_span;
 use tracing::instrument::Instrument;
 
 use crate::config::{CentralSyncClientConfig, StateSyncConfig};
+use crate::preconfirmed_block::{empty_pending_data, run_preconfirmed_block_poller};
 
 pub struct StateSyncRunner {
     network_future: BoxFuture<'static, Result<(), NetworkError>>,
@@ -72,6 +76,7 @@ pub struct StateSyncRunner {
     p2p_sync_server_future: BoxFuture<'static, Never>,
     central_sync_client_future: BoxFuture<'static, Result<(), CentralStateSyncError>>,
     new_block_dev_null_future: BoxFuture<'static, Never>,
+    preconfirmed_block_poller_future: BoxFuture<'static, Never>,
     rpc_server_future: BoxFuture<'static, ()>,
 }
 
@@ -94,6 +99,9 @@ impl ComponentStarter for StateSyncRunner {
             _never = &mut self.new_block_dev_null_future => {
                 unreachable!("Return type Never should never be constructed")
             }
+            _never = &mut self.preconfirmed_block_poller_future => {
+                unreachable!("Return type Never should never be constructed")
+            }
             _ = &mut self.rpc_server_future => {
                 panic!("JSON_RPC server stopped unexpectedly");
             }
@@ -111,18 +119,17 @@ pub struct StateSyncResources {
 
 impl StateSyncResources {
     pub fn new(storage_config: &StorageConfig) -> Self {
+        migrate_storage(storage_config.clone()).expect("StateSyncRunner failed migrating storage");
         let (storage_reader, storage_writer) =
             open_storage(storage_config.clone()).expect("StateSyncRunner failed opening storage");
+        if let Some(storage_pruner) = storage_writer.pruner() {
+            std::thread::Builder::new()
+                .name("storage_pruner".to_string())
+                .spawn(move || storage_pruner.run())
+                .expect("StateSyncRunner failed spawning the storage pruner");
+        }
         let shared_highest_block = Arc::new(RwLock::new(None));
-        let pending_data = Arc::new(RwLock::new(PendingData {
-            // The pending data might change later to DeprecatedPendingBlock, depending on the
-            // response from the feeder gateway.
-            block: PendingBlockOrDeprecated::Current(PendingBlock {
-                parent_block_hash: BlockHash(felt!(GENESIS_HASH)),
-                ..Default::default()
-            }),
-            ..Default::default()
-        }));
+        let pending_data = Arc::new(RwLock::new(empty_pending_data()));
         let pending_classes = Arc::new(RwLock::new(PendingClasses::default()));
         Self { storage_reader, storage_writer, shared_highest_block, pending_data, pending_classes }
     }
@@ -131,8 +138,11 @@ impl StateSyncResources {
 impl StateSyncRunner {
     pub fn new(
         config: StateSyncConfig,
+        base_layer_config: EthereumBaseLayerConfig,
         new_block_receiver: Receiver<SyncBlock>,
         class_manager_client: SharedClassManagerClient,
+        batcher_client: Option<SharedBatcherClient>,
+        mempool_client: Option<SharedMempoolClient>,
     ) -> (Self, StorageReader) {
         let StateSyncConfig {
             storage_config,
@@ -141,6 +151,8 @@ impl StateSyncRunner {
             network_config,
             revert_config,
             rpc_config,
+            preconfirmed_block_polling_interval_millis,
+            l1_to_l2_messages_indexer_config,
         } = config;
 
         let StateSyncResources {
@@ -191,6 +203,7 @@ impl StateSyncRunner {
                     p2p_sync_server_future: pending().boxed(),
                     central_sync_client_future: pending().boxed(),
                     new_block_dev_null_future: pending().boxed(),
+                    preconfirmed_block_poller_future: pending().boxed(),
                     rpc_server_future: pending().boxed(),
                 },
                 storage_reader,
@@ -214,8 +227,20 @@ impl StateSyncRunner {
             )
         });
 
+        // The messages sent from the base layer to L2 are indexed by whichever sync client is on,
+        // since it owns the storage writer.
+        let l1_to_l2_messages_indexer = l1_to_l2_messages_indexer_config.map(|indexer_config| {
+            L1ToL2MessagesIndexer::new(
+                indexer_config,
+                storage_reader.clone(),
+                EthereumBaseLayerContract::new(base_layer_config),
+                storage_config.db_config.chain_id.clone(),
+            )
+        });
+
         // Creating the sync clients futures
         // Exactly one of the sync clients must be turned on.
+        let is_central_sync_client = central_sync_client_config.is_some();
         let (p2p_sync_client_future, central_sync_client_future, new_block_dev_null_future) =
             match (p2p_sync_client_config, central_sync_client_config) {
                 (Some(p2p_sync_client_config), None) => {
@@ -230,6 +255,10 @@ impl StateSyncRunner {
                         p2p_sync_client_config,
                         network_manager,
                         new_block_receiver,
+                        l1_to_l2_messages_indexer.as_ref().map_or_else(
+                            || stream::pending().boxed(),
+                            L1ToL2MessagesIndexer::stream,
+                        ),
                         class_manager_client.clone(),
                     );
 
@@ -247,6 +276,7 @@ impl StateSyncRunner {
                         pending_classes.clone(),
                         central_sync_client_config,
                         class_manager_client.clone(),
+                        l1_to_l2_messages_indexer,
                     );
 
                     let p2p_sync_client_future = future::pending().boxed();
@@ -288,6 +318,19 @@ impl StateSyncRunner {
                 (p2p_sync_server_future, network_future)
             }
         };
+        // Serving the block proposed by the local batcher as the pending block, unless the pending
+        // block is polled from the feeder gateway by the central sync client.
+        let preconfirmed_block_poller_future = match batcher_client {
+            Some(batcher_client) if !is_central_sync_client => run_preconfirmed_block_poller(
+                batcher_client,
+                storage_reader.clone(),
+                pending_data.clone(),
+                Duration::from_millis(preconfirmed_block_polling_interval_millis),
+            )
+            .boxed(),
+            _ => future::pending().boxed(),
+        };
+
         // Creating the JSON-RPC server future
         let rpc_server_future = spawn_rpc_server(
             &rpc_config,
@@ -296,6 +339,7 @@ impl StateSyncRunner {
             pending_classes.clone(),
             storage_reader.clone(),
             Some(class_manager_client.clone()),
+            mempool_client,
         );
 
         (
@@ -305,6 +349,7 @@ impl StateSyncRunner {
                 p2p_sync_server_future,
                 central_sync_client_future,
                 new_block_dev_null_future,
+                preconfirmed_block_poller_future,
                 rpc_server_future,
             },
             storage_reader,
@@ -317,6 +362,7 @@ impl StateSyncRunner {
         p2p_sync_client_config: P2pSyncClientConfig,
         network_manager: &mut NetworkManager,
         new_block_receiver: Receiver<SyncBlock>,
+        l1_to_l2_messages_stream: BoxStream<'static, L1ToL2Messages>,
         class_manager_client: SharedClassManagerClient,
     ) -> P2pSyncClient {
         let header_client_sender = network_manager
@@ -339,6 +385,7 @@ impl StateSyncRunner {
             storage_writer,
             p2p_sync_client_channels,
             new_block_receiver.boxed(),
+            l1_to_l2_messages_stream,
             class_manager_client.clone(),
         )
     }
@@ -368,6 +415,7 @@ impl StateSyncRunner {
         P2pSyncServer::new(storage_reader, p2p_sync_server_channels, class_manager_client)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn new_central_state_sync_client(
         storage_reader: StorageReader,
         storage_writer: StorageWriter,
@@ -376,6 +424,7 @@ impl StateSyncRunner {
         pending_classes: Arc<RwLock<PendingClasses>>,
         central_sync_client_config: CentralSyncClientConfig,
         class_manager_client: SharedClassManagerClient,
+        l1_to_l2_messages_indexer: Option<L1ToL2MessagesIndexer<EthereumBaseLayerSource>>,
     ) -> CentralStateSync {
         let CentralSyncClientConfig { sync_config, central_source_config } =
             central_sync_client_config;
@@ -398,6 +447,7 @@ impl StateSyncRunner {
             storage_reader.clone(),
             storage_writer,
             Some(class_manager_client),
+            l1_to_l2_messages_indexer,
         )
     }
 }
@@ -423,6 +473,7 @@ fn spawn_rpc_server(
     pending_classes: Arc<RwLock<PendingClasses>>,
     storage_reader: StorageReader,
     class_manager_client: Option<SharedClassManagerClient>,
+    mempool_client: Option<SharedMempoolClient>,
 ) -> BoxFuture<'static, ()> {
     let rpc_config = rpc_config.clone();
     async move {
@@ -434,6 +485,7 @@ fn spawn_rpc_server(
             storage_reader,
             VERSION_FULL,
             class_manager_client,
+            mempool_client,
         )
         .await
         .expect("Failed running JSON-RPC server");
@@ -14,6 +14,7 @@ fn run_panics_when_network_future_returns() {
     let p2p_sync_server_future = pending().boxed();
     let central_sync_client_future = pending().boxed();
     let new_block_dev_null_future = pending().boxed();
+    let preconfirmed_block_poller_future = pending().boxed();
     let rpc_server_future = pending().boxed();
     let mut state_sync_runner = StateSyncRunner {
         network_future,
@@ -21,6 +22,7 @@ fn run_panics_when_network_future_returns() {
         p2p_sync_server_future,
         central_sync_client_future,
         new_block_dev_null_future,
+        preconfirmed_block_poller_future,
         rpc_server_future,
     };
     state_sync_runner.start().now_or_never().unwrap();
@@ -35,6 +37,7 @@ fn run_panics_when_network_future_returns_error() {
     let p2p_sync_server_future = pending().boxed();
     let central_sync_client_future = pending().boxed();
     let new_block_dev_null_future = pending().boxed();
+    let preconfirmed_block_poller_future = pending().boxed();
     let rpc_server_future = pending().boxed();
     let mut state_sync_runner = StateSyncRunner {
         network_future,
@@ -42,6 +45,7 @@ fn run_panics_when_network_future_returns_error() {
         p2p_sync_server_future,
         central_sync_client_future,
         new_block_dev_null_future,
+        preconfirmed_block_poller_future,
         rpc_server_future,
     };
     state_sync_runner.start().now_or_never().unwrap();
@@ -55,6 +59,7 @@ fn run_panics_when_sync_client_future_returns_error() {
     let p2p_sync_server_future = pending().boxed();
     let central_sync_client_future = pending().boxed();
     let new_block_dev_null_future = pending().boxed();
+    let preconfirmed_block_poller_future = pending().boxed();
     let rpc_server_future = pending().boxed();
     let mut state_sync_runner = StateSyncRunner {
         network_future,
@@ -62,6 +67,7 @@ fn run_panics_when_sync_client_future_returns_error() {
         p2p_sync_server_future,
         central_sync_client_future,
         new_block_dev_null_future,
+        preconfirmed_block_poller_future,
         rpc_server_future,
     };
     state_sync_runner.start().now_or_never().unwrap();
@@ -5,6 +5,11 @@
 //! The common use case is Ethereum, but it can be other blockchains as well (including another
 //! Starknet network).
 //!
+//! Besides the proved blocks, the storage indexes the messages that transactions of the base layer
+//! sent to L2, so that the L1 handler transactions of an L1 transaction can be found by its hash.
+//! The indexed base layer blocks form a single range, which grows upwards as new blocks are
+//! indexed and downwards as older blocks are backfilled.
+//!
 //! Import [`BaseLayerStorageReader`] and [`BaseLayerStorageWriter`] to read and write data related
 //! to the base layer using a [`StorageTxn`].
 //! # Example
@@ -39,16 +44,46 @@
 #[path = "base_layer_test.rs"]
 mod base_layer_test;
 
+use std::ops::RangeInclusive;
+
 use starknet_api::block::BlockNumber;
+use starknet_api::core::L1TransactionHash;
+use starknet_api::transaction::TransactionHash;
 
-use crate::db::table_types::Table;
+use crate::db::table_types::{DbCursorTrait, Table};
 use crate::db::{TransactionKind, RW};
-use crate::{MarkerKind, StorageResult, StorageTxn};
+use crate::{MarkerKind, StorageError, StorageResult, StorageTxn};
+
+/// The messages to L2 that were sent in a range of base layer blocks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct L1ToL2Messages {
+    /// The base layer blocks the messages were sent in.
+    pub l1_blocks: RangeInclusive<u64>,
+    /// The hash of the L1 transaction that sent each message and the hash of its L1 handler
+    /// transaction, in the order the messages were sent.
+    pub messages: Vec<(L1TransactionHash, TransactionHash)>,
+}
 
 /// Interface for reading data related to the base layer.
 pub trait BaseLayerStorageReader {
     /// The block number marker is the first block number that doesn't exist yet in the base layer.
     fn get_base_layer_block_marker(&self) -> StorageResult<BlockNumber>;
+
+    /// Returns the first block of the base layer whose messages to L2 were not indexed, or `None`
+    /// if no block was indexed yet.
+    fn get_l1_to_l2_messages_marker(&self) -> StorageResult<Option<u64>>;
+
+    /// Returns the lowest block of the base layer whose messages to L2 were indexed, or `None` if
+    /// no block was indexed yet.
+    fn get_l1_to_l2_messages_backfill_marker(&self) -> StorageResult<Option<u64>>;
+
+    /// Returns the hashes of the L1 handler transactions of the messages that the given L1
+    /// transaction sent, in the order they were sent. Returns an empty vector if the transaction
+    /// sent no messages or wasn't indexed.
+    fn get_l1_handler_transaction_hashes(
+        &self,
+        l1_transaction_hash: &L1TransactionHash,
+    ) -> StorageResult<Vec<TransactionHash>>;
 }
 
 /// Interface for writing data related to the base layer.
@@ -66,6 +101,11 @@ where
         self,
         reverted_block_number: BlockNumber,
     ) -> StorageResult<Self>;
+
+    /// Indexes the messages to L2 that were sent in the given base layer blocks. The blocks must
+    /// either directly follow the indexed ones or directly precede them, unless no block was
+    /// indexed yet. Indexing a message again has no effect.
+    fn index_l1_to_l2_messages(self, l1_to_l2_messages: &L1ToL2Messages) -> StorageResult<Self>;
 }
 
 impl<Mode: TransactionKind> BaseLayerStorageReader for StorageTxn<'_, Mode> {
@@ -73,6 +113,38 @@ impl<Mode: TransactionKind> BaseLayerStorageReader for StorageTxn<'_, Mode> {
         let markers_table = self.open_table(&self.tables.markers)?;
         Ok(markers_table.get(&self.txn, &MarkerKind::BaseLayerBlock)?.unwrap_or_default())
     }
+
+    fn get_l1_to_l2_messages_marker(&self) -> StorageResult<Option<u64>> {
+        let markers_table = self.open_table(&self.tables.markers)?;
+        Ok(markers_table
+            .get(&self.txn, &MarkerKind::L1ToL2Messages)?
+            .map(|l1_block_number| l1_block_number.0))
+    }
+
+    fn get_l1_to_l2_messages_backfill_marker(&self) -> StorageResult<Option<u64>> {
+        let markers_table = self.open_table(&self.tables.markers)?;
+        Ok(markers_table
+            .get(&self.txn, &MarkerKind::L1ToL2MessagesBackfill)?
+            .map(|l1_block_number| l1_block_number.0))
+    }
+
+    fn get_l1_handler_transaction_hashes(
+        &self,
+        l1_transaction_hash: &L1TransactionHash,
+    ) -> StorageResult<Vec<TransactionHash>> {
+        let l1_to_l2_messages_table = self.open_table(&self.tables.l1_to_l2_messages)?;
+        let mut cursor = l1_to_l2_messages_table.cursor(&self.txn)?;
+        let mut l1_handler_transaction_hashes = Vec::new();
+        let mut current = cursor.lower_bound(&(*l1_transaction_hash, 0))?;
+        while let Some(((got_l1_transaction_hash, _), l1_handler_transaction_hash)) = current {
+            if got_l1_transaction_hash != *l1_transaction_hash {
+                break;
+            }
+            l1_handler_transaction_hashes.push(l1_handler_transaction_hash);
+            current = cursor.next()?;
+        }
+        Ok(l1_handler_transaction_hashes)
+    }
 }
 
 impl BaseLayerStorageWriter for StorageTxn<'_, RW> {
@@ -94,4 +166,52 @@ impl BaseLayerStorageWriter for StorageTxn<'_, RW> {
             Ok(self)
         }
     }
+
+    fn index_l1_to_l2_messages(self, l1_to_l2_messages: &L1ToL2Messages) -> StorageResult<Self> {
+        let L1ToL2Messages { l1_blocks, messages } = l1_to_l2_messages;
+        let (first, last) = (*l1_blocks.start(), *l1_blocks.end());
+        let (marker_kind, marker) = match (
+            self.get_l1_to_l2_messages_backfill_marker()?,
+            self.get_l1_to_l2_messages_marker()?,
+        ) {
+            (Some(_), Some(next)) if first == next => (MarkerKind::L1ToL2Messages, last + 1),
+            (Some(lowest), Some(_)) if last + 1 == lowest => {
+                (MarkerKind::L1ToL2MessagesBackfill, first)
+            }
+            (Some(_), Some(next)) => {
+                return Err(StorageError::MarkerMismatch {
+                    expected: BlockNumber(next),
+                    found: BlockNumber(first),
+                });
+            }
+            _ => {
+                let markers_table = self.open_table(&self.tables.markers)?;
+                markers_table.upsert(
+                    &self.txn,
+                    &MarkerKind::L1ToL2MessagesBackfill,
+                    &BlockNumber(first),
+                )?;
+                (MarkerKind::L1ToL2Messages, last + 1)
+            }
+        };
+        let l1_to_l2_messages_table = self.open_table(&self.tables.l1_to_l2_messages)?;
+        let mut message_index = 0;
+        let mut previous_l1_transaction_hash = None;
+        for (l1_transaction_hash, l1_handler_transaction_hash) in messages {
+            // The messages of an L1 transaction are consecutive.
+            message_index = match previous_l1_transaction_hash {
+                Some(previous) if previous == l1_transaction_hash => message_index + 1,
+                _ => 0,
+            };
+            previous_l1_transaction_hash = Some(l1_transaction_hash);
+            l1_to_l2_messages_table.upsert(
+                &self.txn,
+                &(*l1_transaction_hash, message_index),
+                l1_handler_transaction_hash,
+            )?;
+        }
+        let markers_table = self.open_table(&self.tables.markers)?;
+        markers_table.upsert(&self.txn, &marker_kind, &BlockNumber(marker))?;
+        Ok(self)
+    }
 }
@@ -1,7 +1,11 @@
+use assert_matches::assert_matches;
 use starknet_api::block::BlockNumber;
+use starknet_api::core::L1TransactionHash;
+use starknet_api::tx_hash;
 
-use crate::base_layer::{BaseLayerStorageReader, BaseLayerStorageWriter};
+use crate::base_layer::{BaseLayerStorageReader, BaseLayerStorageWriter, L1ToL2Messages};
 use crate::test_utils::get_test_storage;
+use crate::{StorageError, StorageWriter};
 
 #[tokio::test]
 async fn rw_base_layer_tip_marker() {
@@ -50,3 +54,84 @@ fn try_revert_base_layer_marker() {
     let cur_marker = reader.begin_ro_txn().unwrap().get_base_layer_block_marker().unwrap();
     assert_eq!(cur_marker, BlockNumber(1));
 }
+
+#[test]
+fn l1_to_l2_messages() {
+    let (reader, mut writer) = get_test_storage().0;
+    let first_l1_transaction_hash = L1TransactionHash([1; 32]);
+    let second_l1_transaction_hash = L1TransactionHash([2; 32]);
+    let unknown_l1_transaction_hash = L1TransactionHash([3; 32]);
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_l1_to_l2_messages_marker().unwrap(), None);
+    assert_eq!(txn.get_l1_to_l2_messages_backfill_marker().unwrap(), None);
+
+    let messages = vec![
+        (second_l1_transaction_hash, tx_hash!(0x22)),
+        (second_l1_transaction_hash, tx_hash!(0x21)),
+        (first_l1_transaction_hash, tx_hash!(0x11)),
+    ];
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .index_l1_to_l2_messages(&L1ToL2Messages { l1_blocks: 5..=9, messages: messages.clone() })
+        .unwrap()
+        .commit()
+        .unwrap();
+    // Indexing the same messages again doesn't duplicate them.
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .index_l1_to_l2_messages(&L1ToL2Messages {
+            l1_blocks: 10..=10,
+            messages: messages[..2].to_vec(),
+        })
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_l1_to_l2_messages_marker().unwrap(), Some(11));
+    assert_eq!(txn.get_l1_to_l2_messages_backfill_marker().unwrap(), Some(5));
+    // The hashes are returned in the order the messages were sent.
+    assert_eq!(
+        txn.get_l1_handler_transaction_hashes(&second_l1_transaction_hash).unwrap(),
+        vec![tx_hash!(0x22), tx_hash!(0x21)]
+    );
+    assert_eq!(
+        txn.get_l1_handler_transaction_hashes(&first_l1_transaction_hash).unwrap(),
+        vec![tx_hash!(0x11)]
+    );
+    assert!(txn
+        .get_l1_handler_transaction_hashes(&unknown_l1_transaction_hash)
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn l1_to_l2_messages_are_indexed_in_a_single_range() {
+    let (reader, mut writer) = get_test_storage().0;
+    let index = |writer: &mut StorageWriter, l1_blocks| {
+        writer
+            .begin_rw_txn()
+            .unwrap()
+            .index_l1_to_l2_messages(&L1ToL2Messages { l1_blocks, messages: vec![] })?
+            .commit()
+    };
+
+    index(&mut writer, 10..=19).unwrap();
+    // Backfilling the blocks right below the indexed ones.
+    index(&mut writer, 0..=9).unwrap();
+    // Indexing the blocks right above the indexed ones.
+    index(&mut writer, 20..=29).unwrap();
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_l1_to_l2_messages_marker().unwrap(), Some(30));
+    assert_eq!(txn.get_l1_to_l2_messages_backfill_marker().unwrap(), Some(0));
+
+    // Blocks that would leave a gap are rejected.
+    assert_matches!(
+        index(&mut writer, 31..=40),
+        Err(StorageError::MarkerMismatch { expected: BlockNumber(30), found: BlockNumber(31) })
+    );
+}
@@ -0,0 +1,113 @@
+use apollo_storage::compression_dictionaries::train_compression_dictionary;
+use apollo_storage::compression_utils::CompressionDictionaryKind;
+use apollo_storage::db::DbConfig;
+use apollo_storage::{open_storage, StorageConfig, StorageScope};
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+use starknet_api::core::ChainId;
+
+/// Trains a compression dictionary for a kind of classes and adds it to a storage, so that the
+/// classes of the kind that are written from then on are compressed with it:
+/// cargo run --bin storage_compression --features clap -- train -d <db_path> -c <chain_id> -k casm
+/// The classes of storages that don't have dictionaries are compressed with dictionaries by the
+/// storage migrations (see the storage_migrate binary).
+pub fn main() {
+    let matches = Command::new("Storage compression")
+        .subcommand_required(true)
+        .subcommand(
+            add_common_args(Command::new("train"))
+                .about("Trains a compression dictionary and adds it to the storage")
+                .arg(
+                    Arg::new("max_samples")
+                        .long("max_samples")
+                        .value_parser(value_parser!(usize))
+                        .default_value("10000")
+                        .help("The maximal number of classes to train the dictionary on"),
+                )
+                .arg(
+                    Arg::new("max_dictionary_size")
+                        .long("max_dictionary_size")
+                        .value_parser(value_parser!(usize))
+                        .default_value("131072")
+                        .help("The maximal size of the dictionary in bytes"),
+                ),
+        )
+        .get_matches();
+
+    let (command, sub_matches) = matches.subcommand().expect("A subcommand is required.");
+    let kind = match sub_matches.get_one::<String>("kind").expect("Missing kind").as_str() {
+        "sierra_program" => CompressionDictionaryKind::SierraProgram,
+        "casm" => CompressionDictionaryKind::Casm,
+        "deprecated_contract_class" => CompressionDictionaryKind::DeprecatedContractClass,
+        _ => unreachable!("The kind is validated by clap."),
+    };
+    let (reader, mut writer) =
+        open_storage(storage_config(sub_matches)).expect("Failed to open storage");
+    match command {
+        "train" => {
+            let dictionary = train_compression_dictionary(
+                &reader,
+                kind,
+                *sub_matches.get_one::<usize>("max_samples").expect("Missing max_samples"),
+                *sub_matches
+                    .get_one::<usize>("max_dictionary_size")
+                    .expect("Missing max_dictionary_size"),
+            )
+            .expect("Failed to train the dictionary");
+            let dictionary_size = dictionary.len();
+            let id = writer
+                .add_compression_dictionary(kind, dictionary)
+                .expect("Failed to add the dictionary");
+            println!("Added the {kind:?} dictionary {id:?} of {dictionary_size} bytes");
+        }
+        _ => unreachable!("Unknown subcommand {command}."),
+    }
+}
+
+fn add_common_args(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new("db_path")
+                .short('d')
+                .long("db_path")
+                .required(true)
+                .help("The path to the database"),
+        )
+        .arg(
+            Arg::new("chain_id")
+                .short('c')
+                .long("chain_id")
+                .required(true)
+                .help("The chain id SN_MAIN/SN_SEPOLIA for example"),
+        )
+        .arg(
+            Arg::new("kind")
+                .short('k')
+                .long("kind")
+                .required(true)
+                .value_parser(["sierra_program", "casm", "deprecated_contract_class"])
+                .help("The kind of classes"),
+        )
+        .arg(
+            Arg::new("state_only")
+                .long("state_only")
+                .action(ArgAction::SetTrue)
+                .help("Whether the storage has the state-only scope"),
+        )
+}
+
+fn storage_config(matches: &ArgMatches) -> StorageConfig {
+    let db_path = matches.get_one::<String>("db_path").expect("Missing db_path").to_owned();
+    let chain_id = matches.get_one::<String>("chain_id").expect("Missing chain_id").to_owned();
+    let db_config = DbConfig {
+        path_prefix: db_path.into(),
+        chain_id: ChainId::from(chain_id),
+        enforce_file_exists: true,
+        ..Default::default()
+    };
+    let scope = if matches.get_flag("state_only") {
+        StorageScope::StateOnly
+    } else {
+        StorageScope::FullArchive
+    };
+    StorageConfig { db_config, scope, ..Default::default() }
+}
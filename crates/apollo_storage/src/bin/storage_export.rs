@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use apollo_storage::db::DbConfig;
+use apollo_storage::export::{export_blocks, ExportConfig, ExportFormat};
+use apollo_storage::read_only::open_storage_read_only;
+use apollo_storage::StorageConfig;
+use clap::{value_parser, Arg, Command};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::ChainId;
+
+/// Exports a range of blocks of a storage to JSON lines or Parquet files, resuming from the
+/// checkpoint in the output directory, and prints a summary of the export as JSON:
+/// cargo run --bin storage_export --features clap -- -d <db_path> -c <chain_id> -o <output_dir>
+/// cargo run --bin storage_export --features clap,parquet -- -d <db_path> -c <chain_id> \
+///     -o <output_dir> --format parquet --start 0 --end 100000
+pub fn main() {
+    let matches = Command::new("Storage export")
+        .arg(
+            Arg::new("db_path")
+                .short('d')
+                .long("db_path")
+                .required(true)
+                .help("The path to the database"),
+        )
+        .arg(
+            Arg::new("chain_id")
+                .short('c')
+                .long("chain_id")
+                .required(true)
+                .help("The chain id SN_MAIN/SN_SEPOLIA for example"),
+        )
+        .arg(
+            Arg::new("output_dir")
+                .short('o')
+                .long("output_dir")
+                .required(true)
+                .help("The directory to which the files and the checkpoint are written"),
+        )
+        .arg(
+            Arg::new("format")
+                .long("format")
+                .value_parser(["jsonl", "parquet"])
+                .default_value("jsonl")
+                .help("The format of the files; parquet requires the parquet feature"),
+        )
+        .arg(
+            Arg::new("start")
+                .long("start")
+                .value_parser(value_parser!(u64))
+                .default_value("0")
+                .help("The first block to export, unless the checkpoint is beyond it"),
+        )
+        .arg(
+            Arg::new("end")
+                .long("end")
+                .value_parser(value_parser!(u64))
+                .help("The first block not to export; defaults to the end of the stored blocks"),
+        )
+        .arg(
+            Arg::new("blocks_per_partition")
+                .long("blocks_per_partition")
+                .value_parser(value_parser!(u64))
+                .default_value("10000")
+                .help("The number of blocks in each file"),
+        )
+        .get_matches();
+
+    let db_path = matches.get_one::<String>("db_path").expect("Missing db_path").to_owned();
+    let chain_id = matches.get_one::<String>("chain_id").expect("Missing chain_id").to_owned();
+    let db_config = DbConfig {
+        path_prefix: db_path.into(),
+        chain_id: ChainId::from(chain_id),
+        ..Default::default()
+    };
+    let format = match matches.get_one::<String>("format").expect("Missing format").as_str() {
+        "jsonl" => ExportFormat::Jsonl,
+        #[cfg(feature = "parquet")]
+        "parquet" => ExportFormat::Parquet,
+        format => panic!("The {format} format isn't supported by this build."),
+    };
+    let output_dir =
+        PathBuf::from(matches.get_one::<String>("output_dir").expect("Missing output_dir"));
+    let config = ExportConfig {
+        output_dir,
+        format,
+        start_block: BlockNumber(*matches.get_one::<u64>("start").expect("Missing start")),
+        end_block: matches.get_one::<u64>("end").copied().map(BlockNumber),
+        blocks_per_partition: *matches
+            .get_one::<u64>("blocks_per_partition")
+            .expect("Missing blocks_per_partition"),
+    };
+
+    // Opened in read-only mode, so that it can run while a node is writing to the storage.
+    let reader = open_storage_read_only(StorageConfig { db_config, ..Default::default() })
+        .expect("Failed to open storage");
+    let summary = export_blocks(&reader, &config).expect("Failed to export the blocks");
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&summary).expect("Failed to serialize the export summary")
+    );
+}
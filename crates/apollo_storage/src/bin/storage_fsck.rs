@@ -0,0 +1,75 @@
+use std::process::exit;
+
+use apollo_storage::db::DbConfig;
+use apollo_storage::integrity::check_storage_integrity;
+use apollo_storage::read_only::open_storage_read_only;
+use apollo_storage::{StorageConfig, StorageScope};
+use clap::{Arg, ArgAction, Command};
+use starknet_api::core::ChainId;
+
+/// Checks that a storage is internally consistent, and exits with a non-zero code if it isn't:
+/// cargo run --bin storage_fsck --features clap -- -d <db_path> -c <chain_id>
+pub fn main() {
+    let matches = Command::new("Storage fsck")
+        .arg(
+            Arg::new("db_path")
+                .short('d')
+                .long("db_path")
+                .required(true)
+                .help("The path to the database"),
+        )
+        .arg(
+            Arg::new("chain_id")
+                .short('c')
+                .long("chain_id")
+                .required(true)
+                .help("The chain id SN_MAIN/SN_SEPOLIA for example"),
+        )
+        .arg(
+            Arg::new("state_only")
+                .long("state_only")
+                .action(ArgAction::SetTrue)
+                .help("Whether the storage has the state-only scope"),
+        )
+        .arg(
+            Arg::new("verify_block_hashes")
+                .long("verify_block_hashes")
+                .action(ArgAction::SetTrue)
+                .help("Recompute the block hashes and compare them with the stored ones"),
+        )
+        .get_matches();
+
+    let db_path = matches.get_one::<String>("db_path").expect("Missing db_path").to_owned();
+    let chain_id = matches.get_one::<String>("chain_id").expect("Missing chain_id").to_owned();
+    let db_config = DbConfig {
+        path_prefix: db_path.into(),
+        chain_id: ChainId::from(chain_id),
+        ..Default::default()
+    };
+    let scope = if matches.get_flag("state_only") {
+        StorageScope::StateOnly
+    } else {
+        StorageScope::FullArchive
+    };
+    // Opened in read-only mode, so that it can run while a node is writing to the storage.
+    let reader = open_storage_read_only(StorageConfig { db_config, scope, ..Default::default() })
+        .expect("Failed to open storage");
+
+    let report = check_storage_integrity(&reader, matches.get_flag("verify_block_hashes"))
+        .expect("Failed to read the storage");
+    for (table, n_entries) in &report.n_entries {
+        println!("{table}: {n_entries} entries");
+    }
+    if matches.get_flag("verify_block_hashes") {
+        println!("Verified the hashes of {} blocks", report.n_verified_block_hashes);
+    }
+    if report.issues.is_empty() {
+        println!("No issues found");
+        return;
+    }
+    for issue in &report.issues {
+        println!("{issue}");
+    }
+    println!("Found {} issues", report.issues.len());
+    exit(1);
+}
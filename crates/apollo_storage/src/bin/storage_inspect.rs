@@ -0,0 +1,93 @@
+use std::io::{stdin, stdout, BufRead, Write};
+
+use apollo_storage::db::DbConfig;
+use apollo_storage::inspection::{execute_command, InspectionCommand};
+use apollo_storage::read_only::open_storage_read_only;
+use apollo_storage::{StorageConfig, StorageReader, StorageScope};
+use clap::{Arg, ArgAction, Command};
+use serde_json::json;
+use starknet_api::core::ChainId;
+
+/// Inspects the content of a storage. Runs the given command, or reads commands from the standard
+/// input if none is given, and prints their results as JSON. See the `inspection` module of
+/// `apollo_storage` for the supported commands, e.g.:
+/// cargo run --bin storage_inspect --features clap -- -d <db_path> -c <chain_id> header 5
+pub fn main() {
+    let matches = Command::new("Storage inspect")
+        .arg(
+            Arg::new("db_path")
+                .short('d')
+                .long("db_path")
+                .required(true)
+                .help("The path to the database"),
+        )
+        .arg(
+            Arg::new("chain_id")
+                .short('c')
+                .long("chain_id")
+                .required(true)
+                .help("The chain id SN_MAIN/SN_SEPOLIA for example"),
+        )
+        .arg(
+            Arg::new("state_only")
+                .long("state_only")
+                .action(ArgAction::SetTrue)
+                .help("Whether the storage has the state-only scope"),
+        )
+        .arg(
+            Arg::new("command")
+                .num_args(1..)
+                .trailing_var_arg(true)
+                .allow_hyphen_values(true)
+                .help(
+                    "The command to run; if not given, commands are read from the standard input",
+                ),
+        )
+        .get_matches();
+
+    let db_path = matches.get_one::<String>("db_path").expect("Missing db_path").to_owned();
+    let chain_id = matches.get_one::<String>("chain_id").expect("Missing chain_id").to_owned();
+    let db_config = DbConfig {
+        path_prefix: db_path.into(),
+        chain_id: ChainId::from(chain_id),
+        ..Default::default()
+    };
+    let scope = if matches.get_flag("state_only") {
+        StorageScope::StateOnly
+    } else {
+        StorageScope::FullArchive
+    };
+    // Opened in read-only mode, so that it can run while a node is writing to the storage.
+    let reader = open_storage_read_only(StorageConfig { db_config, scope, ..Default::default() })
+        .expect("Failed to open storage");
+
+    if let Some(command) = matches.get_many::<String>("command") {
+        let command = command.map(String::as_str).collect::<Vec<_>>().join(" ");
+        println!("{}", run_command(&reader, &command));
+        return;
+    }
+    let mut lines = stdin().lock().lines();
+    loop {
+        print!("> ");
+        stdout().flush().expect("Failed to flush the standard output");
+        let Some(line) = lines.next() else {
+            break;
+        };
+        let line = line.expect("Failed to read the standard input");
+        match line.trim() {
+            "" => continue,
+            "exit" | "quit" => break,
+            command => println!("{}", run_command(&reader, command)),
+        }
+    }
+}
+
+// Returns the pretty-printed JSON result of the command, or an error object if it failed.
+fn run_command(reader: &StorageReader, command: &str) -> String {
+    let result = match command.parse::<InspectionCommand>() {
+        Ok(command) => execute_command(reader, &command)
+            .unwrap_or_else(|err| json!({ "error": err.to_string() })),
+        Err(err) => json!({ "error": err.to_string() }),
+    };
+    serde_json::to_string_pretty(&result).expect("Failed to serialize the result")
+}
@@ -0,0 +1,56 @@
+use apollo_storage::db::DbConfig;
+use apollo_storage::migrations::migrate_storage;
+use apollo_storage::{StorageConfig, StorageScope};
+use clap::{Arg, ArgAction, Command};
+use starknet_api::core::ChainId;
+
+/// Runs the pending migrations of a storage, e.g., before starting a node with a newer version,
+/// and prints the completed migrations as JSON:
+/// cargo run --bin storage_migrate --features clap -- -d <db_path> -c <chain_id>
+pub fn main() {
+    let matches = Command::new("Storage migrate")
+        .arg(
+            Arg::new("db_path")
+                .short('d')
+                .long("db_path")
+                .required(true)
+                .help("The path to the database"),
+        )
+        .arg(
+            Arg::new("chain_id")
+                .short('c')
+                .long("chain_id")
+                .required(true)
+                .help("The chain id SN_MAIN/SN_SEPOLIA for example"),
+        )
+        .arg(
+            Arg::new("state_only")
+                .long("state_only")
+                .action(ArgAction::SetTrue)
+                .help("Whether the storage has the state-only scope"),
+        )
+        .get_matches();
+
+    let db_path = matches.get_one::<String>("db_path").expect("Missing db_path").to_owned();
+    let chain_id = matches.get_one::<String>("chain_id").expect("Missing chain_id").to_owned();
+    let db_config = DbConfig {
+        path_prefix: db_path.into(),
+        chain_id: ChainId::from(chain_id),
+        // Never create a new storage.
+        enforce_file_exists: true,
+        ..Default::default()
+    };
+    let scope = if matches.get_flag("state_only") {
+        StorageScope::StateOnly
+    } else {
+        StorageScope::FullArchive
+    };
+    let completed_migrations =
+        migrate_storage(StorageConfig { db_config, scope, ..Default::default() })
+            .expect("Failed to migrate the storage");
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&completed_migrations)
+            .expect("Failed to serialize the completed migrations")
+    );
+}
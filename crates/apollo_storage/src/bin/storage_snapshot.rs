@@ -0,0 +1,100 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+use apollo_storage::db::DbConfig;
+use apollo_storage::read_only::open_storage_read_only;
+use apollo_storage::snapshot::{export_snapshot, import_snapshot};
+use apollo_storage::{StorageConfig, StorageScope};
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use starknet_api::core::ChainId;
+
+/// Exports a snapshot of a storage to an archive, or imports a snapshot archive into a new storage:
+/// cargo run --bin storage_snapshot --features clap -- export -d <db_path> -c <chain_id> -a <path>
+/// cargo run --bin storage_snapshot --features clap -- import -d <db_path> -c <chain_id> -a <path>
+pub fn main() {
+    let matches = Command::new("Storage snapshot")
+        .subcommand_required(true)
+        .subcommand(
+            add_common_args(Command::new("export"))
+                .about("Exports a consistent snapshot of the storage to an archive"),
+        )
+        .subcommand(
+            add_common_args(Command::new("import"))
+                .about("Imports a snapshot archive into a new storage"),
+        )
+        .get_matches();
+
+    match matches.subcommand() {
+        Some(("export", sub_matches)) => {
+            let config = storage_config(sub_matches);
+            let reader = open_storage_read_only(config.clone()).expect("Failed to open storage");
+            let archive =
+                File::create(archive_path(sub_matches)).expect("Failed to create the archive file");
+            let mut archive = BufWriter::new(archive);
+            let manifest = export_snapshot(&reader, &config.db_config, &mut archive)
+                .expect("Failed to export the snapshot");
+            archive.flush().expect("Failed to write the archive file");
+            println!("Exported the snapshot: {manifest:?}");
+        }
+        Some(("import", sub_matches)) => {
+            let config = storage_config(sub_matches);
+            let archive =
+                File::open(archive_path(sub_matches)).expect("Failed to open the archive file");
+            let manifest = import_snapshot(BufReader::new(archive), config)
+                .expect("Failed to import the snapshot");
+            println!("Imported the snapshot: {manifest:?}");
+        }
+        _ => unreachable!("A subcommand is required."),
+    }
+}
+
+fn add_common_args(command: Command) -> Command {
+    command
+        .arg(
+            Arg::new("db_path")
+                .short('d')
+                .long("db_path")
+                .required(true)
+                .help("The path to the database"),
+        )
+        .arg(
+            Arg::new("chain_id")
+                .short('c')
+                .long("chain_id")
+                .required(true)
+                .help("The chain id SN_MAIN/SN_SEPOLIA for example"),
+        )
+        .arg(
+            Arg::new("archive_path")
+                .short('a')
+                .long("archive_path")
+                .required(true)
+                .help("The path to the snapshot archive"),
+        )
+        .arg(
+            Arg::new("state_only")
+                .long("state_only")
+                .action(ArgAction::SetTrue)
+                .help("Whether the storage has the state-only scope"),
+        )
+}
+
+fn storage_config(matches: &ArgMatches) -> StorageConfig {
+    let db_path = matches.get_one::<String>("db_path").expect("Missing db_path").to_owned();
+    let chain_id = matches.get_one::<String>("chain_id").expect("Missing chain_id");
+    let db_config = DbConfig {
+        path_prefix: db_path.into(),
+        chain_id: ChainId::from(chain_id.to_owned()),
+        ..Default::default()
+    };
+    let scope = if matches.get_flag("state_only") {
+        StorageScope::StateOnly
+    } else {
+        StorageScope::FullArchive
+    };
+    StorageConfig { db_config, scope, ..Default::default() }
+}
+
+fn archive_path(matches: &ArgMatches) -> String {
+    matches.get_one::<String>("archive_path").expect("Missing archive_path").to_owned()
+}
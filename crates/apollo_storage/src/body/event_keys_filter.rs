@@ -0,0 +1,214 @@
+//! Interface for filtering blocks by the keys of their events.
+//!
+//! For each block whose body is stored, a bloom filter over the keys of the block's events is
+//! stored as well. Each key is inserted together with its position in the keys of its event, so a
+//! query of events by keys can skip the blocks whose filter rules out a match without reading their
+//! transaction outputs. A bloom filter may have false positives, but never false negatives. The
+//! size of a filter grows with the number of keys inserted into it, so the rate of false positives
+//! doesn't grow with the number of events in the block.
+//! Import [`EventKeysFilterReader`] to read the filters using a [`StorageTxn`].
+//!
+//! The missing filters of storages that were written before the filters were introduced are
+//! written by a migration of the storage (see [`crate::migrations`]). Blocks without a filter may
+//! match any keys.
+
+#[cfg(test)]
+#[path = "event_keys_filter_test.rs"]
+mod event_keys_filter_test;
+
+use std::array;
+use std::collections::HashSet;
+
+use starknet_api::block::BlockNumber;
+use starknet_api::hash::starknet_keccak_hash;
+use starknet_api::transaction::{EventKey, TransactionOutput};
+use tracing::info;
+
+use crate::body::BodyStorageReader;
+use crate::db::serialization::NoVersionValueWrapper;
+use crate::db::table_types::{SimpleTable, Table};
+use crate::db::{DbTransaction, TableHandle, TransactionKind, RW};
+use crate::migrations::{MigrationStep, StorageMigration, VersionComponent};
+use crate::pruning::PruningStorageReader;
+use crate::version::Version;
+use crate::{MarkerKind, MarkersTable, StorageResult, StorageTxn};
+
+/// The number of bits of the filter of a block per key inserted into it. Together with
+/// `N_BITS_PER_KEY`, bounds the rate of false positives at about 2%.
+pub const EVENT_KEYS_FILTER_BITS_PER_KEY: usize = 10;
+// The number of bits set in the filter for each inserted key.
+const N_BITS_PER_KEY: usize = 3;
+
+pub(crate) type EventKeysFiltersTable<'env> =
+    TableHandle<'env, BlockNumber, NoVersionValueWrapper<EventKeysFilter>, SimpleTable>;
+
+/// A bloom filter over the keys of the events of a block, where each key is inserted together with
+/// its position in the keys of its event.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct EventKeysFilter(pub(crate) Vec<u8>);
+
+impl EventKeysFilter {
+    /// Creates the filter of the events of the given transaction outputs.
+    pub fn from_transaction_outputs<'a>(
+        transaction_outputs: impl IntoIterator<Item = &'a TransactionOutput>,
+    ) -> Self {
+        Self::from_keys(
+            transaction_outputs
+                .into_iter()
+                .flat_map(|output| output.events())
+                .flat_map(|event| event.content.keys.iter().enumerate()),
+        )
+    }
+
+    /// Creates a filter of the given keys, each with its position in the keys of its event.
+    pub fn from_keys<'a>(keys: impl IntoIterator<Item = (usize, &'a EventKey)>) -> Self {
+        let keys = keys.into_iter().collect::<HashSet<_>>();
+        let size_in_bytes = (keys.len() * EVENT_KEYS_FILTER_BITS_PER_KEY).div_ceil(8);
+        let mut filter = Self(vec![0; size_in_bytes]);
+        for (position, key) in keys {
+            for bit_index in filter.bit_indices(position, key) {
+                filter.0[bit_index / 8] |= 1 << (bit_index % 8);
+            }
+        }
+        filter
+    }
+
+    /// Returns false if no event has the key at the given position.
+    pub fn may_contain(&self, position: usize, key: &EventKey) -> bool {
+        // An empty filter has no keys.
+        !self.0.is_empty()
+            && self
+                .bit_indices(position, key)
+                .into_iter()
+                .all(|bit_index| self.0[bit_index / 8] & (1 << (bit_index % 8)) != 0)
+    }
+
+    /// Returns false if no event matches the given keys, where an event matches if for each
+    /// position, the set of keys at the position is empty or contains the event's key at the
+    /// position.
+    pub fn may_match(&self, keys: &[HashSet<EventKey>]) -> bool {
+        keys.iter().enumerate().all(|(position, keys_at_position)| {
+            keys_at_position.is_empty()
+                || keys_at_position.iter().any(|key| self.may_contain(position, key))
+        })
+    }
+
+    // Returns the indices of the bits that represent the key at the given position, each taken
+    // from a different 4 bytes of the hash of the position and the key. The filter must not be
+    // empty.
+    fn bit_indices(&self, position: usize, key: &EventKey) -> [usize; N_BITS_PER_KEY] {
+        let size_in_bits = self.0.len() * 8;
+        let position = u64::try_from(position).expect("The position should fit in a u64.");
+        let mut input = position.to_be_bytes().to_vec();
+        input.extend_from_slice(&key.0.to_bytes_be());
+        let hash = starknet_keccak_hash(&input).to_bytes_be();
+        array::from_fn(|i| {
+            let end = hash.len() - 4 * i;
+            let bytes = hash[end - 4..end].try_into().expect("The slice should have 4 bytes.");
+            usize::try_from(u32::from_be_bytes(bytes)).expect("A u32 should fit in a usize.")
+                % size_in_bits
+        })
+    }
+}
+
+/// Interface for reading the event keys filters.
+pub trait EventKeysFilterReader {
+    /// Returns the event keys filter of the block with the given number, or None if it is not
+    /// stored (e.g., the block body wasn't written or was pruned).
+    fn get_event_keys_filter(
+        &self,
+        block_number: BlockNumber,
+    ) -> StorageResult<Option<EventKeysFilter>>;
+
+    /// Returns false if the block with the given number has no event that matches the given keys.
+    /// Returns true if the block may have such an event or its filter is not stored.
+    fn block_may_match_event_keys(
+        &self,
+        block_number: BlockNumber,
+        keys: &[HashSet<EventKey>],
+    ) -> StorageResult<bool> {
+        Ok(self.get_event_keys_filter(block_number)?.is_none_or(|filter| filter.may_match(keys)))
+    }
+}
+
+impl<Mode: TransactionKind> EventKeysFilterReader for StorageTxn<'_, Mode> {
+    fn get_event_keys_filter(
+        &self,
+        block_number: BlockNumber,
+    ) -> StorageResult<Option<EventKeysFilter>> {
+        let event_keys_filters_table = self.open_table(&self.tables.event_keys_filters)?;
+        Ok(event_keys_filters_table.get(&self.txn, &block_number)?)
+    }
+}
+
+// Writes the filter of a block body, and advances the event keys filter marker if all the filters
+// up to the block are written.
+pub(crate) fn write_event_keys_filter<'env>(
+    txn: &DbTransaction<'env, RW>,
+    event_keys_filters_table: &'env EventKeysFiltersTable<'env>,
+    markers_table: &'env MarkersTable<'env>,
+    block_number: BlockNumber,
+    transaction_outputs: &[TransactionOutput],
+) -> StorageResult<()> {
+    let filter = EventKeysFilter::from_transaction_outputs(transaction_outputs);
+    event_keys_filters_table.upsert(txn, &block_number, &filter)?;
+    let marker = markers_table.get(txn, &MarkerKind::EventKeysFilter)?.unwrap_or_default();
+    if marker == block_number {
+        markers_table.upsert(txn, &MarkerKind::EventKeysFilter, &block_number.unchecked_next())?;
+    }
+    Ok(())
+}
+
+// Deletes the filter of a reverted block body.
+pub(crate) fn revert_event_keys_filter<'env>(
+    txn: &DbTransaction<'env, RW>,
+    event_keys_filters_table: &'env EventKeysFiltersTable<'env>,
+    markers_table: &'env MarkersTable<'env>,
+    block_number: BlockNumber,
+) -> StorageResult<()> {
+    event_keys_filters_table.delete(txn, &block_number)?;
+    let marker = markers_table.get(txn, &MarkerKind::EventKeysFilter)?.unwrap_or_default();
+    if marker > block_number {
+        markers_table.upsert(txn, &MarkerKind::EventKeysFilter, &block_number)?;
+    }
+    Ok(())
+}
+
+/// Writes the missing event keys filters of the stored block bodies, which storages of blocks
+/// version 6.2 don't have.
+pub(crate) const EVENT_KEYS_FILTERS_MIGRATION: StorageMigration = StorageMigration {
+    name: "event_keys_filters",
+    component: VersionComponent::Blocks,
+    from_version: Version { major: 6, minor: 2 },
+    to_version: Version { major: 6, minor: 3 },
+    batch_size: 100,
+    migrate_batch: migrate_event_keys_filters_batch,
+};
+
+// Writes the filters of a batch of the stored block bodies, starting at the block number given by
+// the progress. The filters of pruned blocks are not needed, so they are skipped.
+fn migrate_event_keys_filters_batch(
+    txn: StorageTxn<'_, RW>,
+    progress: u64,
+    batch_size: u64,
+) -> StorageResult<(StorageTxn<'_, RW>, MigrationStep)> {
+    let markers_table = txn.open_table(&txn.tables.markers)?;
+    let event_keys_filters_table = txn.open_table(&txn.tables.event_keys_filters)?;
+
+    let first_block = BlockNumber(progress).max(txn.get_pruning_marker()?);
+    let body_marker = txn.get_body_marker()?;
+    if first_block >= body_marker {
+        markers_table.upsert(&txn.txn, &MarkerKind::EventKeysFilter, &body_marker)?;
+        return Ok((txn, MigrationStep::Done));
+    }
+
+    let batch_end = BlockNumber(body_marker.0.min(first_block.0.saturating_add(batch_size)));
+    for block_number in (first_block.0..batch_end.0).map(BlockNumber) {
+        let transaction_outputs =
+            txn.get_block_transaction_outputs(block_number)?.unwrap_or_default();
+        let filter = EventKeysFilter::from_transaction_outputs(&transaction_outputs);
+        event_keys_filters_table.upsert(&txn.txn, &block_number, &filter)?;
+    }
+    info!("Wrote the event keys filters up to block {batch_end} (excluding).");
+    Ok((txn, MigrationStep::Continue(batch_end.0)))
+}
@@ -0,0 +1,147 @@
+use std::collections::HashSet;
+
+use apollo_test_utils::get_test_block;
+use pretty_assertions::assert_eq;
+use starknet_api::block::BlockNumber;
+use starknet_api::felt;
+use starknet_api::transaction::EventKey;
+
+use crate::body::event_keys_filter::{
+    EventKeysFilter,
+    EventKeysFilterReader,
+    EVENT_KEYS_FILTERS_MIGRATION,
+};
+use crate::body::BodyStorageWriter;
+use crate::db::table_types::Table;
+use crate::migrations::{run_migrations, STORAGE_MIGRATIONS};
+use crate::test_utils::get_test_storage;
+use crate::version::{VersionStorageReader, VERSION_BLOCKS_KEY};
+use crate::{MarkerKind, STORAGE_VERSION_BLOCKS};
+
+const N_BLOCKS: u64 = 3;
+
+fn key(value: u64) -> EventKey {
+    EventKey(felt!(value))
+}
+
+#[test]
+fn filter_matches_inserted_keys() {
+    let filter = EventKeysFilter::from_keys([(0, &key(1)), (1, &key(2))]);
+
+    assert!(filter.may_contain(0, &key(1)));
+    assert!(filter.may_contain(1, &key(2)));
+    assert!(!filter.may_contain(1, &key(1)));
+    assert!(!filter.may_contain(0, &key(3)));
+
+    assert!(filter.may_match(&[]));
+    assert!(filter.may_match(&[HashSet::from([key(1), key(3)])]));
+    assert!(filter.may_match(&[HashSet::new(), HashSet::from([key(2)])]));
+    assert!(!filter.may_match(&[HashSet::from([key(2)])]));
+    assert!(!filter.may_match(&[HashSet::from([key(1)]), HashSet::from([key(3)])]));
+    assert!(!filter.may_match(&[HashSet::new(), HashSet::new(), HashSet::from([key(1)])]));
+}
+
+#[test]
+fn empty_filter_matches_no_keys() {
+    let filter = EventKeysFilter::from_keys([]);
+    assert!(!filter.may_contain(0, &key(1)));
+    assert!(filter.may_match(&[HashSet::new()]));
+    assert!(!filter.may_match(&[HashSet::from([key(1)])]));
+}
+
+#[test]
+fn filter_of_many_keys_is_not_saturated() {
+    const N_KEYS: u64 = 10000;
+    let keys = (0..N_KEYS).map(key).collect::<Vec<_>>();
+    let filter = EventKeysFilter::from_keys(keys.iter().map(|key| (0, key)));
+
+    assert!(keys.iter().all(|key| filter.may_contain(0, key)));
+    let n_false_positives =
+        (N_KEYS..2 * N_KEYS).filter(|value| filter.may_contain(0, &key(*value))).count();
+    // The expected rate of false positives is about 2%.
+    assert!(n_false_positives < 500, "{n_false_positives} false positives.");
+}
+
+#[test]
+fn append_and_revert_body_update_filters() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let body = get_test_block(2, Some(2), None, None).body;
+    let expected_filter = EventKeysFilter::from_transaction_outputs(&body.transaction_outputs);
+    let event_keys = body.transaction_outputs[0].events()[0].content.keys.clone();
+
+    writer.begin_rw_txn().unwrap().append_body(BlockNumber(0), body).unwrap().commit().unwrap();
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_event_keys_filter(BlockNumber(0)).unwrap(), Some(expected_filter));
+    let keys_filter = event_keys.into_iter().map(|key| HashSet::from([key])).collect::<Vec<_>>();
+    assert!(txn.block_may_match_event_keys(BlockNumber(0), &keys_filter).unwrap());
+    // Blocks without a filter may match any keys.
+    assert!(txn.block_may_match_event_keys(BlockNumber(1), &[HashSet::from([key(1)])]).unwrap());
+    drop(txn);
+
+    let (txn, _) = writer.begin_rw_txn().unwrap().revert_body(BlockNumber(0)).unwrap();
+    txn.commit().unwrap();
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_event_keys_filter(BlockNumber(0)).unwrap(), None);
+    let markers_table = txn.open_table(&txn.tables.markers).unwrap();
+    assert_eq!(
+        markers_table.get(&txn.txn, &MarkerKind::EventKeysFilter).unwrap(),
+        Some(BlockNumber(0))
+    );
+}
+
+#[test]
+fn migration_writes_missing_filters() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let bodies = (0..N_BLOCKS)
+        .map(|i| {
+            let mut body = get_test_block(1, Some(2), None, None).body;
+            // Make the transaction hashes unique across blocks.
+            body.transaction_hashes[0].0 += felt!(i);
+            body
+        })
+        .collect::<Vec<_>>();
+    let mut txn = writer.begin_rw_txn().unwrap();
+    for (i, body) in bodies.iter().enumerate() {
+        txn = txn.append_body(BlockNumber(i.try_into().unwrap()), body.clone()).unwrap();
+    }
+    txn.commit().unwrap();
+
+    // Simulate a storage that was written before the filters were introduced.
+    let txn = writer.begin_rw_txn().unwrap();
+    let event_keys_filters_table = txn.open_table(&txn.tables.event_keys_filters).unwrap();
+    let markers_table = txn.open_table(&txn.tables.markers).unwrap();
+    for i in 0..N_BLOCKS {
+        event_keys_filters_table.delete(&txn.txn, &BlockNumber(i)).unwrap();
+    }
+    markers_table.upsert(&txn.txn, &MarkerKind::EventKeysFilter, &BlockNumber(0)).unwrap();
+    let version_table = txn.open_table(&txn.tables.storage_version).unwrap();
+    version_table
+        .upsert(
+            &txn.txn,
+            &VERSION_BLOCKS_KEY.to_string(),
+            &EVENT_KEYS_FILTERS_MIGRATION.from_version,
+        )
+        .unwrap();
+    txn.commit().unwrap();
+
+    let completed_migrations = run_migrations(&mut writer, STORAGE_MIGRATIONS).unwrap();
+    assert_eq!(completed_migrations.len(), 1);
+    assert_eq!(completed_migrations[0].name, EVENT_KEYS_FILTERS_MIGRATION.name);
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_blocks_version().unwrap(),
+        Some(STORAGE_VERSION_BLOCKS)
+    );
+
+    let txn = reader.begin_ro_txn().unwrap();
+    for (i, body) in bodies.iter().enumerate() {
+        assert_eq!(
+            txn.get_event_keys_filter(BlockNumber(i.try_into().unwrap())).unwrap(),
+            Some(EventKeysFilter::from_transaction_outputs(&body.transaction_outputs))
+        );
+    }
+    let markers_table = txn.open_table(&txn.tables.markers).unwrap();
+    assert_eq!(
+        markers_table.get(&txn.txn, &MarkerKind::EventKeysFilter).unwrap(),
+        Some(BlockNumber(N_BLOCKS))
+    );
+}
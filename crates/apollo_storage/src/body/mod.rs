@@ -5,7 +5,8 @@
 //! Import [`BodyStorageReader`] and [`BodyStorageWriter`] to read and write data related
 //! to the block bodies using a [`StorageTxn`].
 //!
-//! See [`events`] module for the interface for handling events.
+//! See [`events`] module for the interface for handling events, and [`event_keys_filter`] module
+//! for the interface for filtering blocks by the keys of their events.
 //!
 //!  # Example
 //! ```
@@ -40,6 +41,7 @@
 
 #[cfg(test)]
 mod body_test;
+pub mod event_keys_filter;
 pub mod events;
 
 use std::collections::HashSet;
@@ -57,9 +59,11 @@ use starknet_api::transaction::{
 };
 use tracing::debug;
 
+use crate::body::event_keys_filter::{revert_event_keys_filter, write_event_keys_filter};
 use crate::db::serialization::{NoVersionValueWrapper, VersionZeroWrapper};
 use crate::db::table_types::{CommonPrefix, DbCursorTrait, NoValue, SimpleTable, Table};
 use crate::db::{DbTransaction, TableHandle, TransactionKind, RW};
+use crate::pruning::is_pruned;
 use crate::{
     FileHandlers,
     MarkerKind,
@@ -377,7 +381,8 @@ impl BodyStorageWriter for StorageTxn<'_, RW> {
         let markers_table = self.open_table(&self.tables.markers)?;
         update_marker(&self.txn, &markers_table, block_number)?;
 
-        if self.scope != StorageScope::StateOnly {
+        // The transactions of a block that was already pruned are not written.
+        if self.scope != StorageScope::StateOnly && !is_pruned(&self, block_number)? {
             let events_table = self.open_table(&self.tables.events)?;
             let transaction_hash_to_idx_table =
                 self.open_table(&self.tables.transaction_hash_to_idx)?;
@@ -394,6 +399,15 @@ impl BodyStorageWriter for StorageTxn<'_, RW> {
                 &events_table,
                 block_number,
             )?;
+
+            let event_keys_filters_table = self.open_table(&self.tables.event_keys_filters)?;
+            write_event_keys_filter(
+                &self.txn,
+                &event_keys_filters_table,
+                &markers_table,
+                block_number,
+                &block_body.transaction_outputs,
+            )?;
         }
 
         Ok(self)
@@ -451,6 +465,14 @@ impl BodyStorageWriter for StorageTxn<'_, RW> {
                 transaction_hash_to_idx_table.delete(&self.txn, tx_hash)?;
                 transaction_metadata_table.delete(&self.txn, &tx_index)?;
             }
+
+            let event_keys_filters_table = self.open_table(&self.tables.event_keys_filters)?;
+            revert_event_keys_filter(
+                &self.txn,
+                &event_keys_filters_table,
+                &markers_table,
+                block_number,
+            )?;
             Some((transactions, transaction_outputs, transaction_hashes))
         };
 
@@ -0,0 +1,439 @@
+//! Zstd dictionaries for compressing the classes in the storage.
+//!
+//! The classes take most of the space of the storage files, and their programs have a lot in
+//! common, so compressing them with dictionaries trained on the classes of the storage shrinks them
+//! considerably. A dictionary is trained with [`train_compression_dictionary`] and added with
+//! [`StorageWriter::add_compression_dictionary`]; from then on, classes of its kind are compressed
+//! with it. The classes of storages that were written before the dictionaries were introduced are
+//! rewritten with dictionaries trained on them by a migration of the storage (see
+//! [`crate::migrations`]), which then releases the file space of their previous copies. Classes
+//! written before a newer dictionary was added stay compressed with the previous one.
+//!
+//! Dictionaries are never removed, since values compressed with them may still be in the storage.
+//! See [`crate::compression_utils`] for the format of the compressed values.
+//!
+//! # Example
+//! ```
+//! use apollo_storage::compression_dictionaries::train_compression_dictionary;
+//! use apollo_storage::compression_utils::CompressionDictionaryKind;
+//! use apollo_storage::open_storage;
+//! # use apollo_storage::{db::DbConfig, StorageConfig};
+//! # use starknet_api::core::ChainId;
+//!
+//! # let dir_handle = tempfile::tempdir().unwrap();
+//! # let dir = dir_handle.path().to_path_buf();
+//! # let db_config = DbConfig {
+//! #     path_prefix: dir,
+//! #     chain_id: ChainId::Mainnet,
+//! #     enforce_file_exists: false,
+//! #     min_size: 1 << 20,    // 1MB
+//! #     max_size: 1 << 35,    // 32GB
+//! #     growth_step: 1 << 26, // 64MB
+//! # };
+//! # let storage_config = StorageConfig{db_config, ..Default::default()};
+//! let (reader, mut writer) = open_storage(storage_config)?;
+//! let kind = CompressionDictionaryKind::Casm;
+//! // Training fails if the storage doesn't have enough classes of this kind.
+//! if let Ok(dictionary) = train_compression_dictionary(&reader, kind, 1000, 1 << 17) {
+//!     writer.add_compression_dictionary(kind, dictionary)?;
+//! }
+//! # Ok::<(), apollo_storage::StorageError>(())
+//! ```
+
+#[cfg(test)]
+#[path = "compression_dictionaries_test.rs"]
+mod compression_dictionaries_test;
+
+use std::fmt::Debug;
+
+use starknet_api::core::ClassHash;
+use tracing::{info, warn};
+
+use crate::compression_utils::{
+    CompressionDictionaries,
+    CompressionDictionaryId,
+    CompressionDictionaryKind,
+    StoredCompressionDictionary,
+};
+use crate::db::serialization::{NoVersionValueWrapper, ValueSerde};
+use crate::db::table_types::{DbCursorTrait, SimpleTable, Table};
+use crate::db::{DbError, DbTransaction, TableIdentifier, TransactionKind, RO, RW};
+use crate::migrations::{MigrationStep, StorageMigration, VersionComponent};
+use crate::mmap_file::LocationInFile;
+use crate::serialization::serializers::{
+    casm_to_compress,
+    deprecated_contract_class_to_compress,
+    sierra_program_to_compress,
+};
+use crate::state::data::IndexedDeprecatedContractClass;
+use crate::version::Version;
+use crate::{OffsetKind, StorageError, StorageReader, StorageResult, StorageTxn, StorageWriter};
+
+// The maximal number of classes on which the migration trains a dictionary of each kind.
+const TRAINING_MAX_SAMPLES: usize = 1000;
+// The maximal size in bytes of the dictionaries that the migration trains.
+const TRAINING_MAX_DICTIONARY_SIZE: usize = 1 << 17;
+
+type CompressionDictionariesTable = TableIdentifier<
+    CompressionDictionaryId,
+    NoVersionValueWrapper<StoredCompressionDictionary>,
+    SimpleTable,
+>;
+
+// Returns all the compression dictionaries in the storage.
+pub(crate) fn load_compression_dictionaries<Mode: TransactionKind>(
+    txn: &DbTransaction<'_, Mode>,
+    compression_dictionaries_table: &CompressionDictionariesTable,
+) -> StorageResult<CompressionDictionaries> {
+    let table = txn.open_table(compression_dictionaries_table)?;
+    let mut cursor = table.cursor(txn)?;
+    let mut dictionaries = CompressionDictionaries::default();
+    while let Some((id, stored_dictionary)) = cursor.next()? {
+        dictionaries.insert(id, &stored_dictionary);
+    }
+    Ok(dictionaries)
+}
+
+// Reloads the compression dictionaries if dictionaries were added since they were loaded, which
+// happens when the storage was opened as read-only and another process writes to it.
+pub(crate) fn reload_compression_dictionaries_if_needed(
+    txn: &StorageTxn<'_, RO>,
+) -> StorageResult<()> {
+    let n_dictionaries = txn.txn.get_table_entries(&txn.tables.compression_dictionaries)?;
+    if n_dictionaries == txn.file_handlers.compression_dictionaries.get().n_dictionaries() {
+        return Ok(());
+    }
+    txn.file_handlers
+        .compression_dictionaries
+        .set(load_compression_dictionaries(&txn.txn, &txn.tables.compression_dictionaries)?);
+    Ok(())
+}
+
+impl StorageWriter {
+    /// Adds a compression dictionary, with which the values of the given kind are compressed from
+    /// now on, and returns its id.
+    pub fn add_compression_dictionary(
+        &mut self,
+        kind: CompressionDictionaryKind,
+        dictionary: Vec<u8>,
+    ) -> StorageResult<CompressionDictionaryId> {
+        let txn = self.begin_rw_txn()?;
+        let id = insert_compression_dictionary(&txn, kind, dictionary)?;
+        txn.commit()?;
+
+        // The readers share the dictionaries with the writer.
+        let txn = self.begin_rw_txn()?;
+        let dictionaries =
+            load_compression_dictionaries(&txn.txn, &txn.tables.compression_dictionaries)?;
+        drop(txn);
+        self.file_writers.compression_dictionaries.set(dictionaries);
+        Ok(id)
+    }
+}
+
+// Inserts a compression dictionary into the storage and returns its id. The dictionaries that the
+// storage compresses with aren't reloaded.
+fn insert_compression_dictionary(
+    txn: &StorageTxn<'_, RW>,
+    kind: CompressionDictionaryKind,
+    dictionary: Vec<u8>,
+) -> StorageResult<CompressionDictionaryId> {
+    let table = txn.open_table(&txn.tables.compression_dictionaries)?;
+    let id = match table.cursor(&txn.txn)?.prev()? {
+        Some((CompressionDictionaryId(last_id), _)) => CompressionDictionaryId(last_id + 1),
+        None => CompressionDictionaryId::default(),
+    };
+    table.insert(&txn.txn, &id, &StoredCompressionDictionary { kind, dictionary })?;
+    Ok(id)
+}
+
+/// Trains a compression dictionary of at most `max_dictionary_size` bytes for the given kind of
+/// values, on at most `max_samples` classes of the storage.
+///
+/// # Errors
+/// Returns [`crate::StorageError::IOError`] if the storage doesn't have enough classes to train on.
+pub fn train_compression_dictionary(
+    reader: &StorageReader,
+    kind: CompressionDictionaryKind,
+    max_samples: usize,
+    max_dictionary_size: usize,
+) -> StorageResult<Vec<u8>> {
+    train_dictionary_in_txn(&reader.begin_ro_txn()?, kind, max_samples, max_dictionary_size)
+}
+
+fn train_dictionary_in_txn<Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+    kind: CompressionDictionaryKind,
+    max_samples: usize,
+    max_dictionary_size: usize,
+) -> StorageResult<Vec<u8>> {
+    // The classes are ordered by their hash, so the samples are effectively random.
+    let samples = match kind {
+        CompressionDictionaryKind::SierraProgram => {
+            collect_samples(txn, &txn.tables.declared_classes, max_samples, |location| {
+                let contract_class = txn.file_handlers.get_contract_class_unchecked(*location)?;
+                Ok(sierra_program_to_compress(&contract_class)
+                    .map_err(|_| DbError::Serialization)?)
+            })?
+        }
+        CompressionDictionaryKind::Casm => {
+            collect_samples(txn, &txn.tables.casms, max_samples, |location| {
+                let casm = txn.file_handlers.get_casm_unchecked(*location)?;
+                Ok(casm_to_compress(&casm).map_err(|_| DbError::Serialization)?)
+            })?
+        }
+        CompressionDictionaryKind::DeprecatedContractClass => {
+            collect_samples(txn, &txn.tables.deprecated_declared_classes, max_samples, |value| {
+                let deprecated_contract_class = txn
+                    .file_handlers
+                    .get_deprecated_contract_class_unchecked(value.location_in_file)?;
+                Ok(deprecated_contract_class_to_compress(&deprecated_contract_class)
+                    .map_err(|_| DbError::Serialization)?)
+            })?
+        }
+    };
+    Ok(zstd::dict::from_samples(&samples, max_dictionary_size)?)
+}
+
+fn collect_samples<Mode: TransactionKind, V: ValueSerde + Debug>(
+    txn: &StorageTxn<'_, Mode>,
+    table_id: &TableIdentifier<ClassHash, V, SimpleTable>,
+    max_samples: usize,
+    get_sample: impl Fn(&V::Value) -> StorageResult<Vec<u8>>,
+) -> StorageResult<Vec<Vec<u8>>> {
+    let table = txn.open_table(table_id)?;
+    let mut cursor = table.cursor(&txn.txn)?;
+    let mut samples = Vec::new();
+    while samples.len() < max_samples {
+        let Some((_class_hash, value)) = cursor.next()? else {
+            break;
+        };
+        samples.push(get_sample(&value)?);
+    }
+    Ok(samples)
+}
+
+/// Compresses the classes of storages of state version 6.1, which were written without
+/// dictionaries, with dictionaries trained on them.
+pub(crate) const COMPRESSION_DICTIONARIES_MIGRATION: StorageMigration = StorageMigration {
+    name: "compression_dictionaries",
+    component: VersionComponent::State,
+    from_version: Version { major: 6, minor: 1 },
+    to_version: Version { major: 6, minor: 2 },
+    batch_size: 1000,
+    migrate_batch: migrate_compression_dictionaries_batch,
+};
+
+// The kinds of classes, in the order in which the migration rewrites them.
+const MIGRATED_KINDS: [CompressionDictionaryKind; 3] = [
+    CompressionDictionaryKind::SierraProgram,
+    CompressionDictionaryKind::Casm,
+    CompressionDictionaryKind::DeprecatedContractClass,
+];
+
+// The progress of the migration is the number of classes that were rewritten or skipped, counting
+// the classes of each kind in the order of `MIGRATED_KINDS`, and the classes of a kind by their
+// hashes. The first batch of a kind trains its dictionary, unless the storage already has one. If
+// the dictionary can't be trained (e.g., the kind doesn't have enough classes), the classes of the
+// kind are skipped. Once all the classes were rewritten, the disk space of their previous copies is
+// released (see [`crate::mmap_file`]).
+fn migrate_compression_dictionaries_batch(
+    txn: StorageTxn<'_, RW>,
+    progress: u64,
+    batch_size: u64,
+) -> StorageResult<(StorageTxn<'_, RW>, MigrationStep)> {
+    let mut n_previous_classes = 0;
+    for kind in MIGRATED_KINDS {
+        let n_classes = u64::try_from(match kind {
+            CompressionDictionaryKind::SierraProgram => {
+                txn.txn.get_table_entries(&txn.tables.declared_classes)?
+            }
+            CompressionDictionaryKind::Casm => txn.txn.get_table_entries(&txn.tables.casms)?,
+            CompressionDictionaryKind::DeprecatedContractClass => {
+                txn.txn.get_table_entries(&txn.tables.deprecated_declared_classes)?
+            }
+        })
+        .expect("The number of classes should fit in a u64.");
+        if progress >= n_previous_classes + n_classes {
+            n_previous_classes += n_classes;
+            continue;
+        }
+
+        let n_skipped_classes = progress - n_previous_classes;
+        if n_skipped_classes == 0
+            && !txn.file_handlers.compression_dictionaries.get().has_dictionary_of_kind(kind)
+        {
+            match train_dictionary_in_txn(
+                &txn,
+                kind,
+                TRAINING_MAX_SAMPLES,
+                TRAINING_MAX_DICTIONARY_SIZE,
+            ) {
+                Ok(dictionary) => {
+                    let id = insert_compression_dictionary(&txn, kind, dictionary)?;
+                    info!("Trained the {kind:?} compression dictionary {id:?}.");
+                    txn.file_handlers.compression_dictionaries.set(load_compression_dictionaries(
+                        &txn.txn,
+                        &txn.tables.compression_dictionaries,
+                    )?);
+                }
+                Err(StorageError::IOError(err)) => {
+                    warn!(
+                        "Not recompressing the {kind:?} classes, since a compression dictionary \
+                         couldn't be trained on them: {err}."
+                    );
+                    return Ok((txn, MigrationStep::Continue(n_previous_classes + n_classes)));
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let n_rewritten_classes = recompress_batch(&txn, kind, n_skipped_classes, batch_size)?;
+        return Ok((txn, MigrationStep::Continue(progress + n_rewritten_classes)));
+    }
+
+    // The rewritten classes were committed, so the space before the first copy of each kind that is
+    // in use can be released.
+    for kind in MIGRATED_KINDS {
+        release_previous_class_copies(&txn, kind)?;
+    }
+    Ok((txn, MigrationStep::Done))
+}
+
+// Rewrites the classes of the given kind, skipping the given number of classes, in the order of
+// their hashes, and returns the number of rewritten classes.
+fn recompress_batch(
+    txn: &StorageTxn<'_, RW>,
+    kind: CompressionDictionaryKind,
+    n_skipped_classes: u64,
+    batch_size: u64,
+) -> StorageResult<u64> {
+    let file_offsets_table = txn.txn.open_table(&txn.tables.file_offsets)?;
+    match kind {
+        CompressionDictionaryKind::SierraProgram => rewrite_classes(
+            txn,
+            &txn.tables.declared_classes,
+            n_skipped_classes,
+            batch_size,
+            |location| {
+                let contract_class = txn.file_handlers.get_contract_class_unchecked(*location)?;
+                let location = txn.file_handlers.append_contract_class(&contract_class);
+                file_offsets_table.upsert(
+                    &txn.txn,
+                    &OffsetKind::ContractClass,
+                    &location.next_offset(),
+                )?;
+                Ok(location)
+            },
+        ),
+        CompressionDictionaryKind::Casm => {
+            rewrite_classes(txn, &txn.tables.casms, n_skipped_classes, batch_size, |location| {
+                let casm = txn.file_handlers.get_casm_unchecked(*location)?;
+                let location = txn.file_handlers.append_casm(&casm);
+                file_offsets_table.upsert(&txn.txn, &OffsetKind::Casm, &location.next_offset())?;
+                Ok(location)
+            })
+        }
+        CompressionDictionaryKind::DeprecatedContractClass => rewrite_classes(
+            txn,
+            &txn.tables.deprecated_declared_classes,
+            n_skipped_classes,
+            batch_size,
+            |value| {
+                let deprecated_contract_class = txn
+                    .file_handlers
+                    .get_deprecated_contract_class_unchecked(value.location_in_file)?;
+                let location =
+                    txn.file_handlers.append_deprecated_contract_class(&deprecated_contract_class);
+                file_offsets_table.upsert(
+                    &txn.txn,
+                    &OffsetKind::DeprecatedContractClass,
+                    &location.next_offset(),
+                )?;
+                Ok(IndexedDeprecatedContractClass {
+                    block_number: value.block_number,
+                    location_in_file: location,
+                })
+            },
+        ),
+    }
+}
+
+// Rewrites at most `batch_size` of the classes in the table, after skipping the given number of
+// classes, and returns the number of rewritten classes. Skipping the classes with the cursor reads
+// only the table, which is small compared to the classes.
+fn rewrite_classes<V: ValueSerde + Debug>(
+    txn: &StorageTxn<'_, RW>,
+    table_id: &TableIdentifier<ClassHash, V, SimpleTable>,
+    n_skipped_classes: u64,
+    batch_size: u64,
+    rewrite: impl Fn(&V::Value) -> StorageResult<V::Value>,
+) -> StorageResult<u64> {
+    let table = txn.open_table(table_id)?;
+    let mut cursor = table.cursor(&txn.txn)?;
+    for _ in 0..n_skipped_classes {
+        cursor.next()?;
+    }
+    let mut batch = Vec::new();
+    while batch.len() < usize::try_from(batch_size).expect("The batch size should fit in a usize.")
+    {
+        let Some(entry) = cursor.next()? else {
+            break;
+        };
+        batch.push(entry);
+    }
+    drop(cursor);
+
+    for (class_hash, value) in &batch {
+        table.upsert(&txn.txn, class_hash, &rewrite(value)?)?;
+    }
+    Ok(u64::try_from(batch.len()).expect("The batch size should fit in a u64."))
+}
+
+// Releases the disk space of the file of the given kind of classes before the first class that is
+// in use. The file holds only the classes of the kind, so the space before it holds only previous
+// copies of rewritten classes.
+fn release_previous_class_copies(
+    txn: &StorageTxn<'_, RW>,
+    kind: CompressionDictionaryKind,
+) -> StorageResult<()> {
+    match kind {
+        CompressionDictionaryKind::SierraProgram => {
+            if let Some(location) = first_class_location(txn, &txn.tables.declared_classes, |v| *v)?
+            {
+                txn.file_handlers.contract_class.release_up_to(location)?;
+            }
+        }
+        CompressionDictionaryKind::Casm => {
+            if let Some(location) = first_class_location(txn, &txn.tables.casms, |v| *v)? {
+                txn.file_handlers.casm.release_up_to(location)?;
+            }
+        }
+        CompressionDictionaryKind::DeprecatedContractClass => {
+            if let Some(location) =
+                first_class_location(txn, &txn.tables.deprecated_declared_classes, |value| {
+                    value.location_in_file
+                })?
+            {
+                txn.file_handlers.deprecated_contract_class.release_up_to(location)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Returns the location of the class in the table that comes first in its file.
+fn first_class_location<V: ValueSerde + Debug>(
+    txn: &StorageTxn<'_, RW>,
+    table_id: &TableIdentifier<ClassHash, V, SimpleTable>,
+    get_location: impl Fn(&V::Value) -> LocationInFile,
+) -> StorageResult<Option<LocationInFile>> {
+    let table = txn.open_table(table_id)?;
+    let mut cursor = table.cursor(&txn.txn)?;
+    let mut first_location = None;
+    while let Some((_class_hash, value)) = cursor.next()? {
+        let location = get_location(&value);
+        first_location = Some(first_location.map_or(location, |first| location.min(first)));
+    }
+    Ok(first_location)
+}
@@ -0,0 +1,87 @@
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use pretty_assertions::assert_eq;
+use starknet_api::core::ClassHash;
+use starknet_api::felt;
+use starknet_api::test_utils::read_json_file;
+
+use crate::compiled_class::{CasmStorageReader, CasmStorageWriter};
+use crate::compression_dictionaries::COMPRESSION_DICTIONARIES_MIGRATION;
+use crate::compression_utils::{CompressionDictionaryId, CompressionDictionaryKind};
+use crate::db::table_types::Table;
+use crate::migrations::{run_migrations, STORAGE_MIGRATIONS};
+use crate::serialization::serializers::casm_to_compress;
+use crate::test_utils::get_test_storage_with_config_by_scope;
+use crate::version::{VersionStorageReader, VERSION_STATE_KEY};
+use crate::{open_storage, OffsetKind, StorageScope, STORAGE_VERSION_STATE};
+
+#[test]
+fn add_dictionary_and_migrate() {
+    let ((reader, mut writer), config, _temp_dir) =
+        get_test_storage_with_config_by_scope(StorageScope::FullArchive);
+    let casm: CasmContractClass =
+        serde_json::from_value(read_json_file("compiled_class.json")).unwrap();
+    let (old_class_hash, new_class_hash) = (ClassHash(felt!("0x1")), ClassHash(felt!("0x2")));
+    writer.begin_rw_txn().unwrap().append_casm(&old_class_hash, &casm).unwrap().commit().unwrap();
+    let casm_file_size = || {
+        let txn = reader.begin_ro_txn().unwrap();
+        let file_offsets_table = txn.txn.open_table(&txn.tables.file_offsets).unwrap();
+        file_offsets_table.get(&txn.txn, &OffsetKind::Casm).unwrap().unwrap()
+    };
+    let size_without_dictionary = casm_file_size();
+
+    // A dictionary with the content of the CASM compresses it to almost nothing.
+    let id = writer
+        .add_compression_dictionary(
+            CompressionDictionaryKind::Casm,
+            casm_to_compress(&casm).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(id, CompressionDictionaryId(0));
+    writer.begin_rw_txn().unwrap().append_casm(&new_class_hash, &casm).unwrap().commit().unwrap();
+    let size_with_dictionary = casm_file_size() - size_without_dictionary;
+    assert!(size_with_dictionary < size_without_dictionary / 5);
+
+    // Both the classes written before and after the dictionary was added are readable.
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_casm(&old_class_hash).unwrap().unwrap(), casm);
+    assert_eq!(txn.get_casm(&new_class_hash).unwrap().unwrap(), casm);
+    drop(txn);
+
+    // Simulate a storage of the state version that precedes the dictionaries. The migration
+    // rewrites the CASMs with their existing dictionary, and doesn't train dictionaries for the
+    // other kinds, which have no classes.
+    let txn = writer.begin_rw_txn().unwrap();
+    let version_table = txn.open_table(&txn.tables.storage_version).unwrap();
+    version_table
+        .upsert(
+            &txn.txn,
+            &VERSION_STATE_KEY.to_string(),
+            &COMPRESSION_DICTIONARIES_MIGRATION.from_version,
+        )
+        .unwrap();
+    txn.commit().unwrap();
+    let completed_migrations = run_migrations(&mut writer, STORAGE_MIGRATIONS).unwrap();
+    assert_eq!(completed_migrations.len(), 1);
+    assert_eq!(completed_migrations[0].name, COMPRESSION_DICTIONARIES_MIGRATION.name);
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_state_version().unwrap(),
+        Some(STORAGE_VERSION_STATE)
+    );
+    assert_eq!(casm_file_size(), size_without_dictionary + 3 * size_with_dictionary);
+    // The classes are readable after the space of their previous copies was released.
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_casm(&old_class_hash).unwrap().unwrap(), casm);
+    assert_eq!(txn.get_casm(&new_class_hash).unwrap().unwrap(), casm);
+    drop(txn);
+
+    // The dictionaries are loaded when the storage is opened.
+    drop((reader, writer));
+    let (reader, mut writer) = open_storage(config).unwrap();
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_casm(&old_class_hash).unwrap().unwrap(), casm);
+    assert_eq!(txn.get_casm(&new_class_hash).unwrap().unwrap(), casm);
+    drop(txn);
+    let id =
+        writer.add_compression_dictionary(CompressionDictionaryKind::Casm, vec![1, 2, 3]).unwrap();
+    assert_eq!(id, CompressionDictionaryId(1));
+}
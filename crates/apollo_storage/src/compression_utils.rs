@@ -1,7 +1,26 @@
+//! Compression of the values in the storage.
+//!
+//! Every compressed value starts with the tag of the [`CompressionCodec`] it was written with,
+//! followed by the codec's payload. Values written before codecs were introduced are bare zstd
+//! frames, and are recognized by the zstd magic number, which never collides with a tag.
+//!
+//! The classes, whose programs are similar to each other, can be compressed with zstd dictionaries
+//! trained on the classes of the storage (see [`crate::compression_dictionaries`]). A dictionary is
+//! never changed nor deleted, so every value stays readable; the storage compresses new values of
+//! each [`CompressionDictionaryKind`] with the latest dictionary of that kind.
+
 #[cfg(test)]
 #[path = "compression_utils_test.rs"]
 mod compression_utils_test;
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::io::{Error, ErrorKind};
+use std::sync::{Arc, RwLock};
+
+use zstd::dict::{DecoderDictionary, EncoderDictionary};
+
 use crate::db::serialization::{StorageSerde, StorageSerdeError};
 
 // TODO(dvir): create one compressor/decompressor only once (maybe only once per thread) to prevent
@@ -13,8 +32,133 @@ use crate::db::serialization::{StorageSerde, StorageSerdeError};
 // TODO(Dvir): consider defining this for each type separately and pass it as an argument to the
 // decompress function.
 pub(crate) const MAX_DECOMPRESSED_SIZE: usize = 1 << 28; // 256 MB
-// The compression level to use. Higher levels are slower but compress better.
+                                                         // The compression level to use. Higher levels are slower but compress better.
 const COMPRESSION_LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
+// The first byte of the magic number of a zstd frame, with which values written without a codec
+// tag start.
+const ZSTD_MAGIC_FIRST_BYTE: u8 = 0x28;
+
+/// The codec with which a value was compressed. Its tag is the first byte of the compressed value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum CompressionCodec {
+    /// A zstd frame.
+    Zstd = 1,
+    /// The id of a dictionary, as a big-endian u32, followed by a zstd frame compressed with it.
+    ZstdWithDictionary = 2,
+}
+
+impl CompressionCodec {
+    /// The tag of the codec, which is the first byte of the values compressed with it.
+    #[allow(clippy::as_conversions)]
+    pub const fn tag(self) -> u8 {
+        self as u8
+    }
+}
+
+/// The kinds of values that are compressed with dictionaries.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
+pub enum CompressionDictionaryKind {
+    /// The program of a Sierra contract class.
+    SierraProgram,
+    /// A CASM contract class.
+    Casm,
+    /// The program and the ABI of a deprecated contract class.
+    DeprecatedContractClass,
+}
+
+/// The id of a compression dictionary in the storage. Ids are assigned in increasing order.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, Ord, PartialOrd)]
+pub struct CompressionDictionaryId(pub u32);
+
+/// A compression dictionary as it is kept in the storage.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct StoredCompressionDictionary {
+    pub kind: CompressionDictionaryKind,
+    pub dictionary: Vec<u8>,
+}
+
+// A dictionary prepared for compression and decompression.
+struct PreparedDictionary {
+    encoder: EncoderDictionary<'static>,
+    decoder: DecoderDictionary<'static>,
+}
+
+/// The compression dictionaries of a storage.
+#[derive(Default)]
+pub(crate) struct CompressionDictionaries {
+    by_id: HashMap<CompressionDictionaryId, PreparedDictionary>,
+    // The dictionary with which new values of each kind are compressed.
+    latest: HashMap<CompressionDictionaryKind, CompressionDictionaryId>,
+}
+
+impl CompressionDictionaries {
+    pub(crate) fn n_dictionaries(&self) -> usize {
+        self.by_id.len()
+    }
+
+    pub(crate) fn has_dictionary_of_kind(&self, kind: CompressionDictionaryKind) -> bool {
+        self.latest.contains_key(&kind)
+    }
+
+    // Adds a dictionary, which becomes the latest of its kind if its id is the largest.
+    pub(crate) fn insert(
+        &mut self,
+        id: CompressionDictionaryId,
+        stored_dictionary: &StoredCompressionDictionary,
+    ) {
+        self.by_id.insert(
+            id,
+            PreparedDictionary {
+                encoder: EncoderDictionary::copy(&stored_dictionary.dictionary, COMPRESSION_LEVEL),
+                decoder: DecoderDictionary::copy(&stored_dictionary.dictionary),
+            },
+        );
+        let latest = self.latest.entry(stored_dictionary.kind).or_insert(id);
+        *latest = id.max(*latest);
+    }
+}
+
+impl Debug for CompressionDictionaries {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CompressionDictionaries").field("latest", &self.latest).finish()
+    }
+}
+
+/// The compression dictionaries of a storage, shared by its reader and writer.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SharedCompressionDictionaries(Arc<RwLock<Arc<CompressionDictionaries>>>);
+
+impl SharedCompressionDictionaries {
+    pub(crate) fn new(dictionaries: CompressionDictionaries) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(dictionaries))))
+    }
+
+    pub(crate) fn get(&self) -> Arc<CompressionDictionaries> {
+        self.0.read().expect("Compression dictionaries lock is poisoned.").clone()
+    }
+
+    pub(crate) fn set(&self, dictionaries: CompressionDictionaries) {
+        *self.0.write().expect("Compression dictionaries lock is poisoned.") =
+            Arc::new(dictionaries);
+    }
+
+    /// Runs `f` such that the values it serializes and deserializes are compressed and
+    /// decompressed with these dictionaries.
+    pub(crate) fn scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT_DICTIONARIES.with(|current| current.replace(Some(self.get())));
+        let result = f();
+        CURRENT_DICTIONARIES.with(|current| current.replace(previous));
+        result
+    }
+}
+
+thread_local! {
+    // The dictionaries of the storage whose values are being serialized or deserialized, since
+    // StorageSerde has no context.
+    static CURRENT_DICTIONARIES: RefCell<Option<Arc<CompressionDictionaries>>> =
+        const { RefCell::new(None) };
+}
 
 /// Returns the compressed data in a vector.
 ///
@@ -24,7 +168,36 @@ const COMPRESSION_LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
 /// # Errors
 /// Returns [`std::io::Error`] if any read error is encountered.
 pub fn compress(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
-    zstd::bulk::compress(data, COMPRESSION_LEVEL)
+    let mut compressed = vec![CompressionCodec::Zstd.tag()];
+    compressed.extend(zstd::bulk::compress(data, COMPRESSION_LEVEL)?);
+    Ok(compressed)
+}
+
+/// Returns the data compressed with the latest dictionary of the given kind, or without a
+/// dictionary if there is none.
+///
+/// # Errors
+/// Returns [`std::io::Error`] if any read error is encountered.
+pub(crate) fn compress_with_dictionary(
+    data: &[u8],
+    kind: CompressionDictionaryKind,
+) -> Result<Vec<u8>, std::io::Error> {
+    CURRENT_DICTIONARIES.with(|current| {
+        let current = current.borrow();
+        let Some((id, dictionary)) = current.as_ref().and_then(|dictionaries| {
+            let id = dictionaries.latest.get(&kind)?;
+            Some((id, &dictionaries.by_id[id]))
+        }) else {
+            return compress(data);
+        };
+        let mut compressed = vec![CompressionCodec::ZstdWithDictionary.tag()];
+        compressed.extend(id.0.to_be_bytes());
+        compressed.extend(
+            zstd::bulk::Compressor::with_prepared_dictionary(&dictionary.encoder)?
+                .compress(data)?,
+        );
+        Ok(compressed)
+    })
 }
 
 /// Serialized and then compress object.
@@ -48,7 +221,34 @@ pub fn serialize_and_compress(object: &impl StorageSerde) -> Result<Vec<u8>, Sto
 /// # Errors
 /// Returns [`std::io::Error`] if any read error is encountered.
 pub fn decompress(data: &[u8]) -> Result<Vec<u8>, std::io::Error> {
-    zstd::bulk::decompress(data, MAX_DECOMPRESSED_SIZE)
+    match data.split_first() {
+        Some((&ZSTD_MAGIC_FIRST_BYTE, _)) => zstd::bulk::decompress(data, MAX_DECOMPRESSED_SIZE),
+        Some((&tag, frame)) if tag == CompressionCodec::Zstd.tag() => {
+            zstd::bulk::decompress(frame, MAX_DECOMPRESSED_SIZE)
+        }
+        Some((&tag, payload)) if tag == CompressionCodec::ZstdWithDictionary.tag() => {
+            let (id, frame) = payload.split_first_chunk::<4>().ok_or_else(|| {
+                Error::new(ErrorKind::InvalidData, "Missing the compression dictionary id.")
+            })?;
+            let id = CompressionDictionaryId(u32::from_be_bytes(*id));
+            CURRENT_DICTIONARIES.with(|current| {
+                let current = current.borrow();
+                let dictionary = current
+                    .as_ref()
+                    .and_then(|dictionaries| dictionaries.by_id.get(&id))
+                    .ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::NotFound,
+                            format!("Missing the compression dictionary {id:?}."),
+                        )
+                    })?;
+                let mut decompressor =
+                    zstd::bulk::Decompressor::with_prepared_dictionary(&dictionary.decoder)?;
+                decompressor.decompress(frame, MAX_DECOMPRESSED_SIZE)
+            })
+        }
+        _ => Err(Error::new(ErrorKind::InvalidData, "Unknown compression codec.")),
+    }
 }
 
 /// Decompress a vector directly from a reader.
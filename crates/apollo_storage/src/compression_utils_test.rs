@@ -2,7 +2,19 @@ use pretty_assertions::assert_eq;
 use starknet_api::deprecated_contract_class::Program;
 use starknet_api::test_utils::read_json_file;
 
-use super::{compress, decompress, decompress_from_reader, serialize_and_compress};
+use super::{
+    compress,
+    compress_with_dictionary,
+    decompress,
+    decompress_from_reader,
+    serialize_and_compress,
+    CompressionCodec,
+    CompressionDictionaries,
+    CompressionDictionaryId,
+    CompressionDictionaryKind,
+    SharedCompressionDictionaries,
+    StoredCompressionDictionary,
+};
 use crate::db::serialization::StorageSerde;
 
 #[test]
@@ -23,3 +35,43 @@ fn object_compression() {
     let restored_program = Program::deserialize_from(&mut decompressed.as_slice()).unwrap();
     assert_eq!(program, restored_program);
 }
+
+#[test]
+fn legacy_compression_is_readable() {
+    let bytes = vec![30, 5, 23, 12, 47];
+    let legacy_compressed = zstd::bulk::compress(bytes.as_slice(), 0).unwrap();
+    assert_eq!(decompress(legacy_compressed.as_slice()).unwrap(), bytes);
+    assert_eq!(compress(bytes.as_slice()).unwrap()[0], CompressionCodec::Zstd.tag());
+}
+
+#[test]
+fn dictionary_compression() {
+    let bytes = b"a sierra program that looks like many other sierra programs".to_vec();
+    let mut dictionaries = CompressionDictionaries::default();
+    dictionaries.insert(
+        CompressionDictionaryId(7),
+        &StoredCompressionDictionary {
+            kind: CompressionDictionaryKind::SierraProgram,
+            dictionary: bytes.clone(),
+        },
+    );
+    let dictionaries = SharedCompressionDictionaries::new(dictionaries);
+
+    let compressed = dictionaries.scope(|| {
+        compress_with_dictionary(bytes.as_slice(), CompressionDictionaryKind::SierraProgram)
+            .unwrap()
+    });
+    assert_eq!(compressed[0], CompressionCodec::ZstdWithDictionary.tag());
+    assert_eq!(compressed[1..5], 7_u32.to_be_bytes());
+    assert!(compressed.len() < compress(bytes.as_slice()).unwrap().len());
+    assert_eq!(dictionaries.scope(|| decompress(compressed.as_slice()).unwrap()), bytes);
+    // The dictionary is needed for decompressing.
+    assert!(decompress(compressed.as_slice()).is_err());
+
+    // Kinds without a dictionary are compressed without one.
+    let compressed = dictionaries.scope(|| {
+        compress_with_dictionary(bytes.as_slice(), CompressionDictionaryKind::Casm).unwrap()
+    });
+    assert_eq!(compressed[0], CompressionCodec::Zstd.tag());
+    assert_eq!(decompress(compressed.as_slice()).unwrap(), bytes);
+}
@@ -1,8 +1,12 @@
+use std::fmt::Debug;
+
 use human_bytes::human_bytes;
 use libmdbx::Info;
 use serde::{Deserialize, Serialize};
 
-use super::{DbReader, DbResult};
+use super::serialization::{Key, ValueSerde};
+use super::table_types::TableType;
+use super::{DbReader, DbResult, DbTransaction, TableIdentifier, TransactionKind};
 
 /// A single table statistics.
 #[derive(Serialize, Deserialize, Debug)]
@@ -92,6 +96,17 @@ impl DbReader {
     }
 }
 
+impl<Mode: TransactionKind> DbTransaction<'_, Mode> {
+    // Returns the number of entries in a table, as seen by this transaction.
+    pub(crate) fn get_table_entries<K: Key + Debug, V: ValueSerde + Debug, T: TableType>(
+        &self,
+        table_id: &TableIdentifier<K, V, T>,
+    ) -> DbResult<usize> {
+        let table = self.txn.open_table(Some(table_id.name))?;
+        Ok(self.txn.table_stat(&table)?.entries())
+    }
+}
+
 // Serialize bytes as a human readable string.
 // For example 1024*1024 bytes will be serialized as "1 MiB".
 fn readable_bytes<S>(bytes_num: &u64, s: S) -> Result<S::Ok, S::Error>
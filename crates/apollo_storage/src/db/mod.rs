@@ -33,17 +33,19 @@ use apollo_config::dumping::{ser_param, SerializeConfig};
 use apollo_config::validators::validate_ascii;
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 use apollo_proc_macros::latency_histogram;
-use libmdbx::{DatabaseFlags, Geometry, PageSize, WriteMap};
+use libmdbx::{DatabaseFlags, Geometry, PageSize, WriteFlags, WriteMap};
 use serde::{Deserialize, Serialize};
 use starknet_api::core::ChainId;
 use validator::Validate;
 
 use self::serialization::{Key, ValueSerde};
 use self::table_types::{DbCursor, DbCursorTrait};
-use crate::db::table_types::TableType;
+use crate::db::table_types::{CommonPrefix, SimpleTable, TableType};
 
 // Maximum number of Sub-Databases.
-const MAX_DBS: usize = 20;
+const MAX_DBS: usize = 24;
+// Maximum number of concurrent read transactions, in all the processes that use the environment.
+const MAX_READERS: u32 = 1 << 13; // 8K readers
 
 // Note that NO_TLS mode is used by default.
 type EnvironmentKind = WriteMap;
@@ -167,7 +169,7 @@ pub enum DbError {
     Append,
 }
 
-type DbResult<V> = result::Result<V, DbError>;
+pub(crate) type DbResult<V> = result::Result<V, DbError>;
 
 /// A helper struct for DbError::KeyAlreadyExists.
 #[derive(Debug)]
@@ -196,7 +198,6 @@ pub(crate) fn open_env(config: &DbConfig) -> DbResult<(DbReader, DbWriter)> {
     if config.enforce_file_exists && !db_file_path.exists() {
         return Err(DbError::FileDoesNotExist(db_file_path));
     }
-    const MAX_READERS: u32 = 1 << 13; // 8K readers
     let env = Arc::new(
         Environment::new()
             .set_geometry(Geometry {
@@ -220,6 +221,29 @@ pub(crate) fn open_env(config: &DbConfig) -> DbResult<(DbReader, DbWriter)> {
     Ok((DbReader { env: env.clone() }, DbWriter { env }))
 }
 
+/// Opens an existing MDBX environment for reading only, and returns a reader to it. The environment
+/// may be used at the same time by another process that writes to it; MDBX remaps the database
+/// file when it grows, so the reader's transactions always see the latest committed data.
+pub(crate) fn open_env_read_only(config: &DbConfig) -> DbResult<DbReader> {
+    let db_file_path = config.path().join("mdbx.dat");
+    if !db_file_path.exists() {
+        return Err(DbError::FileDoesNotExist(db_file_path));
+    }
+    // The geometry is determined by the existing database.
+    let env = Arc::new(
+        Environment::new()
+            .set_max_tables(MAX_DBS)
+            .set_max_readers(MAX_READERS)
+            .set_flags(DatabaseFlags {
+                mode: libmdbx::Mode::ReadOnly,
+                no_rdahead: true,
+                ..Default::default()
+            })
+            .open(&config.path())?,
+    );
+    Ok(DbReader { env })
+}
+
 // Size in bytes.
 const MDBX_MIN_PAGESIZE: usize = 256;
 const MDBX_MAX_PAGESIZE: usize = 65536; // 64KB
@@ -249,6 +273,74 @@ impl DbReader {
     pub(crate) fn begin_ro_txn(&self) -> DbResult<DbReadTransaction<'_>> {
         Ok(DbReadTransaction { txn: self.env.begin_ro_txn()? })
     }
+
+    // Returns the identifier of an existing table, without creating it.
+    fn existing_table<K: Key + Debug, V: ValueSerde + Debug, T: TableType>(
+        &self,
+        name: &'static str,
+    ) -> DbResult<TableIdentifier<K, V, T>> {
+        self.begin_ro_txn()?.txn.open_table(Some(name))?;
+        Ok(TableIdentifier {
+            name,
+            _key_type: PhantomData {},
+            _value_type: PhantomData {},
+            _table_type: PhantomData {},
+        })
+    }
+}
+
+/// Provides the identifiers of the tables of the database. A [`DbWriter`] creates the tables that
+/// don't exist, while a [`DbReader`] fails on them.
+pub(crate) trait TableOpener {
+    fn open_simple_table<K: Key + Debug, V: ValueSerde + Debug>(
+        &mut self,
+        name: &'static str,
+    ) -> DbResult<TableIdentifier<K, V, SimpleTable>>;
+
+    fn open_common_prefix_table<MainKey: Key + Debug, SubKey: Key + Debug, V: ValueSerde + Debug>(
+        &mut self,
+        name: &'static str,
+    ) -> DbResult<TableIdentifier<(MainKey, SubKey), V, CommonPrefix>>
+    where
+        (MainKey, SubKey): Key + Debug;
+}
+
+impl TableOpener for DbWriter {
+    fn open_simple_table<K: Key + Debug, V: ValueSerde + Debug>(
+        &mut self,
+        name: &'static str,
+    ) -> DbResult<TableIdentifier<K, V, SimpleTable>> {
+        self.create_simple_table(name)
+    }
+
+    fn open_common_prefix_table<MainKey: Key + Debug, SubKey: Key + Debug, V: ValueSerde + Debug>(
+        &mut self,
+        name: &'static str,
+    ) -> DbResult<TableIdentifier<(MainKey, SubKey), V, CommonPrefix>>
+    where
+        (MainKey, SubKey): Key + Debug,
+    {
+        self.create_common_prefix_table(name)
+    }
+}
+
+impl TableOpener for DbReader {
+    fn open_simple_table<K: Key + Debug, V: ValueSerde + Debug>(
+        &mut self,
+        name: &'static str,
+    ) -> DbResult<TableIdentifier<K, V, SimpleTable>> {
+        self.existing_table(name)
+    }
+
+    fn open_common_prefix_table<MainKey: Key + Debug, SubKey: Key + Debug, V: ValueSerde + Debug>(
+        &mut self,
+        name: &'static str,
+    ) -> DbResult<TableIdentifier<(MainKey, SubKey), V, CommonPrefix>>
+    where
+        (MainKey, SubKey): Key + Debug,
+    {
+        self.existing_table(name)
+    }
 }
 
 type DbReadTransaction<'env> = DbTransaction<'env, RO>;
@@ -257,6 +349,12 @@ impl DbWriter {
     pub(crate) fn begin_rw_txn(&mut self) -> DbResult<DbWriteTransaction<'_>> {
         Ok(DbWriteTransaction { txn: self.env.begin_rw_txn()? })
     }
+
+    // Returns another writer of the environment. Beginning a write transaction blocks while
+    // another writer's write transaction is open.
+    pub(crate) fn another_writer(&self) -> Self {
+        Self { env: self.env.clone() }
+    }
 }
 
 type DbWriteTransaction<'env> = DbTransaction<'env, RW>;
@@ -294,6 +392,40 @@ impl<Mode: TransactionKind> DbTransaction<'_, Mode> {
         })
     }
 }
+impl<Mode: TransactionKind> DbTransaction<'_, Mode> {
+    // Calls the given function on the serialized key and value of each entry of the table with the
+    // given name, in the order of the table.
+    pub(crate) fn for_each_raw_entry<E: From<DbError>>(
+        &self,
+        table_name: &str,
+        mut f: impl FnMut(&[u8], &[u8]) -> result::Result<(), E>,
+    ) -> result::Result<(), E> {
+        let table = self.txn.open_table(Some(table_name)).map_err(DbError::from)?;
+        let mut cursor = self.txn.cursor(&table).map_err(DbError::from)?;
+        let mut entry = cursor.first::<DbKeyType<'_>, DbValueType<'_>>().map_err(DbError::from)?;
+        while let Some((key, value)) = entry {
+            f(&key, &value)?;
+            entry = cursor.next::<DbKeyType<'_>, DbValueType<'_>>().map_err(DbError::from)?;
+        }
+        Ok(())
+    }
+}
+
+impl DbTransaction<'_, RW> {
+    // Writes the given serialized keys and values to the table with the given name.
+    pub(crate) fn put_raw_entries(
+        &self,
+        table_name: &str,
+        entries: &[(Vec<u8>, Vec<u8>)],
+    ) -> DbResult<()> {
+        let table = self.txn.open_table(Some(table_name))?;
+        for (key, value) in entries {
+            self.txn.put(&table, key, value, WriteFlags::UPSERT)?;
+        }
+        Ok(())
+    }
+}
+
 pub(crate) struct TableIdentifier<K: Key + Debug, V: ValueSerde + Debug, T: TableType> {
     pub(crate) name: &'static str,
     _key_type: PhantomData<K>,
@@ -8,7 +8,7 @@ use std::marker::PhantomData;
 
 use libmdbx::{TableFlags, WriteFlags};
 
-use super::{DbResult, Table, TableType};
+use super::{DbResult, DecodeRawEntry, Table, TableType};
 use crate::db::serialization::{Key as KeyTrait, StorageSerde, StorageSerdeEx, ValueSerde};
 use crate::db::{
     DbCursor,
@@ -114,6 +114,18 @@ where
     }
 }
 
+// The raw entries of a dup-sort table are its main-keys, each with the bytes of a sub-key and
+// value.
+impl<MainKey: KeyTrait, SubKey: KeyTrait, V: ValueSerde> DecodeRawEntry<(MainKey, SubKey), V>
+    for CommonPrefix
+where
+    (MainKey, SubKey): KeyTrait,
+{
+    fn decode_raw_entry(key: &[u8], value: &[u8]) -> Option<((MainKey, SubKey), V::Value)> {
+        <Self as DupSortUtils<(MainKey, SubKey), V>>::get_key_value_pair(key, value)
+    }
+}
+
 // Adds one to the number represented by the bytes.
 fn add_one(bytes: &mut Vec<u8>) {
     for byte in bytes.iter_mut().rev() {
@@ -68,6 +68,13 @@ pub(crate) trait Table<'env> {
     fn delete(&'env self, txn: &DbTransaction<'env, RW>, key: &Self::Key) -> DbResult<()>;
 }
 
+// Decodes an entry of a table of this type from its key and value as they are stored in the
+// database, i.e., as returned by iterating the raw entries of the table. Returns None if the entry
+// can't be deserialized.
+pub(crate) trait DecodeRawEntry<K: KeyTrait, V: ValueSerde>: TableType {
+    fn decode_raw_entry(key: &[u8], value: &[u8]) -> Option<(K, V::Value)>;
+}
+
 // TODO(dvir): consider adding append functionality using a cursor. It should be more efficient for
 // more than a single append operation (also for other table types).
 pub(crate) trait DbCursorTrait {
@@ -8,7 +8,7 @@ use std::marker::PhantomData;
 
 use libmdbx::{TableFlags, WriteFlags};
 
-use super::{DbResult, Table, TableType};
+use super::{DbResult, DecodeRawEntry, Table, TableType};
 use crate::db::serialization::{Key as KeyTrait, ValueSerde};
 use crate::db::table_types::DbCursorTrait;
 use crate::db::{
@@ -30,6 +30,12 @@ pub(crate) struct SimpleTable;
 
 impl TableType for SimpleTable {}
 
+impl<K: KeyTrait, V: ValueSerde> DecodeRawEntry<K, V> for SimpleTable {
+    fn decode_raw_entry(mut key: &[u8], mut value: &[u8]) -> Option<(K, V::Value)> {
+        Some((K::deserialize(&mut key)?, V::deserialize(&mut value)?))
+    }
+}
+
 impl DbWriter {
     pub(crate) fn create_simple_table<K: KeyTrait + Debug, V: ValueSerde + Debug>(
         &mut self,
@@ -0,0 +1,862 @@
+//! Export of ranges of blocks to files, for analytics.
+//!
+//! [`export_blocks`] reads the blocks of a range from the storage and writes their data to files of
+//! rows, one directory per [`Dataset`]:
+//! - `blocks`: a row per block header.
+//! - `transactions`: a row per transaction.
+//! - `receipts`: a row per transaction output.
+//! - `events`: a row per event.
+//! - `state_diffs`: a row per entry of a state diff, e.g., a storage update or a declared class.
+//!
+//! The columns of each dataset are listed in [`Dataset::schema`] and are changed only together with
+//! [`EXPORT_SCHEMA_VERSION`]. Felts are written as `0x`-prefixed hex strings, amounts that may
+//! exceed 64 bits (fees and gas prices) as decimal strings, and nested data (e.g., the transactions
+//! themselves) as JSON strings.
+//!
+//! The range is split into partitions of [`ExportConfig::blocks_per_partition`] blocks, aligned to
+//! multiples of the partition size. Each partition is read in a single read transaction and written
+//! to a file per dataset, named `<dataset>/<dataset>_<first block>_<last block>.<extension>`. The
+//! files are written under a temporary name and renamed once complete. After all the files of a
+//! partition are written, the checkpoint file in the output directory is updated, so an
+//! interrupted export resumes from the first partition that wasn't completed.
+//!
+//! The export only reads the storage, so it can run against the storage of a stopped node, or of a
+//! running one if the storage is opened with
+//! [`open_storage_read_only`](crate::read_only::open_storage_read_only). The `storage_export`
+//! binary runs an export of the storage in a given directory.
+//!
+//! Files are written as JSON lines, or as Parquet files with the `parquet` feature.
+
+#[cfg(test)]
+#[path = "export_test.rs"]
+mod export_test;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "parquet")]
+use parquet::basic::{Compression, LogicalType, Repetition, Type as PhysicalType, ZstdLevel};
+#[cfg(feature = "parquet")]
+use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+#[cfg(feature = "parquet")]
+use parquet::file::writer::SerializedFileWriter;
+#[cfg(feature = "parquet")]
+use parquet::schema::types::Type as ParquetType;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "parquet")]
+use serde_json::{Map, Value};
+use starknet_api::block::{BlockHeader, BlockNumber};
+use starknet_api::data_availability::L1DataAvailabilityMode;
+use starknet_api::state::ThinStateDiff;
+use starknet_api::transaction::{
+    Transaction,
+    TransactionExecutionStatus,
+    TransactionHash,
+    TransactionOutput,
+};
+use starknet_types_core::felt::Felt;
+use tracing::info;
+
+use crate::body::BodyStorageReader;
+use crate::db::RO;
+use crate::header::HeaderStorageReader;
+use crate::state::StateStorageReader;
+use crate::{StorageReader, StorageResult, StorageTxn};
+
+/// The version of the schema of the exported datasets.
+pub const EXPORT_SCHEMA_VERSION: u32 = 1;
+/// The name of the checkpoint file in the output directory.
+pub const CHECKPOINT_FILE_NAME: &str = "checkpoint.json";
+// The maximal number of rows in a row group of a Parquet file.
+#[cfg(feature = "parquet")]
+const PARQUET_ROW_GROUP_SIZE: usize = 1 << 16;
+
+/// The format of the exported files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// A JSON object per line.
+    Jsonl,
+    /// Zstd-compressed Parquet files.
+    #[cfg(feature = "parquet")]
+    Parquet,
+}
+
+impl ExportFormat {
+    fn file_extension(&self) -> &'static str {
+        match self {
+            Self::Jsonl => "jsonl",
+            #[cfg(feature = "parquet")]
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+/// A kind of exported rows, written to its own directory.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Dataset {
+    /// A row per block header.
+    Blocks,
+    /// A row per transaction.
+    Transactions,
+    /// A row per transaction output.
+    Receipts,
+    /// A row per event.
+    Events,
+    /// A row per block state diff.
+    StateDiffs,
+}
+
+impl Dataset {
+    /// All the datasets.
+    pub const ALL: [Self; 5] =
+        [Self::Blocks, Self::Transactions, Self::Receipts, Self::Events, Self::StateDiffs];
+
+    /// The name of the dataset, which is also the name of its directory.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Blocks => "blocks",
+            Self::Transactions => "transactions",
+            Self::Receipts => "receipts",
+            Self::Events => "events",
+            Self::StateDiffs => "state_diffs",
+        }
+    }
+
+    /// The columns of the rows of the dataset, by their order in the files.
+    pub fn schema(&self) -> &'static [Column] {
+        match self {
+            Self::Blocks => BLOCKS_SCHEMA,
+            Self::Transactions => TRANSACTIONS_SCHEMA,
+            Self::Receipts => RECEIPTS_SCHEMA,
+            Self::Events => EVENTS_SCHEMA,
+            Self::StateDiffs => STATE_DIFFS_SCHEMA,
+        }
+    }
+}
+
+/// The type of the values of a column.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ColumnType {
+    /// An unsigned 64-bit integer.
+    U64,
+    /// A string.
+    String,
+    /// A string that may be null.
+    OptionalString,
+}
+
+/// A column of the rows of a dataset.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Column {
+    /// The name of the column.
+    pub name: &'static str,
+    /// The type of the values of the column.
+    pub column_type: ColumnType,
+}
+
+const fn column(name: &'static str, column_type: ColumnType) -> Column {
+    Column { name, column_type }
+}
+
+const BLOCKS_SCHEMA: &[Column] = &[
+    column("block_number", ColumnType::U64),
+    column("block_hash", ColumnType::String),
+    column("parent_hash", ColumnType::String),
+    column("state_root", ColumnType::String),
+    column("sequencer_address", ColumnType::String),
+    column("timestamp", ColumnType::U64),
+    column("l1_gas_price_wei", ColumnType::String),
+    column("l1_gas_price_fri", ColumnType::String),
+    column("l1_data_gas_price_wei", ColumnType::String),
+    column("l1_data_gas_price_fri", ColumnType::String),
+    column("l2_gas_price_wei", ColumnType::String),
+    column("l2_gas_price_fri", ColumnType::String),
+    column("l1_da_mode", ColumnType::String),
+    column("starknet_version", ColumnType::String),
+    column("transaction_count", ColumnType::U64),
+    column("event_count", ColumnType::U64),
+];
+
+const TRANSACTIONS_SCHEMA: &[Column] = &[
+    column("block_number", ColumnType::U64),
+    column("transaction_index", ColumnType::U64),
+    column("transaction_hash", ColumnType::String),
+    column("transaction_type", ColumnType::String),
+    column("transaction", ColumnType::String),
+];
+
+const RECEIPTS_SCHEMA: &[Column] = &[
+    column("block_number", ColumnType::U64),
+    column("transaction_index", ColumnType::U64),
+    column("transaction_hash", ColumnType::String),
+    column("actual_fee", ColumnType::String),
+    column("execution_status", ColumnType::String),
+    column("revert_reason", ColumnType::OptionalString),
+    column("messages_sent", ColumnType::String),
+    column("execution_resources", ColumnType::String),
+    column("event_count", ColumnType::U64),
+];
+
+const EVENTS_SCHEMA: &[Column] = &[
+    column("block_number", ColumnType::U64),
+    column("transaction_index", ColumnType::U64),
+    column("event_index", ColumnType::U64),
+    column("transaction_hash", ColumnType::String),
+    column("from_address", ColumnType::String),
+    column("keys", ColumnType::String),
+    column("data", ColumnType::String),
+];
+
+const STATE_DIFFS_SCHEMA: &[Column] = &[
+    column("block_number", ColumnType::U64),
+    column("entry_type", ColumnType::String),
+    column("contract_address", ColumnType::OptionalString),
+    column("key", ColumnType::OptionalString),
+    column("value", ColumnType::OptionalString),
+];
+
+/// The configuration of an export.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExportConfig {
+    /// The directory to which the files and the checkpoint are written.
+    pub output_dir: PathBuf,
+    /// The format of the files.
+    pub format: ExportFormat,
+    /// The first block to export, unless the checkpoint is beyond it.
+    pub start_block: BlockNumber,
+    /// The first block not to export. Defaults to the first block whose header, body or state diff
+    /// is missing from the storage.
+    pub end_block: Option<BlockNumber>,
+    /// The number of blocks whose rows are written to the same file of each dataset.
+    pub blocks_per_partition: u64,
+}
+
+/// The progress of an export, written to the checkpoint file after each partition.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ExportCheckpoint {
+    /// The version of the schemas of the exported datasets.
+    pub schema_version: u32,
+    /// The format of the files.
+    pub format: ExportFormat,
+    /// The first block that wasn't exported.
+    pub next_block: BlockNumber,
+}
+
+/// The result of an export.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ExportSummary {
+    /// The first block that was exported in this run.
+    pub start_block: BlockNumber,
+    /// The first block that wasn't exported.
+    pub end_block: BlockNumber,
+    /// The number of partitions that were written in this run.
+    pub n_partitions: u64,
+    /// The number of rows that were written in this run, per dataset.
+    pub n_rows: BTreeMap<Dataset, u64>,
+}
+
+/// An error that can occur when exporting blocks.
+#[allow(missing_docs)]
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error("The number of blocks per partition must be positive.")]
+    EmptyPartitions,
+    #[error(
+        "The checkpoint in {output_dir:?} is of an export of version {schema_version} in the \
+         {format:?} format, expected version {EXPORT_SCHEMA_VERSION} in the {expected_format:?} \
+         format."
+    )]
+    CheckpointMismatch {
+        output_dir: PathBuf,
+        schema_version: u32,
+        format: ExportFormat,
+        expected_format: ExportFormat,
+    },
+    #[error("The {data} of block {block_number} is missing from the storage.")]
+    MissingBlockData { block_number: BlockNumber, data: &'static str },
+    #[cfg(feature = "parquet")]
+    #[error(transparent)]
+    Parquet(#[from] parquet::errors::ParquetError),
+}
+
+/// Exports the blocks of the configured range that weren't exported yet according to the
+/// checkpoint in the output directory, and returns a summary of the export.
+pub fn export_blocks(
+    reader: &StorageReader,
+    config: &ExportConfig,
+) -> StorageResult<ExportSummary> {
+    if config.blocks_per_partition == 0 {
+        return Err(ExportError::EmptyPartitions.into());
+    }
+    let mut start_block = config.start_block;
+    if let Some(checkpoint) = read_checkpoint(&config.output_dir)? {
+        if checkpoint.schema_version != EXPORT_SCHEMA_VERSION || checkpoint.format != config.format
+        {
+            return Err(ExportError::CheckpointMismatch {
+                output_dir: config.output_dir.clone(),
+                schema_version: checkpoint.schema_version,
+                format: checkpoint.format,
+                expected_format: config.format,
+            }
+            .into());
+        }
+        start_block = start_block.max(checkpoint.next_block);
+    }
+    let end_block = match config.end_block {
+        Some(end_block) => end_block,
+        None => {
+            let txn = reader.begin_ro_txn()?;
+            txn.get_header_marker()?.min(txn.get_body_marker()?).min(txn.get_state_marker()?)
+        }
+    };
+    info!("Exporting blocks {start_block} to {end_block} to {:?}.", config.output_dir);
+
+    let mut summary = ExportSummary { start_block, end_block: start_block, ..Default::default() };
+    while summary.end_block < end_block {
+        let partition_start = summary.end_block;
+        let partition_end = BlockNumber(
+            (partition_start.0 / config.blocks_per_partition + 1) * config.blocks_per_partition,
+        )
+        .min(end_block);
+        let n_rows = export_partition(reader, config, partition_start, partition_end)?;
+        for (dataset, n_rows) in n_rows {
+            *summary.n_rows.entry(dataset).or_default() += n_rows;
+        }
+        write_checkpoint(
+            &config.output_dir,
+            &ExportCheckpoint {
+                schema_version: EXPORT_SCHEMA_VERSION,
+                format: config.format,
+                next_block: partition_end,
+            },
+        )?;
+        summary.end_block = partition_end;
+        summary.n_partitions += 1;
+        info!("Exported blocks {partition_start} to {partition_end}.");
+    }
+    Ok(summary)
+}
+
+/// Reads the checkpoint in the given output directory, if there is one.
+pub fn read_checkpoint(output_dir: &Path) -> StorageResult<Option<ExportCheckpoint>> {
+    let path = output_dir.join(CHECKPOINT_FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::from_slice(&fs::read(path)?)?))
+}
+
+fn write_checkpoint(output_dir: &Path, checkpoint: &ExportCheckpoint) -> StorageResult<()> {
+    let path = output_dir.join(CHECKPOINT_FILE_NAME);
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_vec(checkpoint)?)?;
+    fs::rename(temp_path, path)?;
+    Ok(())
+}
+
+// Exports the blocks in [start, end) and returns the number of rows written to each dataset.
+fn export_partition(
+    reader: &StorageReader,
+    config: &ExportConfig,
+    start: BlockNumber,
+    end: BlockNumber,
+) -> StorageResult<BTreeMap<Dataset, u64>> {
+    let mut writers = BTreeMap::new();
+    for dataset in Dataset::ALL {
+        writers.insert(dataset, PartitionWriter::create(config, dataset, start, end)?);
+    }
+    let txn = reader.begin_ro_txn()?;
+    for block_number in start.iter_up_to(end) {
+        export_block(&txn, block_number, &mut writers)?;
+    }
+    drop(txn);
+
+    let mut n_rows = BTreeMap::new();
+    for (dataset, writer) in writers {
+        n_rows.insert(dataset, writer.finish()?);
+    }
+    Ok(n_rows)
+}
+
+fn export_block(
+    txn: &StorageTxn<'_, RO>,
+    block_number: BlockNumber,
+    writers: &mut BTreeMap<Dataset, PartitionWriter>,
+) -> StorageResult<()> {
+    let missing = |data| ExportError::MissingBlockData { block_number, data };
+    let header = txn.get_block_header(block_number)?.ok_or_else(|| missing("header"))?;
+    let transactions =
+        txn.get_block_transactions(block_number)?.ok_or_else(|| missing("transactions"))?;
+    let transaction_hashes = txn
+        .get_block_transaction_hashes(block_number)?
+        .ok_or_else(|| missing("transaction hashes"))?;
+    let transaction_outputs = txn
+        .get_block_transaction_outputs(block_number)?
+        .ok_or_else(|| missing("transaction outputs"))?;
+    let state_diff = txn.get_state_diff(block_number)?.ok_or_else(|| missing("state diff"))?;
+
+    dataset_writer(writers, Dataset::Blocks)
+        .write_row(&block_row(&header, &transaction_outputs))?;
+    for (transaction_index, ((transaction, transaction_hash), transaction_output)) in
+        transactions.iter().zip(&transaction_hashes).zip(&transaction_outputs).enumerate()
+    {
+        let transaction_index = u64::try_from(transaction_index)
+            .expect("The number of transactions should fit in u64.");
+        dataset_writer(writers, Dataset::Transactions).write_row(&transaction_row(
+            block_number,
+            transaction_index,
+            transaction_hash,
+            transaction,
+        )?)?;
+        dataset_writer(writers, Dataset::Receipts).write_row(&receipt_row(
+            block_number,
+            transaction_index,
+            transaction_hash,
+            transaction_output,
+        )?)?;
+        for (event_index, event) in transaction_output.events().iter().enumerate() {
+            dataset_writer(writers, Dataset::Events).write_row(&EventRow {
+                block_number: block_number.0,
+                transaction_index,
+                event_index: u64::try_from(event_index)
+                    .expect("The number of events should fit in u64."),
+                transaction_hash: hex(&transaction_hash.0),
+                from_address: hex(event.from_address.key()),
+                keys: serde_json::to_string(
+                    &event.content.keys.iter().map(|key| hex(&key.0)).collect::<Vec<_>>(),
+                )?,
+                data: serde_json::to_string(
+                    &event.content.data.0.iter().map(hex).collect::<Vec<_>>(),
+                )?,
+            })?;
+        }
+    }
+    for row in state_diff_rows(block_number, &state_diff) {
+        dataset_writer(writers, Dataset::StateDiffs).write_row(&row)?;
+    }
+    Ok(())
+}
+
+fn dataset_writer(
+    writers: &mut BTreeMap<Dataset, PartitionWriter>,
+    dataset: Dataset,
+) -> &mut PartitionWriter {
+    writers.get_mut(&dataset).expect("All the datasets should have writers.")
+}
+
+#[derive(Serialize)]
+struct BlockRow {
+    block_number: u64,
+    block_hash: String,
+    parent_hash: String,
+    state_root: String,
+    sequencer_address: String,
+    timestamp: u64,
+    l1_gas_price_wei: String,
+    l1_gas_price_fri: String,
+    l1_data_gas_price_wei: String,
+    l1_data_gas_price_fri: String,
+    l2_gas_price_wei: String,
+    l2_gas_price_fri: String,
+    l1_da_mode: String,
+    starknet_version: String,
+    transaction_count: u64,
+    event_count: u64,
+}
+
+#[derive(Serialize)]
+struct TransactionRow {
+    block_number: u64,
+    transaction_index: u64,
+    transaction_hash: String,
+    transaction_type: String,
+    transaction: String,
+}
+
+#[derive(Serialize)]
+struct ReceiptRow {
+    block_number: u64,
+    transaction_index: u64,
+    transaction_hash: String,
+    actual_fee: String,
+    execution_status: String,
+    revert_reason: Option<String>,
+    messages_sent: String,
+    execution_resources: String,
+    event_count: u64,
+}
+
+#[derive(Serialize)]
+struct EventRow {
+    block_number: u64,
+    transaction_index: u64,
+    event_index: u64,
+    transaction_hash: String,
+    from_address: String,
+    keys: String,
+    data: String,
+}
+
+#[derive(Serialize)]
+struct StateDiffRow {
+    block_number: u64,
+    entry_type: String,
+    contract_address: Option<String>,
+    key: Option<String>,
+    value: Option<String>,
+}
+
+fn block_row(header: &BlockHeader, transaction_outputs: &[TransactionOutput]) -> BlockRow {
+    let header_without_hash = &header.block_header_without_hash;
+    BlockRow {
+        block_number: header_without_hash.block_number.0,
+        block_hash: hex(&header.block_hash.0),
+        parent_hash: hex(&header_without_hash.parent_hash.0),
+        state_root: hex(&header_without_hash.state_root.0),
+        sequencer_address: hex(header_without_hash.sequencer.0.key()),
+        timestamp: header_without_hash.timestamp.0,
+        l1_gas_price_wei: header_without_hash.l1_gas_price.price_in_wei.0.to_string(),
+        l1_gas_price_fri: header_without_hash.l1_gas_price.price_in_fri.0.to_string(),
+        l1_data_gas_price_wei: header_without_hash.l1_data_gas_price.price_in_wei.0.to_string(),
+        l1_data_gas_price_fri: header_without_hash.l1_data_gas_price.price_in_fri.0.to_string(),
+        l2_gas_price_wei: header_without_hash.l2_gas_price.price_in_wei.0.to_string(),
+        l2_gas_price_fri: header_without_hash.l2_gas_price.price_in_fri.0.to_string(),
+        l1_da_mode: match header_without_hash.l1_da_mode {
+            L1DataAvailabilityMode::Calldata => "CALLDATA",
+            L1DataAvailabilityMode::Blob => "BLOB",
+        }
+        .to_owned(),
+        starknet_version: header_without_hash.starknet_version.to_string(),
+        transaction_count: u64::try_from(transaction_outputs.len())
+            .expect("The number of transactions should fit in u64."),
+        event_count: u64::try_from(
+            transaction_outputs.iter().map(|output| output.events().len()).sum::<usize>(),
+        )
+        .expect("The number of events should fit in u64."),
+    }
+}
+
+fn transaction_row(
+    block_number: BlockNumber,
+    transaction_index: u64,
+    transaction_hash: &TransactionHash,
+    transaction: &Transaction,
+) -> StorageResult<TransactionRow> {
+    let transaction_type = match transaction {
+        Transaction::Declare(_) => "DECLARE",
+        Transaction::Deploy(_) => "DEPLOY",
+        Transaction::DeployAccount(_) => "DEPLOY_ACCOUNT",
+        Transaction::Invoke(_) => "INVOKE",
+        Transaction::L1Handler(_) => "L1_HANDLER",
+    };
+    Ok(TransactionRow {
+        block_number: block_number.0,
+        transaction_index,
+        transaction_hash: hex(&transaction_hash.0),
+        transaction_type: transaction_type.to_owned(),
+        transaction: serde_json::to_string(transaction)?,
+    })
+}
+
+fn receipt_row(
+    block_number: BlockNumber,
+    transaction_index: u64,
+    transaction_hash: &TransactionHash,
+    transaction_output: &TransactionOutput,
+) -> StorageResult<ReceiptRow> {
+    let (execution_status, revert_reason) = match transaction_output.execution_status() {
+        TransactionExecutionStatus::Succeeded => ("SUCCEEDED", None),
+        TransactionExecutionStatus::Reverted(status) => {
+            ("REVERTED", Some(status.revert_reason.clone()))
+        }
+    };
+    Ok(ReceiptRow {
+        block_number: block_number.0,
+        transaction_index,
+        transaction_hash: hex(&transaction_hash.0),
+        actual_fee: transaction_output.actual_fee().0.to_string(),
+        execution_status: execution_status.to_owned(),
+        revert_reason,
+        messages_sent: serde_json::to_string(transaction_output.messages_sent())?,
+        execution_resources: serde_json::to_string(transaction_output.execution_resources())?,
+        event_count: u64::try_from(transaction_output.events().len())
+            .expect("The number of events should fit in u64."),
+    })
+}
+
+fn state_diff_rows(block_number: BlockNumber, state_diff: &ThinStateDiff) -> Vec<StateDiffRow> {
+    let row = |entry_type: &str, contract_address, key, value| StateDiffRow {
+        block_number: block_number.0,
+        entry_type: entry_type.to_owned(),
+        contract_address,
+        key,
+        value,
+    };
+    let mut rows = Vec::new();
+    for (address, class_hash) in &state_diff.deployed_contracts {
+        rows.push(row(
+            "deployed_contract",
+            Some(hex(address.key())),
+            None,
+            Some(hex(&class_hash.0)),
+        ));
+    }
+    for (address, storage_diffs) in &state_diff.storage_diffs {
+        for (key, value) in storage_diffs {
+            rows.push(row(
+                "storage",
+                Some(hex(address.key())),
+                Some(hex(key.key())),
+                Some(hex(value)),
+            ));
+        }
+    }
+    for (class_hash, compiled_class_hash) in &state_diff.declared_classes {
+        rows.push(row(
+            "declared_class",
+            None,
+            Some(hex(&class_hash.0)),
+            Some(hex(&compiled_class_hash.0)),
+        ));
+    }
+    for class_hash in &state_diff.deprecated_declared_classes {
+        rows.push(row("deprecated_declared_class", None, Some(hex(&class_hash.0)), None));
+    }
+    for (address, nonce) in &state_diff.nonces {
+        rows.push(row("nonce", Some(hex(address.key())), None, Some(hex(&nonce.0))));
+    }
+    rows
+}
+
+fn hex(felt: &Felt) -> String {
+    felt.to_hex_string()
+}
+
+// Writes the rows of a dataset in a partition to a temporary file, which is renamed once all the
+// rows are written.
+struct PartitionWriter {
+    path: PathBuf,
+    temp_path: PathBuf,
+    file_writer: FileWriter,
+    n_rows: u64,
+}
+
+enum FileWriter {
+    Jsonl(BufWriter<File>),
+    #[cfg(feature = "parquet")]
+    Parquet(Box<ParquetWriter>),
+}
+
+impl PartitionWriter {
+    fn create(
+        config: &ExportConfig,
+        dataset: Dataset,
+        start: BlockNumber,
+        end: BlockNumber,
+    ) -> StorageResult<Self> {
+        let dir = config.output_dir.join(dataset.name());
+        fs::create_dir_all(&dir)?;
+        let last_block = end.prev().expect("A partition should not be empty.");
+        let path = dir.join(format!(
+            "{}_{:010}_{:010}.{}",
+            dataset.name(),
+            start.0,
+            last_block.0,
+            config.format.file_extension()
+        ));
+        let temp_path = path.with_extension("tmp");
+        let file = BufWriter::new(File::create(&temp_path)?);
+        let file_writer = match config.format {
+            ExportFormat::Jsonl => FileWriter::Jsonl(file),
+            #[cfg(feature = "parquet")]
+            ExportFormat::Parquet => {
+                FileWriter::Parquet(Box::new(ParquetWriter::new(dataset, file)?))
+            }
+        };
+        Ok(Self { path, temp_path, file_writer, n_rows: 0 })
+    }
+
+    fn write_row(&mut self, row: &impl Serialize) -> StorageResult<()> {
+        match &mut self.file_writer {
+            FileWriter::Jsonl(file) => {
+                serde_json::to_writer(&mut *file, row)?;
+                file.write_all(b"\n")?;
+            }
+            #[cfg(feature = "parquet")]
+            FileWriter::Parquet(writer) => {
+                let Value::Object(row) = serde_json::to_value(row)? else {
+                    unreachable!("Rows are serialized to objects.");
+                };
+                writer.write_row(row)?;
+            }
+        }
+        self.n_rows += 1;
+        Ok(())
+    }
+
+    // Completes the file and returns the number of rows written to it.
+    fn finish(self) -> StorageResult<u64> {
+        // Without the parquet feature, the match has a single arm.
+        #[allow(clippy::infallible_destructuring_match)]
+        let mut file = match self.file_writer {
+            FileWriter::Jsonl(file) => file,
+            #[cfg(feature = "parquet")]
+            FileWriter::Parquet(writer) => writer.finish()?,
+        };
+        file.flush()?;
+        file.get_ref().sync_all()?;
+        fs::rename(&self.temp_path, &self.path)?;
+        Ok(self.n_rows)
+    }
+}
+
+// Buffers the values of each column and writes them as a row group when enough rows are buffered.
+#[cfg(feature = "parquet")]
+struct ParquetWriter {
+    schema: &'static [Column],
+    writer: SerializedFileWriter<BufWriter<File>>,
+    columns: Vec<ColumnValues>,
+    n_buffered_rows: usize,
+}
+
+#[cfg(feature = "parquet")]
+enum ColumnValues {
+    U64(Vec<i64>),
+    String(Vec<ByteArray>),
+    // The non-null values and, for each row, whether it isn't null (1) or is null (0).
+    OptionalString { values: Vec<ByteArray>, definition_levels: Vec<i16> },
+}
+
+#[cfg(feature = "parquet")]
+impl ParquetWriter {
+    fn new(dataset: Dataset, file: BufWriter<File>) -> StorageResult<Self> {
+        let schema = dataset.schema();
+        let mut fields = Vec::new();
+        for column in schema {
+            let (physical_type, logical_type, repetition) = match column.column_type {
+                ColumnType::U64 => (
+                    PhysicalType::INT64,
+                    LogicalType::Integer { bit_width: 64, is_signed: false },
+                    Repetition::REQUIRED,
+                ),
+                ColumnType::String => {
+                    (PhysicalType::BYTE_ARRAY, LogicalType::String, Repetition::REQUIRED)
+                }
+                ColumnType::OptionalString => {
+                    (PhysicalType::BYTE_ARRAY, LogicalType::String, Repetition::OPTIONAL)
+                }
+            };
+            fields.push(
+                ParquetType::primitive_type_builder(column.name, physical_type)
+                    .with_logical_type(Some(logical_type))
+                    .with_repetition(repetition)
+                    .build()
+                    .map_err(ExportError::from)?
+                    .into(),
+            );
+        }
+        let parquet_schema = ParquetType::group_type_builder(dataset.name())
+            .with_fields(fields)
+            .build()
+            .map_err(ExportError::from)?;
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::ZSTD(ZstdLevel::default()))
+            .build();
+        let writer = SerializedFileWriter::new(file, parquet_schema.into(), properties.into())
+            .map_err(ExportError::from)?;
+        let columns = schema
+            .iter()
+            .map(|column| match column.column_type {
+                ColumnType::U64 => ColumnValues::U64(Vec::new()),
+                ColumnType::String => ColumnValues::String(Vec::new()),
+                ColumnType::OptionalString => ColumnValues::OptionalString {
+                    values: Vec::new(),
+                    definition_levels: Vec::new(),
+                },
+            })
+            .collect();
+        Ok(Self { schema, writer, columns, n_buffered_rows: 0 })
+    }
+
+    fn write_row(&mut self, mut row: Map<String, Value>) -> StorageResult<()> {
+        for (column, values) in self.schema.iter().zip(&mut self.columns) {
+            let value = row.remove(column.name).unwrap_or(Value::Null);
+            match (values, value) {
+                (ColumnValues::U64(values), value) => {
+                    let value = value.as_u64().expect("U64 columns should have u64 values.");
+                    // Unsigned values are stored in the bits of the signed physical type.
+                    values.push(i64::from_ne_bytes(value.to_ne_bytes()));
+                }
+                (ColumnValues::String(values), Value::String(value)) => {
+                    values.push(value.into_bytes().into());
+                }
+                (ColumnValues::OptionalString { definition_levels, .. }, Value::Null) => {
+                    definition_levels.push(0);
+                }
+                (
+                    ColumnValues::OptionalString { values, definition_levels },
+                    Value::String(value),
+                ) => {
+                    definition_levels.push(1);
+                    values.push(value.into_bytes().into());
+                }
+                (_, value) => {
+                    unreachable!("Unexpected value {value} of the column {}.", column.name)
+                }
+            }
+        }
+        self.n_buffered_rows += 1;
+        if self.n_buffered_rows == PARQUET_ROW_GROUP_SIZE {
+            self.flush_row_group()?;
+        }
+        Ok(())
+    }
+
+    fn flush_row_group(&mut self) -> StorageResult<()> {
+        let mut row_group_writer = self.writer.next_row_group().map_err(ExportError::from)?;
+        for values in &mut self.columns {
+            let mut column_writer = row_group_writer
+                .next_column()
+                .map_err(ExportError::from)?
+                .expect("The schema and the buffered columns should have the same length.");
+            match values {
+                ColumnValues::U64(values) => {
+                    column_writer.typed::<Int64Type>().write_batch(values, None, None)
+                }
+                ColumnValues::String(values) => {
+                    column_writer.typed::<ByteArrayType>().write_batch(values, None, None)
+                }
+                ColumnValues::OptionalString { values, definition_levels } => column_writer
+                    .typed::<ByteArrayType>()
+                    .write_batch(values, Some(definition_levels), None),
+            }
+            .map_err(ExportError::from)?;
+            column_writer.close().map_err(ExportError::from)?;
+            match values {
+                ColumnValues::U64(values) => values.clear(),
+                ColumnValues::String(values) => values.clear(),
+                ColumnValues::OptionalString { values, definition_levels } => {
+                    values.clear();
+                    definition_levels.clear();
+                }
+            }
+        }
+        row_group_writer.close().map_err(ExportError::from)?;
+        self.n_buffered_rows = 0;
+        Ok(())
+    }
+
+    // Writes the buffered rows and the footer, and returns the underlying file.
+    fn finish(mut self) -> StorageResult<BufWriter<File>> {
+        if self.n_buffered_rows > 0 {
+            self.flush_row_group()?;
+        }
+        Ok(self.writer.into_inner().map_err(ExportError::from)?)
+    }
+}
@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::Path;
+
+use apollo_test_utils::{get_test_block, get_test_state_diff};
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use serde_json::{Map, Value};
+use starknet_api::block::{BlockHash, BlockHeader, BlockHeaderWithoutHash, BlockNumber};
+use starknet_api::felt;
+use starknet_api::state::ThinStateDiff;
+use tempfile::tempdir;
+
+use crate::body::BodyStorageWriter;
+use crate::export::{
+    export_blocks,
+    read_checkpoint,
+    Dataset,
+    ExportCheckpoint,
+    ExportConfig,
+    ExportError,
+    ExportFormat,
+    CHECKPOINT_FILE_NAME,
+    EXPORT_SCHEMA_VERSION,
+};
+use crate::header::HeaderStorageWriter;
+use crate::state::StateStorageWriter;
+use crate::test_utils::get_test_storage;
+use crate::{StorageError, StorageWriter};
+
+const N_BLOCKS: u64 = 5;
+const N_TRANSACTIONS_PER_BLOCK: usize = 2;
+const N_EVENTS_PER_TRANSACTION: usize = 3;
+
+fn write_blocks(writer: &mut StorageWriter) -> usize {
+    let mut n_state_diff_entries = 0;
+    for block_number in BlockNumber(0).iter_up_to(BlockNumber(N_BLOCKS)) {
+        let header = BlockHeader {
+            block_hash: BlockHash(felt!(block_number.0 + 1)),
+            block_header_without_hash: BlockHeaderWithoutHash {
+                block_number,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut body =
+            get_test_block(N_TRANSACTIONS_PER_BLOCK, Some(N_EVENTS_PER_TRANSACTION), None, None)
+                .body;
+        // Make the transaction hashes unique across blocks.
+        for tx_hash in &mut body.transaction_hashes {
+            tx_hash.0 += felt!(block_number.0 * 100);
+        }
+        let state_diff: ThinStateDiff = if block_number == BlockNumber(0) {
+            get_test_state_diff().into()
+        } else {
+            ThinStateDiff::default()
+        };
+        n_state_diff_entries += state_diff.deployed_contracts.len()
+            + state_diff.storage_diffs.values().map(|diffs| diffs.len()).sum::<usize>()
+            + state_diff.declared_classes.len()
+            + state_diff.deprecated_declared_classes.len()
+            + state_diff.nonces.len();
+        writer
+            .begin_rw_txn()
+            .unwrap()
+            .append_header(block_number, &header)
+            .unwrap()
+            .append_body(block_number, body)
+            .unwrap()
+            .append_state_diff(block_number, state_diff)
+            .unwrap()
+            .commit()
+            .unwrap();
+    }
+    n_state_diff_entries
+}
+
+// Returns the names of the files of the dataset and their rows.
+fn read_dataset(output_dir: &Path, dataset: Dataset) -> (Vec<String>, Vec<Map<String, Value>>) {
+    let mut file_names = fs::read_dir(output_dir.join(dataset.name()))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    file_names.sort();
+    let mut rows = Vec::new();
+    for file_name in &file_names {
+        let content = fs::read_to_string(output_dir.join(dataset.name()).join(file_name)).unwrap();
+        rows.extend(content.lines().map(|line| serde_json::from_str(line).unwrap()));
+    }
+    (file_names, rows)
+}
+
+#[test]
+fn export_and_resume() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let n_state_diff_entries = write_blocks(&mut writer);
+    let output_dir = tempdir().unwrap();
+    let mut config = ExportConfig {
+        output_dir: output_dir.path().to_path_buf(),
+        format: ExportFormat::Jsonl,
+        start_block: BlockNumber(0),
+        end_block: Some(BlockNumber(3)),
+        blocks_per_partition: 2,
+    };
+
+    let summary = export_blocks(&reader, &config).unwrap();
+    assert_eq!(summary.start_block, BlockNumber(0));
+    assert_eq!(summary.end_block, BlockNumber(3));
+    assert_eq!(summary.n_partitions, 2);
+    assert_eq!(summary.n_rows[&Dataset::Blocks], 3);
+    assert_eq!(
+        read_checkpoint(output_dir.path()).unwrap(),
+        Some(ExportCheckpoint {
+            schema_version: EXPORT_SCHEMA_VERSION,
+            format: ExportFormat::Jsonl,
+            next_block: BlockNumber(3)
+        })
+    );
+
+    // The export resumes from the checkpoint, up to the end of the stored blocks.
+    config.end_block = None;
+    let summary = export_blocks(&reader, &config).unwrap();
+    assert_eq!(summary.start_block, BlockNumber(3));
+    assert_eq!(summary.end_block, BlockNumber(N_BLOCKS));
+    // The partitions are aligned to multiples of their size.
+    assert_eq!(summary.n_partitions, 2);
+
+    let (file_names, blocks) = read_dataset(output_dir.path(), Dataset::Blocks);
+    assert_eq!(
+        file_names,
+        vec![
+            "blocks_0000000000_0000000001.jsonl",
+            "blocks_0000000002_0000000002.jsonl",
+            "blocks_0000000003_0000000003.jsonl",
+            "blocks_0000000004_0000000004.jsonl",
+        ]
+    );
+    let block_numbers = blocks.iter().map(|row| row["block_number"].as_u64().unwrap());
+    assert!(block_numbers.eq(0..N_BLOCKS));
+
+    let n_transactions = usize::try_from(N_BLOCKS).unwrap() * N_TRANSACTIONS_PER_BLOCK;
+    let expected_n_rows = [
+        (Dataset::Blocks, usize::try_from(N_BLOCKS).unwrap()),
+        (Dataset::Transactions, n_transactions),
+        (Dataset::Receipts, n_transactions),
+        (Dataset::Events, n_transactions * N_EVENTS_PER_TRANSACTION),
+        (Dataset::StateDiffs, n_state_diff_entries),
+    ];
+    for (dataset, expected_n_rows) in expected_n_rows {
+        let (_, rows) = read_dataset(output_dir.path(), dataset);
+        assert_eq!(rows.len(), expected_n_rows, "{}", dataset.name());
+        // The rows match the schema of the dataset.
+        let mut expected_columns =
+            dataset.schema().iter().map(|column| column.name).collect::<Vec<_>>();
+        expected_columns.sort();
+        for row in rows {
+            let mut columns = row.keys().map(String::as_str).collect::<Vec<_>>();
+            columns.sort();
+            assert_eq!(columns, expected_columns, "{}", dataset.name());
+        }
+    }
+}
+
+#[test]
+fn mismatching_checkpoint() {
+    let ((reader, _writer), _temp_dir) = get_test_storage();
+    let output_dir = tempdir().unwrap();
+    let checkpoint = ExportCheckpoint {
+        schema_version: 0,
+        format: ExportFormat::Jsonl,
+        next_block: BlockNumber(1),
+    };
+    fs::write(
+        output_dir.path().join(CHECKPOINT_FILE_NAME),
+        serde_json::to_vec(&checkpoint).unwrap(),
+    )
+    .unwrap();
+    let config = ExportConfig {
+        output_dir: output_dir.path().to_path_buf(),
+        format: ExportFormat::Jsonl,
+        start_block: BlockNumber(0),
+        end_block: None,
+        blocks_per_partition: 1,
+    };
+    assert_matches!(
+        export_blocks(&reader, &config),
+        Err(StorageError::ExportError(ExportError::CheckpointMismatch { schema_version: 0, .. }))
+    );
+}
@@ -0,0 +1,255 @@
+//! Inspection of the content of the storage, for debugging.
+//!
+//! An [`InspectionCommand`] is parsed from a line of text, e.g., `header 5` or
+//! `events --from 5 --to 10 --address 0x1`, and [`execute_command`] returns its result as JSON.
+//! The `storage_inspect` binary runs the commands on the storage in a given directory.
+//!
+//! The supported commands are:
+//! - `header <block_number>`
+//! - `tx <transaction_hash>`
+//! - `state-diff <block_number>`
+//! - `storage-at <state_number> <address> <key>`
+//! - `nonce-at <state_number> <address>`
+//! - `class-hash-at <state_number> <address>`
+//! - `class <class_hash>`
+//! - `events --from <block_number> --to <block_number> [--address <address>] [--limit <n>]`, where
+//!   the range of blocks is inclusive and at most `n` events are returned (by default,
+//!   [`DEFAULT_EVENTS_LIMIT`]). Blocks without a body (e.g., pruned blocks) are skipped.
+//! - `markers`
+//! - `stats`
+//!
+//! Numbers may be given in decimal or in hex with a `0x` prefix.
+
+#[cfg(test)]
+#[path = "inspection_test.rs"]
+mod inspection_test;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde_json::{json, Value};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::state::{StateNumber, StorageKey};
+use starknet_api::transaction::TransactionHash;
+use starknet_types_core::felt::Felt;
+
+use crate::body::BodyStorageReader;
+use crate::class::ClassStorageReader;
+use crate::compiled_class::CasmStorageReader;
+use crate::db::table_types::{DbCursorTrait, Table};
+use crate::header::HeaderStorageReader;
+use crate::state::StateStorageReader;
+use crate::{StorageQuery, StorageReader, StorageResult};
+
+/// The maximal number of events returned by an events command without a limit.
+pub const DEFAULT_EVENTS_LIMIT: usize = 1000;
+
+/// A command for inspecting the storage.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum InspectionCommand {
+    /// Get the header of the block with the given number.
+    Header(BlockNumber),
+    /// Get the transaction with the given hash, its index and its output.
+    Transaction(TransactionHash),
+    /// Get the state diff of the block with the given number.
+    StateDiff(BlockNumber),
+    /// Get a value of the state at a given state number.
+    State(StorageQuery),
+    /// Get the class with the given hash, and its CASM.
+    Class(ClassHash),
+    /// Get up to `limit` of the events emitted in the given range of blocks (inclusive),
+    /// optionally only those emitted by the given contract.
+    Events {
+        /// The first block of the range.
+        from_block: BlockNumber,
+        /// The last block of the range.
+        to_block: BlockNumber,
+        /// The contract that emitted the events, if only its events are requested.
+        address: Option<ContractAddress>,
+        /// The maximal number of events to return.
+        limit: usize,
+    },
+    /// Get the markers of the storage.
+    Markers,
+    /// Get statistics of the tables and the mmap files.
+    Stats,
+}
+
+/// An error of parsing an [`InspectionCommand`].
+#[derive(thiserror::Error, Debug, Clone, Eq, PartialEq)]
+#[error("Invalid command: {0}.")]
+pub struct ParseCommandError(pub String);
+
+impl FromStr for InspectionCommand {
+    type Err = ParseCommandError;
+
+    fn from_str(command: &str) -> Result<Self, Self::Err> {
+        let words = command.split_whitespace().collect::<Vec<_>>();
+        let command = match words.as_slice() {
+            ["header", block_number] => Self::Header(parse_block_number(block_number)?),
+            ["tx", tx_hash] => Self::Transaction(TransactionHash(parse_felt(tx_hash)?)),
+            ["state-diff", block_number] => Self::StateDiff(parse_block_number(block_number)?),
+            ["storage-at", state_number, address, key] => Self::State(StorageQuery::GetStorageAt(
+                StateNumber(parse_block_number(state_number)?),
+                parse_address(address)?,
+                StorageKey::try_from(parse_felt(key)?)
+                    .map_err(|_| ParseCommandError(format!("invalid storage key {key}")))?,
+            )),
+            ["nonce-at", state_number, address] => Self::State(StorageQuery::GetNonceAt(
+                StateNumber(parse_block_number(state_number)?),
+                parse_address(address)?,
+            )),
+            ["class-hash-at", state_number, address] => Self::State(StorageQuery::GetClassHashAt(
+                StateNumber(parse_block_number(state_number)?),
+                parse_address(address)?,
+            )),
+            ["class", class_hash] => Self::Class(ClassHash(parse_felt(class_hash)?)),
+            ["events", flags @ ..] => parse_events_command(flags)?,
+            ["markers"] => Self::Markers,
+            ["stats"] => Self::Stats,
+            _ => return Err(ParseCommandError(format!("unknown command '{command}'"))),
+        };
+        Ok(command)
+    }
+}
+
+fn parse_events_command(flags: &[&str]) -> Result<InspectionCommand, ParseCommandError> {
+    let (mut from_block, mut to_block, mut address) = (None, None, None);
+    let mut limit = DEFAULT_EVENTS_LIMIT;
+    for flag_and_value in flags.chunks(2) {
+        match flag_and_value {
+            ["--from", value] => from_block = Some(parse_block_number(value)?),
+            ["--to", value] => to_block = Some(parse_block_number(value)?),
+            ["--address", value] => address = Some(parse_address(value)?),
+            ["--limit", value] => {
+                limit = value
+                    .parse()
+                    .map_err(|_| ParseCommandError(format!("invalid events limit {value}")))?;
+            }
+            _ => {
+                return Err(ParseCommandError(format!(
+                    "unexpected events arguments '{}'",
+                    flag_and_value.join(" ")
+                )));
+            }
+        }
+    }
+    let (Some(from_block), Some(to_block)) = (from_block, to_block) else {
+        return Err(ParseCommandError("events requires --from and --to".to_owned()));
+    };
+    if from_block > to_block {
+        return Err(ParseCommandError(format!(
+            "the events range {from_block} to {to_block} is empty"
+        )));
+    }
+    Ok(InspectionCommand::Events { from_block, to_block, address, limit })
+}
+
+fn parse_felt(value: &str) -> Result<Felt, ParseCommandError> {
+    Felt::from_str(value).map_err(|_| ParseCommandError(format!("invalid felt {value}")))
+}
+
+fn parse_block_number(value: &str) -> Result<BlockNumber, ParseCommandError> {
+    let block_number = u64::try_from(parse_felt(value)?)
+        .map_err(|_| ParseCommandError(format!("invalid block number {value}")))?;
+    Ok(BlockNumber(block_number))
+}
+
+fn parse_address(value: &str) -> Result<ContractAddress, ParseCommandError> {
+    ContractAddress::try_from(parse_felt(value)?)
+        .map_err(|_| ParseCommandError(format!("invalid contract address {value}")))
+}
+
+/// Executes the command on a snapshot of the storage, and returns its result as JSON. Data that
+/// doesn't exist in the storage is returned as null.
+pub fn execute_command(
+    reader: &StorageReader,
+    command: &InspectionCommand,
+) -> StorageResult<Value> {
+    let txn = reader.begin_ro_txn()?;
+    let result = match command {
+        InspectionCommand::Header(block_number) => json!({
+            "header": txn.get_block_header(*block_number)?,
+            "signature": txn.get_block_signature(*block_number)?,
+        }),
+        InspectionCommand::Transaction(tx_hash) => {
+            match txn.get_transaction_idx_by_hash(tx_hash)? {
+                None => Value::Null,
+                Some(tx_index) => json!({
+                    "block_number": tx_index.0,
+                    "offset_in_block": tx_index.1.0,
+                    "transaction": txn.get_transaction(tx_index)?,
+                    "output": txn.get_transaction_output(tx_index)?,
+                }),
+            }
+        }
+        InspectionCommand::StateDiff(block_number) => {
+            serde_json::to_value(txn.get_state_diff(*block_number)?)?
+        }
+        InspectionCommand::State(query) => {
+            let state_reader = txn.get_state_reader()?;
+            match query {
+                StorageQuery::GetClassHashAt(state_number, address) => {
+                    serde_json::to_value(state_reader.get_class_hash_at(*state_number, address)?)?
+                }
+                StorageQuery::GetNonceAt(state_number, address) => {
+                    serde_json::to_value(state_reader.get_nonce_at(*state_number, address)?)?
+                }
+                StorageQuery::GetStorageAt(state_number, address, key) => serde_json::to_value(
+                    state_reader.get_storage_at(*state_number, address, key)?,
+                )?,
+            }
+        }
+        InspectionCommand::Class(class_hash) => json!({
+            "class": txn.get_class(class_hash)?,
+            "deprecated_class": txn.get_deprecated_class(class_hash)?,
+            "casm": txn.get_casm(class_hash)?,
+        }),
+        InspectionCommand::Events { from_block, to_block, address, limit } => {
+            let mut events = Vec::new();
+            // There are no bodies from the body marker.
+            let end_block = txn.get_body_marker()?.min(to_block.unchecked_next());
+            'blocks: for block_number in (from_block.0..end_block.0).map(BlockNumber) {
+                let (Some(tx_hashes), Some(tx_outputs)) = (
+                    txn.get_block_transaction_hashes(block_number)?,
+                    txn.get_block_transaction_outputs(block_number)?,
+                ) else {
+                    continue;
+                };
+                for (offset, (tx_hash, tx_output)) in tx_hashes.iter().zip(&tx_outputs).enumerate()
+                {
+                    for event in tx_output.events() {
+                        if address.is_some_and(|address| address != event.from_address) {
+                            continue;
+                        }
+                        if events.len() == *limit {
+                            break 'blocks;
+                        }
+                        events.push(json!({
+                            "block_number": block_number,
+                            "transaction_hash": tx_hash,
+                            "offset_in_block": offset,
+                            "event": event,
+                        }));
+                    }
+                }
+            }
+            Value::Array(events)
+        }
+        InspectionCommand::Markers => {
+            let markers_table = txn.open_table(&txn.tables.markers)?;
+            let mut cursor = markers_table.cursor(&txn.txn)?;
+            let mut markers = BTreeMap::new();
+            while let Some((marker_kind, marker)) = cursor.next()? {
+                markers.insert(format!("{marker_kind:?}"), marker);
+            }
+            serde_json::to_value(markers)?
+        }
+        InspectionCommand::Stats => json!({
+            "db": reader.db_tables_stats()?,
+            "mmap_files": reader.mmap_files_stats(),
+        }),
+    };
+    Ok(result)
+}
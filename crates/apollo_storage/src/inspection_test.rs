@@ -0,0 +1,164 @@
+use apollo_test_utils::{get_test_block, get_test_state_diff};
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use serde_json::{json, Value};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::{ClassHash, ContractAddress};
+use starknet_api::state::{StateNumber, ThinStateDiff};
+use starknet_api::transaction::TransactionHash;
+use starknet_api::{contract_address, felt, storage_key};
+
+use crate::body::BodyStorageWriter;
+use crate::header::HeaderStorageWriter;
+use crate::inspection::{
+    execute_command,
+    InspectionCommand,
+    ParseCommandError,
+    DEFAULT_EVENTS_LIMIT,
+};
+use crate::pruning::PruningStorageWriter;
+use crate::state::StateStorageWriter;
+use crate::test_utils::get_test_storage;
+use crate::StorageQuery;
+
+#[test]
+fn parse_commands() {
+    assert_eq!("header 5".parse(), Ok(InspectionCommand::Header(BlockNumber(5))));
+    assert_eq!(
+        "tx 0x1a".parse(),
+        Ok(InspectionCommand::Transaction(TransactionHash(felt!("0x1a"))))
+    );
+    assert_eq!(" state-diff  0x10 ".parse(), Ok(InspectionCommand::StateDiff(BlockNumber(16))));
+    assert_eq!(
+        "storage-at 3 0x1 0x2".parse(),
+        Ok(InspectionCommand::State(StorageQuery::GetStorageAt(
+            StateNumber(BlockNumber(3)),
+            contract_address!("0x1"),
+            storage_key!("0x2"),
+        )))
+    );
+    assert_eq!(
+        "nonce-at 3 0x1".parse(),
+        Ok(InspectionCommand::State(StorageQuery::GetNonceAt(
+            StateNumber(BlockNumber(3)),
+            contract_address!("0x1"),
+        )))
+    );
+    assert_eq!("class 0x3".parse(), Ok(InspectionCommand::Class(ClassHash(felt!("0x3")))));
+    assert_eq!(
+        "events --to 4 --from 2 --address 0x1".parse(),
+        Ok(InspectionCommand::Events {
+            from_block: BlockNumber(2),
+            to_block: BlockNumber(4),
+            address: Some(contract_address!("0x1")),
+            limit: DEFAULT_EVENTS_LIMIT,
+        })
+    );
+    assert_eq!(
+        "events --from 2 --to 4 --limit 10".parse(),
+        Ok(InspectionCommand::Events {
+            from_block: BlockNumber(2),
+            to_block: BlockNumber(4),
+            address: None,
+            limit: 10,
+        })
+    );
+    assert_eq!("markers".parse(), Ok(InspectionCommand::Markers));
+    assert_eq!("stats".parse(), Ok(InspectionCommand::Stats));
+
+    for invalid_command in [
+        "",
+        "header",
+        "header x",
+        "markers 1",
+        "events --from 1",
+        "events --from 2 --to 1",
+        "events --from 1 --to 2 --limit x",
+    ] {
+        assert_matches!(invalid_command.parse::<InspectionCommand>(), Err(ParseCommandError(_)));
+    }
+}
+
+#[test]
+fn execute_commands() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let block = get_test_block(2, Some(2), None, None);
+    let state_diff = ThinStateDiff::from(get_test_state_diff());
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &block.header)
+        .unwrap()
+        .append_body(BlockNumber(0), block.body.clone())
+        .unwrap()
+        .append_state_diff(BlockNumber(0), state_diff.clone())
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let execute = |command: &str| execute_command(&reader, &command.parse().unwrap()).unwrap();
+
+    assert_eq!(execute("state-diff 0"), serde_json::to_value(&state_diff).unwrap());
+    assert_eq!(execute("state-diff 1"), Value::Null);
+
+    let tx_hash = block.body.transaction_hashes[1];
+    let tx_result = execute(&format!("tx {}", tx_hash.0));
+    assert_eq!(tx_result["block_number"], json!(0));
+    assert_eq!(tx_result["offset_in_block"], json!(1));
+    assert_eq!(
+        tx_result["transaction"],
+        serde_json::to_value(&block.body.transactions[1]).unwrap()
+    );
+
+    let address: ContractAddress = *state_diff.nonces.keys().next().unwrap();
+    assert_eq!(
+        execute(&format!("nonce-at 1 {}", address.0.key())),
+        serde_json::to_value(state_diff.nonces[&address]).unwrap()
+    );
+
+    let events = execute("events --from 0 --to 5");
+    let n_events: usize =
+        block.body.transaction_outputs.iter().map(|output| output.events().len()).sum();
+    assert_eq!(events.as_array().unwrap().len(), n_events);
+    assert_eq!(execute("events --from 0 --to 5 --limit 1").as_array().unwrap().len(), 1);
+    let from_address = block.body.transaction_outputs[0].events()[0].from_address;
+    let events = execute(&format!("events --from 0 --to 0 --address {}", from_address.0.key()));
+    assert!(events.as_array().unwrap().iter().all(|event| {
+        event["event"]["from_address"] == serde_json::to_value(from_address).unwrap()
+    }));
+
+    let markers = execute("markers");
+    assert_eq!(markers["Header"], json!(1));
+    assert_eq!(markers["Body"], json!(1));
+    assert_eq!(markers["State"], json!(1));
+
+    let stats = execute("stats");
+    assert!(stats["db"]["tables_stats"]["headers"].is_object());
+    assert!(stats["mmap_files"]["transaction"].is_object());
+}
+
+#[test]
+fn events_of_blocks_without_body_are_skipped() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let bodies = (0..2_u128)
+        .map(|i| {
+            let mut body = get_test_block(1, Some(2), None, None).body;
+            // Make the transaction hashes unique across blocks.
+            body.transaction_hashes[0].0 += felt!(i);
+            body
+        })
+        .collect::<Vec<_>>();
+    let mut txn = writer.begin_rw_txn().unwrap();
+    for (i, body) in (0..).zip(&bodies) {
+        txn = txn
+            .append_body(BlockNumber(i), body.clone())
+            .unwrap()
+            .append_state_diff(BlockNumber(i), ThinStateDiff::default())
+            .unwrap();
+    }
+    txn.prune_blocks(BlockNumber(1)).unwrap().commit().unwrap();
+
+    let events = execute_command(&reader, &"events --from 0 --to 1".parse().unwrap()).unwrap();
+    assert_eq!(events.as_array().unwrap().len(), bodies[1].transaction_outputs[0].events().len());
+    assert!(events.as_array().unwrap().iter().all(|event| event["block_number"] == json!(1)));
+}
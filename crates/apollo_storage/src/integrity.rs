@@ -0,0 +1,643 @@
+//! Verification of the internal consistency of the storage.
+//!
+//! [`check_storage_integrity`] walks all the tables of the storage in a single read transaction and
+//! reports the inconsistencies it finds, each identified by the exact key of the corrupted entry:
+//! - Entries that can't be deserialized.
+//! - Markers that violate the invariants between them, missing entries of blocks below a marker,
+//!   and entries of blocks at or beyond it.
+//! - Locations in the mmap files that point beyond the written part of the file, or to an object
+//!   that can't be deserialized.
+//! - Transaction hashes and block hashes that aren't indexed back to their transaction or block.
+//! - Optionally, block hashes that don't match the hash recomputed from their header, and state
+//!   diffs that don't match the commitment in their block's header.
+//!
+//! The `storage_fsck` binary runs the check on the storage in a given directory.
+
+#[cfg(test)]
+#[path = "integrity_test.rs"]
+mod integrity_test;
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use starknet_api::block::{BlockHash, BlockNumber};
+use starknet_api::block_hash::block_hash_calculator::{
+    calculate_block_hash,
+    concat_counts,
+    BlockHeaderCommitments,
+};
+use starknet_api::block_hash::state_diff_hash::calculate_state_diff_hash;
+use starknet_api::core::StateDiffCommitment;
+use tracing::info;
+
+use crate::db::serialization::{Key, ValueSerde};
+use crate::db::table_types::{DecodeRawEntry, Table};
+use crate::db::{DbError, TableIdentifier, RO};
+use crate::header::HeaderStorageReader;
+use crate::mmap_file::LocationInFile;
+use crate::state::StateStorageReader;
+use crate::{MarkerKind, OffsetKind, StorageReader, StorageResult, StorageTxn};
+
+/// An inconsistency found in the storage.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum IntegrityIssue {
+    #[error("The entry {key} of the table {table} can't be deserialized.")]
+    UndecodableEntry { table: &'static str, key: String },
+    #[error(
+        "The {lower} marker {lower_marker} is greater than the {upper} marker {upper_marker}."
+    )]
+    MarkersOrder {
+        lower: &'static str,
+        lower_marker: BlockNumber,
+        upper: &'static str,
+        upper_marker: BlockNumber,
+    },
+    #[error("The entry {key} is missing from the table {table}.")]
+    MissingEntry { table: &'static str, key: String },
+    #[error(
+        "The entry {key} of the table {table} belongs to a block at or beyond the {marker_name} \
+         marker {marker}."
+    )]
+    EntryBeyondMarker {
+        table: &'static str,
+        key: String,
+        marker_name: &'static str,
+        marker: BlockNumber,
+    },
+    #[error(
+        "The entry {key} of the table {table} points to {location:?}, beyond the {file_offset} \
+         written bytes of the {offset_kind:?} file."
+    )]
+    LocationOutOfFile {
+        table: &'static str,
+        key: String,
+        offset_kind: OffsetKind,
+        location: LocationInFile,
+        file_offset: usize,
+    },
+    #[error(
+        "The object at {location:?} in the {offset_kind:?} file, pointed to by the entry {key} of \
+         the table {table}, can't be deserialized."
+    )]
+    UndecodableFileObject {
+        table: &'static str,
+        key: String,
+        offset_kind: OffsetKind,
+        location: LocationInFile,
+    },
+    #[error("The entry {key} of the table {table} doesn't match its index: {msg}.")]
+    IndexMismatch { table: &'static str, key: String, msg: String },
+    #[error("The parent hash {parent_hash} of block {block_number} isn't the hash of its parent.")]
+    ParentHashMismatch { block_number: BlockNumber, parent_hash: BlockHash },
+    #[error(
+        "The hash {stored} of block {block_number} doesn't match its recomputed hash {computed}."
+    )]
+    BlockHashMismatch { block_number: BlockNumber, stored: BlockHash, computed: BlockHash },
+    #[error(
+        "The state diff commitment {stored:?} of block {block_number} doesn't match its \
+         recomputed commitment {computed:?}."
+    )]
+    StateDiffCommitmentMismatch {
+        block_number: BlockNumber,
+        stored: StateDiffCommitment,
+        computed: StateDiffCommitment,
+    },
+}
+
+/// The result of an integrity check of the storage.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IntegrityReport {
+    /// The number of entries in each table.
+    pub n_entries: BTreeMap<&'static str, u64>,
+    /// The number of blocks whose hash was recomputed. Blocks whose headers lack the commitments
+    /// or whose Starknet version predates the current block hash calculation are skipped.
+    pub n_verified_block_hashes: u64,
+    /// The inconsistencies found, by the order they were found.
+    pub issues: Vec<IntegrityIssue>,
+}
+
+/// Checks the internal consistency of the storage, as described in the [module
+/// documentation](self). Fails only if the storage can't be read; the inconsistencies are
+/// returned in the report.
+pub fn check_storage_integrity(
+    reader: &StorageReader,
+    verify_block_hashes: bool,
+) -> StorageResult<IntegrityReport> {
+    let txn = reader.begin_ro_txn()?;
+    let mut checker = IntegrityChecker::new(&txn)?;
+    checker.check_markers();
+    checker.check_tables()?;
+    checker.check_missing_entries()?;
+    if verify_block_hashes {
+        checker.check_block_hashes()?;
+    }
+    Ok(checker.report)
+}
+
+// The markers of the storage, read from the markers table.
+struct Markers {
+    header: BlockNumber,
+    body: BlockNumber,
+    state: BlockNumber,
+    class: BlockNumber,
+    compiled_class: BlockNumber,
+    base_layer_block: BlockNumber,
+    class_manager_block: BlockNumber,
+    pruned: BlockNumber,
+    event_keys_filter: BlockNumber,
+}
+
+struct IntegrityChecker<'a> {
+    txn: &'a StorageTxn<'a, RO>,
+    markers: Markers,
+    file_offsets: BTreeMap<OffsetKind, usize>,
+    report: IntegrityReport,
+}
+
+impl<'a> IntegrityChecker<'a> {
+    fn new(txn: &'a StorageTxn<'a, RO>) -> StorageResult<Self> {
+        let markers_table = txn.txn.open_table(&txn.tables.markers)?;
+        // A corrupted marker is reported when the markers table is walked.
+        let marker = |marker_kind: MarkerKind| match markers_table.get(&txn.txn, &marker_kind) {
+            Ok(marker) => Ok(marker.unwrap_or_default()),
+            Err(DbError::InnerDeserialization) => Ok(BlockNumber::default()),
+            Err(err) => Err(err),
+        };
+        let markers = Markers {
+            header: marker(MarkerKind::Header)?,
+            body: marker(MarkerKind::Body)?,
+            state: marker(MarkerKind::State)?,
+            class: marker(MarkerKind::Class)?,
+            compiled_class: marker(MarkerKind::CompiledClass)?,
+            base_layer_block: marker(MarkerKind::BaseLayerBlock)?,
+            class_manager_block: marker(MarkerKind::ClassManagerBlock)?,
+            pruned: marker(MarkerKind::Pruned)?,
+            event_keys_filter: marker(MarkerKind::EventKeysFilter)?,
+        };
+
+        let file_offsets_table = txn.txn.open_table(&txn.tables.file_offsets)?;
+        let mut file_offsets = BTreeMap::new();
+        for offset_kind in OffsetKind::ALL {
+            let offset = file_offsets_table.get(&txn.txn, &offset_kind)?.unwrap_or_default();
+            file_offsets.insert(offset_kind, offset);
+        }
+
+        Ok(Self { txn, markers, file_offsets, report: IntegrityReport::default() })
+    }
+
+    fn report_issue(&mut self, issue: IntegrityIssue) {
+        self.report.issues.push(issue);
+    }
+
+    fn check_markers(&mut self) {
+        let markers = &self.markers;
+        let invariants = [
+            ("compiled class", markers.compiled_class, "class", markers.class),
+            ("class", markers.class, "state", markers.state),
+            ("state", markers.state, "header", markers.header),
+            ("body", markers.body, "header", markers.header),
+            ("base layer block", markers.base_layer_block, "header", markers.header),
+            ("class manager block", markers.class_manager_block, "state", markers.state),
+            ("pruning", markers.pruned, "state", markers.state),
+            ("event keys filter", markers.event_keys_filter, "body", markers.body),
+        ];
+        for (lower, lower_marker, upper, upper_marker) in invariants {
+            if lower_marker > upper_marker {
+                self.report_issue(IntegrityIssue::MarkersOrder {
+                    lower,
+                    lower_marker,
+                    upper,
+                    upper_marker,
+                });
+            }
+        }
+    }
+
+    // Walks all the tables, and checks each entry against the markers and the entries it refers
+    // to.
+    fn check_tables(&mut self) -> StorageResult<()> {
+        let txn = self.txn;
+        let tables = &txn.tables;
+        let block_hash_to_number_table = txn.txn.open_table(&tables.block_hash_to_number)?;
+        let headers_table = txn.txn.open_table(&tables.headers)?;
+        let transaction_hash_to_idx_table = txn.txn.open_table(&tables.transaction_hash_to_idx)?;
+        let transaction_metadata_table = txn.txn.open_table(&tables.transaction_metadata)?;
+        let (header_marker, body_marker, state_marker) =
+            (self.markers.header, self.markers.body, self.markers.state);
+
+        self.walk_table(&tables.block_hash_to_number, |checker, block_hash, block_number| {
+            let indexed_hash = headers_table
+                .get(&txn.txn, &block_number)
+                .ok()
+                .flatten()
+                .map(|header| header.block_hash);
+            if indexed_hash != Some(block_hash) {
+                checker.report_issue(IntegrityIssue::IndexMismatch {
+                    table: tables.block_hash_to_number.name,
+                    key: format!("{block_hash:?}"),
+                    msg: format!(
+                        "the header of block {block_number} has the hash {indexed_hash:?}"
+                    ),
+                });
+            }
+            Ok(())
+        })?;
+        self.walk_table(&tables.block_signatures, |checker, block_number, _| {
+            checker.check_below_marker(
+                tables.block_signatures.name,
+                &block_number,
+                block_number,
+                "header",
+                header_marker,
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.casms, |checker, class_hash, location| {
+            checker.check_location(
+                tables.casms.name,
+                &class_hash,
+                OffsetKind::Casm,
+                location,
+                |location| txn.file_handlers.get_casm_unchecked(location),
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.compression_dictionaries, |_, _, _| Ok(()))?;
+        self.walk_table(&tables.contract_storage, |checker, key, _| {
+            checker.check_below_marker(
+                tables.contract_storage.name,
+                &key,
+                key.1,
+                "state",
+                state_marker,
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.declared_classes, |checker, class_hash, location| {
+            checker.check_location(
+                tables.declared_classes.name,
+                &class_hash,
+                OffsetKind::ContractClass,
+                location,
+                |location| txn.file_handlers.get_contract_class_unchecked(location),
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.declared_classes_block, |checker, class_hash, block_number| {
+            checker.check_below_marker(
+                tables.declared_classes_block.name,
+                &class_hash,
+                block_number,
+                "state",
+                state_marker,
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.deprecated_declared_classes, |checker, class_hash, class| {
+            checker.check_location(
+                tables.deprecated_declared_classes.name,
+                &class_hash,
+                OffsetKind::DeprecatedContractClass,
+                class.location_in_file,
+                |location| txn.file_handlers.get_deprecated_contract_class_unchecked(location),
+            );
+            Ok(())
+        })?;
+        self.walk_table(
+            &tables.deprecated_declared_classes_block,
+            |checker, class_hash, block_number| {
+                checker.check_below_marker(
+                    tables.deprecated_declared_classes_block.name,
+                    &class_hash,
+                    block_number,
+                    "state",
+                    state_marker,
+                );
+                Ok(())
+            },
+        )?;
+        self.walk_table(&tables.deployed_contracts, |checker, key, _| {
+            checker.check_below_marker(
+                tables.deployed_contracts.name,
+                &key,
+                key.1,
+                "state",
+                state_marker,
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.events, |checker, key, _| {
+            checker.check_below_marker(tables.events.name, &key, key.1.0, "body", body_marker);
+            Ok(())
+        })?;
+        self.walk_table(&tables.event_keys_filters, |checker, block_number, _| {
+            checker.check_below_marker(
+                tables.event_keys_filters.name,
+                &block_number,
+                block_number,
+                "body",
+                body_marker,
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.headers, |checker, block_number, header| {
+            checker.check_below_marker(
+                tables.headers.name,
+                &block_number,
+                block_number,
+                "header",
+                header_marker,
+            );
+            let indexed_number =
+                block_hash_to_number_table.get(&txn.txn, &header.block_hash).ok().flatten();
+            if header.block_number != block_number || indexed_number != Some(block_number) {
+                checker.report_issue(IntegrityIssue::IndexMismatch {
+                    table: tables.headers.name,
+                    key: format!("{block_number:?}"),
+                    msg: format!(
+                        "the header is of block {} and its hash {:?} is indexed to block \
+                         {indexed_number:?}",
+                        header.block_number, header.block_hash
+                    ),
+                });
+            }
+            Ok(())
+        })?;
+        // The messages are indexed from the base layer, so their transactions may be in blocks
+        // that aren't stored yet.
+        self.walk_table(&tables.l1_to_l2_messages, |_, _, _| Ok(()))?;
+        self.walk_table(&tables.markers, |_, _, _| Ok(()))?;
+        self.walk_table(&tables.migrations_progress, |_, _, _| Ok(()))?;
+        self.walk_table(&tables.nonces, |checker, key, _| {
+            checker.check_below_marker(tables.nonces.name, &key, key.1, "state", state_marker);
+            Ok(())
+        })?;
+        self.walk_table(&tables.file_offsets, |_, _, _| Ok(()))?;
+        self.walk_table(&tables.state_diffs, |checker, block_number, location| {
+            checker.check_below_marker(
+                tables.state_diffs.name,
+                &block_number,
+                block_number,
+                "state",
+                state_marker,
+            );
+            checker.check_location(
+                tables.state_diffs.name,
+                &block_number,
+                OffsetKind::ThinStateDiff,
+                location,
+                |location| txn.file_handlers.get_thin_state_diff_unchecked(location),
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.transaction_hash_to_idx, |checker, tx_hash, tx_index| {
+            let indexed_hash = transaction_metadata_table
+                .get(&txn.txn, &tx_index)
+                .ok()
+                .flatten()
+                .map(|tx_metadata| tx_metadata.tx_hash);
+            if indexed_hash != Some(tx_hash) {
+                checker.report_issue(IntegrityIssue::IndexMismatch {
+                    table: tables.transaction_hash_to_idx.name,
+                    key: format!("{tx_hash:?}"),
+                    msg: format!("the transaction {tx_index:?} has the hash {indexed_hash:?}"),
+                });
+            }
+            Ok(())
+        })?;
+        self.walk_table(&tables.transaction_metadata, |checker, tx_index, tx_metadata| {
+            let table = tables.transaction_metadata.name;
+            checker.check_below_marker(table, &tx_index, tx_index.0, "body", body_marker);
+            checker.check_location(
+                table,
+                &tx_index,
+                OffsetKind::Transaction,
+                tx_metadata.tx_location,
+                |location| txn.file_handlers.get_transaction_unchecked(location),
+            );
+            checker.check_location(
+                table,
+                &tx_index,
+                OffsetKind::TransactionOutput,
+                tx_metadata.tx_output_location,
+                |location| txn.file_handlers.get_transaction_output_unchecked(location),
+            );
+            let indexed_tx_index =
+                transaction_hash_to_idx_table.get(&txn.txn, &tx_metadata.tx_hash).ok().flatten();
+            if indexed_tx_index != Some(tx_index) {
+                checker.report_issue(IntegrityIssue::IndexMismatch {
+                    table,
+                    key: format!("{tx_index:?}"),
+                    msg: format!(
+                        "the transaction hash {:?} is indexed to {indexed_tx_index:?}",
+                        tx_metadata.tx_hash
+                    ),
+                });
+            }
+            Ok(())
+        })?;
+        self.walk_table(&tables.starknet_version, |checker, block_number, _| {
+            checker.check_below_marker(
+                tables.starknet_version.name,
+                &block_number,
+                block_number,
+                "header",
+                header_marker,
+            );
+            Ok(())
+        })?;
+        self.walk_table(&tables.storage_version, |_, _, _| Ok(()))?;
+        self.walk_table(&tables.class_hash_to_executable_class_hash, |_, _, _| Ok(()))?;
+        Ok(())
+    }
+
+    // Calls the given function on each entry of the table that can be deserialized, and reports
+    // the entries that can't.
+    fn walk_table<K, V, T>(
+        &mut self,
+        table_id: &TableIdentifier<K, V, T>,
+        mut check_entry: impl FnMut(&mut Self, K, V::Value) -> StorageResult<()>,
+    ) -> StorageResult<()>
+    where
+        K: Key + Debug,
+        V: ValueSerde + Debug,
+        T: DecodeRawEntry<K, V>,
+    {
+        let (txn, table) = (self.txn, table_id.name);
+        let mut n_entries = 0;
+        txn.txn.for_each_raw_entry(table, |key, value| -> StorageResult<()> {
+            n_entries += 1;
+            match T::decode_raw_entry(key, value) {
+                Some((key, value)) => check_entry(self, key, value),
+                None => {
+                    self.report_issue(IntegrityIssue::UndecodableEntry { table, key: hex(key) });
+                    Ok(())
+                }
+            }
+        })?;
+        info!("Checked the {n_entries} entries of the table {table}.");
+        self.report.n_entries.insert(table, n_entries);
+        Ok(())
+    }
+
+    // Reports an entry of the given block if the block is at or beyond the marker.
+    fn check_below_marker(
+        &mut self,
+        table: &'static str,
+        key: &impl Debug,
+        block_number: BlockNumber,
+        marker_name: &'static str,
+        marker: BlockNumber,
+    ) {
+        if block_number >= marker {
+            self.report_issue(IntegrityIssue::EntryBeyondMarker {
+                table,
+                key: format!("{key:?}"),
+                marker_name,
+                marker,
+            });
+        }
+    }
+
+    // Reports a location that points beyond the written part of its file, or to an object that
+    // can't be read from it.
+    fn check_location<T>(
+        &mut self,
+        table: &'static str,
+        key: &impl Debug,
+        offset_kind: OffsetKind,
+        location: LocationInFile,
+        read_object: impl FnOnce(LocationInFile) -> StorageResult<T>,
+    ) {
+        let file_offset = self.file_offsets.get(&offset_kind).copied().unwrap_or_default();
+        let key = format!("{key:?}");
+        // Objects beyond the written part of the file must not be read, since they may be beyond
+        // the end of the mapped memory.
+        if location.next_offset() > file_offset {
+            self.report_issue(IntegrityIssue::LocationOutOfFile {
+                table,
+                key,
+                offset_kind,
+                location,
+                file_offset,
+            });
+        } else if read_object(location).is_err() {
+            self.report_issue(IntegrityIssue::UndecodableFileObject {
+                table,
+                key,
+                offset_kind,
+                location,
+            });
+        }
+    }
+
+    // Reports the blocks below the markers that weren't pruned and whose header or state diff is
+    // missing.
+    fn check_missing_entries(&mut self) -> StorageResult<()> {
+        let txn = self.txn;
+        let headers_table = txn.txn.open_table(&txn.tables.headers)?;
+        let state_diffs_table = txn.txn.open_table(&txn.tables.state_diffs)?;
+        for block_number in (self.markers.pruned.0..self.markers.header.0).map(BlockNumber) {
+            // Headers that can't be deserialized were already reported.
+            if matches!(headers_table.get(&txn.txn, &block_number), Ok(None)) {
+                self.report_issue(IntegrityIssue::MissingEntry {
+                    table: txn.tables.headers.name,
+                    key: format!("{block_number:?}"),
+                });
+            }
+        }
+        for block_number in (self.markers.pruned.0..self.markers.state.0).map(BlockNumber) {
+            if matches!(state_diffs_table.get(&txn.txn, &block_number), Ok(None)) {
+                self.report_issue(IntegrityIssue::MissingEntry {
+                    table: txn.tables.state_diffs.name,
+                    key: format!("{block_number:?}"),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // Verifies the hashes of the blocks against their headers and parents, and the state diff
+    // commitments in their headers against their state diffs.
+    fn check_block_hashes(&mut self) -> StorageResult<()> {
+        let mut parent_hash = None;
+        for block_number in (self.markers.pruned.0..self.markers.header.0).map(BlockNumber) {
+            // Missing or corrupted headers and state diffs were already reported.
+            let Ok(Some(header)) = self.txn.get_block_header(block_number) else {
+                parent_hash = None;
+                continue;
+            };
+            let header_without_hash = &header.block_header_without_hash;
+            if parent_hash.is_some_and(|parent_hash| parent_hash != header_without_hash.parent_hash)
+            {
+                self.report_issue(IntegrityIssue::ParentHashMismatch {
+                    block_number,
+                    parent_hash: header_without_hash.parent_hash,
+                });
+            }
+            parent_hash = Some(header.block_hash);
+
+            if let (Some(stored), Ok(Some(state_diff))) =
+                (header.state_diff_commitment, self.txn.get_state_diff(block_number))
+            {
+                let computed = calculate_state_diff_hash(&state_diff);
+                if computed != stored {
+                    self.report_issue(IntegrityIssue::StateDiffCommitmentMismatch {
+                        block_number,
+                        stored,
+                        computed,
+                    });
+                }
+            }
+
+            let (
+                Some(state_diff_commitment),
+                Some(transaction_commitment),
+                Some(event_commitment),
+                Some(receipt_commitment),
+                Some(state_diff_length),
+            ) = (
+                header.state_diff_commitment,
+                header.transaction_commitment,
+                header.event_commitment,
+                header.receipt_commitment,
+                header.state_diff_length,
+            )
+            else {
+                continue;
+            };
+            let block_commitments = BlockHeaderCommitments {
+                transaction_commitment,
+                event_commitment,
+                receipt_commitment,
+                state_diff_commitment,
+                concatenated_counts: concat_counts(
+                    header.n_transactions,
+                    header.n_events,
+                    state_diff_length,
+                    header_without_hash.l1_da_mode,
+                ),
+            };
+            // Blocks of versions before 0.13.2 have a different hash calculation.
+            let Ok(computed) = calculate_block_hash(header_without_hash.clone(), block_commitments)
+            else {
+                continue;
+            };
+            self.report.n_verified_block_hashes += 1;
+            if computed != header.block_hash {
+                self.report_issue(IntegrityIssue::BlockHashMismatch {
+                    block_number,
+                    stored: header.block_hash,
+                    computed,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let digits: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("0x{digits}")
+}
@@ -0,0 +1,173 @@
+use apollo_test_utils::{get_test_block, get_test_state_diff};
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{
+    BlockHash,
+    BlockHeader,
+    BlockHeaderWithoutHash,
+    BlockNumber,
+    StarknetVersion,
+};
+use starknet_api::block_hash::block_hash_calculator::{
+    calculate_block_hash,
+    concat_counts,
+    BlockHeaderCommitments,
+};
+use starknet_api::core::{
+    EventCommitment,
+    ReceiptCommitment,
+    StateDiffCommitment,
+    TransactionCommitment,
+};
+use starknet_api::felt;
+use starknet_api::transaction::TransactionOffsetInBlock;
+
+use crate::body::{BodyStorageWriter, TransactionIndex};
+use crate::db::table_types::Table;
+use crate::header::HeaderStorageWriter;
+use crate::integrity::{check_storage_integrity, IntegrityIssue};
+use crate::state::StateStorageWriter;
+use crate::test_utils::get_test_storage;
+use crate::{table_names, MarkerKind, OffsetKind, StorageWriter};
+
+fn write_block(writer: &mut StorageWriter) {
+    let header = BlockHeader { block_hash: BlockHash(felt!("0x1")), ..Default::default() };
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &header)
+        .unwrap()
+        .append_body(BlockNumber(0), get_test_block(2, Some(2), None, None).body)
+        .unwrap()
+        .append_state_diff(BlockNumber(0), get_test_state_diff().into())
+        .unwrap()
+        .commit()
+        .unwrap();
+}
+
+// Returns a header of a block with the given number and parent, whose hash is computed from its
+// fields.
+fn header_with_valid_hash(block_number: BlockNumber, parent_hash: BlockHash) -> BlockHeader {
+    let mut header = BlockHeader {
+        block_header_without_hash: BlockHeaderWithoutHash {
+            block_number,
+            parent_hash,
+            starknet_version: StarknetVersion::V0_13_4,
+            ..Default::default()
+        },
+        state_diff_commitment: Some(StateDiffCommitment::default()),
+        transaction_commitment: Some(TransactionCommitment::default()),
+        event_commitment: Some(EventCommitment::default()),
+        receipt_commitment: Some(ReceiptCommitment::default()),
+        state_diff_length: Some(0),
+        ..Default::default()
+    };
+    let block_commitments = BlockHeaderCommitments {
+        concatenated_counts: concat_counts(0, 0, 0, header.block_header_without_hash.l1_da_mode),
+        ..Default::default()
+    };
+    header.block_hash =
+        calculate_block_hash(header.block_header_without_hash.clone(), block_commitments).unwrap();
+    header
+}
+
+#[test]
+fn consistent_storage() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    write_block(&mut writer);
+
+    let report = check_storage_integrity(&reader, true).unwrap();
+    assert_eq!(report.issues, vec![]);
+    assert_eq!(report.n_entries.keys().copied().collect::<Vec<_>>(), {
+        let mut table_names = table_names().to_vec();
+        table_names.sort();
+        table_names
+    });
+    assert_eq!(report.n_entries["headers"], 1);
+    assert_eq!(report.n_entries["transaction_metadata"], 2);
+}
+
+#[test]
+fn corrupted_storage() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    write_block(&mut writer);
+    let tx_hash = get_test_block(2, Some(2), None, None).body.transaction_hashes[0];
+
+    let txn = writer.begin_rw_txn().unwrap();
+    // An undecodable header.
+    txn.txn.put_raw_entries("headers", &[(vec![1], vec![1, 2, 3])]).unwrap();
+    // A state diff beyond the written part of its file.
+    let file_offsets_table = txn.txn.open_table(&txn.tables.file_offsets).unwrap();
+    file_offsets_table.upsert(&txn.txn, &OffsetKind::ThinStateDiff, &0).unwrap();
+    // A transaction whose hash isn't indexed.
+    let transaction_hash_to_idx_table =
+        txn.txn.open_table(&txn.tables.transaction_hash_to_idx).unwrap();
+    transaction_hash_to_idx_table.delete(&txn.txn, &tx_hash).unwrap();
+    // A block below the header marker without a header.
+    let markers_table = txn.txn.open_table(&txn.tables.markers).unwrap();
+    markers_table.upsert(&txn.txn, &MarkerKind::Header, &BlockNumber(2)).unwrap();
+    txn.commit().unwrap();
+
+    let report = check_storage_integrity(&reader, false).unwrap();
+    let [undecodable_header, state_diff_location, transaction_metadata, missing_header] =
+        report.issues.as_slice()
+    else {
+        panic!("Unexpected issues: {:?}", report.issues);
+    };
+    assert_eq!(
+        undecodable_header,
+        &IntegrityIssue::UndecodableEntry { table: "headers", key: "0x01".to_owned() }
+    );
+    assert_matches!(
+        state_diff_location,
+        IntegrityIssue::LocationOutOfFile {
+            table: "state_diffs",
+            key,
+            offset_kind: OffsetKind::ThinStateDiff,
+            file_offset: 0,
+            ..
+        } if key == &format!("{:?}", BlockNumber(0))
+    );
+    let tx_index = TransactionIndex(BlockNumber(0), TransactionOffsetInBlock(0));
+    assert_matches!(
+        transaction_metadata,
+        IntegrityIssue::IndexMismatch { table: "transaction_metadata", key, .. }
+            if key == &format!("{tx_index:?}")
+    );
+    assert_eq!(
+        missing_header,
+        &IntegrityIssue::MissingEntry { table: "headers", key: format!("{:?}", BlockNumber(1)) }
+    );
+}
+
+#[test]
+fn block_hashes_verification() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let first_header = header_with_valid_hash(BlockNumber(0), BlockHash::default());
+    let mut second_header = header_with_valid_hash(BlockNumber(1), first_header.block_hash);
+    let computed_hash = second_header.block_hash;
+    second_header.block_hash = BlockHash(felt!("0x1234"));
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &first_header)
+        .unwrap()
+        .append_header(BlockNumber(1), &second_header)
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    // The block hashes are verified only on demand.
+    assert_eq!(check_storage_integrity(&reader, false).unwrap().issues, vec![]);
+
+    let report = check_storage_integrity(&reader, true).unwrap();
+    assert_eq!(report.n_verified_block_hashes, 2);
+    assert_eq!(
+        report.issues,
+        vec![IntegrityIssue::BlockHashMismatch {
+            block_number: BlockNumber(1),
+            stored: second_header.block_hash,
+            computed: computed_hash,
+        }]
+    );
+}
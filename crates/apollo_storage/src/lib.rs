@@ -57,12 +57,14 @@
 //! indicates a change that is migratable.
 //!
 //! When a storage is opened with [`StorageScope::StateOnly`], only the state version must match.
-//! For storage opened with [`StorageScope::FullArchive`], both versions must match the crate's
-//! versions.
+//! For storage opened with [`StorageScope::FullArchive`] or [`StorageScope::Pruned`], both versions
+//! must match the crate's versions.
 //!
 //! Incompatibility occurs when the code and the database have differing major versions. However,
 //! if the code has the same major version but a higher minor version compared to the database, it
-//! will still function properly.
+//! will still function properly. Minor versions that require rewriting existing data are upgraded
+//! by the [`migrations`] of the storage, which are run explicitly (e.g., by the node) before the
+//! storage is opened.
 //!
 //! Example cases:
 //! - Code: {major: 0, minor: 0}, Database: {major: 1, minor: 0} will fail due to major version
@@ -81,6 +83,7 @@ pub mod class;
 pub mod class_hash;
 pub mod class_manager;
 pub mod compiled_class;
+pub mod compression_dictionaries;
 #[cfg(feature = "document_calls")]
 pub mod document_calls;
 pub mod storage_metrics;
@@ -88,9 +91,16 @@ pub mod storage_metrics;
 #[doc(hidden)]
 pub mod compression_utils;
 pub mod db;
+pub mod export;
 pub mod header;
+pub mod inspection;
+pub mod integrity;
+pub mod migrations;
 pub mod mmap_file;
+pub mod pruning;
+pub mod read_only;
 mod serialization;
+pub mod snapshot;
 pub mod state;
 mod version;
 
@@ -104,7 +114,7 @@ pub mod test_utils;
 
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::Debug;
-use std::fs;
+use std::fs::{self, File, OpenOptions};
 use std::sync::Arc;
 
 use apollo_config::dumping::{prepend_sub_config_name, ser_param, SerializeConfig};
@@ -115,6 +125,7 @@ use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
 use db::db_stats::{DbTableStats, DbWholeStats};
 use db::serialization::{Key, NoVersionValueWrapper, ValueSerde, VersionZeroWrapper};
 use db::table_types::{CommonPrefix, NoValue, Table, TableType};
+use fs2::FileExt;
 use mmap_file::{
     open_file,
     FileHandler,
@@ -126,45 +137,76 @@ use mmap_file::{
 };
 use serde::{Deserialize, Serialize};
 use starknet_api::block::{BlockHash, BlockNumber, BlockSignature, StarknetVersion};
-use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, Nonce};
+use starknet_api::core::{ClassHash, CompiledClassHash, ContractAddress, L1TransactionHash, Nonce};
 use starknet_api::deprecated_contract_class::ContractClass as DeprecatedContractClass;
 use starknet_api::state::{SierraContractClass, StateNumber, StorageKey, ThinStateDiff};
 use starknet_api::transaction::{Transaction, TransactionHash, TransactionOutput};
 use starknet_types_core::felt::Felt;
 use tracing::{debug, info, warn};
-use validator::Validate;
+use validator::{Validate, ValidationError};
 use version::{StorageVersionError, Version};
 
+use crate::body::event_keys_filter::EventKeysFilter;
 use crate::body::TransactionIndex;
+use crate::compression_dictionaries::{
+    load_compression_dictionaries,
+    reload_compression_dictionaries_if_needed,
+};
+use crate::compression_utils::{
+    CompressionDictionaryId,
+    SharedCompressionDictionaries,
+    StoredCompressionDictionary,
+};
 use crate::db::table_types::SimpleTable;
 use crate::db::{
     open_env,
     DbConfig,
     DbError,
     DbReader,
+    DbResult,
     DbTransaction,
     DbWriter,
     TableHandle,
     TableIdentifier,
+    TableOpener,
     TransactionKind,
     RO,
     RW,
 };
+use crate::export::ExportError;
 use crate::header::StorageBlockHeader;
+use crate::migrations::{
+    run_migrations,
+    verify_no_pending_migrations,
+    CompletedMigration,
+    STORAGE_MIGRATIONS,
+};
 use crate::mmap_file::MMapFileStats;
+use crate::pruning::{PruningStorageReader, MIN_RETAINED_BLOCKS};
+use crate::snapshot::SnapshotError;
 use crate::state::data::IndexedDeprecatedContractClass;
 use crate::version::{VersionStorageReader, VersionStorageWriter};
 
 // For more details on the storage version, see the module documentation.
 /// The current version of the storage state code.
-pub const STORAGE_VERSION_STATE: Version = Version { major: 6, minor: 0 };
+pub const STORAGE_VERSION_STATE: Version = Version { major: 6, minor: 2 };
 /// The current version of the storage blocks code.
-pub const STORAGE_VERSION_BLOCKS: Version = Version { major: 6, minor: 0 };
+pub const STORAGE_VERSION_BLOCKS: Version = Version { major: 6, minor: 3 };
 
-/// Opens a storage and returns a [`StorageReader`] and a [`StorageWriter`].
+/// Opens a storage and returns a [`StorageReader`] and a [`StorageWriter`]. Doesn't run the pending
+/// migrations of the storage, and fails if it has any; see [`migrations::migrate_storage`].
 pub fn open_storage(
     storage_config: StorageConfig,
 ) -> StorageResult<(StorageReader, StorageWriter)> {
+    let (reader, writer, _completed_migrations) = open_storage_inner(storage_config, false)?;
+    Ok((reader, writer))
+}
+
+// Opens a storage and, if `should_migrate` is set, runs its pending migrations and returns them.
+fn open_storage_inner(
+    storage_config: StorageConfig,
+    should_migrate: bool,
+) -> StorageResult<(StorageReader, StorageWriter, Vec<CompletedMigration>)> {
     info!("Opening storage: {}", storage_config.db_config.path_prefix.display());
     if !storage_config.db_config.path_prefix.exists()
         && !storage_config.db_config.enforce_file_exists
@@ -174,79 +216,114 @@ pub fn open_storage(
     }
 
     let (db_reader, mut db_writer) = open_env(&storage_config.db_config)?;
-    let tables = Arc::new(Tables {
-        block_hash_to_number: db_writer.create_simple_table("block_hash_to_number")?,
-        block_signatures: db_writer.create_simple_table("block_signatures")?,
-        casms: db_writer.create_simple_table("casms")?,
-        contract_storage: db_writer.create_common_prefix_table("contract_storage")?,
-        declared_classes: db_writer.create_simple_table("declared_classes")?,
-        declared_classes_block: db_writer.create_simple_table("declared_classes_block")?,
-        deprecated_declared_classes: db_writer
-            .create_simple_table("deprecated_declared_classes")?,
-        deprecated_declared_classes_block: db_writer
-            .create_simple_table("deprecated_declared_classes_block")?,
-        deployed_contracts: db_writer.create_simple_table("deployed_contracts")?,
-        events: db_writer.create_common_prefix_table("events")?,
-        headers: db_writer.create_simple_table("headers")?,
-        markers: db_writer.create_simple_table("markers")?,
-        nonces: db_writer.create_common_prefix_table("nonces")?,
-        file_offsets: db_writer.create_simple_table("file_offsets")?,
-        state_diffs: db_writer.create_simple_table("state_diffs")?,
-        transaction_hash_to_idx: db_writer.create_simple_table("transaction_hash_to_idx")?,
-        transaction_metadata: db_writer.create_simple_table("transaction_metadata")?,
-
-        // Version tables.
-        starknet_version: db_writer.create_simple_table("starknet_version")?,
-        storage_version: db_writer.create_simple_table("storage_version")?,
-
-        // Class hashes.
-        class_hash_to_executable_class_hash: db_writer
-            .create_simple_table("class_hash_to_executable_class_hash")?,
-    });
+    let tables = Arc::new(open_tables(&mut db_writer)?);
+    let compression_dictionaries =
+        SharedCompressionDictionaries::new(load_compression_dictionaries(
+            &db_reader.begin_ro_txn()?,
+            &tables.compression_dictionaries,
+        )?);
     let (file_writers, file_readers) = open_storage_files(
         &storage_config.db_config,
         storage_config.mmap_file_config,
         db_reader.clone(),
         &tables.file_offsets,
+        compression_dictionaries,
     )?;
 
+    let file_space_lock = Arc::new(open_file_space_lock(&storage_config.db_config)?);
+
     let reader = StorageReader {
         db_reader,
         tables: tables.clone(),
         scope: storage_config.scope,
         file_readers,
+        read_only: false,
+    };
+    let writer = StorageWriter {
+        db_writer,
+        tables,
+        scope: storage_config.scope,
+        file_writers,
+        file_space_lock,
     };
-    let writer = StorageWriter { db_writer, tables, scope: storage_config.scope, file_writers };
 
-    let writer = set_version_if_needed(reader.clone(), writer)?;
+    let (writer, completed_migrations) =
+        set_version_if_needed(reader.clone(), writer, should_migrate)?;
     verify_storage_version(reader.clone())?;
-    Ok((reader, writer))
+    Ok((reader, writer, completed_migrations))
+}
+
+// Returns the tables of the storage, which are created if the given DB handle is a writer.
+fn open_tables(db: &mut impl TableOpener) -> DbResult<Tables> {
+    Ok(Tables {
+        block_hash_to_number: db.open_simple_table("block_hash_to_number")?,
+        block_signatures: db.open_simple_table("block_signatures")?,
+        casms: db.open_simple_table("casms")?,
+        compression_dictionaries: db.open_simple_table("compression_dictionaries")?,
+        contract_storage: db.open_common_prefix_table("contract_storage")?,
+        declared_classes: db.open_simple_table("declared_classes")?,
+        declared_classes_block: db.open_simple_table("declared_classes_block")?,
+        deprecated_declared_classes: db.open_simple_table("deprecated_declared_classes")?,
+        deprecated_declared_classes_block: db
+            .open_simple_table("deprecated_declared_classes_block")?,
+        deployed_contracts: db.open_simple_table("deployed_contracts")?,
+        events: db.open_common_prefix_table("events")?,
+        event_keys_filters: db.open_simple_table("event_keys_filters")?,
+        headers: db.open_simple_table("headers")?,
+        l1_to_l2_messages: db.open_simple_table("l1_to_l2_messages")?,
+        markers: db.open_simple_table("markers")?,
+        migrations_progress: db.open_simple_table("migrations_progress")?,
+        nonces: db.open_common_prefix_table("nonces")?,
+        file_offsets: db.open_simple_table("file_offsets")?,
+        state_diffs: db.open_simple_table("state_diffs")?,
+        transaction_hash_to_idx: db.open_simple_table("transaction_hash_to_idx")?,
+        transaction_metadata: db.open_simple_table("transaction_metadata")?,
+
+        // Version tables.
+        starknet_version: db.open_simple_table("starknet_version")?,
+        storage_version: db.open_simple_table("storage_version")?,
+
+        // Class hashes.
+        class_hash_to_executable_class_hash: db
+            .open_simple_table("class_hash_to_executable_class_hash")?,
+    })
 }
 
 // In case storage version does not exist, set it to the crate version.
 // Expected to happen once - when the node is launched for the first time.
 // If the storage scope has changed, update accordingly.
+// Otherwise, if `should_migrate` is set, run the pending migrations and return them, and if not,
+// fail if there are pending migrations.
 fn set_version_if_needed(
     reader: StorageReader,
     mut writer: StorageWriter,
-) -> StorageResult<StorageWriter> {
-    let Some(existing_storage_version) = get_storage_version(reader)? else {
+    should_migrate: bool,
+) -> StorageResult<(StorageWriter, Vec<CompletedMigration>)> {
+    let Some(existing_storage_version) = get_storage_version(reader.clone())? else {
         // Initialize the storage version.
         writer.begin_rw_txn()?.set_state_version(&STORAGE_VERSION_STATE)?.commit()?;
-        // If in full-archive mode, also set the block version.
-        if writer.scope == StorageScope::FullArchive {
+        // If blocks are stored, also set the block version.
+        if writer.scope != StorageScope::StateOnly {
             writer.begin_rw_txn()?.set_blocks_version(&STORAGE_VERSION_BLOCKS)?.commit()?;
         }
         debug!(
             "Storage was initialized with state_version: {:?}, scope: {:?}, blocks_version: {:?}",
             STORAGE_VERSION_STATE, writer.scope, STORAGE_VERSION_BLOCKS
         );
-        return Ok(writer);
+        return Ok((writer, Vec::new()));
     };
     debug!("Existing storage state: {:?}", existing_storage_version);
     // Handle the case where the storage scope has changed.
     match existing_storage_version {
         StorageVersion::FullArchive(FullArchiveVersion { state_version: _, blocks_version: _ }) => {
+            // A pruned storage cannot change back to full-archive mode.
+            if writer.scope == StorageScope::FullArchive
+                && reader.begin_ro_txn()?.get_pruning_marker()? > BlockNumber(0)
+            {
+                return Err(StorageError::StorageVersionInconsistency(
+                    StorageVersionError::InconsistentStorageScope,
+                ));
+            }
             // TODO(yael): consider optimizing by deleting the block's data if the scope has changed
             // to StateOnly
             if writer.scope == StorageScope::StateOnly {
@@ -258,17 +335,25 @@ fn set_version_if_needed(
             }
         }
         StorageVersion::StateOnly(StateOnlyVersion { state_version: _ }) => {
-            // The storage cannot change from state-only to full-archive mode.
-            if writer.scope == StorageScope::FullArchive {
+            // The storage cannot change from state-only to a mode that stores blocks.
+            if writer.scope != StorageScope::StateOnly {
                 return Err(StorageError::StorageVersionInconsistency(
                     StorageVersionError::InconsistentStorageScope,
                 ));
             }
         }
     }
-    // Update the version if it's lower than the crate version.
+    let completed_migrations = if should_migrate {
+        run_migrations(&mut writer, STORAGE_MIGRATIONS)?
+    } else {
+        // The version must not be updated beyond a pending migration.
+        verify_no_pending_migrations(&reader.begin_ro_txn()?, STORAGE_MIGRATIONS)?;
+        Vec::new()
+    };
+    // Update the version if it's lower than the crate version. The versions between the migrations
+    // require no migration.
     let mut wtxn = writer.begin_rw_txn()?;
-    match existing_storage_version {
+    match get_storage_version(reader)?.expect("Storage should be initialized.") {
         StorageVersion::FullArchive(FullArchiveVersion { state_version, blocks_version }) => {
             // This allow is for when STORAGE_VERSION_STATE.minor = 0.
             #[allow(clippy::absurd_extreme_comparisons)]
@@ -308,7 +393,7 @@ fn set_version_if_needed(
         }
     }
     wtxn.commit()?;
-    Ok(writer)
+    Ok((writer, completed_migrations))
 }
 
 #[derive(Debug)]
@@ -413,6 +498,12 @@ pub enum StorageScope {
     /// Stores the data describing the current state. In this mode the transaction, events and
     /// state-diffs are not stored.
     StateOnly,
+    /// Stores all types of data, but only for the last `retained_blocks` blocks of the state; the
+    /// data of older blocks is deleted by the node, except for the latest state. See [`pruning`].
+    Pruned {
+        /// The number of blocks to retain, at least [`pruning::MIN_RETAINED_BLOCKS`].
+        retained_blocks: u64,
+    },
 }
 
 /// A struct for starting RO transactions ([`StorageTxn`]) to the storage.
@@ -422,18 +513,30 @@ pub struct StorageReader {
     file_readers: FileHandlers<RO>,
     tables: Arc<Tables>,
     scope: StorageScope,
+    // Whether the storage was opened with [`read_only::open_storage_read_only`].
+    read_only: bool,
 }
 
 impl StorageReader {
     /// Takes a snapshot of the current state of the storage and returns a [`StorageTxn`] for
     /// reading data from the storage.
     pub fn begin_ro_txn(&self) -> StorageResult<StorageTxn<'_, RO>> {
-        Ok(StorageTxn {
+        let txn = StorageTxn {
             txn: self.db_reader.begin_ro_txn()?,
             file_handlers: self.file_readers.clone(),
             tables: self.tables.clone(),
             scope: self.scope,
-        })
+        };
+        if self.read_only {
+            // The process that writes to the storage may have added compression dictionaries.
+            reload_compression_dictionaries_if_needed(&txn)?;
+        }
+        Ok(txn)
+    }
+
+    /// Returns whether the storage was opened with [`read_only::open_storage_read_only`].
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
     }
 
     /// Returns metadata about the tables in the storage.
@@ -464,6 +567,8 @@ pub struct StorageWriter {
     file_writers: FileHandlers<RW>,
     tables: Arc<Tables>,
     scope: StorageScope,
+    // See [`FILE_SPACE_LOCK_FILE_NAME`].
+    file_space_lock: Arc<File>,
 }
 
 impl StorageWriter {
@@ -477,6 +582,68 @@ impl StorageWriter {
             scope: self.scope,
         })
     }
+
+    // Returns another writer of the storage. The database serializes the write transactions of
+    // the writers, so they may write the storage concurrently.
+    pub(crate) fn another_writer(&self) -> Self {
+        Self {
+            db_writer: self.db_writer.another_writer(),
+            file_writers: self.file_writers.clone(),
+            tables: self.tables.clone(),
+            scope: self.scope,
+            file_space_lock: self.file_space_lock.clone(),
+        }
+    }
+
+    // Locks the release of the disk space of the mmap files, waiting for the snapshot exports that
+    // are running to finish.
+    pub(crate) fn lock_file_space_release(&self) -> StorageResult<FileSpaceReleaseLock> {
+        self.file_space_lock.lock_exclusive()?;
+        Ok(FileSpaceReleaseLock(self.file_space_lock.clone()))
+    }
+
+    // Locks the release of the disk space of the mmap files, or returns `None` if a snapshot is
+    // being exported.
+    pub(crate) fn try_lock_file_space_release(
+        &self,
+    ) -> StorageResult<Option<FileSpaceReleaseLock>> {
+        match self.file_space_lock.try_lock_exclusive() {
+            Ok(()) => Ok(Some(FileSpaceReleaseLock(self.file_space_lock.clone()))),
+            Err(err) if err.kind() == fs2::lock_contended_error().kind() => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+// The name of the file, in the directory of the storage, whose lock holds off the release of the
+// disk space of the mmap files. A snapshot export holds a shared lock on it while it copies the
+// files, and the space is released only under an exclusive lock, so the copies don't contain the
+// zeros of released space. Being a file lock, it holds off the release by other processes too.
+const FILE_SPACE_LOCK_FILE_NAME: &str = "file_space.lock";
+
+// Opens the file whose lock holds off the release of the disk space of the mmap files, creating it
+// if needed; see [`FILE_SPACE_LOCK_FILE_NAME`].
+pub(crate) fn open_file_space_lock(db_config: &DbConfig) -> StorageResult<File> {
+    Ok(OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(db_config.path().join(FILE_SPACE_LOCK_FILE_NAME))?)
+}
+
+// An exclusive lock on the release of the disk space of the mmap files, which is unlocked when it
+// is dropped; see [`FILE_SPACE_LOCK_FILE_NAME`].
+pub(crate) struct FileSpaceReleaseLock(Arc<File>);
+
+impl Drop for FileSpaceReleaseLock {
+    fn drop(&mut self) {
+        // Due to [#48919](https://github.com/rust-lang/rust/issues/48919) we use fully qualified
+        // syntax, instead of `self.0.unlock()`.
+        if let Err(err) = FileExt::unlock(self.0.as_ref()) {
+            warn!("Failed to unlock the release of the file space: {err}.");
+        }
+    }
 }
 
 /// A struct for interacting with the storage.
@@ -529,6 +696,7 @@ struct_field_names! {
         block_hash_to_number: TableIdentifier<BlockHash, NoVersionValueWrapper<BlockNumber>, SimpleTable>,
         block_signatures: TableIdentifier<BlockNumber, VersionZeroWrapper<BlockSignature>, SimpleTable>,
         casms: TableIdentifier<ClassHash, VersionZeroWrapper<LocationInFile>, SimpleTable>,
+        compression_dictionaries: TableIdentifier<CompressionDictionaryId, NoVersionValueWrapper<StoredCompressionDictionary>, SimpleTable>,
         // Empirically, defining the common prefix as (ContractAddress, StorageKey) is better space-wise than defining the
         // common prefix only as ContractAddress.
         contract_storage: TableIdentifier<((ContractAddress, StorageKey), BlockNumber), NoVersionValueWrapper<Felt>, CommonPrefix>,
@@ -539,8 +707,12 @@ struct_field_names! {
         // TODO(dvir): consider use here also the CommonPrefix table type.
         deployed_contracts: TableIdentifier<(ContractAddress, BlockNumber), VersionZeroWrapper<ClassHash>, SimpleTable>,
         events: TableIdentifier<(ContractAddress, TransactionIndex), NoVersionValueWrapper<NoValue>, CommonPrefix>,
+        event_keys_filters: TableIdentifier<BlockNumber, NoVersionValueWrapper<EventKeysFilter>, SimpleTable>,
         headers: TableIdentifier<BlockNumber, VersionZeroWrapper<StorageBlockHeader>, SimpleTable>,
+        // The L1 handler transactions of the messages sent by an L1 transaction, by sending order.
+        l1_to_l2_messages: TableIdentifier<(L1TransactionHash, u32), NoVersionValueWrapper<TransactionHash>, SimpleTable>,
         markers: TableIdentifier<MarkerKind, VersionZeroWrapper<BlockNumber>, SimpleTable>,
+        migrations_progress: TableIdentifier<String, NoVersionValueWrapper<u64>, SimpleTable>,
         nonces: TableIdentifier<(ContractAddress, BlockNumber), VersionZeroWrapper<Nonce>, CommonPrefix>,
         file_offsets: TableIdentifier<OffsetKind, NoVersionValueWrapper<usize>, SimpleTable>,
         state_diffs: TableIdentifier<BlockNumber, VersionZeroWrapper<LocationInFile>, SimpleTable>,
@@ -606,6 +778,10 @@ pub enum StorageError {
     #[error(transparent)]
     MMapFileError(#[from] MMapFileError),
     #[error(transparent)]
+    SnapshotError(#[from] SnapshotError),
+    #[error(transparent)]
+    ExportError(#[from] ExportError),
+    #[error(transparent)]
     StorageVersionInconsistency(#[from] StorageVersionError),
     #[error("The table {table_name} is unused under the {storage_scope:?} storage scope.")]
     ScopeError { table_name: String, storage_scope: StorageScope },
@@ -623,6 +799,15 @@ pub enum StorageError {
          {block_number}."
     )]
     BlockSignatureForNonExistingBlock { block_number: BlockNumber, block_signature: BlockSignature },
+    #[error(
+        "Cannot prune the blocks up to {target_pruning_marker}, beyond the state marker \
+         {state_marker}."
+    )]
+    InvalidPruningTarget { target_pruning_marker: BlockNumber, state_marker: BlockNumber },
+    #[error(
+        "The state {state_number:?} was pruned; the first available block is {pruning_marker}."
+    )]
+    StatePruned { state_number: StateNumber, pruning_marker: BlockNumber },
 }
 
 /// A type alias that maps to std::result::Result<T, StorageError>.
@@ -636,9 +821,19 @@ pub struct StorageConfig {
     pub db_config: DbConfig,
     #[validate]
     pub mmap_file_config: MmapFileConfig,
+    #[validate(custom = "validate_storage_scope")]
     pub scope: StorageScope,
 }
 
+fn validate_storage_scope(scope: &StorageScope) -> Result<(), ValidationError> {
+    if let StorageScope::Pruned { retained_blocks } = scope {
+        if *retained_blocks < MIN_RETAINED_BLOCKS {
+            return Err(ValidationError::new("retained_blocks is below the minimal retention"));
+        }
+    }
+    Ok(())
+}
+
 impl SerializeConfig for StorageConfig {
     fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
         let mut dumped_config = BTreeMap::from_iter([ser_param(
@@ -669,6 +864,9 @@ pub struct DbStats {
 // - CompiledClass <= Class <= State <= Header
 // - Body <= Header
 // - BaseLayerBlock <= Header
+// - ClassManagerBlock <= State
+// - Pruned <= State
+// - EventKeysFilter <= Body
 // Event is currently unsupported.
 pub(crate) enum MarkerKind {
     Header,
@@ -682,6 +880,17 @@ pub(crate) enum MarkerKind {
     /// Marks the block beyond the last block that its classes can't be compiled with the current
     /// compiler version used in the class manager. Determined by starknet version.
     CompilerBackwardCompatibility,
+    /// Marks the first block whose data was not pruned.
+    Pruned,
+    /// Marks the first block whose event keys filter may be missing although its body was
+    /// written.
+    EventKeysFilter,
+    /// Marks the first L1 block whose messages to L2 were not indexed. Unlike the other markers,
+    /// its value is a block number of the base layer.
+    L1ToL2Messages,
+    /// Marks the lowest L1 block whose messages to L2 were indexed. Unlike the other markers, it
+    /// is inclusive, and it decreases as older L1 blocks are backfilled.
+    L1ToL2MessagesBackfill,
 }
 
 pub(crate) type MarkersTable<'env> =
@@ -695,6 +904,8 @@ struct FileHandlers<Mode: TransactionKind> {
     deprecated_contract_class: FileHandler<VersionZeroWrapper<DeprecatedContractClass>, Mode>,
     transaction_output: FileHandler<VersionZeroWrapper<TransactionOutput>, Mode>,
     transaction: FileHandler<VersionZeroWrapper<Transaction>, Mode>,
+    // The dictionaries with which the classes are compressed.
+    compression_dictionaries: SharedCompressionDictionaries,
 }
 
 impl FileHandlers<RW> {
@@ -706,12 +917,12 @@ impl FileHandlers<RW> {
 
     // Appends a contract class to the corresponding file and returns its location.
     fn append_contract_class(&self, contract_class: &SierraContractClass) -> LocationInFile {
-        self.clone().contract_class.append(contract_class)
+        self.compression_dictionaries.scope(|| self.clone().contract_class.append(contract_class))
     }
 
     // Appends a CASM to the corresponding file and returns its location.
     fn append_casm(&self, casm: &CasmContractClass) -> LocationInFile {
-        self.clone().casm.append(casm)
+        self.compression_dictionaries.scope(|| self.clone().casm.append(casm))
     }
 
     // Appends a deprecated contract class to the corresponding file and returns its location.
@@ -719,7 +930,8 @@ impl FileHandlers<RW> {
         &self,
         deprecated_contract_class: &DeprecatedContractClass,
     ) -> LocationInFile {
-        self.clone().deprecated_contract_class.append(deprecated_contract_class)
+        self.compression_dictionaries
+            .scope(|| self.clone().deprecated_contract_class.append(deprecated_contract_class))
     }
 
     // Appends a thin transaction output to the corresponding file and returns its location.
@@ -773,14 +985,17 @@ impl<Mode: TransactionKind> FileHandlers<Mode> {
         &self,
         location: LocationInFile,
     ) -> StorageResult<SierraContractClass> {
-        self.contract_class.get(location)?.ok_or(StorageError::DBInconsistency {
+        let contract_class =
+            self.compression_dictionaries.scope(|| self.contract_class.get(location))?;
+        contract_class.ok_or(StorageError::DBInconsistency {
             msg: format!("ContractClass at location {:?} not found.", location),
         })
     }
 
     // Returns the CASM at the given location or an error in case it doesn't exist.
     fn get_casm_unchecked(&self, location: LocationInFile) -> StorageResult<CasmContractClass> {
-        self.casm.get(location)?.ok_or(StorageError::DBInconsistency {
+        let casm = self.compression_dictionaries.scope(|| self.casm.get(location))?;
+        casm.ok_or(StorageError::DBInconsistency {
             msg: format!("CasmContractClass at location {:?} not found.", location),
         })
     }
@@ -791,7 +1006,9 @@ impl<Mode: TransactionKind> FileHandlers<Mode> {
         &self,
         location: LocationInFile,
     ) -> StorageResult<DeprecatedContractClass> {
-        self.deprecated_contract_class.get(location)?.ok_or(StorageError::DBInconsistency {
+        let deprecated_contract_class =
+            self.compression_dictionaries.scope(|| self.deprecated_contract_class.get(location))?;
+        deprecated_contract_class.ok_or(StorageError::DBInconsistency {
             msg: format!("DeprecatedContractClass at location {:?} not found.", location),
         })
     }
@@ -820,6 +1037,7 @@ fn open_storage_files(
     mmap_file_config: MmapFileConfig,
     db_reader: DbReader,
     file_offsets_table: &TableIdentifier<OffsetKind, NoVersionValueWrapper<usize>, SimpleTable>,
+    compression_dictionaries: SharedCompressionDictionaries,
 ) -> StorageResult<(FileHandlers<RW>, FileHandlers<RO>)> {
     let db_transaction = db_reader.begin_ro_txn()?;
     let table = db_transaction.open_table(file_offsets_table)?;
@@ -829,7 +1047,7 @@ fn open_storage_files(
         table.get(&db_transaction, &OffsetKind::ThinStateDiff)?.unwrap_or_default();
     let (thin_state_diff_writer, thin_state_diff_reader) = open_file(
         mmap_file_config.clone(),
-        db_config.path().join("thin_state_diff.dat"),
+        db_config.path().join(OffsetKind::ThinStateDiff.file_name()),
         thin_state_diff_offset,
     )?;
 
@@ -837,19 +1055,22 @@ fn open_storage_files(
         table.get(&db_transaction, &OffsetKind::ContractClass)?.unwrap_or_default();
     let (contract_class_writer, contract_class_reader) = open_file(
         mmap_file_config.clone(),
-        db_config.path().join("contract_class.dat"),
+        db_config.path().join(OffsetKind::ContractClass.file_name()),
         contract_class_offset,
     )?;
 
     let casm_offset = table.get(&db_transaction, &OffsetKind::Casm)?.unwrap_or_default();
-    let (casm_writer, casm_reader) =
-        open_file(mmap_file_config.clone(), db_config.path().join("casm.dat"), casm_offset)?;
+    let (casm_writer, casm_reader) = open_file(
+        mmap_file_config.clone(),
+        db_config.path().join(OffsetKind::Casm.file_name()),
+        casm_offset,
+    )?;
 
     let deprecated_contract_class_offset =
         table.get(&db_transaction, &OffsetKind::DeprecatedContractClass)?.unwrap_or_default();
     let (deprecated_contract_class_writer, deprecated_contract_class_reader) = open_file(
         mmap_file_config.clone(),
-        db_config.path().join("deprecated_contract_class.dat"),
+        db_config.path().join(OffsetKind::DeprecatedContractClass.file_name()),
         deprecated_contract_class_offset,
     )?;
 
@@ -857,14 +1078,17 @@ fn open_storage_files(
         table.get(&db_transaction, &OffsetKind::TransactionOutput)?.unwrap_or_default();
     let (transaction_output_writer, transaction_output_reader) = open_file(
         mmap_file_config.clone(),
-        db_config.path().join("transaction_output.dat"),
+        db_config.path().join(OffsetKind::TransactionOutput.file_name()),
         transaction_output_offset,
     )?;
 
     let transaction_offset =
         table.get(&db_transaction, &OffsetKind::Transaction)?.unwrap_or_default();
-    let (transaction_writer, transaction_reader) =
-        open_file(mmap_file_config, db_config.path().join("transaction.dat"), transaction_offset)?;
+    let (transaction_writer, transaction_reader) = open_file(
+        mmap_file_config,
+        db_config.path().join(OffsetKind::Transaction.file_name()),
+        transaction_offset,
+    )?;
 
     Ok((
         FileHandlers {
@@ -874,6 +1098,7 @@ fn open_storage_files(
             deprecated_contract_class: deprecated_contract_class_writer,
             transaction_output: transaction_output_writer,
             transaction: transaction_writer,
+            compression_dictionaries: compression_dictionaries.clone(),
         },
         FileHandlers {
             thin_state_diff: thin_state_diff_reader,
@@ -882,6 +1107,7 @@ fn open_storage_files(
             deprecated_contract_class: deprecated_contract_class_reader,
             transaction_output: transaction_output_reader,
             transaction: transaction_reader,
+            compression_dictionaries,
         },
     ))
 }
@@ -903,6 +1129,30 @@ pub enum OffsetKind {
     Transaction,
 }
 
+impl OffsetKind {
+    // All the kinds of mmap files.
+    pub(crate) const ALL: [OffsetKind; 6] = [
+        OffsetKind::ThinStateDiff,
+        OffsetKind::ContractClass,
+        OffsetKind::Casm,
+        OffsetKind::DeprecatedContractClass,
+        OffsetKind::TransactionOutput,
+        OffsetKind::Transaction,
+    ];
+
+    // Returns the name of the file of this kind in the storage directory.
+    pub(crate) fn file_name(&self) -> &'static str {
+        match self {
+            OffsetKind::ThinStateDiff => "thin_state_diff.dat",
+            OffsetKind::ContractClass => "contract_class.dat",
+            OffsetKind::Casm => "casm.dat",
+            OffsetKind::DeprecatedContractClass => "deprecated_contract_class.dat",
+            OffsetKind::TransactionOutput => "transaction_output.dat",
+            OffsetKind::Transaction => "transaction.dat",
+        }
+    }
+}
+
 /// A storage query. Used for benchmarking in the storage_benchmark binary.
 // TODO(dvir): add more queries (especially get casm).
 // TODO(dvir): consider move this, maybe to test_utils.
@@ -0,0 +1,222 @@
+//! Versioned migrations of the storage.
+//!
+//! A change of the storage that requires rewriting existing data, e.g., adding an index of the
+//! stored blocks, bumps the minor version of the relevant part of the storage (see the
+//! [`crate`]-level documentation) and registers a [`StorageMigration`] in [`STORAGE_MIGRATIONS`]
+//! that upgrades storages from the previous version, so such changes don't require a re-sync.
+//! The migrations run in order only with [`migrate_storage`], which the node calls before it opens
+//! its storages and the `storage_migrate` binary runs. [`open_storage`](crate::open_storage) fails
+//! on a storage with pending migrations, so other users of the storage (e.g., tools) never migrate
+//! it.
+//!
+//! A migration runs in batches, each in its own transaction. The progress of the migration is
+//! committed together with each batch, so an interrupted migration resumes from its last batch the
+//! next time the migrations run. The version of the storage is set to the target version of the
+//! migration in the transaction of its last batch. The last batch may release the disk space of
+//! the data that the previous batches rewrote (see [`crate::mmap_file`]), so snapshot exports don't
+//! run concurrently with migrations.
+
+#[cfg(test)]
+#[path = "migrations_test.rs"]
+mod migrations_test;
+
+use serde::Serialize;
+use tracing::info;
+
+use crate::body::event_keys_filter::EVENT_KEYS_FILTERS_MIGRATION;
+use crate::compression_dictionaries::COMPRESSION_DICTIONARIES_MIGRATION;
+use crate::db::table_types::Table;
+use crate::db::{TransactionKind, RW};
+use crate::version::{StorageVersionError, Version, VersionStorageReader, VersionStorageWriter};
+use crate::{open_storage_inner, StorageConfig, StorageResult, StorageTxn, StorageWriter};
+
+/// The part of the storage whose version a migration upgrades.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize)]
+pub enum VersionComponent {
+    /// The version of the state, see [`crate::STORAGE_VERSION_STATE`].
+    State,
+    /// The version of the blocks, see [`crate::STORAGE_VERSION_BLOCKS`].
+    Blocks,
+}
+
+impl VersionComponent {
+    fn get_version<Mode: TransactionKind>(
+        &self,
+        txn: &StorageTxn<'_, Mode>,
+    ) -> StorageResult<Option<Version>> {
+        match self {
+            Self::State => txn.get_state_version(),
+            Self::Blocks => txn.get_blocks_version(),
+        }
+    }
+
+    fn set_version<'env>(
+        &self,
+        txn: StorageTxn<'env, RW>,
+        version: &Version,
+    ) -> StorageResult<StorageTxn<'env, RW>> {
+        match self {
+            Self::State => txn.set_state_version(version),
+            Self::Blocks => txn.set_blocks_version(version),
+        }
+    }
+}
+
+/// The result of migrating a batch.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum MigrationStep {
+    /// The migration should continue from the given progress.
+    Continue(u64),
+    /// The migration is done.
+    Done,
+}
+
+/// Migrates a batch of at most the given size, starting from the given progress, whose meaning is
+/// up to the migration (e.g., a block number). The progress of a migration that hasn't started is
+/// 0.
+pub(crate) type MigrateBatchFn =
+    for<'env> fn(
+        StorageTxn<'env, RW>,
+        u64,
+        u64,
+    ) -> StorageResult<(StorageTxn<'env, RW>, MigrationStep)>;
+
+/// A migration of the storage from one version to a higher minor version.
+pub(crate) struct StorageMigration {
+    /// A unique name of the migration, under which its progress is stored.
+    pub name: &'static str,
+    pub component: VersionComponent,
+    pub from_version: Version,
+    pub to_version: Version,
+    pub batch_size: u64,
+    pub migrate_batch: MigrateBatchFn,
+}
+
+/// The migrations of the storage, ordered by their versions.
+pub(crate) const STORAGE_MIGRATIONS: &[StorageMigration] =
+    &[COMPRESSION_DICTIONARIES_MIGRATION, EVENT_KEYS_FILTERS_MIGRATION];
+
+/// A migration that was completed.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct CompletedMigration {
+    /// The name of the migration.
+    pub name: &'static str,
+    /// The component of the storage version that the migration upgraded.
+    pub component: VersionComponent,
+    /// The version of the component before the migration.
+    pub from_version: Version,
+    /// The version of the component after the migration.
+    pub to_version: Version,
+    /// The number of batches that were migrated, not including the batches of a previous run that
+    /// was interrupted.
+    pub n_batches: u64,
+}
+
+/// Opens the storage, runs its pending migrations, and returns the completed migrations. Creates
+/// the storage if it doesn't exist and the configuration allows it.
+pub fn migrate_storage(storage_config: StorageConfig) -> StorageResult<Vec<CompletedMigration>> {
+    let (_reader, _writer, completed_migrations) = open_storage_inner(storage_config, true)?;
+    Ok(completed_migrations)
+}
+
+/// Fails if one of the migrations should run on the storage; see [`run_migrations`].
+pub(crate) fn verify_no_pending_migrations<Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+    migrations: &[StorageMigration],
+) -> StorageResult<()> {
+    for migration in migrations {
+        if let Some(storage_version) = version_to_migrate(txn, migration)? {
+            return Err(StorageVersionError::PendingMigration {
+                name: migration.name,
+                storage_version,
+            }
+            .into());
+        }
+    }
+    Ok(())
+}
+
+// Returns the version of the storage if the migration should run on it, i.e., if the target
+// version of the migration is higher than the version of the storage, with the same major version.
+fn version_to_migrate<Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+    migration: &StorageMigration,
+) -> StorageResult<Option<Version>> {
+    let Some(storage_version) = migration.component.get_version(txn)? else {
+        // The storage doesn't have this part, e.g., the blocks of a state-only storage.
+        return Ok(None);
+    };
+    if storage_version.major != migration.to_version.major
+        || storage_version.minor >= migration.to_version.minor
+    {
+        return Ok(None);
+    }
+    Ok(Some(storage_version))
+}
+
+// Runs, in order, the migrations whose target version is higher than the version of the storage
+// (with the same major version). Versions between the target version of a migration and the source
+// version of the next one require no migration, so the version is set to the source version before
+// running a migration.
+pub(crate) fn run_migrations(
+    writer: &mut StorageWriter,
+    migrations: &[StorageMigration],
+) -> StorageResult<Vec<CompletedMigration>> {
+    let mut completed_migrations = Vec::new();
+    for migration in migrations {
+        let mut txn = writer.begin_rw_txn()?;
+        let Some(storage_version) = version_to_migrate(&txn, migration)? else {
+            continue;
+        };
+        if storage_version.minor < migration.from_version.minor {
+            txn = migration.component.set_version(txn, &migration.from_version)?;
+        }
+        txn.commit()?;
+        completed_migrations.push(run_migration(writer, migration)?);
+    }
+    Ok(completed_migrations)
+}
+
+fn run_migration(
+    writer: &mut StorageWriter,
+    migration: &StorageMigration,
+) -> StorageResult<CompletedMigration> {
+    let name = migration.name.to_owned();
+    let mut n_batches = 0;
+    // A migration may release the disk space of the data it rewrote, so it waits for the snapshot
+    // exports that are running to finish.
+    let _file_space_release_lock = writer.lock_file_space_release()?;
+    loop {
+        let txn = writer.begin_rw_txn()?;
+        let progress =
+            txn.open_table(&txn.tables.migrations_progress)?.get(&txn.txn, &name)?.unwrap_or(0);
+        if n_batches == 0 {
+            info!(
+                "Running the storage migration {name} from version {} to {}, starting at progress \
+                 {progress}.",
+                migration.from_version, migration.to_version
+            );
+        }
+        let (txn, step) = (migration.migrate_batch)(txn, progress, migration.batch_size)?;
+        n_batches += 1;
+        let migrations_progress_table = txn.open_table(&txn.tables.migrations_progress)?;
+        match step {
+            MigrationStep::Continue(progress) => {
+                migrations_progress_table.upsert(&txn.txn, &name, &progress)?;
+                txn.commit()?;
+            }
+            MigrationStep::Done => {
+                migrations_progress_table.delete(&txn.txn, &name)?;
+                migration.component.set_version(txn, &migration.to_version)?.commit()?;
+                info!("Completed the storage migration {name} in {n_batches} batches.");
+                return Ok(CompletedMigration {
+                    name: migration.name,
+                    component: migration.component,
+                    from_version: migration.from_version.clone(),
+                    to_version: migration.to_version.clone(),
+                    n_batches,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use std::sync::Mutex;
+
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+
+use crate::db::table_types::Table;
+use crate::db::RW;
+use crate::migrations::{
+    run_migrations,
+    verify_no_pending_migrations,
+    MigrationStep,
+    StorageMigration,
+    VersionComponent,
+    STORAGE_MIGRATIONS,
+};
+use crate::test_utils::get_test_storage;
+use crate::version::{StorageVersionError, Version, VersionStorageReader};
+use crate::{
+    StorageError,
+    StorageResult,
+    StorageTxn,
+    STORAGE_VERSION_BLOCKS,
+    STORAGE_VERSION_STATE,
+};
+
+const TEST_MIGRATION_END: u64 = 5;
+// The progress at which the test migration fails, if any.
+static FAIL_AT_PROGRESS: Mutex<Option<u64>> = Mutex::new(None);
+// The progress of each batch of the test migration.
+static MIGRATED_BATCHES: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+
+fn test_migrate_batch(
+    txn: StorageTxn<'_, RW>,
+    progress: u64,
+    batch_size: u64,
+) -> StorageResult<(StorageTxn<'_, RW>, MigrationStep)> {
+    if *FAIL_AT_PROGRESS.lock().unwrap() == Some(progress) {
+        return Err(StorageError::DBInconsistency { msg: "Test migration failure.".to_owned() });
+    }
+    MIGRATED_BATCHES.lock().unwrap().push(progress);
+    let next_progress = progress + batch_size;
+    if next_progress >= TEST_MIGRATION_END {
+        return Ok((txn, MigrationStep::Done));
+    }
+    Ok((txn, MigrationStep::Continue(next_progress)))
+}
+
+fn done_migrate_batch(
+    txn: StorageTxn<'_, RW>,
+    _progress: u64,
+    _batch_size: u64,
+) -> StorageResult<(StorageTxn<'_, RW>, MigrationStep)> {
+    Ok((txn, MigrationStep::Done))
+}
+
+fn next_minor_version(version: &Version) -> Version {
+    Version { major: version.major, minor: version.minor + 1 }
+}
+
+#[test]
+fn registered_migrations_are_ordered() {
+    for component in [VersionComponent::State, VersionComponent::Blocks] {
+        let crate_version = match component {
+            VersionComponent::State => STORAGE_VERSION_STATE,
+            VersionComponent::Blocks => STORAGE_VERSION_BLOCKS,
+        };
+        let mut previous_to_version: Option<&Version> = None;
+        for migration in STORAGE_MIGRATIONS.iter().filter(|m| m.component == component) {
+            assert_eq!(migration.from_version.major, migration.to_version.major);
+            assert!(migration.from_version.minor < migration.to_version.minor);
+            assert_eq!(migration.to_version.major, crate_version.major);
+            assert!(migration.to_version.minor <= crate_version.minor);
+            assert!(migration.batch_size > 0);
+            if let Some(previous_to_version) = previous_to_version {
+                assert!(previous_to_version.minor <= migration.from_version.minor);
+            }
+            previous_to_version = Some(&migration.to_version);
+        }
+    }
+    let mut names = STORAGE_MIGRATIONS.iter().map(|migration| migration.name).collect::<Vec<_>>();
+    names.sort();
+    names.dedup();
+    assert_eq!(names.len(), STORAGE_MIGRATIONS.len());
+}
+
+#[test]
+fn interrupted_migration_resumes() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let to_version = next_minor_version(&next_minor_version(&STORAGE_VERSION_BLOCKS));
+    let migrations = [StorageMigration {
+        name: "test_migration",
+        component: VersionComponent::Blocks,
+        from_version: next_minor_version(&STORAGE_VERSION_BLOCKS),
+        to_version: to_version.clone(),
+        batch_size: 2,
+        migrate_batch: test_migrate_batch,
+    }];
+
+    *FAIL_AT_PROGRESS.lock().unwrap() = Some(4);
+    assert_matches!(
+        run_migrations(&mut writer, &migrations),
+        Err(StorageError::DBInconsistency { .. })
+    );
+    let txn = reader.begin_ro_txn().unwrap();
+    // The version is set to the source version of the migration before it starts.
+    assert_eq!(txn.get_blocks_version().unwrap(), Some(migrations[0].from_version.clone()));
+    let migrations_progress_table = txn.open_table(&txn.tables.migrations_progress).unwrap();
+    assert_eq!(
+        migrations_progress_table.get(&txn.txn, &"test_migration".to_owned()).unwrap(),
+        Some(4)
+    );
+    drop(txn);
+
+    *FAIL_AT_PROGRESS.lock().unwrap() = None;
+    let completed_migrations = run_migrations(&mut writer, &migrations).unwrap();
+    assert_eq!(completed_migrations.len(), 1);
+    assert_eq!(completed_migrations[0].n_batches, 1);
+    assert_eq!(*MIGRATED_BATCHES.lock().unwrap(), vec![0, 2, 4]);
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_blocks_version().unwrap(), Some(to_version));
+    let migrations_progress_table = txn.open_table(&txn.tables.migrations_progress).unwrap();
+    assert_eq!(
+        migrations_progress_table.get(&txn.txn, &"test_migration".to_owned()).unwrap(),
+        None
+    );
+    drop(txn);
+
+    // A completed migration doesn't run again.
+    assert_eq!(run_migrations(&mut writer, &migrations).unwrap(), vec![]);
+    assert_eq!(*MIGRATED_BATCHES.lock().unwrap(), vec![0, 2, 4]);
+}
+
+#[test]
+fn pending_migration_fails_verification() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let migrations = [StorageMigration {
+        name: "pending_test_migration",
+        component: VersionComponent::Blocks,
+        from_version: STORAGE_VERSION_BLOCKS,
+        to_version: next_minor_version(&STORAGE_VERSION_BLOCKS),
+        batch_size: 1,
+        migrate_batch: done_migrate_batch,
+    }];
+
+    assert_matches!(
+        verify_no_pending_migrations(&reader.begin_ro_txn().unwrap(), &migrations),
+        Err(StorageError::StorageVersionInconsistency(StorageVersionError::PendingMigration {
+            name: "pending_test_migration",
+            ..
+        }))
+    );
+    assert_eq!(run_migrations(&mut writer, &migrations).unwrap().len(), 1);
+    verify_no_pending_migrations(&reader.begin_ro_txn().unwrap(), &migrations).unwrap();
+}
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use apollo_test_utils::get_rng;
+use assert_matches::assert_matches;
 use pretty_assertions::assert_eq;
 use rand::Rng;
 use tempfile::tempdir;
@@ -274,6 +275,39 @@ fn reader_when_writer_is_out_of_scope() {
     dir.close().unwrap();
 }
 
+#[cfg(target_os = "linux")]
+#[test]
+fn release_up_to() {
+    use std::os::unix::fs::MetadataExt;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().to_path_buf().join("test_release_up_to");
+    let offset = 0;
+    let (mut writer, reader) = open_file::<NoVersionValueWrapper<Vec<u8>>>(
+        get_mmap_file_test_config(),
+        path.clone(),
+        offset,
+    )
+    .unwrap();
+    let released_location = writer.append(&vec![1; 4 * page_size::get()]);
+    let data = vec![2, 3];
+    let location_in_file = writer.append(&data);
+    writer.flush();
+    let allocated_blocks = || std::fs::metadata(&path).unwrap().blocks();
+    let allocated_blocks_before_release = allocated_blocks();
+
+    writer.release_up_to(location_in_file).unwrap();
+
+    assert!(allocated_blocks() < allocated_blocks_before_release);
+    assert_eq!(reader.get(location_in_file).unwrap().unwrap(), data);
+    assert_matches!(
+        reader.get(released_location),
+        Err(MMapFileError::LocationReleased { location }) if location == released_location
+    );
+
+    dir.close().unwrap();
+}
+
 #[test]
 fn storage_serde_test_location_in_file() {
     let item = LocationInFile::get_test_instance(&mut get_rng());
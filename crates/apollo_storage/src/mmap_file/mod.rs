@@ -13,13 +13,14 @@ use std::fs::{File, OpenOptions};
 use std::marker::PhantomData;
 use std::path::PathBuf;
 use std::result;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
 use apollo_config::dumping::{ser_param, SerializeConfig};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
 #[cfg(test)]
 use apollo_test_utils::GetTestInstance;
-use memmap2::{MmapMut, MmapOptions};
+use memmap2::{Mmap, MmapMut, MmapOptions};
 #[cfg(test)]
 use rand_chacha::ChaCha8Rng;
 use serde::{Deserialize, Serialize};
@@ -100,6 +101,22 @@ pub enum MMapFileError {
     /// Number conversion error.
     #[error(transparent)]
     TryFromInt(#[from] std::num::TryFromIntError),
+
+    /// Reading an object beyond the end of a file.
+    #[error("The location {location:?} is beyond the end of the file, whose size is {file_size}.")]
+    LocationOutOfBounds {
+        /// The location that was read.
+        location: LocationInFile,
+        /// The size of the file.
+        file_size: usize,
+    },
+
+    /// Reading an object whose disk space was released (e.g., since it was pruned).
+    #[error("The disk space of the location {location:?} was released.")]
+    LocationReleased {
+        /// The location that was read.
+        location: LocationInFile,
+    },
 }
 
 /// A trait for writing to a memory mapped file.
@@ -118,7 +135,7 @@ pub(crate) trait Reader<V: ValueSerde> {
 }
 
 /// Represents a location in the file.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
 pub struct LocationInFile {
     /// Offset in the file.
     offset: usize,
@@ -133,19 +150,35 @@ impl LocationInFile {
     }
 }
 
+/// The memory map of a file, which is read-only if the storage was opened as read-only.
+#[derive(Debug)]
+enum FileMemoryMap {
+    Writable(MmapMut),
+    // Only kept alive, since the readers read the file through the pointer of the map.
+    ReadOnly(#[allow(dead_code)] Mmap),
+}
+
 /// Represents a memory mapped append only file.
 #[derive(Debug)]
 struct MMapFile<V: ValueSerde> {
     config: MmapFileConfig,
     file: File,
     size: usize,
-    mmap: MmapMut,
+    mmap: FileMemoryMap,
     offset: usize,
     should_flush: bool,
     _value_type: PhantomData<V>,
 }
 
 impl<V: ValueSerde> MMapFile<V> {
+    /// Returns the writable memory map of the file.
+    fn writable_mmap(&mut self) -> &mut MmapMut {
+        match &mut self.mmap {
+            FileMemoryMap::Writable(mmap) => mmap,
+            FileMemoryMap::ReadOnly(_) => unreachable!("Read-only files have no writers."),
+        }
+    }
+
     /// Grows the file by the growth step.
     fn grow(&mut self) {
         self.flush();
@@ -159,7 +192,7 @@ impl<V: ValueSerde> MMapFile<V> {
     /// Flushes the mmap to the file.
     fn flush(&mut self) {
         trace!("Flushing mmap to file");
-        self.mmap.flush().expect("Failed to flush the mmap");
+        self.writable_mmap().flush().expect("Failed to flush the mmap");
         self.should_flush = false;
     }
 }
@@ -178,32 +211,78 @@ pub(crate) fn open_file<V: ValueSerde>(
     let mmap_file = MMapFile {
         config,
         file,
-        mmap,
+        mmap: FileMemoryMap::Writable(mmap),
         size: size.try_into().expect("size should fit in usize"),
         offset,
         should_flush: false,
         _value_type: PhantomData {},
     };
     let shared_mmap_file = Arc::new(Mutex::new(mmap_file));
+    let released_offset = Arc::new(AtomicUsize::new(0));
 
     let mut write_file_handler: FileHandler<V, RW> = FileHandler {
         memory_ptr: mmap_ptr,
         mmap_file: shared_mmap_file.clone(),
+        released_offset: released_offset.clone(),
+        read_only: false,
         _mode: PhantomData,
     };
     write_file_handler.grow_file_if_needed(0);
 
-    let read_file_handler: FileHandler<V, RO> =
-        FileHandler { memory_ptr: mmap_ptr, mmap_file: shared_mmap_file, _mode: PhantomData };
+    let read_file_handler: FileHandler<V, RO> = FileHandler {
+        memory_ptr: mmap_ptr,
+        mmap_file: shared_mmap_file,
+        released_offset,
+        read_only: false,
+        _mode: PhantomData,
+    };
 
     Ok((write_file_handler, read_file_handler))
 }
 
+/// Opens an existing memory mapped file for reading only. Another process may append to the file
+/// at the same time; the mapping covers the maximal size of the file, so objects appended after
+/// the file was opened are readable too.
+#[instrument(level = "debug", err)]
+pub(crate) fn open_file_read_only<V: ValueSerde>(
+    config: MmapFileConfig,
+    path: PathBuf,
+    offset: usize,
+) -> MmapFileResult<FileHandler<V, RO>> {
+    let file = OpenOptions::new().read(true).open(path)?;
+    let size = file.metadata()?.len();
+    let mmap = unsafe { MmapOptions::new().len(config.max_size).map(&file)? };
+    let mmap_ptr = mmap.as_ptr();
+    let mmap_file = MMapFile {
+        config,
+        file,
+        mmap: FileMemoryMap::ReadOnly(mmap),
+        size: size.try_into()?,
+        offset,
+        should_flush: false,
+        _value_type: PhantomData {},
+    };
+    Ok(FileHandler {
+        memory_ptr: mmap_ptr,
+        mmap_file: Arc::new(Mutex::new(mmap_file)),
+        // The disk space of the file is released by the process that writes it.
+        released_offset: Arc::new(AtomicUsize::new(0)),
+        read_only: true,
+        _mode: PhantomData,
+    })
+}
+
 /// A wrapper around `MMapFile` that provides both write and read interfaces.
 #[derive(Clone, Debug)]
 pub(crate) struct FileHandler<V: ValueSerde, Mode: TransactionKind> {
     memory_ptr: *const u8,
     mmap_file: Arc<Mutex<MMapFile<V>>>,
+    // The disk space before this offset was released; see `FileHandler::release_up_to`. Shared by
+    // the writer and the readers of the file, which fail to read the objects before it.
+    released_offset: Arc<AtomicUsize>,
+    // Whether the file was opened with `open_file_read_only`, in which case it may be grown by
+    // another process.
+    read_only: bool,
     _mode: PhantomData<Mode>,
 }
 
@@ -223,6 +302,39 @@ impl<V: ValueSerde> FileHandler<V, RW> {
     }
 }
 
+impl<V: ValueSerde> FileHandler<V, RW> {
+    /// Releases the disk space of the objects located before the given location, which must not
+    /// be read anymore; reading them afterwards (or while they are released) fails with
+    /// [`MMapFileError::LocationReleased`] instead of returning zeros. The locations of the other
+    /// objects are not changed. Only the whole pages before the location are released, and only on
+    /// Linux, on file systems that support punching holes in files.
+    pub(crate) fn release_up_to(&self, location: LocationInFile) -> MmapFileResult<()> {
+        self.release_up_to_offset(location.offset)
+    }
+
+    /// Releases the disk space before the given offset; see [`FileHandler::release_up_to`].
+    pub(crate) fn release_up_to_offset(&self, offset: usize) -> MmapFileResult<()> {
+        let mut mmap_file = self.mmap_file.lock().expect("Lock should not be poisoned");
+        let page_size = page_size::get();
+        let end = offset.min(mmap_file.offset) / page_size * page_size;
+        let start = self.released_offset.load(Ordering::SeqCst);
+        if end <= start {
+            return Ok(());
+        }
+        debug!("Releasing the file space from offset {start} to {end}.");
+        // The offset is advanced before the space is released, so that a reader that reads the
+        // released zeros sees it when it verifies the location after reading.
+        self.released_offset.store(end, Ordering::SeqCst);
+        #[cfg(target_os = "linux")]
+        {
+            // SAFETY: The readers don't use the objects they read from the released range.
+            let advice = unsafe { memmap2::Advice::remove() };
+            mmap_file.writable_mmap().advise_range(advice, start, end - start)?;
+        }
+        Ok(())
+    }
+}
+
 impl<V: ValueSerde + Debug> Writer<V> for FileHandler<V, RW> {
     fn append(&mut self, val: &V::Value) -> LocationInFile {
         trace!("Inserting object: {:?}", val);
@@ -233,11 +345,10 @@ impl<V: ValueSerde + Debug> Writer<V> for FileHandler<V, RW> {
             let mut mmap_file = self.mmap_file.lock().expect("Lock should not be poisoned");
             offset = mmap_file.offset;
             trace!("Inserting object at offset: {}", offset);
-            let mmap_slice = &mut mmap_file.mmap[offset..];
+            let mmap = mmap_file.writable_mmap();
+            let mmap_slice = &mut mmap[offset..];
             mmap_slice[..len].copy_from_slice(&serialized);
-            mmap_file
-                .mmap
-                .flush_async_range(offset, len)
+            mmap.flush_async_range(offset, len)
                 .expect("Failed to asynchronously flush the mmap after inserting");
             mmap_file.offset += len;
             mmap_file.should_flush = true;
@@ -259,6 +370,10 @@ impl<V: ValueSerde, Mode: TransactionKind> Reader<V> for FileHandler<V, Mode> {
     /// Returns an object from the file.
     fn get(&self, location: LocationInFile) -> MmapFileResult<Option<V::Value>> {
         trace!("Reading object at location: {:?}", location);
+        if self.read_only {
+            self.verify_location_in_file(location)?;
+        }
+        self.verify_location_not_released(location)?;
         let mut bytes = unsafe {
             std::slice::from_raw_parts(
                 self.memory_ptr.offset(location.offset.try_into()?),
@@ -266,7 +381,10 @@ impl<V: ValueSerde, Mode: TransactionKind> Reader<V> for FileHandler<V, Mode> {
             )
         };
         trace!("Deserializing object: {:?}", bytes);
-        Ok(V::deserialize(&mut bytes))
+        let value = V::deserialize(&mut bytes);
+        // The disk space of the object may have been released while it was read.
+        self.verify_location_not_released(location)?;
+        Ok(value)
     }
 }
 
@@ -280,6 +398,29 @@ pub struct MMapFileStats {
 }
 
 impl<V: ValueSerde, Mode: TransactionKind> FileHandler<V, Mode> {
+    // Verifies that an object can be read from the mapping of a read-only file, since reading
+    // beyond the end of the file crashes the process. The size of the file is refreshed if needed,
+    // since another process may have grown it.
+    fn verify_location_in_file(&self, location: LocationInFile) -> MmapFileResult<()> {
+        let mut mmap_file = self.mmap_file.lock().expect("Lock should not be poisoned");
+        if location.next_offset() > mmap_file.size {
+            mmap_file.size = mmap_file.file.metadata()?.len().try_into()?;
+            debug!("Refreshed the size of a read-only file to {}.", mmap_file.size);
+        }
+        if location.next_offset() > mmap_file.size.min(mmap_file.config.max_size) {
+            return Err(MMapFileError::LocationOutOfBounds { location, file_size: mmap_file.size });
+        }
+        Ok(())
+    }
+
+    // Verifies that the disk space of an object was not released, since its bytes read as zeros.
+    fn verify_location_not_released(&self, location: LocationInFile) -> MmapFileResult<()> {
+        if location.offset < self.released_offset.load(Ordering::SeqCst) {
+            return Err(MMapFileError::LocationReleased { location });
+        }
+        Ok(())
+    }
+
     pub fn stats(&self) -> MMapFileStats {
         let mmap_file = self.mmap_file.lock().expect("Lock should not be poisoned");
         MMapFileStats { size: mmap_file.size, offset: mmap_file.offset }
@@ -0,0 +1,351 @@
+//! Interface for pruning the history of a storage opened with [`StorageScope::Pruned`].
+//!
+//! Pruning deletes the data of the blocks that are older than the retention window: their headers,
+//! signatures, transactions, events and their keys filters, state diffs, and the historical values
+//! of the storage, nonces and class hashes of contracts that were overridden by a later block below
+//! the window.
+//! The latest value of each state entry below the window is kept, so the state at any state number
+//! inside the window (in particular, the latest state) remains complete. Declared classes are part
+//! of the latest state and are never pruned.
+//!
+//! The disk space of the pruned transactions, transaction outputs and state diffs in the memory
+//! mapped files is released one pruning step after they were pruned (or later, if a snapshot is
+//! being exported meanwhile), so that readers that started before they were pruned have a
+//! [`PRUNING_INTERVAL`] to finish reading them. Readers that read them afterwards fail with
+//! [`MMapFileError::LocationReleased`](crate::mmap_file::MMapFileError::LocationReleased) rather
+//! than reading zeros.
+//!
+//! A storage opened with [`StorageScope::Pruned`] is pruned by the [`StoragePruner`] that the node
+//! runs on a thread of its own: every [`PRUNING_INTERVAL`], it prunes a bounded batch of blocks.
+//! Other users of the storage (e.g., tools) don't prune it.
+
+#[cfg(test)]
+#[path = "pruning_test.rs"]
+mod pruning_test;
+
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::time::Duration;
+
+use starknet_api::block::BlockNumber;
+use starknet_api::state::ThinStateDiff;
+use starknet_api::transaction::TransactionOffsetInBlock;
+use tracing::{debug, error, info, warn};
+
+use crate::body::{BodyStorageReader, TransactionIndex};
+use crate::db::serialization::{Key, ValueSerde};
+use crate::db::table_types::{DbCursor, DbCursorTrait, Table, TableType};
+use crate::db::{DbTransaction, TableHandle, TransactionKind, RW};
+use crate::state::StateStorageReader;
+use crate::{MarkerKind, StorageError, StorageResult, StorageScope, StorageTxn, StorageWriter};
+
+/// The interval between consecutive pruning steps of a [`StoragePruner`].
+pub const PRUNING_INTERVAL: Duration = Duration::from_secs(10);
+/// The maximal number of blocks pruned in a single pruning step, so that it does not delay the
+/// writes of the storage for long.
+const MAX_BLOCKS_PER_PRUNING_TXN: u64 = 100;
+/// The minimal number of retained blocks. The blocks that may still be reverted (e.g., in a reorg)
+/// must not be pruned.
+pub const MIN_RETAINED_BLOCKS: u64 = 1024;
+
+/// Interface for reading data related to pruning.
+pub trait PruningStorageReader {
+    /// The pruning marker is the first block number whose data was not pruned.
+    fn get_pruning_marker(&self) -> StorageResult<BlockNumber>;
+}
+
+/// Interface for pruning the history of the storage.
+pub trait PruningStorageWriter
+where
+    Self: Sized,
+{
+    /// Prunes the data of the blocks from the pruning marker up to (excluding) the given block
+    /// number, and advances the pruning marker to it. Fails if the state diff of one of these
+    /// blocks was not written yet.
+    // To enforce that no commit happen after a failure, we consume and return Self on success.
+    fn prune_blocks(self, target_pruning_marker: BlockNumber) -> StorageResult<Self>;
+}
+
+impl<Mode: TransactionKind> PruningStorageReader for StorageTxn<'_, Mode> {
+    fn get_pruning_marker(&self) -> StorageResult<BlockNumber> {
+        let markers_table = self.open_table(&self.tables.markers)?;
+        Ok(markers_table.get(&self.txn, &MarkerKind::Pruned)?.unwrap_or_default())
+    }
+}
+
+impl PruningStorageWriter for StorageTxn<'_, RW> {
+    fn prune_blocks(self, target_pruning_marker: BlockNumber) -> StorageResult<Self> {
+        let state_marker = self.get_state_marker()?;
+        if target_pruning_marker > state_marker {
+            return Err(StorageError::InvalidPruningTarget { target_pruning_marker, state_marker });
+        }
+
+        let pruning_marker = self.get_pruning_marker()?;
+        for block_number in (pruning_marker.0..target_pruning_marker.0).map(BlockNumber) {
+            self.prune_header(block_number)?;
+            self.prune_body(block_number)?;
+            self.prune_state_diff(block_number)?;
+        }
+
+        if target_pruning_marker > pruning_marker {
+            let markers_table = self.open_table(&self.tables.markers)?;
+            markers_table.upsert(&self.txn, &MarkerKind::Pruned, &target_pruning_marker)?;
+            debug!("Pruned the blocks {pruning_marker} to {target_pruning_marker} (excluding).");
+        }
+        Ok(self)
+    }
+}
+
+impl StorageTxn<'_, RW> {
+    fn prune_header(&self, block_number: BlockNumber) -> StorageResult<()> {
+        let headers_table = self.open_table(&self.tables.headers)?;
+        let block_hash_to_number_table = self.open_table(&self.tables.block_hash_to_number)?;
+        let block_signatures_table = self.open_table(&self.tables.block_signatures)?;
+
+        // The starknet version table is kept, since it is indexed by the first block of each
+        // version.
+        let Some(header) = headers_table.get(&self.txn, &block_number)? else {
+            return Ok(());
+        };
+        headers_table.delete(&self.txn, &block_number)?;
+        block_hash_to_number_table.delete(&self.txn, &header.block_hash)?;
+        if block_signatures_table.get(&self.txn, &block_number)?.is_some() {
+            block_signatures_table.delete(&self.txn, &block_number)?;
+        }
+        Ok(())
+    }
+
+    fn prune_body(&self, block_number: BlockNumber) -> StorageResult<()> {
+        if block_number >= self.get_body_marker()? {
+            return Ok(());
+        }
+        let transaction_metadata_table = self.open_table(&self.tables.transaction_metadata)?;
+        let transaction_hash_to_idx_table =
+            self.open_table(&self.tables.transaction_hash_to_idx)?;
+        let events_table = self.open_table(&self.tables.events)?;
+        let event_keys_filters_table = self.open_table(&self.tables.event_keys_filters)?;
+
+        event_keys_filters_table.delete(&self.txn, &block_number)?;
+        let (Some(transaction_hashes), Some(transaction_outputs)) = (
+            self.get_block_transaction_hashes(block_number)?,
+            self.get_block_transaction_outputs(block_number)?,
+        ) else {
+            return Ok(());
+        };
+        for (offset, (tx_hash, tx_output)) in
+            transaction_hashes.iter().zip(transaction_outputs.iter()).enumerate()
+        {
+            let tx_index = TransactionIndex(block_number, TransactionOffsetInBlock(offset));
+            for event in tx_output.events().iter() {
+                events_table.delete(&self.txn, &(event.from_address, tx_index))?;
+            }
+            transaction_hash_to_idx_table.delete(&self.txn, tx_hash)?;
+            transaction_metadata_table.delete(&self.txn, &tx_index)?;
+        }
+        Ok(())
+    }
+
+    fn prune_state_diff(&self, block_number: BlockNumber) -> StorageResult<()> {
+        let state_diffs_table = self.open_table(&self.tables.state_diffs)?;
+        let deployed_contracts_table = self.open_table(&self.tables.deployed_contracts)?;
+        let nonces_table = self.open_table(&self.tables.nonces)?;
+        let storage_table = self.open_table(&self.tables.contract_storage)?;
+
+        let Some(ThinStateDiff { deployed_contracts, storage_diffs, nonces, .. }) =
+            self.get_state_diff(block_number)?
+        else {
+            return Ok(());
+        };
+
+        // Nonces are also written for newly deployed contracts.
+        let nonce_writers: HashSet<_> = nonces.keys().chain(deployed_contracts.keys()).collect();
+        for contract_address in nonce_writers {
+            prune_overridden_value(&self.txn, &nonces_table, *contract_address, block_number)?;
+        }
+        for contract_address in deployed_contracts.keys() {
+            prune_overridden_value(
+                &self.txn,
+                &deployed_contracts_table,
+                *contract_address,
+                block_number,
+            )?;
+        }
+        for (contract_address, storage_entries) in &storage_diffs {
+            for storage_key in storage_entries.keys() {
+                prune_overridden_value(
+                    &self.txn,
+                    &storage_table,
+                    (*contract_address, *storage_key),
+                    block_number,
+                )?;
+            }
+        }
+
+        state_diffs_table.delete(&self.txn, &block_number)?;
+        Ok(())
+    }
+}
+
+// Deletes the previous write of a state entry that was written at the given block, which is below
+// the pruning target. The previous write was overridden by the given block and is not the value of
+// the entry at any state inside the retention window. The write at the given block is deleted in
+// turn when the next write of the entry is pruned, so only the latest write of the entry below the
+// target is kept, even if its next write is pruned by a later batch.
+fn prune_overridden_value<'env, E, V, T>(
+    txn: &'env DbTransaction<'env, RW>,
+    table: &'env TableHandle<'env, (E, BlockNumber), V, T>,
+    state_entry: E,
+    block_number: BlockNumber,
+) -> StorageResult<()>
+where
+    E: Copy + Debug + PartialEq,
+    (E, BlockNumber): Key + Debug,
+    V: ValueSerde + Debug,
+    T: TableType,
+    TableHandle<'env, (E, BlockNumber), V, T>:
+        Table<'env, Key = (E, BlockNumber), Value = V, TableVariant = T>,
+    for<'txn> DbCursor<'txn, RW, (E, BlockNumber), V, T>:
+        DbCursorTrait<Key = (E, BlockNumber), Value = V>,
+{
+    let mut cursor = table.cursor(txn)?;
+    let Some((write_key, _)) = cursor.lower_bound(&(state_entry, block_number))? else {
+        return Ok(());
+    };
+    if write_key != (state_entry, block_number) {
+        return Ok(());
+    }
+    let previous_write = cursor.prev()?;
+    if let Some(((previous_state_entry, previous_block_number), _)) = previous_write {
+        if previous_state_entry == state_entry {
+            table.delete(txn, &(state_entry, previous_block_number))?;
+        }
+    }
+    Ok(())
+}
+
+/// Prunes a storage opened with [`StorageScope::Pruned`]; see [`StorageWriter::pruner`]. It writes
+/// the storage concurrently with the storage writer, and the database serializes their write
+/// transactions, so a pruning step delays the writes of the storage writer by at most one bounded
+/// transaction.
+pub struct StoragePruner {
+    writer: StorageWriter,
+    retained_blocks: u64,
+    // The pruning marker after the previous pruning step. The file space of the blocks before it
+    // is released in the next step.
+    previous_pruning_marker: Option<BlockNumber>,
+}
+
+impl StorageWriter {
+    /// Returns a pruner of the storage, if it was opened with [`StorageScope::Pruned`]; otherwise,
+    /// returns `None`. Should only be called by the node that syncs the storage, which should run
+    /// the pruner on a thread of its own.
+    pub fn pruner(&self) -> Option<StoragePruner> {
+        let StorageScope::Pruned { retained_blocks } = self.scope else {
+            return None;
+        };
+        Some(StoragePruner {
+            writer: self.another_writer(),
+            retained_blocks,
+            previous_pruning_marker: None,
+        })
+    }
+}
+
+impl StoragePruner {
+    /// Prunes the storage every [`PRUNING_INTERVAL`]. Blocks the current thread and never returns.
+    pub fn run(mut self) -> ! {
+        info!("Running the storage pruner, retaining the last {} blocks.", self.retained_blocks);
+        loop {
+            self.prune_step();
+            std::thread::sleep(PRUNING_INTERVAL);
+        }
+    }
+
+    // Releases the file space of the blocks pruned by the previous step, and prunes a bounded batch
+    // of blocks. Errors are logged, and the step is retried after the pruning interval.
+    pub(crate) fn prune_step(&mut self) {
+        if let Some(previous_pruning_marker) = self.previous_pruning_marker {
+            if let Err(err) = release_pruned_file_space(&mut self.writer, previous_pruning_marker) {
+                warn!("Failed to release the file space of the pruned blocks: {err}.");
+            }
+        }
+        match prune_batch(&mut self.writer, self.retained_blocks) {
+            Ok(pruning_marker) => self.previous_pruning_marker = Some(pruning_marker),
+            Err(err) => error!("Failed to prune the storage: {err}."),
+        }
+    }
+}
+
+/// Prunes the blocks that are older than the last `retained_blocks` blocks of the state, in
+/// transactions of a bounded number of blocks, regardless of whether pruning is enabled. Returns
+/// the new pruning marker.
+pub fn prune_storage(
+    writer: &mut StorageWriter,
+    retained_blocks: u64,
+) -> StorageResult<BlockNumber> {
+    let mut pruning_marker = writer.begin_rw_txn()?.get_pruning_marker()?;
+    loop {
+        let new_pruning_marker = prune_batch(writer, retained_blocks)?;
+        if new_pruning_marker == pruning_marker {
+            return Ok(pruning_marker);
+        }
+        pruning_marker = new_pruning_marker;
+    }
+}
+
+// Prunes up to `MAX_BLOCKS_PER_PRUNING_TXN` of the blocks that are older than the last
+// `retained_blocks` blocks of the state, in a single transaction. Returns the new pruning marker.
+fn prune_batch(writer: &mut StorageWriter, retained_blocks: u64) -> StorageResult<BlockNumber> {
+    let txn = writer.begin_rw_txn()?;
+    let pruning_marker = txn.get_pruning_marker()?;
+    let target_pruning_marker =
+        BlockNumber(txn.get_state_marker()?.0.saturating_sub(retained_blocks));
+    if target_pruning_marker <= pruning_marker {
+        return Ok(pruning_marker);
+    }
+    let batch_end = BlockNumber(
+        target_pruning_marker.0.min(pruning_marker.0.saturating_add(MAX_BLOCKS_PER_PRUNING_TXN)),
+    );
+    txn.prune_blocks(batch_end)?.commit()?;
+    Ok(batch_end)
+}
+
+// Releases the file space of the transactions, transaction outputs and state diffs that are
+// located before those of the given pruning marker. The data of a block is appended to the files
+// after the data of the blocks before it, so that space is only used by pruned (or reverted) data.
+// The space is not released while a snapshot is exported, and is released by a later call instead.
+pub(crate) fn release_pruned_file_space(
+    writer: &mut StorageWriter,
+    pruning_marker: BlockNumber,
+) -> StorageResult<()> {
+    let Some(_file_space_release_lock) = writer.try_lock_file_space_release()? else {
+        debug!("Holding off the release of the file space of the pruned blocks during an export.");
+        return Ok(());
+    };
+    let txn = writer.begin_rw_txn()?;
+    let state_diffs_table = txn.open_table(&txn.tables.state_diffs)?;
+    if let Some(location) = state_diffs_table.get(&txn.txn, &pruning_marker)? {
+        txn.file_handlers.thin_state_diff.release_up_to(location)?;
+    }
+
+    let transaction_metadata_table = txn.open_table(&txn.tables.transaction_metadata)?;
+    let mut cursor = transaction_metadata_table.cursor(&txn.txn)?;
+    let first_retained_transaction =
+        cursor.lower_bound(&TransactionIndex(pruning_marker, TransactionOffsetInBlock(0)))?;
+    if let Some((_, transaction_metadata)) = first_retained_transaction {
+        txn.file_handlers.transaction.release_up_to(transaction_metadata.tx_location)?;
+        txn.file_handlers
+            .transaction_output
+            .release_up_to(transaction_metadata.tx_output_location)?;
+    }
+    Ok(())
+}
+
+// Returns whether the data of the given block was already pruned, and hence should not be written.
+pub(crate) fn is_pruned<Mode: TransactionKind>(
+    txn: &StorageTxn<'_, Mode>,
+    block_number: BlockNumber,
+) -> StorageResult<bool> {
+    Ok(matches!(txn.scope, StorageScope::Pruned { .. })
+        && block_number < txn.get_pruning_marker()?)
+}
@@ -0,0 +1,287 @@
+use apollo_test_utils::get_test_block;
+use assert_matches::assert_matches;
+use indexmap::indexmap;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{BlockBody, BlockHash, BlockHeader, BlockHeaderWithoutHash, BlockNumber};
+use starknet_api::core::{ClassHash, Nonce};
+use starknet_api::state::{StateNumber, ThinStateDiff};
+use starknet_api::transaction::TransactionOffsetInBlock;
+use starknet_api::{class_hash, contract_address, felt, storage_key};
+use validator::Validate;
+
+use crate::body::{BodyStorageReader, BodyStorageWriter, TransactionIndex};
+use crate::db::table_types::{DbCursorTrait, Table};
+use crate::header::{HeaderStorageReader, HeaderStorageWriter};
+use crate::pruning::{
+    prune_batch,
+    prune_storage,
+    PruningStorageReader,
+    PruningStorageWriter,
+    MIN_RETAINED_BLOCKS,
+};
+use crate::state::{StateStorageReader, StateStorageWriter};
+use crate::test_utils::{get_test_storage, get_test_storage_by_scope};
+use crate::{StorageConfig, StorageError, StorageScope, StorageWriter};
+
+const N_BLOCKS: u64 = 4;
+
+// Writes blocks in which the same storage entry, nonce and class hash of a contract are updated,
+// and another contract is written only in the first block.
+fn write_blocks(writer: &mut StorageWriter) {
+    let contract = contract_address!("0x1");
+    let untouched_contract = contract_address!("0x2");
+    let key = storage_key!("0x10");
+    // A single transaction per block, with distinct hashes.
+    let body = get_test_block(usize::try_from(N_BLOCKS).unwrap(), Some(1), None, None).body;
+
+    let mut txn = writer.begin_rw_txn().unwrap();
+    for (i, ((transaction, transaction_output), transaction_hash)) in (0..N_BLOCKS).zip(
+        body.transactions.into_iter().zip(body.transaction_outputs).zip(body.transaction_hashes),
+    ) {
+        let block_number = BlockNumber(i);
+        let header = BlockHeader {
+            block_hash: BlockHash(felt!(i + 1)),
+            block_header_without_hash: BlockHeaderWithoutHash {
+                block_number,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut state_diff = ThinStateDiff {
+            deployed_contracts: indexmap! { contract => ClassHash(felt!(i)) },
+            storage_diffs: indexmap! { contract => indexmap! { key => felt!(i) } },
+            nonces: indexmap! { contract => Nonce(felt!(i)) },
+            ..Default::default()
+        };
+        if i == 0 {
+            state_diff.deployed_contracts.insert(untouched_contract, class_hash!("0x100"));
+            state_diff.storage_diffs.insert(untouched_contract, indexmap! { key => felt!("0x7") });
+        }
+        txn = txn
+            .append_header(block_number, &header)
+            .unwrap()
+            .append_body(
+                block_number,
+                BlockBody {
+                    transactions: vec![transaction],
+                    transaction_outputs: vec![transaction_output],
+                    transaction_hashes: vec![transaction_hash],
+                },
+            )
+            .unwrap()
+            .append_state_diff(block_number, state_diff)
+            .unwrap();
+    }
+    txn.commit().unwrap();
+}
+
+#[test]
+fn prune_blocks() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    write_blocks(&mut writer);
+    let tx_hash_of_pruned_block = reader
+        .begin_ro_txn()
+        .unwrap()
+        .get_block_transaction_hashes(BlockNumber(0))
+        .unwrap()
+        .unwrap()[0];
+
+    writer.begin_rw_txn().unwrap().prune_blocks(BlockNumber(2)).unwrap().commit().unwrap();
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_pruning_marker().unwrap(), BlockNumber(2));
+
+    // The data of the pruned blocks was deleted.
+    for block_number in [BlockNumber(0), BlockNumber(1)] {
+        assert_eq!(txn.get_block_header(block_number).unwrap(), None);
+        assert_eq!(
+            txn.get_block_number_by_hash(&BlockHash(felt!(block_number.0 + 1))).unwrap(),
+            None
+        );
+        assert_eq!(
+            txn.get_transaction(TransactionIndex(block_number, TransactionOffsetInBlock(0)))
+                .unwrap(),
+            None
+        );
+        assert_eq!(txn.get_state_diff(block_number).unwrap(), None);
+    }
+    assert_eq!(txn.get_transaction_idx_by_hash(&tx_hash_of_pruned_block).unwrap(), None);
+
+    // The data of the retained blocks was kept.
+    for block_number in [BlockNumber(2), BlockNumber(3)] {
+        assert!(txn.get_block_header(block_number).unwrap().is_some());
+        assert!(txn.get_state_diff(block_number).unwrap().is_some());
+        assert!(txn
+            .get_transaction(TransactionIndex(block_number, TransactionOffsetInBlock(0)))
+            .unwrap()
+            .is_some());
+    }
+
+    // The states inside the retention window are complete, including the values written before it.
+    let state_reader = txn.get_state_reader().unwrap();
+    let contract = contract_address!("0x1");
+    let untouched_contract = contract_address!("0x2");
+    let key = storage_key!("0x10");
+    for i in 2..=N_BLOCKS {
+        let state_number = StateNumber(BlockNumber(i));
+        let last_block = i - 1;
+        assert_eq!(
+            state_reader.get_storage_at(state_number, &contract, &key).unwrap(),
+            felt!(last_block)
+        );
+        assert_eq!(
+            state_reader.get_nonce_at(state_number, &contract).unwrap(),
+            Some(Nonce(felt!(last_block)))
+        );
+        assert_eq!(
+            state_reader.get_class_hash_at(state_number, &contract).unwrap(),
+            Some(ClassHash(felt!(last_block)))
+        );
+        assert_eq!(
+            state_reader.get_storage_at(state_number, &untouched_contract, &key).unwrap(),
+            felt!("0x7")
+        );
+        assert_eq!(
+            state_reader.get_class_hash_at(state_number, &untouched_contract).unwrap(),
+            Some(class_hash!("0x100"))
+        );
+    }
+
+    // The states before the retention window are unavailable.
+    assert_matches!(
+        state_reader.get_storage_at(StateNumber(BlockNumber(1)), &contract, &key),
+        Err(StorageError::StatePruned { pruning_marker: BlockNumber(2), .. })
+    );
+}
+
+#[test]
+fn prune_blocks_beyond_state_marker_fails() {
+    let ((_reader, mut writer), _temp_dir) = get_test_storage();
+    write_blocks(&mut writer);
+
+    assert_matches!(
+        writer.begin_rw_txn().unwrap().prune_blocks(BlockNumber(N_BLOCKS + 1)).err(),
+        Some(StorageError::InvalidPruningTarget { state_marker: BlockNumber(N_BLOCKS), .. })
+    );
+}
+
+#[test]
+fn prune_storage_retains_last_blocks() {
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    write_blocks(&mut writer);
+
+    assert_eq!(prune_storage(&mut writer, 1).unwrap(), BlockNumber(N_BLOCKS - 1));
+    // Pruning again has no effect.
+    assert_eq!(prune_storage(&mut writer, 1).unwrap(), BlockNumber(N_BLOCKS - 1));
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_pruning_marker().unwrap(), BlockNumber(N_BLOCKS - 1));
+    assert!(txn.get_block_header(BlockNumber(N_BLOCKS - 1)).unwrap().is_some());
+    assert_eq!(txn.get_block_header(BlockNumber(N_BLOCKS - 2)).unwrap(), None);
+}
+
+#[test]
+fn pruning_batches_keep_only_the_latest_write_below_the_marker() {
+    const N_WRITTEN_BLOCKS: u64 = 250;
+    let ((reader, mut writer), _temp_dir) = get_test_storage();
+    let contract = contract_address!("0x1");
+    let key = storage_key!("0x10");
+    let mut txn = writer.begin_rw_txn().unwrap();
+    for i in 0..N_WRITTEN_BLOCKS {
+        let state_diff = ThinStateDiff {
+            storage_diffs: indexmap! { contract => indexmap! { key => felt!(i) } },
+            ..Default::default()
+        };
+        txn = txn.append_state_diff(BlockNumber(i), state_diff).unwrap();
+    }
+    txn.commit().unwrap();
+
+    // Each batch prunes up to `MAX_BLOCKS_PER_PRUNING_TXN` blocks.
+    let mut pruning_marker = BlockNumber(0);
+    while pruning_marker < BlockNumber(N_WRITTEN_BLOCKS - 1) {
+        pruning_marker = prune_batch(&mut writer, 1).unwrap();
+    }
+
+    let txn = reader.begin_ro_txn().unwrap();
+    let storage_table = txn.open_table(&txn.tables.contract_storage).unwrap();
+    let mut cursor = storage_table.cursor(&txn.txn).unwrap();
+    let mut written_blocks = Vec::new();
+    let mut current = cursor.lower_bound(&((contract, key), BlockNumber(0))).unwrap();
+    while let Some((((write_contract, write_key), block_number), _)) = current {
+        if (write_contract, write_key) != (contract, key) {
+            break;
+        }
+        written_blocks.push(block_number);
+        current = cursor.next().unwrap();
+    }
+    assert_eq!(written_blocks, vec![BlockNumber(N_WRITTEN_BLOCKS - 2), pruning_marker]);
+    assert_eq!(
+        txn.get_state_reader()
+            .unwrap()
+            .get_storage_at(StateNumber(pruning_marker), &contract, &key)
+            .unwrap(),
+        felt!(N_WRITTEN_BLOCKS - 2)
+    );
+}
+
+#[test]
+fn pruned_body_is_not_written() {
+    let ((reader, mut writer), _temp_dir) =
+        get_test_storage_by_scope(StorageScope::Pruned { retained_blocks: 1 });
+    let mut txn = writer.begin_rw_txn().unwrap();
+    for i in 0..N_BLOCKS {
+        txn = txn.append_state_diff(BlockNumber(i), ThinStateDiff::default()).unwrap();
+    }
+    txn.commit().unwrap();
+    prune_storage(&mut writer, 1).unwrap();
+
+    let body = get_test_block(1, None, None, None).body;
+    let tx_hash = body.transaction_hashes[0];
+    writer.begin_rw_txn().unwrap().append_body(BlockNumber(0), body).unwrap().commit().unwrap();
+
+    let txn = reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_body_marker().unwrap(), BlockNumber(1));
+    assert_eq!(txn.get_transaction_idx_by_hash(&tx_hash).unwrap(), None);
+}
+
+#[test]
+fn pruner_prunes_the_storage() {
+    let ((reader, mut writer), _temp_dir) =
+        get_test_storage_by_scope(StorageScope::Pruned { retained_blocks: 1 });
+    write_blocks(&mut writer);
+    let mut pruner = writer.pruner().unwrap();
+    assert_eq!(reader.begin_ro_txn().unwrap().get_pruning_marker().unwrap(), BlockNumber(0));
+
+    pruner.prune_step();
+    assert_eq!(
+        reader.begin_ro_txn().unwrap().get_pruning_marker().unwrap(),
+        BlockNumber(N_BLOCKS - 1)
+    );
+
+    // The writer keeps writing while the storage is pruned.
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_state_diff(BlockNumber(N_BLOCKS), ThinStateDiff::default())
+        .unwrap()
+        .commit()
+        .unwrap();
+    pruner.prune_step();
+    assert_eq!(reader.begin_ro_txn().unwrap().get_pruning_marker().unwrap(), BlockNumber(N_BLOCKS));
+}
+
+#[test]
+fn full_archive_has_no_pruner() {
+    let ((_reader, writer), _temp_dir) = get_test_storage();
+    assert!(writer.pruner().is_none());
+}
+
+#[test]
+fn retained_blocks_below_minimum_are_invalid() {
+    let config = |retained_blocks| StorageConfig {
+        scope: StorageScope::Pruned { retained_blocks },
+        ..Default::default()
+    };
+    assert!(config(MIN_RETAINED_BLOCKS - 1).validate().is_err());
+    assert_eq!(config(MIN_RETAINED_BLOCKS).validate(), Ok(()));
+}
@@ -0,0 +1,155 @@
+//! Read-only access to a storage that is written by another process.
+//!
+//! [`open_storage_read_only`] attaches to the MDBX environment and the mmap files of an existing
+//! storage without taking ownership of them, so it can be used, e.g., by RPC replicas or analytics
+//! jobs that run next to a node on the same host. Each transaction of the returned
+//! [`StorageReader`] sees the data that was committed by the writing process when it began.
+//!
+//! The writing process grows the database and the mmap files while they are read:
+//! - MDBX remaps the database when it grows.
+//! - The mmap files are mapped up to their maximal size, so data that is appended to them after
+//!   they were opened is readable. Reading from a location beyond the end of a file fails instead
+//!   of crashing the process.
+//! - Compression dictionaries that are added by the writing process are reloaded when a transaction
+//!   begins.
+//!
+//! Migrations don't run in read-only mode, so the storage must have the crate's storage version,
+//! i.e., it must have been opened by a writer of the same version.
+//!
+//! MDBX doesn't support opening a storage twice in the same process, so the storage mustn't be
+//! open in the process that opens it read-only.
+//!
+//! # Example
+//! ```
+//! use apollo_storage::header::HeaderStorageReader;
+//! use apollo_storage::open_storage;
+//! use apollo_storage::read_only::open_storage_read_only;
+//! # use apollo_storage::{db::DbConfig, StorageConfig};
+//! # use starknet_api::core::ChainId;
+//!
+//! # let dir_handle = tempfile::tempdir().unwrap();
+//! # let db_config = DbConfig {
+//! #     path_prefix: dir_handle.path().to_path_buf(),
+//! #     chain_id: ChainId::Mainnet,
+//! #     enforce_file_exists: false,
+//! #     min_size: 1 << 20,    // 1MB
+//! #     max_size: 1 << 35,    // 32GB
+//! #     growth_step: 1 << 26, // 64MB
+//! # };
+//! # let storage_config = StorageConfig { db_config, ..Default::default() };
+//! // Done by another process, e.g., a node.
+//! # drop(open_storage(storage_config.clone())?);
+//!
+//! let reader = open_storage_read_only(storage_config)?;
+//! let header_marker = reader.begin_ro_txn()?.get_header_marker()?;
+//! # Ok::<(), apollo_storage::StorageError>(())
+//! ```
+
+#[cfg(test)]
+#[path = "read_only_test.rs"]
+mod read_only_test;
+
+use std::sync::Arc;
+
+use tracing::info;
+
+use crate::compression_dictionaries::load_compression_dictionaries;
+use crate::compression_utils::SharedCompressionDictionaries;
+use crate::db::open_env_read_only;
+use crate::db::table_types::Table;
+use crate::mmap_file::open_file_read_only;
+use crate::version::StorageVersionError;
+use crate::{
+    get_storage_version,
+    open_tables,
+    verify_storage_version,
+    FileHandlers,
+    OffsetKind,
+    StorageConfig,
+    StorageError,
+    StorageReader,
+    StorageResult,
+    StorageScope,
+    StorageVersion,
+};
+
+/// Opens an existing storage for reading only, while another process may be writing to it, and
+/// returns a [`StorageReader`] for it.
+///
+/// # Errors
+/// Returns an error if the storage doesn't exist, if it lacks tables or files, or if its version or
+/// scope doesn't match the crate's version and the given scope.
+pub fn open_storage_read_only(storage_config: StorageConfig) -> StorageResult<StorageReader> {
+    info!("Opening storage in read-only mode: {}", storage_config.db_config.path_prefix.display());
+    let mut db_reader = open_env_read_only(&storage_config.db_config)?;
+    let tables = Arc::new(open_tables(&mut db_reader)?);
+
+    let txn = db_reader.begin_ro_txn()?;
+    let compression_dictionaries = SharedCompressionDictionaries::new(
+        load_compression_dictionaries(&txn, &tables.compression_dictionaries)?,
+    );
+    let file_offsets_table = txn.open_table(&tables.file_offsets)?;
+    let db_path = storage_config.db_config.path();
+    let mmap_file_config = storage_config.mmap_file_config;
+    let offset = |offset_kind| -> StorageResult<usize> {
+        Ok(file_offsets_table.get(&txn, &offset_kind)?.unwrap_or_default())
+    };
+    let file_readers = FileHandlers {
+        thin_state_diff: open_file_read_only(
+            mmap_file_config.clone(),
+            db_path.join(OffsetKind::ThinStateDiff.file_name()),
+            offset(OffsetKind::ThinStateDiff)?,
+        )?,
+        contract_class: open_file_read_only(
+            mmap_file_config.clone(),
+            db_path.join(OffsetKind::ContractClass.file_name()),
+            offset(OffsetKind::ContractClass)?,
+        )?,
+        casm: open_file_read_only(
+            mmap_file_config.clone(),
+            db_path.join(OffsetKind::Casm.file_name()),
+            offset(OffsetKind::Casm)?,
+        )?,
+        deprecated_contract_class: open_file_read_only(
+            mmap_file_config.clone(),
+            db_path.join(OffsetKind::DeprecatedContractClass.file_name()),
+            offset(OffsetKind::DeprecatedContractClass)?,
+        )?,
+        transaction_output: open_file_read_only(
+            mmap_file_config.clone(),
+            db_path.join(OffsetKind::TransactionOutput.file_name()),
+            offset(OffsetKind::TransactionOutput)?,
+        )?,
+        transaction: open_file_read_only(
+            mmap_file_config,
+            db_path.join(OffsetKind::Transaction.file_name()),
+            offset(OffsetKind::Transaction)?,
+        )?,
+        compression_dictionaries,
+    };
+    drop(txn);
+
+    let reader = StorageReader {
+        db_reader,
+        tables,
+        scope: storage_config.scope,
+        file_readers,
+        read_only: true,
+    };
+    match get_storage_version(reader.clone())? {
+        None => {
+            return Err(StorageError::StorageVersionInconsistency(
+                StorageVersionError::MissingStorageVersion,
+            ));
+        }
+        // The blocks of a state-only storage can't be read.
+        Some(StorageVersion::StateOnly(_)) if storage_config.scope != StorageScope::StateOnly => {
+            return Err(StorageError::StorageVersionInconsistency(
+                StorageVersionError::InconsistentStorageScope,
+            ));
+        }
+        Some(_) => {}
+    }
+    verify_storage_version(reader.clone())?;
+    Ok(reader)
+}
@@ -0,0 +1,138 @@
+use std::env;
+use std::fs::{self, metadata};
+use std::process::Command;
+
+use apollo_test_utils::get_test_block;
+use assert_matches::assert_matches;
+use cairo_lang_starknet_classes::casm_contract_class::CasmContractClass;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{BlockBody, BlockNumber};
+use starknet_api::core::ClassHash;
+use starknet_api::felt;
+use starknet_api::test_utils::read_json_file;
+use starknet_api::transaction::TransactionHash;
+
+use crate::body::{BodyStorageReader, BodyStorageWriter};
+use crate::compiled_class::{CasmStorageReader, CasmStorageWriter};
+use crate::compression_utils::CompressionDictionaryKind;
+use crate::db::DbError;
+use crate::mmap_file::MmapFileConfig;
+use crate::read_only::open_storage_read_only;
+use crate::serialization::serializers::casm_to_compress;
+use crate::test_utils::get_test_config;
+use crate::version::StorageVersionError;
+use crate::{open_storage, OffsetKind, StorageConfig, StorageError, StorageScope};
+
+// The environment variable with the storage config of the writer process of read_while_writing.
+const WRITER_CONFIG_ENV_VAR: &str = "READ_ONLY_TEST_WRITER_CONFIG";
+// The file, in the directory of the storage, to which the writer process writes the bodies it
+// appended.
+const BODIES_FILE_NAME: &str = "bodies.json";
+
+fn class_hash() -> ClassHash {
+    ClassHash(felt!("0x1"))
+}
+
+fn casm() -> CasmContractClass {
+    serde_json::from_value(read_json_file("compiled_class.json")).unwrap()
+}
+
+// MDBX doesn't support opening a storage twice in the same process, so the writes that
+// read_while_writing reads are done by a child process that runs this test.
+#[test]
+#[ignore = "Run by read_while_writing in a child process."]
+fn write_for_read_only_reader() {
+    let Ok(config) = env::var(WRITER_CONFIG_ENV_VAR) else {
+        return;
+    };
+    let config: StorageConfig = serde_json::from_str(&config).unwrap();
+    let (_reader, mut writer) = open_storage(config.clone()).unwrap();
+
+    // Write bodies until the transactions file grows.
+    let transactions_file = config.db_config.path().join(OffsetKind::Transaction.file_name());
+    let initial_file_size = metadata(&transactions_file).unwrap().len();
+    let mut bodies = Vec::new();
+    while metadata(&transactions_file).unwrap().len() == initial_file_size {
+        assert!(bodies.len() < 10000, "The transactions file didn't grow.");
+        let block_number = BlockNumber(bodies.len().try_into().unwrap());
+        let mut body = get_test_block(10, None, None, None).body;
+        for (i, tx_hash) in body.transaction_hashes.iter_mut().enumerate() {
+            let tx_hash_value = block_number.0 * 100 + u64::try_from(i).unwrap();
+            *tx_hash = TransactionHash(felt!(tx_hash_value));
+        }
+        writer
+            .begin_rw_txn()
+            .unwrap()
+            .append_body(block_number, body.clone())
+            .unwrap()
+            .commit()
+            .unwrap();
+        bodies.push(body);
+    }
+    fs::write(config.db_config.path().join(BODIES_FILE_NAME), serde_json::to_vec(&bodies).unwrap())
+        .unwrap();
+
+    // Compress a class with a dictionary that is added after the reader opened the storage.
+    let casm = casm();
+    writer
+        .add_compression_dictionary(
+            CompressionDictionaryKind::Casm,
+            casm_to_compress(&casm).unwrap(),
+        )
+        .unwrap();
+    writer.begin_rw_txn().unwrap().append_casm(&class_hash(), &casm).unwrap().commit().unwrap();
+}
+
+#[test]
+fn read_while_writing() {
+    let (mut config, _temp_dir) = get_test_config(None);
+    // A small growth step, so that the files grow while they are read.
+    config.mmap_file_config =
+        MmapFileConfig { max_size: 1 << 24, growth_step: 1 << 16, max_object_size: 1 << 14 };
+    drop(open_storage(config.clone()).unwrap());
+    let read_only_reader = open_storage_read_only(config.clone()).unwrap();
+    assert!(read_only_reader.is_read_only());
+    assert_eq!(read_only_reader.begin_ro_txn().unwrap().get_body_marker().unwrap(), BlockNumber(0));
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "read_only::read_only_test::write_for_read_only_reader", "--ignored"])
+        .env(WRITER_CONFIG_ENV_VAR, serde_json::to_string(&config).unwrap())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    // The bodies beyond the initial size of the transactions file are readable.
+    let bodies: Vec<BlockBody> =
+        serde_json::from_slice(&fs::read(config.db_config.path().join(BODIES_FILE_NAME)).unwrap())
+            .unwrap();
+    let txn = read_only_reader.begin_ro_txn().unwrap();
+    assert_eq!(txn.get_body_marker().unwrap(), BlockNumber(bodies.len().try_into().unwrap()));
+    for (block_number, body) in bodies.iter().enumerate() {
+        let block_number = BlockNumber(block_number.try_into().unwrap());
+        assert_eq!(txn.get_block_transactions(block_number).unwrap().unwrap(), body.transactions);
+    }
+
+    // Classes that are compressed with a dictionary that was added after the storage was opened
+    // are readable.
+    assert_eq!(txn.get_casm(&class_hash()).unwrap().unwrap(), casm());
+}
+
+#[test]
+fn open_mismatching_storage() {
+    let (config, _temp_dir) = get_test_config(None);
+    assert_matches!(
+        open_storage_read_only(config.clone()).err(),
+        Some(StorageError::InnerError(DbError::FileDoesNotExist(_)))
+    );
+
+    let mut state_only_config = config.clone();
+    state_only_config.scope = StorageScope::StateOnly;
+    drop(open_storage(state_only_config.clone()).unwrap());
+    open_storage_read_only(state_only_config).unwrap();
+    assert_matches!(
+        open_storage_read_only(config).err(),
+        Some(StorageError::StorageVersionInconsistency(
+            StorageVersionError::InconsistentStorageScope
+        ))
+    );
+}
@@ -37,6 +37,7 @@ use starknet_api::core::{
     EthAddress,
     EventCommitment,
     GlobalRoot,
+    L1TransactionHash,
     Nonce,
     PatriciaKey,
     ReceiptCommitment,
@@ -126,14 +127,19 @@ use starknet_api::transaction::{
 use starknet_types_core::felt::Felt;
 use tracing::warn;
 
+use crate::body::event_keys_filter::EventKeysFilter;
 use crate::body::events::EventIndex;
 use crate::body::TransactionIndex;
 use crate::compression_utils::{
     compress,
+    compress_with_dictionary,
     decompress,
     decompress_from_reader,
     serialize_and_compress,
+    CompressionDictionaryId,
+    CompressionDictionaryKind,
     IsCompressed,
+    StoredCompressionDictionary,
 };
 use crate::db::serialization::{StorageSerde, StorageSerdeError};
 use crate::db::table_types::NoValue;
@@ -188,6 +194,12 @@ auto_storage_serde! {
     pub struct Calldata(pub Arc<Vec<Felt>>);
     pub struct CompiledClassHash(pub StarkHash);
     pub struct ClassHash(pub StarkHash);
+    pub struct CompressionDictionaryId(pub u32);
+    pub enum CompressionDictionaryKind {
+        SierraProgram = 0,
+        Casm = 1,
+        DeprecatedContractClass = 2,
+    }
     pub struct ContractAddressSalt(pub StarkHash);
     pub enum ContractClassAbiEntry {
         Event(EventAbiEntry) = 0,
@@ -283,6 +295,7 @@ auto_storage_serde! {
     pub enum EventType {
         Event = 0,
     }
+    struct EventKeysFilter(pub Vec<u8>);
     pub struct Fee(pub u128);
     pub enum FunctionStateMutability {
         View = 0,
@@ -318,6 +331,7 @@ auto_storage_serde! {
         Blob = 1,
     }
     pub struct L1ToL2Payload(pub Vec<Felt>);
+    pub struct L1TransactionHash(pub [u8; 32]);
     pub struct L2ToL1Payload(pub Vec<Felt>);
     enum MarkerKind {
         Header = 0,
@@ -329,6 +343,10 @@ auto_storage_serde! {
         BaseLayerBlock = 6,
         ClassManagerBlock = 7,
         CompilerBackwardCompatibility = 8,
+        Pruned = 9,
+        EventKeysFilter = 10,
+        L1ToL2Messages = 11,
+        L1ToL2MessagesBackfill = 12,
     }
     pub struct MessageToL1 {
         pub to_address: EthAddress,
@@ -420,6 +438,10 @@ auto_storage_serde! {
         V0_14_0 = 21,
     }
     pub struct StateDiffCommitment(pub PoseidonHash);
+    pub struct StoredCompressionDictionary {
+        pub kind: CompressionDictionaryKind,
+        pub dictionary: Vec<u8>,
+    }
     pub struct Tip(pub u64);
     pub struct TransactionCommitment(pub StarkHash);
     pub struct TypedParameter {
@@ -516,6 +538,7 @@ auto_storage_serde! {
     (ContractAddress, StorageKey);
     (ContractAddress, TransactionIndex);
     ((ContractAddress, StorageKey), BlockNumber);
+    (L1TransactionHash, u32);
     (usize, Vec<Hint>);
     (usize, Vec<String>);
 }
@@ -978,9 +1001,46 @@ impl StorageSerde for TransactionOffsetInBlock {
 ////////////////////////////////////////////////////////////////////////
 //  Custom serialization with compression.
 ////////////////////////////////////////////////////////////////////////
+// The parts of the classes that are compressed with dictionaries, which are also the samples for
+// training the dictionaries.
+
+pub(crate) fn sierra_program_to_compress(
+    contract_class: &SierraContractClass,
+) -> Result<Vec<u8>, StorageSerdeError> {
+    let mut to_compress: Vec<u8> = Vec::new();
+    contract_class.sierra_program.serialize_into(&mut to_compress)?;
+    Ok(to_compress)
+}
+
+pub(crate) fn deprecated_contract_class_to_compress(
+    contract_class: &DeprecatedContractClass,
+) -> Result<Vec<u8>, StorageSerdeError> {
+    // Compress together the program and abi for better compression results.
+    let mut to_compress: Vec<u8> = Vec::new();
+    contract_class.abi.serialize_into(&mut to_compress)?;
+    contract_class.program.serialize_into(&mut to_compress)?;
+    Ok(to_compress)
+}
+
+pub(crate) fn casm_to_compress(casm: &CasmContractClass) -> Result<Vec<u8>, StorageSerdeError> {
+    let mut to_compress: Vec<u8> = Vec::new();
+    casm.prime.serialize_into(&mut to_compress)?;
+    casm.compiler_version.serialize_into(&mut to_compress)?;
+    casm.bytecode.serialize_into(&mut to_compress)?;
+    casm.bytecode_segment_lengths.serialize_into(&mut to_compress)?;
+    casm.hints.serialize_into(&mut to_compress)?;
+    casm.pythonic_hints.serialize_into(&mut to_compress)?;
+    casm.entry_points_by_type.serialize_into(&mut to_compress)?;
+    Ok(to_compress)
+}
+
 impl StorageSerde for SierraContractClass {
     fn serialize_into(&self, res: &mut impl std::io::Write) -> Result<(), StorageSerdeError> {
-        serialize_and_compress(&self.sierra_program)?.serialize_into(res)?;
+        compress_with_dictionary(
+            &sierra_program_to_compress(self)?,
+            CompressionDictionaryKind::SierraProgram,
+        )?
+        .serialize_into(res)?;
         self.contract_class_version.serialize_into(res)?;
         self.entry_points_by_type.serialize_into(res)?;
         serialize_and_compress(&self.abi)?.serialize_into(res)?;
@@ -1003,10 +1063,7 @@ create_storage_serde_test!(SierraContractClass);
 
 impl StorageSerde for DeprecatedContractClass {
     fn serialize_into(&self, res: &mut impl std::io::Write) -> Result<(), StorageSerdeError> {
-        // Compress together the program and abi for better compression results.
-        let mut to_compress: Vec<u8> = Vec::new();
-        self.abi.serialize_into(&mut to_compress)?;
-        self.program.serialize_into(&mut to_compress)?;
+        let to_compress = deprecated_contract_class_to_compress(self)?;
         if to_compress.len() > crate::compression_utils::MAX_DECOMPRESSED_SIZE {
             warn!(
                 "DeprecatedContractClass serialization size is too large and will lead to \
@@ -1014,7 +1071,10 @@ impl StorageSerde for DeprecatedContractClass {
                 to_compress.len()
             );
         }
-        let compressed = compress(to_compress.as_slice())?;
+        let compressed = compress_with_dictionary(
+            to_compress.as_slice(),
+            CompressionDictionaryKind::DeprecatedContractClass,
+        )?;
         compressed.serialize_into(res)?;
         self.entry_points_by_type.serialize_into(res)?;
         Ok(())
@@ -1058,14 +1118,7 @@ impl<TYPE: Default> StorageSerde for FunctionAbiEntry<TYPE> {
 
 impl StorageSerde for CasmContractClass {
     fn serialize_into(&self, res: &mut impl std::io::Write) -> Result<(), StorageSerdeError> {
-        let mut to_compress: Vec<u8> = Vec::new();
-        self.prime.serialize_into(&mut to_compress)?;
-        self.compiler_version.serialize_into(&mut to_compress)?;
-        self.bytecode.serialize_into(&mut to_compress)?;
-        self.bytecode_segment_lengths.serialize_into(&mut to_compress)?;
-        self.hints.serialize_into(&mut to_compress)?;
-        self.pythonic_hints.serialize_into(&mut to_compress)?;
-        self.entry_points_by_type.serialize_into(&mut to_compress)?;
+        let to_compress = casm_to_compress(self)?;
         if to_compress.len() > crate::compression_utils::MAX_DECOMPRESSED_SIZE {
             warn!(
                 "CasmContractClass serialization size is too large and will lead to \
@@ -1073,7 +1126,8 @@ impl StorageSerde for CasmContractClass {
                 to_compress.len()
             );
         }
-        let compressed = compress(to_compress.as_slice())?;
+        let compressed =
+            compress_with_dictionary(to_compress.as_slice(), CompressionDictionaryKind::Casm)?;
         compressed.serialize_into(res)?;
 
         Ok(())
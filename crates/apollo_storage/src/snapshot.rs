@@ -0,0 +1,586 @@
+//! Export and import of consistent snapshots of the storage, for bootstrapping a node without
+//! syncing from genesis.
+//!
+//! A snapshot is taken from a single read transaction, so it is consistent even while the storage
+//! is written (e.g., by a running node). It contains the entries of all the tables and the content
+//! of the mmap files up to the offsets that were committed when the transaction began. Note that
+//! the read transaction is open during the whole export, so the database can't reuse the pages
+//! that are freed meanwhile and may grow. Likewise, the disk space of the mmap files isn't released
+//! (e.g., by pruning) during the export.
+//!
+//! The snapshot is written as a zstd-compressed archive of entries. The first entry is the
+//! [`SnapshotManifest`], followed by an entry for each table and an entry for each mmap file. Each
+//! entry is followed by its SHA-256 checksum, which is validated on import.
+//!
+//! The snapshot is exported from a [`StorageReader`] that can be opened with
+//! [`open_storage_read_only`](crate::read_only::open_storage_read_only), next to a running node.
+//! It is imported into a staging directory, which is moved to the path of the storage only after
+//! all of its entries were verified, so a failed import doesn't leave a partial storage behind.
+//!
+//! # Example
+//! ```
+//! use apollo_storage::read_only::open_storage_read_only;
+//! use apollo_storage::snapshot::{export_snapshot, import_snapshot};
+//! use apollo_storage::{open_storage, StorageConfig};
+//! # use apollo_storage::db::DbConfig;
+//! # use starknet_api::core::ChainId;
+//!
+//! # let dir_handle = tempfile::tempdir().unwrap();
+//! # let db_config = DbConfig {
+//! #     path_prefix: dir_handle.path().join("source"),
+//! #     chain_id: ChainId::Mainnet,
+//! #     enforce_file_exists: false,
+//! #     min_size: 1 << 20,    // 1MB
+//! #     max_size: 1 << 35,    // 32GB
+//! #     growth_step: 1 << 26, // 64MB
+//! # };
+//! # std::fs::create_dir_all(db_config.path()).unwrap();
+//! # let storage_config = StorageConfig { db_config, ..Default::default() };
+//! # let mut target_storage_config = storage_config.clone();
+//! # target_storage_config.db_config.path_prefix = dir_handle.path().join("target");
+//! // Done by another process, e.g., a node.
+//! # drop(open_storage(storage_config.clone())?);
+//!
+//! let reader = open_storage_read_only(storage_config.clone())?;
+//! let mut archive = Vec::new();
+//! let manifest = export_snapshot(&reader, &storage_config.db_config, &mut archive)?;
+//!
+//! // The target storage must not exist.
+//! let imported_manifest = import_snapshot(archive.as_slice(), target_storage_config.clone())?;
+//! assert_eq!(imported_manifest, manifest);
+//! let (target_reader, _target_writer) = open_storage(target_storage_config)?;
+//! # Ok::<(), apollo_storage::StorageError>(())
+//! ```
+
+#[cfg(test)]
+#[path = "snapshot_test.rs"]
+mod snapshot_test;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Write};
+use std::path::PathBuf;
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+use fs2::FileExt;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use starknet_api::block::BlockNumber;
+use starknet_api::core::ChainId;
+use tracing::{info, warn};
+
+use crate::body::BodyStorageReader;
+use crate::db::table_types::Table;
+use crate::db::DbConfig;
+use crate::header::HeaderStorageReader;
+use crate::state::StateStorageReader;
+use crate::version::{StorageVersionError, Version, VersionStorageReader};
+use crate::{
+    open_file_space_lock,
+    open_storage,
+    table_names,
+    OffsetKind,
+    StorageConfig,
+    StorageReader,
+    StorageResult,
+    StorageScope,
+    STORAGE_VERSION_BLOCKS,
+    STORAGE_VERSION_STATE,
+};
+
+/// The version of the format of the snapshot archives.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+const MAGIC: &[u8; 8] = b"APSNPSHT";
+const COMPRESSION_LEVEL: i32 = zstd::DEFAULT_COMPRESSION_LEVEL;
+// The size of the chunks in which the content of an entry is written, in bytes.
+const CHUNK_SIZE: usize = 1 << 20;
+// The maximal total size of the table entries written in a single transaction on import, in bytes.
+const MAX_IMPORT_BATCH_SIZE: usize = 1 << 28;
+const MANIFEST_ENTRY_NAME: &str = "manifest.json";
+// The directory, under the path prefix of the storage, in which a snapshot is imported before it is
+// moved to the path of the storage.
+const IMPORT_STAGING_DIR: &str = "snapshot_import";
+
+/// The description of the content of a snapshot.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SnapshotManifest {
+    /// The chain of the storage.
+    pub chain_id: ChainId,
+    /// The scope of the storage.
+    pub scope: StorageScope,
+    /// The state version of the storage.
+    pub state_version: Version,
+    /// The blocks version of the storage, if it stores blocks.
+    pub blocks_version: Option<Version>,
+    /// The first block whose header is not in the snapshot.
+    pub header_marker: BlockNumber,
+    /// The first block whose body is not in the snapshot.
+    pub body_marker: BlockNumber,
+    /// The first block whose state diff is not in the snapshot.
+    pub state_marker: BlockNumber,
+    /// The names of the tables in the snapshot, by their order in the archive.
+    pub tables: Vec<String>,
+    /// The names and sizes of the mmap files in the snapshot, by their order in the archive.
+    pub files: BTreeMap<String, u64>,
+}
+
+/// An error that can occur when exporting or importing a snapshot.
+#[allow(missing_docs)]
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error("The snapshot archive is malformed: {msg}.")]
+    InvalidArchive { msg: String },
+    #[error(
+        "Unsupported snapshot format version {format_version} (expected \
+         {SNAPSHOT_FORMAT_VERSION})."
+    )]
+    UnsupportedFormatVersion { format_version: u32 },
+    #[error("Expected the snapshot entry {expected}, found {found}.")]
+    UnexpectedEntry { expected: String, found: String },
+    #[error("The checksum of the snapshot entry {entry} does not match its content.")]
+    ChecksumMismatch { entry: String },
+    #[error(
+        "The snapshot is of chain {snapshot_chain_id}, but the storage is configured for chain \
+         {config_chain_id}."
+    )]
+    ChainIdMismatch { snapshot_chain_id: ChainId, config_chain_id: ChainId },
+    #[error("Cannot import a snapshot into the existing storage at {0:?}.")]
+    StorageExists(PathBuf),
+    #[error("The snapshot contains the unknown table {0}.")]
+    UnknownTable(String),
+    #[error("The snapshot contains the unknown file {0}.")]
+    UnknownFile(String),
+    #[error("The size of the file {file_name} is {size} bytes, expected {expected_size} bytes.")]
+    FileSizeMismatch { file_name: String, size: u64, expected_size: u64 },
+    #[error(
+        "The markers of the imported storage (header: {header_marker}, body: {body_marker}, \
+         state: {state_marker}) don't match the snapshot manifest."
+    )]
+    ImportedMarkersMismatch {
+        header_marker: BlockNumber,
+        body_marker: BlockNumber,
+        state_marker: BlockNumber,
+    },
+}
+
+/// Writes a consistent snapshot of the storage to the given writer, and returns its manifest.
+/// The configuration must be the one the storage was opened with.
+pub fn export_snapshot(
+    reader: &StorageReader,
+    db_config: &DbConfig,
+    archive: impl Write,
+) -> StorageResult<SnapshotManifest> {
+    // Holds off the release of the disk space of the files until they are copied, so that the
+    // copies don't contain zeros at the locations of the data of the transaction. Locked before the
+    // transaction begins, since the space released before was not used by the data it reads.
+    let file_space_lock = open_file_space_lock(db_config)?;
+    FileExt::lock_shared(&file_space_lock)?;
+    let txn = reader.begin_ro_txn()?;
+    let file_offsets_table = txn.open_table(&txn.tables.file_offsets)?;
+    let mut files = BTreeMap::new();
+    for offset_kind in OffsetKind::ALL {
+        let size = file_offsets_table.get(&txn.txn, &offset_kind)?.unwrap_or_default();
+        files.insert(
+            offset_kind.file_name().to_string(),
+            u64::try_from(size).expect("The file size should fit in u64."),
+        );
+    }
+    let manifest = SnapshotManifest {
+        chain_id: db_config.chain_id.clone(),
+        scope: reader.get_scope(),
+        state_version: txn.get_state_version()?.unwrap_or_default(),
+        blocks_version: txn.get_blocks_version()?,
+        header_marker: txn.get_header_marker()?,
+        body_marker: txn.get_body_marker()?,
+        state_marker: txn.get_state_marker()?,
+        tables: table_names().iter().map(|name| name.to_string()).collect(),
+        files,
+    };
+    info!("Exporting a snapshot of the storage: {manifest:?}.");
+
+    let mut archive = ArchiveWriter::new(archive)?;
+    let mut entry = archive.begin_entry(MANIFEST_ENTRY_NAME)?;
+    serde_json::to_writer(&mut entry, &manifest)?;
+    entry.finish()?;
+
+    for table_name in &manifest.tables {
+        let mut entry = archive.begin_entry(table_name)?;
+        txn.txn.for_each_raw_entry(table_name, |key, value| -> StorageResult<()> {
+            Ok(write_record(&mut entry, key, value)?)
+        })?;
+        // A zero byte ends the records of the table.
+        entry.write_u8(0)?;
+        entry.finish()?;
+        info!("Exported the table {table_name}.");
+    }
+
+    for (file_name, expected_size) in &manifest.files {
+        let mut entry = archive.begin_entry(file_name)?;
+        let file = File::open(db_config.path().join(file_name))?;
+        let size = io::copy(&mut file.take(*expected_size), &mut entry)?;
+        if size != *expected_size {
+            return Err(SnapshotError::FileSizeMismatch {
+                file_name: file_name.clone(),
+                size,
+                expected_size: *expected_size,
+            }
+            .into());
+        }
+        entry.finish()?;
+        info!("Exported the file {file_name}.");
+    }
+
+    archive.finish()?.flush()?;
+    Ok(manifest)
+}
+
+/// Restores the storage described by the given configuration from a snapshot, and returns the
+/// snapshot's manifest. The storage must not exist. The snapshot's versions must be supported by
+/// the crate; the restored storage is migrated to the crate's versions when it is opened.
+/// The storage is created only if the whole snapshot was imported and verified.
+pub fn import_snapshot(
+    archive: impl Read,
+    storage_config: StorageConfig,
+) -> StorageResult<SnapshotManifest> {
+    let db_path = storage_config.db_config.path();
+    if db_path.exists() && fs::read_dir(&db_path)?.next().is_some() {
+        return Err(SnapshotError::StorageExists(db_path).into());
+    }
+
+    let mut archive = ArchiveReader::new(archive)?;
+    let mut entry = archive.next_entry(MANIFEST_ENTRY_NAME)?;
+    let manifest: SnapshotManifest = serde_json::from_reader(&mut entry)?;
+    entry.finish()?;
+    verify_manifest(&manifest, &storage_config)?;
+    info!("Importing a snapshot of the storage: {manifest:?}.");
+
+    let mut staging_config = storage_config.clone();
+    let staging_dir = storage_config.db_config.path_prefix.join(IMPORT_STAGING_DIR);
+    staging_config.db_config.path_prefix = staging_dir.clone();
+    if staging_dir.exists() {
+        info!("Removing the staging directory of a previous import: {staging_dir:?}.");
+        fs::remove_dir_all(&staging_dir)?;
+    }
+    let staging_path = staging_config.db_config.path();
+    fs::create_dir_all(&staging_path)?;
+    if let Err(err) = import_entries(&mut archive, &manifest, staging_config) {
+        if let Err(remove_err) = fs::remove_dir_all(&staging_dir) {
+            warn!("Failed to remove the staging directory {staging_dir:?}: {remove_err}.");
+        }
+        return Err(err);
+    }
+
+    // An empty directory is replaced by the rename.
+    fs::rename(&staging_path, &db_path)?;
+    fs::remove_dir(&staging_dir)?;
+    info!("Moved the imported storage to {db_path:?}.");
+    Ok(manifest)
+}
+
+// Imports the tables and the files of a snapshot into the storage described by the configuration,
+// whose directory must be empty, and verifies them.
+fn import_entries(
+    archive: &mut ArchiveReader<impl Read>,
+    manifest: &SnapshotManifest,
+    storage_config: StorageConfig,
+) -> StorageResult<()> {
+    let db_path = storage_config.db_config.path();
+    // Create the tables and the files. The state-only scope doesn't write anything else, and the
+    // versions are overridden by the snapshot's.
+    let (reader, mut writer) =
+        open_storage(StorageConfig { scope: StorageScope::StateOnly, ..storage_config })?;
+    for table_name in &manifest.tables {
+        let mut entry = archive.next_entry(table_name)?;
+        let mut batch = Vec::new();
+        let mut batch_size = 0;
+        while let Some((key, value)) = read_record(&mut entry)? {
+            batch_size += key.len() + value.len();
+            batch.push((key, value));
+            if batch_size >= MAX_IMPORT_BATCH_SIZE {
+                let txn = writer.db_writer.begin_rw_txn()?;
+                txn.put_raw_entries(table_name, &batch)?;
+                txn.commit()?;
+                batch.clear();
+                batch_size = 0;
+            }
+        }
+        let txn = writer.db_writer.begin_rw_txn()?;
+        txn.put_raw_entries(table_name, &batch)?;
+        txn.commit()?;
+        entry.finish()?;
+        info!("Imported the table {table_name}.");
+    }
+
+    // Verify that the imported tables match the snapshot.
+    let txn = reader.begin_ro_txn()?;
+    let (header_marker, body_marker, state_marker) =
+        (txn.get_header_marker()?, txn.get_body_marker()?, txn.get_state_marker()?);
+    if (header_marker, body_marker, state_marker)
+        != (manifest.header_marker, manifest.body_marker, manifest.state_marker)
+    {
+        return Err(SnapshotError::ImportedMarkersMismatch {
+            header_marker,
+            body_marker,
+            state_marker,
+        }
+        .into());
+    }
+    drop(txn);
+    drop(reader);
+    drop(writer);
+
+    for (file_name, expected_size) in &manifest.files {
+        let mut entry = archive.next_entry(file_name)?;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(db_path.join(file_name))?;
+        let size = io::copy(&mut entry, &mut file)?;
+        if size != *expected_size {
+            return Err(SnapshotError::FileSizeMismatch {
+                file_name: file_name.clone(),
+                size,
+                expected_size: *expected_size,
+            }
+            .into());
+        }
+        file.sync_all()?;
+        entry.finish()?;
+        info!("Imported the file {file_name}.");
+    }
+    Ok(())
+}
+
+// Verifies that the snapshot can be imported into the storage described by the configuration.
+fn verify_manifest(
+    manifest: &SnapshotManifest,
+    storage_config: &StorageConfig,
+) -> StorageResult<()> {
+    if manifest.chain_id != storage_config.db_config.chain_id {
+        return Err(SnapshotError::ChainIdMismatch {
+            snapshot_chain_id: manifest.chain_id.clone(),
+            config_chain_id: storage_config.db_config.chain_id.clone(),
+        }
+        .into());
+    }
+    verify_version_supported(&STORAGE_VERSION_STATE, &manifest.state_version)?;
+    if let Some(blocks_version) = &manifest.blocks_version {
+        verify_version_supported(&STORAGE_VERSION_BLOCKS, blocks_version)?;
+    }
+    if let Some(table_name) =
+        manifest.tables.iter().find(|table_name| !table_names().contains(&table_name.as_str()))
+    {
+        return Err(SnapshotError::UnknownTable(table_name.clone()).into());
+    }
+    if let Some(file_name) = manifest.files.keys().find(|file_name| {
+        !OffsetKind::ALL.iter().any(|offset_kind| offset_kind.file_name() == file_name.as_str())
+    }) {
+        return Err(SnapshotError::UnknownFile(file_name.clone()).into());
+    }
+    Ok(())
+}
+
+// A storage version is supported if it has the crate's major version and a minor version that is
+// not higher than the crate's.
+fn verify_version_supported(
+    crate_version: &Version,
+    storage_version: &Version,
+) -> StorageResult<()> {
+    if crate_version.major != storage_version.major || crate_version.minor < storage_version.minor {
+        return Err(StorageVersionError::InconsistentStorageVersion {
+            crate_version: crate_version.clone(),
+            storage_version: storage_version.clone(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+// A table entry is written as a one byte, followed by the key and the value.
+fn write_record(writer: &mut impl Write, key: &[u8], value: &[u8]) -> io::Result<()> {
+    writer.write_u8(1)?;
+    write_bytes(writer, key)?;
+    write_bytes(writer, value)
+}
+
+fn read_record(reader: &mut impl Read) -> StorageResult<Option<(Vec<u8>, Vec<u8>)>> {
+    match reader.read_u8()? {
+        0 => Ok(None),
+        1 => Ok(Some((read_bytes(reader)?, read_bytes(reader)?))),
+        _ => Err(invalid_archive("invalid table entry")),
+    }
+}
+
+fn write_bytes(writer: &mut impl Write, bytes: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many bytes to write."))?;
+    writer.write_u32::<BigEndian>(len)?;
+    writer.write_all(bytes)
+}
+
+fn read_bytes(reader: &mut impl Read) -> io::Result<Vec<u8>> {
+    let len = reader.read_u32::<BigEndian>()?;
+    let mut bytes = vec![0; usize::try_from(len).expect("u32 should fit in usize")];
+    reader.read_exact(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn invalid_archive(msg: &str) -> crate::StorageError {
+    SnapshotError::InvalidArchive { msg: msg.to_string() }.into()
+}
+
+struct ArchiveWriter<W: Write> {
+    encoder: zstd::Encoder<'static, W>,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    fn new(writer: W) -> io::Result<Self> {
+        let mut encoder = zstd::Encoder::new(writer, COMPRESSION_LEVEL)?;
+        encoder.write_all(MAGIC)?;
+        encoder.write_u32::<BigEndian>(SNAPSHOT_FORMAT_VERSION)?;
+        Ok(Self { encoder })
+    }
+
+    fn begin_entry(&mut self, name: &str) -> io::Result<EntryWriter<'_, W>> {
+        write_bytes(&mut self.encoder, name.as_bytes())?;
+        Ok(EntryWriter {
+            encoder: &mut self.encoder,
+            hasher: Sha256::new(),
+            buffer: Vec::with_capacity(CHUNK_SIZE),
+        })
+    }
+
+    fn finish(self) -> io::Result<W> {
+        self.encoder.finish()
+    }
+}
+
+// Writes the content of an entry in chunks, each prefixed by its length. An empty chunk ends the
+// entry, and is followed by the checksum of the content.
+struct EntryWriter<'a, W: Write> {
+    encoder: &'a mut zstd::Encoder<'static, W>,
+    hasher: Sha256,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> EntryWriter<'_, W> {
+    fn write_chunk(&mut self) -> io::Result<()> {
+        self.hasher.update(&self.buffer);
+        write_bytes(self.encoder, &self.buffer)?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    fn finish(mut self) -> io::Result<()> {
+        if !self.buffer.is_empty() {
+            self.write_chunk()?;
+        }
+        write_bytes(self.encoder, &[])?;
+        let checksum = self.hasher.finalize();
+        self.encoder.write_all(&checksum)
+    }
+}
+
+impl<W: Write> Write for EntryWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(CHUNK_SIZE - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        if self.buffer.len() == CHUNK_SIZE {
+            self.write_chunk()?;
+        }
+        Ok(len)
+    }
+
+    // The chunks are written when they are full or when the entry is finished.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+struct ArchiveReader<R: Read> {
+    decoder: zstd::Decoder<'static, BufReader<R>>,
+}
+
+impl<R: Read> ArchiveReader<R> {
+    fn new(reader: R) -> StorageResult<Self> {
+        let mut decoder = zstd::Decoder::new(reader)?;
+        let mut magic = [0; MAGIC.len()];
+        decoder.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(invalid_archive("not a snapshot archive"));
+        }
+        let format_version = decoder.read_u32::<BigEndian>()?;
+        if format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedFormatVersion { format_version }.into());
+        }
+        Ok(Self { decoder })
+    }
+
+    // Returns a reader of the next entry, which is expected to have the given name.
+    fn next_entry(&mut self, expected_name: &str) -> StorageResult<EntryReader<'_, R>> {
+        let name = String::from_utf8(read_bytes(&mut self.decoder)?)
+            .map_err(|_| invalid_archive("invalid entry name"))?;
+        if name != expected_name {
+            return Err(SnapshotError::UnexpectedEntry {
+                expected: expected_name.to_string(),
+                found: name,
+            }
+            .into());
+        }
+        Ok(EntryReader {
+            decoder: &mut self.decoder,
+            name,
+            hasher: Sha256::new(),
+            remaining_in_chunk: 0,
+            ended: false,
+        })
+    }
+}
+
+struct EntryReader<'a, R: Read> {
+    decoder: &'a mut zstd::Decoder<'static, BufReader<R>>,
+    name: String,
+    hasher: Sha256,
+    remaining_in_chunk: usize,
+    ended: bool,
+}
+
+impl<R: Read> EntryReader<'_, R> {
+    // Verifies that the entry was read to its end and that its checksum matches its content.
+    fn finish(mut self) -> StorageResult<()> {
+        if self.read(&mut [0; 1])? != 0 {
+            return Err(invalid_archive(&format!("unexpected data in entry {}", self.name)));
+        }
+        let mut checksum = [0; 32];
+        self.decoder.read_exact(&mut checksum)?;
+        if checksum[..] != self.hasher.finalize()[..] {
+            return Err(SnapshotError::ChecksumMismatch { entry: self.name }.into());
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for EntryReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining_in_chunk == 0 {
+            if self.ended {
+                return Ok(0);
+            }
+            let chunk_len = self.decoder.read_u32::<BigEndian>()?;
+            self.remaining_in_chunk = usize::try_from(chunk_len).expect("u32 should fit in usize");
+            if self.remaining_in_chunk == 0 {
+                self.ended = true;
+                return Ok(0);
+            }
+        }
+        let len = buf.len().min(self.remaining_in_chunk);
+        let len = self.decoder.read(&mut buf[..len])?;
+        if len == 0 && !buf.is_empty() {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.hasher.update(&buf[..len]);
+        self.remaining_in_chunk -= len;
+        Ok(len)
+    }
+}
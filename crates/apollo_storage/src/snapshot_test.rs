@@ -0,0 +1,220 @@
+use std::io::{self, Write};
+
+use apollo_test_utils::{get_test_block, get_test_state_diff};
+use assert_matches::assert_matches;
+use pretty_assertions::assert_eq;
+use starknet_api::block::{BlockBody, BlockHash, BlockHeader, BlockHeaderWithoutHash, BlockNumber};
+use starknet_api::core::ChainId;
+use starknet_api::felt;
+use starknet_api::state::ThinStateDiff;
+use starknet_api::transaction::TransactionOffsetInBlock;
+
+use crate::body::{BodyStorageReader, BodyStorageWriter, TransactionIndex};
+use crate::header::{HeaderStorageReader, HeaderStorageWriter};
+use crate::mmap_file::MMapFileError;
+use crate::pruning::PruningStorageReader;
+use crate::snapshot::{export_snapshot, import_snapshot, SnapshotError, IMPORT_STAGING_DIR};
+use crate::state::{StateStorageReader, StateStorageWriter};
+use crate::test_utils::get_test_config;
+use crate::{open_storage, StorageConfig, StorageError, StorageScope};
+
+// Returns the config of a storage with a single block, and a snapshot of the storage.
+fn get_snapshot() -> (StorageConfig, Vec<u8>, tempfile::TempDir) {
+    let (config, temp_dir) = get_test_config(None);
+    let (reader, mut writer) = open_storage(config.clone()).unwrap();
+    let block = get_test_block(2, Some(2), None, None);
+    writer
+        .begin_rw_txn()
+        .unwrap()
+        .append_header(BlockNumber(0), &block.header)
+        .unwrap()
+        .append_body(BlockNumber(0), block.body)
+        .unwrap()
+        .append_state_diff(BlockNumber(0), get_test_state_diff().into())
+        .unwrap()
+        .commit()
+        .unwrap();
+
+    let mut archive = Vec::new();
+    export_snapshot(&reader, &config.db_config, &mut archive).unwrap();
+    (config, archive, temp_dir)
+}
+
+#[test]
+fn export_and_import() {
+    let (source_config, archive, _source_dir) = get_snapshot();
+    let (target_config, _target_dir) = get_test_config(None);
+
+    let manifest = import_snapshot(archive.as_slice(), target_config.clone()).unwrap();
+    assert_eq!(manifest.header_marker, BlockNumber(1));
+    assert_eq!(manifest.body_marker, BlockNumber(1));
+    assert_eq!(manifest.state_marker, BlockNumber(1));
+
+    let (source_reader, _source_writer) = open_storage(source_config).unwrap();
+    let (target_reader, _target_writer) = open_storage(target_config).unwrap();
+    let source_txn = source_reader.begin_ro_txn().unwrap();
+    let target_txn = target_reader.begin_ro_txn().unwrap();
+    assert_eq!(
+        target_txn.get_block_header(BlockNumber(0)).unwrap(),
+        source_txn.get_block_header(BlockNumber(0)).unwrap()
+    );
+    // The transactions and the state diffs are read from the mmap files.
+    assert_eq!(
+        target_txn.get_block_transactions(BlockNumber(0)).unwrap(),
+        source_txn.get_block_transactions(BlockNumber(0)).unwrap()
+    );
+    assert_eq!(
+        target_txn.get_block_transaction_outputs(BlockNumber(0)).unwrap(),
+        source_txn.get_block_transaction_outputs(BlockNumber(0)).unwrap()
+    );
+    assert_eq!(
+        target_txn.get_state_diff(BlockNumber(0)).unwrap(),
+        source_txn.get_state_diff(BlockNumber(0)).unwrap()
+    );
+}
+
+#[test]
+fn import_into_existing_storage_fails() {
+    let (config, archive, _temp_dir) = get_snapshot();
+
+    assert_matches!(
+        import_snapshot(archive.as_slice(), config),
+        Err(StorageError::SnapshotError(SnapshotError::StorageExists(_)))
+    );
+}
+
+#[test]
+fn import_of_other_chain_fails() {
+    let (_config, archive, _source_dir) = get_snapshot();
+    let (mut target_config, _target_dir) = get_test_config(None);
+    target_config.db_config.chain_id = ChainId::Other("other_chain".to_owned());
+
+    assert_matches!(
+        import_snapshot(archive.as_slice(), target_config),
+        Err(StorageError::SnapshotError(SnapshotError::ChainIdMismatch { .. }))
+    );
+}
+
+#[test]
+fn import_of_corrupted_snapshot_fails() {
+    let (_config, archive, _source_dir) = get_snapshot();
+    let (target_config, _target_dir) = get_test_config(None);
+
+    // Corrupt the checksum of the last entry.
+    let mut decompressed_archive = zstd::decode_all(archive.as_slice()).unwrap();
+    *decompressed_archive.last_mut().unwrap() ^= 1;
+    let corrupted_archive = zstd::encode_all(decompressed_archive.as_slice(), 0).unwrap();
+
+    assert_matches!(
+        import_snapshot(corrupted_archive.as_slice(), target_config.clone()),
+        Err(StorageError::SnapshotError(SnapshotError::ChecksumMismatch { entry }))
+            if entry == "transaction_output.dat"
+    );
+    // Nothing was written to the storage, and the staging directory was removed.
+    assert!(!target_config.db_config.path().join("mdbx.dat").exists());
+    assert!(!target_config.db_config.path_prefix.join(IMPORT_STAGING_DIR).exists());
+}
+
+// An archive that runs a callback before it is first written, i.e., while a snapshot is exported.
+struct ArchiveWithCallback<F: FnOnce()> {
+    archive: Vec<u8>,
+    callback: Option<F>,
+}
+
+impl<F: FnOnce()> Write for ArchiveWithCallback<F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if let Some(callback) = self.callback.take() {
+            callback();
+        }
+        self.archive.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.archive.flush()
+    }
+}
+
+#[test]
+fn pruning_during_export() {
+    const N_BLOCKS: u64 = 3;
+    const N_TRANSACTIONS_PER_BLOCK: usize = 200;
+    let (config, _source_dir) = get_test_config(Some(StorageScope::Pruned { retained_blocks: 1 }));
+    let (reader, mut writer) = open_storage(config.clone()).unwrap();
+    // Enough transactions to fill whole pages of the transactions file, whose space is released.
+    let body = get_test_block(
+        usize::try_from(N_BLOCKS).unwrap() * N_TRANSACTIONS_PER_BLOCK,
+        None,
+        None,
+        None,
+    )
+    .body;
+    let blocks_transactions: Vec<_> =
+        body.transactions.chunks(N_TRANSACTIONS_PER_BLOCK).map(<[_]>::to_vec).collect();
+    let mut txn = writer.begin_rw_txn().unwrap();
+    for (block_number, ((transactions, transaction_outputs), transaction_hashes)) in
+        (0..N_BLOCKS).map(BlockNumber).zip(
+            blocks_transactions
+                .iter()
+                .zip(body.transaction_outputs.chunks(N_TRANSACTIONS_PER_BLOCK))
+                .zip(body.transaction_hashes.chunks(N_TRANSACTIONS_PER_BLOCK)),
+        )
+    {
+        let header = BlockHeader {
+            block_hash: BlockHash(felt!(block_number.0 + 1)),
+            block_header_without_hash: BlockHeaderWithoutHash {
+                block_number,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let body = BlockBody {
+            transactions: transactions.clone(),
+            transaction_outputs: transaction_outputs.to_vec(),
+            transaction_hashes: transaction_hashes.to_vec(),
+        };
+        txn = txn
+            .append_header(block_number, &header)
+            .unwrap()
+            .append_body(block_number, body)
+            .unwrap()
+            .append_state_diff(block_number, ThinStateDiff::default())
+            .unwrap();
+    }
+    txn.commit().unwrap();
+    let mut pruner = writer.pruner().unwrap();
+    let first_transaction = TransactionIndex(BlockNumber(0), TransactionOffsetInBlock(0));
+    let txn_before_pruning = reader.begin_ro_txn().unwrap();
+
+    // The first step prunes the blocks, and the second one would release their space.
+    let mut archive = ArchiveWithCallback {
+        archive: Vec::new(),
+        callback: Some(|| {
+            pruner.prune_step();
+            pruner.prune_step();
+        }),
+    };
+    let manifest = export_snapshot(&reader, &config.db_config, &mut archive).unwrap();
+    let archive = archive.archive;
+    assert_eq!(manifest.body_marker, BlockNumber(N_BLOCKS));
+    assert_eq!(reader.begin_ro_txn().unwrap().get_pruning_marker().unwrap(), BlockNumber(2));
+
+    // The space was not released during the export, so the exported files are complete.
+    assert_eq!(
+        txn_before_pruning.get_transaction(first_transaction).unwrap().as_ref(),
+        blocks_transactions[0].first()
+    );
+    let (target_config, _target_dir) = get_test_config(None);
+    import_snapshot(archive.as_slice(), target_config.clone()).unwrap();
+    let (target_reader, _target_writer) = open_storage(target_config).unwrap();
+    let target_txn = target_reader.begin_ro_txn().unwrap();
+    for (block_number, transactions) in (0..N_BLOCKS).map(BlockNumber).zip(blocks_transactions) {
+        assert_eq!(target_txn.get_block_transactions(block_number).unwrap(), Some(transactions));
+    }
+
+    // The space is released once the export finished.
+    pruner.prune_step();
+    assert_matches!(
+        txn_before_pruning.get_transaction(first_transaction),
+        Err(StorageError::MMapFileError(MMapFileError::LocationReleased { .. }))
+    );
+}
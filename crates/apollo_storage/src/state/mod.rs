@@ -72,6 +72,7 @@ use crate::db::{DbTransaction, TableHandle, TransactionKind, RW};
 #[cfg(feature = "document_calls")]
 use crate::document_calls::{add_query, StorageQuery};
 use crate::mmap_file::LocationInFile;
+use crate::pruning::PruningStorageReader;
 use crate::state::data::IndexedDeprecatedContractClass;
 use crate::{
     FileHandlers,
@@ -198,6 +199,8 @@ pub struct StateReader<'env, Mode: TransactionKind> {
     storage_table: ContractStorageTable<'env>,
     markers_table: MarkersTable<'env>,
     file_handlers: &'env FileHandlers<Mode>,
+    // The first block whose state diff was not pruned; the states before it are unavailable.
+    pruning_marker: BlockNumber,
 }
 
 impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
@@ -222,6 +225,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
         let nonces_table = txn.txn.open_table(&txn.tables.nonces)?;
         let storage_table = txn.txn.open_table(&txn.tables.contract_storage)?;
         let markers_table = txn.txn.open_table(&txn.tables.markers)?;
+        let pruning_marker = txn.get_pruning_marker()?;
         Ok(StateReader {
             txn: &txn.txn,
             declared_classes_table,
@@ -233,9 +237,21 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
             storage_table,
             markers_table,
             file_handlers: &txn.file_handlers,
+            pruning_marker,
         })
     }
 
+    // Returns an error if the history needed for reading at the given state number was pruned.
+    fn verify_state_not_pruned(&self, state_number: StateNumber) -> StorageResult<()> {
+        if state_number.block_after() < self.pruning_marker {
+            return Err(StorageError::StatePruned {
+                state_number,
+                pruning_marker: self.pruning_marker,
+            });
+        }
+        Ok(())
+    }
+
     /// Returns the class hash at a given state number.
     /// If class hash is not found, returns `None`.
     ///
@@ -253,6 +269,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
         // TODO(dvir): create an attribute instead of this.
         #[cfg(feature = "document_calls")]
         add_query(StorageQuery::GetClassHashAt(state_number, *address));
+        self.verify_state_not_pruned(state_number)?;
 
         let first_irrelevant_block: BlockNumber = state_number.block_after();
         let db_key = (*address, first_irrelevant_block);
@@ -283,6 +300,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
     ) -> StorageResult<Option<Nonce>> {
         #[cfg(feature = "document_calls")]
         add_query(StorageQuery::GetNonceAt(state_number, *address));
+        self.verify_state_not_pruned(state_number)?;
 
         // State diff updates are indexed by the block_number at which they occurred.
         let block_number: BlockNumber = state_number.block_after();
@@ -307,6 +325,7 @@ impl<'env, Mode: TransactionKind> StateReader<'env, Mode> {
     ) -> StorageResult<Felt> {
         #[cfg(feature = "document_calls")]
         add_query(StorageQuery::GetStorageAt(state_number, *address, *key));
+        self.verify_state_not_pruned(state_number)?;
 
         // The updates to the storage key are indexed by the block_number at which they occurred.
         let first_irrelevant_block: BlockNumber = state_number.block_after();
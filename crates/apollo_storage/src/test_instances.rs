@@ -1,4 +1,6 @@
 use apollo_test_utils::{auto_impl_get_test_instance, get_number_of_variants, GetTestInstance};
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
 use starknet_api::block::{BlockHash, BlockNumber, BlockTimestamp, GasPrice, GasPricePerToken};
 use starknet_api::core::{
     EventCommitment,
@@ -16,8 +18,14 @@ use starknet_api::transaction::{
     TransactionOffsetInBlock,
 };
 
+use crate::body::event_keys_filter::EventKeysFilter;
 use crate::body::TransactionIndex;
-use crate::compression_utils::IsCompressed;
+use crate::compression_utils::{
+    CompressionDictionaryId,
+    CompressionDictionaryKind,
+    IsCompressed,
+    StoredCompressionDictionary,
+};
 use crate::header::StorageBlockHeader;
 use crate::mmap_file::LocationInFile;
 use crate::state::data::IndexedDeprecatedContractClass;
@@ -47,6 +55,12 @@ auto_impl_get_test_instance! {
         pub n_events: usize,
     }
 
+    pub struct CompressionDictionaryId(pub u32);
+    pub enum CompressionDictionaryKind {
+        SierraProgram = 0,
+        Casm = 1,
+        DeprecatedContractClass = 2,
+    }
     struct EventIndex(pub TransactionIndex, pub EventIndexInTransactionOutput);
     pub struct IndexedDeprecatedContractClass {
         pub block_number: BlockNumber,
@@ -66,6 +80,10 @@ auto_impl_get_test_instance! {
         BaseLayerBlock = 6,
         ClassManagerBlock = 7,
         CompilerBackwardCompatibility = 8,
+        Pruned = 9,
+        EventKeysFilter = 10,
+        L1ToL2Messages = 11,
+        L1ToL2MessagesBackfill = 12,
     }
     pub enum OffsetKind {
         ThinStateDiff = 0,
@@ -73,6 +91,10 @@ auto_impl_get_test_instance! {
         Casm = 2,
         DeprecatedContractClass = 3,
     }
+    pub struct StoredCompressionDictionary {
+        pub kind: CompressionDictionaryKind,
+        pub dictionary: Vec<u8>,
+    }
     pub struct TransactionMetadata{
         pub tx_hash: TransactionHash,
         pub tx_location: LocationInFile,
@@ -84,3 +106,11 @@ auto_impl_get_test_instance! {
         pub minor: u32,
     }
 }
+
+impl GetTestInstance for EventKeysFilter {
+    fn get_test_instance(rng: &mut ChaCha8Rng) -> Self {
+        let mut filter = vec![0; 32];
+        rng.fill(&mut filter[..]);
+        Self(filter)
+    }
+}
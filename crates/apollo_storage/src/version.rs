@@ -2,14 +2,16 @@
 #[path = "version_test.rs"]
 mod version_test;
 
+use serde::{Deserialize, Serialize};
+
 use crate::db::table_types::Table;
 use crate::db::{TransactionKind, RW};
 use crate::{StorageError, StorageResult, StorageTxn};
 
-const VERSION_STATE_KEY: &str = "storage_version_state";
-const VERSION_BLOCKS_KEY: &str = "storage_version_blocks";
+pub(crate) const VERSION_STATE_KEY: &str = "storage_version_state";
+pub(crate) const VERSION_BLOCKS_KEY: &str = "storage_version_blocks";
 
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Version {
     pub major: u32,
     pub minor: u32,
@@ -28,6 +30,15 @@ pub enum StorageVersionError {
     )]
     InconsistentStorageScope,
 
+    #[error("The storage has no version; it must be opened by a writer first.")]
+    MissingStorageVersion,
+
+    #[error(
+        "The storage migration {name} from version {storage_version} is pending. Migrate the \
+         storage by running the node or the storage_migrate binary."
+    )]
+    PendingMigration { name: &'static str, storage_version: Version },
+
     #[error(
         "Trying to set a DB minor version {crate_version:} which is not higher that the existing \
          one {storage_version}."
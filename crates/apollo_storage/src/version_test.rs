@@ -3,6 +3,7 @@ use pretty_assertions::assert_eq;
 use rand::Rng;
 
 use crate::db::table_types::Table;
+use crate::migrations::migrate_storage;
 use crate::test_utils::{
     get_test_storage,
     get_test_storage_by_scope,
@@ -106,6 +107,16 @@ fn version_migration() {
     drop(reader);
     drop(writer);
 
+    // The storage can't be opened before it's migrated.
+    let Err(err) = open_storage(config.clone()) else {
+        panic!("Unexpected Ok.");
+    };
+    assert_matches!(
+        err,
+        StorageError::StorageVersionInconsistency(StorageVersionError::PendingMigration { .. })
+    );
+    migrate_storage(config.clone()).unwrap();
+
     // Reopen the storage and verify the version.
     let (reader, _) = open_storage(config).unwrap();
 
@@ -283,7 +294,7 @@ fn test_set_version_if_needed() {
     reader.scope = StorageScope::FullArchive;
     writer.scope = StorageScope::FullArchive;
     assert!(
-        set_version_if_needed(reader, writer).is_err(),
+        set_version_if_needed(reader, writer, true).is_err(),
         "Should fail, because storage scope cannot shift from state-only to full-archive."
     );
 }
@@ -59,6 +59,7 @@ use starknet_api::core::{
     EthAddress,
     EventCommitment,
     GlobalRoot,
+    L1TransactionHash,
     Nonce,
     ReceiptCommitment,
     SequencerContractAddress,
@@ -947,6 +948,7 @@ auto_impl_get_test_instance! {
     (ContractAddress, Nonce);
     (ContractAddress, StorageKey, BlockHash);
     (ContractAddress, StorageKey, BlockNumber);
+    (L1TransactionHash, u32);
     (CompiledClassHash, SierraContractClass);
     (usize, Vec<Hint>);
     (usize, Vec<String>);
@@ -1183,6 +1185,14 @@ impl GetTestInstance for GasVector {
     }
 }
 
+impl GetTestInstance for L1TransactionHash {
+    fn get_test_instance(rng: &mut ChaCha8Rng) -> Self {
+        let mut hash = [0; 32];
+        rng.fill_bytes(&mut hash);
+        Self(hash)
+    }
+}
+
 impl GetTestInstance for NonZeroU32 {
     fn get_test_instance(rng: &mut ChaCha8Rng) -> Self {
         max(1, rng.next_u32()).try_into().expect("Failed to convert a non-zero u32 to NonZeroU32")
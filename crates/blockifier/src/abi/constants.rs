@@ -28,6 +28,7 @@ pub const STATE_DIFF_SIZE: &str = "state_diff_size";
 pub const N_MEMORY_HOLES: &str = "n_memory_holes";
 pub const SIERRA_GAS: &str = "sierra_gas";
 pub const N_TXS: &str = "n_txs";
+pub const PROVING_COST: &str = "proving_cost";
 
 // Casm hash calculation-related constants.
 pub const CAIRO0_ENTRY_POINT_STRUCT_SIZE: usize = 2;
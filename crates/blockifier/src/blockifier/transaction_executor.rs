@@ -10,7 +10,8 @@ use thiserror::Error;
 
 use crate::blockifier::block::pre_process_block;
 use crate::blockifier::config::TransactionExecutorConfig;
-use crate::bouncer::{Bouncer, BouncerWeights, CasmHashComputationData};
+use crate::bouncer::{BlockCapacityUtilization, Bouncer, BouncerWeights, CasmHashComputationData};
+use crate::concurrency::stats::ConcurrencyStats;
 use crate::concurrency::worker_logic::WorkerExecutor;
 use crate::context::BlockContext;
 use crate::state::cached_state::{CachedState, CommitmentStateDiff, StateMaps, TransactionalState};
@@ -51,7 +52,10 @@ pub struct BlockExecutionSummary {
     pub state_diff: CommitmentStateDiff,
     pub compressed_state_diff: Option<CommitmentStateDiff>,
     pub bouncer_weights: BouncerWeights,
+    pub capacity_utilization: BlockCapacityUtilization,
     pub casm_hash_computation_data: CasmHashComputationData,
+    // Statistics of the concurrent execution; `None` if the block was executed sequentially.
+    pub concurrency_stats: Option<ConcurrencyStats>,
 }
 
 /// A transaction executor, used for building a single block.
@@ -60,6 +64,8 @@ pub struct TransactionExecutor<S: StateReader> {
     pub bouncer: Bouncer,
     // Note: this config must not affect the execution result (e.g. state diff and traces).
     pub config: TransactionExecutorConfig,
+    // Accumulated over the executed chunks; only updated in concurrency mode.
+    pub concurrency_stats: ConcurrencyStats,
 
     // State-related fields.
     // The transaction executor operates at the block level. In concurrency mode, it moves the
@@ -100,6 +106,7 @@ impl<S: StateReader> TransactionExecutor<S> {
             block_context,
             bouncer: Bouncer::new(bouncer_config),
             config,
+            concurrency_stats: ConcurrencyStats::default(),
             block_state: Some(block_state),
         }
     }
@@ -192,7 +199,13 @@ impl<S: StateReader> TransactionExecutor<S> {
             state_diff: state_diff.into(),
             compressed_state_diff,
             bouncer_weights: *self.bouncer.get_accumulated_weights(),
+            capacity_utilization: self.bouncer.get_capacity_utilization(),
             casm_hash_computation_data: mem::take(&mut self.bouncer.casm_hash_computation_data),
+            concurrency_stats: self
+                .config
+                .concurrency_config
+                .enabled
+                .then(|| mem::take(&mut self.concurrency_stats)),
         })
     }
 }
@@ -343,6 +356,7 @@ impl<S: StateReader + Send + Sync> TransactionExecutor<S> {
              {validate_counter}; Abort counter: {abort_counter}; Abort in commit counter: \
              {abort_in_commit_counter}"
         );
+        self.concurrency_stats.extend(worker_executor.metrics.get_chunk_stats(n_committed_txs));
         let mut tx_execution_results = Vec::new();
         for execution_output in worker_executor.execution_outputs.iter() {
             if tx_execution_results.len() >= n_committed_txs {
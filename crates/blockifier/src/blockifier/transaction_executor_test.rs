@@ -19,7 +19,7 @@ use crate::blockifier::transaction_executor::{
     TransactionExecutorError,
     BLOCK_STATE_ACCESS_ERR,
 };
-use crate::bouncer::{Bouncer, BouncerWeights};
+use crate::bouncer::{Bouncer, BouncerDimension, BouncerWeights};
 use crate::context::BlockContext;
 use crate::state::cached_state::CachedState;
 use crate::state::state_api::StateReader;
@@ -252,6 +252,39 @@ fn test_l1_handler(block_context: BlockContext) {
     tx_executor_test_body(state, block_context, tx, expected_bouncer_weights);
 }
 
+// The proving cost must not depend on whether the calls run on the VM or natively, so that nodes
+// with different runners agree on whether a block is full.
+#[rstest]
+#[case::vm(RunnableCairo1::Casm)]
+#[cfg_attr(feature = "cairo_native", case::native(RunnableCairo1::Native))]
+fn test_proving_cost_of_sierra_gas_calls(
+    block_context: BlockContext,
+    #[case] runnable_version: RunnableCairo1,
+) {
+    let cairo_version = CairoVersion::Cairo1(runnable_version);
+    let test_contract = FeatureContract::TestContract(cairo_version);
+    let account_contract = FeatureContract::AccountWithoutValidations(cairo_version);
+    let state = test_state_with_contract_manager(
+        &block_context.chain_info,
+        BALANCE,
+        &[(test_contract, 1), (account_contract, 1)],
+    );
+    let mut tx_executor =
+        TransactionExecutor::new(state, block_context, TransactionExecutorConfig::default());
+
+    let calldata = create_calldata(test_contract.get_instance_address(0), "test_pedersen", &[]);
+    let invoke_tx = executable_invoke_tx(invoke_tx_args! {
+        sender_address: account_contract.get_instance_address(0),
+        calldata,
+        version: TransactionVersion::THREE,
+    });
+    let (tx_execution_info, _state_diff) =
+        tx_executor.execute(&AccountTransaction::new_for_sequencing(invoke_tx).into()).unwrap();
+    assert!(!tx_execution_info.is_reverted());
+
+    assert_eq!(tx_executor.bouncer.get_accumulated_weights().proving_cost, 6122435);
+}
+
 #[rstest]
 #[case::happy_flow(BouncerWeights::empty(), 10)]
 #[should_panic(expected = "BlockFull: Transaction cannot be added to the current block, block \
@@ -343,6 +376,10 @@ fn test_execute_txs_bouncing(#[values(true, false)] concurrency_enabled: bool) {
         )
     );
     assert!(results[2].is_ok());
+    assert_eq!(
+        tx_executor.bouncer.get_capacity_utilization().closing_dimensions,
+        vec![BouncerDimension::NEvents]
+    );
 
     // Check state.
     assert_eq!(
@@ -376,6 +413,17 @@ fn test_execute_txs_bouncing(#[values(true, false)] concurrency_enabled: bool) {
             .unwrap(),
         nonce!(4_u32)
     );
+
+    // Check the concurrency statistics.
+    let concurrency_stats = tx_executor.finalize().unwrap().concurrency_stats;
+    if concurrency_enabled {
+        let concurrency_stats = concurrency_stats.unwrap();
+        assert_eq!(concurrency_stats.n_chunks, 3);
+        assert_eq!(concurrency_stats.tx_execution_counts.len(), 5);
+        assert!(concurrency_stats.tx_execution_counts.iter().all(|&n_executions| n_executions > 0));
+    } else {
+        assert!(concurrency_stats.is_none());
+    }
 }
 
 #[cfg(feature = "cairo_native")]
@@ -68,6 +68,8 @@ pub struct RawVersionedConstants {
     pub archival_data_gas_costs: ArchivalDataGasCosts,
     pub allocation_cost: AllocationCost,
     pub vm_resource_fee_cost: VmResourceCosts,
+    #[serde(default, deserialize_with = "builtin_map_from_string_map")]
+    pub builtin_proving_weights: HashMap<BuiltinName, u64>,
 
     // Feature flags.
     pub disable_cairo0_redeclaration: bool,
@@ -201,16 +203,20 @@ impl AllocationCost {
         AllocationCost { blob_cost: GasVector::ZERO, gas_cost: GasVector::ZERO };
 
     pub fn get_cost(&self, use_kzg_da: bool) -> &GasVector {
-        if use_kzg_da { &self.blob_cost } else { &self.gas_cost }
+        if use_kzg_da {
+            &self.blob_cost
+        } else {
+            &self.gas_cost
+        }
     }
 }
 
 // TODO(Dori): This (along with the Serialize impl) is implemented in pub(crate) scope in the VM
 // (named   serde_generic_map_impl); use it if and when it's public.
-fn builtin_map_from_string_map<'de, D: Deserializer<'de>>(
+fn builtin_map_from_string_map<'de, D: Deserializer<'de>, V: Deserialize<'de>>(
     d: D,
-) -> Result<HashMap<BuiltinName, ResourceCost>, D::Error> {
-    HashMap::<String, ResourceCost>::deserialize(d)?
+) -> Result<HashMap<BuiltinName, V>, D::Error> {
+    HashMap::<String, V>::deserialize(d)?
         .into_iter()
         .map(|(k, v)| BuiltinName::from_str_with_suffix(&k).map(|k| (k, v)))
         .collect::<Option<HashMap<_, _>>>()
@@ -258,6 +264,11 @@ pub struct VersionedConstants {
     // Cost of allocating a storage cell.
     pub allocation_cost: AllocationCost,
 
+    // Bouncer related.
+    // The weight of a single instance of each builtin in the proving cost of a block. Builtins
+    // without a weight do not contribute to the proving cost.
+    pub builtin_proving_weights: Arc<HashMap<BuiltinName, u64>>,
+
     // Resources.
     os_resources: Arc<OsResources>,
 
@@ -289,6 +300,7 @@ impl From<RawVersionedConstants> for VersionedConstants {
             vm_resource_fee_cost: Arc::new(raw_vc.vm_resource_fee_cost),
             enable_tip: raw_vc.enable_tip,
             allocation_cost: raw_vc.allocation_cost,
+            builtin_proving_weights: Arc::new(raw_vc.builtin_proving_weights),
             os_resources: Arc::new(os_resources),
             gateway: raw_vc.gateway,
         }
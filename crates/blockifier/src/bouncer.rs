@@ -7,6 +7,8 @@ use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use serde::{Deserialize, Serialize};
 use starknet_api::core::ClassHash;
 use starknet_api::execution_resources::GasAmount;
+use strum::{EnumVariantNames, IntoEnumIterator};
+use strum_macros::{EnumIter, IntoStaticStr};
 
 use crate::blockifier::transaction_executor::{
     TransactionExecutorError,
@@ -101,15 +103,61 @@ pub struct BouncerWeights {
     pub state_diff_size: usize,
     pub sierra_gas: GasAmount,
     pub n_txs: usize,
+    pub proving_cost: usize,
 }
 
 impl BouncerWeights {
-    impl_checked_ops!(l1_gas, message_segment_length, n_events, state_diff_size, sierra_gas, n_txs);
+    impl_checked_ops!(
+        l1_gas,
+        message_segment_length,
+        n_events,
+        state_diff_size,
+        sierra_gas,
+        n_txs,
+        proving_cost
+    );
 
     pub fn has_room(&self, other: Self) -> bool {
         self.checked_sub(other).is_some()
     }
 
+    pub fn get(&self, dimension: BouncerDimension) -> u64 {
+        match dimension {
+            BouncerDimension::L1Gas => u64_from_usize(self.l1_gas),
+            BouncerDimension::MessageSegmentLength => u64_from_usize(self.message_segment_length),
+            BouncerDimension::NEvents => u64_from_usize(self.n_events),
+            BouncerDimension::StateDiffSize => u64_from_usize(self.state_diff_size),
+            BouncerDimension::SierraGas => self.sierra_gas.0,
+            BouncerDimension::NTxs => u64_from_usize(self.n_txs),
+            BouncerDimension::ProvingCost => u64_from_usize(self.proving_cost),
+        }
+    }
+
+    /// Returns the dimensions in which the given weights exceed these weights (as a capacity).
+    pub fn exceeded_dimensions(&self, weights: &Self) -> Vec<BouncerDimension> {
+        BouncerDimension::iter()
+            .filter(|&dimension| weights.get(dimension) > self.get(dimension))
+            .collect()
+    }
+
+    /// Returns the ratio between the given weights and these weights (as a capacity), per
+    /// dimension. A dimension of zero capacity is considered fully utilized.
+    pub fn utilization(&self, weights: &Self) -> BTreeMap<BouncerDimension, f64> {
+        BouncerDimension::iter()
+            .map(|dimension| {
+                let capacity = self.get(dimension);
+                // Precision loss is acceptable for reporting.
+                #[allow(clippy::as_conversions)]
+                let utilization = if capacity == 0 {
+                    1.0
+                } else {
+                    weights.get(dimension) as f64 / capacity as f64
+                };
+                (dimension, utilization)
+            })
+            .collect()
+    }
+
     pub fn max() -> Self {
         Self {
             l1_gas: usize::MAX,
@@ -118,6 +166,7 @@ impl BouncerWeights {
             state_diff_size: usize::MAX,
             sierra_gas: GasAmount::MAX,
             n_txs: usize::MAX,
+            proving_cost: usize::MAX,
         }
     }
 
@@ -129,6 +178,7 @@ impl BouncerWeights {
             state_diff_size: 0,
             sierra_gas: GasAmount::ZERO,
             n_txs: 0,
+            proving_cost: 0,
         }
     }
 }
@@ -143,6 +193,7 @@ impl Default for BouncerWeights {
             state_diff_size: 4000,
             sierra_gas: GasAmount(400000000),
             n_txs: 600,
+            proving_cost: 400000000,
         }
     }
 }
@@ -185,6 +236,12 @@ impl SerializeConfig for BouncerWeights {
             "An upper bound on the total number of transactions in a block.",
             ParamPrivacyInput::Public,
         )]));
+        dump.append(&mut BTreeMap::from([ser_param(
+            "proving_cost",
+            &self.proving_cost,
+            "An upper bound on the total proving cost of the builtins used in a block.",
+            ParamPrivacyInput::Public,
+        )]));
         dump
     }
 }
@@ -194,17 +251,56 @@ impl std::fmt::Display for BouncerWeights {
         write!(
             f,
             "BouncerWeights {{ l1_gas: {}, message_segment_length: {}, n_events: {}, \
-             state_diff_size: {}, sierra_gas: {}, n_txs: {} }}",
+             state_diff_size: {}, sierra_gas: {}, n_txs: {}, proving_cost: {} }}",
             self.l1_gas,
             self.message_segment_length,
             self.n_events,
             self.state_diff_size,
             self.sierra_gas,
-            self.n_txs
+            self.n_txs,
+            self.proving_cost
         )
     }
 }
 
+/// A dimension of the block capacity, i.e., a field of [BouncerWeights].
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Deserialize,
+    EnumIter,
+    EnumVariantNames,
+    Eq,
+    Hash,
+    IntoStaticStr,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BouncerDimension {
+    L1Gas,
+    MessageSegmentLength,
+    NEvents,
+    StateDiffSize,
+    SierraGas,
+    NTxs,
+    ProvingCost,
+}
+
+/// The utilization of the block capacity, reported when the block is closed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockCapacityUtilization {
+    /// The ratio between the accumulated weights and the max capacity of the block, per dimension.
+    pub utilization: BTreeMap<BouncerDimension, f64>,
+    /// The dimensions whose capacity would have been exceeded by the first transaction that did
+    /// not fit in the block; empty if the block was closed for another reason (e.g., a deadline).
+    pub closing_dimensions: Vec<BouncerDimension>,
+}
+
 #[derive(Debug, PartialEq, Default, Clone, Deserialize, Serialize)]
 pub struct CasmHashComputationData {
     pub class_hash_to_casm_hash_computation_gas: HashMap<ClassHash, GasAmount>,
@@ -249,6 +345,8 @@ pub struct Bouncer {
 
     pub bouncer_config: BouncerConfig,
     accumulated_weights: BouncerWeights,
+    // The dimensions that caused the first transaction rejected for a full block not to fit.
+    closing_dimensions: Vec<BouncerDimension>,
 }
 
 impl Bouncer {
@@ -263,6 +361,7 @@ impl Bouncer {
             bouncer_config: BouncerConfig::empty(),
             accumulated_weights: BouncerWeights::empty(),
             casm_hash_computation_data: CasmHashComputationData::empty(),
+            closing_dimensions: Vec::new(),
         }
     }
 
@@ -270,6 +369,16 @@ impl Bouncer {
         &self.accumulated_weights
     }
 
+    pub fn get_capacity_utilization(&self) -> BlockCapacityUtilization {
+        BlockCapacityUtilization {
+            utilization: self
+                .bouncer_config
+                .block_max_capacity
+                .utilization(&self.accumulated_weights),
+            closing_dimensions: self.closing_dimensions.clone(),
+        }
+    }
+
     pub fn get_executed_class_hashes(&self) -> HashSet<ClassHash> {
         self.casm_hash_computation_data
             .class_hash_to_casm_hash_computation_gas
@@ -315,16 +424,21 @@ impl Bouncer {
             "Addition overflow. Transaction weights: {tx_bouncer_weights:?}, block weights: {:?}.",
             self.accumulated_weights
         );
-        if !self
-            .bouncer_config
-            .has_room(self.accumulated_weights.checked_add(tx_bouncer_weights).expect(&err_msg))
-        {
+        let weights_with_tx =
+            self.accumulated_weights.checked_add(tx_bouncer_weights).expect(&err_msg);
+        if !self.bouncer_config.has_room(weights_with_tx) {
+            let exceeded_dimensions =
+                self.bouncer_config.block_max_capacity.exceeded_dimensions(&weights_with_tx);
             log::debug!(
-                "Transaction cannot be added to the current block, block capacity reached; \
-                 transaction weights: {:?}, block weights: {:?}.",
+                "Transaction cannot be added to the current block, block capacity reached in \
+                 {:?}; transaction weights: {:?}, block weights: {:?}.",
+                exceeded_dimensions,
                 tx_weights.bouncer_weights,
                 self.accumulated_weights
             );
+            if self.closing_dimensions.is_empty() {
+                self.closing_dimensions = exceeded_dimensions;
+            }
             Err(TransactionExecutorError::BlockFull)?
         }
 
@@ -413,6 +527,62 @@ pub fn builtins_to_sierra_gas(
     GasAmount(total_gas)
 }
 
+/// Returns the proving cost of the given builtin instances, according to the per-builtin proving
+/// weights in the versioned constants.
+pub fn builtins_to_proving_cost(
+    builtin_counts: &BuiltinCounterMap,
+    versioned_constants: &VersionedConstants,
+) -> usize {
+    let proving_weights = &versioned_constants.builtin_proving_weights;
+
+    let total_cost = builtin_counts
+        .iter()
+        .try_fold(0u64, |accumulated_cost, (builtin, &count)| {
+            let Some(&builtin_weight) = proving_weights.get(builtin) else {
+                return Some(accumulated_cost);
+            };
+            let builtin_total_cost = u64_from_usize(count).checked_mul(builtin_weight)?;
+            accumulated_cost.checked_add(builtin_total_cost)
+        })
+        .unwrap_or_else(|| {
+            panic!(
+                "Overflow occurred while computing the proving cost of builtins. Builtins: {:?}",
+                builtin_counts
+            )
+        });
+
+    usize_from_u64(total_cost).expect("The proving cost of builtins should fit in a usize.")
+}
+
+/// Returns an upper bound on the proving cost of the builtins used by SierraGas-tracked calls,
+/// derived from their Sierra gas rather than from their builtin counts, which native execution
+/// doesn't report. This keeps the proving cost of a transaction the same whether its calls run on
+/// the VM or natively. Sierra code pays the gas cost of every builtin instance it uses, so the
+/// proving cost is at most the gas times the highest ratio of a builtin's proving weight to its gas
+/// cost.
+pub fn sierra_gas_to_proving_cost(
+    sierra_gas: GasAmount,
+    versioned_constants: &VersionedConstants,
+) -> usize {
+    let gas_costs = &versioned_constants.os_constants.gas_costs.builtins;
+
+    let proving_cost = versioned_constants
+        .builtin_proving_weights
+        .iter()
+        .filter_map(|(builtin, &builtin_weight)| {
+            // Builtins without a gas cost are not used by Sierra code.
+            let builtin_gas_cost = gas_costs.get_builtin_gas_cost(builtin).ok()?;
+            (builtin_gas_cost > 0).then(|| {
+                (u128::from(sierra_gas.0) * u128::from(builtin_weight))
+                    .div_ceil(u128::from(builtin_gas_cost))
+            })
+        })
+        .max()
+        .unwrap_or_default();
+
+    usize::try_from(proving_cost).expect("The proving cost of Sierra gas should fit in a usize.")
+}
+
 pub fn get_tx_weights<S: StateReader>(
     state_reader: &S,
     executed_class_hashes: &HashSet<ClassHash>,
@@ -428,8 +598,11 @@ pub fn get_tx_weights<S: StateReader>(
     let patrticia_update_resources = get_particia_update_resources(n_visited_storage_entries);
 
     let vm_resources = &patrticia_update_resources + &tx_resources.computation.vm_resources;
+    let vm_resources_proving_cost =
+        builtins_to_proving_cost(&vm_resources.prover_builtins(), versioned_constants);
     let vm_resources_gas = vm_resources_to_sierra_gas(vm_resources, versioned_constants);
     let sierra_gas = tx_resources.computation.sierra_gas;
+    let sierra_gas_proving_cost = sierra_gas_to_proving_cost(sierra_gas, versioned_constants);
     let sierra_gas_without_casm_hash_computation =
         sierra_gas.checked_add(vm_resources_gas).unwrap_or_else(|| {
             panic!(
@@ -443,6 +616,17 @@ pub fn get_tx_weights<S: StateReader>(
     let total_casm_hash_computation_resources = class_hash_to_casm_hash_computation_resources
         .values()
         .fold(ExecutionResources::default(), |acc, resources| &acc + resources);
+    let proving_cost = [
+        vm_resources_proving_cost,
+        sierra_gas_proving_cost,
+        builtins_to_proving_cost(
+            &total_casm_hash_computation_resources.prover_builtins(),
+            versioned_constants,
+        ),
+    ]
+    .into_iter()
+    .try_fold(0_usize, usize::checked_add)
+    .expect("Addition overflow while adding proving costs.");
     let total_casm_hash_computation_gas =
         vm_resources_to_sierra_gas(total_casm_hash_computation_resources, versioned_constants);
     let sierra_gas = sierra_gas_without_casm_hash_computation
@@ -471,6 +655,7 @@ pub fn get_tx_weights<S: StateReader>(
         state_diff_size: get_onchain_data_segment_length(&state_changes_keys.count()),
         sierra_gas,
         n_txs: 1,
+        proving_cost,
     };
 
     Ok(TxWeights { bouncer_weights, casm_hash_computation_data })
@@ -1,6 +1,9 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
 
 use assert_matches::assert_matches;
+use cairo_vm::types::builtin_name::BuiltinName;
+use cairo_vm::vm::runners::cairo_runner::ExecutionResources;
 use rstest::{fixture, rstest};
 use starknet_api::execution_resources::GasAmount;
 use starknet_api::transaction::fields::Fee;
@@ -8,9 +11,13 @@ use starknet_api::{class_hash, contract_address, storage_key};
 
 use super::BouncerConfig;
 use crate::blockifier::transaction_executor::TransactionExecutorError;
+use crate::blockifier_versioned_constants::VersionedConstants;
 use crate::bouncer::{
+    get_tx_weights,
+    sierra_gas_to_proving_cost,
     verify_tx_weights_within_max_capacity,
     Bouncer,
+    BouncerDimension,
     BouncerWeights,
     CasmHashComputationData,
     TxWeights,
@@ -42,6 +49,7 @@ fn block_max_capacity() -> BouncerWeights {
         state_diff_size: 20,
         sierra_gas: GasAmount(20),
         n_txs: 20,
+        proving_cost: 20,
     }
 }
 
@@ -59,6 +67,7 @@ fn test_block_weights_has_room_sierra_gas(block_max_capacity: BouncerWeights) {
         state_diff_size: 7,
         sierra_gas: GasAmount(7),
         n_txs: 7,
+        proving_cost: 7,
     };
 
     assert!(block_max_capacity.has_room(bouncer_weights));
@@ -70,6 +79,7 @@ fn test_block_weights_has_room_sierra_gas(block_max_capacity: BouncerWeights) {
         state_diff_size: 5,
         sierra_gas: GasAmount(25),
         n_txs: 5,
+        proving_cost: 5,
     };
 
     assert!(!block_max_capacity.has_room(bouncer_weights_exceeds_max));
@@ -91,11 +101,47 @@ fn test_block_weights_has_room_n_txs(
         state_diff_size: 7,
         sierra_gas: GasAmount(7),
         n_txs,
+        proving_cost: 7,
     };
 
     assert_eq!(block_max_capacity.has_room(bouncer_weights), has_room);
 }
 
+#[rstest]
+fn test_block_weights_utilization(block_max_capacity: BouncerWeights) {
+    let bouncer_weights = BouncerWeights {
+        l1_gas: 5,
+        message_segment_length: 10,
+        n_events: 0,
+        state_diff_size: 20,
+        sierra_gas: GasAmount(21),
+        n_txs: 1,
+        proving_cost: 10,
+    };
+
+    assert_eq!(
+        block_max_capacity.utilization(&bouncer_weights),
+        BTreeMap::from([
+            (BouncerDimension::L1Gas, 0.25),
+            (BouncerDimension::MessageSegmentLength, 0.5),
+            (BouncerDimension::NEvents, 0.0),
+            (BouncerDimension::StateDiffSize, 1.0),
+            (BouncerDimension::SierraGas, 1.05),
+            (BouncerDimension::NTxs, 0.05),
+            (BouncerDimension::ProvingCost, 0.5),
+        ])
+    );
+    assert_eq!(
+        block_max_capacity.exceeded_dimensions(&bouncer_weights),
+        vec![BouncerDimension::SierraGas]
+    );
+    // A dimension of zero capacity is fully utilized.
+    assert_eq!(
+        BouncerWeights::empty().utilization(&BouncerWeights::empty())[&BouncerDimension::NTxs],
+        1.0
+    );
+}
+
 #[rstest]
 #[case::empty_initial_bouncer(Bouncer::new(BouncerConfig::empty()))]
 #[case::non_empty_initial_bouncer(Bouncer {
@@ -114,12 +160,14 @@ fn test_block_weights_has_room_n_txs(
         state_diff_size: 10,
         sierra_gas: GasAmount(10),
         n_txs: 1,
+        proving_cost: 10,
     },
     casm_hash_computation_data: CasmHashComputationData{
         class_hash_to_casm_hash_computation_gas: HashMap::from([
         (class_hash!(0_u128), GasAmount(5))]),
         sierra_gas_without_casm_hash_computation: GasAmount(5),
-    }
+    },
+    closing_dimensions: Vec::new(),
 })]
 fn test_bouncer_update(#[case] initial_bouncer: Bouncer) {
     let execution_summary_to_update = ExecutionSummary {
@@ -138,6 +186,7 @@ fn test_bouncer_update(#[case] initial_bouncer: Bouncer) {
         state_diff_size: 2,
         sierra_gas: GasAmount(9),
         n_txs: 1,
+        proving_cost: 3,
     };
 
     let class_hash_to_casm_hash_computation_gas_to_update =
@@ -189,6 +238,7 @@ fn test_bouncer_try_update_sierra_gas(
         state_diff_size: 10,
         sierra_gas: GasAmount(10),
         n_txs: 10,
+        proving_cost: 10,
     };
 
     let mut bouncer = Bouncer { accumulated_weights, bouncer_config, ..Bouncer::empty() };
@@ -214,8 +264,12 @@ fn test_bouncer_try_update_sierra_gas(
         &block_context.versioned_constants,
     )
     .map_err(TransactionExecutorError::TransactionExecutionError);
-    let expected_weights =
-        BouncerWeights { sierra_gas: added_gas, n_txs: 1, ..BouncerWeights::empty() };
+    let expected_weights = BouncerWeights {
+        sierra_gas: added_gas,
+        n_txs: 1,
+        proving_cost: sierra_gas_to_proving_cost(added_gas, &block_context.versioned_constants),
+        ..BouncerWeights::empty()
+    };
 
     if result.is_ok() {
         // Try to update the bouncer.
@@ -253,6 +307,7 @@ fn test_bouncer_try_update_n_txs(
         state_diff_size: 10,
         sierra_gas: GasAmount(10),
         n_txs: 19,
+        proving_cost: 10,
     };
 
     let mut bouncer = Bouncer { accumulated_weights, bouncer_config, ..Bouncer::empty() };
@@ -287,4 +342,57 @@ fn test_bouncer_try_update_n_txs(
     );
 
     assert_matches!(result, Err(TransactionExecutorError::BlockFull));
+    let capacity_utilization = bouncer.get_capacity_utilization();
+    assert_eq!(capacity_utilization.closing_dimensions, vec![BouncerDimension::NTxs]);
+    assert_eq!(capacity_utilization.utilization[&BouncerDimension::NTxs], 1.0);
+    assert_eq!(capacity_utilization.utilization[&BouncerDimension::L1Gas], 0.5);
+}
+
+#[rstest]
+fn test_get_tx_weights_proving_cost(state: CachedState<DictStateReader>) {
+    let mut versioned_constants = VersionedConstants::create_for_testing();
+    versioned_constants.builtin_proving_weights =
+        Arc::new(HashMap::from([(BuiltinName::pedersen, 10), (BuiltinName::range_check, 1)]));
+    let tx_resources = TransactionResources {
+        computation: ComputationResources {
+            vm_resources: ExecutionResources {
+                builtin_instance_counter: HashMap::from([
+                    (BuiltinName::pedersen, 2),
+                    (BuiltinName::range_check, 3),
+                    // Builtins without a proving weight do not contribute to the proving cost.
+                    (BuiltinName::bitwise, 4),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let tx_weights = get_tx_weights(
+        &state,
+        &HashSet::new(),
+        0,
+        &tx_resources,
+        &StateChangesKeys::default(),
+        &versioned_constants,
+    )
+    .unwrap();
+    assert_eq!(tx_weights.bouncer_weights.proving_cost, 23);
+}
+
+#[test]
+fn test_sierra_gas_to_proving_cost() {
+    let mut versioned_constants = VersionedConstants::create_for_testing();
+    let builtin_gas_costs = versioned_constants.os_constants.gas_costs.builtins;
+    // The bound follows the builtin with the highest proving weight per unit of gas.
+    versioned_constants.builtin_proving_weights = Arc::new(HashMap::from([
+        (BuiltinName::pedersen, 2 * builtin_gas_costs.pedersen),
+        (BuiltinName::range_check, builtin_gas_costs.range_check),
+        // Builtins that Sierra code doesn't use are ignored.
+        (BuiltinName::segment_arena, 1000),
+    ]));
+
+    assert_eq!(sierra_gas_to_proving_cost(GasAmount(1000), &versioned_constants), 2000);
+    assert_eq!(sierra_gas_to_proving_cost(GasAmount::ZERO, &versioned_constants), 0);
 }
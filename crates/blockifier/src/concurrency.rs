@@ -1,5 +1,7 @@
+pub mod conflict_prediction;
 pub mod fee_utils;
 pub mod scheduler;
+pub mod stats;
 #[cfg(any(feature = "testing", test))]
 pub mod test_utils;
 pub mod utils;
@@ -0,0 +1,141 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use indexmap::IndexMap;
+use starknet_api::core::ContractAddress;
+
+use crate::state::cached_state::StorageEntry;
+use crate::transaction::objects::TransactionExecutionInfo;
+
+#[cfg(test)]
+#[path = "conflict_prediction_test.rs"]
+pub mod test;
+
+/// Predicts the storage entries a transaction is going to access, based on the storage entries
+/// accessed by the previously executed transactions of the same sender.
+#[derive(Debug)]
+pub struct AccessSetPredictor {
+    max_n_senders: usize,
+    // Ordered by recency of the last recorded execution, to evict the least recent senders first.
+    access_sets: IndexMap<ContractAddress, HashSet<StorageEntry>>,
+}
+
+/// The group of a transaction in a conflict-aware order; transactions of the same group are
+/// predicted to conflict with each other.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum ConflictGroup {
+    // Transactions without an account sender (i.e., L1 handlers) keep their relative order.
+    NoSender,
+    Sender(ContractAddress),
+    StorageEntry(StorageEntry),
+}
+
+impl AccessSetPredictor {
+    pub fn new(max_n_senders: usize) -> Self {
+        Self { max_n_senders, access_sets: IndexMap::new() }
+    }
+
+    /// Records the storage entries accessed by the execution of a transaction (excluding validation
+    /// and fee transfer) as the predicted access set of the following transactions of its sender.
+    pub fn record(
+        &mut self,
+        sender_address: ContractAddress,
+        tx_execution_info: &TransactionExecutionInfo,
+    ) {
+        let access_set: HashSet<StorageEntry> = tx_execution_info
+            .execute_call_info
+            .iter()
+            .flat_map(|call_info| call_info.iter())
+            .flat_map(|call_info| {
+                let storage_address = call_info.call.storage_address;
+                call_info
+                    .storage_access_tracker
+                    .accessed_storage_keys
+                    .iter()
+                    .map(move |&storage_key| (storage_address, storage_key))
+            })
+            .collect();
+
+        self.access_sets.shift_remove(&sender_address);
+        self.access_sets.insert(sender_address, access_set);
+        if self.access_sets.len() > self.max_n_senders {
+            self.access_sets.shift_remove_index(0);
+        }
+    }
+
+    pub fn predicted_access_set(
+        &self,
+        sender_address: ContractAddress,
+    ) -> Option<&HashSet<StorageEntry>> {
+        self.access_sets.get(&sender_address)
+    }
+
+    /// Returns a permutation of the transactions (given by their account senders, `None` for
+    /// transactions without one), that spreads out transactions predicted to access the same
+    /// storage entries, so they are less likely to be executed concurrently and abort each other.
+    ///
+    /// All the transactions of a sender belong to the same group, and the relative order inside a
+    /// group is kept, hence the per-account nonce order is preserved.
+    pub fn conflict_aware_order(&self, sender_addresses: &[Option<ContractAddress>]) -> Vec<usize> {
+        // Count, for each storage entry, the distinct senders predicted to access it.
+        let distinct_senders: HashSet<ContractAddress> =
+            sender_addresses.iter().flatten().copied().collect();
+        let mut n_accessing_senders: HashMap<StorageEntry, usize> = HashMap::new();
+        for access_set in
+            distinct_senders.iter().filter_map(|&sender| self.predicted_access_set(sender))
+        {
+            for &storage_entry in access_set {
+                *n_accessing_senders.entry(storage_entry).or_default() += 1;
+            }
+        }
+
+        // Assign each transaction to the group of its most contended predicted storage entry.
+        let mut groups: IndexMap<ConflictGroup, VecDeque<usize>> = IndexMap::new();
+        for (tx_index, sender_address) in sender_addresses.iter().enumerate() {
+            let group = match sender_address {
+                None => ConflictGroup::NoSender,
+                Some(sender_address) => self
+                    .predicted_access_set(*sender_address)
+                    .into_iter()
+                    .flatten()
+                    .map(|storage_entry| (n_accessing_senders[storage_entry], storage_entry))
+                    .filter(|&(n_senders, _)| n_senders > 1)
+                    .max_by(|(n_senders_a, entry_a), (n_senders_b, entry_b)| {
+                        n_senders_a.cmp(n_senders_b).then_with(|| entry_b.cmp(entry_a))
+                    })
+                    .map_or(ConflictGroup::Sender(*sender_address), |(_, &storage_entry)| {
+                        ConflictGroup::StorageEntry(storage_entry)
+                    }),
+            };
+            groups.entry(group).or_default().push_back(tx_index);
+        }
+
+        // Keep the given order if no conflicts are predicted.
+        if !groups.keys().any(|group| matches!(group, ConflictGroup::StorageEntry(_))) {
+            return (0..sender_addresses.len()).collect();
+        }
+
+        // Interleave the groups, taking one transaction of each group in turn.
+        let mut order = Vec::with_capacity(sender_addresses.len());
+        let mut groups: Vec<VecDeque<usize>> = groups.into_values().collect();
+        while !groups.is_empty() {
+            groups.retain_mut(|group| match group.pop_front() {
+                Some(tx_index) => {
+                    order.push(tx_index);
+                    true
+                }
+                None => false,
+            });
+        }
+        order
+    }
+}
+
+/// Reorders the given items according to a permutation of their indices.
+pub fn apply_order<T>(items: Vec<T>, order: &[usize]) -> Vec<T> {
+    assert_eq!(items.len(), order.len(), "The order must be a permutation of the items.");
+    let mut items: Vec<Option<T>> = items.into_iter().map(Some).collect();
+    order
+        .iter()
+        .map(|&index| items[index].take().expect("The order must be a permutation of the items."))
+        .collect()
+}
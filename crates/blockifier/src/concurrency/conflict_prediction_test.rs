@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+use starknet_api::core::ContractAddress;
+use starknet_api::state::StorageKey;
+use starknet_api::{contract_address, storage_key};
+
+use crate::concurrency::conflict_prediction::{apply_order, AccessSetPredictor};
+use crate::execution::call_info::{CallInfo, StorageAccessTracker};
+use crate::execution::entry_point::CallEntryPoint;
+use crate::transaction::objects::TransactionExecutionInfo;
+
+const AMM_POOL: &str = "0x100";
+
+/// Returns an execution info of a transaction calling `contract_address`, which accesses the given
+/// storage keys of it in an inner call.
+fn execution_info_accessing(
+    contract_address: ContractAddress,
+    storage_keys: &[StorageKey],
+) -> TransactionExecutionInfo {
+    let inner_call = CallInfo {
+        call: CallEntryPoint { storage_address: contract_address, ..Default::default() },
+        storage_access_tracker: StorageAccessTracker {
+            accessed_storage_keys: storage_keys.iter().copied().collect(),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    TransactionExecutionInfo {
+        execute_call_info: Some(CallInfo { inner_calls: vec![inner_call], ..Default::default() }),
+        ..Default::default()
+    }
+}
+
+#[rstest]
+fn test_record_access_set() {
+    let sender = contract_address!("0x1");
+    let mut predictor = AccessSetPredictor::new(2);
+    assert_eq!(predictor.predicted_access_set(sender), None);
+
+    predictor.record(
+        sender,
+        &execution_info_accessing(contract_address!(AMM_POOL), &[storage_key!("0x7")]),
+    );
+    assert_eq!(
+        predictor.predicted_access_set(sender),
+        Some(&HashSet::from([(contract_address!(AMM_POOL), storage_key!("0x7"))]))
+    );
+
+    // The latest execution of a sender overrides its prediction.
+    predictor.record(sender, &execution_info_accessing(contract_address!(AMM_POOL), &[]));
+    assert_eq!(predictor.predicted_access_set(sender), Some(&HashSet::new()));
+
+    // The least recent sender is evicted when the capacity is exceeded.
+    predictor.record(contract_address!("0x2"), &TransactionExecutionInfo::default());
+    predictor.record(contract_address!("0x3"), &TransactionExecutionInfo::default());
+    assert_eq!(predictor.predicted_access_set(sender), None);
+    assert!(predictor.predicted_access_set(contract_address!("0x3")).is_some());
+}
+
+#[rstest]
+fn test_conflict_aware_order() {
+    let pool_swapper_0 = contract_address!("0x1");
+    let pool_swapper_1 = contract_address!("0x2");
+    let pool_swapper_2 = contract_address!("0x3");
+    let unknown_sender = contract_address!("0x4");
+    let independent_sender = contract_address!("0x5");
+
+    let mut predictor = AccessSetPredictor::new(10);
+    let pool_reserves = [storage_key!("0x10"), storage_key!("0x11")];
+    for pool_swapper in [pool_swapper_0, pool_swapper_1, pool_swapper_2] {
+        predictor.record(
+            pool_swapper,
+            &execution_info_accessing(contract_address!(AMM_POOL), &pool_reserves),
+        );
+    }
+    predictor.record(
+        independent_sender,
+        &execution_info_accessing(contract_address!("0x200"), &[storage_key!("0x10")]),
+    );
+
+    let sender_addresses = [
+        Some(pool_swapper_0),
+        Some(pool_swapper_0),
+        Some(pool_swapper_1),
+        Some(pool_swapper_2),
+        None,
+        Some(unknown_sender),
+        Some(independent_sender),
+        None,
+    ];
+    let order = predictor.conflict_aware_order(&sender_addresses);
+
+    // Groups, by first appearance: the pool swappers [0, 1, 2, 3], L1 handlers [4, 7], and the
+    // singleton groups of the unknown [5] and the independent [6] senders.
+    assert_eq!(order, vec![0, 4, 5, 6, 1, 7, 2, 3]);
+}
+
+#[rstest]
+fn test_conflict_aware_order_without_history() {
+    let predictor = AccessSetPredictor::new(10);
+    let sender_addresses =
+        [Some(contract_address!("0x1")), Some(contract_address!("0x1")), None, None];
+    assert_eq!(predictor.conflict_aware_order(&sender_addresses), vec![0, 1, 2, 3]);
+}
+
+#[rstest]
+fn test_apply_order() {
+    assert_eq!(apply_order(vec!['a', 'b', 'c'], &[2, 0, 1]), vec!['c', 'a', 'b']);
+}
+
+#[rstest]
+#[should_panic(expected = "The order must be a permutation of the items.")]
+fn test_apply_order_not_a_permutation() {
+    apply_order(vec!['a', 'b', 'c'], &[0, 0, 1]);
+}
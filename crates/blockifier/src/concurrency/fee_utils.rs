@@ -54,7 +54,9 @@ pub fn complete_fee_transfer_flow(
         add_fee_to_sequencer_balance(
             tx_context.fee_token_address(),
             state,
-            tx_execution_info.receipt.fee,
+            tx_context
+                .fee_in_fee_token(tx_execution_info.receipt.fee)
+                .expect("The fee was already converted when transferring it."),
             &tx_context.block_context,
             sequencer_balance,
             tx_context.tx_info.sender_address(),
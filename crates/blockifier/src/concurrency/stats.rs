@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::state::cached_state::StorageEntry;
+
+#[cfg(test)]
+#[path = "stats_test.rs"]
+pub mod test;
+
+/// Statistics of a concurrent (Block-STM) execution, accumulated over all the chunks of a block.
+/// Used to tune the concurrency config (chunk size and number of workers) on real load.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConcurrencyStats {
+    pub n_chunks: usize,
+    pub n_executions: usize,
+    pub n_validations: usize,
+    // Aborts of validation tasks.
+    pub n_aborts: usize,
+    // Failed re-validations in the commit phase (each triggers a re-execution).
+    pub n_aborts_in_commit: usize,
+    // The number of times each committed transaction of the block was executed, in block order.
+    pub tx_execution_counts: Vec<usize>,
+    // The number of aborts caused by each storage entry whose read value became stale.
+    pub conflicting_storage_keys: HashMap<StorageEntry, usize>,
+    // Cumulative time spent by all workers in each phase. Note that the commit phase includes
+    // re-executions of transactions that failed re-validation.
+    pub execute_duration: Duration,
+    pub validate_duration: Duration,
+    pub commit_duration: Duration,
+}
+
+impl ConcurrencyStats {
+    /// Merges the statistics of a subsequent chunk into the statistics of the block.
+    pub fn extend(&mut self, chunk_stats: ConcurrencyStats) {
+        self.n_chunks += chunk_stats.n_chunks;
+        self.n_executions += chunk_stats.n_executions;
+        self.n_validations += chunk_stats.n_validations;
+        self.n_aborts += chunk_stats.n_aborts;
+        self.n_aborts_in_commit += chunk_stats.n_aborts_in_commit;
+        self.tx_execution_counts.extend(chunk_stats.tx_execution_counts);
+        for (storage_entry, n_aborts) in chunk_stats.conflicting_storage_keys {
+            *self.conflicting_storage_keys.entry(storage_entry).or_default() += n_aborts;
+        }
+        self.execute_duration += chunk_stats.execute_duration;
+        self.validate_duration += chunk_stats.validate_duration;
+        self.commit_duration += chunk_stats.commit_duration;
+    }
+
+    /// Returns the number of re-executions of the committed transactions.
+    pub fn n_reexecutions(&self) -> usize {
+        self.tx_execution_counts.iter().map(|n_executions| n_executions.saturating_sub(1)).sum()
+    }
+
+    /// Returns the maximal number of executions of a single committed transaction.
+    pub fn max_executions_per_tx(&self) -> usize {
+        self.tx_execution_counts.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Returns the (at most) `n` storage entries that caused the most aborts, in descending order.
+    pub fn most_conflicting_storage_keys(&self, n: usize) -> Vec<(StorageEntry, usize)> {
+        let mut conflicting_storage_keys: Vec<_> =
+            self.conflicting_storage_keys.iter().map(|(&key, &count)| (key, count)).collect();
+        conflicting_storage_keys.sort_by(|(key_a, count_a), (key_b, count_b)| {
+            count_b.cmp(count_a).then_with(|| key_a.cmp(key_b))
+        });
+        conflicting_storage_keys.truncate(n);
+        conflicting_storage_keys
+    }
+}
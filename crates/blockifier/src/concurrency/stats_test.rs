@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use pretty_assertions::assert_eq;
+use starknet_api::{contract_address, storage_key};
+
+use crate::concurrency::stats::ConcurrencyStats;
+
+#[test]
+fn test_extend_and_summarize() {
+    let hot_key = (contract_address!("0x1"), storage_key!("0x10"));
+    let cold_key = (contract_address!("0x2"), storage_key!("0x20"));
+    let mut block_stats = ConcurrencyStats::default();
+    block_stats.extend(ConcurrencyStats {
+        n_chunks: 1,
+        n_executions: 4,
+        n_validations: 5,
+        n_aborts: 1,
+        n_aborts_in_commit: 0,
+        tx_execution_counts: vec![1, 2, 1],
+        conflicting_storage_keys: HashMap::from([(hot_key, 1)]),
+        execute_duration: Duration::from_millis(3),
+        validate_duration: Duration::from_millis(2),
+        commit_duration: Duration::from_millis(1),
+    });
+    block_stats.extend(ConcurrencyStats {
+        n_chunks: 1,
+        n_executions: 2,
+        n_validations: 2,
+        n_aborts: 0,
+        n_aborts_in_commit: 2,
+        tx_execution_counts: vec![3],
+        conflicting_storage_keys: HashMap::from([(hot_key, 1), (cold_key, 1)]),
+        execute_duration: Duration::from_millis(1),
+        validate_duration: Duration::from_millis(1),
+        commit_duration: Duration::from_millis(4),
+    });
+
+    assert_eq!(block_stats.n_chunks, 2);
+    assert_eq!(block_stats.n_executions, 6);
+    assert_eq!(block_stats.n_validations, 7);
+    assert_eq!(block_stats.n_aborts, 1);
+    assert_eq!(block_stats.n_aborts_in_commit, 2);
+    assert_eq!(block_stats.tx_execution_counts, vec![1, 2, 1, 3]);
+    assert_eq!(block_stats.n_reexecutions(), 3);
+    assert_eq!(block_stats.max_executions_per_tx(), 3);
+    assert_eq!(block_stats.most_conflicting_storage_keys(1), vec![(hot_key, 2)]);
+    assert_eq!(block_stats.most_conflicting_storage_keys(5), vec![(hot_key, 2), (cold_key, 1)]);
+    assert_eq!(block_stats.execute_duration, Duration::from_millis(4));
+    assert_eq!(block_stats.validate_duration, Duration::from_millis(3));
+    assert_eq!(block_stats.commit_duration, Duration::from_millis(5));
+}
@@ -7,7 +7,7 @@ use starknet_types_core::felt::Felt;
 use crate::concurrency::versioned_storage::VersionedStorage;
 use crate::concurrency::TxIndex;
 use crate::execution::contract_class::RunnableCompiledClass;
-use crate::state::cached_state::{ContractClassMapping, StateMaps};
+use crate::state::cached_state::{ContractClassMapping, StateMaps, StorageEntry};
 use crate::state::errors::StateError;
 use crate::state::state_api::{StateReader, StateResult, UpdatableState};
 
@@ -134,6 +134,28 @@ impl<S: StateReader> VersionedState<S> {
         true
     }
 
+    /// Returns the storage entries in the read set whose values were changed by preceding
+    /// transactions, i.e., the storage conflicts that invalidate the read set.
+    fn conflicting_storage_keys(
+        &mut self,
+        tx_index: TxIndex,
+        reads: &StateMaps,
+    ) -> Vec<StorageEntry> {
+        if tx_index == 0 {
+            return Vec::new();
+        }
+        // Ignore values written by the current transaction.
+        let tx_index = tx_index - 1;
+        reads
+            .storage
+            .iter()
+            .filter(|&(&storage_entry, expected_value)| {
+                &self.storage.read(tx_index, storage_entry).expect(READ_ERR) != expected_value
+            })
+            .map(|(&storage_entry, _)| storage_entry)
+            .collect()
+    }
+
     fn apply_writes(
         &mut self,
         tx_index: TxIndex,
@@ -261,6 +283,10 @@ impl<S: StateReader> VersionedStateProxy<S> {
         self.state().validate_reads(self.tx_index, reads)
     }
 
+    pub fn conflicting_storage_keys(&self, reads: &StateMaps) -> Vec<StorageEntry> {
+        self.state().conflicting_storage_keys(self.tx_index, reads)
+    }
+
     pub fn delete_writes(&self, writes: &StateMaps, class_hash_to_class: &ContractClassMapping) {
         self.state().delete_writes(self.tx_index, writes, class_hash_to_class);
     }
@@ -383,6 +383,13 @@ fn test_false_validate_reads(
     let version_state_proxy = safe_versioned_state.pin_version(0);
     version_state_proxy.state().apply_writes(0, &tx_0_writes, &HashMap::default());
     assert!(!safe_versioned_state.pin_version(1).validate_reads(&tx_1_reads));
+
+    // Only storage writes are reported as conflicting storage keys.
+    let expected_conflicting_storage_keys: Vec<_> = tx_0_writes.storage.keys().copied().collect();
+    assert_eq!(
+        safe_versioned_state.pin_version(1).conflicting_storage_keys(&tx_1_reads),
+        expected_conflicting_storage_keys
+    );
 }
 
 #[rstest]
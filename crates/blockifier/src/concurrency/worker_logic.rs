@@ -1,20 +1,26 @@
 use std::collections::HashMap;
 use std::fmt::Debug;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::Mutex;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use super::versioned_state::VersionedState;
 use crate::blockifier::transaction_executor::TransactionExecutorError;
 use crate::bouncer::Bouncer;
 use crate::concurrency::fee_utils::complete_fee_transfer_flow;
 use crate::concurrency::scheduler::{Scheduler, Task};
+use crate::concurrency::stats::ConcurrencyStats;
 use crate::concurrency::utils::lock_mutex_in_array;
 use crate::concurrency::versioned_state::ThreadSafeVersionedState;
 use crate::concurrency::TxIndex;
 use crate::context::BlockContext;
-use crate::state::cached_state::{ContractClassMapping, StateMaps, TransactionalState};
+use crate::state::cached_state::{
+    ContractClassMapping,
+    StateMaps,
+    StorageEntry,
+    TransactionalState,
+};
 use crate::state::state_api::{StateReader, UpdatableState};
 use crate::transaction::objects::{TransactionExecutionInfo, TransactionExecutionResult};
 use crate::transaction::transaction_execution::Transaction;
@@ -34,15 +40,37 @@ pub struct ExecutionTaskOutput {
     pub result: TransactionExecutionResult<TransactionExecutionInfo>,
 }
 
-#[derive(Default)]
 pub struct ConcurrencyMetrics {
     abort_counter: AtomicUsize,
     abort_in_commit_counter: AtomicUsize,
     execute_counter: AtomicUsize,
     validate_counter: AtomicUsize,
+    // The number of executions of each transaction in the chunk, including re-executions during
+    // the commit phase.
+    tx_execution_counters: Box<[AtomicUsize]>,
+    conflicting_storage_keys: Mutex<HashMap<StorageEntry, usize>>,
+    execute_nanos: AtomicU64,
+    validate_nanos: AtomicU64,
+    commit_nanos: AtomicU64,
 }
 
 impl ConcurrencyMetrics {
+    pub fn new(chunk_size: usize) -> Self {
+        Self {
+            abort_counter: AtomicUsize::new(0),
+            abort_in_commit_counter: AtomicUsize::new(0),
+            execute_counter: AtomicUsize::new(0),
+            validate_counter: AtomicUsize::new(0),
+            tx_execution_counters: std::iter::repeat_with(|| AtomicUsize::new(0))
+                .take(chunk_size)
+                .collect(),
+            conflicting_storage_keys: Mutex::new(HashMap::new()),
+            execute_nanos: AtomicU64::new(0),
+            validate_nanos: AtomicU64::new(0),
+            commit_nanos: AtomicU64::new(0),
+        }
+    }
+
     pub fn count_abort(&self) {
         self.abort_counter.fetch_add(1, Ordering::Relaxed);
     }
@@ -55,6 +83,25 @@ impl ConcurrencyMetrics {
     pub fn count_validate(&self) {
         self.validate_counter.fetch_add(1, Ordering::Relaxed);
     }
+    pub fn count_tx_execution(&self, tx_index: TxIndex) {
+        self.tx_execution_counters[tx_index].fetch_add(1, Ordering::Relaxed);
+    }
+    pub fn count_conflicting_storage_keys(&self, storage_keys: Vec<StorageEntry>) {
+        let mut conflicting_storage_keys =
+            self.conflicting_storage_keys.lock().expect("Failed to lock conflicting keys.");
+        for storage_key in storage_keys {
+            *conflicting_storage_keys.entry(storage_key).or_default() += 1;
+        }
+    }
+    pub fn add_execute_duration(&self, duration: Duration) {
+        self.execute_nanos.fetch_add(duration_as_nanos(duration), Ordering::Relaxed);
+    }
+    pub fn add_validate_duration(&self, duration: Duration) {
+        self.validate_nanos.fetch_add(duration_as_nanos(duration), Ordering::Relaxed);
+    }
+    pub fn add_commit_duration(&self, duration: Duration) {
+        self.commit_nanos.fetch_add(duration_as_nanos(duration), Ordering::Relaxed);
+    }
     pub fn get_metrics(&self) -> (usize, usize, usize, usize) {
         (
             self.abort_counter.load(Ordering::Relaxed),
@@ -63,6 +110,35 @@ impl ConcurrencyMetrics {
             self.validate_counter.load(Ordering::Relaxed),
         )
     }
+
+    /// Returns the statistics of the chunk, considering only the first `n_committed_txs`
+    /// transactions for the per-transaction statistics.
+    pub fn get_chunk_stats(&self, n_committed_txs: usize) -> ConcurrencyStats {
+        let (n_aborts, n_aborts_in_commit, n_executions, n_validations) = self.get_metrics();
+        ConcurrencyStats {
+            n_chunks: 1,
+            n_executions,
+            n_validations,
+            n_aborts,
+            n_aborts_in_commit,
+            tx_execution_counts: self.tx_execution_counters[..n_committed_txs]
+                .iter()
+                .map(|counter| counter.load(Ordering::Relaxed))
+                .collect(),
+            conflicting_storage_keys: self
+                .conflicting_storage_keys
+                .lock()
+                .expect("Failed to lock conflicting keys.")
+                .clone(),
+            execute_duration: Duration::from_nanos(self.execute_nanos.load(Ordering::Relaxed)),
+            validate_duration: Duration::from_nanos(self.validate_nanos.load(Ordering::Relaxed)),
+            commit_duration: Duration::from_nanos(self.commit_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+fn duration_as_nanos(duration: Duration) -> u64 {
+    u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX)
 }
 
 pub struct WorkerExecutor<'a, S: StateReader> {
@@ -85,7 +161,7 @@ impl<'a, S: StateReader> WorkerExecutor<'a, S> {
         let scheduler = Scheduler::new(chunk.len());
         let execution_outputs =
             std::iter::repeat_with(|| Mutex::new(None)).take(chunk.len()).collect();
-        let metrics = ConcurrencyMetrics::default();
+        let metrics = ConcurrencyMetrics::new(chunk.len());
 
         WorkerExecutor {
             scheduler,
@@ -110,7 +186,7 @@ impl<'a, S: StateReader> WorkerExecutor<'a, S> {
         let scheduler = Scheduler::new(chunk.len());
         let execution_outputs =
             std::iter::repeat_with(|| Mutex::new(None)).take(chunk.len()).collect();
-        let metrics = ConcurrencyMetrics::default();
+        let metrics = ConcurrencyMetrics::new(chunk.len());
 
         WorkerExecutor {
             scheduler,
@@ -148,7 +224,9 @@ impl<'a, S: StateReader> WorkerExecutor<'a, S> {
     fn commit_while_possible(&self) {
         if let Some(mut tx_committer) = self.scheduler.try_enter_commit_phase() {
             while let Some(tx_index) = tx_committer.try_commit() {
+                let start_time = Instant::now();
                 let commit_succeeded = self.commit_tx(tx_index);
+                self.metrics.add_commit_duration(start_time.elapsed());
                 if !commit_succeeded {
                     tx_committer.halt_scheduler();
                 }
@@ -158,11 +236,14 @@ impl<'a, S: StateReader> WorkerExecutor<'a, S> {
 
     fn execute(&self, tx_index: TxIndex) {
         self.metrics.count_execute();
+        let start_time = Instant::now();
         self.execute_tx(tx_index);
+        self.metrics.add_execute_duration(start_time.elapsed());
         self.scheduler.finish_execution(tx_index)
     }
 
     fn execute_tx(&self, tx_index: TxIndex) {
+        self.metrics.count_tx_execution(tx_index);
         let mut tx_versioned_state = self.state.pin_version(tx_index);
         let tx = &self.chunk[tx_index];
         // TODO(Yoni): is it necessary to use a transactional state here?
@@ -200,6 +281,13 @@ impl<'a, S: StateReader> WorkerExecutor<'a, S> {
 
     fn validate(&self, tx_index: TxIndex) -> Task {
         self.metrics.count_validate();
+        let start_time = Instant::now();
+        let task = self.validate_inner(tx_index);
+        self.metrics.add_validate_duration(start_time.elapsed());
+        task
+    }
+
+    fn validate_inner(&self, tx_index: TxIndex) -> Task {
         let tx_versioned_state = self.state.pin_version(tx_index);
         let execution_output = lock_mutex_in_array(&self.execution_outputs, tx_index);
         let execution_output = execution_output.as_ref().expect(EXECUTION_OUTPUTS_UNWRAP_ERROR);
@@ -209,6 +297,8 @@ impl<'a, S: StateReader> WorkerExecutor<'a, S> {
         let aborted = !reads_valid && self.scheduler.try_validation_abort(tx_index);
         if aborted {
             self.metrics.count_abort();
+            self.metrics
+                .count_conflicting_storage_keys(tx_versioned_state.conflicting_storage_keys(reads));
             tx_versioned_state
                 .delete_writes(&execution_output.state_diff, &execution_output.contract_classes);
             self.scheduler.finish_abort(tx_index)
@@ -241,6 +331,8 @@ impl<'a, S: StateReader> WorkerExecutor<'a, S> {
         if !reads_valid {
             // Revalidate failed: re-execute the transaction.
             self.metrics.count_abort_in_commit();
+            self.metrics
+                .count_conflicting_storage_keys(tx_versioned_state.conflicting_storage_keys(reads));
             tx_versioned_state.delete_writes(
                 &execution_output_ref.state_diff,
                 &execution_output_ref.contract_classes,
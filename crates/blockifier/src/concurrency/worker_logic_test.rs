@@ -527,6 +527,14 @@ fn test_worker_validate(default_all_resource_bounds: ValidResourceBounds) {
 
     let next_task2 = worker_executor.validate(tx_index);
     assert_eq!(next_task2, Task::AskForTask);
+
+    // Verify the abort and its cause are reflected in the statistics.
+    let chunk_stats = worker_executor.metrics.get_chunk_stats(txs.len());
+    assert_eq!(chunk_stats.n_aborts, 1);
+    assert_eq!(chunk_stats.n_validations, 3);
+    assert_eq!(chunk_stats.tx_execution_counts, vec![1, 1]);
+    // The abort was caused by a stale nonce read, which is not a storage conflict.
+    assert!(chunk_stats.conflicting_storage_keys.is_empty());
 }
 
 #[rstest]
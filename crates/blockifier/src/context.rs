@@ -1,9 +1,12 @@
 use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::sync::Arc;
 
 use apollo_config::dumping::{prepend_sub_config_name, ser_param, SerializeConfig};
 use apollo_config::{ParamPath, ParamPrivacyInput, SerializedParam};
-use serde::{Deserialize, Serialize};
+use num_rational::Ratio;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use starknet_api::block::{BlockInfo, BlockNumber, BlockTimestamp, FeeType, GasPriceVector};
 use starknet_api::core::{ChainId, ContractAddress};
 use starknet_api::execution_resources::GasAmount;
@@ -14,11 +17,15 @@ use starknet_api::transaction::fields::{
     Tip,
     ValidResourceBounds,
 };
+use starknet_api::transaction::TransactionVersion;
+use starknet_types_core::felt::Felt;
 
 use crate::blockifier_versioned_constants::VersionedConstants;
 use crate::bouncer::BouncerConfig;
 use crate::execution::call_info::CallInfo;
 use crate::execution::common_hints::ExecutionMode;
+use crate::fee::resources::TransactionFeeResult;
+use crate::transaction::errors::TransactionFeeError;
 use crate::transaction::objects::{
     CurrentTransactionInfo,
     HasRelatedFeeType,
@@ -33,8 +40,15 @@ pub struct TransactionContext {
 }
 
 impl TransactionContext {
+    pub fn fee_token(&self) -> &FeeToken {
+        self.block_context.fee_token_registry.get_by_tx_version(self.tx_info.version())
+    }
     pub fn fee_token_address(&self) -> ContractAddress {
-        self.block_context.chain_info.fee_token_address(&self.tx_info.fee_type())
+        self.fee_token().address
+    }
+    /// Returns the amount of the transaction's fee token charged for the given fee.
+    pub fn fee_in_fee_token(&self, fee: Fee) -> TransactionFeeResult<Fee> {
+        self.fee_token().convert_fee(fee)
     }
     pub fn is_sequencer_the_sender(&self) -> bool {
         self.tx_info.sender_address() == self.block_context.block_info.sequencer_address
@@ -121,6 +135,7 @@ pub struct BlockContext {
     pub(crate) chain_info: ChainInfo,
     pub(crate) versioned_constants: VersionedConstants,
     pub(crate) bouncer_config: BouncerConfig,
+    pub(crate) fee_token_registry: FeeTokenRegistry,
 }
 
 impl BlockContext {
@@ -130,7 +145,14 @@ impl BlockContext {
         versioned_constants: VersionedConstants,
         bouncer_config: BouncerConfig,
     ) -> Self {
-        BlockContext { block_info, chain_info, versioned_constants, bouncer_config }
+        let fee_token_registry = FeeTokenRegistry::from(&chain_info);
+        BlockContext {
+            block_info,
+            chain_info,
+            versioned_constants,
+            bouncer_config,
+            fee_token_registry,
+        }
     }
 
     pub fn block_info(&self) -> &BlockInfo {
@@ -145,6 +167,10 @@ impl BlockContext {
         &self.versioned_constants
     }
 
+    pub fn fee_token_registry(&self) -> &FeeTokenRegistry {
+        &self.fee_token_registry
+    }
+
     pub fn to_tx_context(
         &self,
         tx_info_creator: &impl TransactionInfoCreator,
@@ -200,6 +226,8 @@ impl BlockContext {
 pub struct ChainInfo {
     pub chain_id: ChainId,
     pub fee_token_addresses: FeeTokenAddresses,
+    #[serde(default)]
+    pub additional_fee_tokens: AdditionalFeeTokens,
 }
 
 impl ChainInfo {
@@ -217,18 +245,30 @@ impl Default for ChainInfo {
             // TODO(guyn): should we remove the default value for chain_id?
             chain_id: ChainId::Other("0x0".to_string()),
             fee_token_addresses: FeeTokenAddresses::default(),
+            additional_fee_tokens: AdditionalFeeTokens::default(),
         }
     }
 }
 
 impl SerializeConfig for ChainInfo {
     fn dump(&self) -> BTreeMap<ParamPath, SerializedParam> {
-        let members = BTreeMap::from_iter([ser_param(
-            "chain_id",
-            &self.chain_id,
-            "The chain ID of the StarkNet chain.",
-            ParamPrivacyInput::Public,
-        )]);
+        let members = BTreeMap::from_iter([
+            ser_param(
+                "chain_id",
+                &self.chain_id,
+                "The chain ID of the StarkNet chain.",
+                ParamPrivacyInput::Public,
+            ),
+            ser_param(
+                "additional_fee_tokens",
+                &self.additional_fee_tokens,
+                "Fee tokens overriding the ETH and STRK fee tokens, as comma-separated \
+                 'version:address:rate' items: transactions of the version and above are charged \
+                 in the token at the address, rate (an integer or a fraction 'n/d') units of the \
+                 token per unit of the fee.",
+                ParamPrivacyInput::Public,
+            ),
+        ]);
 
         vec![
             members,
@@ -273,3 +313,156 @@ impl SerializeConfig for FeeTokenAddresses {
         ])
     }
 }
+
+/// A token in which transaction fees are charged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeToken {
+    pub address: ContractAddress,
+    /// The amount of the token charged per unit of the fee, as computed by the gas prices of the
+    /// transaction's fee type (i.e., per WEI or FRI).
+    pub conversion_rate: Ratio<u128>,
+}
+
+impl FeeToken {
+    /// Returns a token charged one-to-one with the fee.
+    pub fn new(address: ContractAddress) -> Self {
+        Self { address, conversion_rate: Ratio::from_integer(1) }
+    }
+
+    /// Converts a fee to the amount of the token charged for it, rounding up.
+    pub fn convert_fee(&self, fee: Fee) -> TransactionFeeResult<Fee> {
+        let (numer, denom) = (*self.conversion_rate.numer(), *self.conversion_rate.denom());
+        let fee_times_numer =
+            fee.0.checked_mul(numer).ok_or(TransactionFeeError::FeeConversionOverflow {
+                fee,
+                conversion_rate: self.conversion_rate,
+            })?;
+        Ok(Fee(fee_times_numer.div_ceil(denom)))
+    }
+}
+
+/// The tokens in which transaction fees are charged, keyed by transaction version: a transaction
+/// is charged in the token registered for the highest version not above its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeeTokenRegistry {
+    tokens: BTreeMap<TransactionVersion, FeeToken>,
+}
+
+impl FeeTokenRegistry {
+    /// Creates a registry charging all transaction versions in the given token.
+    pub fn new(base_token: FeeToken) -> Self {
+        Self { tokens: BTreeMap::from([(TransactionVersion::ZERO, base_token)]) }
+    }
+
+    /// Charges transactions of the given version and above (up to the next registered version) in
+    /// the given token.
+    pub fn register(&mut self, from_version: TransactionVersion, token: FeeToken) {
+        self.tokens.insert(from_version, token);
+    }
+
+    pub fn get_by_tx_version(&self, version: TransactionVersion) -> &FeeToken {
+        self.tokens
+            .range(..=version)
+            .next_back()
+            .map(|(_, token)| token)
+            .expect("The base fee token is registered for version zero.")
+    }
+}
+
+/// Charges deprecated transactions in ETH and V3 transactions in STRK.
+impl From<&FeeTokenAddresses> for FeeTokenRegistry {
+    fn from(fee_token_addresses: &FeeTokenAddresses) -> Self {
+        let mut registry = Self::new(FeeToken::new(fee_token_addresses.eth_fee_token_address));
+        registry.register(
+            TransactionVersion::THREE,
+            FeeToken::new(fee_token_addresses.strk_fee_token_address),
+        );
+        registry
+    }
+}
+
+/// Charges transactions in ETH and STRK, unless overridden by the chain's additional fee tokens.
+impl From<&ChainInfo> for FeeTokenRegistry {
+    fn from(chain_info: &ChainInfo) -> Self {
+        let mut registry = Self::from(&chain_info.fee_token_addresses);
+        for (from_version, token) in &chain_info.additional_fee_tokens.0 {
+            registry.register(*from_version, token.clone());
+        }
+        registry
+    }
+}
+
+/// Fee tokens registered on top of the ETH and STRK fee tokens, each with the lowest transaction
+/// version it charges. Serialized as comma-separated `version:address:rate` items, where the rate
+/// is either an integer or a fraction `numerator/denominator`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AdditionalFeeTokens(pub Vec<(TransactionVersion, FeeToken)>);
+
+impl AdditionalFeeTokens {
+    pub fn addresses(&self) -> impl Iterator<Item = ContractAddress> + '_ {
+        self.0.iter().map(|(_, token)| token.address)
+    }
+}
+
+impl FromStr for AdditionalFeeTokens {
+    type Err = String;
+
+    fn from_str(raw: &str) -> Result<Self, Self::Err> {
+        let parse_item = |item: &str| {
+            let [version, address, conversion_rate] =
+                item.trim().split(':').collect::<Vec<_>>()[..]
+            else {
+                return Err(format!("Expected 'version:address:rate', got '{item}'."));
+            };
+            let version = version
+                .parse::<u64>()
+                .map_err(|e| format!("Invalid transaction version '{version}': {e}."))?;
+            let address = Felt::from_hex(address)
+                .map_err(|e| e.to_string())
+                .and_then(|felt| ContractAddress::try_from(felt).map_err(|e| e.to_string()))
+                .map_err(|e| format!("Invalid fee token address '{address}': {e}"))?;
+            let conversion_rate = conversion_rate
+                .parse::<Ratio<u128>>()
+                .map_err(|e| format!("Invalid conversion rate '{conversion_rate}': {e}."))?;
+            if *conversion_rate.numer() == 0 {
+                return Err(format!("The conversion rate of fee token '{address}' is zero."));
+            }
+            Ok((TransactionVersion(Felt::from(version)), FeeToken { address, conversion_rate }))
+        };
+
+        if raw.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        raw.split(',').map(parse_item).collect::<Result<_, _>>().map(Self)
+    }
+}
+
+impl Display for AdditionalFeeTokens {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let items: Vec<_> = self
+            .0
+            .iter()
+            .map(|(version, token)| {
+                format!(
+                    "{}:{}:{}",
+                    version.0,
+                    token.address.0.key().to_hex_string(),
+                    token.conversion_rate
+                )
+            })
+            .collect();
+        write!(f, "{}", items.join(","))
+    }
+}
+
+impl Serialize for AdditionalFeeTokens {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for AdditionalFeeTokens {
+    fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+        String::deserialize(de)?.parse().map_err(serde::de::Error::custom)
+    }
+}
@@ -188,7 +188,11 @@ impl FeeCheckReport {
         if can_pay {
             return Ok(());
         }
-        Err(FeeCheckError::InsufficientFeeTokenBalance { fee, balance_low, balance_high })?
+        Err(FeeCheckError::InsufficientFeeTokenBalance {
+            fee: tx_context.fee_in_fee_token(fee)?,
+            balance_low,
+            balance_high,
+        })?
     }
 
     /// Checks that the actual resources used are within the bounds set by the sender.
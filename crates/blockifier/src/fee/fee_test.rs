@@ -1,11 +1,12 @@
 use assert_matches::assert_matches;
 use blockifier_test_utils::cairo_versions::CairoVersion;
+use blockifier_test_utils::calldata::create_trivial_calldata;
 use blockifier_test_utils::contracts::FeatureContract;
 use cairo_vm::types::builtin_name::BuiltinName;
+use num_rational::Ratio;
 use rstest::rstest;
 use starknet_api::block::{FeeType, GasPrice, NonzeroGasPrice};
 use starknet_api::execution_resources::{GasAmount, GasVector};
-use starknet_api::invoke_tx_args;
 use starknet_api::test_utils::{
     DEFAULT_ETH_L1_DATA_GAS_PRICE,
     DEFAULT_ETH_L1_GAS_PRICE,
@@ -23,20 +24,34 @@ use starknet_api::transaction::fields::{
     Tip,
     ValidResourceBounds,
 };
+use starknet_api::transaction::TransactionVersion;
+use starknet_api::{contract_address, felt, invoke_tx_args};
 
 use crate::blockifier::block::validated_gas_prices;
 use crate::blockifier_versioned_constants::VersionedConstants;
-use crate::context::BlockContext;
+use crate::context::{
+    AdditionalFeeTokens,
+    BlockContext,
+    FeeToken,
+    FeeTokenAddresses,
+    FeeTokenRegistry,
+};
 use crate::fee::fee_checks::{FeeCheckError, FeeCheckReportFields, PostExecutionReport};
 use crate::fee::fee_utils::{get_fee_by_gas_vector, get_vm_resources_cost};
 use crate::fee::receipt::TransactionReceipt;
+use crate::state::state_api::StateReader;
 use crate::test_utils::initial_test_state::test_state;
 use crate::test_utils::{gas_vector_from_vm_usage, get_vm_resource_usage, BALANCE};
+use crate::transaction::errors::TransactionFeeError;
 use crate::transaction::test_utils::{
     all_resource_bounds,
     block_context,
+    create_test_init_data,
+    default_all_resource_bounds,
     invoke_tx_with_default_flags,
     l1_resource_bounds,
+    run_invoke_tx,
+    TestInitData,
 };
 use crate::utils::u64_from_usize;
 
@@ -390,3 +405,119 @@ fn test_initial_sierra_gas(
     let actual = block_context.to_tx_context(&account_tx).initial_sierra_gas().0;
     assert_eq!(actual, expected)
 }
+
+#[rstest]
+fn test_fee_token_registry() {
+    let fee_token_addresses = FeeTokenAddresses {
+        eth_fee_token_address: contract_address!("0x1"),
+        strk_fee_token_address: contract_address!("0x2"),
+    };
+    let mut registry = FeeTokenRegistry::from(&fee_token_addresses);
+    assert_eq!(
+        registry.get_by_tx_version(TransactionVersion::ONE),
+        &FeeToken::new(contract_address!("0x1"))
+    );
+    assert_eq!(
+        registry.get_by_tx_version(TransactionVersion::THREE),
+        &FeeToken::new(contract_address!("0x2"))
+    );
+
+    let appchain_token =
+        FeeToken { address: contract_address!("0x3"), conversion_rate: Ratio::new(3, 2) };
+    registry.register(TransactionVersion::THREE, appchain_token.clone());
+    assert_eq!(
+        registry.get_by_tx_version(TransactionVersion::TWO),
+        &FeeToken::new(contract_address!("0x1"))
+    );
+    assert_eq!(registry.get_by_tx_version(TransactionVersion::THREE), &appchain_token);
+    // Later versions are charged in the token of the latest version registered below them.
+    assert_eq!(registry.get_by_tx_version(TransactionVersion(felt!(4_u8))), &appchain_token);
+
+    // The converted fee is rounded up.
+    assert_eq!(appchain_token.convert_fee(Fee(4)).unwrap(), Fee(6));
+    assert_eq!(appchain_token.convert_fee(Fee(5)).unwrap(), Fee(8));
+
+    let expensive_token =
+        FeeToken { address: contract_address!("0x4"), conversion_rate: Ratio::new(u128::MAX, 2) };
+    assert_matches!(
+        expensive_token.convert_fee(Fee(3)),
+        Err(TransactionFeeError::FeeConversionOverflow { fee: Fee(3), .. })
+    );
+}
+
+#[rstest]
+#[case::empty("", vec![])]
+#[case::integer_rate("3:0x3:2", vec![(TransactionVersion::THREE, Ratio::from_integer(2))])]
+#[case::fraction_rates(
+    "1:0x3:1/3, 3:0x3:3/2",
+    vec![(TransactionVersion::ONE, Ratio::new(1, 3)), (TransactionVersion::THREE, Ratio::new(3, 2))]
+)]
+fn test_additional_fee_tokens_config(
+    #[case] raw: &str,
+    #[case] expected_tokens: Vec<(TransactionVersion, Ratio<u128>)>,
+) {
+    let additional_fee_tokens: AdditionalFeeTokens = raw.parse().unwrap();
+    let expected = AdditionalFeeTokens(
+        expected_tokens
+            .into_iter()
+            .map(|(version, conversion_rate)| {
+                (version, FeeToken { address: contract_address!("0x3"), conversion_rate })
+            })
+            .collect(),
+    );
+    assert_eq!(additional_fee_tokens, expected);
+    assert_eq!(additional_fee_tokens.to_string().parse::<AdditionalFeeTokens>().unwrap(), expected);
+}
+
+#[rstest]
+#[case::missing_rate("3:0x3")]
+#[case::invalid_version("v3:0x3:1")]
+#[case::invalid_address("3:address:1")]
+#[case::zero_rate("3:0x3:0")]
+fn test_invalid_additional_fee_tokens_config(#[case] raw: &str) {
+    assert!(raw.parse::<AdditionalFeeTokens>().is_err());
+}
+
+#[rstest]
+fn test_charge_fee_in_registered_token(
+    block_context: BlockContext,
+    default_all_resource_bounds: ValidResourceBounds,
+) {
+    // Charge V3 transactions in the ETH token (which the test account is funded with) at a
+    // conversion rate different than one.
+    let mut chain_info = block_context.chain_info.clone();
+    let fee_token_address = chain_info.fee_token_addresses.eth_fee_token_address;
+    let fee_token = FeeToken { address: fee_token_address, conversion_rate: Ratio::new(3, 2) };
+    chain_info.additional_fee_tokens =
+        AdditionalFeeTokens(vec![(TransactionVersion::THREE, fee_token.clone())]);
+    let block_context = BlockContext::new(
+        block_context.block_info,
+        chain_info,
+        block_context.versioned_constants,
+        block_context.bouncer_config,
+    );
+
+    let TestInitData { mut state, account_address, contract_address, mut nonce_manager } =
+        create_test_init_data(&block_context.chain_info, CairoVersion::Cairo0);
+    let execution_info = run_invoke_tx(
+        &mut state,
+        &block_context,
+        invoke_tx_args! {
+            sender_address: account_address,
+            calldata: create_trivial_calldata(contract_address),
+            resource_bounds: default_all_resource_bounds,
+            nonce: nonce_manager.next(account_address),
+        },
+    )
+    .unwrap();
+
+    let charged_amount = fee_token.convert_fee(execution_info.receipt.fee).unwrap();
+    assert!(charged_amount > execution_info.receipt.fee);
+    let fee_transfer_call_info = execution_info.fee_transfer_call_info.unwrap();
+    assert_eq!(fee_transfer_call_info.call.storage_address, fee_token_address);
+    assert_eq!(fee_transfer_call_info.call.calldata.0[1], felt!(charged_amount.0));
+    assert_eq!(
+        state.get_fee_token_balance(account_address, fee_token_address).unwrap(),
+        (felt!(BALANCE.0 - charged_amount.0), felt!(0_u8))
+    );
+}
@@ -119,7 +119,8 @@ pub fn get_fee_by_gas_vector(
     gas_vector.cost(block_info.gas_prices.gas_price_vector(fee_type), tip)
 }
 
-/// Returns the current fee balance and a boolean indicating whether the balance covers the fee.
+/// Returns the current fee token balance and a boolean indicating whether the balance covers the
+/// fee (converted to the transaction's fee token).
 pub fn get_balance_and_if_covers_fee(
     state: &mut dyn StateReader,
     tx_context: &TransactionContext,
@@ -128,12 +129,13 @@ pub fn get_balance_and_if_covers_fee(
     let tx_info = &tx_context.tx_info;
     let (balance_low, balance_high) =
         state.get_fee_token_balance(tx_info.sender_address(), tx_context.fee_token_address())?;
+    let fee_in_fee_token = tx_context.fee_in_fee_token(fee)?;
     Ok((
         balance_low,
         balance_high,
         // TODO(Dori,1/10/2023): If/when fees can be more than 128 bit integers, this should be
         //   updated.
-        balance_high > Felt::ZERO || balance_low >= Felt::from(fee.0),
+        balance_high > Felt::ZERO || balance_low >= Felt::from(fee_in_fee_token.0),
     ))
 }
 
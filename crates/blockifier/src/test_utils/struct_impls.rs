@@ -26,7 +26,13 @@ use starknet_api::test_utils::{
 use crate::blockifier::config::{CairoNativeRunConfig, ContractClassManagerConfig};
 use crate::blockifier_versioned_constants::VersionedConstants;
 use crate::bouncer::{BouncerConfig, BouncerWeights};
-use crate::context::{BlockContext, ChainInfo, FeeTokenAddresses, TransactionContext};
+use crate::context::{
+    AdditionalFeeTokens,
+    BlockContext,
+    ChainInfo,
+    FeeTokenAddresses,
+    TransactionContext,
+};
 use crate::execution::call_info::{CallExecution, CallInfo, Retdata};
 use crate::execution::common_hints::ExecutionMode;
 #[cfg(feature = "cairo_native")]
@@ -144,27 +150,28 @@ impl ChainInfo {
                 eth_fee_token_address: contract_address!(TEST_ERC20_CONTRACT_ADDRESS),
                 strk_fee_token_address: contract_address!(TEST_ERC20_CONTRACT_ADDRESS2),
             },
+            additional_fee_tokens: AdditionalFeeTokens::default(),
         }
     }
 }
 
 impl BlockContext {
     pub fn create_for_testing() -> Self {
-        Self {
-            block_info: BlockInfo::create_for_testing(),
-            chain_info: ChainInfo::create_for_testing(),
-            versioned_constants: VersionedConstants::create_for_testing(),
-            bouncer_config: BouncerConfig::max(),
-        }
+        Self::new(
+            BlockInfo::create_for_testing(),
+            ChainInfo::create_for_testing(),
+            VersionedConstants::create_for_testing(),
+            BouncerConfig::max(),
+        )
     }
 
     pub fn create_for_account_testing() -> Self {
-        Self {
-            block_info: BlockInfo::create_for_testing(),
-            chain_info: ChainInfo::create_for_testing(),
-            versioned_constants: VersionedConstants::create_for_account_testing(),
-            bouncer_config: BouncerConfig::max(),
-        }
+        Self::new(
+            BlockInfo::create_for_testing(),
+            ChainInfo::create_for_testing(),
+            VersionedConstants::create_for_account_testing(),
+            BouncerConfig::max(),
+        )
     }
 
     pub fn create_for_bouncer_testing(max_n_events_in_block: usize) -> Self {
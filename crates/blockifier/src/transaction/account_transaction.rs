@@ -448,7 +448,7 @@ impl AccountTransaction {
         actual_fee: Fee,
     ) -> TransactionExecutionResult<CallInfo> {
         // The least significant 128 bits of the amount transferred.
-        let lsb_amount = Felt::from(actual_fee.0);
+        let lsb_amount = Felt::from(tx_context.fee_in_fee_token(actual_fee)?.0);
         // The most significant 128 bits of the amount transferred.
         let msb_amount = Felt::ZERO;
 
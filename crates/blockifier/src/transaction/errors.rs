@@ -1,5 +1,6 @@
 use cairo_vm::types::errors::program_errors::ProgramError;
 use num_bigint::BigUint;
+use num_rational::Ratio;
 use starknet_api::block::GasPrice;
 use starknet_api::core::{ClassHash, ContractAddress, EntryPointSelector, Nonce};
 use starknet_api::execution_resources::GasAmount;
@@ -41,6 +42,11 @@ pub enum TransactionFeeError {
     CairoResourcesNotContainedInFeeCosts,
     #[error(transparent)]
     ExecuteFeeTransferError(#[from] EntryPointExecutionError),
+    #[error(
+        "Converting the fee ({}) to the fee token at the rate {conversion_rate} overflowed.",
+        fee.0
+    )]
+    FeeConversionOverflow { fee: Fee, conversion_rate: Ratio<u128> },
     #[error("Actual fee ({}) exceeded max fee ({}).", actual_fee.0, max_fee.0)]
     FeeTransferError { max_fee: Fee, actual_fee: Fee },
     #[error("Actual fee ({}) exceeded paid fee on L1 ({}).", actual_fee.0, paid_fee.0)]
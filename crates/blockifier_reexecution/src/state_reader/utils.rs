@@ -6,7 +6,7 @@ use std::sync::LazyLock;
 use apollo_gateway::config::RpcStateReaderConfig;
 use apollo_rpc_execution::{ETH_FEE_CONTRACT_ADDRESS, STRK_FEE_CONTRACT_ADDRESS};
 use assert_matches::assert_matches;
-use blockifier::context::{ChainInfo, FeeTokenAddresses};
+use blockifier::context::{AdditionalFeeTokens, ChainInfo, FeeTokenAddresses};
 use blockifier::state::cached_state::{CachedState, CommitmentStateDiff, StateMaps};
 use blockifier::state::state_api::StateReader;
 use indexmap::IndexMap;
@@ -67,7 +67,11 @@ pub fn get_rpc_state_reader_config() -> RpcStateReaderConfig {
 
 /// Returns the chain info of mainnet.
 pub fn get_chain_info(chain_id: &ChainId) -> ChainInfo {
-    ChainInfo { chain_id: chain_id.clone(), fee_token_addresses: get_fee_token_addresses(chain_id) }
+    ChainInfo {
+        chain_id: chain_id.clone(),
+        fee_token_addresses: get_fee_token_addresses(chain_id),
+        additional_fee_tokens: AdditionalFeeTokens::default(),
+    }
 }
 
 // TODO(Aner): import the following functions instead, to reduce code duplication.
@@ -11,7 +11,7 @@ use blockifier::blockifier::transaction_executor::{
 };
 use blockifier::blockifier_versioned_constants::VersionedConstants;
 use blockifier::bouncer::BouncerConfig;
-use blockifier::context::{BlockContext, ChainInfo, FeeTokenAddresses};
+use blockifier::context::{AdditionalFeeTokens, BlockContext, ChainInfo, FeeTokenAddresses};
 use blockifier::state::contract_class_manager::ContractClassManager;
 use blockifier::state::state_reader_and_contract_manager::StateReaderAndContractManager;
 use blockifier::transaction::objects::TransactionExecutionInfo;
@@ -220,7 +220,9 @@ impl PyBlockExecutor {
             state_diff,
             compressed_state_diff,
             bouncer_weights,
+            capacity_utilization: _,
             casm_hash_computation_data,
+            concurrency_stats: _,
         } = self.tx_executor().finalize()?;
         let py_state_diff = PyStateDiff::from(state_diff);
         let py_compressed_state_diff = compressed_state_diff.map(PyStateDiff::from);
@@ -445,6 +447,7 @@ impl TryFrom<PyOsConfig> for ChainInfo {
                     py_os_config.fee_token_address.0,
                 )?,
             },
+            additional_fee_tokens: AdditionalFeeTokens::default(),
         })
     }
 }
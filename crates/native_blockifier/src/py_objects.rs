@@ -142,6 +142,8 @@ fn hash_map_into_bouncer_weights(
             .unwrap_or_else(|err| panic!("Failed to convert 'sierra_gas' into GasAmount: {err}.")),
     );
     let n_txs = data.remove(constants::N_TXS).expect("n_txs must be present");
+    // The proving cost is optional, to support configurations predating it.
+    let proving_cost = data.remove(constants::PROVING_COST).unwrap_or(usize::MAX);
     assert!(data.is_empty(), "Extra keys in bouncer weights: {data:?}");
 
     Ok(BouncerWeights {
@@ -151,6 +153,7 @@ fn hash_map_into_bouncer_weights(
         n_events,
         sierra_gas,
         n_txs,
+        proving_cost,
     })
 }
 
@@ -0,0 +1,48 @@
+// config compiler to support coverage_attribute feature when running coverage in nightly mode
+// within this crate
+#![cfg_attr(coverage_nightly, feature(coverage_attribute))]
+
+use std::env::args;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+use apollo_storage::open_storage;
+use apollo_storage::snapshot::{export_snapshot, import_snapshot};
+use papyrus_node::config::NodeConfig;
+
+const USAGE: &str =
+    "Usage: papyrus_storage_snapshot <export|import> <archive path> [node config arguments]";
+
+/// Exports a snapshot of the node's storage to an archive, or imports a snapshot archive into the
+/// node's storage, which must not exist. The storage is taken from the node's configuration, e.g.:
+/// cargo run --bin papyrus_storage_snapshot -- export snapshot.zst --config_file config.json
+#[cfg_attr(coverage_nightly, coverage_attribute)]
+fn main() {
+    let mut args = args();
+    let executable = args.next().expect("Missing the executable name");
+    let (Some(command), Some(archive_path)) = (args.next(), args.next()) else {
+        panic!("{USAGE}");
+    };
+    let config = NodeConfig::load_and_process(std::iter::once(executable).chain(args).collect())
+        .expect("Failed to load the node config");
+
+    match command.as_str() {
+        "export" => {
+            let (reader, _writer) =
+                open_storage(config.storage.clone()).expect("Failed to open storage");
+            let archive = File::create(archive_path).expect("Failed to create the archive file");
+            let mut archive = BufWriter::new(archive);
+            let manifest = export_snapshot(&reader, &config.storage.db_config, &mut archive)
+                .expect("Failed to export the snapshot");
+            archive.flush().expect("Failed to write the archive file");
+            println!("Exported the snapshot: {manifest:?}");
+        }
+        "import" => {
+            let archive = File::open(archive_path).expect("Failed to open the archive file");
+            let manifest = import_snapshot(BufReader::new(archive), config.storage)
+                .expect("Failed to import the snapshot");
+            println!("Imported the snapshot: {manifest:?}");
+        }
+        _ => panic!("{USAGE}"),
+    }
+}
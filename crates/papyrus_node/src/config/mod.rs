@@ -12,6 +12,7 @@ use std::path::{Path, PathBuf};
 use std::time::Duration;
 use std::{env, fs, io};
 
+use apollo_central_sync::l1_to_l2_messages::L1ToL2MessagesIndexerConfig;
 use apollo_central_sync::sources::central::CentralSourceConfig;
 use apollo_central_sync::SyncConfig;
 use apollo_config::dumping::{
@@ -64,6 +65,9 @@ pub struct NodeConfig {
     /// If p2p sync is active, then network must be active too.
     // TODO(yair): Change NodeConfig to have an option of enum of SyncConfig or P2pSyncConfig.
     pub p2p_sync: Option<P2pSyncClientConfig>,
+    /// None if the messages sent from the base layer to L2 shouldn't be indexed. They are indexed
+    /// by whichever of sync and p2p_sync is active.
+    pub l1_to_l2_messages_indexer: Option<L1ToL2MessagesIndexerConfig>,
     pub consensus: Option<ConsensusConfig>,
     pub context: Option<ContextConfig>,
     // TODO(shahak): Make network non-optional once it's developed enough.
@@ -83,6 +87,7 @@ impl Default for NodeConfig {
             storage: StorageConfig::default(),
             sync: Some(SyncConfig { store_sierras_and_casms: true, ..Default::default() }),
             p2p_sync: None,
+            l1_to_l2_messages_indexer: None,
             consensus: None,
             context: None,
             network: None,
@@ -101,6 +106,7 @@ impl SerializeConfig for NodeConfig {
             prepend_sub_config_name(self.storage.dump(), "storage"),
             ser_optional_sub_config(&self.sync, "sync"),
             ser_optional_sub_config(&self.p2p_sync, "p2p_sync"),
+            ser_optional_sub_config(&self.l1_to_l2_messages_indexer, "l1_to_l2_messages_indexer"),
             ser_optional_sub_config(&self.consensus, "consensus"),
             ser_optional_sub_config(&self.context, "context"),
             ser_optional_sub_config(&self.network, "network"),
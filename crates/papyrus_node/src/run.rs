@@ -7,6 +7,7 @@ use std::process::exit;
 use std::sync::Arc;
 use std::time::Duration;
 
+use apollo_central_sync::l1_to_l2_messages::L1ToL2MessagesIndexer;
 use apollo_central_sync::sources::base_layer::EthereumBaseLayerSource;
 use apollo_central_sync::sources::central::{CentralError, CentralSource, CentralSourceConfig};
 use apollo_central_sync::sources::pending::PendingSource;
@@ -26,6 +27,7 @@ use apollo_starknet_client::reader::objects::pending_data::{
     PendingBlockOrDeprecated,
 };
 use apollo_starknet_client::reader::PendingData;
+use apollo_storage::migrations::migrate_storage;
 use apollo_storage::storage_metrics::update_storage_metrics;
 use apollo_storage::{open_storage, StorageReader, StorageWriter};
 use futures::StreamExt;
@@ -84,7 +86,13 @@ pub struct PapyrusTaskHandles {
 
 impl PapyrusResources {
     pub fn new(config: &NodeConfig) -> anyhow::Result<Self> {
+        migrate_storage(config.storage.clone())?;
         let (storage_reader, storage_writer) = open_storage(config.storage.clone())?;
+        if let Some(storage_pruner) = storage_writer.pruner() {
+            std::thread::Builder::new()
+                .name("storage_pruner".to_string())
+                .spawn(move || storage_pruner.run())?;
+        }
         let (maybe_network_manager, local_peer_id) = build_network_manager(config.network.clone())?;
         let shared_highest_block = Arc::new(RwLock::new(None));
         let pending_data = Arc::new(RwLock::new(PendingData {
@@ -144,6 +152,7 @@ async fn spawn_rpc_server(
         storage_reader,
         VERSION_FULL,
         None,
+        None,
     )
     .await?;
     Ok(tokio::spawn(async move {
@@ -185,6 +194,7 @@ async fn run_sync(
     pending_data: Arc<RwLock<PendingData>>,
     pending_classes: Arc<RwLock<PendingClasses>>,
     storage: (StorageReader, StorageWriter),
+    l1_to_l2_messages_indexer: Option<L1ToL2MessagesIndexer<EthereumBaseLayerSource>>,
 ) -> anyhow::Result<()> {
     let (sync_config, central_config, base_layer_config) = configs;
     let (storage_reader, storage_writer) = storage;
@@ -206,6 +216,7 @@ async fn run_sync(
         storage_reader.clone(),
         storage_writer,
         class_manager_client,
+        l1_to_l2_messages_indexer,
     );
     Ok(sync.run().await?)
 }
@@ -221,6 +232,16 @@ async fn spawn_sync_client(
     pending_classes: Arc<RwLock<PendingClasses>>,
     class_manager_client: SharedClassManagerClient,
 ) -> JoinHandle<anyhow::Result<()>> {
+    // The messages sent from the base layer to L2 are indexed by whichever sync client is active,
+    // since it owns the storage writer.
+    let l1_to_l2_messages_indexer = config.l1_to_l2_messages_indexer.map(|indexer_config| {
+        L1ToL2MessagesIndexer::new(
+            indexer_config,
+            storage_reader.clone(),
+            EthereumBaseLayerSource::new(config.base_layer.clone()),
+            config.storage.db_config.chain_id.clone(),
+        )
+    });
     match (config.sync, config.p2p_sync) {
         (Some(_), Some(_)) => {
             panic!("One of --sync.#is_none or --p2p_sync.#is_none must be turned on");
@@ -235,6 +256,7 @@ async fn spawn_sync_client(
                 pending_data,
                 pending_classes,
                 storage,
+                l1_to_l2_messages_indexer,
             ))
         }
         (None, Some(p2p_sync_client_config)) => {
@@ -260,6 +282,10 @@ async fn spawn_sync_client(
                 storage_writer,
                 p2p_sync_client_channels,
                 futures::stream::pending().boxed(),
+                l1_to_l2_messages_indexer.as_ref().map_or_else(
+                    || futures::stream::pending().boxed(),
+                    L1ToL2MessagesIndexer::stream,
+                ),
                 class_manager_client,
             );
             tokio::spawn(async move { Ok(p2p_sync.run().await.map(|_never| ())?) })
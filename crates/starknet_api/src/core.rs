@@ -495,6 +495,25 @@ impl From<EthAddress> for PrefixedBytesAsHex<20_usize> {
     }
 }
 
+/// The hash of an Ethereum transaction.
+#[derive(
+    Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
+)]
+#[serde(from = "PrefixedBytesAsHex<32_usize>", into = "PrefixedBytesAsHex<32_usize>")]
+pub struct L1TransactionHash(pub [u8; 32]);
+
+impl From<PrefixedBytesAsHex<32_usize>> for L1TransactionHash {
+    fn from(val: PrefixedBytesAsHex<32_usize>) -> Self {
+        L1TransactionHash(val.0)
+    }
+}
+
+impl From<L1TransactionHash> for PrefixedBytesAsHex<32_usize> {
+    fn from(hash: L1TransactionHash) -> Self {
+        BytesAsHex(hash.0)
+    }
+}
+
 /// A public key of a sequencer.
 #[derive(
     Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Deserialize, Serialize, PartialOrd, Ord,
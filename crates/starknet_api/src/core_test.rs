@@ -10,6 +10,7 @@ use crate::core::{
     ChainId,
     ContractAddress,
     EthAddress,
+    L1TransactionHash,
     Nonce,
     PatriciaKey,
     StarknetApiError,
@@ -83,6 +84,19 @@ fn eth_address_serde() {
     assert_eq!(restored, eth_address);
 }
 
+#[test]
+fn l1_transaction_hash_serde() {
+    let mut hash = [0; 32];
+    hash[0] = 0xab;
+    hash[31] = 0x01;
+    let l1_transaction_hash = L1TransactionHash(hash);
+    let serialized = serde_json::to_string(&l1_transaction_hash).unwrap();
+    assert_eq!(serialized, format!(r#""0xab{}01""#, "0".repeat(60)));
+
+    let restored = serde_json::from_str::<L1TransactionHash>(&serialized).unwrap();
+    assert_eq!(restored, l1_transaction_hash);
+}
+
 #[test]
 fn nonce_overflow() {
     // Increment on this value should overflow back to 0.
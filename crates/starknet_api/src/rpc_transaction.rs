@@ -138,6 +138,18 @@ impl InternalRpcTransactionWithoutTxHash {
     }
 }
 
+impl From<InternalRpcTransactionWithoutTxHash> for Transaction {
+    fn from(internal_rpc_transaction: InternalRpcTransactionWithoutTxHash) -> Self {
+        match internal_rpc_transaction {
+            InternalRpcTransactionWithoutTxHash::Declare(tx) => Transaction::Declare(tx.into()),
+            InternalRpcTransactionWithoutTxHash::DeployAccount(tx) => {
+                Transaction::DeployAccount(tx.tx.into())
+            }
+            InternalRpcTransactionWithoutTxHash::Invoke(tx) => Transaction::Invoke(tx.into()),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, Hash, SizeOf)]
 pub struct InternalRpcTransaction {
     pub tx: InternalRpcTransactionWithoutTxHash,